@@ -1,6 +1,7 @@
 use std::{
     env,
     path::{Path, PathBuf},
+    process::Command,
 };
 
 fn main() {
@@ -31,30 +32,77 @@ fn main() {
         output_lib_path.join("charsets.jar"),
     )
     .unwrap();
+    std::fs::copy(
+        input_lib_path.join("net.properties"),
+        output_lib_path.join("net.properties"),
+    )
+    .unwrap();
+    std::fs::create_dir_all(output_lib_path.join("security")).unwrap();
+    std::fs::copy(
+        input_lib_path.join("security").join("java.security"),
+        output_lib_path.join("security").join("java.security"),
+    )
+    .unwrap();
 }
 
 fn get_output_path() -> PathBuf {
     let manifest_dir_string = env::var("CARGO_MANIFEST_DIR").unwrap();
     let build_type = env::var("PROFILE").unwrap();
-    let path = Path::new(&manifest_dir_string)
+    Path::new(&manifest_dir_string)
         .join("target")
-        .join(build_type);
-    return PathBuf::from(path);
+        .join(build_type)
 }
 
-fn copy_lib_rsvm_zip(output_lib_path: &PathBuf) {
-    let input_lib_rsvm_zip = std::env::var("CARGO_CDYLIB_FILE_RSVM_ZIP").unwrap();
-    let input_lib_rsvm_zip_path = PathBuf::from(Path::new(&input_lib_rsvm_zip));
-    let output_filename = get_lib_rsvm_zip_output_filename();
-    std::fs::copy(
-        input_lib_rsvm_zip_path,
-        output_lib_path.join(output_filename),
-    )
-    .unwrap();
+/// The JDK libraries whose natives are built into rsvm. `System.loadLibrary`
+/// only has to find a file for them, which the rsvm_zip stub stands in for.
+const BUILTIN_LIBS: &[&str] = &["zip", "management", "net", "nio"];
+
+fn copy_lib_rsvm_zip(output_lib_path: &Path) {
+    let input_lib_rsvm_zip_path = build_lib_rsvm_zip();
+    for lib_name in BUILTIN_LIBS {
+        std::fs::copy(
+            &input_lib_rsvm_zip_path,
+            output_lib_path.join(get_lib_output_filename(lib_name)),
+        )
+        .unwrap();
+    }
 }
 
-fn get_lib_rsvm_zip_output_filename() -> String {
-    let mut lib_rsvm_zip = String::from("zip");
+fn get_lib_output_filename(lib_name: &str) -> String {
+    let mut lib_filename = String::from(lib_name);
+    if cfg!(target_os = "linux") {
+        lib_filename.insert_str(0, "lib");
+        lib_filename.push_str(".so");
+    } else if cfg!(target_os = "macos") {
+        lib_filename.insert_str(0, "lib");
+        lib_filename.push_str(".dylib");
+    } else if cfg!(windows) {
+        lib_filename.push_str(".dll");
+    }
+    lib_filename
+}
+
+/// Builds lib/rsvm_zip as a separate cargo invocation, artifact dependencies
+/// would need the unstable `-Z bindeps`.
+fn build_lib_rsvm_zip() -> PathBuf {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let target_dir = PathBuf::from(env::var("OUT_DIR").unwrap()).join("rsvm_zip");
+    let build_type = env::var("PROFILE").unwrap();
+    let mut cargo = Command::new(env::var("CARGO").unwrap());
+    cargo
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(manifest_dir.join("lib").join("rsvm_zip").join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .env_remove("CARGO_ENCODED_RUSTFLAGS");
+    if build_type == "release" {
+        cargo.arg("--release");
+    }
+    let status = cargo.status().unwrap();
+    assert!(status.success(), "failed to build rsvm_zip");
+
+    let mut lib_rsvm_zip = String::from("rsvm_zip");
     if cfg!(target_os = "linux") {
         lib_rsvm_zip.insert_str(0, "lib");
         lib_rsvm_zip.push_str(".so");
@@ -64,5 +112,5 @@ fn get_lib_rsvm_zip_output_filename() -> String {
     } else if cfg!(windows) {
         lib_rsvm_zip.push_str(".dll");
     }
-    return lib_rsvm_zip;
+    target_dir.join(build_type).join(lib_rsvm_zip)
 }
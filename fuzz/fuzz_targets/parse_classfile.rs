@@ -0,0 +1,10 @@
+//! Feeds arbitrary bytes to the class file parser of the VM, which has to
+//! return an error for malformed input instead of panicking.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = rsvm::classfile::parser::parse_classfile_bytes(data);
+});
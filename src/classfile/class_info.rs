@@ -13,13 +13,13 @@ use crate::{
     },
     thread::ThreadPtr,
     value::JValue,
-    vm::{VMError, VMPtr},
+    vm::{VMError, VMPtr, VM},
     JArray, JClassPtr, ObjectPtr,
 };
 
 macro_rules! make_java_lang_number_infos {
     ($(
-        {$info_name:ident, $cls_name:expr, $val_ty:ty}
+        {$info_name:ident, $cls_name:expr, $val_ty:ty, $desc:expr}
     ),*) => {
         $(
             #[derive(Default)]
@@ -27,19 +27,31 @@ macro_rules! make_java_lang_number_infos {
             pub(crate) struct $info_name {
                 cls: JClassPtr,
                 value: FieldPtr,
+                value_of: MethodPtr,
             }
 
             impl $info_name {
                 pub(crate) fn new(cls: JClassPtr, thread: ThreadPtr) -> Result<Self, VMError> {
                     let vm = thread.vm();
-                    let (value, _) = cls.get_field_with_name(vm.shared_objs().symbols().value);
-                    assert!(value.is_not_null());
+                    let (value, _) = cls.get_field_with_name(vm.shared_objs().symbols().value)?;
+                    let value_of = cls.resolve_local_method_unchecked(
+                        vm.get_symbol("valueOf"),
+                        vm.get_symbol(concat!("(", $desc, ")L", $cls_name, ";")),
+                    );
+                    assert!(value_of.is_not_null());
                     Ok(Self {
                         cls,
                         value,
+                        value_of,
                     })
                 }
 
+                /// Boxes `value` with `valueOf`, which returns the cached box of
+                /// a value like a small `Integer`, as Java code would get.
+                pub(crate) fn box_value(&self, value: JValue, vm: &VM) -> Result<ObjectPtr, VMError> {
+                    return vm.call_static(self.cls, self.value_of, &[value]);
+                }
+
                 #[allow(dead_code)]
                 #[inline]
                 pub(crate) fn get_value(&self, obj: ObjectPtr) -> $val_ty {
@@ -57,14 +69,14 @@ macro_rules! make_java_lang_number_infos {
 }
 
 make_java_lang_number_infos!(
-    {JavaLangCharInfo, "java/lang/Char", JChar},
-    {JavaLangByteInfo, "java/lang/Byte", JByte},
-    {JavaLangBooleanInfo, "java/lang/Boolean", JBoolean},
-    {JavaLangIntegerInfo, "java/lang/Integer", JInt},
-    {JavaLangShortInfo, "java/lang/Short", JShort},
-    {JavaLangLongInfo, "java/lang/Long", JLong},
-    {JavaLangFloatInfo, "java/lang/Float", JFloat},
-    {JavaLangDoubleInfo, "java/lang/Double", JDouble}
+    {JavaLangCharInfo, "java/lang/Character", JChar, "C"},
+    {JavaLangByteInfo, "java/lang/Byte", JByte, "B"},
+    {JavaLangBooleanInfo, "java/lang/Boolean", JBoolean, "Z"},
+    {JavaLangIntegerInfo, "java/lang/Integer", JInt, "I"},
+    {JavaLangShortInfo, "java/lang/Short", JShort, "S"},
+    {JavaLangLongInfo, "java/lang/Long", JLong, "J"},
+    {JavaLangFloatInfo, "java/lang/Float", JFloat, "F"},
+    {JavaLangDoubleInfo, "java/lang/Double", JDouble, "D"}
 );
 
 #[derive(Default)]
@@ -76,8 +88,7 @@ pub(crate) struct JavaLangStringInfo {
 impl JavaLangStringInfo {
     pub(crate) fn new(jstring_cls: JClassPtr, thread: ThreadPtr) -> Result<Self, VMError> {
         let value_field_name = thread.vm().symbol_table.get_or_insert("value");
-        let (value_field, _) = jstring_cls.get_field_with_name(value_field_name);
-        assert!(value_field.is_not_null());
+        let (value_field, _) = jstring_cls.get_field_with_name(value_field_name)?;
         return Ok(Self {
             jstring_cls,
             value_field,
@@ -162,7 +173,7 @@ impl JavaLangStringInfo {
     }
 
     pub fn get_chars(&self, str: JStringPtr) -> JCharArrayPtr {
-        return JCharArrayPtr::from_isize(self.value_field.fast_get_value(str.cast()) as isize);
+        return self.value_field.get_typed_value(str.cast());
     }
 }
 
@@ -173,6 +184,8 @@ pub(crate) struct JavaLangThreadInfo {
     ctor: MethodPtr,
     daemon: FieldPtr,
     priority: FieldPtr,
+    thread_name: FieldPtr,
+    thread_status: FieldPtr,
     metadata_offset: u16,
 }
 
@@ -182,8 +195,10 @@ impl JavaLangThreadInfo {
         let ctor_name = vm.shared_objs().symbols().ctor_init;
         let ctor_descriptor = vm.get_symbol("(Ljava/lang/ThreadGroup;Ljava/lang/Runnable;)V");
         let ctor = cls.resolve_local_method_unchecked(ctor_name, ctor_descriptor);
-        let (daemon, _) = cls.get_field_with_name(vm.get_symbol("daemon"));
-        let (priority, _) = cls.get_field_with_name(vm.get_symbol("priority"));
+        let (daemon, _) = cls.get_field_with_name(vm.get_symbol("daemon"))?;
+        let (priority, _) = cls.get_field_with_name(vm.get_symbol("priority"))?;
+        let (thread_name, _) = cls.get_field_with_name(vm.get_symbol("name"))?;
+        let (thread_status, _) = cls.get_field_with_name(vm.get_symbol("threadStatus"))?;
         debug_assert!(ctor.is_not_null());
         Ok(Self {
             cls,
@@ -191,6 +206,8 @@ impl JavaLangThreadInfo {
             ctor,
             daemon,
             priority,
+            thread_name,
+            thread_status,
             metadata_offset: cls.class_data().metadata_offset(),
         })
     }
@@ -246,6 +263,24 @@ impl JavaLangThreadInfo {
         self.name
     }
 
+    pub(crate) fn is_daemon(&self, jthread: ObjectPtr) -> bool {
+        return self.daemon.get_typed_value::<JBoolean>(jthread) != 0;
+    }
+
+    /// The name of `jthread`, empty while its constructor has not set one.
+    pub(crate) fn thread_name(&self, jthread: ObjectPtr) -> String {
+        let name: JCharArrayPtr = self.thread_name.get_typed_value(jthread);
+        if name.is_null() {
+            return String::new();
+        }
+        let name: Vec<u16> = name.to_slice().iter().map(|c| *c as u16).collect();
+        return String::from_utf16_lossy(&name);
+    }
+
+    pub(crate) fn set_thread_status(&self, jthread: ObjectPtr, thread_status: JInt) {
+        self.thread_status.set_typed_value(jthread, thread_status);
+    }
+
     pub(crate) const fn metadata_size() -> u16 {
         return size_of::<JInt>() as u16;
     }
@@ -269,8 +304,7 @@ pub(crate) struct JavaLangThreadGroupInfo {
 
 impl JavaLangThreadGroupInfo {
     pub(crate) fn new(cls: JClassPtr, thread: ThreadPtr) -> Result<Self, VMError> {
-        cls.initialize(thread)
-            .map_err(|e| VMError::ClassInitError(e))?;
+        cls.initialize(thread)?;
         let vm = thread.vm();
         let ctor_name = vm.shared_objs().symbols().ctor_init;
         let ctor_descriptor = vm.shared_objs().symbols().noargs_retv_descriptor;
@@ -299,17 +333,15 @@ impl JavaLangReflectFieldInfo {
         let vm = thread.vm();
         let field_arr_cls = vm
             .bootstrap_class_loader
-            .load_class("[Ljava/lang/reflect/Field;")
-            .map_err(|e| VMError::ClassLoaderErr(e))?;
+            .load_class("[Ljava/lang/reflect/Field;")?;
 
         let method_name = vm.shared_objs().symbols().ctor_init;
         let method_descriptor = vm.get_symbol(
             "(Ljava/lang/Class;Ljava/lang/String;Ljava/lang/Class;IILjava/lang/String;[B)V",
         );
         let ctor = cls.resolve_local_method_unchecked(method_name, method_descriptor);
-        let (slot_field, _) = cls.get_field_with_name(vm.shared_objs().symbols().slot);
+        let (slot_field, _) = cls.get_field_with_name(vm.shared_objs().symbols().slot)?;
         assert!(ctor.is_not_null());
-        assert!(slot_field.is_not_null());
         return Ok(Self {
             cls,
             field_arr_cls,
@@ -351,23 +383,18 @@ impl JavaLangReflectConstructorInfo {
         let vm = thread.vm();
         let ctor_arr_cls = vm
             .bootstrap_class_loader
-            .load_class("[Ljava/lang/reflect/Constructor;")
-            .map_err(|e| VMError::ClassLoaderErr(e))?;
+            .load_class("[Ljava/lang/reflect/Constructor;")?;
         let ctor = vm.shared_objs().symbols().ctor_init;
         let ctor_descriptor = vm.get_symbol(
             "(Ljava/lang/Class;[Ljava/lang/Class;[Ljava/lang/Class;IILjava/lang/String;[B[B)V",
         );
         let ctor = cls.resolve_local_method_unchecked(ctor, ctor_descriptor);
-        let (clazz, _) = cls.get_field_with_name(vm.get_symbol("clazz"));
-        let (slot, _) = cls.get_field_with_name(vm.shared_objs().symbols().slot);
-        let (param_types, _) = cls.get_field_with_name(vm.get_symbol("parameterTypes"));
-        let (modifiers, _) = cls.get_field_with_name(vm.get_symbol("modifiers"));
+        let (clazz, _) = cls.get_field_with_name(vm.get_symbol("clazz"))?;
+        let (slot, _) = cls.get_field_with_name(vm.shared_objs().symbols().slot)?;
+        let (param_types, _) = cls.get_field_with_name(vm.get_symbol("parameterTypes"))?;
+        let (modifiers, _) = cls.get_field_with_name(vm.get_symbol("modifiers"))?;
 
         assert!(ctor.is_not_null());
-        assert!(clazz.is_not_null());
-        assert!(slot.is_not_null());
-        assert!(param_types.is_not_null());
-        assert!(modifiers.is_not_null());
         return Ok(Self {
             cls,
             ctor_arr_cls,
@@ -430,6 +457,86 @@ impl JavaLangReflectConstructorInfo {
     }
 }
 
+#[derive(Default)]
+pub(crate) struct JavaLangReflectMethodInfo {
+    cls: JClassPtr,
+    method_arr_cls: JClassPtr,
+    clazz: FieldPtr,
+    slot: FieldPtr,
+    ctor: MethodPtr,
+}
+
+impl JavaLangReflectMethodInfo {
+    pub(crate) fn new(cls: JClassPtr, thread: ThreadPtr) -> Result<Self, VMError> {
+        let vm = thread.vm();
+        let method_arr_cls = vm
+            .bootstrap_class_loader
+            .load_class("[Ljava/lang/reflect/Method;")?;
+        let ctor = vm.shared_objs().symbols().ctor_init;
+        let ctor_descriptor = vm.get_symbol(
+            "(Ljava/lang/Class;Ljava/lang/String;[Ljava/lang/Class;Ljava/lang/Class;[Ljava/lang/Class;IILjava/lang/String;[B[B[B)V",
+        );
+        let ctor = cls.resolve_local_method_unchecked(ctor, ctor_descriptor);
+        let (clazz, _) = cls.get_field_with_name(vm.get_symbol("clazz"))?;
+        let (slot, _) = cls.get_field_with_name(vm.shared_objs().symbols().slot)?;
+        assert!(ctor.is_not_null());
+        return Ok(Self {
+            cls,
+            method_arr_cls,
+            clazz,
+            slot,
+            ctor,
+        });
+    }
+
+    pub fn get_decl_cls(&self, method: ObjectPtr) -> JClassPtr {
+        self.clazz.get_typed_value(method)
+    }
+
+    pub fn get_slot(&self, method: ObjectPtr) -> JInt {
+        self.slot.get_typed_value(method)
+    }
+
+    pub(crate) fn new_method(
+        &self,
+        decl_cls: JClassPtr,
+        name: JStringPtr,
+        param_types_arr: JArrayPtr,
+        ret_type: JClassPtr,
+        checked_ex_arr: JArrayPtr,
+        modifiers: JInt,
+        slot: JInt,
+        signature: JStringPtr,
+        thread: ThreadPtr,
+    ) -> Handle<Object> {
+        let method_handle = Handle::new(Object::new(self.cls, thread));
+        let method = method_handle.as_ptr();
+        thread.vm().call_obj_void(
+            method,
+            self.ctor,
+            &[
+                JValue::with_obj_val(decl_cls.cast()),
+                JValue::with_obj_val(name.cast()),
+                JValue::with_obj_val(param_types_arr.cast()),
+                JValue::with_obj_val(ret_type.cast()),
+                JValue::with_obj_val(checked_ex_arr.cast()),
+                JValue::with_int_val(modifiers),
+                JValue::with_int_val(slot),
+                JValue::with_obj_val(signature.cast()),
+                // annotations, parameter annotations and the annotation default
+                JValue::with_obj_null(),
+                JValue::with_obj_null(),
+                JValue::with_obj_null(),
+            ],
+        );
+        return method_handle;
+    }
+
+    pub fn new_method_arr(&self, length: JInt, thread: ThreadPtr) -> Handle<JArray> {
+        return Handle::new(JArray::new(length, self.method_arr_cls, thread));
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct JavaUtilPropertiesInfo {
     put_method: MethodPtr,
@@ -451,11 +558,12 @@ impl JavaUtilPropertiesInfo {
     }
 
     pub fn put(&self, objref: ObjectPtr, key: ObjectPtr, value: ObjectPtr, vm: VMPtr) {
-        vm.call_obj(
+        vm.call_virtual::<ObjectPtr>(
             objref,
             self.put_method,
             &[JValue::with_obj_val(key), JValue::with_obj_val(value)],
-        );
+        )
+        .unwrap();
     }
 }
 
@@ -501,7 +609,7 @@ impl JavaIOFileInfo {
     pub(crate) fn new(cls: JClassPtr, thread: ThreadPtr) -> Result<Self, VMError> {
         let vm = thread.vm();
         let symbols = vm.shared_objs().symbols();
-        let (path, _) = cls.get_field_with_name(symbols.path);
+        let (path, _) = cls.get_field_with_name(symbols.path)?;
         return Ok(Self { cls, path });
     }
 
@@ -524,13 +632,9 @@ impl JavaIOFileDescriptorInfo {
         let vm = thread.vm();
         let symbols = vm.shared_objs().symbols();
         #[cfg(target_family = "unix")]
-        let (fd, _) = cls.get_field_with_name(symbols.fd);
-        #[cfg(target_os = "windows")]
-        let (handle, _) = cls.get_field_with_name(symbols.handle);
-        #[cfg(target_family = "unix")]
-        assert!(fd.is_not_null());
+        let (fd, _) = cls.get_field_with_name(symbols.fd)?;
         #[cfg(target_os = "windows")]
-        assert!(handle.is_not_null());
+        let (handle, _) = cls.get_field_with_name(symbols.handle)?;
         Ok(Self {
             cls,
             #[cfg(target_family = "unix")]
@@ -551,6 +655,18 @@ impl JavaIOFileDescriptorInfo {
         debug_assert!(obj_ref.jclass() == self.cls);
         return self.handle.get_typed_value(obj_ref);
     }
+
+    #[cfg(target_family = "unix")]
+    pub(crate) fn set_fd(&self, obj_ref: ObjectPtr, fd: JInt) {
+        debug_assert!(obj_ref.jclass() == self.cls);
+        self.fd.set_typed_value(obj_ref, fd);
+    }
+
+    #[cfg(target_os = "windows")]
+    pub(crate) fn set_handle(&self, obj_ref: ObjectPtr, handle: JLong) {
+        debug_assert!(obj_ref.jclass() == self.cls);
+        self.handle.set_typed_value(obj_ref, handle);
+    }
 }
 
 #[derive(Default)]
@@ -562,8 +678,7 @@ impl JavaIOFileOutputStreamInfo {
     pub(crate) fn new(cls: JClassPtr, thread: ThreadPtr) -> Result<Self, VMError> {
         let vm = thread.vm();
         let symbols = vm.shared_objs().symbols();
-        let (fd, _) = cls.get_field_with_name(symbols.fd);
-        assert!(fd.is_not_null());
+        let (fd, _) = cls.get_field_with_name(symbols.fd)?;
         Ok(Self { fd })
     }
 
@@ -582,10 +697,8 @@ impl JavaLangClassLoaderNativeLibraryInfo {
     pub(crate) fn new(cls: JClassPtr, thread: ThreadPtr) -> Result<Self, VMError> {
         let vm = thread.vm();
         let symbols = vm.shared_objs().symbols();
-        let (handle, _) = cls.get_field_with_name(symbols.handle);
-        let (from_class, _) = cls.get_field_with_name(symbols.from_class);
-        assert!(handle.is_not_null());
-        assert!(from_class.is_not_null());
+        let (handle, _) = cls.get_field_with_name(symbols.handle)?;
+        let (from_class, _) = cls.get_field_with_name(symbols.from_class)?;
         return Ok(Self { handle, from_class });
     }
 
@@ -1,21 +1,86 @@
-use parking_lot::ReentrantMutex;
+use parking_lot::{Mutex, ReentrantMutex, RwLock};
 
 use super::parser::ClassParser;
 use super::reader::{ClassReader, OwnedBytesClassReader};
+use super::redefine;
 use super::ClassLoadErr;
 use crate::classfile::descriptor::{Descriptor, DescriptorParser};
+use crate::compat;
 use crate::object::hash_table::{GetEntryWithKey, HashTable, HashTablePtr};
 use crate::object::prelude::*;
 use crate::object::string::Utf8String;
+use crate::stats::ClassLoadRecord;
 use crate::thread::{Thread, ThreadPtr};
 use crate::utils;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs::File;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 
+/// The dimensions an array class can have at most.
+const MAX_ARRAY_DIMENSIONS: usize = 255;
+
+/// Converts a binary name such as `java.lang.String` or `[Ljava.lang.String;`
+/// to the internal name the loader uses, `java/lang/String` and
+/// `[Ljava/lang/String;`. Internal names pass through unchanged.
+pub(crate) fn internal_class_name(binary_name: &str) -> Result<String, ClassLoadErr> {
+    let name = binary_name.replace('.', "/");
+    check_internal_class_name(&name)?;
+    return Ok(name);
+}
+
+/// Returns the internal name of the array class whose component class is
+/// named `component_name`.
+pub(crate) fn array_class_name(component_name: &str) -> String {
+    return if component_name.starts_with('[') {
+        format!("[{}", component_name)
+    } else {
+        format!("[L{};", component_name)
+    };
+}
+
+/// Checks that `name` is a slash separated class name, or the name of an
+/// array class of a primitive type or of such a class.
+fn check_internal_class_name(name: &str) -> Result<(), ClassLoadErr> {
+    let dimensions = name.bytes().take_while(|&b| b == b'[').count();
+    let component = &name[dimensions..];
+    let valid = if dimensions == 0 {
+        is_plain_class_name(name)
+    } else if dimensions > MAX_ARRAY_DIMENSIONS {
+        false
+    } else {
+        match component.as_bytes() {
+            [b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z'] => true,
+            [b'L', .., b';'] => is_plain_class_name(&component[1..component.len() - 1]),
+            _ => false,
+        }
+    };
+    if !valid {
+        return Err(ClassLoadErr::InvalidClassName(name.to_string()));
+    }
+    return Ok(());
+}
+
+fn is_plain_class_name(name: &str) -> bool {
+    return name
+        .split('/')
+        .all(|part| !part.is_empty() && !part.contains(['.', ';', '[']));
+}
+
+/// Loads classes from the class path. Loading is parallel-capable: a thread
+/// holds the lock of a class name while reading and defining that class, so a
+/// concurrent load of the same class waits for it and then finds the result,
+/// while loads of different classes proceed side by side.
 #[derive(Default)]
 pub struct BootstrapClassLoader {
     cp_entries: ReentrantMutex<RefCell<Vec<Box<dyn ClassPathEntry>>>>,
-    loaded_classes: ReentrantMutex<RefCell<HashTablePtr>>,
+    loaded_classes: RwLock<HashTablePtr>,
+    /// The locks of the classes being loaded, dropped once no thread waits
+    /// for them.
+    loading_locks: Mutex<HashMap<String, Arc<ReentrantMutex<()>>>>,
+    next_anonymous_id: AtomicU64,
 }
 
 impl BootstrapClassLoader {
@@ -40,7 +105,9 @@ impl BootstrapClassLoader {
 
         return Self {
             cp_entries: ReentrantMutex::new(RefCell::new(cp_entries)),
-            loaded_classes: ReentrantMutex::new(RefCell::new(HashTable::new(thread))),
+            loaded_classes: RwLock::new(HashTable::new(thread)),
+            loading_locks: Mutex::new(HashMap::new()),
+            next_anonymous_id: AtomicU64::new(0),
         };
     }
 
@@ -58,9 +125,8 @@ impl BootstrapClassLoader {
             cls.as_usize(),
             cls.jclass().as_usize()
         );
-        self.do_with_mut_loaded_classes(|loaded_cls| {
-            *loaded_cls = loaded_cls.insert(cls, thread);
-        });
+        let mut loaded_classes = self.loaded_classes.write();
+        *loaded_classes = loaded_classes.insert(cls, thread);
     }
 
     pub fn find_class(&self, class_name: &str) -> Option<JClassPtr> {
@@ -75,12 +141,126 @@ impl BootstrapClassLoader {
         });
     }
 
+    /// A snapshot of every class in the loaded class table, including array
+    /// and primitive classes, in load order with
+    /// [`VMConfig::deterministic_tables`](crate::vm::VMConfig::deterministic_tables)
+    /// and in no particular order otherwise.
+    pub fn loaded_classes(&self) -> Vec<JClassPtr> {
+        return self.do_with_loaded_classes(|loaded_classes| {
+            return loaded_classes.iter::<JClass>().collect();
+        });
+    }
+
+    /// The URL of the resource `name`, a `/`-separated path like
+    /// `META-INF/services/java.sql.Driver`, in the first class path entry
+    /// that has it.
+    pub fn find_resource(&self, name: &str) -> Option<String> {
+        return self.find_resources(name).into_iter().next();
+    }
+
+    /// The URLs of the resource `name` in every class path entry that has it,
+    /// in class path order.
+    pub fn find_resources(&self, name: &str) -> Vec<String> {
+        if !Self::is_resource_name(name) {
+            return Vec::new();
+        }
+        let cp_entries = self.cp_entries.lock();
+        return unsafe { &mut *(*cp_entries).as_ptr() }
+            .iter_mut()
+            .filter_map(|entry| entry.resource_url(name))
+            .collect();
+    }
+
+    /// The bytes of the resource `name` in the first class path entry that
+    /// has it.
+    pub fn read_resource(&self, name: &str) -> Option<Vec<u8>> {
+        if !Self::is_resource_name(name) {
+            return None;
+        }
+        let cp_entries = self.cp_entries.lock();
+        return unsafe { &mut *(*cp_entries).as_ptr() }
+            .iter_mut()
+            .find_map(|entry| entry.resource(name));
+    }
+
+    /// The bytes of the resource `name` in the class path entry whose URL for
+    /// it is `url`, see [`find_resources`](Self::find_resources).
+    pub fn read_resource_at(&self, name: &str, url: &str) -> Option<Vec<u8>> {
+        if !Self::is_resource_name(name) {
+            return None;
+        }
+        let cp_entries = self.cp_entries.lock();
+        return unsafe { &mut *(*cp_entries).as_ptr() }
+            .iter_mut()
+            .find_map(|entry| {
+                if entry.resource_url(name)? != url {
+                    return None;
+                }
+                return entry.resource(name);
+            });
+    }
+
+    /// The names of the files in the directory `dir` of every class path
+    /// entry, e.g. `META-INF/services`, without subdirectories and in class
+    /// path order. A name found in several entries is listed once.
+    pub fn list_resources(&self, dir: &str) -> Vec<String> {
+        let dir = dir.trim_end_matches('/');
+        if !Self::is_resource_name(dir) {
+            return Vec::new();
+        }
+        let cp_entries = self.cp_entries.lock();
+        let mut names: Vec<String> = Vec::new();
+        for entry in unsafe { &mut *(*cp_entries).as_ptr() }.iter_mut() {
+            for name in entry.list(dir) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        return names;
+    }
+
+    /// The services with a provider-configuration file in
+    /// `META-INF/services` of any class path entry, by binary name.
+    pub fn services(&self) -> Vec<String> {
+        return self.list_resources(SERVICES_DIR);
+    }
+
+    /// The binary names of the providers of `service` listed in
+    /// `META-INF/services/<service>` of every class path entry, in the order
+    /// `java.util.ServiceLoader` finds them.
+    pub fn service_providers(&self, service: &str) -> Vec<String> {
+        let name = format!("{}/{}", SERVICES_DIR, service);
+        if !Self::is_resource_name(&name) {
+            return Vec::new();
+        }
+        let cp_entries = self.cp_entries.lock();
+        let mut providers: Vec<String> = Vec::new();
+        for entry in unsafe { &mut *(*cp_entries).as_ptr() }.iter_mut() {
+            if let Some(config) = entry.resource(&name) {
+                for provider in parse_provider_config(&config) {
+                    if !providers.contains(&provider) {
+                        providers.push(provider);
+                    }
+                }
+            }
+        }
+        return providers;
+    }
+
+    /// Resource names are relative and stay inside their class path entry.
+    fn is_resource_name(name: &str) -> bool {
+        return !name.is_empty()
+            && !name.starts_with('/')
+            && name.split('/').all(|part| part != "..");
+    }
+
     pub fn load_binary_name_class(
         // self: &Arc<Self>,
         &self,
         binary_class_name: &str,
     ) -> Result<JClassPtr, ClassLoadErr> {
-        let internal_class_name = binary_class_name.replace(".", "/");
+        let internal_class_name = internal_class_name(binary_class_name)?;
         let thread = Thread::current();
         return self.load_class_depth(thread, internal_class_name.as_str(), 0);
     }
@@ -119,11 +299,101 @@ impl BootstrapClassLoader {
         if let Some(find_cls) = self.find_class(class_name) {
             return Ok(find_cls);
         }
-        let loaded_class = self.do_load_class(thread, class_name)?;
+        let loading_lock = self.loading_lock(class_name);
+        let loaded_class = {
+            let _loading = loading_lock.lock();
+            // another thread may have loaded the class while this one waited
+            match self.find_class(class_name) {
+                Some(find_cls) => Ok(find_cls),
+                None => self.do_load_class(thread, class_name),
+            }
+        };
+        self.release_loading_lock(class_name, loading_lock);
         // self.add_loaded_classes(&[loaded_class]);
         let _depth = depth + 1;
         // self.link_class(loaded_class, thread, depth)?;
-        return Ok(loaded_class);
+        return loaded_class;
+    }
+
+    /// Returns the class of `dimensions` dimensional arrays of `component`,
+    /// creating the array classes that do not exist yet. Every class caches
+    /// the class of its arrays, so only the first request for an array class
+    /// looks it up by name.
+    pub(crate) fn get_or_create_array_class(
+        &self,
+        component: JClassPtr,
+        dimensions: u8,
+        thread: ThreadPtr,
+    ) -> Result<JClassPtr, ClassLoadErr> {
+        let mut array_class = component;
+        for _ in 0..dimensions {
+            array_class = self.array_class_of(array_class, thread)?;
+        }
+        return Ok(array_class);
+    }
+
+    fn array_class_of(
+        &self,
+        component: JClassPtr,
+        thread: ThreadPtr,
+    ) -> Result<JClassPtr, ClassLoadErr> {
+        let slot = unsafe {
+            compat::atomic_ptr_from_ptr(
+                component.class_data().array_class_slot() as *mut *mut JClass
+            )
+        };
+        let cached = slot.load(Ordering::Acquire);
+        if !cached.is_null() {
+            return Ok(JClassPtr::from_raw(cached));
+        }
+        let array_class = if component.class_data().is_primitive() {
+            let array_class = thread
+                .vm()
+                .preloaded_classes()
+                .get_primitive_array_class(component);
+            if array_class.is_null() {
+                // there are no arrays of void
+                return Err(ClassLoadErr::InvalidClassName(format!(
+                    "[{}",
+                    component.name().as_str()
+                )));
+            }
+            array_class
+        } else {
+            let class_name = array_class_name(component.name().as_str());
+            if class_name.bytes().take_while(|&b| b == b'[').count() > MAX_ARRAY_DIMENSIONS {
+                return Err(ClassLoadErr::InvalidClassName(class_name));
+            }
+            let class_name = thread.vm().symbol_table.get_or_insert(&class_name);
+            let mut loaded_classes = self.loaded_classes.write();
+            // another thread may have created the same array class meanwhile
+            match loaded_classes.get_value_by_str(class_name) {
+                Some(array_class) => array_class,
+                None => {
+                    let array_class = JClass::new_array_class(class_name, component, thread);
+                    *loaded_classes = loaded_classes.insert(array_class, thread);
+                    array_class
+                }
+            }
+        };
+        slot.store(array_class.as_mut_raw_ptr(), Ordering::Release);
+        return Ok(array_class);
+    }
+
+    fn loading_lock(&self, class_name: &str) -> Arc<ReentrantMutex<()>> {
+        let mut loading_locks = self.loading_locks.lock();
+        return loading_locks
+            .entry(class_name.to_string())
+            .or_insert_with(|| Arc::new(ReentrantMutex::new(())))
+            .clone();
+    }
+
+    fn release_loading_lock(&self, class_name: &str, loading_lock: Arc<ReentrantMutex<()>>) {
+        let mut loading_locks = self.loading_locks.lock();
+        // one reference is the map's and one ours, any other is a waiting thread
+        if Arc::strong_count(&loading_lock) == 2 {
+            loading_locks.remove(class_name);
+        }
     }
 
     fn do_load_class(
@@ -132,50 +402,166 @@ impl BootstrapClassLoader {
         class_name: &str,
     ) -> Result<JClassPtr, ClassLoadErr> {
         assert!(class_name.len() > 0);
-        if class_name.starts_with('[') && class_name.len() > 1 {
-            let mut component_class_name = &class_name[1..];
+        if let Some(mut component_class_name) = class_name.strip_prefix('[') {
+            check_internal_class_name(class_name)?;
             if component_class_name.starts_with('L') {
                 component_class_name = &component_class_name[1..component_class_name.len() - 1];
             }
             let component_class = self.load_class(component_class_name)?;
-            let class_name = thread.vm().symbol_table.get_or_insert(class_name);
-            let result = JClass::new_array_class(class_name, component_class, Thread::current());
-            self.do_with_mut_loaded_classes(|loaded_classes| {
-                *loaded_classes = loaded_classes.insert(result, thread);
-            });
-            return Ok(result);
+            return self.get_or_create_array_class(component_class, 1, thread);
         }
         if class_name == "MethodCall$Sub" {
             println!("123");
         }
-        let cp_entries = self.cp_entries.lock();
-        for entry in unsafe { &mut *(*cp_entries).as_ptr() }.iter_mut() {
-            if let Some(reader) = entry.reader(class_name) {
-                return self.do_with_mut_loaded_classes(
-                    |loaded_classes| -> Result<JClassPtr, ClassLoadErr> {
-                        let mut parser =
-                            ClassParser::new(thread.class_loader(), reader, thread.vm());
-                        let result = parser.parse_class()?;
-                        *loaded_classes = loaded_classes.insert(result, thread);
-                        return Ok(result);
-                    },
-                );
+        let load_start = Instant::now();
+        let (reader, source) = {
+            let cp_entries = self.cp_entries.lock();
+            let found = unsafe { &mut *(*cp_entries).as_ptr() }
+                .iter_mut()
+                .find_map(|entry| {
+                    let reader = entry.reader(class_name)?;
+                    return Some((reader, entry.source().to_string()));
+                });
+            match found {
+                Some(found) => found,
+                None => return Err(ClassLoadErr::ClassNotFound(class_name.to_string())),
+            }
+        };
+        let mut parser = ClassParser::new(thread.class_loader(), reader, thread.vm());
+        let result = parser.parse_class()?;
+        self.add_loaded_class(result, thread)?;
+        Self::record_class_load(thread, result, Some(&source), load_start);
+        return Ok(result);
+    }
+
+    /// Defines a class from in-memory class file bytes.
+    pub(crate) fn define_class(
+        &self,
+        bytes: Vec<u8>,
+        thread: ThreadPtr,
+    ) -> Result<JClassPtr, ClassLoadErr> {
+        return self.define_class_with_loader(None, bytes, thread.class_loader(), thread);
+    }
+
+    /// Defines the class in `bytes` with `jclass_loader`, null for the
+    /// bootstrap loader, as its defining loader. `name`, like `java/lang/Foo`,
+    /// is the class `bytes` has to define when given.
+    pub(crate) fn define_class_with_loader(
+        &self,
+        name: Option<&str>,
+        bytes: Vec<u8>,
+        jclass_loader: ObjectPtr,
+        thread: ThreadPtr,
+    ) -> Result<JClassPtr, ClassLoadErr> {
+        let load_start = Instant::now();
+        let reader = Box::new(OwnedBytesClassReader::new(bytes));
+        let result = ClassParser::new(jclass_loader, reader, thread.vm()).parse_class()?;
+        if let Some(name) = name {
+            if result.name().as_str() != name {
+                return Err(ClassLoadErr::WrongName {
+                    expected: name.to_string(),
+                    found: result.name().as_str().to_string(),
+                });
             }
         }
-        todo!(
-            "throw ClassNotFoundException, cannot find class: {}",
-            class_name
-        );
+        self.add_loaded_class(result, thread)?;
+        Self::record_class_load(thread, result, None, load_start);
+        return Ok(result);
     }
 
-    fn do_with_loaded_classes<R, F: FnOnce(HashTablePtr) -> R>(&self, f: F) -> R {
-        let loaded_classes = self.loaded_classes.lock();
-        return f(unsafe { *(*loaded_classes).as_ptr() });
+    /// Defines the class in `bytes` as an anonymous class of `host`, with the
+    /// loader of `host`. The class is named after the one in `bytes` plus a
+    /// unique suffix, so several anonymous classes can come from the same
+    /// bytes, and the non-null `cp_patches` replace its constant pool entries
+    /// of the same index.
+    pub(crate) fn define_anonymous_class(
+        &self,
+        bytes: Vec<u8>,
+        host: JClassPtr,
+        cp_patches: Vec<ObjectPtr>,
+        thread: ThreadPtr,
+    ) -> Result<JClassPtr, ClassLoadErr> {
+        let load_start = Instant::now();
+        let id = self.next_anonymous_id.fetch_add(1, Ordering::Relaxed);
+        let reader = Box::new(OwnedBytesClassReader::new(bytes));
+        let result = ClassParser::new(host.class_loader(), reader, thread.vm())
+            .anonymous(id, cp_patches)
+            .parse_class()?;
+        self.add_loaded_class(result, thread)?;
+        Self::record_class_load(thread, result, None, load_start);
+        return Ok(result);
+    }
+
+    /// Replaces the method bodies of the loaded `class` with those in `bytes`,
+    /// a new class file of it, see [`redefine`](super::redefine).
+    pub(crate) fn redefine_class(
+        &self,
+        class: JClassPtr,
+        bytes: Vec<u8>,
+        thread: ThreadPtr,
+    ) -> Result<(), ClassLoadErr> {
+        let class_name = class.name().as_str().to_string();
+        let loading_lock = self.loading_lock(&class_name);
+        let result = {
+            let _loading = loading_lock.lock();
+            Self::parse_class_bytes(bytes, thread)
+                .and_then(|redefined| redefine::redefine_methods(class, redefined, thread))
+        };
+        self.release_loading_lock(&class_name, loading_lock);
+        return result;
+    }
+
+    /// Parses a class without adding it to the loaded classes.
+    fn parse_class_bytes(bytes: Vec<u8>, thread: ThreadPtr) -> Result<JClassPtr, ClassLoadErr> {
+        let reader = Box::new(OwnedBytesClassReader::new(bytes));
+        let mut parser = ClassParser::new(thread.class_loader(), reader, thread.vm());
+        return parser.parse_class();
+    }
+
+    fn add_loaded_class(&self, class: JClassPtr, thread: ThreadPtr) -> Result<(), ClassLoadErr> {
+        let mut loaded_classes = self.loaded_classes.write();
+        if loaded_classes
+            .get_value_by_str::<_, JClass>(class.name())
+            .is_some()
+        {
+            let class_name = class.name().as_str().to_string();
+            return Err(ClassLoadErr::DuplicateClass(class_name));
+        }
+        *loaded_classes = loaded_classes.insert(class, thread);
+        return Ok(());
+    }
+
+    fn record_class_load(
+        thread: ThreadPtr,
+        class: JClassPtr,
+        source: Option<&str>,
+        load_start: Instant,
+    ) {
+        let vm = thread.vm();
+        let record = ClassLoadRecord {
+            name: class.binary_name(),
+            loader: "bootstrap".to_string(),
+            source: source.map(|source| source.to_string()),
+            elapsed: load_start.elapsed(),
+        };
+        if vm.cfg.verbose_class {
+            println!(
+                "[Loaded {} from {}]",
+                record.name,
+                source.unwrap_or("__JVM_DefineClass__")
+            );
+        }
+        vm.log_event(format!(
+            "Loaded {} from {}",
+            record.name,
+            source.unwrap_or("__JVM_DefineClass__")
+        ));
+        vm.stats
+            .record_class_load(record, vm.cfg.record_class_loads);
     }
 
-    fn do_with_mut_loaded_classes<R, F: FnOnce(&mut HashTablePtr) -> R>(&self, f: F) -> R {
-        let loaded_classes = self.loaded_classes.lock();
-        return f(unsafe { &mut *(*loaded_classes).as_ptr() });
+    fn do_with_loaded_classes<R, F: FnOnce(HashTablePtr) -> R>(&self, f: F) -> R {
+        return f(*self.loaded_classes.read());
     }
 }
 
@@ -190,11 +576,42 @@ impl GetEntryWithKey<SymbolPtr> for JClass {
     }
 }
 
-const CLASS_SUFFIX: &'static str = ".class";
+const SERVICES_DIR: &str = "META-INF/services";
+
+/// The provider names of a provider-configuration file: one per line, with
+/// `#` starting a comment and surrounding whitespace ignored.
+pub(crate) fn parse_provider_config(config: &[u8]) -> Vec<String> {
+    let mut providers: Vec<String> = Vec::new();
+    for line in String::from_utf8_lossy(config).lines() {
+        let line = match line.find('#') {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+        let provider = line.trim();
+        if !provider.is_empty() && !providers.iter().any(|p| p == provider) {
+            providers.push(provider.to_string());
+        }
+    }
+    return providers;
+}
+
+const CLASS_SUFFIX: &str = ".class";
 const CLASS_SUFFIX_LEN: usize = CLASS_SUFFIX.len();
 
 trait ClassPathEntry {
     fn reader(&mut self, filename: &str) -> Option<Box<dyn ClassReader>>;
+
+    /// The bytes of the file `name` in the entry.
+    fn resource(&mut self, name: &str) -> Option<Vec<u8>>;
+
+    /// The URL of the file `name` if the entry has it.
+    fn resource_url(&mut self, name: &str) -> Option<String>;
+
+    /// The names of the files directly in the directory `dir`.
+    fn list(&mut self, dir: &str) -> Vec<String>;
+
+    /// The directory or jar of the entry, as given on the class path.
+    fn source(&self) -> &str;
 }
 
 struct ClassPathDirEntry {
@@ -234,9 +651,44 @@ impl ClassPathEntry for ClassPathDirEntry {
             return None;
         }
     }
+
+    fn resource(&mut self, name: &str) -> Option<Vec<u8>> {
+        let path = format!("{}{}", self.dir, name);
+        if !std::path::Path::new(&path).is_file() {
+            return None;
+        }
+        return std::fs::read(path).ok();
+    }
+
+    fn resource_url(&mut self, name: &str) -> Option<String> {
+        let path = format!("{}{}", self.dir, name);
+        if !std::path::Path::new(&path).is_file() {
+            return None;
+        }
+        return Some(format!("file:{}", absolute_path(&path)));
+    }
+
+    fn list(&mut self, dir: &str) -> Vec<String> {
+        let entries = match std::fs::read_dir(format!("{}{}", self.dir, dir)) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        names.sort();
+        return names;
+    }
+
+    fn source(&self) -> &str {
+        return &self.dir;
+    }
 }
 
 struct ClassPathJarEntry {
+    jar: String,
     archive: zip::ZipArchive<File>,
 }
 
@@ -251,7 +703,10 @@ impl ClassPathJarEntry {
         } else {
             return None;
         };
-        return Some(Self { archive });
+        return Some(Self {
+            jar: jar.to_string(),
+            archive,
+        });
     }
 
     fn construct_entry_path(filename: &str) -> String {
@@ -289,4 +744,44 @@ impl ClassPathEntry for ClassPathJarEntry {
             None
         };
     }
+
+    fn resource(&mut self, name: &str) -> Option<Vec<u8>> {
+        let mut entry_file = self.archive.by_name(name).ok()?;
+        if entry_file.is_dir() {
+            return None;
+        }
+        let mut buf = Vec::with_capacity(entry_file.size() as usize);
+        std::io::copy(&mut entry_file, &mut buf).ok()?;
+        return Some(buf);
+    }
+
+    fn resource_url(&mut self, name: &str) -> Option<String> {
+        if self.archive.by_name(name).ok()?.is_dir() {
+            return None;
+        }
+        return Some(format!("jar:file:{}!/{}", absolute_path(&self.jar), name));
+    }
+
+    fn list(&mut self, dir: &str) -> Vec<String> {
+        let prefix = format!("{}/", dir);
+        return self
+            .archive
+            .file_names()
+            .filter_map(|name| name.strip_prefix(prefix.as_str()))
+            .filter(|name| !name.is_empty() && !name.contains('/'))
+            .map(|name| name.to_string())
+            .collect();
+    }
+
+    fn source(&self) -> &str {
+        return &self.jar;
+    }
+}
+
+/// `path` made absolute for a URL, as is if it cannot be resolved.
+fn absolute_path(path: &str) -> String {
+    return match std::fs::canonicalize(path) {
+        Ok(path) => path.to_string_lossy().into_owned(),
+        Err(_) => path.to_string(),
+    };
 }
@@ -10,6 +10,8 @@ use crate::{
     JClassPtr, ObjectPtr,
 };
 
+const MAX_ARRAY_DIMENSIONS: usize = 255;
+
 pub struct DescriptorParser<'a> {
     value: &'a [u8],
     offset: usize,
@@ -37,6 +39,11 @@ impl<'a> DescriptorParser<'a> {
         return result;
     }
 
+    /// The offset of the next descriptor in the bytes.
+    pub fn offset(&self) -> usize {
+        return self.offset;
+    }
+
     fn read(&mut self) -> u8 {
         let prefix;
         unsafe {
@@ -151,82 +158,59 @@ impl<'a> DescriptorParser<'a> {
                     Descriptor::InvalidDescriptor
                 }
             }
-            b'L' => loop {
-                if self.offset == self.value.len() {
-                    return Descriptor::InvalidDescriptor;
-                }
-                if self.peek() != b';' {
-                    self.offset += 1;
-                } else {
-                    // [Ljava/lang/Object; -> [Ljava/lang/Object;
-                    // Ljava/lang/Object; -> java/lang/Object
-                    let class_symbol;
-                    if self.cur_arr {
-                        class_symbol = self.class_symbol(symbol_start, self.offset);
+            b'L' => {
+                let name_start = self.offset;
+                loop {
+                    if self.offset == self.value.len() {
+                        return Descriptor::InvalidDescriptor;
+                    }
+                    if self.peek() != b';' {
+                        self.offset += 1;
+                    } else if self.offset == name_start {
+                        return Descriptor::InvalidDescriptor;
                     } else {
-                        class_symbol = self.class_symbol(symbol_start + 1, self.offset - 1);
+                        // [Ljava/lang/Object; -> [Ljava/lang/Object;
+                        // Ljava/lang/Object; -> java/lang/Object
+                        let class_symbol;
+                        if self.cur_arr {
+                            class_symbol = self.class_symbol(symbol_start, self.offset);
+                        } else {
+                            class_symbol = self.class_symbol(symbol_start + 1, self.offset - 1);
+                        }
+                        self.offset += 1;
+                        return Descriptor::Symbol(class_symbol, size_of::<ObjectPtr>());
                     }
-                    self.offset += 1;
-                    return Descriptor::Symbol(class_symbol, size_of::<ObjectPtr>());
                 }
-            },
+            }
             b'[' => {
                 if prev_offset == -1 && self.value.len() == 2 {
-                    match self.peek() {
-                        b'B' => {
-                            return Descriptor::ResolvedClass(
-                                preloaded_classes.byte_arr_cls(),
-                                size_of::<ObjectPtr>(),
-                            )
-                        }
-                        b'C' => {
-                            return Descriptor::ResolvedClass(
-                                preloaded_classes.char_arr_cls(),
-                                size_of::<ObjectPtr>(),
-                            )
-                        }
-                        b'D' => {
-                            return Descriptor::ResolvedClass(
-                                preloaded_classes.double_arr_cls(),
-                                size_of::<ObjectPtr>(),
-                            )
-                        }
-                        b'F' => {
-                            return Descriptor::ResolvedClass(
-                                preloaded_classes.float_arr_cls(),
-                                size_of::<ObjectPtr>(),
-                            )
-                        }
-                        b'I' => {
-                            return Descriptor::ResolvedClass(
-                                preloaded_classes.int_arr_cls(),
-                                size_of::<ObjectPtr>(),
-                            )
-                        }
-                        b'J' => {
-                            return Descriptor::ResolvedClass(
-                                preloaded_classes.long_arr_cls(),
-                                size_of::<ObjectPtr>(),
-                            )
-                        }
-                        b'S' => {
-                            return Descriptor::ResolvedClass(
-                                preloaded_classes.short_arr_cls(),
-                                size_of::<ObjectPtr>(),
-                            )
-                        }
-                        b'Z' => {
-                            return Descriptor::ResolvedClass(
-                                preloaded_classes.bool_arr_cls(),
-                                size_of::<ObjectPtr>(),
-                            )
-                        }
-                        b'V' => return Descriptor::InvalidDescriptor,
-                        _ => {}
+                    let array_cls = match self.peek() {
+                        b'B' => preloaded_classes.byte_arr_cls(),
+                        b'C' => preloaded_classes.char_arr_cls(),
+                        b'D' => preloaded_classes.double_arr_cls(),
+                        b'F' => preloaded_classes.float_arr_cls(),
+                        b'I' => preloaded_classes.int_arr_cls(),
+                        b'J' => preloaded_classes.long_arr_cls(),
+                        b'S' => preloaded_classes.short_arr_cls(),
+                        b'Z' => preloaded_classes.bool_arr_cls(),
+                        _ => JClassPtr::null(),
+                    };
+                    if array_cls.is_not_null() {
+                        self.offset += 1;
+                        return Descriptor::ResolvedClass(array_cls, size_of::<ObjectPtr>());
                     }
                 }
+                // a class file may not describe more than 255 dimensions
+                if self.offset - symbol_start > MAX_ARRAY_DIMENSIONS {
+                    return Descriptor::InvalidDescriptor;
+                }
                 self.cur_arr = true;
-                return self.next_class(symbol_start as isize);
+                return match self.next_class(symbol_start as isize) {
+                    Descriptor::OpenParenthesis
+                    | Descriptor::CloseParenthesis
+                    | Descriptor::End => Descriptor::InvalidDescriptor,
+                    component => component,
+                };
             }
             b')' => {
                 return Descriptor::CloseParenthesis;
@@ -247,6 +231,83 @@ impl<'a> DescriptorParser<'a> {
     }
 }
 
+/// The operand stack slots of the parameters of the method `descriptor`,
+/// two for each long and double and one for any other type, without the
+/// receiver. `None` if the descriptor is malformed.
+pub fn args_slots(descriptor: &[u8]) -> Option<usize> {
+    let mut bytes = descriptor.strip_prefix(b"(")?.iter();
+    let mut slots = 0;
+    loop {
+        match *bytes.next()? {
+            b')' => return Some(slots),
+            b'J' | b'D' => slots += 2,
+            b'B' | b'C' | b'F' | b'I' | b'S' | b'Z' => slots += 1,
+            prefix @ (b'L' | b'[') => {
+                let mut prefix = prefix;
+                while prefix == b'[' {
+                    prefix = *bytes.next()?;
+                }
+                match prefix {
+                    b'L' => while *bytes.next()? != b';' {},
+                    b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' => {}
+                    _ => return None,
+                }
+                slots += 1;
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Whether `descriptor` is a field descriptor (jvms-4.3.2).
+pub fn is_field_descriptor(descriptor: &[u8]) -> bool {
+    return field_descriptor_end(descriptor, 0) == Some(descriptor.len());
+}
+
+/// Whether `descriptor` is a method descriptor (jvms-4.3.3).
+pub fn is_method_descriptor(descriptor: &[u8]) -> bool {
+    if descriptor.first() != Some(&b'(') {
+        return false;
+    }
+    let mut idx = 1;
+    while descriptor.get(idx) != Some(&b')') {
+        match field_descriptor_end(descriptor, idx) {
+            Some(end) => idx = end,
+            None => return false,
+        }
+    }
+    idx += 1;
+    let end = if descriptor.get(idx) == Some(&b'V') {
+        Some(idx + 1)
+    } else {
+        field_descriptor_end(descriptor, idx)
+    };
+    return end == Some(descriptor.len());
+}
+
+/// The end of the field descriptor starting at `start`, `None` if there is
+/// none.
+fn field_descriptor_end(descriptor: &[u8], start: usize) -> Option<usize> {
+    let mut idx = start;
+    while descriptor.get(idx) == Some(&b'[') {
+        idx += 1;
+    }
+    if idx - start > MAX_ARRAY_DIMENSIONS {
+        return None;
+    }
+    return match descriptor.get(idx)? {
+        b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' => Some(idx + 1),
+        b'L' => {
+            let name_length = descriptor[idx + 1..].iter().position(|&b| b == b';')?;
+            if name_length == 0 {
+                return None;
+            }
+            Some(idx + 1 + name_length + 1)
+        }
+        _ => None,
+    };
+}
+
 #[derive(PartialEq, Debug)]
 pub enum Descriptor {
     ResolvedClass(JClassPtr, usize),
@@ -261,7 +322,7 @@ pub enum Descriptor {
 mod tests {
     use crate::{classfile::descriptor::Descriptor, memory::POINTER_SIZE, test::run_in_vm};
 
-    use super::DescriptorParser;
+    use super::{args_slots, is_field_descriptor, is_method_descriptor, DescriptorParser};
 
     #[test]
     fn parse_primitive_descriptor() {
@@ -394,4 +455,132 @@ mod tests {
             assert_eq!(Descriptor::End, descriptor.next());
         });
     }
+
+    #[test]
+    fn parse_array_descriptor() {
+        run_in_vm("./tests/classes", |vm| {
+            let preloaded_classes = vm.preloaded_classes();
+            let array_symbol =
+                |name: &str| Descriptor::Symbol(vm.symbol_table.get_or_insert(name), POINTER_SIZE);
+            let mut descriptor = DescriptorParser::from_symbol(
+                vm.get_symbol("([[Ljava/lang/String;[I[[JZ[[[D)[[Ljava/lang/String;"),
+                &vm,
+            );
+            assert_eq!(Descriptor::OpenParenthesis, descriptor.next());
+            assert_eq!(array_symbol("[[Ljava/lang/String;"), descriptor.next());
+            assert_eq!(array_symbol("[I"), descriptor.next());
+            assert_eq!(array_symbol("[[J"), descriptor.next());
+            assert_eq!(
+                Descriptor::ResolvedClass(preloaded_classes.bool_cls(), 1),
+                descriptor.next()
+            );
+            assert_eq!(array_symbol("[[[D"), descriptor.next());
+            assert_eq!(Descriptor::CloseParenthesis, descriptor.next());
+            assert_eq!(array_symbol("[[Ljava/lang/String;"), descriptor.next());
+            assert_eq!(Descriptor::End, descriptor.next());
+
+            for ret in ["[B", "[[C", "[Ljava/lang/Object;", "[[[Ljava/lang/Object;"] {
+                let method_descriptor = format!("(){}", ret);
+                let mut descriptor =
+                    DescriptorParser::from_symbol(vm.get_symbol(&method_descriptor), &vm);
+                assert_eq!(Descriptor::OpenParenthesis, descriptor.next());
+                assert_eq!(Descriptor::CloseParenthesis, descriptor.next());
+                assert_eq!(array_symbol(ret), descriptor.next());
+                assert_eq!(Descriptor::End, descriptor.next());
+            }
+
+            let primitive_arrays = [
+                ("[B", preloaded_classes.byte_arr_cls()),
+                ("[C", preloaded_classes.char_arr_cls()),
+                ("[D", preloaded_classes.double_arr_cls()),
+                ("[F", preloaded_classes.float_arr_cls()),
+                ("[I", preloaded_classes.int_arr_cls()),
+                ("[J", preloaded_classes.long_arr_cls()),
+                ("[S", preloaded_classes.short_arr_cls()),
+                ("[Z", preloaded_classes.bool_arr_cls()),
+            ];
+            for (name, array_cls) in primitive_arrays {
+                let mut descriptor = DescriptorParser::from_symbol(vm.get_symbol(name), &vm);
+                assert_eq!(
+                    Descriptor::ResolvedClass(array_cls, POINTER_SIZE),
+                    descriptor.next()
+                );
+                assert_eq!(Descriptor::End, descriptor.next());
+            }
+        });
+    }
+
+    #[test]
+    fn parse_nested_array_descriptor() {
+        run_in_vm("./tests/classes", |vm| {
+            let deepest = format!("{}Ljava/lang/Object;", "[".repeat(255));
+            let mut descriptor = DescriptorParser::from_symbol(vm.get_symbol(&deepest), &vm);
+            assert_eq!(
+                Descriptor::Symbol(vm.symbol_table.get_or_insert(&deepest), POINTER_SIZE),
+                descriptor.next()
+            );
+            assert_eq!(Descriptor::End, descriptor.next());
+
+            let too_deep = format!("([{}I)V", "[".repeat(255));
+            let mut descriptor = DescriptorParser::from_symbol(vm.get_symbol(&too_deep), &vm);
+            assert_eq!(Descriptor::OpenParenthesis, descriptor.next());
+            assert_eq!(Descriptor::InvalidDescriptor, descriptor.next());
+        });
+    }
+
+    #[test]
+    fn parse_invalid_descriptor() {
+        run_in_vm("./tests/classes", |vm| {
+            let is_invalid = |value: &str| {
+                let mut descriptor = DescriptorParser::from_symbol(vm.get_symbol(value), &vm);
+                loop {
+                    match descriptor.next() {
+                        Descriptor::InvalidDescriptor => return true,
+                        Descriptor::End => return false,
+                        _ => {}
+                    }
+                }
+            };
+            assert!(!is_invalid("([[Ljava/lang/String;)[I"));
+            assert!(is_invalid("([)V"));
+            assert!(is_invalid("([V)V"));
+            assert!(is_invalid("()[V"));
+            assert!(is_invalid("()["));
+            assert!(is_invalid("(L;)V"));
+            assert!(is_invalid("([L;)V"));
+            assert!(is_invalid("([[Ljava/lang/String)V"));
+        });
+    }
+
+    #[test]
+    fn method_args_slots() {
+        assert_eq!(Some(0), args_slots(b"()V"));
+        assert_eq!(Some(6), args_slots(b"(IJDLjava/lang/String;)J"));
+        assert_eq!(Some(3), args_slots(b"([J[[Ljava/lang/Object;Z)V"));
+        assert_eq!(Some(4), args_slots(b"([[D[[[Ljava/lang/String;J)[[D"));
+        assert_eq!(None, args_slots(b"IJ"));
+        assert_eq!(None, args_slots(b"(Ljava/lang/String"));
+        assert_eq!(None, args_slots(b"(V)V"));
+        assert_eq!(None, args_slots(b"([V)V"));
+    }
+
+    #[test]
+    fn descriptor_syntax() {
+        assert!(is_field_descriptor(b"[[Ljava/lang/String;"));
+        assert!(is_field_descriptor(b"J"));
+        assert!(!is_field_descriptor(b"V"));
+        assert!(!is_field_descriptor(b"L;"));
+        assert!(!is_field_descriptor(b"Ljava/lang/String"));
+        assert!(!is_field_descriptor(b"II"));
+        assert!(is_field_descriptor(&[&[b'['; 255][..], b"I"].concat()));
+        assert!(!is_field_descriptor(&[&[b'['; 256][..], b"I"].concat()));
+
+        assert!(is_method_descriptor(b"()V"));
+        assert!(is_method_descriptor(b"(IJ[Ljava/lang/Object;)[D"));
+        assert!(!is_method_descriptor(b"(V)V"));
+        assert!(!is_method_descriptor(b"()[V"));
+        assert!(!is_method_descriptor(b"(I"));
+        assert!(!is_method_descriptor(b"()VV"));
+        assert!(!is_method_descriptor(b"I"));
+    }
 }
@@ -2,15 +2,135 @@ pub mod class_info;
 pub mod class_loader;
 pub mod parser;
 pub mod reader;
+pub(crate) mod redefine;
 pub mod descriptor;
 
 // pub use class_loader::ClassLoader;
 
 // pub type ClassLoadErr = String;
 
+use std::{error::Error, fmt, str::Utf8Error};
+
 #[derive(Debug)]
 pub enum ClassLoadErr {
+    /// No class path entry provides the class.
+    ClassNotFound(String),
+    BadMagic {
+        found: u32,
+    },
+    UnsupportedVersion {
+        found: u16,
+        max: u16,
+    },
+    /// The class file ends before `needed` more bytes at `offset`.
+    Truncated {
+        offset: usize,
+        needed: usize,
+    },
+    /// The constant pool entry at `index` is cut off.
+    TruncatedPool {
+        index: u16,
+        source: Box<ClassLoadErr>,
+    },
+    InvalidUtf8 {
+        index: u16,
+        source: Utf8Error,
+    },
+    InvalidDescriptor {
+        member: String,
+        descriptor: String,
+    },
+    UnsupportedAttribute(String),
+    /// A class listed in `interfaces` is not an interface.
+    NotAnInterface {
+        class: String,
+        interface: String,
+    },
+    /// Loading the superclass or a superinterface of `class` failed.
+    Dependency {
+        class: String,
+        source: Box<ClassLoadErr>,
+    },
     InvalidFormat(String),
-    VerifyFailed(String),
-    ClassLoaderInvalidLockState(String),
+    /// A class name is empty, has an empty package segment or is a
+    /// malformed array class name.
+    InvalidClassName(String),
+    /// The class file is well-formed but fails verification.
+    Verify(String),
+    /// A class of the same name is already loaded.
+    DuplicateClass(String),
+    /// The class file defines `found` instead of the class `expected`.
+    WrongName {
+        expected: String,
+        found: String,
+    },
+    /// The new class file of `class` changes more than method bodies.
+    UnsupportedRedefinition {
+        class: String,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for ClassLoadErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            ClassLoadErr::ClassNotFound(name) => write!(f, "cannot find class {}", name),
+            ClassLoadErr::BadMagic { found } => {
+                write!(f, "bad magic number 0x{:08X}", found)
+            }
+            ClassLoadErr::UnsupportedVersion { found, max } => write!(
+                f,
+                "unsupported class file major version {}, at most {} is supported",
+                found, max
+            ),
+            ClassLoadErr::Truncated { offset, needed } => write!(
+                f,
+                "class file truncated, expected {} bytes at offset {}",
+                needed, offset
+            ),
+            ClassLoadErr::TruncatedPool { index, .. } => {
+                write!(f, "constant pool entry #{} is truncated", index)
+            }
+            ClassLoadErr::InvalidUtf8 { index, .. } => {
+                write!(f, "invalid CONSTANT_Utf8 at #{}", index)
+            }
+            ClassLoadErr::InvalidDescriptor { member, descriptor } => {
+                write!(f, "invalid descriptor {} for {}", descriptor, member)
+            }
+            ClassLoadErr::UnsupportedAttribute(name) => {
+                write!(f, "unsupported attribute {}", name)
+            }
+            ClassLoadErr::NotAnInterface { class, interface } => write!(
+                f,
+                "{} cannot implement {}, it is not an interface",
+                class, interface
+            ),
+            ClassLoadErr::Dependency { class, .. } => {
+                write!(f, "cannot load a supertype of {}", class)
+            }
+            ClassLoadErr::InvalidFormat(msg) => write!(f, "invalid class file: {}", msg),
+            ClassLoadErr::InvalidClassName(name) => write!(f, "invalid class name {}", name),
+            ClassLoadErr::Verify(msg) => write!(f, "verification failed: {}", msg),
+            ClassLoadErr::DuplicateClass(name) => {
+                write!(f, "attempted duplicate class definition for {}", name)
+            }
+            ClassLoadErr::WrongName { expected, found } => {
+                write!(f, "{} (wrong name: {})", expected, found)
+            }
+            ClassLoadErr::UnsupportedRedefinition { class, reason } => {
+                write!(f, "cannot redefine {}: {}", class, reason)
+            }
+        };
+    }
+}
+
+impl Error for ClassLoadErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return match self {
+            ClassLoadErr::TruncatedPool { source, .. }
+            | ClassLoadErr::Dependency { source, .. } => Some(source.as_ref()),
+            ClassLoadErr::InvalidUtf8 { source, .. } => Some(source),
+            _ => None,
+        };
+    }
 }
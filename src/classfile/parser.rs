@@ -1,26 +1,34 @@
-use super::reader::ClassReader;
+use super::reader::{ClassReader, ExternalBytesClassReader};
 use super::ClassLoadErr;
-use crate::classfile::descriptor::{Descriptor, DescriptorParser};
+use crate::classfile::descriptor::{self, Descriptor, DescriptorParser};
 use crate::handle::Handle;
-use crate::memory::heap::Heap;
 use crate::object::array::JArray;
 use crate::object::class::{FieldLayout, JClass, JClassPtr, VTable, VTableInfo};
 use crate::object::constant_pool::{ConstantPool, ConstantTag};
 use crate::object::field::{Field, FieldAccessFlags};
-use crate::object::method::{ExceptionTable, Method, MethodPtr};
+use crate::object::method::{ExceptionTable, LineNumber, Method, MethodAccessFlags, MethodPtr};
 use crate::object::prelude::*;
+use crate::object::string::JString;
+use crate::object::symbol::decode_modified_utf8;
+use crate::platform::{MAX_CLASS_FILE_VERSION, MIN_CLASS_FILE_VERSION};
 use crate::thread::Thread;
 use crate::vm::VM;
-use std::convert::TryInto;
-use std::f64;
 
 const CLASS_FILE_MAGIC: u32 = 0xCAFEBABE;
 
+/// How deep annotations may nest in element values, each level is a call.
+const MAX_ANNOTATION_DEPTH: usize = 64;
+
 pub struct ClassParser<'a> {
     jclass_loader: ObjectPtr,
     reader: Box<dyn ClassReader>,
     vm: &'a VM,
     this_class_name: SymbolPtr,
+    /// Set for an anonymous class, whose name gets this suffix.
+    anonymous_id: Option<u64>,
+    /// The objects replacing the constant pool entries of an anonymous
+    /// class, by index. Null leaves an entry as it is.
+    cp_patches: Vec<ObjectPtr>,
 }
 
 impl<'a> ClassParser<'a> {
@@ -30,32 +38,48 @@ impl<'a> ClassParser<'a> {
             reader,
             vm,
             this_class_name: SymbolPtr::null(),
+            anonymous_id: None,
+            cp_patches: Vec::new(),
         }
     }
 
+    /// Parses the class as an anonymous class named `<name>/<id>`, whose
+    /// constant pool entries are replaced by the non-null `cp_patches`.
+    pub(crate) fn anonymous(mut self, id: u64, cp_patches: Vec<ObjectPtr>) -> Self {
+        self.anonymous_id = Some(id);
+        self.cp_patches = cp_patches;
+        return self;
+    }
+
+    /// Reads and checks the class file `bytes` without a VM, the first step
+    /// of [`parse_class`](Self::parse_class). Nothing is interned, allocated
+    /// on a VM heap or loaded, the constant pool is a scratch table dropped
+    /// with the result. Malformed input of any kind is an error rather than
+    /// a panic, so untrusted bytes can be checked, and fuzzed, this way.
+    pub fn parse_scratch(bytes: &[u8]) -> Result<ClassFile, ClassLoadErr> {
+        return ClassFileReader::read(&mut ExternalBytesClassReader::new(bytes));
+    }
+
     pub fn parse_class(&mut self) -> Result<JClassPtr, ClassLoadErr> {
-        let magic = self.reader.read_ubyte4()?;
-        if magic != CLASS_FILE_MAGIC {
-            return Err(ClassLoadErr::InvalidFormat(
-                "cannot identify the magic number".to_string(),
-            ));
-        }
-        let _minor_version = self.reader.read_ubyte2()?;
-        let major_version = self.reader.read_ubyte2()?;
-        if !Self::major_version_is_support(major_version) {
-            return Err(ClassLoadErr::InvalidFormat(
-                "unsupported class file version".to_string(),
-            ));
-        }
-        let cp = self.parse_constant_pool()?;
-        let access_flags = self.reader.read_ubyte2()?;
-        let this_class = self.reader.read_ubyte2()?;
-        let class_name = cp.get_class_name(this_class);
+        let class_file = ClassFileReader::read(self.reader.as_mut())?;
+        let mut cp = self.new_constant_pool(&class_file)?;
+        self.patch_constant_pool(&mut cp)?;
+        let access_flags = class_file.access_flags;
+        let this_class = class_file.this_class;
+        let class_name = match self.anonymous_id {
+            Some(id) => {
+                // self-references of the class resolve to the renamed class
+                let name = format!("{}/{}", cp.get_class_name(this_class).as_str(), id);
+                let name = self.vm.get_symbol(&name);
+                let name_index = cp.get_class_name_index(this_class);
+                cp.set_utf8(name_index, name);
+                name
+            }
+            None => cp.get_class_name(this_class),
+        };
         self.this_class_name = class_name;
-        let super_class_index = self.reader.read_ubyte2()?;
-        let super_class_name = if super_class_index != 0 {
-            let super_class_name = cp.get_class_name(super_class_index);
-            super_class_name
+        let super_class_name = if class_file.super_class != 0 {
+            cp.get_class_name(class_file.super_class)
         } else {
             Ptr::null()
         };
@@ -63,24 +87,26 @@ impl<'a> ClassParser<'a> {
         let java_lang_class_bootstrapping = self.vm.shared_objs().symbols().java_lang_Class
             == class_name
             && self.vm.shared_objs().is_bootstrapping();
-        let interfaces = self.parse_interfaces(&cp, java_lang_class_bootstrapping)?;
+        let interfaces =
+            self.new_interfaces(&cp, &class_file.interfaces, java_lang_class_bootstrapping)?;
         let ParsedFields {
             fields,
             static_fields_size,
             inst_size,
             metadata_offset,
-        } = self.parse_fields(&cp)?;
+        } = self.new_fields(&cp, &class_file.fields)?;
         if java_lang_class_bootstrapping {
             // bootstrapping
             self.vm.shared_objs().bootstrap(inst_size);
         }
         let mut init_method = MethodPtr::null();
-        let methods = self.parse_methods(&cp, &mut init_method)?;
+        let methods = self.new_methods(&cp, &class_file.methods, &mut init_method)?;
 
         let super_class = if super_class_name.is_not_null() {
             self.vm
                 .bootstrap_class_loader
-                .load_class(super_class_name.as_str())?
+                .load_class(super_class_name.as_str())
+                .map_err(|e| self.dependency_err(e))?
         } else {
             JClassPtr::null()
         };
@@ -92,7 +118,8 @@ impl<'a> ClassParser<'a> {
                 let iface = self
                     .vm
                     .bootstrap_class_loader
-                    .load_class(iface_name.as_str())?;
+                    .load_class(iface_name.as_str())
+                    .map_err(|e| self.dependency_err(e))?;
                 interfaces.set(iface_idx, iface.cast());
             }
         }
@@ -108,10 +135,12 @@ impl<'a> ClassParser<'a> {
             );
             VTable::obtain_vtab_info(
                 access_flags,
+                class_name,
+                self.jclass_loader,
                 methods.as_ptr(),
                 super_class,
                 interfaces.as_ptr(),
-                self.vm.shared_objs().symbols().ctor_init,
+                self.vm,
             )
         };
 
@@ -140,149 +169,165 @@ impl<'a> ClassParser<'a> {
             Thread::current(),
         );
         debug_assert_eq!(class.class_data().methods().length(), methods.length());
-        self.parse_class_attrs(&cp, class)?;
-        debug_assert_eq!(self.reader.available_bytes().len(), 0);
+        for inner_class in class_file.inner_classes.iter() {
+            if cp.get_class_name(inner_class.inner_class_info_index) == self.this_class_name {
+                class
+                    .class_data()
+                    .set_inner_access_flags(inner_class.access_flags);
+            }
+        }
+        if let Some(source_file_index) = class_file.source_file {
+            class
+                .class_data()
+                .set_source_file(cp.get_utf8(source_file_index));
+        }
         return Ok(class);
     }
 
-    /// jvms-4.4
-    fn parse_constant_pool(&mut self) -> Result<Handle<ConstantPool>, ClassLoadErr> {
-        let cp_count = self.reader.read_ubyte2()?;
-        // The constant_pool table is indexed from 1 to constant_pool_count - 1.
-        let mut cp = Handle::new(ConstantPool::new(cp_count, Thread::current()));
-        let mut index = 1;
-        while index <= cp_count - 1 {
-            let tag_u8: u8 = self.reader.read_ubyte1()?;
-            let tag = ConstantTag::from(tag_u8);
-            match tag {
-                ConstantTag::Utf8 => {
-                    let length = usize::from(self.reader.read_ubyte2()?);
-                    let bytes = self.reader.peek_nbytes(length)?;
-                    let symbol = std::str::from_utf8(bytes).map_err(|e| {
-                        ClassLoadErr::InvalidFormat(format!("invalid CONSTANT_Utf8: {}", e))
-                    })?;
-                    cp.set_utf8(index, self.vm.symbol_table.get_or_insert(symbol));
-                    self.reader.skip(length);
-                }
-                ConstantTag::Integer => cp.set_int32(index, self.reader.read_ubyte4()? as i32),
-                ConstantTag::Float => cp.set_float(index, self.reader.read_ubyte4()? as f32),
-                ConstantTag::Long => {
-                    let high_bytes = (self.reader.read_ubyte4()? as JLong) << 32;
-                    let low_bytes = self.reader.read_ubyte4()? as JLong;
-                    cp.set_long(index, high_bytes | low_bytes);
-                    index += 1
-                }
-                ConstantTag::Double => {
-                    let high_bytes = (self.reader.read_ubyte4()? as u64) << 32;
-                    let low_bytes = self.reader.read_ubyte4()?;
-                    let bits = high_bytes | low_bytes as u64;
-                    let result: JDouble = match bits {
-                        0x7ff0000000000000 => f64::INFINITY,
-                        0xfff0000000000000 => f64::NEG_INFINITY,
-                        _bits @ 0x7ff0000000000001..=0x7fffffffffffffff
-                        | _bits @ 0xfff0000000000001..=0xffffffffffffffff => f64::NAN,
-                        _ => {
-                            let s: i32 = if (bits >> 63) == 0 { 1 } else { -1 };
-                            let e: u32 = ((bits >> 52) & 0x7ff) as u32;
-                            let m: i64 = if e == 0 {
-                                ((bits & 0xfffffffffffff) << 1) as i64
-                            } else {
-                                ((bits & 0xfffffffffffff) | 0x10000000000000) as i64
-                            };
-                            s as f64 * m as f64 * i32::pow(2, e - 1075) as f64
-                        }
-                    };
-                    cp.set_double(index, result);
-                    index += 1
+    /// The constant pool of the class, with the Utf8 constants of the
+    /// scratch one interned.
+    fn new_constant_pool(
+        &self,
+        class_file: &ClassFile,
+    ) -> Result<Handle<ConstantPool>, ClassLoadErr> {
+        let constants = &class_file.constant_pool;
+        let mut cp = Handle::new(ConstantPool::new(constants.len() as u16, Thread::current()));
+        for (index, constant) in constants.iter().enumerate() {
+            let index = index as u16;
+            match *constant {
+                Constant::Unusable => {}
+                Constant::Utf8(ref bytes) => {
+                    let symbol = self
+                        .vm
+                        .symbol_table
+                        .intern_bytes(bytes)
+                        .map_err(|source| ClassLoadErr::InvalidUtf8 { index, source })?;
+                    cp.set_utf8(index, symbol);
                 }
-                ConstantTag::Class => cp.set_class_index(index, self.reader.read_ubyte2()?),
-                ConstantTag::String => cp.set_string(index, self.reader.read_ubyte2()?),
-                ConstantTag::Fieldref => cp.set_field_ref(
-                    index,
-                    self.reader.read_ubyte2()?,
-                    self.reader.read_ubyte2()?,
-                ),
-                ConstantTag::Methodref => cp.set_method_ref(
-                    index,
-                    self.reader.read_ubyte2()?,
-                    self.reader.read_ubyte2()?,
-                ),
-                ConstantTag::InterfaceMethodref => cp.set_interface_method_ref(
-                    index,
-                    self.reader.read_ubyte2()?,
-                    self.reader.read_ubyte2()?,
-                ),
-                ConstantTag::NameAndType => cp.set_name_and_type(
-                    index,
-                    self.reader.read_ubyte2()?,
-                    self.reader.read_ubyte2()?,
-                ),
-                ConstantTag::MethodHandle => cp.set_method_handle(
-                    index,
-                    self.reader.read_ubyte1()?,
-                    self.reader.read_ubyte2()?,
-                ),
-                ConstantTag::MethodType => cp.set_method_type(index, self.reader.read_ubyte2()?),
-                ConstantTag::InvokeDynamic => cp.set_invoke_dynamic(
-                    index,
-                    self.reader.read_ubyte2()?,
-                    self.reader.read_ubyte2()?,
-                ),
-                _ => {
-                    cp.set_invalid(index);
-                    self.reader.skip(1);
+                Constant::Integer(value) => cp.set_int32(index, value),
+                Constant::Float(value) => cp.set_float(index, value),
+                Constant::Long(value) => cp.set_long(index, value),
+                Constant::Double(value) => cp.set_double(index, value),
+                Constant::Class(name_index) => cp.set_class_index(index, name_index),
+                Constant::String(string_index) => cp.set_string(index, string_index),
+                Constant::Fieldref(class_index, name_and_type_index) => {
+                    cp.set_field_ref(index, class_index, name_and_type_index)
                 }
-            };
-            index += 1
+                Constant::Methodref(class_index, name_and_type_index) => {
+                    cp.set_method_ref(index, class_index, name_and_type_index)
+                }
+                Constant::InterfaceMethodref(class_index, name_and_type_index) => {
+                    cp.set_interface_method_ref(index, class_index, name_and_type_index)
+                }
+                Constant::NameAndType(name_index, descriptor_index) => {
+                    cp.set_name_and_type(index, name_index, descriptor_index)
+                }
+                Constant::MethodHandle(ref_kind, ref_index) => {
+                    cp.set_method_handle(index, ref_kind, ref_index)
+                }
+                Constant::MethodType(descriptor_index) => {
+                    cp.set_method_type(index, descriptor_index)
+                }
+                Constant::InvokeDynamic(bootstrap_method_attr_index, name_and_type_index) => {
+                    cp.set_invoke_dynamic(index, bootstrap_method_attr_index, name_and_type_index)
+                }
+            }
+        }
+        return Ok(cp);
+    }
+
+    /// Replaces the constant pool entries of an anonymous class: a Utf8 entry
+    /// by a string, a String entry by any object that `ldc` then pushes, and
+    /// a Class entry by a class, whose name the entry then refers to.
+    fn patch_constant_pool(&self, cp: &mut Handle<ConstantPool>) -> Result<(), ClassLoadErr> {
+        if self.cp_patches.len() > usize::from(cp.length()) {
+            return Err(ClassLoadErr::InvalidFormat(format!(
+                "{} constant pool patches for {} constants",
+                self.cp_patches.len(),
+                cp.length()
+            )));
+        }
+        let symbols = self.vm.shared_objs().symbols();
+        for (index, patch) in self.cp_patches.iter().enumerate() {
+            if patch.is_null() {
+                continue;
+            }
+            let index = index as u16;
+            let patch_cls = patch.jclass();
+            match cp.get_tag(index) {
+                ConstantTag::Utf8 if patch_cls.name() == symbols.java_lang_String => {
+                    let value = JString::to_rust_string(patch.cast(), self.vm);
+                    cp.set_utf8(index, self.vm.get_symbol(&value));
+                }
+                ConstantTag::String => cp.cache_string(index, patch.cast()),
+                ConstantTag::Class if patch_cls == self.vm.preloaded_classes().jclass_cls() => {
+                    let class: JClassPtr = patch.cast();
+                    let name_index = cp.get_class_name_index(index);
+                    cp.set_utf8(name_index, class.name());
+                }
+                tag => {
+                    return Err(ClassLoadErr::InvalidFormat(format!(
+                        "cannot patch the {:?} constant #{} with a {}",
+                        tag,
+                        index,
+                        patch_cls.name().as_str()
+                    )))
+                }
+            }
         }
-        Ok(cp)
+        return Ok(());
     }
 
-    fn parse_interfaces(
+    fn new_interfaces(
         &mut self,
         cp: &Handle<ConstantPool>,
+        class_indexes: &[u16],
         java_lang_class_bootstrapping: bool,
     ) -> Result<Handle<JArray>, ClassLoadErr> {
-        let length = JInt::from(self.reader.read_ubyte2()?);
-        if length == 0 {
+        if class_indexes.is_empty() {
             return Ok(Handle::new(self.vm.shared_objs().empty_sys_arr));
         }
+        let length = class_indexes.len() as JInt;
         let interfaces = Handle::new(JArray::new_internal_permanent(length, Thread::current()));
-        for index in 0..length {
-            let class_name = cp.get_class_name(self.reader.read_ubyte2()?);
+        for (index, &class_index) in class_indexes.iter().enumerate() {
+            let class_name = cp.get_class_name(class_index);
             let class = if java_lang_class_bootstrapping {
                 class_name.cast()
             } else {
                 let class = self
                     .vm
                     .bootstrap_class_loader
-                    .load_class(class_name.as_str())?;
+                    .load_class(class_name.as_str())
+                    .map_err(|e| self.dependency_err(e))?;
                 if !class.class_data().is_interface() {
-                    return Err(ClassLoadErr::VerifyFailed(
-                        "class file format error: invalid interface".to_string(),
-                    ));
+                    return Err(ClassLoadErr::NotAnInterface {
+                        class: self.this_class_name.as_str().to_string(),
+                        interface: class_name.as_str().to_string(),
+                    });
                 }
                 class
             };
-            interfaces.set(index, class.cast());
+            interfaces.set(index as JInt, class.cast());
         }
         Ok(interfaces)
     }
 
-    fn parse_fields(&mut self, cp: &Handle<ConstantPool>) -> Result<ParsedFields, ClassLoadErr> {
-        let fields_count = self.reader.read_ubyte2()? as JInt;
+    fn new_fields(
+        &mut self,
+        cp: &Handle<ConstantPool>,
+        field_infos: &[FieldInfo],
+    ) -> Result<ParsedFields, ClassLoadErr> {
         let mut static_fields_layout = FieldLayout::default();
         let mut inst_fields_layout = FieldLayout::default(); // TODO
         let thread = Thread::current();
-        let fields = Handle::new(JArray::new_internal_permanent(fields_count, thread));
-        for field_index in 0..fields_count {
-            let access_flags = self.reader.read_ubyte2()?;
-            let name_index = self.reader.read_ubyte2()?;
-            let name = cp.get_utf8(name_index);
-            debug_assert!(name.as_str().len() > 0);
-            let descriptor_index = self.reader.read_ubyte2()?;
-            let descriptor = cp.get_utf8(descriptor_index);
-            let attrs_count = self.reader.read_ubyte2()?;
+        let fields = Handle::new(JArray::new_internal_permanent(
+            field_infos.len() as JInt,
+            thread,
+        ));
+        for (field_index, field_info) in field_infos.iter().enumerate() {
+            let access_flags = field_info.access_flags;
+            let name = cp.get_utf8(field_info.name_index);
+            let descriptor = cp.get_utf8(field_info.descriptor_index);
             let field_class_or_null: JClassPtr;
             let field_val_size: u16;
             let descriptor_symbol: SymbolPtr;
@@ -312,20 +357,23 @@ impl<'a> ClassParser<'a> {
                         descriptor.as_str()
                     );
                 }
-                _ => {
+                _ => return Err(self.descriptor_err(name, descriptor)),
+            };
+
+            let field_offset = if FieldAccessFlags::is_static(access_flags) {
+                static_fields_layout.obtain_field_offset(field_val_size)
+            } else {
+                inst_fields_layout.obtain_field_offset(field_val_size)
+            };
+            let field_offset = match field_offset {
+                Some(field_offset) => field_offset,
+                None => {
                     return Err(ClassLoadErr::InvalidFormat(format!(
-                        "invalid descriptor for field {}",
-                        name.as_str()
+                        "{}: the fields take more than 64 KB",
+                        self.this_class_name.as_str()
                     )))
                 }
             };
-
-            let field_offset;
-            if FieldAccessFlags::is_static(access_flags) {
-                field_offset = static_fields_layout.obtain_field_offset(field_val_size);
-            } else {
-                field_offset = inst_fields_layout.obtain_field_offset(field_val_size);
-            }
             let mut field = Field::new(
                 access_flags,
                 field_offset,
@@ -334,297 +382,788 @@ impl<'a> ClassParser<'a> {
                 field_class_or_null,
                 thread,
             );
+            field.set_constval_index(field_info.constval_index);
+            fields.set(field_index as JInt, field.cast());
+        }
+        let inst_size = inst_fields_layout.get_aligned_size();
+        let static_fields_size = static_fields_layout.get_aligned_size();
+        Ok(ParsedFields {
+            fields,
+            static_fields_size,
+            inst_size: self
+                .vm
+                .shared_objs()
+                .resize_for_metadata(self.this_class_name, inst_size),
+            metadata_offset: inst_size,
+        })
+    }
+
+    fn new_methods(
+        &mut self,
+        cp: &Handle<ConstantPool>,
+        method_infos: &[MethodInfo],
+        init_method: &mut MethodPtr,
+    ) -> Result<Handle<JArray>, ClassLoadErr> {
+        let thread = Thread::current();
+        let methods = Handle::new(JArray::new_internal_permanent(
+            method_infos.len() as JInt,
+            thread,
+        ));
+        for (index, method_info) in method_infos.iter().enumerate() {
+            let name = cp.get_utf8(method_info.name_index);
+            let descriptor = cp.get_utf8(method_info.descriptor_index);
 
+            let mut descriptor_it = DescriptorParser::from_symbol(descriptor, self.vm);
+            if Descriptor::OpenParenthesis != descriptor_it.next() {
+                return Err(self.descriptor_err(name, descriptor));
+            }
+            // the parameter and return classes are loaded when the method is linked
+            let mut param_descriptors = Vec::new();
+            let has_close_parenthesis: bool;
+            'parse_descriptor: loop {
+                let param_start = descriptor_it.offset();
+                match descriptor_it.next() {
+                    Descriptor::ResolvedClass(param_cls, _)
+                        if param_cls == self.vm.preloaded_classes().void_cls() =>
+                    {
+                        return Err(self.descriptor_err(name, descriptor));
+                    }
+                    Descriptor::ResolvedClass(..) | Descriptor::Symbol(..) => {}
+                    Descriptor::CloseParenthesis => {
+                        has_close_parenthesis = true;
+                        break 'parse_descriptor;
+                    }
+                    Descriptor::OpenParenthesis
+                    | Descriptor::InvalidDescriptor
+                    | Descriptor::End => {
+                        return Err(self.descriptor_err(name, descriptor));
+                    }
+                };
+                let param_descriptor = &descriptor.as_str()[param_start..descriptor_it.offset()];
+                param_descriptors.push(self.vm.get_symbol(param_descriptor));
+            }
+            if !has_close_parenthesis {
+                return Err(self.descriptor_err(name, descriptor));
+            }
+            let param_descriptors = if param_descriptors.is_empty() {
+                self.vm.shared_objs().empty_sys_arr
+            } else {
+                let array = JArray::new_internal_permanent(
+                    param_descriptors.len() as JInt,
+                    Thread::current(),
+                );
+                for (idx, param_descriptor) in param_descriptors.iter().enumerate() {
+                    array.set(idx as JInt, param_descriptor.cast());
+                }
+                array
+            };
+
+            let ret_start = descriptor_it.offset();
+            match descriptor_it.next() {
+                Descriptor::ResolvedClass(..) | Descriptor::Symbol(..) => {}
+                _ => return Err(self.descriptor_err(name, descriptor)),
+            };
+            let ret_descriptor = self
+                .vm
+                .get_symbol(&descriptor.as_str()[ret_start..descriptor_it.offset()]);
+            if Descriptor::End != descriptor_it.next() {
+                return Err(self.descriptor_err(name, descriptor));
+            }
+
+            let no_handlers = Vec::new();
+            let no_lines = Vec::new();
+            let method = match &method_info.code {
+                Some(code) => Method::new(
+                    cp.as_ptr(),
+                    method_info.access_flags,
+                    name,
+                    descriptor,
+                    param_descriptors,
+                    ret_descriptor,
+                    code.max_stack,
+                    code.max_locals,
+                    code.code.len() as u16,
+                    code.code.as_ptr(),
+                    &code.ex_tab,
+                    &code.line_tab,
+                    thread,
+                ),
+                None => Method::new(
+                    cp.as_ptr(),
+                    method_info.access_flags,
+                    name,
+                    descriptor,
+                    param_descriptors,
+                    ret_descriptor,
+                    0,
+                    0,
+                    0,
+                    std::ptr::null(),
+                    &no_handlers,
+                    &no_lines,
+                    thread,
+                ),
+            };
+            method
+                .as_mut_ref()
+                .set_caller_sensitive(method_info.caller_sensitive);
+            if name.as_str() == "<clinit>" {
+                *init_method = method;
+            }
+            methods.set(index as JInt, method.cast());
+        }
+        return Ok(methods);
+    }
+
+    fn descriptor_err(&self, member: SymbolPtr, descriptor: SymbolPtr) -> ClassLoadErr {
+        return ClassLoadErr::InvalidDescriptor {
+            member: format!("{}.{}", self.this_class_name.as_str(), member.as_str()),
+            descriptor: descriptor.as_str().to_string(),
+        };
+    }
+
+    fn dependency_err(&self, source: ClassLoadErr) -> ClassLoadErr {
+        return ClassLoadErr::Dependency {
+            class: self.this_class_name.as_str().to_string(),
+            source: Box::new(source),
+        };
+    }
+}
+
+/// Reads and checks the class file `bytes` without a VM, the entry point
+/// for fuzzing, see [`ClassParser::parse_scratch`].
+pub fn parse_classfile_bytes(bytes: &[u8]) -> Result<ClassFile, ClassLoadErr> {
+    return ClassParser::parse_scratch(bytes);
+}
+
+/// A class file read and checked by [`ClassParser::parse_scratch`]. The
+/// constants are referred to by their index in `constant_pool`.
+pub struct ClassFile {
+    pub minor_version: u16,
+    pub major_version: u16,
+    /// A scratch copy of the constant pool, index 0 is unusable.
+    pub constant_pool: Vec<Constant>,
+    pub access_flags: u16,
+    /// The Class constant of the class.
+    pub this_class: u16,
+    /// The Class constant of the superclass, 0 only for `java/lang/Object`.
+    pub super_class: u16,
+    /// The Class constants of the superinterfaces.
+    pub interfaces: Vec<u16>,
+    pub fields: Vec<FieldInfo>,
+    pub methods: Vec<MethodInfo>,
+    /// The entries of the `InnerClasses` attribute.
+    pub inner_classes: Vec<InnerClass>,
+    /// The Utf8 constant of the `SourceFile` attribute.
+    pub source_file: Option<u16>,
+}
+
+impl ClassFile {
+    /// The bytes of the Utf8 constant at `index`, in modified UTF-8.
+    pub fn utf8(&self, index: u16) -> Option<&[u8]> {
+        return utf8_constant(&self.constant_pool, index);
+    }
+
+    /// The name of the Class constant at `index`.
+    pub fn class_name(&self, index: u16) -> Option<&[u8]> {
+        return class_constant_name(&self.constant_pool, index);
+    }
+}
+
+/// A constant pool entry of a [`ClassFile`], jvms-4.4.
+#[derive(Clone, Debug)]
+pub enum Constant {
+    /// Index 0 and the slot after a Long or Double.
+    Unusable,
+    /// Checked to be modified UTF-8 that has a UTF-8 form.
+    Utf8(Vec<u8>),
+    Integer(JInt),
+    Float(JFloat),
+    Long(JLong),
+    Double(JDouble),
+    Class(u16),
+    String(u16),
+    Fieldref(u16, u16),
+    Methodref(u16, u16),
+    InterfaceMethodref(u16, u16),
+    NameAndType(u16, u16),
+    MethodHandle(u8, u16),
+    MethodType(u16),
+    InvokeDynamic(u16, u16),
+}
+
+pub struct FieldInfo {
+    pub access_flags: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    /// The constant of the `ConstantValue` attribute, 0 if there is none.
+    pub constval_index: u16,
+}
+
+pub struct MethodInfo {
+    pub access_flags: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    /// The `Code` attribute, `None` for abstract and native methods.
+    pub code: Option<CodeInfo>,
+    /// Whether it is annotated with `@sun.reflect.CallerSensitive`.
+    pub caller_sensitive: bool,
+}
+
+pub struct CodeInfo {
+    pub max_stack: u16,
+    pub max_locals: u16,
+    pub code: Vec<u8>,
+    pub ex_tab: Vec<ExceptionTable>,
+    pub line_tab: Vec<LineNumber>,
+}
+
+pub struct InnerClass {
+    /// The Class constant of the inner class.
+    pub inner_class_info_index: u16,
+    pub access_flags: u16,
+}
+
+fn utf8_constant(cp: &[Constant], index: u16) -> Option<&[u8]> {
+    return match cp.get(usize::from(index)) {
+        Some(Constant::Utf8(bytes)) => Some(bytes),
+        _ => None,
+    };
+}
+
+fn class_constant_name(cp: &[Constant], index: u16) -> Option<&[u8]> {
+    return match cp.get(usize::from(index)) {
+        Some(Constant::Class(name_index)) => utf8_constant(cp, *name_index),
+        _ => None,
+    };
+}
+
+/// Reads a class file into a [`ClassFile`], checking every constant pool
+/// reference it follows, so that [`ClassParser`] can take them as given.
+struct ClassFileReader<'r> {
+    reader: &'r mut dyn ClassReader,
+    cp: Vec<Constant>,
+    /// The name of the class once it is read, for the error messages.
+    this_class_name: String,
+}
+
+impl<'r> ClassFileReader<'r> {
+    fn read(reader: &'r mut dyn ClassReader) -> Result<ClassFile, ClassLoadErr> {
+        let mut class_reader = ClassFileReader {
+            reader,
+            cp: Vec::new(),
+            this_class_name: String::new(),
+        };
+        return class_reader.read_class_file();
+    }
+
+    fn read_class_file(&mut self) -> Result<ClassFile, ClassLoadErr> {
+        let magic = self.reader.read_ubyte4()?;
+        if magic != CLASS_FILE_MAGIC {
+            return Err(ClassLoadErr::BadMagic { found: magic });
+        }
+        let minor_version = self.reader.read_ubyte2()?;
+        let major_version = self.reader.read_ubyte2()?;
+        if !Self::major_version_is_support(major_version) {
+            return Err(ClassLoadErr::UnsupportedVersion {
+                found: major_version,
+                max: MAX_CLASS_FILE_VERSION,
+            });
+        }
+        self.read_constant_pool()?;
+        self.check_constant_pool()?;
+        let access_flags = self.reader.read_ubyte2()?;
+        let this_class = self.reader.read_ubyte2()?;
+        self.this_class_name = String::from_utf8_lossy(self.class_name(this_class)?).into_owned();
+        let super_class = self.reader.read_ubyte2()?;
+        if super_class != 0 {
+            self.class_name(super_class)?;
+        } else if self.this_class_name != "java/lang/Object" {
+            return Err(ClassLoadErr::InvalidFormat(format!(
+                "{} has no superclass",
+                self.this_class_name
+            )));
+        }
+        let interfaces_count = self.reader.read_ubyte2()?;
+        let mut interfaces = Vec::with_capacity(usize::from(interfaces_count));
+        for _ in 0..interfaces_count {
+            let class_index = self.reader.read_ubyte2()?;
+            self.class_name(class_index)?;
+            interfaces.push(class_index);
+        }
+        let fields = self.read_fields()?;
+        let methods = self.read_methods()?;
+        let mut class_file = ClassFile {
+            minor_version,
+            major_version,
+            constant_pool: Vec::new(),
+            access_flags,
+            this_class,
+            super_class,
+            interfaces,
+            fields,
+            methods,
+            inner_classes: Vec::new(),
+            source_file: None,
+        };
+        self.read_class_attrs(&mut class_file)?;
+        if !self.reader.available_bytes().is_empty() {
+            return Err(ClassLoadErr::InvalidFormat(format!(
+                "{}: {} extra bytes at offset {}",
+                self.this_class_name,
+                self.reader.available_bytes().len(),
+                self.reader.offset()
+            )));
+        }
+        class_file.constant_pool = std::mem::take(&mut self.cp);
+        return Ok(class_file);
+    }
+
+    /// jvms-4.4
+    fn read_constant_pool(&mut self) -> Result<(), ClassLoadErr> {
+        let cp_count = self.reader.read_ubyte2()?;
+        if cp_count == 0 {
+            return Err(ClassLoadErr::InvalidFormat(format!(
+                "constant_pool_count is 0 at offset {}",
+                self.reader.offset() - 2
+            )));
+        }
+        // The constant_pool table is indexed from 1 to constant_pool_count - 1.
+        self.cp = Vec::with_capacity(usize::from(cp_count));
+        self.cp.push(Constant::Unusable);
+        while self.cp.len() < usize::from(cp_count) {
+            let index = self.cp.len() as u16;
+            let offset = self.reader.offset();
+            let constant = self.read_constant(index).map_err(|e| match e {
+                ClassLoadErr::Truncated { .. } => ClassLoadErr::TruncatedPool {
+                    index,
+                    source: Box::new(e),
+                },
+                e => e,
+            })?;
+            let wide = matches!(constant, Constant::Long(_) | Constant::Double(_));
+            self.cp.push(constant);
+            if wide {
+                if self.cp.len() == usize::from(cp_count) {
+                    return Err(ClassLoadErr::InvalidFormat(format!(
+                        "the 8-byte constant #{} at offset {} is the last constant",
+                        index, offset
+                    )));
+                }
+                self.cp.push(Constant::Unusable);
+            }
+        }
+        return Ok(());
+    }
+
+    fn read_constant(&mut self, index: u16) -> Result<Constant, ClassLoadErr> {
+        let offset = self.reader.offset();
+        let reader = &mut self.reader;
+        let constant = match reader.read_ubyte1()? {
+            1 => {
+                let length = usize::from(reader.read_ubyte2()?);
+                let bytes = reader.peek_nbytes(length)?.to_vec();
+                if let Err(source) = std::str::from_utf8(&bytes) {
+                    if decode_modified_utf8(&bytes).is_none() {
+                        return Err(ClassLoadErr::InvalidUtf8 { index, source });
+                    }
+                }
+                reader.skip(length);
+                Constant::Utf8(bytes)
+            }
+            3 => Constant::Integer(reader.read_ubyte4()? as JInt),
+            4 => Constant::Float(JFloat::from_bits(reader.read_ubyte4()?)),
+            5 => {
+                let high_bytes = (reader.read_ubyte4()? as u64) << 32;
+                let low_bytes = reader.read_ubyte4()? as u64;
+                Constant::Long((high_bytes | low_bytes) as JLong)
+            }
+            6 => {
+                let high_bytes = (reader.read_ubyte4()? as u64) << 32;
+                let low_bytes = reader.read_ubyte4()? as u64;
+                Constant::Double(JDouble::from_bits(high_bytes | low_bytes))
+            }
+            7 => Constant::Class(reader.read_ubyte2()?),
+            8 => Constant::String(reader.read_ubyte2()?),
+            9 => Constant::Fieldref(reader.read_ubyte2()?, reader.read_ubyte2()?),
+            10 => Constant::Methodref(reader.read_ubyte2()?, reader.read_ubyte2()?),
+            11 => Constant::InterfaceMethodref(reader.read_ubyte2()?, reader.read_ubyte2()?),
+            12 => Constant::NameAndType(reader.read_ubyte2()?, reader.read_ubyte2()?),
+            15 => Constant::MethodHandle(reader.read_ubyte1()?, reader.read_ubyte2()?),
+            16 => Constant::MethodType(reader.read_ubyte2()?),
+            18 => Constant::InvokeDynamic(reader.read_ubyte2()?, reader.read_ubyte2()?),
+            tag => {
+                // the size of the entry is unknown, nothing after it can be read
+                return Err(ClassLoadErr::InvalidFormat(format!(
+                    "unknown constant tag {} of #{} at offset {}",
+                    tag, index, offset
+                )));
+            }
+        };
+        return Ok(constant);
+    }
+
+    /// Checks that every entry refers to entries of the kinds jvms-4.4 asks
+    /// for, the constant pool of the VM relies on it.
+    fn check_constant_pool(&self) -> Result<(), ClassLoadErr> {
+        for index in 1..self.cp.len() as u16 {
+            match self.cp[usize::from(index)] {
+                Constant::Class(name_index) => {
+                    self.utf8(name_index)?;
+                }
+                Constant::String(string_index) => {
+                    self.utf8(string_index)?;
+                }
+                Constant::Fieldref(class_index, name_and_type_index) => {
+                    self.class_name(class_index)?;
+                    self.check_member_ref(name_and_type_index, false)?;
+                }
+                Constant::Methodref(class_index, name_and_type_index)
+                | Constant::InterfaceMethodref(class_index, name_and_type_index) => {
+                    self.class_name(class_index)?;
+                    self.check_member_ref(name_and_type_index, true)?;
+                }
+                Constant::NameAndType(name_index, descriptor_index) => {
+                    self.utf8(name_index)?;
+                    self.utf8(descriptor_index)?;
+                }
+                Constant::MethodHandle(ref_kind, ref_index) => {
+                    let referenced = self.constant(ref_index)?;
+                    let valid = match ref_kind {
+                        1..=4 => matches!(referenced, Constant::Fieldref(..)),
+                        5 | 8 => matches!(referenced, Constant::Methodref(..)),
+                        6 | 7 => matches!(
+                            referenced,
+                            Constant::Methodref(..) | Constant::InterfaceMethodref(..)
+                        ),
+                        9 => matches!(referenced, Constant::InterfaceMethodref(..)),
+                        _ => false,
+                    };
+                    if !valid {
+                        return Err(ClassLoadErr::InvalidFormat(format!(
+                            "invalid method handle #{} of kind {}",
+                            index, ref_kind
+                        )));
+                    }
+                }
+                Constant::MethodType(descriptor_index) => {
+                    let descriptor = self.utf8(descriptor_index)?;
+                    if !descriptor::is_method_descriptor(descriptor) {
+                        return Err(ClassLoadErr::InvalidDescriptor {
+                            member: format!("method type #{}", index),
+                            descriptor: String::from_utf8_lossy(descriptor).into_owned(),
+                        });
+                    }
+                }
+                Constant::InvokeDynamic(_, name_and_type_index) => {
+                    self.check_member_ref(name_and_type_index, true)?;
+                }
+                Constant::Unusable
+                | Constant::Utf8(_)
+                | Constant::Integer(_)
+                | Constant::Float(_)
+                | Constant::Long(_)
+                | Constant::Double(_) => {}
+            }
+        }
+        return Ok(());
+    }
+
+    /// Checks the NameAndType at `index` of a field or method reference.
+    fn check_member_ref(&self, index: u16, method: bool) -> Result<(), ClassLoadErr> {
+        let (name, descriptor) = match self.constant(index)? {
+            Constant::NameAndType(name_index, descriptor_index) => {
+                (self.utf8(*name_index)?, self.utf8(*descriptor_index)?)
+            }
+            _ => return Err(self.wrong_kind(index, "NameAndType")),
+        };
+        let valid_descriptor = if method {
+            descriptor::is_method_descriptor(descriptor)
+        } else {
+            descriptor::is_field_descriptor(descriptor)
+        };
+        if !valid_descriptor {
+            return Err(ClassLoadErr::InvalidDescriptor {
+                member: String::from_utf8_lossy(name).into_owned(),
+                descriptor: String::from_utf8_lossy(descriptor).into_owned(),
+            });
+        }
+        return Ok(());
+    }
+
+    /// Checks the name and the descriptor of a field or method of the class.
+    fn check_member(
+        &self,
+        name_index: u16,
+        descriptor_index: u16,
+        method: bool,
+    ) -> Result<(), ClassLoadErr> {
+        let name = self.utf8(name_index)?;
+        if name.is_empty() {
+            return Err(ClassLoadErr::InvalidFormat(format!(
+                "{}: a member has an empty name",
+                self.this_class_name
+            )));
+        }
+        let descriptor = self.utf8(descriptor_index)?;
+        let valid_descriptor = if method {
+            descriptor::is_method_descriptor(descriptor)
+        } else {
+            descriptor::is_field_descriptor(descriptor)
+        };
+        if !valid_descriptor {
+            return Err(ClassLoadErr::InvalidDescriptor {
+                member: format!("{}.{}", self.this_class_name, String::from_utf8_lossy(name)),
+                descriptor: String::from_utf8_lossy(descriptor).into_owned(),
+            });
+        }
+        return Ok(());
+    }
+
+    fn read_fields(&mut self) -> Result<Vec<FieldInfo>, ClassLoadErr> {
+        let fields_count = self.reader.read_ubyte2()?;
+        let mut fields = Vec::with_capacity(usize::from(fields_count));
+        for _ in 0..fields_count {
+            let access_flags = self.reader.read_ubyte2()?;
+            let name_index = self.reader.read_ubyte2()?;
+            let descriptor_index = self.reader.read_ubyte2()?;
+            self.check_member(name_index, descriptor_index, false)?;
+            let mut constval_index = 0;
+            let attrs_count = self.reader.read_ubyte2()?;
             for _attr_index in 0..attrs_count {
-                let attr_name_index = self.reader.read_ubyte2()?;
-                let attr_name = cp.get_utf8(attr_name_index);
-                let attr_length = self.reader.read_ubyte4()?;
-                let attr_name_str = (*attr_name).as_str();
-                match attr_name_str {
+                let attr = self.read_attr_header()?;
+                match attr.name.as_str() {
                     "ConstantValue" => {
-                        let constval_index = self.reader.read_ubyte2()?;
-                        field.set_constval_index(constval_index);
+                        constval_index = self.reader.read_ubyte2()?;
+                        if FieldAccessFlags::is_static(access_flags) {
+                            self.check_constant_value(constval_index, descriptor_index)?;
+                        }
                     }
                     // "Synthetic" => assert!(attr_length == 0),
                     "Signature" => {
                         self.reader.read_ubyte2()?; // signature_index(ignore)
                     }
                     // "Deprecated" => assert!(attr_length == 0),
-                    "RuntimeVisibleAnnotations" => {
-                        let num_annos = self.reader.read_ubyte2()?;
-                        for _anno_index in 0..num_annos {
-                            self.parse_annotation(cp)?;
-                        }
-                    }
-                    "RuntimeInvisibleAnnotations" => {
+                    "RuntimeVisibleAnnotations" | "RuntimeInvisibleAnnotations" => {
                         let num_annos = self.reader.read_ubyte2()?;
                         for _anno_index in 0..num_annos {
-                            self.parse_annotation(cp)?;
+                            self.read_annotation(0)?;
                         }
                     }
                     _ => {
-                        self.reader.skip(attr_length as usize);
+                        self.reader.skip(attr.length);
                     }
                 }
+                self.end_attr(&attr)?;
             }
-            fields.set(field_index, field.cast());
-            // fields.set_field(field_index as isize, field);
+            fields.push(FieldInfo {
+                access_flags,
+                name_index,
+                descriptor_index,
+                constval_index,
+            });
         }
-        let inst_size = inst_fields_layout.get_aligned_size();
-        let static_fields_size = static_fields_layout.get_aligned_size();
-        Ok(ParsedFields {
-            fields,
-            static_fields_size,
-            inst_size: self
-                .vm
-                .shared_objs()
-                .resize_for_metadata(self.this_class_name, inst_size),
-            metadata_offset: inst_size,
-        })
+        return Ok(fields);
     }
 
-    fn parse_annotation(&mut self, cp: &Handle<ConstantPool>) -> Result<(), ClassLoadErr> {
-        let _type_index = self.reader.read_ubyte2()?;
+    /// A static field is initialized with its `ConstantValue`, which has to
+    /// be a constant of the type of the field.
+    fn check_constant_value(
+        &self,
+        constval_index: u16,
+        descriptor_index: u16,
+    ) -> Result<(), ClassLoadErr> {
+        let valid = matches!(
+            (self.constant(constval_index)?, self.utf8(descriptor_index)?),
+            (Constant::Integer(_), b"B" | b"C" | b"I" | b"S" | b"Z")
+                | (Constant::Float(_), b"F")
+                | (Constant::Long(_), b"J")
+                | (Constant::Double(_), b"D")
+                | (Constant::String(_), b"Ljava/lang/String;")
+        );
+        if !valid {
+            return Err(ClassLoadErr::InvalidFormat(format!(
+                "{}: the ConstantValue #{} does not fit its field",
+                self.this_class_name, constval_index
+            )));
+        }
+        return Ok(());
+    }
+
+    /// Skips an annotation and returns the index of the descriptor of its
+    /// type.
+    fn read_annotation(&mut self, depth: usize) -> Result<u16, ClassLoadErr> {
+        if depth > MAX_ANNOTATION_DEPTH {
+            return Err(ClassLoadErr::InvalidFormat(format!(
+                "{}: annotations nested deeper than {}",
+                self.this_class_name, MAX_ANNOTATION_DEPTH
+            )));
+        }
+        let type_index = self.reader.read_ubyte2()?;
+        self.utf8(type_index)?;
         let num_element_value_pairs = self.reader.read_ubyte2()?;
         for _element_index in 0..num_element_value_pairs {
-            self.parse_annotation_element(cp)?;
+            let _element_name_index = self.reader.read_ubyte2()?;
+            self.read_element_value(depth)?;
         }
-        return Ok(());
+        return Ok(type_index);
     }
 
-    fn parse_annotation_element(&mut self, cp: &Handle<ConstantPool>) -> Result<(), ClassLoadErr> {
-        let _element_name_index = self.reader.read_ubyte2()?;
+    fn read_element_value(&mut self, depth: usize) -> Result<(), ClassLoadErr> {
+        let offset = self.reader.offset();
         let element_tag = self.reader.read_ubyte1()?;
-        match element_tag as char {
-            'B' | 'C' | 'D' | 'F' | 'I' | 'J' | 'S' | 'Z' | 's' => {
+        match element_tag {
+            b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's' => {
                 let _constval_index = self.reader.read_ubyte2()?;
             }
-            'e' => {
+            b'e' => {
                 let _type_name_index = self.reader.read_ubyte2()?;
                 let _const_name_index = self.reader.read_ubyte2()?;
             }
-            'c' => {
+            b'c' => {
                 let class_index = self.reader.read_ubyte2()?;
-                let _class_name = cp.get_utf8(class_index);
+                self.utf8(class_index)?;
             }
-            '@' => {
-                self.parse_annotation(cp)?;
+            b'@' => {
+                self.read_annotation(depth + 1)?;
             }
-            '[' => {
+            b'[' => {
                 let num_values = self.reader.read_ubyte2()?;
                 for _element_index in 0..num_values {
-                    self.parse_annotation_element(cp)?;
+                    self.read_element_value(depth + 1)?;
                 }
             }
             _ => {
-                // TODO: unknown tag
+                return Err(ClassLoadErr::InvalidFormat(format!(
+                    "{}: unknown element_value tag {} at offset {}",
+                    self.this_class_name, element_tag, offset
+                )));
             }
         }
         return Ok(());
     }
 
-    fn parse_methods(
-        &mut self,
-        cp: &Handle<ConstantPool>,
-        init_method: &mut MethodPtr,
-    ) -> Result<Handle<JArray>, ClassLoadErr> {
-        let methods_count = self.reader.read_ubyte2()? as JInt;
-        let thread = Thread::current();
-        let methods = Handle::new(JArray::new_internal_permanent(methods_count, thread));
-        for index in 0..methods_count {
+    fn read_methods(&mut self) -> Result<Vec<MethodInfo>, ClassLoadErr> {
+        let methods_count = self.reader.read_ubyte2()?;
+        let mut methods = Vec::with_capacity(usize::from(methods_count));
+        for _ in 0..methods_count {
             let access_flags = self.reader.read_ubyte2()?;
             let name_index = self.reader.read_ubyte2()?;
-            let name = cp.get_utf8(name_index);
-            debug_assert!(name.as_str().len() > 0);
             let descriptor_index = self.reader.read_ubyte2()?;
-            let descriptor = cp.get_utf8(descriptor_index);
-
-            let mut descriptor_it = DescriptorParser::from_symbol(descriptor, self.vm);
-            if Descriptor::OpenParenthesis != descriptor_it.next() {
-                return Err(ClassLoadErr::InvalidFormat(format!(
-                    "invalid method descriptor: {}, expected '('",
-                    descriptor.as_str()
-                )));
-            }
-            let mut params = Vec::new();
-            let has_close_parenthesis: bool;
-            'parse_descriptor: loop {
-                let param_cls = match descriptor_it.next() {
-                    Descriptor::ResolvedClass(resolved_cls, _) => resolved_cls,
-                    Descriptor::Symbol(symbol, _) => {
-                        if let Some(loaded_cls) = self
-                            .vm
-                            .bootstrap_class_loader
-                            .find_class_with_symbol(symbol)
-                        {
-                            loaded_cls
-                        } else {
-                            symbol.cast()
-                        }
-                    }
-                    Descriptor::CloseParenthesis => {
-                        has_close_parenthesis = true;
-                        break 'parse_descriptor;
-                    }
-                    Descriptor::OpenParenthesis
-                    | Descriptor::InvalidDescriptor
-                    | Descriptor::End => {
-                        return Err(ClassLoadErr::InvalidFormat(format!(
-                            "{}#{} invalid method descriptor: {}",
-                            self.this_class_name.as_str(),
-                            name.as_str(),
-                            descriptor.as_str()
-                        )));
-                    }
-                };
-                debug_assert!(param_cls.is_not_null());
-                params.push(param_cls);
-            }
-            if !has_close_parenthesis {
-                return Err(ClassLoadErr::InvalidFormat(format!(
-                    "invalid method descriptor: {}, expected ')'",
-                    descriptor.as_str()
-                )));
-            }
-            let params = if params.is_empty() {
-                self.vm.shared_objs().empty_sys_arr
-            } else {
-                let method_params =
-                    JArray::new_internal_permanent(params.len() as JInt, Thread::current());
-                for i in 0..params.len() {
-                    unsafe {
-                        method_params.set(i as JInt, params.get_unchecked(i).cast());
-                    }
-                }
-                method_params
-            };
-
-            let (ret_type, ret_descriptor) = match descriptor_it.next() {
-                Descriptor::ResolvedClass(ret_type, _) => (ret_type, descriptor),
-                Descriptor::Symbol(ret_descriptor, _) => (JClassPtr::null(), ret_descriptor),
-                _ => {
-                    return Err(ClassLoadErr::InvalidFormat(format!(
-                        "invalid method descriptor: {}, expected return type",
-                        descriptor.as_str()
-                    )))
-                }
-            };
-            let mut max_stack: u16 = 0;
-            let mut max_locals: u16 = 0;
-            let mut code_length: u16 = 0;
-            let mut code: *const u8 = std::ptr::null();
-            let mut ex_tab = Vec::new();
+            self.check_member(name_index, descriptor_index, true)?;
+            let mut code: Option<CodeInfo> = None;
+            let mut caller_sensitive = false;
 
             let attrs_count = self.reader.read_ubyte2()?;
             for _attr_index in 0..attrs_count {
-                let attr_name_index = self.reader.read_ubyte2()?;
-                if attr_name_index > cp.length() {
-                    return Err(ClassLoadErr::InvalidFormat(
-                        "invalid method attr index".to_string(),
-                    ));
-                }
-                let attr_length = self.reader.read_ubyte4()?;
-                let attr_name = cp.get_utf8(attr_name_index);
-                match attr_name.as_str() {
+                let attr = self.read_attr_header()?;
+                match attr.name.as_str() {
                     "Code" => {
-                        max_stack = self.reader.read_ubyte2()?.try_into().unwrap();
-                        max_locals = self.reader.read_ubyte2()?.try_into().unwrap();
-                        self.parse_code(name, &mut code_length, &mut code)?;
-                        ex_tab = self.parse_ex_tab(cp, name, code_length)?;
-                        let code_attrs_count = self.reader.read_ubyte2()?;
-                        for _code_attr_index in 0..code_attrs_count {
-                            let _code_attr_name_index = self.reader.read_ubyte2()?;
-                            let code_attr_length = self.reader.read_ubyte4()?;
-                            self.reader.skip(code_attr_length as usize); // ignore attrs of the code
+                        if code.is_some() {
+                            return Err(ClassLoadErr::InvalidFormat(format!(
+                                "{}#{}: two Code attributes",
+                                self.this_class_name,
+                                self.utf8_lossy(name_index)
+                            )));
+                        }
+                        code = Some(self.read_code(name_index)?);
+                    }
+                    "RuntimeVisibleAnnotations" => {
+                        let num_annos = self.reader.read_ubyte2()?;
+                        for _anno_index in 0..num_annos {
+                            let anno_type = self.read_annotation(0)?;
+                            if self.utf8(anno_type)? == b"Lsun/reflect/CallerSensitive;" {
+                                caller_sensitive = true;
+                            }
                         }
                     }
                     _ => {
-                        self.reader.skip(attr_length as usize); // ignore all other attrs
+                        self.reader.skip(attr.length); // ignore all other attrs
                     }
                 }
+                self.end_attr(&attr)?;
             }
-
-            let method = Method::new(
+            if let Some(code) = &code {
+                self.check_frame_size(name_index, descriptor_index, access_flags, code.max_locals)?;
+            }
+            methods.push(MethodInfo {
                 access_flags,
-                name,
-                descriptor,
-                params,
-                ret_type,
-                ret_descriptor,
-                max_stack,
-                max_locals,
-                code_length,
+                name_index,
+                descriptor_index,
                 code,
-                &ex_tab,
-                thread,
-            );
-            if name.as_str() == "<clinit>" {
-                *init_method = method;
-            }
-            methods.set(index, method.cast());
+                caller_sensitive,
+            });
         }
         return Ok(methods);
     }
 
-    fn parse_code(
-        &mut self,
-        method_name: SymbolPtr,
-        code_length: &mut u16,
-        code: &mut *const u8,
-    ) -> Result<(), ClassLoadErr> {
+    /// Reads the `Code` attribute of the method `name_index` after its
+    /// header.
+    fn read_code(&mut self, name_index: u16) -> Result<CodeInfo, ClassLoadErr> {
+        let max_stack = self.reader.read_ubyte2()?;
+        let max_locals = self.reader.read_ubyte2()?;
         let code_len = self.reader.read_ubyte4()?;
         if code_len >= 65536 {
             return Err(ClassLoadErr::InvalidFormat(format!(
                 "{}#{}: invalid code_length",
-                self.this_class_name.as_str(),
-                method_name.as_str(),
+                self.this_class_name,
+                self.utf8_lossy(name_index),
             )));
         }
-        assert!(self.reader.readable_length() >= code_len as usize);
-        let code_buf = Ptr::from_raw(self.reader.available_buffer());
-        *code_length = code_len as u16;
-        *code = code_buf.as_raw_ptr();
-
-        self.reader.skip(code_len as usize);
-        Ok(())
+        let code = self.reader.peek_nbytes(code_len as usize)?.to_vec();
+        self.reader.skip(code.len());
+        let ex_tab = self.read_ex_tab(name_index, code_len as u16)?;
+        let mut line_tab = Vec::new();
+        let code_attrs_count = self.reader.read_ubyte2()?;
+        for _code_attr_index in 0..code_attrs_count {
+            let code_attr = self.read_attr_header()?;
+            match code_attr.name.as_str() {
+                "LineNumberTable" => {
+                    let entries_count = self.reader.read_ubyte2()?;
+                    for _entry_index in 0..entries_count {
+                        let start_pc = self.reader.read_ubyte2()?;
+                        let line_number = self.reader.read_ubyte2()?;
+                        line_tab.push(LineNumber::new(start_pc, line_number));
+                    }
+                }
+                _ => self.reader.skip(code_attr.length), // ignore other attrs of the code
+            }
+            self.end_attr(&code_attr)?;
+        }
+        return Ok(CodeInfo {
+            max_stack,
+            max_locals,
+            code,
+            ex_tab,
+            line_tab,
+        });
     }
 
-    fn parse_ex_tab(
+    fn read_ex_tab(
         &mut self,
-        cp: &Handle<ConstantPool>,
-        method_name: SymbolPtr,
+        name_index: u16,
         code_length: u16,
     ) -> Result<Vec<ExceptionTable>, ClassLoadErr> {
         let ex_tab_length = self.reader.read_ubyte2()?;
         let mut result: Vec<ExceptionTable> = Vec::with_capacity(ex_tab_length as usize);
         for _ in 0..ex_tab_length {
             let start_pc = self.reader.read_ubyte2()?;
-            if start_pc >= code_length {
-                return Err(ClassLoadErr::InvalidFormat(format!(
-                    "{}#{}: invalid exception_table",
-                    self.this_class_name.as_str(),
-                    method_name.as_str()
-                )));
-            }
             let end_pc = self.reader.read_ubyte2()?;
-            if end_pc > code_length || start_pc >= end_pc {
-                return Err(ClassLoadErr::InvalidFormat(format!(
-                    "{}#{}: invalid exception_table",
-                    self.this_class_name.as_str(),
-                    method_name.as_str()
-                )));
-            }
             let handler_pc = self.reader.read_ubyte2()?;
-            if handler_pc > code_length {
+            if start_pc >= code_length
+                || end_pc > code_length
+                || start_pc >= end_pc
+                || handler_pc > code_length
+            {
                 return Err(ClassLoadErr::InvalidFormat(format!(
                     "{}#{}: invalid exception_table",
-                    self.this_class_name.as_str(),
-                    method_name.as_str()
+                    self.this_class_name,
+                    self.utf8_lossy(name_index)
                 )));
             }
             let catch_type = self.reader.read_ubyte2()?;
             if catch_type != 0 {
-                cp.get_class_name(catch_type);
+                self.class_name(catch_type)?;
             }
             result.push(ExceptionTable::new(
                 start_pc, end_pc, handler_pc, catch_type,
@@ -633,94 +1172,296 @@ impl<'a> ClassParser<'a> {
         return Ok(result);
     }
 
-    fn parse_class_attrs(
-        &mut self,
-        cp: &Handle<ConstantPool>,
-        mut _class: JClassPtr,
+    /// The arguments, and the receiver of an instance method, are the first
+    /// locals of the frame, which has `max_locals` slots.
+    fn check_frame_size(
+        &self,
+        name_index: u16,
+        descriptor_index: u16,
+        access_flags: u16,
+        max_locals: u16,
     ) -> Result<(), ClassLoadErr> {
+        let receiver_slots = if access_flags & MethodAccessFlags::AccStatic as u16 != 0 {
+            0
+        } else {
+            1
+        };
+        let descriptor = self.utf8(descriptor_index)?;
+        // the descriptor was checked with the method
+        let args_slots = descriptor::args_slots(descriptor).unwrap_or(0) + receiver_slots;
+        if usize::from(max_locals) < args_slots {
+            return Err(ClassLoadErr::Verify(format!(
+                "{}#{}{}: the {} slots of the arguments do not fit into {} locals",
+                self.this_class_name,
+                self.utf8_lossy(name_index),
+                String::from_utf8_lossy(descriptor),
+                args_slots,
+                max_locals
+            )));
+        }
+        return Ok(());
+    }
+
+    fn read_class_attrs(&mut self, class_file: &mut ClassFile) -> Result<(), ClassLoadErr> {
         let class_attrs_count = self.reader.read_ubyte2()?;
-        let thread = Thread::current();
         for _ in 0..class_attrs_count {
-            let attr_name_index = self.reader.read_ubyte2()?;
-            let attr_length = self.reader.read_ubyte4()?;
-            let attr_name = cp.get_utf8(attr_name_index);
-            match attr_name.as_str() {
+            let attr = self.read_attr_header()?;
+            match attr.name.as_str() {
                 "InnerClasses" => {
                     let num_inners = self.reader.read_ubyte2()?;
-                    let inners = JArray::new_internal_permanent(num_inners as JInt, thread);
-                    for inner_idx in 0..num_inners {
+                    for _inner_idx in 0..num_inners {
                         let inner_class_info_index = self.reader.read_ubyte2()?;
                         let _outer_class_info_index = self.reader.read_ubyte2()?;
                         let _inner_name_index = self.reader.read_ubyte2()?;
-                        let _inner_class_access_flags = self.reader.read_ubyte2()?;
-                        inners.set(
-                            inner_idx as JInt,
-                            cp.get_class_name(inner_class_info_index as u16).cast(),
-                        );
+                        let inner_class_access_flags = self.reader.read_ubyte2()?;
+                        self.class_name(inner_class_info_index)?;
+                        class_file.inner_classes.push(InnerClass {
+                            inner_class_info_index,
+                            access_flags: inner_class_access_flags,
+                        });
                     }
-                    // class.set_inners(inners); TODO
-                    continue;
                 }
                 "EnclosingMethod" => {
                     let class_index = self.reader.read_ubyte2()?;
                     let method_index = self.reader.read_ubyte2()?;
+                    self.class_name(class_index)?;
                     if method_index != 0 {
-                        debug_assert_ne!(method_index, 0); // todo:
+                        match self.constant(method_index)? {
+                            Constant::NameAndType(..) => {}
+                            _ => return Err(self.wrong_kind(method_index, "NameAndType")),
+                        }
                     }
-                    debug_assert_ne!(class_index, 0);
-                    continue;
-                }
-                "Synthetic" => {
-                    todo!()
                 }
                 "Signature" => {
                     let signature_index = self.reader.read_ubyte2()?;
-                    let _signature = cp.get_utf8(signature_index);
+                    self.utf8(signature_index)?;
                     // TODO: generic
-                    continue;
                 }
                 "SourceFile" => {
-                    self.reader.skip(attr_length as usize);
-                    continue;
+                    let source_file_index = self.reader.read_ubyte2()?;
+                    self.utf8(source_file_index)?;
+                    class_file.source_file = Some(source_file_index);
                 }
-                "SourceDebugExtension" => {
+                "Synthetic"
+                | "SourceDebugExtension"
+                | "Deprecated"
+                | "RuntimeVisibleAnnotations"
+                | "RuntimeInvisibleAnnotations"
+                | "BootstrapMethods" => {
                     // TODO
+                    self.reader.skip(attr.length);
                 }
-                "Deprecated" => {
-                    // TODO
-                }
-                "RuntimeVisibleAnnotations" => {
-                    // TODO
-                }
-                "RuntimeInvisibleAnnotations" => {
-                    // TODO
-                }
-                "BootstrapMethods" => {}
-                _ => {
-                    return Err(ClassLoadErr::InvalidFormat(format!(
-                        "unsupported attribute: {}",
-                        attr_name.as_str()
-                    )))
-                }
+                _ => return Err(ClassLoadErr::UnsupportedAttribute(attr.name)),
             }
-
-            self.reader.skip(attr_length as usize);
+            self.end_attr(&attr)?;
         }
 
         return Ok(());
     }
 
+    /// Reads the header of an attribute, whose name has to be a Utf8
+    /// constant and whose body has to be within the class file.
+    fn read_attr_header(&mut self) -> Result<AttrHeader, ClassLoadErr> {
+        let offset = self.reader.offset();
+        let name_index = self.reader.read_ubyte2()?;
+        let length = self.reader.read_ubyte4()? as usize;
+        let name = match utf8_constant(&self.cp, name_index) {
+            Some(name) => String::from_utf8_lossy(name).into_owned(),
+            None => {
+                return Err(ClassLoadErr::InvalidFormat(format!(
+                    "the name #{} of the attribute at offset {} is not a Utf8 constant",
+                    name_index, offset
+                )))
+            }
+        };
+        self.reader.peek_nbytes(length)?;
+        return Ok(AttrHeader {
+            name,
+            offset,
+            length,
+            body_offset: self.reader.offset(),
+        });
+    }
+
+    /// Fails unless exactly the body of `attr` was read since its header,
+    /// the rest of the class file would be read from the wrong offset.
+    fn end_attr(&self, attr: &AttrHeader) -> Result<(), ClassLoadErr> {
+        let read = self.reader.offset() - attr.body_offset;
+        if read != attr.length {
+            return Err(ClassLoadErr::InvalidFormat(format!(
+                "{}: the {} attribute at offset {} has {} bytes, {} were read",
+                self.this_class_name, attr.name, attr.offset, attr.length, read
+            )));
+        }
+        return Ok(());
+    }
+
+    fn constant(&self, index: u16) -> Result<&Constant, ClassLoadErr> {
+        return match self.cp.get(usize::from(index)) {
+            Some(Constant::Unusable) | None => Err(ClassLoadErr::InvalidFormat(format!(
+                "invalid constant pool index #{}",
+                index
+            ))),
+            Some(constant) => Ok(constant),
+        };
+    }
+
+    fn utf8(&self, index: u16) -> Result<&[u8], ClassLoadErr> {
+        return utf8_constant(&self.cp, index).ok_or_else(|| self.wrong_kind(index, "Utf8"));
+    }
+
+    /// The Utf8 constant at `index`, which was checked, for a message.
+    fn utf8_lossy(&self, index: u16) -> String {
+        return String::from_utf8_lossy(utf8_constant(&self.cp, index).unwrap_or_default())
+            .into_owned();
+    }
+
+    fn class_name(&self, index: u16) -> Result<&[u8], ClassLoadErr> {
+        return class_constant_name(&self.cp, index).ok_or_else(|| self.wrong_kind(index, "Class"));
+    }
+
+    fn wrong_kind(&self, index: u16, expected: &str) -> ClassLoadErr {
+        return ClassLoadErr::InvalidFormat(format!(
+            "constant #{} is not a {} constant",
+            index, expected
+        ));
+    }
+
     fn major_version_is_support(major_version: u16) -> bool {
         match major_version {
-            _m @ 45..=57 => true,
+            _m @ MIN_CLASS_FILE_VERSION..=MAX_CLASS_FILE_VERSION => true,
             _ => false,
         }
     }
 }
 
+/// The header of an attribute at `offset`, whose body of `length` bytes
+/// starts at `body_offset`.
+struct AttrHeader {
+    name: String,
+    offset: usize,
+    length: usize,
+    body_offset: usize,
+}
+
 struct ParsedFields {
     fields: Handle<JArray>,
     static_fields_size: u16,
     inst_size: u16,
     metadata_offset: u16,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::{parse_classfile_bytes, ClassParser};
+    use crate::{
+        classfile::{reader::OwnedBytesClassReader, ClassLoadErr},
+        test,
+        thread::Thread,
+    };
+
+    fn class_bytes(class_name: &str) -> Vec<u8> {
+        test::ensure_class_exists("./tests/classes", class_name);
+        let path = format!("./tests/classes/{}.class", class_name.replace('.', "/"));
+        return std::fs::read(path).unwrap();
+    }
+
+    #[test]
+    fn parse_runtime_classes() {
+        let jar = std::fs::File::open("./lib/unix_rt.jar").unwrap();
+        let mut archive = zip::ZipArchive::new(jar).unwrap();
+        let mut parsed = 0;
+        let mut unpaired_surrogates = 0;
+        for idx in 0..archive.len() {
+            let mut entry = archive.by_index(idx).unwrap();
+            if !entry.name().ends_with(".class") {
+                continue;
+            }
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes).unwrap();
+            let class = match ClassParser::parse_scratch(&bytes) {
+                Ok(class) => class,
+                // a few encoders keep unpaired surrogates, which symbols cannot hold
+                Err(ClassLoadErr::InvalidUtf8 { .. }) => {
+                    unpaired_surrogates += 1;
+                    continue;
+                }
+                Err(e) => panic!("{}: {}", entry.name(), e),
+            };
+            let name = class.class_name(class.this_class).unwrap();
+            assert_eq!(entry.name().as_bytes(), [name, b".class"].concat());
+            parsed += 1;
+        }
+        assert!(parsed > 1000);
+        assert!(unpaired_surrogates < 10);
+    }
+
+    #[test]
+    fn parse_scratch() {
+        let class = ClassParser::parse_scratch(&class_bytes("rsvm.Disassembly")).unwrap();
+        assert_eq!(
+            Some(&b"rsvm/Disassembly"[..]),
+            class.class_name(class.this_class)
+        );
+        assert_eq!(
+            Some(&b"java/lang/Object"[..]),
+            class.class_name(class.super_class)
+        );
+        assert_eq!(1, class.fields.len());
+        assert_eq!(Some(&b"total"[..]), class.utf8(class.fields[0].name_index));
+        assert_eq!(
+            Some(&b"J"[..]),
+            class.utf8(class.fields[0].descriptor_index)
+        );
+        let method = |name: &[u8]| {
+            return class
+                .methods
+                .iter()
+                .find(|method| class.utf8(method.name_index) == Some(name))
+                .unwrap();
+        };
+        let add = method(b"add");
+        assert_eq!(Some(&b"(II)I"[..]), class.utf8(add.descriptor_index));
+        assert_eq!(4, add.code.as_ref().unwrap().code.len());
+        assert!(method(b"nothing").code.is_none());
+    }
+
+    /// Every prefix of a class file, and every byte of it replaced, has to be
+    /// parsed to a result, `parse_class` included.
+    #[test]
+    fn malformed_classfiles() {
+        let bytes = class_bytes("rsvm.Disassembly");
+        for length in 0..bytes.len() {
+            assert!(parse_classfile_bytes(&bytes[..length]).is_err());
+        }
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert!(matches!(
+            ClassParser::parse_scratch(&extended),
+            Err(ClassLoadErr::InvalidFormat(_))
+        ));
+        test::run_in_vm("./tests/classes", move |vm| {
+            let parse_class = |bytes: Vec<u8>| {
+                let reader = Box::new(OwnedBytesClassReader::new(bytes));
+                let thread = Thread::current();
+                return ClassParser::new(thread.class_loader(), reader, vm.as_ref()).parse_class();
+            };
+            for length in 0..bytes.len() {
+                assert!(parse_class(bytes[..length].to_vec()).is_err());
+            }
+            for idx in 0..bytes.len() {
+                for value in [0x00, 0x01, 0x7f, 0x80, 0xff] {
+                    let mut mutated = bytes.clone();
+                    mutated[idx] = value;
+                    if ClassParser::parse_scratch(&mutated).is_ok() {
+                        let _ = parse_class(mutated);
+                    } else {
+                        assert!(parse_class(mutated).is_err());
+                    }
+                }
+            }
+        });
+    }
+}
@@ -4,9 +4,10 @@ use std::convert::TryInto;
 pub trait ClassReader {
     fn read_ubyte1(&mut self) -> Result<u8, ClassLoadErr> {
         if self.offset() + 1 > self.class_bytes().len() {
-            return Err(ClassLoadErr::InvalidFormat(
-                "out of range, expected 1 byte".to_string(),
-            ));
+            return Err(ClassLoadErr::Truncated {
+                offset: self.offset(),
+                needed: 1,
+            });
         }
         let result = self.class_bytes()[self.offset()];
         self.skip(1);
@@ -15,14 +16,16 @@ pub trait ClassReader {
 
     fn read_ubyte2(&mut self) -> Result<u16, ClassLoadErr> {
         if self.offset() + 2 > self.class_bytes().len() {
-            return Err(ClassLoadErr::InvalidFormat(
-                "out of range, expected 2 bytes".to_string(),
-            ));
+            return Err(ClassLoadErr::Truncated {
+                offset: self.offset(),
+                needed: 2,
+            });
         }
         let bytes: &[u8] = &self.class_bytes()[self.offset()..self.offset() + 2];
-        let bytes: [u8; 2] = bytes
-            .try_into()
-            .map_err(|_| ClassLoadErr::InvalidFormat("cannot read 2 bytes".to_string()))?;
+        let bytes: [u8; 2] = bytes.try_into().map_err(|_| ClassLoadErr::Truncated {
+            offset: self.offset(),
+            needed: 2,
+        })?;
         let result = u16::from_be_bytes(bytes);
         self.skip(2);
         Ok(result)
@@ -30,14 +33,16 @@ pub trait ClassReader {
 
     fn read_ubyte4(&mut self) -> Result<u32, ClassLoadErr> {
         if self.offset() + 4 > self.class_bytes().len() {
-            return Err(ClassLoadErr::InvalidFormat(
-                "out of range, expected 4 bytes".to_string(),
-            ));
+            return Err(ClassLoadErr::Truncated {
+                offset: self.offset(),
+                needed: 4,
+            });
         }
         let bytes: &[u8] = &self.class_bytes()[self.offset()..self.offset() + 4];
-        let bytes: [u8; 4] = bytes
-            .try_into()
-            .map_err(|_| ClassLoadErr::InvalidFormat("cannot read 4 bytes".to_string()))?;
+        let bytes: [u8; 4] = bytes.try_into().map_err(|_| ClassLoadErr::Truncated {
+            offset: self.offset(),
+            needed: 4,
+        })?;
         let result = u32::from_be_bytes(bytes);
         self.skip(4);
         Ok(result)
@@ -45,10 +50,10 @@ pub trait ClassReader {
 
     fn peek_nbytes(&mut self, n: usize) -> Result<&[u8], ClassLoadErr> {
         if self.offset() + n > self.class_bytes().len() {
-            return Err(ClassLoadErr::InvalidFormat(format!(
-                "out of range, expected {} bytes",
-                n
-            )));
+            return Err(ClassLoadErr::Truncated {
+                offset: self.offset(),
+                needed: n,
+            });
         }
         let bytes: &[u8] = &self.class_bytes()[self.offset()..self.offset() + n];
         return Ok(bytes);
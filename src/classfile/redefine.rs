@@ -0,0 +1,144 @@
+//! Method-body-only class redefinition, see
+//! [`VM::redefine_class`](crate::vm::VM::redefine_class).
+//!
+//! A new class file may only change the code of existing methods. The new
+//! methods and their constant pool replace the old ones in the class and in
+//! every vtable, so the next invocation runs the new body. Frames already
+//! running an old method finish it with the old code and pool. Methods are
+//! resolved by name on every invocation, and the static fields resolved by
+//! the old pool stay the same fields, so there is no cached resolution to
+//! invalidate.
+
+use super::ClassLoadErr;
+use crate::object::array::JArrayPtr;
+use crate::object::class::{ClassData, JClass, JClassPtr};
+use crate::object::field::Field;
+use crate::object::method::{Method, MethodPtr};
+use crate::object::prelude::*;
+use crate::thread::ThreadPtr;
+
+/// Gives `class` the methods and constant pool of `redefined`, a class parsed
+/// from a new class file of `class` and never added to the loaded classes.
+pub(crate) fn redefine_methods(
+    class: JClassPtr,
+    redefined: JClassPtr,
+    thread: ThreadPtr,
+) -> Result<(), ClassLoadErr> {
+    let unsupported = |reason| unsupported(class, reason);
+    if class.name() != redefined.name() {
+        return Err(unsupported("the class name differs"));
+    }
+    let class_data = class.class_data();
+    let redefined_data = redefined.class_data();
+    if class_data.super_class() != redefined_data.super_class() {
+        return Err(unsupported("attempted to change the superclass"));
+    }
+    if elements::<JClass>(class_data.interfaces())
+        != elements::<JClass>(redefined_data.interfaces())
+    {
+        return Err(unsupported("attempted to change the interfaces"));
+    }
+    if class_data.access_flags() != redefined_data.access_flags() {
+        return Err(unsupported("attempted to change the class modifiers"));
+    }
+    let fields = elements::<Field>(class_data.fields());
+    let redefined_fields = elements::<Field>(redefined_data.fields());
+    let vm = thread.vm();
+    let same_fields = fields.len() == redefined_fields.len()
+        && fields
+            .iter()
+            .zip(&redefined_fields)
+            .all(|(field, redefined)| {
+                return field.name(vm) == redefined.name(vm)
+                    && field.descriptor(vm) == redefined.descriptor(vm)
+                    && field.access_flags() == redefined.access_flags();
+            });
+    if !same_fields {
+        return Err(unsupported("attempted to change the fields"));
+    }
+    let replacements = match_methods(class, redefined)?;
+
+    // the constant values of the fields are indexes into the new pool
+    for (field, redefined) in fields.iter().zip(&redefined_fields) {
+        field
+            .as_mut_ref()
+            .set_constval_index(redefined.constval_index());
+    }
+    let link = class.is_linked() && !class_data.is_interface();
+    let loaded_classes = thread.vm().bootstrap_class_loader.loaded_classes();
+    let methods = class_data.methods();
+    for (idx, mut method) in replacements {
+        let old: MethodPtr = methods.get(idx).cast();
+        method.as_mut_ref().set_decl_cls(class);
+        if link {
+            // the parameter types are those of the old method, loaded already
+            ClassData::link_method(method, class, thread)
+                .expect("the parameter classes of a linked method are loaded");
+        }
+        if method.is_native() && old.is_registered_native() {
+            method.register_native(old.native_fn());
+        } else if method.is_native() && method.native_fn().is_null() {
+            method.set_native_fn(old.native_fn());
+        }
+        if class_data.init_method() == old {
+            class_data.as_mut_ref().set_init_method(method);
+        }
+        methods.set(idx, method.cast());
+        for loaded_class in &loaded_classes {
+            loaded_class.class_data().replace_vtab_method(old, method);
+        }
+        log::trace!(
+            "redefined {}.{}{}",
+            class.name().as_str(),
+            method.name().as_str(),
+            method.descriptor().as_str()
+        );
+    }
+    class_data.as_mut_ref().cp = redefined_data.cp;
+    return Ok(());
+}
+
+/// Pairs the index of each method of `class` with the method of the same
+/// name and descriptor in `redefined`.
+fn match_methods(
+    class: JClassPtr,
+    redefined: JClassPtr,
+) -> Result<Vec<(JInt, MethodPtr)>, ClassLoadErr> {
+    let unsupported = |reason| unsupported(class, reason);
+    let methods = elements::<Method>(class.class_data().methods());
+    let redefined_methods = elements::<Method>(redefined.class_data().methods());
+    if redefined_methods.len() > methods.len() {
+        return Err(unsupported("attempted to add a method"));
+    }
+    let mut replacements = Vec::with_capacity(methods.len());
+    for (idx, method) in methods.iter().enumerate() {
+        let redefined = redefined_methods.iter().find(|redefined| {
+            return redefined.name() == method.name()
+                && redefined.descriptor() == method.descriptor();
+        });
+        match redefined {
+            Some(redefined) if redefined.access_flags() != method.access_flags() => {
+                return Err(unsupported("attempted to change the method modifiers"));
+            }
+            Some(redefined) => replacements.push((idx as JInt, *redefined)),
+            None => return Err(unsupported("attempted to delete a method")),
+        }
+    }
+    return Ok(replacements);
+}
+
+fn unsupported(class: JClassPtr, reason: &'static str) -> ClassLoadErr {
+    return ClassLoadErr::UnsupportedRedefinition {
+        class: class.binary_name(),
+        reason,
+    };
+}
+
+fn elements<T>(array: JArrayPtr) -> Vec<Ptr<T>> {
+    if array.is_null() {
+        return Vec::new();
+    }
+    return (0..array.length())
+        .map(|idx| array.get(idx).cast())
+        .collect();
+}
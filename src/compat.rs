@@ -0,0 +1,60 @@
+//! Replacements for the unstable library APIs rsvm uses. With the `nightly`
+//! feature they forward to the standard library, otherwise they fall back to
+//! equivalents that build on stable Rust.
+
+use std::sync::atomic::{AtomicI32, AtomicI64, AtomicPtr};
+
+#[cfg(not(feature = "nightly"))]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The address of `ptr`, see `<*const T>::addr`.
+#[inline(always)]
+pub(crate) fn addr<T>(ptr: *const T) -> usize {
+    #[cfg(feature = "nightly")]
+    return ptr.addr();
+    #[cfg(not(feature = "nightly"))]
+    return ptr as usize;
+}
+
+/// See `AtomicI32::from_ptr`.
+#[inline(always)]
+pub(crate) unsafe fn atomic_i32_from_ptr<'a>(ptr: *mut i32) -> &'a AtomicI32 {
+    #[cfg(feature = "nightly")]
+    return AtomicI32::from_ptr(ptr);
+    #[cfg(not(feature = "nightly"))]
+    return &*(ptr as *const AtomicI32);
+}
+
+/// See `AtomicI64::from_ptr`.
+#[inline(always)]
+pub(crate) unsafe fn atomic_i64_from_ptr<'a>(ptr: *mut i64) -> &'a AtomicI64 {
+    #[cfg(feature = "nightly")]
+    return AtomicI64::from_ptr(ptr);
+    #[cfg(not(feature = "nightly"))]
+    return &*(ptr as *const AtomicI64);
+}
+
+/// See `AtomicPtr::from_ptr`.
+#[inline(always)]
+pub(crate) unsafe fn atomic_ptr_from_ptr<'a, T>(ptr: *mut *mut T) -> &'a AtomicPtr<T> {
+    #[cfg(feature = "nightly")]
+    return AtomicPtr::from_ptr(ptr);
+    #[cfg(not(feature = "nightly"))]
+    return &*(ptr as *const AtomicPtr<T>);
+}
+
+/// A unique, non-zero number for the current thread, see
+/// `ThreadId::as_u64`. Without the nightly API each thread takes the next
+/// number on first use.
+pub(crate) fn current_thread_id_value() -> u64 {
+    #[cfg(feature = "nightly")]
+    return std::thread::current().id().as_u64().into();
+    #[cfg(not(feature = "nightly"))]
+    {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        thread_local! {
+            static ID: u64 = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        }
+        return ID.with(|id| *id);
+    }
+}
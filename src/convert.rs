@@ -0,0 +1,446 @@
+//! Conversions between Rust values and [`JValue`]s, driven by the descriptors
+//! of the Java method being called.
+//!
+//! Primitives are boxed when the parameter is a reference type (an `i32`
+//! passed as `java.lang.Object` becomes an `Integer`) and unboxed again on the
+//! way back. Strings and `Vec`s of primitives are copied into new Java objects.
+
+use paste::paste;
+
+use crate::{
+    classfile::descriptor::{Descriptor, DescriptorParser},
+    object::prelude::{JBoolean, JChar},
+    value::JValue,
+    vm::{VMError, VM},
+    ObjectPtr,
+};
+
+pub trait ToJValue {
+    /// Converts `self` into a value of the Java type described by `ty`.
+    fn to_jvalue(self, ty: &str, vm: &VM) -> Result<JValue, VMError>;
+}
+
+pub trait FromJValue: Sized {
+    /// Converts `value`, whose Java type is described by `ty`, into `Self`.
+    fn from_jvalue(value: JValue, ty: &str, vm: &VM) -> Result<Self, VMError>;
+}
+
+/// An argument list, implemented for tuples of up to eight [`ToJValue`]s.
+pub trait JArgs {
+    fn to_jargs(self, param_types: &[&str], vm: &VM) -> Result<Vec<JValue>, VMError>;
+}
+
+/// Element types of the primitive arrays `Vec<T>` converts to and from.
+pub trait ArrayElement: Sized {
+    const ARRAY_DESCRIPTOR: &'static str;
+
+    fn new_array(values: &[Self], vm: &VM) -> ObjectPtr;
+
+    fn get_array(array: ObjectPtr, vm: &VM) -> Vec<Self>;
+}
+
+/// Splits a method descriptor into its parameter and return descriptors, e.g.
+/// `(I[JLjava/lang/String;)V` gives `["I", "[J", "Ljava/lang/String;"]` and `"V"`.
+pub fn split_method_descriptor<'d>(
+    descriptor: &'d str,
+    vm: &VM,
+) -> Result<(Vec<&'d str>, &'d str), VMError> {
+    let invalid = || VMError::RuntimeError(format!("invalid method descriptor {}", descriptor));
+    let mut parser = DescriptorParser::from_bytes(descriptor.as_bytes(), vm);
+    if parser.next() != Descriptor::OpenParenthesis {
+        return Err(invalid());
+    }
+    let mut params = Vec::new();
+    loop {
+        let start = parser.offset();
+        match parser.next() {
+            Descriptor::ResolvedClass(cls, _) if cls == vm.preloaded_classes().void_cls() => {
+                return Err(invalid());
+            }
+            Descriptor::ResolvedClass(..) | Descriptor::Symbol(..) => {
+                params.push(&descriptor[start..parser.offset()]);
+            }
+            Descriptor::CloseParenthesis => break,
+            _ => return Err(invalid()),
+        }
+    }
+    let ret_start = parser.offset();
+    match parser.next() {
+        Descriptor::ResolvedClass(..) | Descriptor::Symbol(..) => {}
+        _ => return Err(invalid()),
+    }
+    let ret = &descriptor[ret_start..parser.offset()];
+    if parser.next() != Descriptor::End {
+        return Err(invalid());
+    }
+    return Ok((params, ret));
+}
+
+fn mismatch(rust_type: &str, ty: &str) -> VMError {
+    return VMError::RuntimeError(format!("cannot convert between {} and {}", rust_type, ty));
+}
+
+fn is_reference(ty: &str) -> bool {
+    return ty.starts_with('L') || ty.starts_with('[');
+}
+
+fn unbox_value(value: JValue, ty: &str, box_cls: &str) -> Result<ObjectPtr, VMError> {
+    let obj = value.obj_val();
+    if obj.is_null() {
+        return Err(VMError::RuntimeError(format!("cannot unbox null {}", ty)));
+    }
+    if obj.jclass().name().as_str() != box_cls {
+        return Err(mismatch(box_cls, obj.jclass().name().as_str()));
+    }
+    return Ok(obj);
+}
+
+macro_rules! primitive_conversions {
+    ($(($rust_type:ty, $val:ident, $desc:expr, $box_cls:expr, $info:ident, $array:ident)),*) => {
+        paste! {
+            $(
+                impl From<$rust_type> for JValue {
+                    fn from(value: $rust_type) -> Self {
+                        return JValue::[<with_ $val>](value);
+                    }
+                }
+
+                impl ToJValue for $rust_type {
+                    fn to_jvalue(self, ty: &str, vm: &VM) -> Result<JValue, VMError> {
+                        if ty == $desc {
+                            return Ok(JValue::from(self));
+                        }
+                        if ty == concat!("L", $box_cls, ";")
+                            || ty == "Ljava/lang/Object;"
+                            || ty == "Ljava/lang/Number;"
+                        {
+                            let info = vm.shared_objs().class_infos().$info();
+                            return Ok(JValue::with_obj_val(info.box_value(JValue::from(self), vm)?));
+                        }
+                        return Err(mismatch(stringify!($rust_type), ty));
+                    }
+                }
+
+                impl FromJValue for $rust_type {
+                    fn from_jvalue(value: JValue, ty: &str, vm: &VM) -> Result<Self, VMError> {
+                        if ty == $desc {
+                            return Ok(value.$val());
+                        }
+                        if is_reference(ty) {
+                            let obj = unbox_value(value, ty, $box_cls)?;
+                            return Ok(vm.shared_objs().class_infos().$info().get_value(obj));
+                        }
+                        return Err(mismatch(stringify!($rust_type), ty));
+                    }
+                }
+
+                impl ArrayElement for $rust_type {
+                    const ARRAY_DESCRIPTOR: &'static str = concat!("[", $desc);
+
+                    fn new_array(values: &[Self], vm: &VM) -> ObjectPtr {
+                        return vm.[<new_ $array _array>](values).cast();
+                    }
+
+                    fn get_array(array: ObjectPtr, vm: &VM) -> Vec<Self> {
+                        return vm.[<get_ $array _array>](array.cast());
+                    }
+                }
+            )*
+        }
+    };
+}
+
+primitive_conversions!(
+    (
+        i8,
+        byte_val,
+        "B",
+        "java/lang/Byte",
+        java_lang_byte_info,
+        byte
+    ),
+    (
+        i16,
+        short_val,
+        "S",
+        "java/lang/Short",
+        java_lang_short_info,
+        short
+    ),
+    (
+        i32,
+        int_val,
+        "I",
+        "java/lang/Integer",
+        java_lang_integer_info,
+        int
+    ),
+    (
+        i64,
+        long_val,
+        "J",
+        "java/lang/Long",
+        java_lang_long_info,
+        long
+    ),
+    (
+        f32,
+        float_val,
+        "F",
+        "java/lang/Float",
+        java_lang_float_info,
+        float
+    ),
+    (
+        f64,
+        double_val,
+        "D",
+        "java/lang/Double",
+        java_lang_double_info,
+        double
+    )
+);
+
+impl From<bool> for JValue {
+    fn from(value: bool) -> Self {
+        return JValue::with_bool_val(value as JBoolean);
+    }
+}
+
+impl ToJValue for bool {
+    fn to_jvalue(self, ty: &str, vm: &VM) -> Result<JValue, VMError> {
+        if ty == "Z" {
+            return Ok(JValue::from(self));
+        }
+        if ty == "Ljava/lang/Boolean;" || ty == "Ljava/lang/Object;" {
+            let info = vm.shared_objs().class_infos().java_lang_boolean_info();
+            return Ok(JValue::with_obj_val(
+                info.box_value(JValue::from(self), vm)?,
+            ));
+        }
+        return Err(mismatch("bool", ty));
+    }
+}
+
+impl FromJValue for bool {
+    fn from_jvalue(value: JValue, ty: &str, vm: &VM) -> Result<Self, VMError> {
+        if ty == "Z" {
+            return Ok(value.bool_val() != 0);
+        }
+        if is_reference(ty) {
+            let obj = unbox_value(value, ty, "java/lang/Boolean")?;
+            let info = vm.shared_objs().class_infos().java_lang_boolean_info();
+            return Ok(info.get_value(obj) != 0);
+        }
+        return Err(mismatch("bool", ty));
+    }
+}
+
+/// Java chars are UTF-16 code units, so only BMP characters convert.
+impl TryFrom<char> for JValue {
+    type Error = VMError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        let mut utf16 = [0u16; 2];
+        if value.encode_utf16(&mut utf16).len() != 1 {
+            return Err(VMError::RuntimeError(format!(
+                "char {:?} is not a single UTF-16 code unit",
+                value
+            )));
+        }
+        return Ok(JValue::with_char_val(utf16[0] as JChar));
+    }
+}
+
+impl ToJValue for char {
+    fn to_jvalue(self, ty: &str, vm: &VM) -> Result<JValue, VMError> {
+        let value = JValue::try_from(self)?;
+        if ty == "C" {
+            return Ok(value);
+        }
+        if ty == "Ljava/lang/Character;" || ty == "Ljava/lang/Object;" {
+            let info = vm.shared_objs().class_infos().java_lang_char_info();
+            return Ok(JValue::with_obj_val(info.box_value(value, vm)?));
+        }
+        return Err(mismatch("char", ty));
+    }
+}
+
+impl FromJValue for char {
+    fn from_jvalue(value: JValue, ty: &str, vm: &VM) -> Result<Self, VMError> {
+        let code_unit = if ty == "C" {
+            value.char_val()
+        } else if is_reference(ty) {
+            let obj = unbox_value(value, ty, "java/lang/Character")?;
+            vm.shared_objs()
+                .class_infos()
+                .java_lang_char_info()
+                .get_value(obj)
+        } else {
+            return Err(mismatch("char", ty));
+        };
+        return char::from_u32(code_unit as u16 as u32)
+            .ok_or_else(|| VMError::RuntimeError(format!("unpaired surrogate {:x}", code_unit)));
+    }
+}
+
+impl ToJValue for &str {
+    fn to_jvalue(self, ty: &str, vm: &VM) -> Result<JValue, VMError> {
+        if ty == "Ljava/lang/String;"
+            || ty == "Ljava/lang/Object;"
+            || ty == "Ljava/lang/CharSequence;"
+        {
+            return Ok(JValue::with_obj_val(vm.new_string(self).cast()));
+        }
+        return Err(mismatch("&str", ty));
+    }
+}
+
+impl ToJValue for String {
+    fn to_jvalue(self, ty: &str, vm: &VM) -> Result<JValue, VMError> {
+        return self.as_str().to_jvalue(ty, vm);
+    }
+}
+
+impl FromJValue for String {
+    fn from_jvalue(value: JValue, ty: &str, vm: &VM) -> Result<Self, VMError> {
+        if !is_reference(ty) {
+            return Err(mismatch("String", ty));
+        }
+        let obj = value.obj_val();
+        if obj.is_null() {
+            return Err(VMError::RuntimeError(format!(
+                "null {} is not a String",
+                ty
+            )));
+        }
+        if obj.jclass().name().as_str() != "java/lang/String" {
+            return Err(mismatch("String", obj.jclass().name().as_str()));
+        }
+        return Ok(vm.get_string(obj.cast()));
+    }
+}
+
+impl<T: ArrayElement> ToJValue for Vec<T> {
+    fn to_jvalue(self, ty: &str, vm: &VM) -> Result<JValue, VMError> {
+        return self.as_slice().to_jvalue(ty, vm);
+    }
+}
+
+impl<T: ArrayElement> ToJValue for &[T] {
+    fn to_jvalue(self, ty: &str, vm: &VM) -> Result<JValue, VMError> {
+        if ty == T::ARRAY_DESCRIPTOR || ty == "Ljava/lang/Object;" {
+            return Ok(JValue::with_obj_val(T::new_array(self, vm)));
+        }
+        return Err(mismatch(T::ARRAY_DESCRIPTOR, ty));
+    }
+}
+
+impl<T: ArrayElement> FromJValue for Vec<T> {
+    fn from_jvalue(value: JValue, ty: &str, vm: &VM) -> Result<Self, VMError> {
+        let array = value.obj_val();
+        if !is_reference(ty) || array.is_null() {
+            return Err(mismatch(T::ARRAY_DESCRIPTOR, ty));
+        }
+        if array.jclass().name().as_str() != T::ARRAY_DESCRIPTOR {
+            return Err(mismatch(
+                T::ARRAY_DESCRIPTOR,
+                array.jclass().name().as_str(),
+            ));
+        }
+        return Ok(T::get_array(array, vm));
+    }
+}
+
+/// `None` converts to and from `null` for reference types.
+impl<T: ToJValue> ToJValue for Option<T> {
+    fn to_jvalue(self, ty: &str, vm: &VM) -> Result<JValue, VMError> {
+        return match self {
+            Some(value) => value.to_jvalue(ty, vm),
+            None if is_reference(ty) => Ok(JValue::with_obj_null()),
+            None => Err(mismatch("None", ty)),
+        };
+    }
+}
+
+impl<T: FromJValue> FromJValue for Option<T> {
+    fn from_jvalue(value: JValue, ty: &str, vm: &VM) -> Result<Self, VMError> {
+        if is_reference(ty) && value.obj_val().is_null() {
+            return Ok(None);
+        }
+        return T::from_jvalue(value, ty, vm).map(Some);
+    }
+}
+
+impl ToJValue for ObjectPtr {
+    fn to_jvalue(self, ty: &str, _vm: &VM) -> Result<JValue, VMError> {
+        if is_reference(ty) {
+            return Ok(JValue::with_obj_val(self));
+        }
+        return Err(mismatch("ObjectPtr", ty));
+    }
+}
+
+impl FromJValue for ObjectPtr {
+    fn from_jvalue(value: JValue, ty: &str, _vm: &VM) -> Result<Self, VMError> {
+        if is_reference(ty) {
+            return Ok(value.obj_val());
+        }
+        return Err(mismatch("ObjectPtr", ty));
+    }
+}
+
+/// Any result, unconverted.
+impl FromJValue for JValue {
+    fn from_jvalue(value: JValue, _ty: &str, _vm: &VM) -> Result<Self, VMError> {
+        return Ok(value);
+    }
+}
+
+impl FromJValue for () {
+    fn from_jvalue(_value: JValue, ty: &str, _vm: &VM) -> Result<Self, VMError> {
+        if ty == "V" {
+            return Ok(());
+        }
+        return Err(mismatch("()", ty));
+    }
+}
+
+macro_rules! tuple_args {
+    ($(($($arg:ident),*)),*) => {
+        $(
+            impl<$($arg: ToJValue),*> JArgs for ($($arg,)*) {
+                #[allow(non_snake_case, unused_mut, unused_variables)]
+                fn to_jargs(self, param_types: &[&str], vm: &VM) -> Result<Vec<JValue>, VMError> {
+                    let ($($arg,)*) = self;
+                    let args_len = <[&str]>::len(&[$(stringify!($arg)),*]);
+                    if args_len != param_types.len() {
+                        return Err(VMError::RuntimeError(format!(
+                            "expected {} arguments, got {}",
+                            param_types.len(),
+                            args_len
+                        )));
+                    }
+                    let mut param_types = param_types.iter();
+                    let mut result = Vec::with_capacity(args_len);
+                    $(
+                        result.push($arg.to_jvalue(param_types.next().unwrap(), vm)?);
+                    )*
+                    return Ok(result);
+                }
+            }
+        )*
+    };
+}
+
+tuple_args!(
+    (),
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H)
+);
@@ -0,0 +1,150 @@
+//! hs_err-style reports written when a VM thread panics, see
+//! `VMConfig::create_crash_reports`.
+
+use std::fs::File;
+use std::io;
+use std::panic::PanicInfo;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::flags;
+use crate::platform;
+use crate::stats::MemoryUsage;
+use crate::thread::{Thread, ThreadPtr};
+
+/// Installs a panic hook writing a crash report for panics on the threads of
+/// a VM with `VMConfig::create_crash_reports`, which then runs the hook
+/// installed before. Only the first call installs the hook.
+pub(crate) fn install_panic_hook() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        let prev_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            report_panic(info);
+            prev_hook(info);
+        }));
+    });
+}
+
+fn report_panic(info: &PanicInfo) {
+    // a panic while writing a report must not write another one
+    static REPORTING: AtomicBool = AtomicBool::new(false);
+    let thread = Thread::current();
+    if thread.is_null()
+        || !thread.vm().cfg.create_crash_reports
+        || REPORTING.swap(true, Ordering::SeqCst)
+    {
+        return;
+    }
+    let mut path = PathBuf::from(thread.vm().cfg.current_dir());
+    path.push(format!("hs_err_pid{}.log", std::process::id()));
+    let written = File::create(&path)
+        .and_then(|mut file| write_crash_report(thread, &info.to_string(), &mut file));
+    match written {
+        Ok(()) => eprintln!(
+            "# An error report file with more information is saved as:\n# {}",
+            path.display()
+        ),
+        Err(e) => eprintln!(
+            "# Failed to write the error report file {}: {}",
+            path.display(),
+            e
+        ),
+    }
+    REPORTING.store(false, Ordering::SeqCst);
+}
+
+/// Writes what went wrong on `thread` along with the state of its VM: the
+/// version, flags, Java stack and recent calls of the thread, the recent VM
+/// events, the heap and the loaded classes.
+pub(crate) fn write_crash_report(
+    thread: ThreadPtr,
+    reason: &str,
+    out: &mut dyn io::Write,
+) -> io::Result<()> {
+    let vm = thread.vm();
+    writeln!(out, "#")?;
+    writeln!(out, "# A fatal error has been detected by rsvm:")?;
+    writeln!(out, "#")?;
+    for line in reason.lines() {
+        writeln!(out, "#  {}", line)?;
+    }
+    writeln!(out, "#")?;
+    writeln!(
+        out,
+        "# VM: {} {} ({} {})",
+        platform::VM_NAME,
+        platform::VM_VERSION,
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )?;
+    writeln!(
+        out,
+        "# Current thread: \"{}\" tid={}",
+        std::thread::current().name().unwrap_or(""),
+        thread.thread_id()
+    )?;
+    writeln!(out, "#")?;
+
+    writeln!(out)?;
+    writeln!(out, "---------------  T H R E A D  ---------------")?;
+    writeln!(out)?;
+    writeln!(out, "Java frames:")?;
+    thread.interpreter().stack().stack_trace(|frame| {
+        let method = frame.method();
+        let _ = writeln!(
+            out,
+            "j  {}.{}{}",
+            method.decl_cls().binary_name(),
+            method.name().as_str(),
+            method.descriptor().as_str()
+        );
+    });
+    writeln!(out)?;
+    writeln!(out, "Last calls (oldest first):")?;
+    for method in thread.interpreter().recent_calls() {
+        writeln!(
+            out,
+            "   {}.{}{}",
+            method.decl_cls().binary_name(),
+            method.name().as_str(),
+            method.descriptor().as_str()
+        )?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "---------------  V M  ---------------")?;
+    writeln!(out)?;
+    flags::print_flags(&vm.cfg, out)?;
+    writeln!(out)?;
+    write_memory_usage(out, "Heap", vm.heap().heap_usage())?;
+    write_memory_usage(out, "Non-heap", vm.heap().non_heap_usage())?;
+    writeln!(out, "Loaded classes: {}", vm.stats.classes_loaded())?;
+    writeln!(out)?;
+    match vm.events.try_events() {
+        Some(events) => {
+            writeln!(out, "Events ({} events):", events.len())?;
+            for event in events {
+                writeln!(
+                    out,
+                    "Event: {:.3}s {}",
+                    event.time.as_secs_f64(),
+                    event.message
+                )?;
+            }
+        }
+        None => writeln!(out, "Events: locked")?,
+    }
+    return Ok(());
+}
+
+fn write_memory_usage(out: &mut dyn io::Write, name: &str, usage: MemoryUsage) -> io::Result<()> {
+    return writeln!(
+        out,
+        "{}: used {}K, committed {}K, max {}K",
+        name,
+        usage.used / 1024,
+        usage.committed / 1024,
+        usage.max / 1024
+    );
+}
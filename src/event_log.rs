@@ -0,0 +1,39 @@
+//! The most recent events of a VM, kept for crash reports.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+/// The number of events an [`EventLog`] keeps, older ones are dropped.
+pub(crate) const EVENT_LOG_CAPACITY: usize = 64;
+
+/// Something that happened in the VM, e.g. a class load or a thread start.
+#[derive(Clone, Debug)]
+pub(crate) struct Event {
+    /// The time since the VM started.
+    pub(crate) time: Duration,
+    pub(crate) message: String,
+}
+
+#[derive(Default)]
+pub(crate) struct EventLog {
+    events: Mutex<VecDeque<Event>>,
+}
+
+impl EventLog {
+    pub(crate) fn log(&self, time: Duration, message: String) {
+        let mut events = self.events.lock();
+        if events.len() == EVENT_LOG_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(Event { time, message });
+    }
+
+    /// The kept events, the oldest first. `None` if the log is locked, it
+    /// does not block as it is read by the crash reporter of a thread that
+    /// may have panicked while logging.
+    pub(crate) fn try_events(&self) -> Option<Vec<Event>> {
+        return Some(self.events.try_lock()?.iter().cloned().collect());
+    }
+}
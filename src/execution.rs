@@ -0,0 +1,188 @@
+//! Cooperative cancellation of the Java code a thread runs, see
+//! [`VM::set_execution_budget`](crate::vm::VM::set_execution_budget) and
+//! [`VM::interrupt_execution`](crate::vm::VM::interrupt_execution).
+//!
+//! The interpreter counts down the instructions it dispatches and polls the
+//! budget and the interrupt flag of its thread when the countdown reaches
+//! zero, every `POLL_INTERVAL` instructions or right where the instruction
+//! budget runs out. A cancelled call unwinds the Java frames of the thread and
+//! fails with [`VMError::Cancelled`](crate::vm::VMError::Cancelled).
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::sandbox::ResourceAccess;
+
+/// The most instructions a thread runs between two polls.
+const POLL_INTERVAL: u32 = 4096;
+
+/// Why the Java code of a thread was cancelled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CancelReason {
+    /// [`VM::interrupt_execution`](crate::vm::VM::interrupt_execution) was
+    /// called for the thread.
+    Interrupted,
+    InstructionBudget,
+    TimeBudget,
+    /// The code started more threads than the
+    /// [`SandboxPolicy`](crate::sandbox::SandboxPolicy) allows.
+    ThreadLimit,
+    /// The code allocated past the heap usage the
+    /// [`SandboxPolicy`](crate::sandbox::SandboxPolicy) allows.
+    HeapLimit,
+    /// The access filter of the [`SandboxPolicy`](crate::sandbox::SandboxPolicy)
+    /// denied a file or socket access.
+    AccessDenied(ResourceAccess),
+    /// The frames of the code reached the limit of the interpreter stack, or
+    /// hit the guard below it.
+    StackOverflow,
+    /// `getfield` or `invokevirtual` faulted on a null object with
+    /// [`VMConfig::implicit_null_checks`](crate::vm::VMConfig::implicit_null_checks).
+    /// Stands in for the `NullPointerException` until the interpreter throws
+    /// exceptions.
+    NullPointer,
+    /// The code referenced a field that its class does not have, or its
+    /// class file was malformed where the interpreter reads it. Stands in
+    /// for the `LinkageError` until the interpreter throws exceptions.
+    LinkageError(String),
+}
+
+impl fmt::Display for CancelReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            CancelReason::Interrupted => f.write_str("interrupted"),
+            CancelReason::InstructionBudget => f.write_str("instruction budget exhausted"),
+            CancelReason::TimeBudget => f.write_str("time budget exhausted"),
+            CancelReason::ThreadLimit => f.write_str("thread limit reached"),
+            CancelReason::HeapLimit => f.write_str("heap limit reached"),
+            CancelReason::AccessDenied(access) => write!(f, "{} denied", access),
+            CancelReason::StackOverflow => f.write_str("stack overflow"),
+            CancelReason::NullPointer => f.write_str("NullPointerException"),
+            CancelReason::LinkageError(msg) => f.write_str(msg),
+        };
+    }
+}
+
+/// Limits on the Java code a thread runs, unlimited by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExecutionBudget {
+    /// The most bytecode instructions the thread may run.
+    pub instructions: Option<u64>,
+    /// The longest the thread may run Java code, counted from when the budget
+    /// is set.
+    pub time: Option<Duration>,
+}
+
+/// The countdown and the budget of the interpreter of a thread.
+pub(crate) struct ExecutionControl {
+    countdown: u32,
+    /// The instructions from the last poll to the one that polls next.
+    interval: u32,
+    instructions_left: Option<u64>,
+    deadline: Option<Instant>,
+    interrupted: AtomicBool,
+    cancelled: Option<CancelReason>,
+}
+
+impl ExecutionControl {
+    pub(crate) fn new() -> Self {
+        return Self {
+            countdown: POLL_INTERVAL,
+            interval: POLL_INTERVAL,
+            instructions_left: None,
+            deadline: None,
+            interrupted: AtomicBool::new(false),
+            cancelled: None,
+        };
+    }
+
+    /// Counts an instruction about to run, whether the interpreter has to
+    /// poll before running it.
+    #[inline(always)]
+    pub(crate) fn tick(&mut self) -> bool {
+        self.countdown -= 1;
+        return self.countdown == 0;
+    }
+
+    /// Charges the instructions since the last poll to the budget, whether
+    /// the code of the thread is cancelled. The instruction that polls only
+    /// runs if it is not.
+    pub(crate) fn poll(&mut self) -> bool {
+        if self.cancelled.is_none() {
+            self.cancelled = self.expired();
+        }
+        self.restart_countdown();
+        return self.cancelled.is_some();
+    }
+
+    fn expired(&mut self) -> Option<CancelReason> {
+        if let Some(left) = self.instructions_left.as_mut() {
+            let executed = u64::from(self.interval);
+            if executed > *left {
+                *left = 0;
+                return Some(CancelReason::InstructionBudget);
+            }
+            *left -= executed;
+        }
+        if self.interrupted.swap(false, Ordering::AcqRel) {
+            return Some(CancelReason::Interrupted);
+        }
+        if self
+            .deadline
+            .map_or(false, |deadline| Instant::now() >= deadline)
+        {
+            return Some(CancelReason::TimeBudget);
+        }
+        return None;
+    }
+
+    /// Polls on the next instruction while cancelled, so that every frame
+    /// unwinds, and at the instruction past the budget otherwise.
+    fn restart_countdown(&mut self) {
+        self.interval = if self.cancelled.is_some() {
+            1
+        } else {
+            match self.instructions_left {
+                Some(left) => left.saturating_add(1).min(u64::from(POLL_INTERVAL)) as u32,
+                None => POLL_INTERVAL,
+            }
+        };
+        self.countdown = self.interval;
+    }
+
+    pub(crate) fn set_budget(&mut self, budget: ExecutionBudget) {
+        self.instructions_left = budget.instructions;
+        self.deadline = budget.time.map(|time| Instant::now() + time);
+        self.restart_countdown();
+    }
+
+    /// Safe to call from any thread, the interpreter notices it at its next
+    /// poll.
+    pub(crate) fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::Release);
+    }
+
+    /// Cancels the code of the thread from the next instruction on, a
+    /// native that cancels it returns first.
+    pub(crate) fn cancel(&mut self, reason: CancelReason) {
+        if self.cancelled.is_none() {
+            self.cancelled = Some(reason);
+        }
+        self.restart_countdown();
+    }
+
+    pub(crate) fn cancelled(&self) -> Option<CancelReason> {
+        self.cancelled.clone()
+    }
+
+    /// Lets the thread run Java code again. A spent budget cancels the next
+    /// call right away until a new budget is set.
+    pub(crate) fn clear_cancelled(&mut self) -> Option<CancelReason> {
+        let cancelled = self.cancelled.take();
+        if cancelled.is_some() {
+            self.restart_countdown();
+        }
+        return cancelled;
+    }
+}
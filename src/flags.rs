@@ -0,0 +1,305 @@
+//! The `-XX` flags of the VM, see [`VMConfig::set_flag`] and
+//! [`VM::print_flags`](crate::vm::VM::print_flags).
+//!
+//! Every flag is a field of [`VMConfig`] registered in [`FLAGS`] with its
+//! name, type, default and description, so the command line, embedders and
+//! `print_flags` all see the same set of toggles.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::memory::{lab, GB, KB, MB};
+use crate::vm::VMConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlagType {
+    /// Switched on by `-XX:+Name` and off by `-XX:-Name`.
+    Bool,
+    /// A byte count set by `-XX:Name=<size>`, optionally with a `k`, `m` or
+    /// `g` suffix.
+    Size,
+}
+
+impl fmt::Display for FlagType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(match self {
+            FlagType::Bool => "bool",
+            FlagType::Size => "size_t",
+        });
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlagValue {
+    Bool(bool),
+    Size(usize),
+}
+
+impl FlagValue {
+    pub fn flag_type(&self) -> FlagType {
+        return match self {
+            FlagValue::Bool(_) => FlagType::Bool,
+            FlagValue::Size(_) => FlagType::Size,
+        };
+    }
+}
+
+impl fmt::Display for FlagValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            FlagValue::Bool(value) => write!(f, "{}", value),
+            FlagValue::Size(value) => write!(f, "{}", value),
+        };
+    }
+}
+
+pub struct Flag {
+    pub name: &'static str,
+    pub default: FlagValue,
+    pub description: &'static str,
+    get: fn(&VMConfig) -> FlagValue,
+    set: fn(&mut VMConfig, FlagValue),
+}
+
+impl Flag {
+    pub fn flag_type(&self) -> FlagType {
+        return self.default.flag_type();
+    }
+
+    /// The value of the flag in `cfg`.
+    pub fn value(&self, cfg: &VMConfig) -> FlagValue {
+        return (self.get)(cfg);
+    }
+
+    pub(crate) fn set_value(&self, cfg: &mut VMConfig, value: FlagValue) {
+        debug_assert_eq!(self.flag_type(), value.flag_type());
+        (self.set)(cfg, value);
+    }
+}
+
+impl fmt::Debug for Flag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f
+            .debug_struct("Flag")
+            .field("name", &self.name)
+            .field("default", &self.default)
+            .finish();
+    }
+}
+
+macro_rules! flags {
+    ($($name:literal => $field:ident: $ty:ident = $default:expr, $description:literal;)*) => {
+        /// All flags, sorted by name.
+        pub static FLAGS: &[Flag] = &[
+            $(Flag {
+                name: $name,
+                default: FlagValue::$ty($default),
+                description: $description,
+                get: |cfg| FlagValue::$ty(cfg.$field),
+                set: |cfg, value| {
+                    if let FlagValue::$ty(value) = value {
+                        cfg.$field = value;
+                    }
+                },
+            },)*
+        ];
+    };
+}
+
+flags! {
+    "CreateCrashReports" => create_crash_reports: Bool = true,
+        "Write an hs_err_pid<pid>.log report when a thread of the VM panics";
+    "DeterministicTables" => deterministic_tables: Bool = false,
+        "Iterate the class, symbol and string tables in insertion order";
+    "ImplicitNullChecks" => implicit_null_checks: Bool = false,
+        "Detect null receivers of getfield and invokevirtual by the page zero fault";
+    "MaxPermSize" => max_perm_size: Size = 256 * MB,
+        "The most memory the space of classes, methods and interned strings may take";
+    "MaxTLABSize" => max_lab_size: Size = lab::DEFAULT_MAX_LAB_SIZE,
+        "The largest local allocation buffer of a thread";
+    "MinTLABSize" => min_lab_size: Size = lab::DEFAULT_MIN_LAB_SIZE,
+        "The smallest local allocation buffer of a thread";
+    "RecordClassLoads" => record_class_loads: Bool = false,
+        "Keep a record of every loaded class in the VM stats";
+    "ShareEmptyArrays" => share_empty_arrays: Bool = false,
+        "Let newarray and anewarray return one shared array for a count of 0";
+    "TLABSize" => lab_size: Size = lab::DEFAULT_LAB_SIZE,
+        "The initial local allocation buffer of a thread";
+    "ThreadStackSize" => stack_size: Size = 2 * MB,
+        "The size of the Java stack of a thread";
+    "TraceClassLoading" => verbose_class: Bool = false,
+        "Print every loaded class and where it was loaded from";
+    "UseLargePages" => large_pages: Bool = false,
+        "Back the heap with huge pages where the OS allows it";
+    "UseNUMAInterleaving" => numa_interleave: Bool = false,
+        "Interleave the heap over the NUMA nodes where the OS allows it";
+}
+
+pub fn find_flag(name: &str) -> Option<&'static Flag> {
+    return FLAGS.iter().find(|flag| flag.name == name);
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FlagError {
+    Unknown(String),
+    /// `-XX:+Name` or `-XX:-Name` for a flag that is not a bool.
+    NotBool(String),
+    /// `-XX:Name` without a value, or with one the flag cannot take.
+    InvalidValue {
+        name: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for FlagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            FlagError::Unknown(name) => write!(f, "unrecognized VM option '{}'", name),
+            FlagError::NotBool(name) => {
+                write!(
+                    f,
+                    "missing '=<value>' for the non-bool VM option '{}'",
+                    name
+                )
+            }
+            FlagError::InvalidValue { name, value } => {
+                write!(f, "improperly specified VM option '{}={}'", name, value)
+            }
+        };
+    }
+}
+
+impl Error for FlagError {}
+
+/// Parses a flag setting `+Name`, `-Name` or `Name=value`, with or without
+/// the `-XX:` prefix.
+pub fn parse_flag(setting: &str) -> Result<(&'static Flag, FlagValue), FlagError> {
+    let setting = setting.strip_prefix("-XX:").unwrap_or(setting);
+    if let Some((name, value)) = setting.split_once('=') {
+        let flag = find_flag(name).ok_or_else(|| FlagError::Unknown(name.to_string()))?;
+        let parsed = match flag.flag_type() {
+            FlagType::Bool => match value {
+                "true" => Some(FlagValue::Bool(true)),
+                "false" => Some(FlagValue::Bool(false)),
+                _ => None,
+            },
+            FlagType::Size => parse_size(value).map(FlagValue::Size),
+        };
+        return match parsed {
+            Some(parsed) => Ok((flag, parsed)),
+            None => Err(FlagError::InvalidValue {
+                name: name.to_string(),
+                value: value.to_string(),
+            }),
+        };
+    }
+    let (name, value) = if let Some(name) = setting.strip_prefix('+') {
+        (name, Some(true))
+    } else if let Some(name) = setting.strip_prefix('-') {
+        (name, Some(false))
+    } else {
+        (setting, None)
+    };
+    let flag = find_flag(name).ok_or_else(|| FlagError::Unknown(name.to_string()))?;
+    if flag.flag_type() != FlagType::Bool {
+        return Err(FlagError::NotBool(name.to_string()));
+    }
+    // a bool flag needs its sign
+    let value = value.ok_or_else(|| FlagError::InvalidValue {
+        name: name.to_string(),
+        value: String::new(),
+    })?;
+    return Ok((flag, FlagValue::Bool(value)));
+}
+
+fn parse_size(value: &str) -> Option<usize> {
+    let (digits, unit) = match value.as_bytes().last()? {
+        b'k' | b'K' => (&value[..value.len() - 1], KB),
+        b'm' | b'M' => (&value[..value.len() - 1], MB),
+        b'g' | b'G' => (&value[..value.len() - 1], GB),
+        _ => (value, 1),
+    };
+    return digits.parse::<usize>().ok()?.checked_mul(unit);
+}
+
+/// Writes every flag with its value in `cfg`, like `-XX:+PrintFlagsFinal`.
+pub fn print_flags(cfg: &VMConfig, out: &mut dyn io::Write) -> io::Result<()> {
+    writeln!(out, "[Global flags]")?;
+    for flag in FLAGS {
+        let value = flag.value(cfg);
+        let origin = if value == flag.default {
+            "{default}"
+        } else {
+            "{set}"
+        };
+        writeln!(
+            out,
+            "{:>7} {:<20} = {:<12} {:<10} {}",
+            flag.flag_type().to_string(),
+            flag.name,
+            value.to_string(),
+            origin,
+            flag.description
+        )?;
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_flag, print_flags, FlagError, FlagValue, FLAGS};
+    use crate::memory::MB;
+    use crate::vm::VMConfig;
+
+    #[test]
+    fn defaults_match_config() {
+        let cfg = VMConfig::default();
+        assert!(FLAGS.windows(2).all(|w| w[0].name < w[1].name));
+        for flag in FLAGS {
+            assert_eq!(flag.default, flag.value(&cfg), "{}", flag.name);
+        }
+    }
+
+    #[test]
+    fn set_flags() {
+        let mut cfg = VMConfig::default();
+        cfg.set_flag("-XX:+ImplicitNullChecks").unwrap();
+        assert!(cfg.implicit_null_checks);
+        cfg.set_flag("+ShareEmptyArrays").unwrap();
+        assert!(cfg.share_empty_arrays);
+        cfg.set_flag("UseLargePages=true").unwrap();
+        assert!(cfg.large_pages);
+        cfg.set_flag("-XX:MaxPermSize=64m").unwrap();
+        assert_eq!(64 * MB, cfg.max_perm_size);
+        cfg.set_flag("TLABSize=8192").unwrap();
+        assert_eq!(Some(FlagValue::Size(8192)), cfg.flag("TLABSize"));
+
+        assert_eq!(
+            Err(FlagError::Unknown("NoSuchFlag".to_string())),
+            parse_flag("+NoSuchFlag").map(|(_, value)| value)
+        );
+        assert_eq!(
+            Err(FlagError::NotBool("MaxPermSize".to_string())),
+            parse_flag("-XX:+MaxPermSize").map(|(_, value)| value)
+        );
+        assert!(parse_flag("MaxPermSize=lots").is_err());
+        assert!(parse_flag("ImplicitNullChecks").is_err());
+        assert!(parse_flag("ImplicitNullChecks=1").is_err());
+    }
+
+    #[test]
+    fn print_all_flags() {
+        let mut cfg = VMConfig::default();
+        cfg.set_flag("+TraceClassLoading").unwrap();
+        let mut out = Vec::new();
+        print_flags(&cfg, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(FLAGS.len() + 1, out.lines().count());
+        let line = out
+            .lines()
+            .find(|line| line.contains("TraceClassLoading"))
+            .unwrap();
+        assert!(line.contains("= true") && line.contains("{set}"));
+    }
+}
@@ -1,22 +1,32 @@
-#![feature(hash_set_entry)]
-#![feature(get_mut_unchecked)]
-#![feature(new_uninit)]
-#![feature(slice_as_chunks)]
-#![feature(thread_id_value)]
-#![feature(atomic_from_ptr)]
-#![feature(strict_provenance)]
+#![cfg_attr(feature = "nightly", feature(thread_id_value))]
+#![cfg_attr(feature = "nightly", feature(atomic_from_ptr))]
+#![cfg_attr(feature = "nightly", feature(strict_provenance))]
+
+#[cfg(not(target_pointer_width = "64"))]
+compile_error!("rsvm only supports 64-bit targets, long and double accesses assume 8-byte words");
 
 pub use object::prelude::{JArray, JClassPtr, ObjectPtr};
 
 pub mod classfile;
+mod compat;
+pub mod convert;
+mod crash_report;
+mod event_log;
+pub mod execution;
+pub mod flags;
 mod gc;
 mod handle;
 mod memory;
 mod native;
 mod object;
 mod os;
+pub mod platform;
+pub mod proxy;
 mod runtime;
+pub mod sampling;
+pub mod sandbox;
 mod shared;
+pub mod stats;
 pub mod thread;
 mod utils;
 pub mod value;
@@ -1,5 +1,12 @@
-use super::space::{SemiSpace, Space};
-use super::{Address, MB};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use super::lab::LocalAllocBuf;
+use super::space::{ChunkedSpace, SemiSpace, Space};
+use super::{Address, MB, POINTER_SIZE};
+use crate::execution::CancelReason;
 use crate::memory::space::SpaceType;
 use crate::object::array::JArrayPtr;
 use crate::object::class::{ClassData, JClass};
@@ -8,6 +15,8 @@ use crate::object::method::MethodPtr;
 use crate::object::prelude::Ptr;
 use crate::object::symbol::SymbolPtr;
 use crate::object::Object;
+use crate::sampling::{AllocationSample, AllocationSampler};
+use crate::stats::MemoryUsage;
 use crate::thread::{Thread, ThreadPtr};
 use crate::{os, JClassPtr, ObjectPtr};
 
@@ -25,33 +34,36 @@ pub struct GCStats {
 pub struct Heap {
     new_space: SemiSpace,
     old_space: Space,
-    perm_space: Space,
+    perm_space: ChunkedSpace,
     code_space: Space,
+    lab_refills: AtomicUsize,
+    lab_waste: AtomicUsize,
+    sampler: RwLock<Option<Arc<AllocationSampler>>>,
     // lo_space: Space,
 }
 
 impl Heap {
-    pub fn new() -> Heap {
+    /// `hints` apply to the data spaces, the code space is always reserved
+    /// with plain pages. The permanent space grows in chunks up to
+    /// `max_perm_size`.
+    pub fn new(hints: os::MemoryHints, max_perm_size: usize) -> Heap {
         let survivor_space_size = 16 * MB;
         let old_space_size = 32 * MB;
-        let perm_space_size = 8 * MB;
+        let perm_chunk_size = 8 * MB;
         let code_space_size = 8 * MB;
         // let lo_space_size = 32 * MB;
 
-        let new_space =
-            SemiSpace::new(os::reserve_memory(survivor_space_size), survivor_space_size);
+        let new_space = SemiSpace::new(
+            os::reserve_memory_with_hints(survivor_space_size, hints),
+            survivor_space_size,
+        );
         let old_space = Space::new(
             SpaceType::OLD,
-            os::reserve_memory(old_space_size),
+            os::reserve_memory_with_hints(old_space_size, hints),
             old_space_size,
             false,
         );
-        let perm_space = Space::new(
-            SpaceType::PERM,
-            os::reserve_memory(perm_space_size),
-            perm_space_size,
-            false,
-        );
+        let perm_space = ChunkedSpace::new(SpaceType::PERM, perm_chunk_size, max_perm_size, hints);
         let code_space = Space::new(
             SpaceType::CODE,
             os::reserve_memory(code_space_size),
@@ -64,6 +76,9 @@ impl Heap {
             old_space,
             perm_space,
             code_space,
+            lab_refills: AtomicUsize::new(0),
+            lab_waste: AtomicUsize::new(0),
+            sampler: RwLock::new(None),
             // lo_space: Space::new(os::reserve_memory(lo_space_size), lo_space_size, false),
         };
     }
@@ -78,13 +93,36 @@ impl Heap {
             self.new_space.end(),
             &self.old_space as *const Space,
             self.old_space.start(),
-            &self.perm_space as *const Space,
+            &self.perm_space as *const ChunkedSpace,
             self.perm_space.start(),
             &self.code_space as *const Space,
             self.code_space.start()
         );
     }
 
+    /// The usage of the spaces of Java objects, the permanent space aside.
+    pub(crate) fn heap_usage(&self) -> MemoryUsage {
+        let size = self.new_space.size() + self.old_space.size();
+        return MemoryUsage {
+            init: size,
+            used: self.new_space.used() + self.old_space.used(),
+            committed: size,
+            max: size,
+        };
+    }
+
+    /// The usage of the permanent space, which grows by chunks, and the code
+    /// space.
+    pub(crate) fn non_heap_usage(&self) -> MemoryUsage {
+        let code_size = self.code_space.size();
+        return MemoryUsage {
+            init: self.perm_space.chunk_size() + code_size,
+            used: self.perm_space.used() + self.code_space.used(),
+            committed: self.perm_space.size() + code_size,
+            max: self.perm_space.max_size() + code_size,
+        };
+    }
+
     pub fn destroy(&self) {
         self.new_space.destroy();
         self.old_space.destroy();
@@ -154,7 +192,15 @@ impl Heap {
 
     pub fn alloc_obj_permanent(&self, size: usize) -> Address {
         assert!(super::is_align_of(size, super::POINTER_SIZE));
-        return self.perm_space.alloc(size);
+        let result = self.perm_space.alloc(size);
+        if result.is_null() {
+            todo!(
+                "throw OutOfMemoryError: Metaspace, {} bytes requested, the permanent space is capped at {} bytes",
+                size,
+                self.perm_space.max_size()
+            );
+        }
+        return result;
     }
 
     pub fn heap_contains(&self, addr: Address) -> bool {
@@ -169,7 +215,7 @@ impl Heap {
             log::trace!(
                 "perm_contains false {:x}, {:x}, {:x}",
                 self.perm_space.start().as_usize(),
-                self.perm_space.end().as_usize(),
+                self.perm_space.size(),
                 addr.as_usize()
             );
         }
@@ -188,29 +234,149 @@ impl Heap {
         return self.new_space.contains(addr);
     }
 
+    /// Calls `visitor` with every Java object allocated in the new and old
+    /// spaces, in address order. Other threads must not allocate meanwhile.
+    ///
+    /// The permanent space is skipped, it mixes objects with VM metadata that
+    /// carries no header.
+    pub fn iterate_objects<F: FnMut(ObjectPtr)>(&self, mut visitor: F) {
+        Self::iterate_space_objects(self.new_space.start(), self.new_space.top(), &mut visitor);
+        Self::iterate_space_objects(self.old_space.start(), self.old_space.top(), &mut visitor);
+    }
+
+    fn iterate_space_objects<F: FnMut(ObjectPtr)>(start: Address, top: Address, visitor: &mut F) {
+        let mut addr = start;
+        while addr < top {
+            let obj = ObjectPtr::from_addr(addr);
+            if obj.jclass().is_null() {
+                // the zeroed, unused tail of a local allocation buffer
+                addr = addr.uoffset(POINTER_SIZE);
+                continue;
+            }
+            let size = obj.heap_size();
+            debug_assert!(super::is_align_of(size, POINTER_SIZE));
+            visitor(obj);
+            addr = addr.uoffset(size);
+        }
+        debug_assert_eq!(addr.as_usize(), top.as_usize());
+    }
+
     pub fn alloc_code(&self, size: usize) -> Address {
         return self.code_space.alloc(size);
     }
 
-    pub fn alloc_obj_lab(size: usize, thread: ThreadPtr) -> Address {
-        let heap = thread.heap();
-        let lab_capacity = thread.lab().capacity();
-        if size > thread.lab().capacity() {
-            return heap.new_space.alloc(size);
-        }
+    pub fn alloc_obj_lab(jclass: JClassPtr, size: usize, thread: ThreadPtr) -> Address {
         let result = Self::alloc_obj_lab_internal(size, thread);
         if result.is_not_null() {
             return result;
         }
-        let buf = heap.new_space.alloc(lab_capacity);
+        return Self::alloc_obj_lab_slow(jclass, size, thread);
+    }
+
+    /// Reached when the local allocation buffer is used up, `size` exceeds its
+    /// capacity, or the allocation crosses the thread's next sample point.
+    fn alloc_obj_lab_slow(jclass: JClassPtr, size: usize, thread: ThreadPtr) -> Address {
+        let heap = thread.heap();
+        let sampler = heap.sampler.read().clone();
+        let lab = thread.as_mut_ref().lab_mut();
+        lab.count_allocated();
+        match &sampler {
+            Some(sampler) if lab.sample_countdown() == usize::MAX => {
+                lab.set_sample_countdown(sampler.interval());
+            }
+            None if lab.sample_countdown() != usize::MAX => {
+                lab.set_sample_countdown(usize::MAX);
+            }
+            _ => {}
+        }
+        let countdown = lab.sample_countdown();
+
+        let result = if size <= lab.remaining() {
+            let result = lab.free();
+            lab.set_free(result.uoffset(size));
+            result
+        } else if size > lab.capacity() {
+            heap.alloc_obj(size, thread)
+        } else {
+            heap.refill_lab(size, thread)
+        };
+
+        if let Some(sampler) = sampler {
+            let lab = thread.as_mut_ref().lab_mut();
+            if size >= countdown {
+                lab.set_sample_countdown(sampler.interval());
+                Self::report_allocation_sample(&sampler, jclass, size, thread);
+            } else {
+                lab.set_sample_countdown(countdown - size);
+            }
+        }
+        return result;
+    }
+
+    /// Without a refill when the buffer would cross the heap usage the
+    /// sandbox allows, so that the cap is checked against the object itself.
+    fn refill_lab(&self, size: usize, thread: ThreadPtr) -> Address {
+        let lab = thread.as_mut_ref().lab_mut();
+        let lab_capacity = lab.adapt_capacity().max(size);
+        if !self.within_heap_cap(lab_capacity, thread) {
+            return self.alloc_obj(size, thread);
+        }
+        let buf = self.alloc_obj_internal(lab_capacity);
         if buf.is_not_null() {
-            let buf_limit = buf.uoffset(lab_capacity);
-            thread.as_mut_ref().lab_mut().new_buf(buf, buf_limit);
-            let result = Self::alloc_obj_lab_internal(size, thread);
-            debug_assert!(result.is_not_null());
+            self.lab_refills.fetch_add(1, Ordering::Relaxed);
+            self.retire_lab(lab);
+            let buf_end = buf.uoffset(lab_capacity);
+            lab.new_buf(buf, buf_end);
+            let result = lab.free();
+            lab.set_free(result.uoffset(size));
             return result;
         }
-        return heap.alloc_obj(size);
+        return self.alloc_obj(size, thread);
+    }
+
+    fn report_allocation_sample(
+        sampler: &AllocationSampler,
+        jclass: JClassPtr,
+        size: usize,
+        thread: ThreadPtr,
+    ) {
+        let mut stack = Vec::new();
+        thread.interpreter().stack().stack_trace(|frame| {
+            stack.push(format!(
+                "{}#{}",
+                frame.class().binary_name(),
+                frame.method().name().as_str()
+            ));
+        });
+        sampler.report(&AllocationSample {
+            class_name: jclass.binary_name(),
+            size,
+            thread_id: thread.thread_id(),
+            stack,
+        });
+    }
+
+    /// Replaces the allocation sampler, threads pick up the change the next
+    /// time they leave the allocation fast path.
+    pub(crate) fn set_allocation_sampler(&self, sampler: Option<AllocationSampler>) {
+        *self.sampler.write() = sampler.map(Arc::new);
+    }
+
+    /// Accounts the unused tail of `lab` as waste, before it is refilled or
+    /// its thread exits. The tail stays zeroed, which is how
+    /// `iterate_objects` skips it.
+    pub(crate) fn retire_lab(&self, lab: &LocalAllocBuf) {
+        self.lab_waste.fetch_add(lab.remaining(), Ordering::Relaxed);
+    }
+
+    /// The number of local allocation buffers handed out to threads.
+    pub fn lab_refills(&self) -> usize {
+        return self.lab_refills.load(Ordering::Relaxed);
+    }
+
+    /// The bytes left unused at the end of retired local allocation buffers.
+    pub fn lab_waste(&self) -> usize {
+        return self.lab_waste.load(Ordering::Relaxed);
     }
 
     fn alloc_obj_lab_internal(size: usize, thread: ThreadPtr) -> Address {
@@ -223,8 +389,16 @@ impl Heap {
         return Address::null();
     }
 
-    fn alloc_obj(&self, size: usize) -> Address {
+    /// An allocation past the heap usage the sandbox allows cancels the code
+    /// of `thread`, the allocation itself still succeeds.
+    fn alloc_obj(&self, size: usize, thread: ThreadPtr) -> Address {
         assert!(super::is_align_of(size, super::POINTER_SIZE));
+        if !self.within_heap_cap(size, thread) {
+            thread
+                .as_mut_ref()
+                .interpreter_mut()
+                .cancel_execution(CancelReason::HeapLimit);
+        }
         let mut result = self.alloc_obj_internal(size);
         if result.is_null() {
             self.minor_gc();
@@ -237,6 +411,14 @@ impl Heap {
         return result;
     }
 
+    fn within_heap_cap(&self, size: usize, thread: ThreadPtr) -> bool {
+        return thread
+            .vm()
+            .cfg
+            .sandbox
+            .allows_heap_usage(self.new_space.used(), size);
+    }
+
     fn alloc_obj_internal(&self, size: usize) -> Address {
         self.new_space.alloc(size)
     }
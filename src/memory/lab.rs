@@ -1,14 +1,49 @@
-use super::{Address, KB};
+use std::time::{Duration, Instant};
+
+use super::{align, Address, KB, POINTER_SIZE};
+
+pub(crate) const DEFAULT_LAB_SIZE: usize = 4 * KB;
+pub(crate) const DEFAULT_MIN_LAB_SIZE: usize = 1 * KB;
+pub(crate) const DEFAULT_MAX_LAB_SIZE: usize = 64 * KB;
+
+/// How long a buffer should last at the thread's allocation rate. Faster
+/// allocating threads get larger buffers and take the new space lock less
+/// often, idle threads give memory back by getting smaller ones.
+const TARGET_REFILL_INTERVAL: Duration = Duration::from_millis(10);
 
 pub struct LocalAllocBuf {
+    start: Address,
     free: Address,
+    /// Where the fast path stops, `end` or the next allocation sample point
+    /// when it falls inside the buffer.
     limit: Address,
+    end: Address,
+    /// The bytes to allocate until the next sample, counted from
+    /// `sample_base`. `usize::MAX` while allocation sampling is off.
+    sample_countdown: usize,
+    sample_base: Address,
     capacity: usize,
+    min_capacity: usize,
+    max_capacity: usize,
+    refilled_at: Option<Instant>,
 }
 
 impl LocalAllocBuf {
-    pub fn new(free: Address, limit: Address) -> Self {
-        return Self { free, limit, capacity: 1 * KB };
+    pub fn new(capacity: usize, min_capacity: usize, max_capacity: usize) -> Self {
+        let min_capacity = align(min_capacity);
+        let max_capacity = align(max_capacity).max(min_capacity);
+        return Self {
+            start: Address::null(),
+            free: Address::null(),
+            limit: Address::null(),
+            end: Address::null(),
+            sample_countdown: usize::MAX,
+            sample_base: Address::null(),
+            capacity: align(capacity).clamp(min_capacity, max_capacity),
+            min_capacity,
+            max_capacity,
+            refilled_at: None,
+        };
     }
 
     pub fn free(&self) -> Address {
@@ -19,22 +54,149 @@ impl LocalAllocBuf {
         self.free = free;
     }
 
-    pub fn new_buf(&mut self, free: Address, limit: Address) {
+    pub fn new_buf(&mut self, free: Address, end: Address) {
+        self.count_allocated();
+        self.start = free;
         self.free = free;
-        self.limit = limit;
+        self.end = end;
+        self.reset_limit();
     }
 
     pub fn capacity(&self) -> usize {
         self.capacity
     }
 
+    /// The bytes the fast path may still hand out.
     pub fn available(&self) -> usize {
         return self.limit.as_usize() - self.free.as_usize();
     }
+
+    /// The bytes left in the buffer, including those past a sample point.
+    pub fn remaining(&self) -> usize {
+        return self.end.as_usize() - self.free.as_usize();
+    }
+
+    pub fn sample_countdown(&self) -> usize {
+        self.sample_countdown
+    }
+
+    /// Restarts the countdown to the next allocation sample from the current
+    /// free pointer, `usize::MAX` turns sampling off.
+    pub fn set_sample_countdown(&mut self, countdown: usize) {
+        self.sample_countdown = countdown;
+        self.reset_limit();
+    }
+
+    /// Takes the bytes allocated since the last call off the sample
+    /// countdown.
+    pub fn count_allocated(&mut self) {
+        if self.sample_countdown != usize::MAX {
+            let allocated = self.free.as_usize() - self.sample_base.as_usize();
+            self.sample_countdown = self.sample_countdown.saturating_sub(allocated);
+        }
+        self.sample_base = self.free;
+    }
+
+    fn reset_limit(&mut self) {
+        self.sample_base = self.free;
+        self.limit = if self.sample_countdown < self.remaining() {
+            self.free.uoffset(self.sample_countdown)
+        } else {
+            self.end
+        };
+    }
+
+    /// Sets the capacity of the next buffer from the bytes allocated out of
+    /// the current one and the time it took, averaged with the current
+    /// capacity to damp bursts.
+    pub fn adapt_capacity(&mut self) -> usize {
+        return self.adapt_capacity_at(Instant::now());
+    }
+
+    /// `adapt_capacity` for a buffer refilled at `now`.
+    fn adapt_capacity_at(&mut self, now: Instant) -> usize {
+        if let Some(refilled_at) = self.refilled_at {
+            let used = (self.free.as_usize() - self.start.as_usize()) as u128;
+            let elapsed = now.duration_since(refilled_at).as_nanos().max(1);
+            let desired = used * TARGET_REFILL_INTERVAL.as_nanos() / elapsed;
+            let desired = align(desired.min(self.max_capacity as u128) as usize);
+            let desired = desired.max(self.min_capacity);
+            let average = (self.capacity + desired) / 2;
+            // round towards `desired` so that the capacity can reach it
+            self.capacity = if desired > self.capacity {
+                align(average)
+            } else {
+                average & !(POINTER_SIZE - 1)
+            };
+        }
+        self.refilled_at = Some(now);
+        return self.capacity;
+    }
 }
 
 impl Default for LocalAllocBuf {
     fn default() -> Self {
-        Self::new(Address::null(), Address::null())
+        Self::new(DEFAULT_LAB_SIZE, DEFAULT_MIN_LAB_SIZE, DEFAULT_MAX_LAB_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::LocalAllocBuf;
+    use crate::memory::{Address, KB};
+
+    fn use_up(lab: &mut LocalAllocBuf, used: usize) {
+        let start = Address::from_usize(0x10000);
+        lab.new_buf(start, start.uoffset(lab.capacity()));
+        lab.set_free(start.uoffset(used));
+    }
+
+    #[test]
+    fn capacity_follows_allocation_rate() {
+        let mut lab = LocalAllocBuf::new(4 * KB, 1 * KB, 64 * KB);
+        let mut now = Instant::now();
+        assert_eq!(4 * KB, lab.adapt_capacity_at(now));
+
+        // filled up in a tenth of the target interval, grows halfway to 40 KB
+        use_up(&mut lab, 4 * KB);
+        now += Duration::from_millis(1);
+        let grown = lab.adapt_capacity_at(now);
+        assert_eq!(22 * KB, grown);
+
+        // barely used, shrinks towards the minimum
+        use_up(&mut lab, 8);
+        now += Duration::from_millis(20);
+        assert_eq!((grown + 1 * KB) / 2, lab.adapt_capacity_at(now));
+        for _ in 0..16 {
+            use_up(&mut lab, 0);
+            now += Duration::from_millis(10);
+            lab.adapt_capacity_at(now);
+        }
+        assert_eq!(1 * KB, lab.capacity());
+    }
+
+    #[test]
+    fn sample_point_limits_fast_path() {
+        let mut lab = LocalAllocBuf::new(4 * KB, 1 * KB, 64 * KB);
+        let start = Address::from_usize(0x10000);
+        lab.new_buf(start, start.uoffset(4 * KB));
+        assert_eq!(4 * KB, lab.available());
+
+        lab.set_free(start.uoffset(1 * KB));
+        lab.set_sample_countdown(2 * KB);
+        assert_eq!(2 * KB, lab.available());
+        assert_eq!(3 * KB, lab.remaining());
+
+        // the countdown carries over into the next buffer
+        lab.set_free(start.uoffset(2 * KB));
+        let next = Address::from_usize(0x20000);
+        lab.new_buf(next, next.uoffset(4 * KB));
+        assert_eq!(1 * KB, lab.sample_countdown());
+        assert_eq!(1 * KB, lab.available());
+
+        lab.set_sample_countdown(usize::MAX);
+        assert_eq!(4 * KB, lab.available());
     }
 }
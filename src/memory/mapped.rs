@@ -0,0 +1,54 @@
+//! Files mapped into memory by `FileChannel.map`.
+
+use std::{collections::HashMap, fs::File, io};
+
+use parking_lot::Mutex;
+
+use super::Address;
+use crate::os::{self, MapMode};
+
+/// The live mappings of `FileChannel.map`. The `Cleaner` of a mapped buffer
+/// releases its mapping through `FileChannelImpl.unmap0`. Buffers are never
+/// collected, so [`unmap_all`](Self::unmap_all) releases the mappings left
+/// when the VM is destroyed.
+#[derive(Default)]
+pub(crate) struct MappedFiles {
+    /// The size of every mapping by its address.
+    mappings: Mutex<HashMap<usize, usize>>,
+}
+
+impl MappedFiles {
+    pub(crate) fn map(
+        &self,
+        file: &File,
+        offset: u64,
+        size: usize,
+        mode: MapMode,
+    ) -> io::Result<Address> {
+        let addr = os::map_file(file, offset, size, mode)?;
+        self.mappings.lock().insert(addr.as_usize(), size);
+        log::trace!("mapped {} bytes at {:?}", size, addr);
+        return Ok(addr);
+    }
+
+    pub(crate) fn unmap(&self, addr: Address, size: usize) -> io::Result<()> {
+        let mapped = self.mappings.lock().remove(&addr.as_usize());
+        debug_assert_eq!(Some(size), mapped, "unmapping {:?}", addr);
+        log::trace!("unmapping {} bytes at {:?}", size, addr);
+        return os::unmap_file(addr, size);
+    }
+
+    /// The bytes of all live mappings.
+    #[cfg(test)]
+    pub(crate) fn mapped_bytes(&self) -> usize {
+        return self.mappings.lock().values().sum();
+    }
+
+    pub(crate) fn unmap_all(&self) {
+        for (addr, size) in self.mappings.lock().drain() {
+            if let Err(e) = os::unmap_file(Address::from_usize(addr), size) {
+                log::debug!("cannot unmap {} bytes at 0x{:x}: {}", size, addr, e);
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@ use std::{cmp::{PartialEq, PartialOrd}, ffi::c_void};
 
 pub mod heap;
 pub mod lab;
+pub mod mapped;
 pub mod space;
 
 pub const KB: usize = 1024;
@@ -98,7 +99,7 @@ pub const fn align(size: usize) -> usize {
 }
 
 #[inline(always)]
-const fn align_of(size: usize, align: usize) -> usize {
+pub(crate) const fn align_of(size: usize, align: usize) -> usize {
     debug_assert!(is_power_of_2(align));
     return (size + align - 1) & (!(align - 1));
 }
@@ -1,10 +1,10 @@
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
 
-use super::is_align_of;
 use super::Address;
+use super::{align_of, is_align_of};
 use crate::os;
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub enum SpaceType {
     NEW,
     OLD,
@@ -65,14 +65,20 @@ impl Space {
         self.start
     }
 
-    pub fn end(&self) -> Address {
-        self.end
-    }
-
     pub fn size(&self) -> usize {
         return self.end.as_usize() - self.start.as_usize();
     }
 
+    /// The end of the allocated part of the space.
+    pub fn top(&self) -> Address {
+        return *self.free.lock().expect("Space::top failed");
+    }
+
+    /// The bytes allocated from the space.
+    pub fn used(&self) -> usize {
+        return self.top().as_usize() - self.start.as_usize();
+    }
+
     pub fn reset(&self) {
         let mut free = self.free.lock().expect("Space::reset failed");
         *free = self.start;
@@ -118,8 +124,183 @@ impl SemiSpace {
         self.to.alloc(size)
     }
 
+    pub fn top(&self) -> Address {
+        self.to.top()
+    }
+
+    /// The size of the semispace objects are allocated in.
+    pub fn size(&self) -> usize {
+        self.to.size()
+    }
+
+    pub fn used(&self) -> usize {
+        self.to.used()
+    }
+
     pub fn flip(&mut self) {
         std::mem::swap(&mut self.from, &mut self.to);
         self.to.reset();
     }
 }
+
+/// A space made of separately mapped chunks, grown on demand until its
+/// reserved size would exceed `max_size`.
+pub struct ChunkedSpace {
+    space_type: SpaceType,
+    chunk_size: usize,
+    max_size: usize,
+    hints: os::MemoryHints,
+    chunks: RwLock<Vec<Space>>,
+}
+
+impl ChunkedSpace {
+    pub fn new(
+        space_type: SpaceType,
+        chunk_size: usize,
+        max_size: usize,
+        hints: os::MemoryHints,
+    ) -> Self {
+        let chunk_size = align_of(chunk_size.min(max_size), os::page_size());
+        let space = ChunkedSpace {
+            space_type,
+            chunk_size,
+            max_size,
+            hints,
+            chunks: RwLock::new(Vec::new()),
+        };
+        let first_chunk = space
+            .new_chunk(chunk_size)
+            .expect("cannot reserve the first chunk");
+        space.chunks.write().unwrap().push(first_chunk);
+        return space;
+    }
+
+    pub fn destroy(&self) {
+        for chunk in self.chunks.read().unwrap().iter() {
+            chunk.destroy();
+        }
+    }
+
+    /// Allocates from the last chunk, adding a chunk large enough for `size`
+    /// when it is full. Returns null if that would exceed `max_size`.
+    pub fn alloc(&self, size: usize) -> Address {
+        {
+            let chunks = self.chunks.read().expect("ChunkedSpace::alloc failed");
+            let result = chunks.last().unwrap().alloc(size);
+            if result.is_not_null() {
+                return result;
+            }
+        }
+        let mut chunks = self.chunks.write().expect("ChunkedSpace::alloc failed");
+        // another thread may have added a chunk meanwhile
+        let result = chunks.last().unwrap().alloc(size);
+        if result.is_not_null() {
+            return result;
+        }
+        let reserved: usize = chunks.iter().map(|chunk| chunk.size()).sum();
+        let chunk_size = (size + self.chunk_size - 1) / self.chunk_size * self.chunk_size;
+        if reserved + chunk_size > self.max_size {
+            log::debug!(
+                "space {:?} reached its maximum size {}, reserved {}, requested {}",
+                self.space_type,
+                self.max_size,
+                reserved,
+                size
+            );
+            return Address::null();
+        }
+        if let Some(chunk) = self.new_chunk(chunk_size) {
+            let result = chunk.alloc(size);
+            chunks.push(chunk);
+            return result;
+        }
+        return Address::null();
+    }
+
+    pub fn contains(&self, addr: Address) -> bool {
+        return self
+            .chunks
+            .read()
+            .expect("ChunkedSpace::contains failed")
+            .iter()
+            .any(|chunk| chunk.contains(addr));
+    }
+
+    /// The start of the first chunk.
+    pub fn start(&self) -> Address {
+        return self.chunks.read().expect("ChunkedSpace::start failed")[0].start();
+    }
+
+    /// The memory reserved by all chunks.
+    pub fn size(&self) -> usize {
+        return self
+            .chunks
+            .read()
+            .expect("ChunkedSpace::size failed")
+            .iter()
+            .map(|chunk| chunk.size())
+            .sum();
+    }
+
+    /// The bytes allocated from all chunks.
+    pub fn used(&self) -> usize {
+        return self
+            .chunks
+            .read()
+            .expect("ChunkedSpace::used failed")
+            .iter()
+            .map(|chunk| chunk.used())
+            .sum();
+    }
+
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    fn new_chunk(&self, size: usize) -> Option<Space> {
+        let start = os::reserve_memory_with_hints(size, self.hints);
+        if start.is_null() {
+            return None;
+        }
+        log::debug!(
+            "space {:?} adds a chunk of {} bytes at {:x}",
+            self.space_type,
+            size,
+            start.as_usize()
+        );
+        return Some(Space::new(self.space_type, start, size, false));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkedSpace, SpaceType};
+    use crate::{memory::MB, os};
+
+    #[test]
+    fn chunked_space_grows_to_max_size() {
+        os::init();
+        let space = ChunkedSpace::new(SpaceType::PERM, MB, 4 * MB, os::MemoryHints::default());
+        let first = space.alloc(MB / 2);
+        assert!(space.contains(first));
+        assert_eq!(MB, space.size());
+
+        let second = space.alloc(MB);
+        assert!(second.is_not_null());
+        assert!(space.contains(second));
+        assert_eq!(2 * MB, space.size());
+
+        let large = space.alloc(MB + 8);
+        assert!(large.is_not_null());
+        assert!(space.contains(large.uoffset(MB)));
+        assert_eq!(4 * MB, space.size());
+
+        assert!(space.alloc(8 * MB).is_null());
+        assert!(space.alloc(MB / 2).is_not_null());
+        space.destroy();
+    }
+}
@@ -1,35 +1,74 @@
 use std::collections::HashMap;
 
 use crate::memory::Address;
+use crate::object::class::ClassData;
+use crate::object::method::MethodPtr;
+use crate::os;
 
+use super::signature::{NativeParams, NativeSignature};
 use super::{
-    java_io_FileDescriptor, java_io_FileInputStream, java_io_FileOutputStream, java_io_FileSystem,
-    java_io_UnixFileSystem, java_io_Win32FileSystem, java_io_WinNTFileSystem, java_lang_Class,
-    java_lang_ClassLoader, java_lang_Double, java_lang_Float, java_lang_Object, java_lang_Runtime,
-    java_lang_String, java_lang_System, java_lang_Thread, java_security_AccessController,
-    java_util_concurrent_atomic_AtomicLong, sun_io_Win32ErrorMode, sun_misc_Signal,
-    sun_misc_Unsafe, sun_misc_VM, sun_reflect_NativeConstructorAccessorImpl,
-    sun_reflect_Reflection,
+    java_io_Console, java_io_FileDescriptor, java_io_FileInputStream, java_io_FileOutputStream,
+    java_io_FileSystem, java_io_RandomAccessFile, java_io_UnixFileSystem, java_io_Win32FileSystem,
+    java_io_WinNTFileSystem, java_lang_Class, java_lang_ClassLoader, java_lang_Double,
+    java_lang_Float, java_lang_Integer, java_lang_Long, java_lang_Object, java_lang_Runtime,
+    java_lang_SecurityManager, java_lang_String, java_lang_System, java_lang_Thread,
+    java_lang_Throwable, java_lang_reflect_Array, java_net_Inet4Address, java_net_Inet6Address,
+    java_net_InetAddress, java_net_InetAddressImplFactory, java_net_PlainSocketImpl,
+    java_net_SocketInputStream, java_net_SocketOutputStream, java_nio_MappedByteBuffer,
+    java_security_AccessController, java_util_ServiceLoader, java_util_TimeZone,
+    java_util_concurrent_atomic_AtomicLong, sun_io_Win32ErrorMode, sun_management_ClassLoadingImpl,
+    sun_management_MemoryImpl, sun_management_ThreadImpl, sun_management_VMManagementImpl,
+    sun_misc_Signal, sun_misc_Unsafe, sun_misc_VM, sun_nio_ch_EPollArrayWrapper,
+    sun_nio_ch_FileChannelImpl, sun_nio_ch_FileDispatcherImpl, sun_nio_ch_IOUtil,
+    sun_nio_ch_KQueueArrayWrapper, sun_nio_ch_NativeThread, sun_nio_ch_Net,
+    sun_nio_ch_ServerSocketChannelImpl, sun_nio_ch_SocketChannelImpl,
+    sun_reflect_NativeConstructorAccessorImpl, sun_reflect_NativeMethodAccessorImpl,
+    sun_reflect_Reflection, sun_security_provider_NativeSeedGenerator,
+    sun_security_provider_SeedGenerator,
+};
+use jni::objects::{JByteArray, JClass, JObject, JObjectArray, JString};
+use jni::sys::{
+    jarray, jboolean, jbyte, jbyteArray, jchar, jclass, jdouble, jfloat, jint, jlong, jobject,
+    jobjectArray, jshort, jstring,
 };
 use paste::paste;
 
+macro_rules! native_ret {
+    () => {
+        ()
+    };
+    ($ret: ty) => {
+        $ret
+    };
+}
+
+/// Registers the builtin native functions, each with the types of its
+/// parameters after the `JNIEnv`, the receiver or class first, and its result
+/// if not `void`. The types are checked against the function when compiling,
+/// and against the descriptor of the native method when it is bound.
 macro_rules! builtin_native_functions {
     ($(
-        {$cls_name: ident, [$($inner_cls_name:ident)*], $native_fn_name: ident}
+        {$cls_name: ident, [$($inner_cls_name:ident)*], $native_fn_name: ident, ($($param: ty), *) $(-> $ret: ty)?}
     ), *) => {
         impl BuiltinNativeFunctions {
             pub fn new() -> Self {
                 let mut fns = HashMap::with_capacity(Self::num_of_natives());
                 paste! {
                     $(
+                        // fails to compile unless the function takes and returns the listed types
+                        let native_fn: <($($param,)*) as NativeParams<native_ret!($($ret)?)>>::Fn =
+                            $cls_name::[<Java_  $cls_name $(_ $inner_cls_name)* _ $native_fn_name>];
                         fns.insert(
                             concat!(
                                 "Java_",
                                 stringify!($cls_name),
-                                $("$", stringify!($inner_cls_name),)*
+                                $("_00024", stringify!($inner_cls_name),)*
                                 "_", stringify!($native_fn_name)
                             ),
-                            Address::new($cls_name::[<Java_  $cls_name $(_ $inner_cls_name)* _ $native_fn_name>] as *const u8),
+                            BuiltinNative::new(
+                                native_fn as *const u8,
+                                <($($param,)*) as NativeParams<native_ret!($($ret)?)>>::signature(),
+                            ),
                         );
                     )*
                 }
@@ -52,112 +91,438 @@ macro_rules! builtin_native_functions {
 }
 
 builtin_native_functions!(
-    {java_lang_Class, [], registerNatives},
-    {java_lang_Class, [], forName0},
-    {java_lang_Class, [], isInstance},
-    {java_lang_Class, [], isAssignableFrom},
-    {java_lang_Class, [], isInterface},
-    {java_lang_Class, [], isArray},
-    {java_lang_Class, [], isPrimitive},
-    {java_lang_Class, [], getName0},
-    {java_lang_Class, [], getClassLoader0},
-    {java_lang_Class, [], getSuperclass},
-    {java_lang_Class, [], getInterfaces},
-    {java_lang_Class, [], getComponentType},
-    {java_lang_Class, [], getModifiers},
-    {java_lang_Class, [], getSigners},
-    {java_lang_Class, [], setSigners},
-    {java_lang_Class, [], getEnclosingMethods},
-    {java_lang_Class, [], getDeclaringClass},
-    {java_lang_Class, [], getProtectionDomain0},
-    {java_lang_Class, [], setProtectionDomain0},
-    {java_lang_Class, [], getPrimitiveClass},
-    {java_lang_Class, [], getGenericSignature},
-    {java_lang_Class, [], getRawAnnotations},
-    {java_lang_Class, [], getConstantPool},
-    {java_lang_Class, [], getDeclaredFields0},
-    {java_lang_Class, [], getDeclaredMethods0},
-    {java_lang_Class, [], getDeclaredConstructors0},
-    {java_lang_Class, [], getDeclaredClasses0},
-    {java_lang_Class, [], desiredAssertionStatus0},
-    {java_lang_ClassLoader, [], registerNatives},
-    {java_lang_ClassLoader, [NativeLibrary], load},
-    {java_lang_System, [], registerNatives},
-    {java_lang_System, [], setIn0},
-    {java_lang_System, [], setOut0},
-    {java_lang_System, [], setErr0},
-    {java_lang_System, [], currentTimeMillis},
-    {java_lang_System, [], nanoTime},
-    {java_lang_System, [], arraycopy},
-    {java_lang_System, [], identityHashCode},
-    {java_lang_System, [], initProperties},
-    {java_lang_System, [], mapLibraryName},
-    {java_lang_Object, [], registerNatives},
-    {java_lang_Object, [], getClass},
-    {java_lang_Object, [], hashCode},
-    {java_lang_Object, [], clone},
-    {java_lang_Object, [], notify},
-    {java_lang_Object, [], notifyAll},
-    {java_lang_Object, [], wait},
-    {java_lang_String, [], intern},
-    {java_lang_Float, [], floatToRawIntBits},
-    {java_lang_Float, [], intBitsToFloat},
-    {java_lang_Double, [], doubleToRawLongBits},
-    {java_lang_Double, [], longBitsToDouble},
-    {java_lang_Thread, [], registerNatives},
-    {java_lang_Thread, [], currentThread},
-    {java_lang_Thread, [], setPriority0},
-    {java_lang_Runtime, [], availableProcessors},
-    {java_lang_Runtime, [], freeMemory},
-    {java_io_FileInputStream, [], initIDs},
-    {java_io_FileOutputStream, [], initIDs},
-    {java_io_FileOutputStream, [], writeBytes},
-    {java_io_FileDescriptor, [], initIDs},
-    {java_io_FileDescriptor, [], set},
-    {java_io_FileSystem, [], getFileSystem},
-
-    {java_io_UnixFileSystem, [], initIDs},
-    {java_io_UnixFileSystem, [], getBooleanAttributes0},
-    {java_io_UnixFileSystem, [], canonicalize0},
-
-    {java_io_WinNTFileSystem, [], initIDs},
-    {java_io_WinNTFileSystem, [], getBooleanAttributes},
-    {java_io_WinNTFileSystem, [], canonicalize0},
-
-    {java_io_Win32FileSystem, [], initIDs},
-
-    {sun_io_Win32ErrorMode, [], setErrorMode},
-
-    {java_util_concurrent_atomic_AtomicLong, [], VMSupportsCS8},
-
-    {java_security_AccessController, [], doPrivileged},
-    {java_security_AccessController, [], getStackAccessControlContext},
-    {sun_reflect_Reflection, [], getCallerClass},
-    {sun_reflect_Reflection, [], getClassAccessFlags},
-    {sun_reflect_NativeConstructorAccessorImpl, [], newInstance0},
-    {sun_misc_Unsafe, [], registerNatives},
-    {sun_misc_Unsafe, [], getByte},
-    {sun_misc_Unsafe, [], putLong},
-    {sun_misc_Unsafe, [], allocateMemory},
-    {sun_misc_Unsafe, [], freeMemory},
-    {sun_misc_Unsafe, [], objectFieldOffset},
-    {sun_misc_Unsafe, [], arrayBaseOffset},
-    {sun_misc_Unsafe, [], arrayIndexScale},
-    {sun_misc_Unsafe, [], addressSize},
-    {sun_misc_Unsafe, [], compareAndSwapObject},
-    {sun_misc_Unsafe, [], compareAndSwapInt},
-    {sun_misc_Unsafe, [], putOrderedObject},
-    {sun_misc_Signal, [], findSignal},
-    {sun_misc_Signal, [], handle0},
-    {sun_misc_VM, [], initialize}
+    {java_lang_Class, [], registerNatives, (JClass)},
+    {java_lang_Class, [], forName0, (JClass, JString, jboolean, JObject) -> jclass},
+    {java_lang_Class, [], isInstance, (JObject, JObject) -> jboolean},
+    {java_lang_Class, [], isAssignableFrom, (JObject, JClass) -> jboolean},
+    {java_lang_Class, [], isInterface, (JObject) -> jboolean},
+    {java_lang_Class, [], isArray, (JObject) -> jboolean},
+    {java_lang_Class, [], isPrimitive, (JObject) -> jboolean},
+    {java_lang_Class, [], getName0, (JObject) -> jstring},
+    {java_lang_Class, [], getClassLoader0, (JObject) -> jobject},
+    {java_lang_Class, [], getSuperclass, (JObject) -> jclass},
+    {java_lang_Class, [], getInterfaces, (JObject) -> jarray},
+    {java_lang_Class, [], getComponentType, (JObject) -> jclass},
+    {java_lang_Class, [], getModifiers, (JObject) -> jint},
+    {java_lang_Class, [], getSigners, (JObject) -> jarray},
+    {java_lang_Class, [], setSigners, (JObject, JObjectArray)},
+    {java_lang_Class, [], getEnclosingMethods, (JObject) -> jarray},
+    {java_lang_Class, [], getDeclaringClass, (JObject) -> jclass},
+    {java_lang_Class, [], getProtectionDomain0, (JObject) -> jobject},
+    {java_lang_Class, [], setProtectionDomain0, (JObject, JObject)},
+    {java_lang_Class, [], getPrimitiveClass, (JClass, JString) -> jclass},
+    {java_lang_Class, [], getGenericSignature, (JObject) -> jstring},
+    {java_lang_Class, [], getRawAnnotations, (JObject) -> jbyteArray},
+    {java_lang_Class, [], getConstantPool, (JObject) -> jobject},
+    {java_lang_Class, [], getDeclaredFields0, (JObject, jboolean) -> jarray},
+    {java_lang_Class, [], getDeclaredMethods0, (JObject, jboolean) -> jarray},
+    {java_lang_Class, [], getDeclaredConstructors0, (JObject, jboolean) -> jarray},
+    {java_lang_Class, [], getDeclaredClasses0, (JObject) -> jarray},
+    {java_lang_Class, [], desiredAssertionStatus0, (JClass, JClass) -> jboolean},
+    {java_lang_ClassLoader, [], registerNatives, (JClass)},
+    {java_lang_ClassLoader, [], findLoadedClass0, (JObject, JObject) -> jclass},
+    {java_lang_ClassLoader, [], findBootstrapClass, (JObject, JObject) -> jclass},
+    {java_lang_ClassLoader, [], getBootstrapResource, (JClass, JObject) -> jobject},
+    {java_lang_ClassLoader, [], getBootstrapResources, (JClass, JObject) -> jobject},
+    {java_lang_ClassLoader, [], getSystemResource, (JClass, JObject) -> jobject},
+    {java_lang_ClassLoader, [], getSystemResources, (JClass, JObject) -> jobject},
+    {java_lang_ClassLoader, [], getSystemResourceAsStream, (JClass, JObject) -> jobject},
+    {java_lang_ClassLoader, [NativeLibrary], load, (JObject, JObject)},
+    {java_lang_System, [], registerNatives, (JClass)},
+    {java_lang_System, [], setIn0, (JClass, JObject)},
+    {java_lang_System, [], setOut0, (JClass, JObject)},
+    {java_lang_System, [], setErr0, (JClass, JObject)},
+    {java_lang_System, [], currentTimeMillis, (JClass) -> jlong},
+    {java_lang_System, [], nanoTime, (JClass) -> jlong},
+    {java_lang_System, [], arraycopy, (JClass, JObject, jint, JObject, jint, jint)},
+    {java_lang_System, [], identityHashCode, (JClass, JObject) -> jint},
+    {java_lang_System, [], initProperties, (JClass, JObject) -> jobject},
+    {java_lang_System, [], mapLibraryName, (JClass, JString) -> jstring},
+    {java_lang_Throwable, [], fillInStackTrace, (JObject, jint) -> jobject},
+    {java_lang_Throwable, [], getStackTraceDepth, (JObject) -> jint},
+    {java_lang_Throwable, [], getStackTraceElement, (JObject, jint) -> jobject},
+    {java_lang_Integer, [], numberOfLeadingZeros, (JClass, jint) -> jint},
+    {java_lang_Integer, [], numberOfTrailingZeros, (JClass, jint) -> jint},
+    {java_lang_Integer, [], bitCount, (JClass, jint) -> jint},
+    {java_lang_Integer, [], reverseBytes, (JClass, jint) -> jint},
+    {java_lang_Long, [], numberOfLeadingZeros, (JClass, jlong) -> jint},
+    {java_lang_Long, [], numberOfTrailingZeros, (JClass, jlong) -> jint},
+    {java_lang_Long, [], bitCount, (JClass, jlong) -> jint},
+    {java_lang_Long, [], reverseBytes, (JClass, jlong) -> jlong},
+    {java_lang_Object, [], registerNatives, (JClass)},
+    {java_lang_Object, [], getClass, (JObject) -> jclass},
+    {java_lang_Object, [], hashCode, (JObject) -> jint},
+    {java_lang_Object, [], clone, (JObject) -> jobject},
+    {java_lang_Object, [], notify, (JObject)},
+    {java_lang_Object, [], notifyAll, (JObject)},
+    {java_lang_Object, [], wait, (JObject, jlong)},
+    {java_lang_String, [], intern, (JObject) -> jstring},
+    {java_lang_Float, [], floatToRawIntBits, (JClass, jfloat) -> jint},
+    {java_lang_Float, [], intBitsToFloat, (JClass, jint) -> jfloat},
+    {java_lang_Double, [], doubleToRawLongBits, (JClass, jdouble) -> jlong},
+    {java_lang_Double, [], longBitsToDouble, (JClass, jlong) -> jdouble},
+    {java_lang_reflect_Array, [], newArray, (JClass, JClass, jint) -> jobject},
+    {java_lang_Thread, [], registerNatives, (JClass)},
+    {java_lang_Thread, [], currentThread, (JClass) -> jobject},
+    {java_lang_Thread, [], setPriority0, (JClass, jint)},
+    {java_lang_Thread, [], isAlive, (JObject) -> jboolean},
+    {java_lang_Thread, [], start0, (JObject)},
+    {java_lang_Thread, [], holdsLock, (JClass, JObject) -> jboolean},
+    {java_lang_Thread, [], isInterrupted, (JObject, jboolean) -> jboolean},
+    {java_lang_Thread, [], sleep, (JClass, jlong)},
+    {java_lang_Thread, [], yield, (JClass)},
+    {java_lang_Runtime, [], availableProcessors, (JObject) -> jint},
+    {java_lang_Runtime, [], freeMemory, (JObject) -> jlong},
+    {java_io_FileInputStream, [], initIDs, (JClass)},
+    {java_io_FileInputStream, [], open, (JObject, JString)},
+    {java_io_FileInputStream, [], read0, (JObject) -> jint},
+    {java_io_FileInputStream, [], readBytes, (JObject, JByteArray, jint, jint) -> jint},
+    {java_io_FileInputStream, [], skip, (JObject, jlong) -> jlong},
+    {java_io_FileInputStream, [], available, (JObject) -> jint},
+    {java_io_FileInputStream, [], close0, (JObject)},
+    {java_io_FileOutputStream, [], initIDs, (JClass)},
+    {java_io_FileOutputStream, [], writeBytes, (JClass, JByteArray, jint, jint, jboolean)},
+    {java_io_FileDescriptor, [], initIDs, (JClass)},
+    {java_io_FileDescriptor, [], set, (JClass, jint) -> jlong},
+    {java_io_FileSystem, [], getFileSystem, (JClass) -> jobject},
+    {java_io_RandomAccessFile, [], initIDs, (JClass)},
+    {java_io_RandomAccessFile, [], open, (JObject, JString, jint)},
+    {java_io_RandomAccessFile, [], read0, (JObject) -> jint},
+    {java_io_RandomAccessFile, [], readBytes0, (JObject, JByteArray, jint, jint) -> jint},
+    {java_io_RandomAccessFile, [], write0, (JObject, jint)},
+    {java_io_RandomAccessFile, [], writeBytes0, (JObject, JByteArray, jint, jint)},
+    {java_io_RandomAccessFile, [], getFilePointer, (JObject) -> jlong},
+    {java_io_RandomAccessFile, [], seek, (JObject, jlong)},
+    {java_io_RandomAccessFile, [], length, (JObject) -> jlong},
+    {java_io_RandomAccessFile, [], setLength, (JObject, jlong)},
+    {java_io_RandomAccessFile, [], close0, (JObject)},
+    {java_io_Console, [], encoding, (JClass) -> jstring},
+    {java_io_Console, [], echo, (JClass, jboolean) -> jboolean},
+    {java_io_Console, [], istty, (JClass) -> jboolean},
+
+    {java_io_UnixFileSystem, [], initIDs, (JClass)},
+    {java_io_UnixFileSystem, [], getBooleanAttributes0, (JClass, JObject) -> jint},
+    {java_io_UnixFileSystem, [], canonicalize0, (JObject, JString) -> jstring},
+
+    {java_io_WinNTFileSystem, [], initIDs, (JClass)},
+    {java_io_WinNTFileSystem, [], getBooleanAttributes, (JClass, JObject) -> jint},
+    {java_io_WinNTFileSystem, [], canonicalize0, (JObject, JString) -> jstring},
+
+    {java_io_Win32FileSystem, [], initIDs, (JClass)},
+
+    {sun_io_Win32ErrorMode, [], setErrorMode, (JClass, jlong) -> jlong},
+
+    {java_net_InetAddress, [], init, (JClass)},
+    {java_net_Inet4Address, [], init, (JClass)},
+    {java_net_Inet6Address, [], init, (JClass)},
+    {java_net_InetAddressImplFactory, [], isIPv6Supported, (JClass) -> jboolean},
+    {java_net_PlainSocketImpl, [], initProto, (JClass)},
+    {java_net_PlainSocketImpl, [], socketCreate, (JObject, jboolean)},
+    {java_net_PlainSocketImpl, [], socketConnect, (JObject, JObject, jint, jint)},
+    {java_net_PlainSocketImpl, [], socketBind, (JObject, JObject, jint)},
+    {java_net_PlainSocketImpl, [], socketListen, (JObject, jint)},
+    {java_net_PlainSocketImpl, [], socketAccept, (JObject, JObject)},
+    {java_net_PlainSocketImpl, [], socketAvailable, (JObject) -> jint},
+    {java_net_PlainSocketImpl, [], socketClose0, (JObject, jboolean)},
+    {java_net_PlainSocketImpl, [], socketShutdown, (JObject, jint)},
+    {java_net_PlainSocketImpl, [], socketSetOption, (JObject, jint, jboolean, JObject)},
+    {java_net_PlainSocketImpl, [], socketGetOption, (JObject, jint, JObject) -> jint},
+    {java_net_PlainSocketImpl, [], socketSendUrgentData, (JObject, jint)},
+    {java_net_SocketInputStream, [], init, (JClass)},
+    {java_net_SocketInputStream, [], socketRead0, (JObject, JObject, JByteArray, jint, jint, jint) -> jint},
+    {java_net_SocketOutputStream, [], init, (JClass)},
+    {java_net_SocketOutputStream, [], socketWrite0, (JObject, JObject, JByteArray, jint, jint)},
+    {java_nio_MappedByteBuffer, [], isLoaded0, (JObject, jlong, jlong, jint) -> jboolean},
+    {java_nio_MappedByteBuffer, [], load0, (JObject, jlong, jlong)},
+    {java_nio_MappedByteBuffer, [], force0, (JObject, JObject, jlong, jlong)},
+    {sun_nio_ch_EPollArrayWrapper, [], init, (JClass)},
+    {sun_nio_ch_EPollArrayWrapper, [], epollCreate, (JObject) -> jint},
+    {sun_nio_ch_EPollArrayWrapper, [], sizeofEPollEvent, (JClass) -> jint},
+    {sun_nio_ch_EPollArrayWrapper, [], offsetofData, (JClass) -> jint},
+    {sun_nio_ch_EPollArrayWrapper, [], epollCtl, (JObject, jint, jint, jint, jint)},
+    {sun_nio_ch_EPollArrayWrapper, [], epollWait, (JObject, jlong, jint, jlong, jint) -> jint},
+    {sun_nio_ch_EPollArrayWrapper, [], interrupt, (JClass, jint)},
+    {sun_nio_ch_FileChannelImpl, [], initIDs, (JClass) -> jlong},
+    {sun_nio_ch_FileChannelImpl, [], map0, (JObject, jint, jlong, jlong) -> jlong},
+    {sun_nio_ch_FileChannelImpl, [], unmap0, (JClass, jlong, jlong) -> jint},
+    {sun_nio_ch_FileChannelImpl, [], position0, (JObject, JObject, jlong) -> jlong},
+    {sun_nio_ch_FileDispatcherImpl, [], init, (JClass)},
+    {sun_nio_ch_FileDispatcherImpl, [], read0, (JClass, JObject, jlong, jint) -> jint},
+    {sun_nio_ch_FileDispatcherImpl, [], write0, (JClass, JObject, jlong, jint) -> jint},
+    {sun_nio_ch_FileDispatcherImpl, [], size0, (JClass, JObject) -> jlong},
+    {sun_nio_ch_FileDispatcherImpl, [], truncate0, (JClass, JObject, jlong) -> jint},
+    {sun_nio_ch_FileDispatcherImpl, [], force0, (JClass, JObject, jboolean) -> jint},
+    {sun_nio_ch_FileDispatcherImpl, [], preClose0, (JClass, JObject)},
+    {sun_nio_ch_FileDispatcherImpl, [], close0, (JClass, JObject)},
+    {sun_nio_ch_FileDispatcherImpl, [], closeIntFD, (JClass, jint)},
+    {sun_nio_ch_IOUtil, [], initIDs, (JClass)},
+    {sun_nio_ch_IOUtil, [], iovMax, (JClass) -> jint},
+    {sun_nio_ch_IOUtil, [], fdVal, (JClass, JObject) -> jint},
+    {sun_nio_ch_IOUtil, [], setfdVal, (JClass, JObject, jint)},
+    {sun_nio_ch_IOUtil, [], fdLimit, (JClass) -> jint},
+    {sun_nio_ch_IOUtil, [], configureBlocking, (JClass, JObject, jboolean)},
+    {sun_nio_ch_IOUtil, [], makePipe, (JClass, jboolean) -> jlong},
+    {sun_nio_ch_IOUtil, [], drain, (JClass, jint) -> jboolean},
+    {sun_nio_ch_KQueueArrayWrapper, [], initStructSizes, (JClass)},
+    {sun_nio_ch_KQueueArrayWrapper, [], init, (JObject) -> jint},
+    {sun_nio_ch_KQueueArrayWrapper, [], register0, (JObject, jint, jint, jint, jint)},
+    {sun_nio_ch_KQueueArrayWrapper, [], kevent0, (JObject, jint, jlong, jint, jlong) -> jint},
+    {sun_nio_ch_KQueueArrayWrapper, [], interrupt, (JClass, jint)},
+    {sun_nio_ch_NativeThread, [], init, (JClass)},
+    {sun_nio_ch_NativeThread, [], current, (JClass) -> jlong},
+    {sun_nio_ch_NativeThread, [], signal, (JClass, jlong)},
+    {sun_nio_ch_Net, [], initIDs, (JClass)},
+    {sun_nio_ch_Net, [], isIPv6Available0, (JClass) -> jboolean},
+    {sun_nio_ch_Net, [], isExclusiveBindAvailable, (JClass) -> jint},
+    {sun_nio_ch_Net, [], canIPv6SocketJoinIPv4Group0, (JClass) -> jboolean},
+    {sun_nio_ch_Net, [], canJoin6WithIPv4Group0, (JClass) -> jboolean},
+    {sun_nio_ch_Net, [], socket0, (JClass, jboolean, jboolean, jboolean) -> jint},
+    {sun_nio_ch_Net, [], bind0, (JClass, JObject, jboolean, jboolean, JObject, jint)},
+    {sun_nio_ch_Net, [], listen, (JClass, JObject, jint)},
+    {sun_nio_ch_Net, [], connect0, (JClass, jboolean, JObject, JObject, jint) -> jint},
+    {sun_nio_ch_Net, [], shutdown, (JClass, JObject, jint)},
+    {sun_nio_ch_Net, [], localPort, (JClass, JObject) -> jint},
+    {sun_nio_ch_Net, [], localInetAddress, (JClass, JObject) -> jobject},
+    {sun_nio_ch_Net, [], remotePort, (JClass, JObject) -> jint},
+    {sun_nio_ch_Net, [], remoteInetAddress, (JClass, JObject) -> jobject},
+    {sun_nio_ch_Net, [], getIntOption0, (JClass, JObject, jboolean, jint, jint) -> jint},
+    {sun_nio_ch_Net, [], setIntOption0, (JClass, JObject, jboolean, jint, jint, jint)},
+    {sun_nio_ch_ServerSocketChannelImpl, [], initIDs, (JClass)},
+    {sun_nio_ch_ServerSocketChannelImpl, [], accept0, (JObject, JObject, JObject, JObject) -> jint},
+    {sun_nio_ch_SocketChannelImpl, [], checkConnect, (JClass, JObject, jboolean, jboolean) -> jint},
+
+    {java_util_concurrent_atomic_AtomicLong, [], VMSupportsCS8, (JClass) -> jboolean},
+    {java_util_ServiceLoader, [], parse, (JObject, JClass, JObject) -> jobject},
+    {java_util_TimeZone, [], getSystemTimeZoneID, (JClass, JString, JString) -> jstring},
+    {java_util_TimeZone, [], getSystemGMTOffsetID, (JClass) -> jstring},
+
+    {java_security_AccessController, [], doPrivileged, (JClass, JObject) -> jobject},
+    {java_security_AccessController, [], doPrivileged__Ljava_security_PrivilegedAction_2Ljava_security_AccessControlContext_2, (JClass, JObject, JObject) -> jobject},
+    {java_security_AccessController, [], doPrivileged__Ljava_security_PrivilegedExceptionAction_2Ljava_security_AccessControlContext_2, (JClass, JObject, JObject) -> jobject},
+    {java_lang_SecurityManager, [], getClassContext, (JObject) -> jobjectArray},
+    {java_lang_SecurityManager, [], classDepth, (JObject, JString) -> jint},
+    {java_lang_SecurityManager, [], classLoaderDepth0, (JObject) -> jint},
+    {java_lang_SecurityManager, [], currentClassLoader0, (JObject) -> jobject},
+    {java_lang_SecurityManager, [], currentLoadedClass0, (JObject) -> jclass},
+    {java_security_AccessController, [], getStackAccessControlContext, (JClass) -> jobject},
+    {sun_reflect_Reflection, [], getCallerClass, (JClass) -> jclass},
+    {sun_reflect_Reflection, [], getCallerClass0, (JClass, jint) -> jclass},
+    {sun_reflect_Reflection, [], getClassAccessFlags, (JClass, JClass) -> jint},
+    {sun_reflect_NativeConstructorAccessorImpl, [], newInstance0, (JClass, JObject, JObject) -> jobject},
+    {sun_reflect_NativeMethodAccessorImpl, [], invoke0, (JClass, JObject, JObject, JObjectArray) -> jobject},
+    {sun_security_provider_NativeSeedGenerator, [], nativeGenerateSeed, (JClass, JByteArray) -> jboolean},
+    {sun_security_provider_SeedGenerator, [], getSystemEntropy, (JClass) -> jbyteArray},
+    {sun_misc_Unsafe, [], registerNatives, (JClass)},
+    {sun_misc_Unsafe, [], getByte__J, (JObject, jlong) -> jbyte},
+    {sun_misc_Unsafe, [], putByte__JB, (JObject, jlong, jbyte)},
+    {sun_misc_Unsafe, [], getShort__J, (JObject, jlong) -> jshort},
+    {sun_misc_Unsafe, [], putShort__JS, (JObject, jlong, jshort)},
+    {sun_misc_Unsafe, [], getInt__J, (JObject, jlong) -> jint},
+    {sun_misc_Unsafe, [], putInt__JI, (JObject, jlong, jint)},
+    {sun_misc_Unsafe, [], getLong__J, (JObject, jlong) -> jlong},
+    {sun_misc_Unsafe, [], putLong__JJ, (JObject, jlong, jlong)},
+    {sun_misc_Unsafe, [], allocateMemory, (JObject, jlong) -> jlong},
+    {sun_misc_Unsafe, [], freeMemory, (JObject, jlong)},
+    {sun_misc_Unsafe, [], setMemory, (JObject, JObject, jlong, jlong, jbyte)},
+    {sun_misc_Unsafe, [], copyMemory, (JObject, JObject, jlong, JObject, jlong, jlong)},
+    {sun_misc_Unsafe, [], objectFieldOffset, (JObject, JObject) -> jlong},
+    {sun_misc_Unsafe, [], ensureClassInitialized, (JObject, JObject)},
+    {sun_misc_Unsafe, [], defineClass__Ljava_lang_String_2_3BIILjava_lang_ClassLoader_2Ljava_security_ProtectionDomain_2, (JObject, JString, JByteArray, jint, jint, JObject, JObject) -> jclass},
+    {sun_misc_Unsafe, [], defineClass__Ljava_lang_String_2_3BII, (JObject, JString, JByteArray, jint, jint) -> jclass},
+    {sun_misc_Unsafe, [], defineAnonymousClass, (JObject, JClass, JByteArray, JObjectArray) -> jclass},
+    {sun_misc_Unsafe, [], arrayBaseOffset, (JObject, JObject) -> jint},
+    {sun_misc_Unsafe, [], arrayIndexScale, (JObject, JObject) -> jint},
+    {sun_misc_Unsafe, [], addressSize, (JObject) -> jint},
+    {sun_misc_Unsafe, [], pageSize, (JObject) -> jint},
+    {sun_misc_Unsafe, [], compareAndSwapObject, (JObject, JObject, jlong, JObject, JObject) -> jboolean},
+    {sun_misc_Unsafe, [], compareAndSwapInt, (JObject, JObject, jlong, jint, jint) -> jboolean},
+    {sun_misc_Unsafe, [], compareAndSwapLong, (JObject, JObject, jlong, jlong, jlong) -> jboolean},
+    {sun_misc_Unsafe, [], getObject, (JObject, JObject, jlong) -> jobject},
+    {sun_misc_Unsafe, [], putObject, (JObject, JObject, jlong, JObject)},
+    {sun_misc_Unsafe, [], putOrderedObject, (JObject, JObject, jlong, JObject)},
+    {sun_misc_Unsafe, [], getObjectVolatile, (JObject, JObject, jlong) -> jobject},
+    {sun_misc_Unsafe, [], putObjectVolatile, (JObject, JObject, jlong, jobject)},
+    {sun_misc_Unsafe, [], getBooleanVolatile, (JObject, JObject, jlong) -> jboolean},
+    {sun_misc_Unsafe, [], putBooleanVolatile, (JObject, JObject, jlong, jboolean)},
+    {sun_misc_Unsafe, [], getByteVolatile, (JObject, JObject, jlong) -> jbyte},
+    {sun_misc_Unsafe, [], putByteVolatile, (JObject, JObject, jlong, jbyte)},
+    {sun_misc_Unsafe, [], getShortVolatile, (JObject, JObject, jlong) -> jshort},
+    {sun_misc_Unsafe, [], putShortVolatile, (JObject, JObject, jlong, jshort)},
+    {sun_misc_Unsafe, [], getCharVolatile, (JObject, JObject, jlong) -> jchar},
+    {sun_misc_Unsafe, [], putCharVolatile, (JObject, JObject, jlong, jchar)},
+    {sun_misc_Unsafe, [], getIntVolatile, (JObject, JObject, jlong) -> jint},
+    {sun_misc_Unsafe, [], putIntVolatile, (JObject, JObject, jlong, jint)},
+    {sun_misc_Unsafe, [], getLongVolatile, (JObject, JObject, jlong) -> jlong},
+    {sun_misc_Unsafe, [], putLongVolatile, (JObject, JObject, jlong, jlong)},
+    {sun_misc_Unsafe, [], getFloatVolatile, (JObject, JObject, jlong) -> jfloat},
+    {sun_misc_Unsafe, [], putFloatVolatile, (JObject, JObject, jlong, jfloat)},
+    {sun_misc_Unsafe, [], getDoubleVolatile, (JObject, JObject, jlong) -> jdouble},
+    {sun_misc_Unsafe, [], putDoubleVolatile, (JObject, JObject, jlong, jdouble)},
+    {sun_management_VMManagementImpl, [], getVersion0, (JClass) -> jstring},
+    {sun_management_VMManagementImpl, [], initOptionalSupportFields, (JClass)},
+    {sun_management_VMManagementImpl, [], isThreadContentionMonitoringEnabled, (JObject) -> jboolean},
+    {sun_management_VMManagementImpl, [], isThreadCpuTimeEnabled, (JObject) -> jboolean},
+    {sun_management_VMManagementImpl, [], isThreadAllocatedMemoryEnabled, (JObject) -> jboolean},
+    {sun_management_VMManagementImpl, [], getTotalClassCount, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getUnloadedClassCount, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getVerboseClass, (JObject) -> jboolean},
+    {sun_management_VMManagementImpl, [], getVerboseGC, (JObject) -> jboolean},
+    {sun_management_VMManagementImpl, [], getProcessId, (JObject) -> jint},
+    {sun_management_VMManagementImpl, [], getVmArguments0, (JObject) -> jobject},
+    {sun_management_VMManagementImpl, [], getStartupTime, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getAvailableProcessors, (JObject) -> jint},
+    {sun_management_VMManagementImpl, [], getTotalCompileTime, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getTotalThreadCount, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getLiveThreadCount, (JObject) -> jint},
+    {sun_management_VMManagementImpl, [], getPeakThreadCount, (JObject) -> jint},
+    {sun_management_VMManagementImpl, [], getDaemonThreadCount, (JObject) -> jint},
+    {sun_management_VMManagementImpl, [], getSafepointCount, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getTotalSafepointTime, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getSafepointSyncTime, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getTotalApplicationNonStoppedTime, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getLoadedClassSize, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getUnloadedClassSize, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getClassLoadingTime, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getMethodDataSize, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getInitializedClassCount, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getClassInitializationTime, (JObject) -> jlong},
+    {sun_management_VMManagementImpl, [], getClassVerificationTime, (JObject) -> jlong},
+    {sun_management_MemoryImpl, [], getMemoryPools0, (JClass) -> jobject},
+    {sun_management_MemoryImpl, [], getMemoryManagers0, (JClass) -> jobject},
+    {sun_management_MemoryImpl, [], getMemoryUsage0, (JObject, jboolean) -> jobject},
+    {sun_management_MemoryImpl, [], setVerboseGC, (JObject, jboolean)},
+    {sun_management_ThreadImpl, [], getThreads, (JClass) -> jobject},
+    {sun_management_ThreadImpl, [], resetPeakThreadCount0, (JClass)},
+    {sun_management_ThreadImpl, [], setThreadCpuTimeEnabled0, (JClass, jboolean)},
+    {sun_management_ThreadImpl, [], setThreadAllocatedMemoryEnabled0, (JClass, jboolean)},
+    {sun_management_ThreadImpl, [], setThreadContentionMonitoringEnabled0, (JClass, jboolean)},
+    {sun_management_ClassLoadingImpl, [], setVerboseClass, (JClass, jboolean)},
+
+    {sun_misc_Signal, [], findSignal, (JClass, JObject) -> jint},
+    {sun_misc_Signal, [], handle0, (JClass, jint, jlong) -> jlong},
+    {sun_misc_VM, [], initialize, (JClass)}
 );
 
+/// Java methods the VM replaces with the builtin native of the same name, by
+/// class, name and descriptor. Every class is defined by the bootstrap class
+/// loader, so the system resources are those of the boot class path, which
+/// the Java code of `ClassLoader` would reach through `sun.misc.Launcher` and
+/// `java.util.zip`, and `ServiceLoader` reads them the same way. The system
+/// entropy of `SeedGenerator` comes from the operating system, as the bundled
+/// `rt.jar` has no `SUN` provider for the digest the Java code takes. The bit
+/// twiddling of `Integer` and `Long` is a single instruction in Rust, but a
+/// loop of shifts and masks when interpreted.
+const BUILTIN_JAVA_METHODS: &[(&str, &str, &str)] = &[
+    (
+        "java/lang/ClassLoader",
+        "getBootstrapResource",
+        "(Ljava/lang/String;)Ljava/net/URL;",
+    ),
+    (
+        "java/lang/ClassLoader",
+        "getBootstrapResources",
+        "(Ljava/lang/String;)Ljava/util/Enumeration;",
+    ),
+    (
+        "java/lang/ClassLoader",
+        "getSystemResource",
+        "(Ljava/lang/String;)Ljava/net/URL;",
+    ),
+    (
+        "java/lang/ClassLoader",
+        "getSystemResources",
+        "(Ljava/lang/String;)Ljava/util/Enumeration;",
+    ),
+    (
+        "java/lang/ClassLoader",
+        "getSystemResourceAsStream",
+        "(Ljava/lang/String;)Ljava/io/InputStream;",
+    ),
+    ("java/lang/Integer", "numberOfLeadingZeros", "(I)I"),
+    ("java/lang/Integer", "numberOfTrailingZeros", "(I)I"),
+    ("java/lang/Integer", "bitCount", "(I)I"),
+    ("java/lang/Integer", "reverseBytes", "(I)I"),
+    ("java/lang/Long", "numberOfLeadingZeros", "(J)I"),
+    ("java/lang/Long", "numberOfTrailingZeros", "(J)I"),
+    ("java/lang/Long", "bitCount", "(J)I"),
+    ("java/lang/Long", "reverseBytes", "(J)J"),
+    (
+        "java/util/ServiceLoader",
+        "parse",
+        "(Ljava/lang/Class;Ljava/net/URL;)Ljava/util/Iterator;",
+    ),
+    (
+        "sun/security/provider/SeedGenerator",
+        "getSystemEntropy",
+        "()[B",
+    ),
+];
+
+/// A builtin native function and the types it was registered with.
+pub(crate) struct BuiltinNative {
+    address: Address,
+    signature: NativeSignature,
+}
+
+impl BuiltinNative {
+    fn new(address: *const u8, signature: NativeSignature) -> Self {
+        return Self {
+            address: Address::new(address),
+            signature,
+        };
+    }
+
+    #[cfg(test)]
+    pub(crate) fn signature(&self) -> &NativeSignature {
+        return &self.signature;
+    }
+
+    /// The function to bind `method` to. Aborts if the function does not
+    /// match the descriptor of `method`, as the interpreter would pass it
+    /// arguments it does not expect.
+    pub(crate) fn address_for(&self, method: MethodPtr) -> Address {
+        let expected = NativeSignature::of_method(method);
+        if self.signature != expected {
+            os::fatal_error(&format!(
+                "builtin native of {}.{}{} takes {}, its descriptor needs {}\n",
+                method.decl_cls().name().as_str(),
+                method.name().as_str(),
+                method.descriptor().as_str(),
+                self.signature,
+                expected
+            ));
+        }
+        return self.address;
+    }
+}
+
 pub(crate) struct BuiltinNativeFunctions {
-    fns: HashMap<&'static str, Address>,
+    fns: HashMap<&'static str, BuiltinNative>,
 }
 
 impl BuiltinNativeFunctions {
-    pub(crate) fn get_builtin_native_fn(&self, fn_name: &str) -> Option<Address> {
-        return self.fns.get(fn_name).copied();
+    pub(crate) fn get_builtin_native_fn(&self, fn_name: &str) -> Option<&BuiltinNative> {
+        return self.fns.get(fn_name);
+    }
+
+    /// The names of the builtin native functions, like
+    /// `Java_java_lang_Object_hashCode`.
+    #[cfg(test)]
+    pub(crate) fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        return self.fns.keys().copied();
+    }
+
+    /// The builtin native replacing the Java method, see
+    /// [`BUILTIN_JAVA_METHODS`].
+    pub(crate) fn get_builtin_java_method_fn(
+        &self,
+        class_name: &str,
+        method_name: &str,
+        descriptor: &str,
+    ) -> Option<&BuiltinNative> {
+        if !BUILTIN_JAVA_METHODS.contains(&(class_name, method_name, descriptor)) {
+            return None;
+        }
+        return self.get_builtin_native_fn(&ClassData::get_native_fn_name(class_name, method_name));
     }
 }
@@ -0,0 +1,331 @@
+//! The JNI invocation API: the `JavaVM` function table natives reach through
+//! `GetJavaVM`, and with the `invocation-api` feature the `JNI_CreateJavaVM`
+//! exports a launcher written for libjvm calls.
+
+use std::ffi::c_void;
+
+use jni::sys::{
+    jint, JNIInvokeInterface_, JavaVMAttachArgs, JNI_EDETACHED, JNI_ERR, JNI_EVERSION, JNI_OK,
+    JNI_VERSION_1_1, JNI_VERSION_1_2, JNI_VERSION_1_4, JNI_VERSION_1_6,
+};
+
+use crate::{
+    object::prelude::{ObjectPtr, Ptr},
+    thread::Thread,
+    vm::VMPtr,
+};
+
+/// The JNI version `GetEnv` and `JNI_GetDefaultJavaVMInitArgs` report.
+pub(crate) const JNI_VERSION: jint = JNI_VERSION_1_6;
+
+pub(crate) type JavaVMWrapperPtr = Ptr<JavaVMWrapper>;
+
+/// The `JavaVM` of a VM. `functions` has to stay the first field, as callers
+/// read the function table through it.
+#[repr(C)]
+pub(crate) struct JavaVMWrapper {
+    functions: *const JNIInvokeInterface_,
+    vm: VMPtr,
+}
+
+impl Default for JavaVMWrapper {
+    fn default() -> Self {
+        return Self {
+            functions: std::ptr::null(),
+            vm: VMPtr::null(),
+        };
+    }
+}
+
+impl JavaVMWrapper {
+    /// Points the wrapper at `vm` and its function table, which must both
+    /// stay where they are.
+    pub(crate) fn bind(&mut self, functions: &JNIInvokeInterface_, vm: VMPtr) {
+        self.functions = functions;
+        self.vm = vm;
+    }
+
+    pub(crate) fn from_raw_vm(vm: *mut jni::sys::JavaVM) -> JavaVMWrapperPtr {
+        return JavaVMWrapperPtr::from_raw(vm as *mut JavaVMWrapper);
+    }
+
+    pub(crate) fn vm(&self) -> VMPtr {
+        return self.vm;
+    }
+
+    pub(crate) fn as_raw(&self) -> *mut jni::sys::JavaVM {
+        return self as *const JavaVMWrapper as *mut jni::sys::JavaVM;
+    }
+}
+
+pub(crate) fn invoke_interface() -> JNIInvokeInterface_ {
+    let mut functions: JNIInvokeInterface_ = unsafe { std::mem::zeroed() };
+    functions.DestroyJavaVM = Some(destroy_java_vm);
+    functions.AttachCurrentThread = Some(attach_current_thread);
+    functions.DetachCurrentThread = Some(detach_current_thread);
+    functions.GetEnv = Some(get_env);
+    functions.AttachCurrentThreadAsDaemon = Some(attach_current_thread_as_daemon);
+    return functions;
+}
+
+pub(crate) fn is_supported_version(version: jint) -> bool {
+    return matches!(
+        version,
+        JNI_VERSION_1_1 | JNI_VERSION_1_2 | JNI_VERSION_1_4 | JNI_VERSION_1_6
+    );
+}
+
+/// Unloads the VM from the calling thread, which is attached first if it is
+/// not. Threads are not interrupted, so no other thread may still run Java
+/// code, and the VM cannot be used afterwards.
+unsafe extern "system" fn destroy_java_vm(java_vm: *mut jni::sys::JavaVM) -> jint {
+    let vm = JavaVMWrapper::from_raw_vm(java_vm).vm();
+    let thread = Thread::current();
+    if thread.is_not_null() && thread.vm_ptr() != vm {
+        return JNI_ERR;
+    }
+    Thread::detach_current_thread();
+    #[cfg(feature = "invocation-api")]
+    created::clear(vm);
+    vm.destroy();
+    return JNI_OK;
+}
+
+unsafe extern "system" fn attach_current_thread(
+    java_vm: *mut jni::sys::JavaVM,
+    penv: *mut *mut c_void,
+    args: *mut c_void,
+) -> jint {
+    return attach(java_vm, penv, args, false);
+}
+
+unsafe extern "system" fn attach_current_thread_as_daemon(
+    java_vm: *mut jni::sys::JavaVM,
+    penv: *mut *mut c_void,
+    args: *mut c_void,
+) -> jint {
+    return attach(java_vm, penv, args, true);
+}
+
+/// Attaches the calling thread with a new `java.lang.Thread` in the group of
+/// `args`, or in the main thread group without one. Attaching an attached
+/// thread again only returns its env. The name in `args` is not used, the
+/// thread gets the name `Thread` gives it.
+unsafe fn attach(
+    java_vm: *mut jni::sys::JavaVM,
+    penv: *mut *mut c_void,
+    args: *mut c_void,
+    is_daemon: bool,
+) -> jint {
+    let vm = JavaVMWrapper::from_raw_vm(java_vm).vm();
+    let mut thread_group = vm.shared_objs().java_lang_thread_group;
+    if !args.is_null() {
+        let args = &*(args as *const JavaVMAttachArgs);
+        if !is_supported_version(args.version) {
+            return JNI_EVERSION;
+        }
+        if !args.group.is_null() {
+            thread_group = ObjectPtr::from_raw(args.group as _);
+        }
+    }
+    let mut thread = Thread::current();
+    if thread.is_null() {
+        if !vm.cfg.sandbox.allows_thread(vm.thread_mgr.thread_count()) {
+            return JNI_ERR;
+        }
+        Thread::attach_current_thread(vm.as_ref());
+        thread = Thread::current();
+        Thread::create_jthread_and_bind(thread, thread_group, is_daemon);
+    } else if thread.vm_ptr() != vm {
+        return JNI_ERR;
+    }
+    *penv = thread.jni_env_mut().as_handle() as *mut c_void;
+    return JNI_OK;
+}
+
+/// Detaches the calling thread, which must not be running a native called
+/// from Java. Detaching a thread that is not attached does nothing.
+unsafe extern "system" fn detach_current_thread(java_vm: *mut jni::sys::JavaVM) -> jint {
+    let vm = JavaVMWrapper::from_raw_vm(java_vm).vm();
+    let thread = Thread::current();
+    if thread.is_null() {
+        return JNI_OK;
+    }
+    if thread.vm_ptr() != vm || thread.interpreter().stack().frame().is_not_null() {
+        return JNI_ERR;
+    }
+    Thread::detach_current_thread();
+    return JNI_OK;
+}
+
+unsafe extern "system" fn get_env(
+    java_vm: *mut jni::sys::JavaVM,
+    penv: *mut *mut c_void,
+    version: jint,
+) -> jint {
+    let vm = JavaVMWrapper::from_raw_vm(java_vm).vm();
+    let mut thread = Thread::current();
+    if thread.is_null() || thread.vm_ptr() != vm {
+        *penv = std::ptr::null_mut();
+        return JNI_EDETACHED;
+    }
+    if !is_supported_version(version) {
+        *penv = std::ptr::null_mut();
+        return JNI_EVERSION;
+    }
+    *penv = thread.jni_env_mut().as_handle() as *mut c_void;
+    return JNI_OK;
+}
+
+/// A `JavaVMOption` of `JNI_CreateJavaVM`.
+#[cfg(feature = "invocation-api")]
+fn apply_option(cfg: &mut crate::vm::VMConfig, option: &str, class_path: &mut String) -> bool {
+    if let Some(path) = option.strip_prefix("-Djava.class.path=") {
+        *class_path = path.to_string();
+    } else if let Some(home) = option.strip_prefix("-Drsvm.home=") {
+        cfg.set_rsvm_home(home);
+    } else if option.starts_with("-XX:") {
+        return cfg.set_flag(option).is_ok();
+    } else if option == "-verbose:class" {
+        cfg.verbose_class = true;
+    } else if !matches!(option, "vfprintf" | "exit" | "abort") {
+        return false;
+    }
+    return true;
+}
+
+/// Creates and initializes a VM on the calling thread, which becomes its
+/// main thread. `args` is a `JavaVMInitArgs` whose options may set the class
+/// path with `-Djava.class.path=`, the rsvm home with `-Drsvm.home=`, any
+/// `-XX:` flag and `-verbose:class`. One VM may exist at a time.
+#[cfg(feature = "invocation-api")]
+unsafe fn create_java_vm(
+    pvm: *mut *mut jni::sys::JavaVM,
+    penv: *mut *mut c_void,
+    args: *mut c_void,
+) -> jint {
+    use jni::sys::{JavaVMInitArgs, JNI_EEXIST, JNI_EINVAL};
+    use std::ffi::CStr;
+
+    if args.is_null() {
+        return JNI_EINVAL;
+    }
+    let args = &*(args as *const JavaVMInitArgs);
+    if !is_supported_version(args.version) {
+        return JNI_EVERSION;
+    }
+    if Thread::current().is_not_null() {
+        return JNI_ERR;
+    }
+    let mut cfg = crate::vm::VMConfig::default();
+    let mut class_path = ".".to_string();
+    let options = if args.options.is_null() {
+        &[]
+    } else {
+        std::slice::from_raw_parts(args.options, args.nOptions.max(0) as usize)
+    };
+    for option in options {
+        let option = CStr::from_ptr(option.optionString).to_string_lossy();
+        if !apply_option(&mut cfg, &option, &mut class_path) && args.ignoreUnrecognized == 0 {
+            eprintln!("Unrecognized option: {}", option);
+            return JNI_EINVAL;
+        }
+    }
+    // the class path is built on the rsvm home, whichever option came first
+    cfg.set_class_path(&class_path);
+
+    if !created::reserve() {
+        return JNI_EEXIST;
+    }
+    let mut vm = crate::vm::VM::new(&cfg);
+    if let Err(e) = vm.init() {
+        eprintln!("Error: {}", e);
+        Thread::detach_current_thread();
+        created::clear(VMPtr::null());
+        return JNI_ERR;
+    }
+    created::set(vm);
+    *pvm = vm.jni().java_vm.as_raw();
+    *penv = Thread::current().jni_env_mut().as_handle() as *mut c_void;
+    return JNI_OK;
+}
+
+/// The VM created with `JNI_CreateJavaVM`, if any.
+#[cfg(feature = "invocation-api")]
+mod created {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::vm::VMPtr;
+
+    /// The address of the VM, or `CREATING` while it is initialized.
+    static CREATED_VM: AtomicUsize = AtomicUsize::new(0);
+    const CREATING: usize = 1;
+
+    pub(super) fn reserve() -> bool {
+        return CREATED_VM
+            .compare_exchange(0, CREATING, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok();
+    }
+
+    pub(super) fn set(vm: VMPtr) {
+        CREATED_VM.store(vm.as_usize(), Ordering::Release);
+    }
+
+    /// Forgets the VM created, or the one reserved if `vm` is null.
+    pub(super) fn clear(vm: VMPtr) {
+        let expected = if vm.is_null() {
+            CREATING
+        } else {
+            vm.as_usize()
+        };
+        let _ = CREATED_VM.compare_exchange(expected, 0, Ordering::AcqRel, Ordering::Acquire);
+    }
+
+    pub(super) fn get() -> Option<VMPtr> {
+        return match CREATED_VM.load(Ordering::Acquire) {
+            0 | CREATING => None,
+            addr => Some(VMPtr::from_usize(addr)),
+        };
+    }
+}
+
+/// Fills in the `JavaVMInitArgs` at `args` with the defaults of its version.
+#[cfg(feature = "invocation-api")]
+#[no_mangle]
+pub unsafe extern "system" fn JNI_GetDefaultJavaVMInitArgs(args: *mut c_void) -> jint {
+    let args = &mut *(args as *mut jni::sys::JavaVMInitArgs);
+    if !is_supported_version(args.version) {
+        args.version = JNI_VERSION;
+        return JNI_EVERSION;
+    }
+    return JNI_OK;
+}
+
+#[cfg(feature = "invocation-api")]
+#[no_mangle]
+pub unsafe extern "system" fn JNI_CreateJavaVM(
+    pvm: *mut *mut jni::sys::JavaVM,
+    penv: *mut *mut c_void,
+    args: *mut c_void,
+) -> jint {
+    return create_java_vm(pvm, penv, args);
+}
+
+#[cfg(feature = "invocation-api")]
+#[no_mangle]
+pub unsafe extern "system" fn JNI_GetCreatedJavaVMs(
+    vm_buf: *mut *mut jni::sys::JavaVM,
+    buf_len: jint,
+    n_vms: *mut jint,
+) -> jint {
+    let vm = created::get();
+    if !n_vms.is_null() {
+        *n_vms = vm.is_some() as jint;
+    }
+    if let Some(vm) = vm {
+        if buf_len > 0 {
+            *vm_buf = vm.jni().java_vm.as_raw();
+        }
+    }
+    return JNI_OK;
+}
@@ -0,0 +1,58 @@
+use jni::{
+    objects::JClass,
+    sys::{jboolean, jstring, JNI_FALSE, JNI_TRUE},
+    JNIEnv,
+};
+
+use crate::{
+    os::{self, StdStream},
+    thread::Thread,
+};
+
+use super::jni::JNIEnvWrapper;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_Console_encoding<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jstring {
+    // null lets Console fall back to the default charset
+    if !os::is_tty(StdStream::Out) {
+        return std::ptr::null_mut();
+    }
+    return match os::console_encoding() {
+        Some(encoding) => {
+            let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+            vm.get_jstr_from_symbol(vm.get_symbol(&encoding), Thread::current())
+                .as_raw_ptr() as _
+        }
+        None => std::ptr::null_mut(),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_Console_echo<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    on: jboolean,
+) -> jboolean {
+    return match os::set_console_echo(on == JNI_TRUE) {
+        Ok(was_on) => was_on as jboolean,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_Console_istty<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jboolean {
+    return if os::is_tty(StdStream::In) && os::is_tty(StdStream::Out) {
+        JNI_TRUE
+    } else {
+        JNI_FALSE
+    };
+}
@@ -1,9 +1,13 @@
+use std::{fs::File, mem::ManuallyDrop};
+
 use jni::{
     objects::JClass,
     sys::{jint, jlong},
     JNIEnv,
 };
 
+use crate::{vm::VM, ObjectPtr};
+
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_java_io_FileDescriptor_initIDs<'local>(
@@ -33,3 +37,89 @@ pub extern "system" fn Java_java_io_FileDescriptor_set<'local>(
     }
     return -1;
 }
+
+/// The file of the `java.io.FileDescriptor` `fd`, left open when the `File`
+/// is dropped.
+pub(super) fn file_of(fd: ObjectPtr, vm: &VM) -> ManuallyDrop<File> {
+    let fd_cls_info = vm
+        .shared_objs()
+        .class_infos()
+        .java_io_file_descriptor_info();
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::fd::FromRawFd;
+
+        let fd = fd_cls_info.get_fd(fd);
+        return ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::io::FromRawHandle;
+
+        let handle = fd_cls_info.get_handle(fd);
+        return ManuallyDrop::new(unsafe { File::from_raw_handle(handle as _) });
+    }
+}
+
+/// Gives `file` to the `java.io.FileDescriptor` `fd`, which closes it in
+/// [`close_file`].
+pub(super) fn set_file(fd: ObjectPtr, file: File, vm: &VM) {
+    let fd_cls_info = vm
+        .shared_objs()
+        .class_infos()
+        .java_io_file_descriptor_info();
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::fd::IntoRawFd;
+
+        fd_cls_info.set_fd(fd, file.into_raw_fd());
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::io::IntoRawHandle;
+
+        fd_cls_info.set_handle(fd, file.into_raw_handle() as _);
+    }
+}
+
+/// Closes the file of the `java.io.FileDescriptor` `fd` and marks `fd`
+/// invalid. Closing an invalid `fd` does nothing.
+pub(super) fn close_file(fd: ObjectPtr, vm: &VM) {
+    let fd_cls_info = vm
+        .shared_objs()
+        .class_infos()
+        .java_io_file_descriptor_info();
+    #[cfg(target_family = "unix")]
+    {
+        if fd_cls_info.get_fd(fd) == -1 {
+            return;
+        }
+        let file = file_of(fd, vm);
+        fd_cls_info.set_fd(fd, -1);
+        drop(ManuallyDrop::into_inner(file));
+    }
+    #[cfg(target_os = "windows")]
+    {
+        if fd_cls_info.get_handle(fd) == -1 {
+            return;
+        }
+        let file = file_of(fd, vm);
+        fd_cls_info.set_handle(fd, -1);
+        drop(ManuallyDrop::into_inner(file));
+    }
+}
+
+/// The `fd` of the `java.io.FileDescriptor` `fd`, the descriptor of its
+/// socket or pipe on every platform.
+pub(super) fn fd_val(fd: ObjectPtr, vm: &VM) -> jint {
+    return match vm.get_field(fd, "fd") {
+        Ok(fd_val) => fd_val,
+        Err(e) => todo!("{:?}", e),
+    };
+}
+
+pub(super) fn set_fd_val(fd: ObjectPtr, fd_val: jint, vm: &VM) {
+    if let Err(e) = vm.set_field(fd, "fd", fd_val) {
+        todo!("{:?}", e);
+    }
+}
@@ -1,4 +1,26 @@
-use jni::{objects::JClass, JNIEnv};
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+};
+
+use jni::{
+    objects::{JByteArray, JClass, JObject, JString as JNIString},
+    sys::{jint, jlong},
+    JNIEnv,
+};
+
+use crate::{
+    object::string::{JString, JStringPtr},
+    sandbox::ResourceAccess,
+    vm::VM,
+    ObjectPtr,
+};
+
+use super::{
+    java_io_FileDescriptor::{close_file, file_of, set_file},
+    java_io_RandomAccessFile::byte_range,
+    jni::JNIEnvWrapper,
+};
 
 #[allow(non_snake_case)]
 #[no_mangle]
@@ -7,3 +29,126 @@ pub extern "system" fn Java_java_io_FileInputStream_initIDs<'local>(
     _cls_ref: JClass<'local>,
 ) {
 }
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_FileInputStream_open<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    path: JNIString<'local>,
+) {
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    if path.is_null() {
+        todo!("throw NullPointerException");
+    }
+    let path = JString::to_rust_string(JStringPtr::from_raw(path.as_raw() as _), vm.as_ref());
+    let access = ResourceAccess::OpenFile {
+        path: path.clone().into(),
+        write: false,
+    };
+    if !vm.cfg.sandbox.check_access(access, env.thread()) {
+        return;
+    }
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(_e) => todo!("throw FileNotFoundException: {}", path),
+    };
+    if file.metadata().map_or(false, |metadata| metadata.is_dir()) {
+        todo!("throw FileNotFoundException: {} (Is a directory)", path);
+    }
+    set_file(fd_of(&obj_ref, vm.as_ref()), file, vm.as_ref());
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_FileInputStream_read0<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    let mut byte = [0u8; 1];
+    return match file.read(&mut byte) {
+        Ok(0) => -1,
+        Ok(_) => byte[0] as jint,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_FileInputStream_readBytes<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    bytes: JByteArray<'local>,
+    off: jint,
+    len: jint,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let bytes = byte_range(&bytes, off, len);
+    if bytes.is_empty() {
+        return 0;
+    }
+    let mut file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    return match file.read(bytes) {
+        Ok(0) => -1,
+        Ok(read) => read as jint,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_FileInputStream_skip<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    n: jlong,
+) -> jlong {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    let (Ok(pos), Ok(new_pos)) = (file.stream_position(), file.seek(SeekFrom::Current(n))) else {
+        todo!("throw IOException: Illegal seek");
+    };
+    return new_pos as jlong - pos as jlong;
+}
+
+/// The bytes left in a regular file, nothing is known to be available in
+/// another stream.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_FileInputStream_available<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    let len = match file.metadata() {
+        Ok(metadata) if metadata.is_file() => metadata.len(),
+        Ok(_) => return 0,
+        Err(_e) => todo!("throw IOException"),
+    };
+    return match file.stream_position() {
+        Ok(pos) => len.saturating_sub(pos).min(jint::MAX as u64) as jint,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_FileInputStream_close0<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    close_file(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+}
+
+/// The `java.io.FileDescriptor` of the `FileInputStream` `obj_ref`.
+fn fd_of(obj_ref: &JObject, vm: &VM) -> ObjectPtr {
+    let obj_ref = ObjectPtr::from_raw(obj_ref.as_raw() as _);
+    return match vm.get_field(obj_ref, "fd") {
+        Ok(fd) => fd,
+        Err(e) => todo!("{:?}", e),
+    };
+}
@@ -1,3 +1,5 @@
+use std::path::{Component, Path, PathBuf};
+
 use jni::{objects::JClass, sys::{jint, jobject}, JNIEnv};
 
 use crate::{handle::Handle, object::Object, thread::Thread};
@@ -7,14 +9,96 @@ use super::jni::JNIEnvWrapper;
 /// see java.lang.FileSystem#BA_EXISTS
 pub(super) const FS_BA_EXISTS: jint = 0x01;
 /// see java.lang.FileSystem#BA_REGULAR
-#[allow(unused)]
 pub(super) const FS_BA_REGULAR: jint = 0x02;
 /// see java.lang.FileSystem#BA_DIRECTORY
 pub(super) const FS_BA_DIRECTORY: jint = 0x04;
 /// see java.lang.FileSystem#BA_HIDDEN
-#[allow(unused)]
+#[cfg_attr(not(windows), allow(unused))]
 pub(super) const FS_BA_HIDDEN: jint = 0x08;
 
+/// The `BA_*` attributes of the file at `path` after following symlinks, 0
+/// if it does not exist. Unix leaves `BA_HIDDEN` to `UnixFileSystem`, which
+/// hides the names starting with a dot.
+pub(super) fn boolean_attributes(path: &str) -> jint {
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_e) => return 0,
+    };
+    // like WinNTFileSystem, anything that is not a directory is regular on
+    // Windows, while unix only counts regular files
+    let mut attrs = FS_BA_EXISTS;
+    if metadata.is_dir() {
+        attrs |= FS_BA_DIRECTORY;
+    } else if metadata.is_file() || cfg!(windows) {
+        attrs |= FS_BA_REGULAR;
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        if metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0 {
+            attrs |= FS_BA_HIDDEN;
+        }
+    }
+    return attrs;
+}
+
+/// The canonical form of the absolute `path`, like `canonicalize_md.c`: the
+/// longest prefix of `path` that exists has its symlinks resolved, and on
+/// Windows the case of its names corrected. The names after it, which do not
+/// exist, are appended, and `.` and `..` are removed.
+pub(super) fn canonicalize(path: &str) -> PathBuf {
+    let components: Vec<Component> = Path::new(path).components().collect();
+    for len in (1..=components.len()).rev() {
+        let prefix: PathBuf = components[..len].iter().collect();
+        if let Ok(resolved) = std::fs::canonicalize(prefix) {
+            let mut canonical = without_verbatim_prefix(resolved);
+            canonical.extend(&components[len..]);
+            return collapse(&canonical);
+        }
+    }
+    return collapse(Path::new(path));
+}
+
+/// Removes `.` and `..` from `path` without looking at the file system. A
+/// `..` of the root is the root.
+fn collapse(path: &Path) -> PathBuf {
+    let mut collapsed = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                collapsed.pop();
+            }
+            component => collapsed.push(component),
+        }
+    }
+    return collapsed;
+}
+
+/// Turns the `\\?\` paths returned by `canonicalize` on Windows back into
+/// the usual `C:\` and `\\server\share` forms.
+#[cfg(windows)]
+fn without_verbatim_prefix(path: PathBuf) -> PathBuf {
+    let path_str = match path.to_str() {
+        Some(path_str) => path_str,
+        None => return path,
+    };
+    if let Some(unc) = path_str.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{}", unc));
+    }
+    if let Some(local) = path_str.strip_prefix(r"\\?\") {
+        return PathBuf::from(local);
+    }
+    return path;
+}
+
+#[cfg(not(windows))]
+fn without_verbatim_prefix(path: PathBuf) -> PathBuf {
+    return path;
+}
+
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_java_io_FileSystem_getFileSystem<'local>(
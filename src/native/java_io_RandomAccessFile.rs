@@ -0,0 +1,263 @@
+use std::{
+    fs::OpenOptions,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+use jni::{
+    objects::{JByteArray, JClass, JObject, JString as JNIString},
+    sys::{jint, jlong},
+    JNIEnv,
+};
+
+use crate::{
+    object::{
+        array::JByteArrayPtr,
+        string::{JString, JStringPtr},
+    },
+    sandbox::ResourceAccess,
+    vm::VM,
+    ObjectPtr,
+};
+
+use super::{
+    java_io_FileDescriptor::{close_file, file_of, set_file},
+    jni::JNIEnvWrapper,
+};
+
+/// see java.io.RandomAccessFile#O_RDWR
+const O_RDWR: jint = 2;
+/// see java.io.RandomAccessFile#O_SYNC
+#[cfg_attr(not(unix), allow(unused))]
+const O_SYNC: jint = 4;
+/// see java.io.RandomAccessFile#O_DSYNC
+#[cfg_attr(not(unix), allow(unused))]
+const O_DSYNC: jint = 8;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_RandomAccessFile_initIDs<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_RandomAccessFile_open<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    path: JNIString<'local>,
+    mode: jint,
+) {
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    if path.is_null() {
+        todo!("throw NullPointerException");
+    }
+    let path = JString::to_rust_string(JStringPtr::from_raw(path.as_raw() as _), vm.as_ref());
+    let access = ResourceAccess::OpenFile {
+        path: path.clone().into(),
+        write: mode & O_RDWR != 0,
+    };
+    if !vm.cfg.sandbox.check_access(access, env.thread()) {
+        return;
+    }
+    let mut options = OpenOptions::new();
+    options.read(true);
+    if mode & O_RDWR != 0 {
+        options.write(true).create(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        if mode & O_SYNC != 0 {
+            options.custom_flags(libc::O_SYNC);
+        } else if mode & O_DSYNC != 0 {
+            options.custom_flags(libc::O_DSYNC);
+        }
+    }
+    let file = match options.open(path) {
+        Ok(file) => file,
+        Err(_e) => todo!("throw FileNotFoundException"),
+    };
+    if file.metadata().map_or(false, |metadata| metadata.is_dir()) {
+        todo!("throw FileNotFoundException: Is a directory");
+    }
+    set_file(fd_of(&obj_ref, vm.as_ref()), file, vm.as_ref());
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_RandomAccessFile_read0<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    let mut byte = [0u8; 1];
+    return match file.read(&mut byte) {
+        Ok(0) => -1,
+        Ok(_) => byte[0] as jint,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_RandomAccessFile_readBytes0<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    bytes: JByteArray<'local>,
+    off: jint,
+    len: jint,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let bytes = byte_range(&bytes, off, len);
+    if bytes.is_empty() {
+        return 0;
+    }
+    let mut file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    return match file.read(bytes) {
+        Ok(0) => -1,
+        Ok(read) => read as jint,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_RandomAccessFile_write0<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    byte: jint,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    if let Err(_e) = file.write_all(&[byte as u8]) {
+        todo!("throw IOException");
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_RandomAccessFile_writeBytes0<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    bytes: JByteArray<'local>,
+    off: jint,
+    len: jint,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let bytes = byte_range(&bytes, off, len);
+    let mut file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    if let Err(_e) = file.write_all(bytes) {
+        todo!("throw IOException");
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_RandomAccessFile_getFilePointer<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+) -> jlong {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    return match file.stream_position() {
+        Ok(pos) => pos as jlong,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_RandomAccessFile_seek<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    pos: jlong,
+) {
+    if pos < 0 {
+        todo!("throw IOException: Negative seek offset");
+    }
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    if let Err(_e) = file.seek(SeekFrom::Start(pos as u64)) {
+        todo!("throw IOException");
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_RandomAccessFile_length<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+) -> jlong {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    return match file.metadata() {
+        Ok(metadata) => metadata.len() as jlong,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_RandomAccessFile_setLength<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    new_length: jlong,
+) {
+    if new_length < 0 {
+        todo!("throw IOException: Invalid argument");
+    }
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut file = file_of(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+    let pos = match file.stream_position() {
+        Ok(pos) => pos,
+        Err(_e) => todo!("throw IOException"),
+    };
+    if let Err(_e) = file.set_len(new_length as u64) {
+        todo!("throw IOException");
+    }
+    // a file pointer past the new end moves to it
+    if pos > new_length as u64 {
+        if let Err(_e) = file.seek(SeekFrom::Start(new_length as u64)) {
+            todo!("throw IOException");
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_io_RandomAccessFile_close0<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    close_file(fd_of(&obj_ref, vm.as_ref()), vm.as_ref());
+}
+
+/// The `java.io.FileDescriptor` of the `RandomAccessFile` `obj_ref`.
+fn fd_of(obj_ref: &JObject, vm: &VM) -> ObjectPtr {
+    let obj_ref = ObjectPtr::from_raw(obj_ref.as_raw() as _);
+    return match vm.get_field(obj_ref, "fd") {
+        Ok(fd) => fd,
+        Err(e) => todo!("{:?}", e),
+    };
+}
+
+/// `bytes[off..off + len]` of a Java byte array.
+pub(super) fn byte_range<'a>(bytes: &JByteArray, off: jint, len: jint) -> &'a mut [u8] {
+    if bytes.is_null() {
+        todo!("throw NullPointerException");
+    }
+    let bytes = JByteArrayPtr::from_raw(bytes.as_raw() as _);
+    if off < 0 || len < 0 || off as i64 + len as i64 > bytes.length() as i64 {
+        todo!("throw IndexOutOfBoundsException");
+    }
+    let data = bytes.data().offset(off as isize);
+    return unsafe {
+        std::slice::from_raw_parts_mut(data.as_mut_raw_ptr() as *mut u8, len as usize)
+    };
+}
@@ -5,7 +5,7 @@ use jni::{
 };
 
 use crate::{
-    native::java_io_FileSystem::{FS_BA_DIRECTORY, FS_BA_EXISTS},
+    native::java_io_FileSystem::{boolean_attributes, canonicalize},
     object::string::{JString, JStringPtr},
     thread::Thread,
     ObjectPtr,
@@ -43,16 +43,7 @@ pub extern "system" fn Java_java_io_UnixFileSystem_getBooleanAttributes0<'local>
         "Java_java_io_UnixFileSystem_getBooleanAttributes0 path {}",
         path
     );
-    match std::fs::metadata(path) {
-        Ok(metadata) => {
-            let mut attrs = FS_BA_EXISTS;
-            if metadata.is_dir() {
-                attrs |= FS_BA_DIRECTORY;
-            }
-            return attrs;
-        }
-        Err(_e) => return 0,
-    };
+    return boolean_attributes(&path);
 }
 
 #[allow(non_snake_case)]
@@ -65,20 +56,20 @@ pub extern "system" fn Java_java_io_UnixFileSystem_canonicalize0<'local>(
     let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
     let path = JStringPtr::from_raw(path.as_raw() as _);
     let rs_path = JString::to_rust_string(path, vm.as_ref());
-    if let Ok(canon_path) = std::path::Path::new(&rs_path).canonicalize() {
-        let canon_path = canon_path.to_str().unwrap();
-        if canon_path == rs_path {
-            return path.as_raw_ptr() as _;
-        }
-        let canon_path = JString::str_to_utf16(canon_path);
-        let thread = Thread::current();
-        let canon_path = vm
-            .shared_objs()
-            .class_infos()
-            .java_lang_string_info()
-            .create_with_utf16(&canon_path, thread);
-        return canon_path.get_ptr().as_raw_ptr() as _;
-    } else {
-        todo!("throw IOException");
+    let canon_path = canonicalize(&rs_path);
+    let canon_path = match canon_path.to_str() {
+        Some(canon_path) => canon_path,
+        None => todo!("throw IOException"),
+    };
+    if canon_path == rs_path {
+        return path.as_raw_ptr() as _;
     }
+    let canon_path = JString::str_to_utf16(canon_path);
+    let thread = Thread::current();
+    let canon_path = vm
+        .shared_objs()
+        .class_infos()
+        .java_lang_string_info()
+        .create_with_utf16(&canon_path, thread);
+    return canon_path.get_ptr().as_raw_ptr() as _;
 }
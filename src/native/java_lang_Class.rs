@@ -14,7 +14,8 @@ use crate::{
         string::JStringPtr,
     },
     thread::Thread,
-    JArray, JClassPtr,
+    vm::VM,
+    JArray, JClassPtr, ObjectPtr,
 };
 
 #[allow(non_snake_case)]
@@ -33,7 +34,6 @@ pub extern "system" fn Java_java_lang_Class_forName0<'local>(
     name: JString<'local>,
     initialize: jboolean,
     _loader: JObject<'local>,
-    _caller: JObject<'local>,
 ) -> jclass {
     type InternalJString = crate::object::string::JString;
 
@@ -56,11 +56,21 @@ pub extern "system" fn Java_java_lang_Class_forName0<'local>(
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_java_lang_Class_isInstance<'local>(
-    _env: JNIEnv<'local>,
-    _obj_ref: JObject<'local>,
-    _obj: JObject<'local>,
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    obj: JObject<'local>,
 ) -> jboolean {
-    todo!();
+    if obj.is_null() {
+        return 0;
+    }
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let obj_ref = JClassPtr::from_raw(obj_ref.as_raw() as _);
+    let obj = ObjectPtr::from_raw(obj.as_raw() as _);
+    return if obj_ref.is_assignable_from(obj.jclass(), vm) {
+        1
+    } else {
+        0
+    };
 }
 
 #[allow(non_snake_case)]
@@ -140,9 +150,10 @@ pub extern "system" fn Java_java_lang_Class_getName0<'local>(
     let wrapper = JNIEnvWrapper::from_raw_env(env.get_raw());
     let vm = wrapper.vm();
     let obj_ref = JClassPtr::from_raw(obj_ref.as_raw() as _);
-
+    // the binary name, e.g. `java.lang.String` or `[Ljava.lang.String;`
+    let name = obj_ref.binary_name();
     return vm
-        .get_jstr_from_symbol(obj_ref.name(), Thread::current())
+        .get_jstr_from_symbol(vm.get_symbol(&name), Thread::current())
         .as_raw_ptr() as _;
 }
 
@@ -202,7 +213,7 @@ pub extern "system" fn Java_java_lang_Class_getModifiers<'local>(
     }
     return JClassPtr::from_raw(obj_ref.as_raw() as _)
         .class_data()
-        .access_flags() as jint;
+        .modifiers() as jint;
 }
 
 #[allow(non_snake_case)]
@@ -344,7 +355,7 @@ pub extern "system" fn Java_java_lang_Class_getDeclaredFields0<'local>(
         let field: FieldPtr = fields.get(idx).cast();
         let j_field = RObject::new(field_cls, thread);
 
-        let field_name = vm.get_jstr_from_symbol(field.name(), thread);
+        let field_name = vm.get_jstr_from_symbol(field.name(vm.as_ref()), thread);
         let field_sig = JStringPtr::null(); // TODO
         let slot = {
             let mut slot = RJValue::with_long_val(0);
@@ -355,7 +366,7 @@ pub extern "system" fn Java_java_lang_Class_getDeclaredFields0<'local>(
         log::trace!(
             "Java_java_lang_Class_getDeclaredFields0 {}#{}, original offset: {}, offset: {}",
             obj_ref.name().as_str(),
-            field.name().as_str(),
+            field.name(vm.as_ref()).as_str(),
             field.layout_offset(),
             slot.int_val()
         );
@@ -382,11 +393,90 @@ pub extern "system" fn Java_java_lang_Class_getDeclaredFields0<'local>(
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_java_lang_Class_getDeclaredMethods0<'local>(
-    _env: JNIEnv<'local>,
-    _obj_ref: JObject<'local>,
-    _public_only: jboolean,
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    public_only: jboolean,
 ) -> jarray {
-    todo!();
+    if obj_ref.is_null() {
+        todo!("throw NullPointerException");
+    }
+    let obj_ref = JClassPtr::from_raw(obj_ref.as_raw() as _);
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let thread = Thread::current();
+    let methods = obj_ref.class_data().methods();
+    let ctor_init = vm.shared_objs().symbols().ctor_init;
+    let reflect_method_info = vm
+        .shared_objs()
+        .class_infos()
+        .java_lang_reflect_method_info();
+    let mut filtered_methods = Vec::new();
+
+    for idx in 0..methods.length() {
+        let method: MethodPtr = methods.get(idx).cast();
+        // bridge and synthetic methods stay, Method.isBridge tells them apart
+        if method.name() == ctor_init || method.name().as_str() == "<clinit>" {
+            continue;
+        }
+        if public_only == 1 && method.is_not_public() {
+            continue;
+        }
+        let param_types_arr = if method.param_count() > 0 {
+            let param_types_arr = JArray::new(
+                method.param_count(),
+                vm.preloaded_classes().jclass_arr_cls(),
+                thread,
+            );
+            for param_idx in 0..method.param_count() {
+                let param_type = param_type(method, param_idx, vm.as_ref());
+                param_types_arr.set(param_idx, param_type.cast());
+            }
+            param_types_arr
+        } else {
+            vm.shared_objs().empty_jcls_arr
+        };
+        let ret_type = ret_type(method, vm.as_ref());
+        let name = vm.get_jstr_from_symbol(method.name(), thread);
+        let signature = JStringPtr::null(); // TODO
+        let reflect_method = reflect_method_info.new_method(
+            obj_ref,
+            name,
+            param_types_arr,
+            ret_type,
+            vm.shared_objs().empty_jcls_arr,
+            method.access_flags() as JInt,
+            idx,
+            signature,
+            thread,
+        );
+        filtered_methods.push(reflect_method);
+    }
+    let filtered_length = filtered_methods.len() as JInt;
+    let result_arr = reflect_method_info.new_method_arr(filtered_length, thread);
+    for idx in 0..filtered_length {
+        result_arr.set(
+            idx,
+            unsafe { filtered_methods.get_unchecked(idx as usize) }.as_ptr(),
+        );
+    }
+    return result_arr.as_ptr().as_raw_ptr() as _;
+}
+
+/// The class of the parameter `idx` of `method`, whose class may not be
+/// linked yet.
+pub(super) fn param_type(method: MethodPtr, idx: JInt, vm: &VM) -> JClassPtr {
+    return match method.param_type(idx, vm) {
+        Ok(class) => class,
+        Err(_) => todo!("throw NoClassDefFoundError"),
+    };
+}
+
+/// The class of the return type of `method`, whose class may not be linked
+/// yet.
+pub(super) fn ret_type(method: MethodPtr, vm: &VM) -> JClassPtr {
+    return match method.ret_type(vm) {
+        Ok(class) => class,
+        Err(_) => todo!("throw NoClassDefFoundError"),
+    };
 }
 
 #[allow(non_snake_case)]
@@ -419,8 +509,7 @@ pub extern "system" fn Java_java_lang_Class_getDeclaredConstructors0<'local>(
             continue;
         }
         let param_types_arr = {
-            let method_params = method.params();
-            let method_params_len = method_params.length();
+            let method_params_len = method.param_count();
             if method_params_len > 0 {
                 let param_types_arr = JArray::new(
                     method_params_len,
@@ -428,7 +517,7 @@ pub extern "system" fn Java_java_lang_Class_getDeclaredConstructors0<'local>(
                     thread,
                 );
                 for idx in 0..method_params_len {
-                    param_types_arr.set(idx, method_params.get(idx));
+                    param_types_arr.set(idx, param_type(method, idx, vm.as_ref()).cast());
                 }
                 param_types_arr
             } else {
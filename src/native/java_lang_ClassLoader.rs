@@ -1,5 +1,6 @@
 use jni::{
     objects::{JClass, JObject},
+    sys::{jclass, jobject},
     JNIEnv,
 };
 use libloading::{Library, Symbol};
@@ -10,9 +11,12 @@ use crate::{
     object::{
         class::ClassData,
         method::MethodPtr,
-        prelude::{JLong, ObjectRawPtr, Ptr},
+        prelude::{JByte, JLong, ObjectRawPtr, Ptr},
         string::{JString, JStringPtr},
     },
+    thread::Thread,
+    value::JValue,
+    vm::VM,
     ObjectPtr,
 };
 
@@ -57,19 +61,181 @@ pub extern "system" fn Java_java_lang_ClassLoader_NativeLibrary_load<'local>(
     let methods = from_cls.class_data().methods();
     for idx in 0..methods.length() {
         let mut method: MethodPtr = methods.get(idx).cast();
-        if method.is_native() {
+        // natives bound with RegisterNatives keep their function
+        if method.is_native() && !method.is_registered_native() {
             let native_fn_name =
                 ClassData::get_native_fn_name(from_cls_name.as_str(), method.name().as_str());
-            unsafe {
-                if let Ok(symbol) = lib.get(native_fn_name.as_bytes()) {
-                    let symbol: Symbol<ObjectRawPtr> = symbol;
-                    if let Some(native_fn) = symbol.try_as_raw_ptr() {
-                        method.set_native_fn(Address::from_c_ptr(native_fn));
-                    }
-                } else {
-                    continue;
+            // the short name first, then the long name of an overloaded native, like JNI
+            let overloaded_fn_name = ClassData::get_overloaded_native_fn_name(
+                &native_fn_name,
+                method.descriptor().as_str(),
+            );
+            for fn_name in [native_fn_name, overloaded_fn_name] {
+                let symbol: Symbol<ObjectRawPtr> = match unsafe { lib.get(fn_name.as_bytes()) } {
+                    Ok(symbol) => symbol,
+                    Err(_) => continue,
+                };
+                if let Some(native_fn) = unsafe { symbol.try_as_raw_ptr() } {
+                    method.set_native_fn(Address::from_c_ptr(native_fn));
+                    break;
                 }
             }
         }
     }
 }
+
+/// Every class is defined by the bootstrap class loader, so a class loader
+/// has loaded a class if the bootstrap class loader has.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_ClassLoader_findLoadedClass0<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    name: JObject<'local>,
+) -> jclass {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let name = resource_name(&name, vm.as_ref()).replace('.', "/");
+    return match vm.bootstrap_class_loader.find_class(&name) {
+        Some(class) => class.as_raw_ptr() as _,
+        None => std::ptr::null_mut(),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_ClassLoader_findBootstrapClass<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    name: JObject<'local>,
+) -> jclass {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let name = resource_name(&name, vm.as_ref());
+    return match vm.bootstrap_class_loader.load_binary_name_class(&name) {
+        Ok(class) => class.as_raw_ptr() as _,
+        Err(_e) => std::ptr::null_mut(),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_ClassLoader_getBootstrapResource<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    name: JObject<'local>,
+) -> jobject {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let name = resource_name(&name, vm.as_ref());
+    return match vm.bootstrap_class_loader.find_resource(&name) {
+        Some(url) => new_url(&url, vm.as_ref()).as_raw_ptr() as _,
+        None => std::ptr::null_mut(),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_ClassLoader_getBootstrapResources<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    name: JObject<'local>,
+) -> jobject {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let vm = vm.as_ref();
+    let name = resource_name(&name, vm);
+    let urls = match vm.new_object("java.util.Vector", "()V", &[]) {
+        Ok(urls) => urls,
+        Err(e) => todo!("{:?}", e),
+    };
+    let vector_cls = urls.jclass();
+    let add_element = vm
+        .get_method(
+            vector_cls,
+            "addElement",
+            "(Ljava/lang/Object;)V",
+            Thread::current(),
+        )
+        .expect("java.util.Vector has addElement");
+    for url in vm.bootstrap_class_loader.find_resources(&name) {
+        let url = new_url(&url, vm);
+        if let Err(e) = vm.call_virtual::<JValue>(urls, add_element, &[JValue::with_obj_val(url)]) {
+            todo!("{:?}", e);
+        }
+    }
+    let elements = vm
+        .get_method(
+            vector_cls,
+            "elements",
+            "()Ljava/util/Enumeration;",
+            Thread::current(),
+        )
+        .expect("java.util.Vector has elements");
+    return match vm.call_virtual::<ObjectPtr>(urls, elements, &[]) {
+        Ok(enumeration) => enumeration.as_raw_ptr() as _,
+        Err(e) => todo!("{:?}", e),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_ClassLoader_getSystemResource<'local>(
+    env: JNIEnv<'local>,
+    cls_ref: JClass<'local>,
+    name: JObject<'local>,
+) -> jobject {
+    // every class belongs to the bootstrap class loader
+    return Java_java_lang_ClassLoader_getBootstrapResource(env, cls_ref, name);
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_ClassLoader_getSystemResources<'local>(
+    env: JNIEnv<'local>,
+    cls_ref: JClass<'local>,
+    name: JObject<'local>,
+) -> jobject {
+    return Java_java_lang_ClassLoader_getBootstrapResources(env, cls_ref, name);
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_ClassLoader_getSystemResourceAsStream<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    name: JObject<'local>,
+) -> jobject {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let vm = vm.as_ref();
+    let name = resource_name(&name, vm);
+    let bytes = match vm.bootstrap_class_loader.read_resource(&name) {
+        Some(bytes) => bytes,
+        None => return std::ptr::null_mut(),
+    };
+    let bytes: Vec<JByte> = bytes.into_iter().map(|byte| byte as JByte).collect();
+    let bytes = vm.new_byte_array(&bytes);
+    return match vm.new_object(
+        "java.io.ByteArrayInputStream",
+        "([B)V",
+        &[JValue::with_obj_val(bytes.cast())],
+    ) {
+        Ok(stream) => stream.as_raw_ptr() as _,
+        Err(e) => todo!("{:?}", e),
+    };
+}
+
+fn resource_name(name: &JObject, vm: &VM) -> String {
+    if name.is_null() {
+        todo!("throw NullPointerException");
+    }
+    return JString::to_rust_string(JStringPtr::from_raw(name.as_raw() as _), vm);
+}
+
+fn new_url(url: &str, vm: &VM) -> ObjectPtr {
+    let spec = vm.new_string(url);
+    return match vm.new_object(
+        "java.net.URL",
+        "(Ljava/lang/String;)V",
+        &[JValue::with_obj_val(spec.cast())],
+    ) {
+        Ok(url) => url,
+        Err(e) => todo!("throw MalformedURLException: {:?}", e),
+    };
+}
@@ -1,5 +1,3 @@
-use std::mem::transmute;
-
 use jni::{
     objects::JClass,
     sys::{jdouble, jlong},
@@ -13,7 +11,7 @@ pub extern "system" fn Java_java_lang_Double_doubleToRawLongBits<'local>(
     _cls_ref: JClass<'local>,
     value: jdouble,
 ) -> jlong {
-    return unsafe { transmute(value) };
+    return value.to_bits() as jlong;
 }
 
 #[allow(non_snake_case)]
@@ -21,7 +19,7 @@ pub extern "system" fn Java_java_lang_Double_doubleToRawLongBits<'local>(
 pub extern "system" fn Java_java_lang_Double_longBitsToDouble<'local>(
     _env: JNIEnv<'local>,
     _cls_ref: JClass<'local>,
-    _bits: jlong,
+    bits: jlong,
 ) -> jdouble {
-    todo!();
+    return f64::from_bits(bits as u64);
 }
@@ -1,5 +1,3 @@
-use std::mem::transmute;
-
 use jni::{
     objects::JClass,
     sys::{jfloat, jint},
@@ -13,7 +11,7 @@ pub extern "system" fn Java_java_lang_Float_floatToRawIntBits<'local>(
     _cls_ref: JClass<'local>,
     value: jfloat,
 ) -> jint {
-    return unsafe { transmute(value) };
+    return value.to_bits() as jint;
 }
 
 #[allow(non_snake_case)]
@@ -21,7 +19,7 @@ pub extern "system" fn Java_java_lang_Float_floatToRawIntBits<'local>(
 pub extern "system" fn Java_java_lang_Float_intBitsToFloat<'local>(
     _env: JNIEnv<'local>,
     _cls_ref: JClass<'local>,
-    _bits: jint,
+    bits: jint,
 ) -> jfloat {
-    todo!();
+    return f32::from_bits(bits as u32);
 }
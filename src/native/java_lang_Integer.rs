@@ -0,0 +1,41 @@
+use jni::{objects::JClass, sys::jint, JNIEnv};
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Integer_numberOfLeadingZeros<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    i: jint,
+) -> jint {
+    return i.leading_zeros() as jint;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Integer_numberOfTrailingZeros<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    i: jint,
+) -> jint {
+    return i.trailing_zeros() as jint;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Integer_bitCount<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    i: jint,
+) -> jint {
+    return i.count_ones() as jint;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Integer_reverseBytes<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    i: jint,
+) -> jint {
+    return i.swap_bytes();
+}
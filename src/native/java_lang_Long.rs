@@ -0,0 +1,45 @@
+use jni::{
+    objects::JClass,
+    sys::{jint, jlong},
+    JNIEnv,
+};
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Long_numberOfLeadingZeros<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    l: jlong,
+) -> jint {
+    return l.leading_zeros() as jint;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Long_numberOfTrailingZeros<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    l: jlong,
+) -> jint {
+    return l.trailing_zeros() as jint;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Long_bitCount<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    l: jlong,
+) -> jint {
+    return l.count_ones() as jint;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Long_reverseBytes<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    l: jlong,
+) -> jlong {
+    return l.swap_bytes();
+}
@@ -0,0 +1,109 @@
+use std::ptr::null_mut;
+
+use jni::{
+    objects::{JObject, JString},
+    sys::{jclass, jint, jobject, jobjectArray},
+    JNIEnv,
+};
+
+use crate::{object::string::JStringPtr, JArray, JClassPtr};
+
+use super::jni::JNIEnvWrapper;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_SecurityManager_getClassContext<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jobjectArray {
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    let thread = env.thread();
+    let classes = thread.interpreter().class_context(false);
+    let result = JArray::new(
+        classes.len() as _,
+        vm.preloaded_classes().jclass_arr_cls(),
+        thread,
+    );
+    for (idx, class) in classes.into_iter().enumerate() {
+        result.set(idx as _, class.cast());
+    }
+    return result.as_raw_ptr() as _;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_SecurityManager_classDepth<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    name: JString<'local>,
+) -> jint {
+    if name.is_null() {
+        todo!("throw NullPointerException");
+    }
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let name = vm
+        .get_string(JStringPtr::from_raw(name.as_raw() as _))
+        .replace('.', "/");
+    let classes = JNIEnvWrapper::from_raw_env(env.get_raw())
+        .thread()
+        .interpreter()
+        .class_context(false);
+    return match classes
+        .iter()
+        .position(|class| class.name().as_str() == name)
+    {
+        Some(depth) => depth as jint,
+        None => -1,
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_SecurityManager_classLoaderDepth0<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jint {
+    return match current_loaded_class(env) {
+        Some((depth, _class)) => depth as jint,
+        None => -1,
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_SecurityManager_currentClassLoader0<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jobject {
+    return match current_loaded_class(env) {
+        Some((_depth, class)) => class.class_loader().as_raw_ptr() as _,
+        None => null_mut(),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_SecurityManager_currentLoadedClass0<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jclass {
+    return match current_loaded_class(env) {
+        Some((_depth, class)) => class.as_raw_ptr() as _,
+        None => null_mut(),
+    };
+}
+
+/// The depth and class of the most recent method on the stack whose class
+/// was defined by a class loader rather than the bootstrap class loader, up
+/// to the first privileged caller.
+fn current_loaded_class(env: JNIEnv) -> Option<(usize, JClassPtr)> {
+    let classes = JNIEnvWrapper::from_raw_env(env.get_raw())
+        .thread()
+        .interpreter()
+        .class_context(true);
+    return classes
+        .into_iter()
+        .enumerate()
+        .find(|(_depth, class)| class.class_loader().is_not_null());
+}
@@ -8,11 +8,14 @@ use jni::{
 
 use crate::{
     classfile::class_info::JavaUtilPropertiesInfo,
+    execution::CancelReason,
     object::{
         array::{self as vm_a, JArrayPtr},
         class::JClass as InternalJClass,
         string::{JString, JStringPtr, Utf16String},
+        symbol::SymbolPtr,
     },
+    os::{self, StdStream},
     thread::ThreadPtr,
     utils,
     vm::VMPtr,
@@ -21,7 +24,7 @@ use crate::{
 
 use crate::{thread::Thread, ObjectPtr};
 
-use super::jni::JNIEnvWrapper;
+use super::jni::{JNIEnvWrapper, JNIEnvWrapperPtr};
 
 #[allow(non_snake_case)]
 #[no_mangle]
@@ -38,11 +41,9 @@ pub extern "system" fn Java_java_lang_System_setIn0<'local>(
     cls_ref: JClass<'local>,
     in_stream: JObject<'local>,
 ) {
-    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
-    let cls_ref = JClassPtr::from_raw(cls_ref.as_raw() as _);
-    let in_stream = ObjectPtr::from_raw(in_stream.as_raw() as _);
-    let (in_field, _) = cls_ref.get_field_with_name(vm.shared_objs().symbols().fd_in);
-    in_field.set_static_value(cls_ref, in_stream);
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let field_name = env.vm().shared_objs().symbols().fd_in;
+    set_std_stream(env, cls_ref, field_name, in_stream);
 }
 
 #[allow(non_snake_case)]
@@ -52,11 +53,9 @@ pub extern "system" fn Java_java_lang_System_setOut0<'local>(
     cls_ref: JClass<'local>,
     out_stream: JObject<'local>,
 ) {
-    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
-    let cls_ref = JClassPtr::from_raw(cls_ref.as_raw() as _);
-    let out_stream = ObjectPtr::from_raw(out_stream.as_raw() as _);
-    let (out_field, _) = cls_ref.get_field_with_name(vm.shared_objs().symbols().fd_out);
-    out_field.set_static_value(cls_ref, out_stream);
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let field_name = env.vm().shared_objs().symbols().fd_out;
+    set_std_stream(env, cls_ref, field_name, out_stream);
 }
 
 #[allow(non_snake_case)]
@@ -66,11 +65,27 @@ pub extern "system" fn Java_java_lang_System_setErr0<'local>(
     cls_ref: JClass<'local>,
     err_stream: JObject<'local>,
 ) {
-    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let field_name = env.vm().shared_objs().symbols().fd_err;
+    set_std_stream(env, cls_ref, field_name, err_stream);
+}
+
+/// Stores `stream` to the static field `field_name` of System, which is
+/// final and so set from native code only.
+fn set_std_stream(env: JNIEnvWrapperPtr, cls_ref: JClass, field_name: SymbolPtr, stream: JObject) {
     let cls_ref = JClassPtr::from_raw(cls_ref.as_raw() as _);
-    let err_stream = ObjectPtr::from_raw(err_stream.as_raw() as _);
-    let (err_field, _) = cls_ref.get_field_with_name(vm.shared_objs().symbols().fd_err);
-    err_field.set_static_value(cls_ref, err_stream);
+    let stream = ObjectPtr::from_raw(stream.as_raw() as _);
+    let field = match cls_ref.get_field_with_name(field_name) {
+        Ok((field, _)) => field,
+        Err(e) => {
+            env.thread()
+                .as_mut_ref()
+                .interpreter_mut()
+                .cancel_execution(CancelReason::LinkageError(e.to_string()));
+            return;
+        }
+    };
+    field.set_static_value(cls_ref, stream);
 }
 
 #[allow(non_snake_case)]
@@ -136,7 +151,18 @@ pub extern "system" fn Java_java_lang_System_arraycopy<'local>(
     let dest_cmpt_cls = dest_cls_data.component_type();
     let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
     if !dest_cmpt_cls.is_assignable_from(src_cmpt_cls, vm) {
-        todo!("throw ArrayStoreException");
+        if InternalJClass::is_primitive(src_cmpt_cls) || InternalJClass::is_primitive(dest_cmpt_cls)
+        {
+            todo!("throw ArrayStoreException");
+        }
+        // e.g. an Object[] into a String[], legal if every element is a String
+        let all_assignable = (src_pos..src_pos + length).all(|idx| {
+            let element = src.get(idx);
+            return element.is_null() || dest_cmpt_cls.is_assignable_from(element.jclass(), vm);
+        });
+        if !all_assignable {
+            todo!("throw ArrayStoreException");
+        }
     }
 
     log::trace!(
@@ -193,11 +219,17 @@ pub extern "system" fn Java_java_lang_System_initProperties<'local>(
     let props = ObjectPtr::from_raw(jni_props.as_raw() as _);
     let props_cls_info = vm.shared_objs().class_infos().java_util_properties_info();
     sys_put_file_encoding(props, props_cls_info, vm, thread);
+    sys_put_std_encodings(props, props_cls_info, vm, thread);
     sys_put_file_separator(props, props_cls_info, vm, thread);
     sys_put_path_separator(props, props_cls_info, vm, thread);
     sys_put_line_separator(props, props_cls_info, vm, thread);
+    sys_put_os_arch(props, props_cls_info, vm, thread);
+    sys_put_os_name(props, props_cls_info, vm, thread);
+    sys_put_locale(props, props_cls_info, vm, thread);
+    sys_put_arch_data_model(props, props_cls_info, vm, thread);
     sys_put_boot_lib_path(props, props_cls_info, vm, thread);
     sys_put_java_home(props, props_cls_info, vm, thread);
+    sys_put_platform(props, props_cls_info, vm, thread);
     return jni_props.as_raw();
 }
 
@@ -242,6 +274,30 @@ fn sys_put_file_encoding(
     props_cls_info.put(props, k_encoding.cast(), v_utf8.cast(), vm);
 }
 
+/// The charset of stdout and stderr when they are terminals, which
+/// `System.initializeSystemClass` gives their print streams.
+fn sys_put_std_encodings(
+    props: ObjectPtr,
+    props_cls_info: &JavaUtilPropertiesInfo,
+    vm: VMPtr,
+    thread: ThreadPtr,
+) {
+    let encoding = match os::console_encoding() {
+        Some(encoding) => encoding,
+        None => return,
+    };
+    let v_encoding = vm.get_jstr_from_symbol(vm.get_symbol(&encoding), thread);
+    for (key, stream) in [
+        ("sun.stdout.encoding", StdStream::Out),
+        ("sun.stderr.encoding", StdStream::Err),
+    ] {
+        if os::is_tty(stream) {
+            let k_encoding = vm.get_jstr_from_symbol(vm.get_symbol(key), thread);
+            props_cls_info.put(props, k_encoding.cast(), v_encoding.cast(), vm);
+        }
+    }
+}
+
 fn sys_put_file_separator(
     props: ObjectPtr,
     props_cls_info: &JavaUtilPropertiesInfo,
@@ -292,6 +348,111 @@ fn sys_put_line_separator(
     props_cls_info.put(props, k_line_separator.cast(), v_line_separator.cast(), vm);
 }
 
+/// The architecture under the name the JDK gives it, which
+/// `java.nio.Bits` checks for unaligned access.
+fn sys_put_os_arch(
+    props: ObjectPtr,
+    props_cls_info: &JavaUtilPropertiesInfo,
+    vm: VMPtr,
+    thread: ThreadPtr,
+) {
+    let k_os_arch = vm.get_jstr_from_symbol(vm.get_symbol("os.arch"), thread);
+    let v_os_arch = match std::env::consts::ARCH {
+        "x86_64" if cfg!(target_os = "macos") => "x86_64",
+        "x86_64" => "amd64",
+        "x86" if cfg!(windows) => "x86",
+        "x86" => "i386",
+        arch => arch,
+    };
+    let v_os_arch = vm.get_jstr_from_symbol(vm.get_symbol(v_os_arch), thread);
+    props_cls_info.put(props, k_os_arch.cast(), v_os_arch.cast(), vm);
+}
+
+fn sys_put_os_name(
+    props: ObjectPtr,
+    props_cls_info: &JavaUtilPropertiesInfo,
+    vm: VMPtr,
+    thread: ThreadPtr,
+) {
+    let k_os_name = vm.get_jstr_from_symbol(vm.get_symbol("os.name"), thread);
+    let v_os_name = match std::env::consts::OS {
+        "linux" => "Linux",
+        "macos" => "Mac OS X",
+        "windows" => "Windows NT (unknown)",
+        os => os,
+    };
+    let v_os_name = vm.get_jstr_from_symbol(vm.get_symbol(v_os_name), thread);
+    props_cls_info.put(props, k_os_name.cast(), v_os_name.cast(), vm);
+
+    if let Some(version) = os::version() {
+        let k_os_version = vm.get_jstr_from_symbol(vm.get_symbol("os.version"), thread);
+        let v_os_version = vm.get_jstr_from_symbol(vm.get_symbol(&version), thread);
+        props_cls_info.put(props, k_os_version.cast(), v_os_version.cast(), vm);
+    }
+}
+
+/// `user.language`, `user.script`, `user.country` and `user.variant`, from
+/// which `java.util.Locale` takes its default. Only the language is put if
+/// the environment names no locale.
+fn sys_put_locale(
+    props: ObjectPtr,
+    props_cls_info: &JavaUtilPropertiesInfo,
+    vm: VMPtr,
+    thread: ThreadPtr,
+) {
+    let locale = os::locale().unwrap_or_else(|| os::Locale {
+        language: "en".to_string(),
+        ..Default::default()
+    });
+    for (key, value) in [
+        ("user.language", &locale.language),
+        ("user.script", &locale.script),
+        ("user.country", &locale.country),
+        ("user.variant", &locale.variant),
+    ] {
+        if value.is_empty() {
+            continue;
+        }
+        let k_locale = vm.get_jstr_from_symbol(vm.get_symbol(key), thread);
+        let v_locale = vm.get_jstr_from_symbol(vm.get_symbol(value), thread);
+        props_cls_info.put(props, k_locale.cast(), v_locale.cast(), vm);
+    }
+}
+
+/// The specification and VM versions of [`VM::capabilities`], so that
+/// libraries sniffing them take the code paths of the class library here.
+///
+/// [`VM::capabilities`]: crate::vm::VM::capabilities
+fn sys_put_platform(
+    props: ObjectPtr,
+    props_cls_info: &JavaUtilPropertiesInfo,
+    vm: VMPtr,
+    thread: ThreadPtr,
+) {
+    for (key, value) in vm.capabilities().system_properties() {
+        let k_platform = vm.get_jstr_from_symbol(vm.get_symbol(key), thread);
+        let v_platform = vm.get_jstr_from_symbol(vm.get_symbol(&value), thread);
+        props_cls_info.put(props, k_platform.cast(), v_platform.cast(), vm);
+    }
+}
+
+/// `sun.arch.data.model`, the pointer width in bits.
+fn sys_put_arch_data_model(
+    props: ObjectPtr,
+    props_cls_info: &JavaUtilPropertiesInfo,
+    vm: VMPtr,
+    thread: ThreadPtr,
+) {
+    let k_data_model = vm.get_jstr_from_symbol(vm.get_symbol("sun.arch.data.model"), thread);
+    let v_data_model = if cfg!(target_pointer_width = "64") {
+        "64"
+    } else {
+        "32"
+    };
+    let v_data_model = vm.get_jstr_from_symbol(vm.get_symbol(v_data_model), thread);
+    props_cls_info.put(props, k_data_model.cast(), v_data_model.cast(), vm);
+}
+
 fn sys_put_boot_lib_path(
     props: ObjectPtr,
     props_cls_info: &JavaUtilPropertiesInfo,
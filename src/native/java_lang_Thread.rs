@@ -1,10 +1,14 @@
+use std::time::Duration;
+
 use jni::{
-    objects::JClass,
-    sys::{jint, jobject},
+    objects::{JClass, JObject},
+    sys::{jboolean, jint, jlong, jobject, JNI_FALSE},
     JNIEnv,
 };
 
-use crate::thread::Thread;
+use crate::{execution::CancelReason, thread::ThreadState, ObjectPtr};
+
+use super::jni::JNIEnvWrapper;
 
 #[allow(non_snake_case)]
 #[no_mangle]
@@ -17,10 +21,13 @@ pub extern "system" fn Java_java_lang_Thread_registerNatives<'local>(
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_java_lang_Thread_currentThread<'local>(
-    _env: JNIEnv<'local>,
+    env: JNIEnv<'local>,
     _cls_ref: JClass<'local>,
 ) -> jobject {
-    return Thread::current().jthread().as_raw_ptr() as _;
+    return JNIEnvWrapper::from_raw_env(env.get_raw())
+        .thread()
+        .jthread()
+        .as_raw_ptr() as _;
 }
 
 #[allow(non_snake_case)]
@@ -32,3 +39,99 @@ pub extern "system" fn Java_java_lang_Thread_setPriority0<'local>(
 ) {
     // TODO
 }
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Thread_isAlive<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+) -> jboolean {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let jthread = ObjectPtr::from_raw(obj_ref.as_raw() as _);
+    return vm.thread_mgr.jthreads().contains(&jthread) as jboolean;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Thread_start0<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+) {
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    let jthread = ObjectPtr::from_raw(obj_ref.as_raw() as _);
+    let thread_info = vm.shared_objs().class_infos().java_lang_thread_info();
+    let thread_cls_name = jthread.jclass().name();
+    if thread_info.is_daemon(jthread) && JDK_REFERENCE_THREADS.contains(&thread_cls_name.as_str()) {
+        // nothing is ever enqueued or finalized without a GC, so the threads
+        // java.lang.ref starts during the VM initialization have no work
+        log::trace!("left {} unstarted", thread_cls_name.as_str());
+        return;
+    }
+    if !vm.cfg.sandbox.allows_thread(vm.thread_mgr.thread_count()) {
+        env.thread()
+            .as_mut_ref()
+            .interpreter_mut()
+            .cancel_execution(CancelReason::ThreadLimit);
+        return;
+    }
+    todo!("start Java threads");
+}
+
+/// The daemon threads `java.lang.ref` starts from its class initializers.
+const JDK_REFERENCE_THREADS: [&str; 2] = [
+    "java/lang/ref/Reference$ReferenceHandler",
+    "java/lang/ref/Finalizer$FinalizerThread",
+];
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Thread_holdsLock<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    obj: JObject<'local>,
+) -> jboolean {
+    if obj.is_null() {
+        todo!("throw NullPointerException");
+    }
+    let obj = ObjectPtr::from_raw(obj.as_raw() as _);
+    return JNIEnvWrapper::from_raw_env(env.get_raw())
+        .thread()
+        .holds_lock(obj) as jboolean;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Thread_isInterrupted<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    _clear_interrupted: jboolean,
+) -> jboolean {
+    // threads cannot be interrupted, there is no interrupt0
+    return JNI_FALSE;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Thread_sleep<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    millis: jlong,
+) {
+    if millis < 0 {
+        todo!("throw IllegalArgumentException");
+    }
+    let thread = JNIEnvWrapper::from_raw_env(env.get_raw()).thread();
+    thread.set_state(ThreadState::TimedWaiting);
+    std::thread::sleep(Duration::from_millis(millis as u64));
+    thread.set_state(ThreadState::Runnable);
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Thread_yield<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+    std::thread::yield_now();
+}
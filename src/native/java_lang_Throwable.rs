@@ -0,0 +1,148 @@
+use jni::{
+    objects::JObject,
+    sys::{jint, jobject},
+    JNIEnv,
+};
+
+use crate::{
+    handle::Handle,
+    object::{
+        array::JLongArrayPtr,
+        method_table::MethodId,
+        prelude::{JLong, ObjectPtr},
+        Object,
+    },
+    runtime::stack_walker::StackFrameInfo,
+    value::JValue,
+    vm::VM,
+};
+
+use super::jni::JNIEnvWrapper;
+
+/// The `backtrace` of a throwable is a `long[]` with an entry per frame, the
+/// id of the method shifted left by 16 bits and the bci of the frame, or
+/// `NO_BCI`. The elements of the stack trace are created on demand from it.
+const NO_BCI: JLong = 0xFFFF;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Throwable_fillInStackTrace<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    _dummy: jint,
+) -> jobject {
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    let throwable = Handle::new(ObjectPtr::from_raw(obj_ref.as_raw() as _));
+    let throwable_cls = vm.preloaded_classes().throwable_cls();
+    let mut frames = env
+        .thread()
+        .interpreter()
+        .stack_frames()
+        .into_iter()
+        .peekable();
+    // leaves out filling in the trace and constructing the throwable
+    while frames
+        .next_if(|frame| {
+            frame.method().name().as_str() == "fillInStackTrace"
+                && throwable_cls.is_assignable_from(frame.method().decl_cls(), vm)
+        })
+        .is_some()
+    {}
+    while frames
+        .next_if(|frame| {
+            frame.method().name().as_str() == "<init>"
+                && frame
+                    .method()
+                    .decl_cls()
+                    .is_assignable_from(throwable.jclass(), vm)
+        })
+        .is_some()
+    {}
+    let backtrace = frames
+        .map(|frame| {
+            let bci = frame.bci().map_or(NO_BCI, JLong::from);
+            return JLong::from(frame.method().id().as_u32()) << 16 | bci;
+        })
+        .collect::<Vec<_>>();
+    let backtrace: ObjectPtr = vm.new_long_array(&backtrace).cast();
+    if let Err(e) = vm.set_field(throwable.as_ptr(), "backtrace", backtrace) {
+        todo!("{:?}", e);
+    }
+    return throwable.as_ptr().as_raw_ptr() as _;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Throwable_getStackTraceDepth<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let backtrace = backtrace_of(&obj_ref, vm.as_ref());
+    if backtrace.is_null() {
+        return 0;
+    }
+    return backtrace.length();
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_Throwable_getStackTraceElement<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    index: jint,
+) -> jobject {
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    let thread = env.thread();
+    let backtrace = backtrace_of(&obj_ref, vm.as_ref());
+    if backtrace.is_null() || index < 0 || index >= backtrace.length() {
+        todo!("throw IndexOutOfBoundsException: {}", index);
+    }
+    let entry = backtrace.get(index);
+    let method = vm
+        .method_table
+        .get(MethodId::from_u32((entry >> 16) as u32));
+    let bci = entry & NO_BCI;
+    let frame = StackFrameInfo::new(method, (bci != NO_BCI).then_some(bci as u16));
+
+    let element_class = vm
+        .bootstrap_class_loader
+        .load_class("java/lang/StackTraceElement")
+        .unwrap();
+    let ctor = vm
+        .get_method(
+            element_class,
+            "<init>",
+            "(Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;I)V",
+            thread,
+        )
+        .unwrap();
+    let declaring_class = Handle::new(vm.new_string(&frame.declaring_class()));
+    let method_name = Handle::new(vm.new_string(&frame.method_name()));
+    let file_name = match frame.file_name() {
+        Some(file_name) => Handle::new(vm.new_string(&file_name)),
+        None => Handle::null(),
+    };
+    let element = Handle::new(Object::new(element_class, thread));
+    vm.call_obj_void(
+        element.as_ptr(),
+        ctor,
+        &[
+            JValue::with_obj_val(declaring_class.as_ptr().cast()),
+            JValue::with_obj_val(method_name.as_ptr().cast()),
+            JValue::with_obj_val(file_name.as_ptr().cast()),
+            JValue::with_int_val(frame.line_number()),
+        ],
+    );
+    return element.as_ptr().as_raw_ptr() as _;
+}
+
+fn backtrace_of(obj_ref: &JObject, vm: &VM) -> JLongArrayPtr {
+    let throwable = ObjectPtr::from_raw(obj_ref.as_raw() as _);
+    return match vm.get_field::<ObjectPtr>(throwable, "backtrace") {
+        Ok(backtrace) => backtrace.cast(),
+        Err(e) => todo!("{:?}", e),
+    };
+}
@@ -0,0 +1,36 @@
+use jni::{
+    objects::JClass,
+    sys::{jint, jobject},
+    JNIEnv,
+};
+
+use crate::{object::array::JArray, thread::Thread, JClassPtr};
+
+use super::jni::JNIEnvWrapper;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_lang_reflect_Array_newArray<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    component_type: JClass<'local>,
+    length: jint,
+) -> jobject {
+    if component_type.is_null() {
+        todo!("throw NullPointerException");
+    }
+    if length < 0 {
+        todo!("throw NegativeArraySizeException");
+    }
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let component_type = JClassPtr::from_raw(component_type.as_raw() as _);
+    let array_cls = match vm.bootstrap_class_loader.get_or_create_array_class(
+        component_type,
+        1,
+        Thread::current(),
+    ) {
+        Ok(array_cls) => array_cls,
+        Err(_e) => todo!("throw IllegalArgumentException"),
+    };
+    return JArray::new(length, array_cls, Thread::current()).as_raw_ptr() as _;
+}
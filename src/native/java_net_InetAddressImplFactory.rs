@@ -0,0 +1,15 @@
+use jni::{
+    objects::JClass,
+    sys::{jboolean, JNI_FALSE},
+    JNIEnv,
+};
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_InetAddressImplFactory_isIPv6Supported<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jboolean {
+    // the sockets of os::aio are IPv4 only
+    return JNI_FALSE;
+}
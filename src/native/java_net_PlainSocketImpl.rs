@@ -0,0 +1,345 @@
+use std::net::SocketAddrV4;
+
+use jni::{
+    objects::{JClass, JObject},
+    sys::{jboolean, jint},
+    JNIEnv,
+};
+
+use crate::{
+    convert::{FromJValue, ToJValue},
+    os::aio::{self, socket_options},
+    sandbox::ResourceAccess,
+    vm::VM,
+    ObjectPtr,
+};
+
+use super::{
+    java_io_FileDescriptor::{fd_val, set_fd_val},
+    jni::JNIEnvWrapper,
+    sun_nio_ch_Net::{inet_address_of, ipv4_of},
+};
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_initProto<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+}
+
+/// Creates the socket of the impl, with `SO_REUSEADDR` for a server socket.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_socketCreate<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    stream: jboolean,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let obj_ref = ObjectPtr::from_raw(obj_ref.as_raw() as _);
+    let server_socket: ObjectPtr = get_field(obj_ref, "serverSocket", vm.as_ref());
+    let fd = match aio::socket(stream != 0, server_socket.is_not_null()) {
+        Ok(fd) => fd,
+        Err(_e) => todo!("throw SocketException"),
+    };
+    set_fd_val(fd_of(obj_ref, vm.as_ref()), fd, vm.as_ref());
+}
+
+/// Connects, waiting at most `timeout` milliseconds if it is positive.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_socketConnect<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    address: JObject<'local>,
+    port: jint,
+    timeout: jint,
+) {
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    let obj_ref = ObjectPtr::from_raw(obj_ref.as_raw() as _);
+    let address = ObjectPtr::from_raw(address.as_raw() as _);
+    let fd = socket_fd(obj_ref, vm.as_ref());
+    let remote = SocketAddrV4::new(ipv4_of(address, vm.as_ref()), port as u16);
+    if !vm
+        .cfg
+        .sandbox
+        .check_access(ResourceAccess::Connect(remote.into()), env.thread())
+    {
+        return;
+    }
+    if let Err(e) = aio::connect_timeout(fd, remote, timeout as i64) {
+        if e.kind() == std::io::ErrorKind::TimedOut {
+            todo!("throw SocketTimeoutException: connect timed out");
+        }
+        todo!("throw ConnectException: {}", e);
+    }
+    set_field(obj_ref, "address", address, vm.as_ref());
+    set_field(obj_ref, "port", port, vm.as_ref());
+    let local_port: jint = get_field(obj_ref, "localport", vm.as_ref());
+    if local_port == 0 {
+        set_field(obj_ref, "localport", local_port_of(fd), vm.as_ref());
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_socketBind<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    address: JObject<'local>,
+    port: jint,
+) {
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    let obj_ref = ObjectPtr::from_raw(obj_ref.as_raw() as _);
+    let address = ObjectPtr::from_raw(address.as_raw() as _);
+    let fd = socket_fd(obj_ref, vm.as_ref());
+    let local = SocketAddrV4::new(ipv4_of(address, vm.as_ref()), port as u16);
+    if !vm
+        .cfg
+        .sandbox
+        .check_access(ResourceAccess::Bind(local.into()), env.thread())
+    {
+        return;
+    }
+    if let Err(_e) = aio::bind(fd, local) {
+        todo!("throw BindException");
+    }
+    set_field(obj_ref, "address", address, vm.as_ref());
+    let local_port = if port == 0 { local_port_of(fd) } else { port };
+    set_field(obj_ref, "localport", local_port, vm.as_ref());
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_socketListen<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    count: jint,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = socket_fd(ObjectPtr::from_raw(obj_ref.as_raw() as _), vm.as_ref());
+    if let Err(_e) = aio::listen(fd, count.max(1)) {
+        todo!("throw SocketException");
+    }
+}
+
+/// Accepts a connection into the impl `socket`, waiting at most the
+/// `SO_TIMEOUT` of this impl if it is positive.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_socketAccept<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    socket: JObject<'local>,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let obj_ref = ObjectPtr::from_raw(obj_ref.as_raw() as _);
+    let socket = ObjectPtr::from_raw(socket.as_raw() as _);
+    if socket.is_null() {
+        todo!("throw NullPointerException: socket is null");
+    }
+    let fd = socket_fd(obj_ref, vm.as_ref());
+    let timeout: jint = get_field(obj_ref, "timeout", vm.as_ref());
+    if timeout > 0 {
+        match aio::wait_ready(fd, false, timeout as i64) {
+            Ok(true) => {}
+            Ok(false) => todo!("throw SocketTimeoutException: Accept timed out"),
+            Err(e) => todo!("throw SocketException: {}", e),
+        }
+    }
+    let (new_fd, remote) = match aio::accept(fd) {
+        Ok(accepted) => accepted,
+        Err(e) => todo!("throw SocketException: {}", e),
+    };
+    set_fd_val(fd_of(socket, vm.as_ref()), new_fd, vm.as_ref());
+    let address = inet_address_of(*remote.ip(), vm.as_ref());
+    set_field(socket, "address", address, vm.as_ref());
+    set_field(socket, "port", remote.port() as jint, vm.as_ref());
+    let local_port: jint = get_field(obj_ref, "localport", vm.as_ref());
+    set_field(socket, "localport", local_port, vm.as_ref());
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_socketAvailable<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = socket_fd(ObjectPtr::from_raw(obj_ref.as_raw() as _), vm.as_ref());
+    return match aio::available(fd) {
+        Ok(available) => available.min(jint::MAX as usize) as jint,
+        Err(e) => todo!("throw SocketException: {}", e),
+    };
+}
+
+/// Closes the socket. Nothing blocks in another thread, so the close is
+/// never deferred.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_socketClose0<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    _use_deferred_close: jboolean,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd_obj = fd_of(ObjectPtr::from_raw(obj_ref.as_raw() as _), vm.as_ref());
+    if fd_obj.is_null() {
+        todo!("throw SocketException: socket already closed");
+    }
+    let fd = fd_val(fd_obj, vm.as_ref());
+    if fd != -1 {
+        set_fd_val(fd_obj, -1, vm.as_ref());
+        let _ = aio::close(fd);
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_socketShutdown<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    howto: jint,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = socket_fd(ObjectPtr::from_raw(obj_ref.as_raw() as _), vm.as_ref());
+    // AbstractPlainSocketImpl.SHUT_RD and SHUT_WR are those of the OS
+    if let Err(e) = aio::shutdown(fd, howto) {
+        todo!("throw SocketException: {}", e);
+    }
+}
+
+/// Sets the option `cmd` of `java.net.SocketOptions`, `value` being an
+/// `Integer` for the options that are not flags.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_socketSetOption<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    cmd: jint,
+    on: jboolean,
+    value: JObject<'local>,
+) {
+    // AbstractPlainSocketImpl keeps the timeout for socketAccept and the
+    // reads of SocketInputStream
+    if cmd == socket_options::SO_TIMEOUT {
+        return;
+    }
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = socket_fd(ObjectPtr::from_raw(obj_ref.as_raw() as _), vm.as_ref());
+    let (level, name) = match aio::socket_option(cmd) {
+        Some(option) => option,
+        None => todo!("throw SocketException: Invalid option"),
+    };
+    let value = match cmd {
+        socket_options::SO_SNDBUF | socket_options::SO_RCVBUF | socket_options::IP_TOS => {
+            get_field(
+                ObjectPtr::from_raw(value.as_raw() as _),
+                "value",
+                vm.as_ref(),
+            )
+        }
+        socket_options::SO_LINGER if on != 0 => get_field(
+            ObjectPtr::from_raw(value.as_raw() as _),
+            "value",
+            vm.as_ref(),
+        ),
+        socket_options::SO_LINGER => -1,
+        _ => (on != 0) as jint,
+    };
+    if let Err(e) = aio::set_socket_option(fd, level, name, value) {
+        todo!("throw SocketException: {}", e);
+    }
+}
+
+/// Reads the option `opt` of `java.net.SocketOptions`. A flag is 1 if set
+/// and -1 if not, `SO_BINDADDR` is stored in the `InetAddressContainer`
+/// `ia_container`.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_socketGetOption<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    opt: jint,
+    ia_container: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = socket_fd(ObjectPtr::from_raw(obj_ref.as_raw() as _), vm.as_ref());
+    if opt == socket_options::SO_BINDADDR {
+        let local = match aio::local_addr(fd) {
+            Ok(local) => local,
+            Err(e) => todo!("throw SocketException: {}", e),
+        };
+        let ia_container = ObjectPtr::from_raw(ia_container.as_raw() as _);
+        let address = inet_address_of(*local.ip(), vm.as_ref());
+        set_field(ia_container, "addr", address, vm.as_ref());
+        return 0;
+    }
+    let (level, name) = match aio::socket_option(opt) {
+        Some(option) => option,
+        None => todo!("throw SocketException: Invalid option"),
+    };
+    let value = match aio::get_socket_option(fd, level, name) {
+        Ok(value) => value,
+        Err(e) => todo!("throw SocketException: {}", e),
+    };
+    return match opt {
+        socket_options::SO_LINGER
+        | socket_options::SO_SNDBUF
+        | socket_options::SO_RCVBUF
+        | socket_options::IP_TOS => value,
+        _ if value == 0 => -1,
+        _ => 1,
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_PlainSocketImpl_socketSendUrgentData<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    data: jint,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = socket_fd(ObjectPtr::from_raw(obj_ref.as_raw() as _), vm.as_ref());
+    if let Err(e) = aio::send_urgent(fd, data as u8) {
+        todo!("throw SocketException: {}", e);
+    }
+}
+
+/// The `java.io.FileDescriptor` of the `SocketImpl` `obj_ref`.
+fn fd_of(obj_ref: ObjectPtr, vm: &VM) -> ObjectPtr {
+    return get_field(obj_ref, "fd", vm);
+}
+
+/// The socket of the `SocketImpl` `obj_ref`.
+fn socket_fd(obj_ref: ObjectPtr, vm: &VM) -> jint {
+    let fd = fd_of(obj_ref, vm);
+    if fd.is_null() {
+        todo!("throw SocketException: Socket closed");
+    }
+    return fd_val(fd, vm);
+}
+
+fn local_port_of(fd: jint) -> jint {
+    return match aio::local_addr(fd) {
+        Ok(local) => local.port() as jint,
+        Err(e) => todo!("throw SocketException: {}", e),
+    };
+}
+
+fn get_field<T: FromJValue>(obj: ObjectPtr, name: &str, vm: &VM) -> T {
+    return match vm.get_field(obj, name) {
+        Ok(value) => value,
+        Err(e) => todo!("{:?}", e),
+    };
+}
+
+fn set_field<T: ToJValue>(obj: ObjectPtr, name: &str, value: T, vm: &VM) {
+    if let Err(e) = vm.set_field(obj, name, value) {
+        todo!("{:?}", e);
+    }
+}
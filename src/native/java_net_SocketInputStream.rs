@@ -0,0 +1,59 @@
+use std::io::Read;
+
+use jni::{
+    objects::{JByteArray, JClass, JObject},
+    sys::jint,
+    JNIEnv,
+};
+
+use crate::{os::aio, ObjectPtr};
+
+use super::{
+    java_io_FileDescriptor::{fd_val, file_of},
+    java_io_RandomAccessFile::byte_range,
+    jni::JNIEnvWrapper,
+};
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_SocketInputStream_init<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+}
+
+/// Reads into `b[off..off + len]`, waiting at most `timeout` milliseconds
+/// for data if it is positive. Returns the bytes read, 0 at the end of the
+/// stream.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_SocketInputStream_socketRead0<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    fd: JObject<'local>,
+    b: JByteArray<'local>,
+    off: jint,
+    len: jint,
+    timeout: jint,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = ObjectPtr::from_raw(fd.as_raw() as _);
+    if fd.is_null() {
+        todo!("throw SocketException: Socket closed");
+    }
+    let buf = byte_range(&b, off, len);
+    if timeout > 0 {
+        match aio::wait_ready(fd_val(fd, vm.as_ref()), false, timeout as i64) {
+            Ok(true) => {}
+            Ok(false) => todo!("throw SocketTimeoutException: Read timed out"),
+            Err(e) => todo!("throw SocketException: {}", e),
+        }
+    }
+    return match file_of(fd, vm.as_ref()).read(buf) {
+        Ok(read) => read as jint,
+        Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => {
+            todo!("throw sun.net.ConnectionResetException: Connection reset")
+        }
+        Err(e) => todo!("throw SocketException: {}", e),
+    };
+}
@@ -0,0 +1,42 @@
+use std::io::Write;
+
+use jni::{
+    objects::{JByteArray, JClass, JObject},
+    sys::jint,
+    JNIEnv,
+};
+
+use crate::ObjectPtr;
+
+use super::{
+    java_io_FileDescriptor::file_of, java_io_RandomAccessFile::byte_range, jni::JNIEnvWrapper,
+};
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_SocketOutputStream_init<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_net_SocketOutputStream_socketWrite0<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    fd: JObject<'local>,
+    b: JByteArray<'local>,
+    off: jint,
+    len: jint,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = ObjectPtr::from_raw(fd.as_raw() as _);
+    if fd.is_null() {
+        todo!("throw SocketException: Socket closed");
+    }
+    let buf = byte_range(&b, off, len);
+    if let Err(e) = file_of(fd, vm.as_ref()).write_all(buf) {
+        todo!("throw SocketException: {}", e);
+    }
+}
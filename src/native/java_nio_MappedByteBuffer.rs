@@ -0,0 +1,45 @@
+use jni::{
+    objects::JObject,
+    sys::{jboolean, jint, jlong},
+    JNIEnv,
+};
+
+use crate::{memory::Address, os};
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_nio_MappedByteBuffer_isLoaded0<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    address: jlong,
+    length: jlong,
+    _page_count: jint,
+) -> jboolean {
+    return os::is_resident(Address::from_usize(address as usize), length as usize) as jboolean;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_nio_MappedByteBuffer_load0<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    address: jlong,
+    length: jlong,
+) {
+    // MappedByteBuffer.load touches every page after the hint
+    os::prefetch(Address::from_usize(address as usize), length as usize);
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_nio_MappedByteBuffer_force0<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    _fd: JObject<'local>,
+    address: jlong,
+    length: jlong,
+) {
+    if let Err(_e) = os::flush_mapped_file(Address::from_usize(address as usize), length as usize) {
+        todo!("throw IOException: flush failed");
+    }
+}
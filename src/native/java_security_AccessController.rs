@@ -6,7 +6,7 @@ use jni::{
     JNIEnv,
 };
 
-use crate::ObjectPtr;
+use crate::{thread::Thread, ObjectPtr};
 
 use super::jni::JNIEnvWrapper;
 
@@ -31,12 +31,50 @@ pub extern "system" fn Java_java_security_AccessController_doPrivileged<'local>(
         action_cls,
         action_cls_info.run_name(),
         action_cls_info.run_descriptor(),
+        vm.as_ref(),
     ) {
         Ok(resolved) => resolved.method,
-        Err(_e) => todo!(),
+        // a PrivilegedExceptionAction, whose run has the same descriptor
+        Err(_e) => match vm.get_method(
+            action.jclass(),
+            "run",
+            "()Ljava/lang/Object;",
+            Thread::current(),
+        ) {
+            Ok(run_method) => run_method,
+            Err(_e) => todo!(),
+        },
     };
-    let result = vm.call_obj(action, run_method, &[]);
-    return result.obj_val().as_raw_ptr() as _;
+    let result: ObjectPtr = vm.call_virtual(action, run_method, &[]).unwrap();
+    return result.as_raw_ptr() as _;
+}
+
+/// Access control contexts are not checked, so the action runs as if it had
+/// none.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_security_AccessController_doPrivileged__Ljava_security_PrivilegedAction_2Ljava_security_AccessControlContext_2<
+    'local,
+>(
+    env: JNIEnv<'local>,
+    cls_ref: JClass<'local>,
+    action: JObject<'local>,
+    _context: JObject<'local>,
+) -> jobject {
+    return Java_java_security_AccessController_doPrivileged(env, cls_ref, action);
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_security_AccessController_doPrivileged__Ljava_security_PrivilegedExceptionAction_2Ljava_security_AccessControlContext_2<
+    'local,
+>(
+    env: JNIEnv<'local>,
+    cls_ref: JClass<'local>,
+    action: JObject<'local>,
+    _context: JObject<'local>,
+) -> jobject {
+    return Java_java_security_AccessController_doPrivileged(env, cls_ref, action);
 }
 
 #[allow(non_snake_case)]
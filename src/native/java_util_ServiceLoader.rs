@@ -0,0 +1,103 @@
+use jni::{
+    objects::{JClass, JObject},
+    sys::jobject,
+    JNIEnv,
+};
+
+use crate::{
+    classfile::class_loader::parse_provider_config, thread::Thread, value::JValue, JClassPtr,
+    ObjectPtr,
+};
+
+use super::jni::JNIEnvWrapper;
+
+/// Replaces `ServiceLoader.parse(Class, URL)`, which reads the
+/// provider-configuration file through `URL.openStream`, so the files in
+/// jars are read without `java.util.zip`. Returns an iterator over the
+/// providers not found before.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_util_ServiceLoader_parse<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    service: JClass<'local>,
+    url: JObject<'local>,
+) -> jobject {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let vm = vm.as_ref();
+    let thread = Thread::current();
+    let this = ObjectPtr::from_raw(obj_ref.as_raw() as _);
+    let service = JClassPtr::from_raw(service.as_raw() as _);
+    let url = ObjectPtr::from_raw(url.as_raw() as _);
+
+    let url_cls = url.jclass();
+    let to_external_form = vm
+        .get_method(url_cls, "toExternalForm", "()Ljava/lang/String;", thread)
+        .expect("java.net.URL has toExternalForm");
+    let url: String = match vm.call_virtual(url, to_external_form, &[]) {
+        Ok(url) => url,
+        Err(e) => todo!("{:?}", e),
+    };
+    let name = format!("META-INF/services/{}", service.binary_name());
+    let config = match vm.bootstrap_class_loader.read_resource_at(&name, &url) {
+        Some(config) => config,
+        None => todo!("throw ServiceConfigurationError: Error reading configuration file"),
+    };
+
+    let providers: ObjectPtr = match vm.get_field(this, "providers") {
+        Ok(providers) => providers,
+        Err(e) => todo!("{:?}", e),
+    };
+    let contains_key = vm
+        .get_method(
+            providers.jclass(),
+            "containsKey",
+            "(Ljava/lang/Object;)Z",
+            thread,
+        )
+        .expect("java.util.LinkedHashMap has containsKey");
+    let names = match vm.new_object("java.util.ArrayList", "()V", &[]) {
+        Ok(names) => names,
+        Err(e) => todo!("{:?}", e),
+    };
+    let add = vm
+        .get_method(names.jclass(), "add", "(Ljava/lang/Object;)Z", thread)
+        .expect("java.util.ArrayList has add");
+    for provider in parse_provider_config(&config) {
+        if !is_binary_name(&provider) {
+            todo!("throw ServiceConfigurationError: Illegal provider-class name");
+        }
+        let provider: ObjectPtr = vm.new_string(&provider).cast();
+        let found: bool =
+            match vm.call_virtual(providers, contains_key, &[JValue::with_obj_val(provider)]) {
+                Ok(found) => found,
+                Err(e) => todo!("{:?}", e),
+            };
+        if !found {
+            if let Err(e) = vm.call_virtual::<bool>(names, add, &[JValue::with_obj_val(provider)]) {
+                todo!("{:?}", e);
+            }
+        }
+    }
+    let iterator = vm
+        .get_method(names.jclass(), "iterator", "()Ljava/util/Iterator;", thread)
+        .expect("java.util.ArrayList has iterator");
+    return match vm.call_virtual::<ObjectPtr>(names, iterator, &[]) {
+        Ok(iterator) => iterator.as_raw_ptr() as _,
+        Err(e) => todo!("{:?}", e),
+    };
+}
+
+/// Whether `name` is a valid class name, like `ServiceLoader.parseLine`
+/// checks it.
+fn is_binary_name(name: &str) -> bool {
+    return name.split('.').all(|part| {
+        let mut chars = part.chars();
+        return match chars.next() {
+            Some(first) if first.is_alphabetic() || first == '_' || first == '$' => {
+                chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+            }
+            _ => false,
+        };
+    });
+}
@@ -0,0 +1,51 @@
+use std::ptr::null_mut;
+
+use jni::{
+    objects::{JClass, JString as JNIString},
+    sys::jstring,
+    JNIEnv,
+};
+
+use crate::os;
+
+use super::jni::JNIEnvWrapper;
+
+/// The tz database name of the system time zone, or null for `GMT`. The
+/// name only finds a zone if `java.home` has its `lib/zi` data; otherwise
+/// `TimeZone` falls back to `getSystemGMTOffsetID()`.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_util_TimeZone_getSystemTimeZoneID<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    _java_home: JNIString<'local>,
+    _country: JNIString<'local>,
+) -> jstring {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    return match os::time_zone_id() {
+        Some(id) => vm.new_string(&id).as_raw_ptr() as _,
+        None => null_mut(),
+    };
+}
+
+/// The current offset of the system time zone as a custom ID, e.g.
+/// `GMT+09:00`, or `GMT` at no offset.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_java_util_TimeZone_getSystemGMTOffsetID<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jstring {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let offset = match os::utc_offset() {
+        Some(offset) => offset,
+        None => return null_mut(),
+    };
+    if offset == 0 {
+        return vm.new_string("GMT").as_raw_ptr() as _;
+    }
+    let sign = if offset < 0 { '-' } else { '+' };
+    let minutes = offset.abs() / 60;
+    let id = format!("GMT{}{:02}:{:02}", sign, minutes / 60, minutes % 60);
+    return vm.new_string(&id).as_raw_ptr() as _;
+}
@@ -1,22 +1,53 @@
-use std::ptr::null_mut;
+use std::ffi::{c_char, c_void, CStr};
 
-use jni::sys::JNINativeInterface_;
+use jni::sys::{
+    jarray, jboolean, jbyte, jclass, jint, jobject, jsize, jweak, JNIInvokeInterface_,
+    JNINativeInterface_, JNINativeMethod, JNI_ERR, JNI_FALSE, JNI_OK,
+};
+use parking_lot::Mutex;
 
-use crate::{object::prelude::Ptr, vm::VMPtr};
+use super::invocation::{self, JavaVMWrapper};
+use crate::{
+    classfile::ClassLoadErr,
+    memory::Address,
+    object::{
+        array::JArrayPtr,
+        class::{ClassData, JClass},
+        method::MethodPtr,
+        prelude::{JClassPtr, ObjectPtr, Ptr},
+        Object,
+    },
+    os,
+    thread::ThreadPtr,
+    vm::VMPtr,
+};
 
 pub(crate) type JNIEnvWrapperPtr = Ptr<JNIEnvWrapper>;
 
+/// The `JNIEnv` of one attached thread, passed to the natives the thread
+/// invokes. `env` has to stay the first field, as natives read the function
+/// table through it.
+#[repr(C)]
 pub(crate) struct JNIEnvWrapper {
     #[allow(unused)]
     env: jni::sys::JNIEnv,
     vm: VMPtr,
+    thread: ThreadPtr,
+    /// The objects the running natives of the thread refer to, innermost
+    /// native last. Each native drops its own when it returns.
+    local_refs: Vec<ObjectPtr>,
+    /// The arrays held by `GetPrimitiveArrayCritical`, latest last.
+    critical_arrays: Vec<JArrayPtr>,
 }
 
 impl JNIEnvWrapper {
-    fn default() -> Self {
+    pub fn new(vm: VMPtr) -> Self {
         Self {
-            env: null_mut(),
-            vm: VMPtr::null(),
+            env: &vm.jni().jni,
+            vm,
+            thread: ThreadPtr::null(),
+            local_refs: Vec::new(),
+            critical_arrays: Vec::new(),
         }
     }
 
@@ -27,30 +58,410 @@ impl JNIEnvWrapper {
     pub fn vm(&self) -> VMPtr {
         self.vm
     }
+
+    /// The thread that owns the env.
+    pub fn thread(&self) -> ThreadPtr {
+        self.thread
+    }
+
+    pub(crate) fn set_thread(&mut self, thread: ThreadPtr) {
+        self.thread = thread;
+    }
+
+    pub(crate) fn as_handle(&self) -> isize {
+        return self as *const JNIEnvWrapper as isize;
+    }
+
+    /// Starts the local references of a native, returns the mark to pass to
+    /// [`pop_local_frame`](Self::pop_local_frame) once it returns.
+    pub(crate) fn push_local_frame(&self) -> usize {
+        return self.local_refs.len();
+    }
+
+    pub(crate) fn pop_local_frame(&mut self, mark: usize) {
+        debug_assert!(mark <= self.local_refs.len());
+        self.local_refs.truncate(mark);
+    }
+
+    /// The object behind a reference a native passes, which may be a weak
+    /// global reference wherever JNI accepts a `jobject`.
+    pub(crate) fn resolve<T>(&self, obj: jobject) -> Ptr<T> {
+        return self.vm.jni().weak_globals.resolve(obj).cast();
+    }
+
+    pub(crate) fn new_local_ref(&mut self, obj: ObjectPtr) -> ObjectPtr {
+        if obj.is_not_null() {
+            self.local_refs.push(obj);
+        }
+        return obj;
+    }
+
+    /// Whether the thread holds an array from `GetPrimitiveArrayCritical`.
+    /// Inside such a critical region it must not call back into Java nor
+    /// call JNI functions other than the critical ones.
+    pub(crate) fn in_critical(&self) -> bool {
+        return !self.critical_arrays.is_empty();
+    }
+
+    /// Aborts if the thread is in a critical region, as `what` is not allowed
+    /// there.
+    pub(crate) fn check_not_critical(&self, what: &str) {
+        if self.in_critical() {
+            os::fatal_error(&format!("JNI critical region: {}\n", what));
+        }
+    }
 }
 
+/// The JNI function table, shared by the envs of all threads. The functions
+/// the VM does not implement yet are left null.
 pub(crate) struct JNIWrapper {
-    #[allow(unused)]
     jni: JNINativeInterface_,
-    env_wrapper: JNIEnvWrapper,
+    invoke: JNIInvokeInterface_,
+    pub(crate) java_vm: JavaVMWrapper,
+    pub(crate) weak_globals: WeakGlobalRefs,
 }
 
 impl JNIWrapper {
     pub fn default() -> Self {
+        let mut jni: JNINativeInterface_ = unsafe { std::mem::zeroed() };
+        jni.GetVersion = Some(get_version);
+        jni.DefineClass = Some(define_class);
+        jni.NewLocalRef = Some(new_local_ref);
+        jni.IsSameObject = Some(is_same_object);
+        jni.NewWeakGlobalRef = Some(new_weak_global_ref);
+        jni.DeleteWeakGlobalRef = Some(delete_weak_global_ref);
+        jni.ExceptionCheck = Some(exception_check);
+        jni.GetPrimitiveArrayCritical = Some(get_primitive_array_critical);
+        jni.ReleasePrimitiveArrayCritical = Some(release_primitive_array_critical);
+        jni.RegisterNatives = Some(register_natives);
+        jni.UnregisterNatives = Some(unregister_natives);
+        jni.GetJavaVM = Some(get_java_vm);
         Self {
-            jni: unsafe { std::mem::zeroed() },
-            env_wrapper: JNIEnvWrapper::default(),
+            jni,
+            invoke: invocation::invoke_interface(),
+            java_vm: JavaVMWrapper::default(),
+            weak_globals: WeakGlobalRefs::default(),
         }
     }
 
-    pub fn init(&mut self, vm: VMPtr) {
-        self.env_wrapper.env = &self.jni;
-        self.env_wrapper.vm = vm;
+    /// Binds the `JavaVM` of `vm`, once the VM is at its final address.
+    pub(crate) fn bind_vm(&mut self, vm: VMPtr) {
+        self.java_vm.bind(&self.invoke, vm);
     }
+}
+
+const WEAK_GLOBAL_REFS_PER_CHUNK: usize = 256;
+
+/// The weak global references of a VM. A reference is the address of its
+/// slot, which is cleared once the referent is collected. The JNI functions
+/// and the results of natives go through [`JNIEnvWrapper::resolve`], so a
+/// weak reference works wherever a `jobject` does.
+#[derive(Default)]
+pub(crate) struct WeakGlobalRefs {
+    slots: Mutex<WeakGlobalSlots>,
+}
+
+#[derive(Default)]
+struct WeakGlobalSlots {
+    /// Slots never move, so a chunk is never dropped before the VM.
+    chunks: Vec<Box<[ObjectPtr]>>,
+    /// The chunk and index of the slots deleted.
+    free: Vec<(usize, usize)>,
+    /// The slots taken in the last chunk.
+    last_chunk_used: usize,
+}
+
+impl WeakGlobalRefs {
+    pub(crate) fn new_ref(&self, obj: ObjectPtr) -> jweak {
+        let mut slots = self.slots.lock();
+        let (chunk, idx) = match slots.free.pop() {
+            Some(slot) => slot,
+            None => {
+                if slots.chunks.is_empty() || slots.last_chunk_used == WEAK_GLOBAL_REFS_PER_CHUNK {
+                    let chunk = vec![ObjectPtr::null(); WEAK_GLOBAL_REFS_PER_CHUNK];
+                    slots.chunks.push(chunk.into_boxed_slice());
+                    slots.last_chunk_used = 0;
+                }
+                slots.last_chunk_used += 1;
+                (slots.chunks.len() - 1, slots.last_chunk_used - 1)
+            }
+        };
+        let slot = &mut slots.chunks[chunk][idx];
+        *slot = obj;
+        return slot as *mut ObjectPtr as jweak;
+    }
+
+    /// Frees the slot of `weak`, deleting it again does nothing.
+    pub(crate) fn delete_ref(&self, weak: jweak) {
+        let mut slots = self.slots.lock();
+        if let Some((chunk, idx)) = Self::find_slot(&slots, weak) {
+            if slots.free.contains(&(chunk, idx)) {
+                return;
+            }
+            slots.chunks[chunk][idx] = ObjectPtr::null();
+            slots.free.push((chunk, idx));
+        }
+    }
+
+    /// The object `obj` refers to, which is `obj` itself unless it is a weak
+    /// global reference. Null once the referent of a weak one is collected.
+    pub(crate) fn resolve(&self, obj: jobject) -> ObjectPtr {
+        let slots = self.slots.lock();
+        return match Self::find_slot(&slots, obj) {
+            Some((chunk, idx)) => slots.chunks[chunk][idx],
+            None => ObjectPtr::from_raw(obj as _),
+        };
+    }
+
+    /// Clears the references whose referent `is_alive` reports unreachable.
+    /// For a collector to call once it knows the live objects, none runs
+    /// yet, so the referents of weak global references are never cleared.
+    #[allow(dead_code)]
+    pub(crate) fn clear_unreachable(&self, is_alive: impl Fn(ObjectPtr) -> bool) {
+        let mut slots = self.slots.lock();
+        for chunk in slots.chunks.iter_mut() {
+            for slot in chunk.iter_mut() {
+                if slot.is_not_null() && !is_alive(*slot) {
+                    *slot = ObjectPtr::null();
+                }
+            }
+        }
+    }
+
+    fn find_slot(slots: &WeakGlobalSlots, obj: jobject) -> Option<(usize, usize)> {
+        let addr = obj as usize;
+        for (chunk_idx, chunk) in slots.chunks.iter().enumerate() {
+            let start = chunk.as_ptr() as usize;
+            let end = start + std::mem::size_of_val(chunk.as_ref());
+            if (start..end).contains(&addr) {
+                return Some((chunk_idx, (addr - start) / std::mem::size_of::<ObjectPtr>()));
+            }
+        }
+        return None;
+    }
+}
+
+unsafe extern "system" fn get_version(_env: *mut jni::sys::JNIEnv) -> jint {
+    return invocation::JNI_VERSION;
+}
+
+/// Defines the class in the `len` bytes at `buf` with `loader`, null for the
+/// bootstrap loader. `name`, like `java/lang/String`, may be null, else it
+/// has to be the name of the class defined.
+unsafe extern "system" fn define_class(
+    env: *mut jni::sys::JNIEnv,
+    name: *const c_char,
+    loader: jobject,
+    buf: *const jbyte,
+    len: jsize,
+) -> jclass {
+    let env = JNIEnvWrapper::from_raw_env(env);
+    env.check_not_critical("DefineClass called");
+    let name = if name.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(name).to_string_lossy())
+    };
+    if buf.is_null() || len < 0 {
+        todo!("throw ClassFormatError");
+    }
+    let bytes = std::slice::from_raw_parts(buf as *const u8, len as usize).to_vec();
+    let vm = env.vm();
+    let loader = env.resolve(loader);
+    let result = vm.bootstrap_class_loader.define_class_with_loader(
+        name.as_deref(),
+        bytes,
+        loader,
+        env.thread(),
+    );
+    return match result {
+        Ok(class) => class.as_c_ptr() as jclass,
+        Err(e) => throw_define_class_err(e),
+    };
+}
+
+/// Stands for the error a failed class definition throws.
+pub(crate) fn throw_define_class_err(err: ClassLoadErr) -> ! {
+    match err {
+        ClassLoadErr::DuplicateClass(_) => todo!("throw LinkageError: {}", err),
+        ClassLoadErr::WrongName { .. } => todo!("throw NoClassDefFoundError: {}", err),
+        ClassLoadErr::Verify(_) => todo!("throw VerifyError: {}", err),
+        ClassLoadErr::UnsupportedVersion { .. } => {
+            todo!("throw UnsupportedClassVersionError: {}", err)
+        }
+        ClassLoadErr::ClassNotFound(_) | ClassLoadErr::Dependency { .. } => {
+            todo!("throw NoClassDefFoundError: {}", err)
+        }
+        _ => todo!("throw ClassFormatError: {}", err),
+    }
+}
+
+/// A local reference to the object `obj` refers to, null for a weak global
+/// reference whose referent was collected.
+unsafe extern "system" fn new_local_ref(env: *mut jni::sys::JNIEnv, obj: jobject) -> jobject {
+    let mut env = JNIEnvWrapper::from_raw_env(env);
+    let obj: ObjectPtr = env.resolve(obj);
+    return env.new_local_ref(obj).as_c_ptr() as _;
+}
+
+unsafe extern "system" fn is_same_object(
+    env: *mut jni::sys::JNIEnv,
+    obj1: jobject,
+    obj2: jobject,
+) -> jboolean {
+    let env = JNIEnvWrapper::from_raw_env(env);
+    return (env.resolve::<Object>(obj1) == env.resolve(obj2)) as jboolean;
+}
+
+unsafe extern "system" fn new_weak_global_ref(env: *mut jni::sys::JNIEnv, obj: jobject) -> jweak {
+    let env = JNIEnvWrapper::from_raw_env(env);
+    let obj: ObjectPtr = env.resolve(obj);
+    if obj.is_null() {
+        return std::ptr::null_mut();
+    }
+    return env.vm().jni().weak_globals.new_ref(obj);
+}
+
+unsafe extern "system" fn delete_weak_global_ref(env: *mut jni::sys::JNIEnv, weak: jweak) {
+    JNIEnvWrapper::from_raw_env(env)
+        .vm()
+        .jni()
+        .weak_globals
+        .delete_ref(weak);
+}
+
+unsafe extern "system" fn exception_check(_env: *mut jni::sys::JNIEnv) -> jboolean {
+    // exceptions cannot be thrown yet, so none is ever pending
+    return JNI_FALSE;
+}
+
+/// Binds the native methods of `clazz` to the given functions. A registered
+/// function takes precedence over the symbols of loaded libraries and over
+/// the builtin natives, and registering again replaces it. Binds none of them
+/// and fails with `JNI_ERR` if one is not a native method of `clazz` or has
+/// no function.
+unsafe extern "system" fn register_natives(
+    env: *mut jni::sys::JNIEnv,
+    clazz: jclass,
+    methods: *const JNINativeMethod,
+    n_methods: jint,
+) -> jint {
+    let env = JNIEnvWrapper::from_raw_env(env);
+    env.check_not_critical("RegisterNatives called");
+    if n_methods <= 0 {
+        return JNI_OK;
+    }
+    if methods.is_null() || clazz.is_null() {
+        return JNI_ERR;
+    }
+    let class: JClassPtr = env.resolve(clazz);
+    let natives = std::slice::from_raw_parts(methods, n_methods as usize);
+    // bind none of them unless all of them are found
+    let mut bindings = Vec::with_capacity(natives.len());
+    for native in natives {
+        if native.name.is_null() || native.signature.is_null() || native.fnPtr.is_null() {
+            return JNI_ERR;
+        }
+        let name = CStr::from_ptr(native.name).to_string_lossy();
+        let descriptor = CStr::from_ptr(native.signature).to_string_lossy();
+        match find_native_method(class, &name, &descriptor) {
+            Some(method) => bindings.push((method, Address::from_c_ptr(native.fnPtr))),
+            None => return JNI_ERR,
+        }
+    }
+    for (mut method, native_fn) in bindings {
+        method.register_native(native_fn);
+    }
+    return JNI_OK;
+}
+
+/// Unbinds the natives of `clazz` bound with `RegisterNatives`, which fall
+/// back to their builtin natives. Libraries loaded before are not searched
+/// again.
+unsafe extern "system" fn unregister_natives(env: *mut jni::sys::JNIEnv, clazz: jclass) -> jint {
+    let env = JNIEnvWrapper::from_raw_env(env);
+    env.check_not_critical("UnregisterNatives called");
+    let vm = env.vm();
+    let class: JClassPtr = env.resolve(clazz);
+    let methods = class.class_data().methods();
+    for idx in 0..methods.length() {
+        let mut method: MethodPtr = methods.get(idx).cast();
+        if method.is_registered_native() {
+            method.unregister_native();
+            ClassData::bind_builtin_native(method, class, vm.as_ref());
+        }
+    }
+    return JNI_OK;
+}
+
+unsafe extern "system" fn get_java_vm(
+    env: *mut jni::sys::JNIEnv,
+    java_vm: *mut *mut jni::sys::JavaVM,
+) -> jint {
+    *java_vm = JNIEnvWrapper::from_raw_env(env).vm().jni().java_vm.as_raw();
+    return JNI_OK;
+}
+
+/// The elements of a primitive array, which stays where it is until the
+/// matching `ReleasePrimitiveArrayCritical`. Objects never move, so the
+/// elements are never copied. Critical regions may nest, but in between the
+/// thread must not call back into Java nor call other JNI functions, and the
+/// native has to release every array before it returns. A collector must not
+/// run while any thread is in a critical region.
+unsafe extern "system" fn get_primitive_array_critical(
+    env: *mut jni::sys::JNIEnv,
+    array: jarray,
+    is_copy: *mut jboolean,
+) -> *mut c_void {
+    let mut env = JNIEnvWrapper::from_raw_env(env);
+    let array: JArrayPtr = env.resolve(array);
+    if array.is_null() {
+        todo!("throw NullPointerException");
+    }
+    if !JClass::is_primitive(array.get_component_type()) {
+        os::fatal_error("JNI critical region: GetPrimitiveArrayCritical of an object array\n");
+    }
+    if !is_copy.is_null() {
+        *is_copy = JNI_FALSE;
+    }
+    env.critical_arrays.push(array);
+    return array.data().as_c_ptr();
+}
+
+/// Ends the critical region of `array`. The elements are the array's own, so
+/// every `mode` leaves the array as the native wrote it.
+unsafe extern "system" fn release_primitive_array_critical(
+    env: *mut jni::sys::JNIEnv,
+    array: jarray,
+    carray: *mut c_void,
+    _mode: jint,
+) {
+    let mut env = JNIEnvWrapper::from_raw_env(env);
+    let array: JArrayPtr = env.resolve(array);
+    let held = env
+        .critical_arrays
+        .iter()
+        .rposition(|held| *held == array && held.data().as_c_ptr() == carray);
+    match held {
+        Some(idx) => {
+            env.critical_arrays.remove(idx);
+        }
+        None => os::fatal_error(
+            "JNI critical region: ReleasePrimitiveArrayCritical of an array not held\n",
+        ),
+    }
+}
 
-    pub fn get_env_handle(&self) -> isize {
-        unsafe {
-            std::mem::transmute(&self.env_wrapper as *const JNIEnvWrapper as *mut jni::sys::JNIEnv)
+fn find_native_method(class: JClassPtr, name: &str, descriptor: &str) -> Option<MethodPtr> {
+    let methods = class.class_data().methods();
+    for idx in 0..methods.length() {
+        let method: MethodPtr = methods.get(idx).cast();
+        if method.is_native()
+            && method.name().as_str() == name
+            && method.descriptor().as_str() == descriptor
+        {
+            return Some(method);
         }
     }
+    return None;
 }
@@ -1,4 +1,7 @@
 pub(crate) mod builtin_natives;
+pub(crate) mod invocation;
+#[allow(non_snake_case)]
+mod java_io_Console;
 #[allow(non_snake_case)]
 mod java_io_FileDescriptor;
 #[allow(non_snake_case)]
@@ -8,6 +11,8 @@ mod java_io_FileOutputStream;
 #[allow(non_snake_case)]
 mod java_io_FileSystem;
 #[allow(non_snake_case)]
+mod java_io_RandomAccessFile;
+#[allow(non_snake_case)]
 mod java_io_UnixFileSystem;
 #[allow(non_snake_case)]
 mod java_io_Win32FileSystem;
@@ -22,29 +27,92 @@ mod java_lang_Double;
 #[allow(non_snake_case)]
 mod java_lang_Float;
 #[allow(non_snake_case)]
+mod java_lang_Integer;
+#[allow(non_snake_case)]
+mod java_lang_Long;
+#[allow(non_snake_case)]
 mod java_lang_Object;
 #[allow(non_snake_case)]
 mod java_lang_Runtime;
 #[allow(non_snake_case)]
+mod java_lang_SecurityManager;
+#[allow(non_snake_case)]
 mod java_lang_String;
 #[allow(non_snake_case)]
 mod java_lang_System;
 #[allow(non_snake_case)]
 mod java_lang_Thread;
 #[allow(non_snake_case)]
+mod java_lang_Throwable;
+#[allow(non_snake_case)]
+mod java_lang_reflect_Array;
+#[allow(non_snake_case)]
+mod java_net_Inet4Address;
+#[allow(non_snake_case)]
+mod java_net_Inet6Address;
+#[allow(non_snake_case)]
+mod java_net_InetAddress;
+#[allow(non_snake_case)]
+mod java_net_InetAddressImplFactory;
+#[allow(non_snake_case)]
+mod java_net_PlainSocketImpl;
+#[allow(non_snake_case)]
+mod java_net_SocketInputStream;
+#[allow(non_snake_case)]
+mod java_net_SocketOutputStream;
+#[allow(non_snake_case)]
+mod java_nio_MappedByteBuffer;
+#[allow(non_snake_case)]
 mod java_security_AccessController;
 #[allow(non_snake_case)]
+mod java_util_ServiceLoader;
+#[allow(non_snake_case)]
+mod java_util_TimeZone;
+#[allow(non_snake_case)]
 mod java_util_concurrent_atomic_AtomicLong;
 pub mod jni;
+pub(crate) mod signature;
 #[allow(non_snake_case)]
 mod sun_io_Win32ErrorMode;
 #[allow(non_snake_case)]
+mod sun_management_ClassLoadingImpl;
+#[allow(non_snake_case)]
+mod sun_management_MemoryImpl;
+#[allow(non_snake_case)]
+mod sun_management_ThreadImpl;
+#[allow(non_snake_case)]
+mod sun_management_VMManagementImpl;
+#[allow(non_snake_case)]
 mod sun_misc_Signal;
 #[allow(non_snake_case)]
 mod sun_misc_Unsafe;
 #[allow(non_snake_case)]
 mod sun_misc_VM;
 #[allow(non_snake_case)]
+mod sun_nio_ch_EPollArrayWrapper;
+#[allow(non_snake_case)]
+mod sun_nio_ch_FileChannelImpl;
+#[allow(non_snake_case)]
+mod sun_nio_ch_FileDispatcherImpl;
+#[allow(non_snake_case)]
+mod sun_nio_ch_IOUtil;
+#[allow(non_snake_case)]
+mod sun_nio_ch_KQueueArrayWrapper;
+#[allow(non_snake_case)]
+mod sun_nio_ch_NativeThread;
+#[allow(non_snake_case)]
+mod sun_nio_ch_Net;
+#[allow(non_snake_case)]
+mod sun_nio_ch_ServerSocketChannelImpl;
+#[allow(non_snake_case)]
+mod sun_nio_ch_SocketChannelImpl;
+#[allow(non_snake_case)]
 mod sun_reflect_NativeConstructorAccessorImpl;
 #[allow(non_snake_case)]
+mod sun_reflect_NativeMethodAccessorImpl;
+#[allow(non_snake_case)]
 mod sun_reflect_Reflection;
+#[allow(non_snake_case)]
+mod sun_security_provider_NativeSeedGenerator;
+#[allow(non_snake_case)]
+mod sun_security_provider_SeedGenerator;
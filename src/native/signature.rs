@@ -0,0 +1,164 @@
+//! The Java types a builtin native function takes and returns, told by the
+//! Rust types it is registered with, so a builtin is only bound to a method
+//! whose descriptor the interpreter passes arguments for the way the function
+//! expects.
+
+use std::fmt;
+
+use jni::{
+    objects::{JClass, JObject, JObjectArray, JPrimitiveArray, JString, JThrowable, TypeArray},
+    sys::{jboolean, jbyte, jchar, jdouble, jfloat, jint, jlong, jobject, jshort},
+    JNIEnv,
+};
+
+use crate::object::{field::FieldKind, method::MethodPtr};
+
+/// The Rust types of native function parameters and results and the Java
+/// types they stand for.
+pub(crate) trait NativeType {
+    const KIND: FieldKind;
+}
+
+macro_rules! native_types {
+    ($($rust_type: ty => $kind: ident), *) => {
+        $(
+            impl NativeType for $rust_type {
+                const KIND: FieldKind = FieldKind::$kind;
+            }
+        )*
+    };
+}
+
+native_types!(
+    jboolean => Boolean,
+    jbyte => Byte,
+    jchar => Char,
+    jshort => Short,
+    jint => Int,
+    jlong => Long,
+    jfloat => Float,
+    jdouble => Double,
+    jobject => Reference
+);
+
+macro_rules! native_ref_types {
+    ($($rust_type: ident), *) => {
+        $(
+            impl<'local> NativeType for $rust_type<'local> {
+                const KIND: FieldKind = FieldKind::Reference;
+            }
+        )*
+    };
+}
+
+native_ref_types!(JObject, JClass, JString, JThrowable, JObjectArray);
+
+impl<'local, T: TypeArray> NativeType for JPrimitiveArray<'local, T> {
+    const KIND: FieldKind = FieldKind::Reference;
+}
+
+/// The result of a native function, `None` for `void`.
+pub(crate) trait NativeResult {
+    const KIND: Option<FieldKind>;
+}
+
+impl NativeResult for () {
+    const KIND: Option<FieldKind> = None;
+}
+
+impl<T: NativeType> NativeResult for T {
+    const KIND: Option<FieldKind> = Some(T::KIND);
+}
+
+/// The parameters after the `JNIEnv`, the first being the receiver or the
+/// class of a static method, and the result of a native function.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) struct NativeSignature {
+    params: Vec<FieldKind>,
+    ret: Option<FieldKind>,
+}
+
+impl NativeSignature {
+    /// The signature a native function of `method` has to have.
+    pub(crate) fn of_method(method: MethodPtr) -> Self {
+        return Self::from_descriptor(method.descriptor().as_str());
+    }
+
+    fn from_descriptor(descriptor: &str) -> Self {
+        let descriptor = descriptor.as_bytes();
+        // the receiver, or the class of a static method
+        let mut params = vec![FieldKind::Reference];
+        let mut idx = 1;
+        while descriptor[idx] != b')' {
+            let start = idx;
+            while descriptor[idx] == b'[' {
+                idx += 1;
+            }
+            if descriptor[idx] == b'L' {
+                while descriptor[idx] != b';' {
+                    idx += 1;
+                }
+            }
+            params.push(FieldKind::from_descriptor(descriptor[start]));
+            idx += 1;
+        }
+        let ret = match descriptor[idx + 1] {
+            b'V' => None,
+            first_char => Some(FieldKind::from_descriptor(first_char)),
+        };
+        return Self { params, ret };
+    }
+}
+
+impl fmt::Display for NativeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for (idx, param) in self.params.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{:?}", param)?;
+        }
+        return match self.ret {
+            Some(ret) => write!(f, ") -> {:?}", ret),
+            None => write!(f, ")"),
+        };
+    }
+}
+
+/// The parameters of a native function after the `JNIEnv`, as a tuple, and
+/// the function taking them and returning `R`.
+pub(crate) trait NativeParams<R> {
+    type Fn;
+
+    fn signature() -> NativeSignature;
+}
+
+macro_rules! native_params {
+    ($(($($param: ident), *)), *) => {
+        $(
+            impl<R: NativeResult, $($param: NativeType), *> NativeParams<R> for ($($param,)*) {
+                type Fn = extern "system" fn(JNIEnv<'static>, $($param), *) -> R;
+
+                fn signature() -> NativeSignature {
+                    return NativeSignature {
+                        params: vec![$($param::KIND), *],
+                        ret: R::KIND,
+                    };
+                }
+            }
+        )*
+    };
+}
+
+native_params!(
+    (A1),
+    (A1, A2),
+    (A1, A2, A3),
+    (A1, A2, A3, A4),
+    (A1, A2, A3, A4, A5),
+    (A1, A2, A3, A4, A5, A6),
+    (A1, A2, A3, A4, A5, A6, A7),
+    (A1, A2, A3, A4, A5, A6, A7, A8),
+    (A1, A2, A3, A4, A5, A6, A7, A8, A9)
+);
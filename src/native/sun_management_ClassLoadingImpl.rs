@@ -0,0 +1,14 @@
+use jni::{objects::JClass, sys::jboolean, JNIEnv};
+
+use super::jni::JNIEnvWrapper;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_ClassLoadingImpl_setVerboseClass<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    verbose: jboolean,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    vm.as_mut_ref().cfg.verbose_class = verbose != 0;
+}
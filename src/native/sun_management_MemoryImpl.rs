@@ -0,0 +1,87 @@
+use jni::{
+    objects::{JClass, JObject},
+    sys::{jboolean, jobject, JNI_TRUE},
+    JNIEnv,
+};
+
+use crate::{
+    handle::Handle,
+    object::{array::JArray, Object},
+    thread::Thread,
+    value::JValue,
+};
+
+use super::jni::JNIEnvWrapper;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_MemoryImpl_getMemoryPools0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jobject {
+    // the spaces are not exposed as memory pools
+    return new_empty_array(env, "[Ljava/lang/management/MemoryPoolMXBean;");
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_MemoryImpl_getMemoryManagers0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jobject {
+    return new_empty_array(env, "[Ljava/lang/management/MemoryManagerMXBean;");
+}
+
+fn new_empty_array(env: JNIEnv, array_class_name: &str) -> jobject {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let array_class = vm
+        .bootstrap_class_loader
+        .load_class(array_class_name)
+        .unwrap();
+    return JArray::empty(array_class, Thread::current()).as_raw_ptr() as _;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_MemoryImpl_getMemoryUsage0<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    heap: jboolean,
+) -> jobject {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let thread = Thread::current();
+    let usage = if heap == JNI_TRUE {
+        vm.heap().heap_usage()
+    } else {
+        vm.heap().non_heap_usage()
+    };
+    let usage_class = vm
+        .bootstrap_class_loader
+        .load_class("java/lang/management/MemoryUsage")
+        .unwrap();
+    let ctor = vm
+        .get_method(usage_class, "<init>", "(JJJJ)V", thread)
+        .unwrap();
+    let result = Handle::new(Object::new(usage_class, thread)).as_ptr();
+    vm.call_obj_void(
+        result,
+        ctor,
+        &[
+            JValue::with_long_val(usage.init as _),
+            JValue::with_long_val(usage.used as _),
+            JValue::with_long_val(usage.committed as _),
+            JValue::with_long_val(usage.max as _),
+        ],
+    );
+    return result.as_raw_ptr() as _;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_MemoryImpl_setVerboseGC<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    _verbose: jboolean,
+) {
+    // there is no GC logging to turn on
+}
@@ -0,0 +1,70 @@
+use jni::{
+    objects::JClass,
+    sys::{jboolean, jobject},
+    JNIEnv,
+};
+
+use crate::{object::array::JArray, thread::Thread};
+
+use super::jni::JNIEnvWrapper;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_ThreadImpl_getThreads<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jobject {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let jthreads = vm.thread_mgr.jthreads();
+    let array_class = vm
+        .bootstrap_class_loader
+        .load_class("[Ljava/lang/Thread;")
+        .unwrap();
+    let result = JArray::new(jthreads.len() as _, array_class, Thread::current());
+    for (idx, jthread) in jthreads.into_iter().enumerate() {
+        result.set(idx as _, jthread);
+    }
+    return result.as_raw_ptr() as _;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_ThreadImpl_resetPeakThreadCount0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    vm.thread_mgr.reset_peak_thread_count();
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_ThreadImpl_setThreadCpuTimeEnabled0<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    _enabled: jboolean,
+) {
+    // thread cpu time is not supported
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_ThreadImpl_setThreadAllocatedMemoryEnabled0<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    _enabled: jboolean,
+) {
+    // thread allocated memory is not supported
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_ThreadImpl_setThreadContentionMonitoringEnabled0<
+    'local,
+>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    _enabled: jboolean,
+) {
+    // thread contention monitoring is not supported
+}
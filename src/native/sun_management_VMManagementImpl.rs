@@ -0,0 +1,217 @@
+use std::time::UNIX_EPOCH;
+
+use jni::{
+    objects::{JClass, JObject},
+    sys::{jboolean, jint, jlong, jobject, jstring, JNI_FALSE},
+    JNIEnv,
+};
+
+use crate::thread::Thread;
+
+use super::jni::JNIEnvWrapper;
+
+/// The version of the management interface, `major.minor`.
+const MANAGEMENT_VERSION: &str = "1.2";
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getVersion0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jstring {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    return vm
+        .get_jstr_from_symbol(vm.get_symbol(MANAGEMENT_VERSION), Thread::current())
+        .as_raw_ptr() as _;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_initOptionalSupportFields<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+    // none of the optional features is supported, the fields stay false
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_isThreadContentionMonitoringEnabled<
+    'local,
+>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jboolean {
+    return JNI_FALSE;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_isThreadCpuTimeEnabled<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jboolean {
+    return JNI_FALSE;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_isThreadAllocatedMemoryEnabled<
+    'local,
+>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jboolean {
+    return JNI_FALSE;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getTotalClassCount<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jlong {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    return vm.stats.classes_loaded() as jlong;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getUnloadedClassCount<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jlong {
+    // classes are never unloaded
+    return 0;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getVerboseClass<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jboolean {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    return vm.cfg.verbose_class as jboolean;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getVerboseGC<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jboolean {
+    return JNI_FALSE;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getProcessId<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jint {
+    return std::process::id() as jint;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getVmArguments0<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jobject {
+    // null stands for no arguments
+    return std::ptr::null_mut();
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getStartupTime<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jlong {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    return vm
+        .start_time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_millis() as jlong);
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getAvailableProcessors<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jint {
+    return std::thread::available_parallelism().map_or(1, |n| n.get()) as _;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getTotalThreadCount<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jlong {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    return vm.thread_mgr.started_thread_count() as jlong;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getLiveThreadCount<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    return vm.thread_mgr.thread_count() as jint;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getPeakThreadCount<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    return vm.thread_mgr.peak_thread_count() as jint;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_management_VMManagementImpl_getDaemonThreadCount<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    return vm.daemon_thread_count() as jint;
+}
+
+/// Defines natives for counters the VM does not keep, which report 0.
+macro_rules! unsupported_counters {
+    ($($native_fn_name:ident),*) => {
+        $(
+            #[allow(non_snake_case)]
+            #[no_mangle]
+            pub extern "system" fn $native_fn_name<'local>(
+                _env: JNIEnv<'local>,
+                _obj_ref: JObject<'local>,
+            ) -> jlong {
+                return 0;
+            }
+        )*
+    };
+}
+
+unsupported_counters!(
+    Java_sun_management_VMManagementImpl_getTotalCompileTime,
+    Java_sun_management_VMManagementImpl_getSafepointCount,
+    Java_sun_management_VMManagementImpl_getTotalSafepointTime,
+    Java_sun_management_VMManagementImpl_getSafepointSyncTime,
+    Java_sun_management_VMManagementImpl_getTotalApplicationNonStoppedTime,
+    Java_sun_management_VMManagementImpl_getLoadedClassSize,
+    Java_sun_management_VMManagementImpl_getUnloadedClassSize,
+    Java_sun_management_VMManagementImpl_getClassLoadingTime,
+    Java_sun_management_VMManagementImpl_getMethodDataSize,
+    Java_sun_management_VMManagementImpl_getInitializedClassCount,
+    Java_sun_management_VMManagementImpl_getClassInitializationTime,
+    Java_sun_management_VMManagementImpl_getClassVerificationTime
+);
@@ -1,21 +1,29 @@
 use std::{
     alloc::Layout,
-    sync::atomic::{AtomicI32, AtomicPtr, Ordering},
+    mem::size_of,
+    sync::atomic::{fence, Ordering},
 };
 
 use jni::{
-    objects::{JClass, JObject},
-    sys::{jboolean, jbyte, jint, jlong},
+    objects::{JByteArray, JClass, JObject, JObjectArray, JString as JNIString},
+    sys::{jboolean, jbyte, jchar, jclass, jdouble, jfloat, jint, jlong, jobject, jshort},
     JNIEnv,
 };
+use paste::paste;
 
 use crate::{
-    memory::{align, POINTER_SIZE},
-    object::prelude::{JInt, Ptr},
+    compat,
+    memory::{align, is_align_of, POINTER_SIZE},
+    object::{
+        array::{JArrayPtr, JByteArrayPtr},
+        prelude::{JInt, JLong, Ptr},
+        string::{JString, JStringPtr},
+    },
+    thread::Thread,
     JClassPtr, ObjectPtr,
 };
 
-use super::jni::JNIEnvWrapper;
+use super::jni::{throw_define_class_err, JNIEnvWrapper};
 
 #[allow(non_snake_case)]
 #[no_mangle]
@@ -25,29 +33,6 @@ pub extern "system" fn Java_sun_misc_Unsafe_registerNatives<'local>(
 ) {
 }
 
-#[allow(non_snake_case)]
-#[no_mangle]
-pub extern "system" fn Java_sun_misc_Unsafe_getByte<'local>(
-    _env: JNIEnv<'local>,
-    _obj_ref: JObject<'local>,
-    address: jlong,
-) -> jbyte {
-    unsafe { *(address as *mut jbyte) }
-}
-
-#[allow(non_snake_case)]
-#[no_mangle]
-pub extern "system" fn Java_sun_misc_Unsafe_putLong<'local>(
-    _env: JNIEnv<'local>,
-    _obj_ref: JObject<'local>,
-    address: jlong,
-    x: jlong,
-) {
-    unsafe {
-        *(address as *mut jlong) = x;
-    }
-}
-
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_sun_misc_Unsafe_allocateMemory<'local>(
@@ -86,6 +71,38 @@ pub extern "system" fn Java_sun_misc_Unsafe_freeMemory<'local>(
     }
 }
 
+/// Fills `bytes` bytes at `offset` of `o`, or at the address `offset` if `o`
+/// is null.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_misc_Unsafe_setMemory<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    o: JObject<'local>,
+    offset: jlong,
+    bytes: jlong,
+    value: jbyte,
+) {
+    let dest: Ptr<u8> = volatile_value_ptr(&o, offset);
+    unsafe { std::ptr::write_bytes(dest.as_mut_raw_ptr(), value as u8, bytes as usize) };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_misc_Unsafe_copyMemory<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    src_base: JObject<'local>,
+    src_offset: jlong,
+    dest_base: JObject<'local>,
+    dest_offset: jlong,
+    bytes: jlong,
+) {
+    let src: Ptr<u8> = volatile_value_ptr(&src_base, src_offset);
+    let dest: Ptr<u8> = volatile_value_ptr(&dest_base, dest_offset);
+    unsafe { std::ptr::copy(src.as_raw_ptr(), dest.as_mut_raw_ptr(), bytes as usize) };
+}
+
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_sun_misc_Unsafe_objectFieldOffset<'local>(
@@ -109,6 +126,122 @@ pub extern "system" fn Java_sun_misc_Unsafe_objectFieldOffset<'local>(
     return offset as jlong;
 }
 
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_misc_Unsafe_ensureClassInitialized<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    cls: JObject<'local>,
+) {
+    if cls.is_null() {
+        todo!("throw NullPointerException");
+    }
+    let cls = JClassPtr::from_raw(cls.as_raw() as _);
+    if let Err(_e) = cls.initialize(Thread::current()) {
+        todo!("throw ExceptionInInitializerError");
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_misc_Unsafe_defineClass__Ljava_lang_String_2_3BIILjava_lang_ClassLoader_2Ljava_security_ProtectionDomain_2<
+    'local,
+>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    name: JNIString<'local>,
+    b: JByteArray<'local>,
+    off: jint,
+    len: jint,
+    loader: JObject<'local>,
+    _protection_domain: JObject<'local>,
+) -> jclass {
+    let loader = ObjectPtr::from_raw(loader.as_raw() as _);
+    return define_class(env, name, b, off, len, loader);
+}
+
+/// Defines the class with the loader of the thread.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_misc_Unsafe_defineClass__Ljava_lang_String_2_3BII<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    name: JNIString<'local>,
+    b: JByteArray<'local>,
+    off: jint,
+    len: jint,
+) -> jclass {
+    let loader = JNIEnvWrapper::from_raw_env(env.get_raw())
+        .thread()
+        .class_loader();
+    return define_class(env, name, b, off, len, loader);
+}
+
+fn define_class(
+    env: JNIEnv,
+    name: JNIString,
+    b: JByteArray,
+    off: jint,
+    len: jint,
+    loader: ObjectPtr,
+) -> jclass {
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    let name = if name.is_null() {
+        None
+    } else {
+        let name = JString::to_rust_string(JStringPtr::from_raw(name.as_raw() as _), &vm);
+        Some(name.replace('.', "/"))
+    };
+    let bytes = byte_array_range(b, off, len);
+    let result = vm.bootstrap_class_loader.define_class_with_loader(
+        name.as_deref(),
+        bytes,
+        loader,
+        env.thread(),
+    );
+    return match result {
+        Ok(class) => class.as_raw_ptr() as jclass,
+        Err(e) => throw_define_class_err(e),
+    };
+}
+
+/// Defines a class that only `host` knows of, see
+/// [`define_anonymous_class`](crate::classfile::class_loader::BootstrapClassLoader::define_anonymous_class).
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_misc_Unsafe_defineAnonymousClass<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    host_class: JClass<'local>,
+    data: JByteArray<'local>,
+    cp_patches: JObjectArray<'local>,
+) -> jclass {
+    if host_class.is_null() || data.is_null() {
+        todo!("throw NullPointerException");
+    }
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    let host = JClassPtr::from_raw(host_class.as_raw() as _);
+    let len = JByteArrayPtr::from_raw(data.as_raw() as _).length();
+    let bytes = byte_array_range(data, 0, len);
+    let cp_patches = if cp_patches.is_null() {
+        Vec::new()
+    } else {
+        let cp_patches = JArrayPtr::from_raw(cp_patches.as_raw() as _);
+        (0..cp_patches.length())
+            .map(|idx| cp_patches.get(idx))
+            .collect()
+    };
+    let result =
+        vm.bootstrap_class_loader
+            .define_anonymous_class(bytes, host, cp_patches, env.thread());
+    return match result {
+        Ok(class) => class.as_raw_ptr() as jclass,
+        Err(e) => throw_define_class_err(e),
+    };
+}
+
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_sun_misc_Unsafe_arrayBaseOffset<'local>(
@@ -140,6 +273,15 @@ pub extern "system" fn Java_sun_misc_Unsafe_addressSize<'local>(
     return crate::memory::POINTER_SIZE as jint;
 }
 
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_misc_Unsafe_pageSize<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jint {
+    return crate::os::page_size() as jint;
+}
+
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_sun_misc_Unsafe_compareAndSwapObject<'local>(
@@ -154,12 +296,15 @@ pub extern "system" fn Java_sun_misc_Unsafe_compareAndSwapObject<'local>(
     let val_ptr: Ptr<ObjectPtr> = target.read_value_ptr(offset as isize);
 
     unsafe {
-        if let Ok(_) = AtomicPtr::from_ptr(val_ptr.as_mut_raw_ptr() as _).compare_exchange(
-            expected.as_raw(),
-            x.as_raw(),
-            Ordering::Acquire,
-            Ordering::Relaxed,
-        ) {
+        let swapped = compat::atomic_ptr_from_ptr(val_ptr.as_mut_raw_ptr() as _)
+            .compare_exchange(
+                expected.as_raw(),
+                x.as_raw(),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .is_ok();
+        if swapped {
             return 1;
         }
     }
@@ -179,18 +324,66 @@ pub extern "system" fn Java_sun_misc_Unsafe_compareAndSwapInt<'local>(
     let target = ObjectPtr::from_raw(o.as_raw() as _);
     let val_ptr: Ptr<JInt> = target.read_value_ptr(offset as isize);
     unsafe {
-        if let Ok(_) = AtomicI32::from_ptr(val_ptr.as_mut_raw_ptr()).compare_exchange(
-            expected,
-            x,
-            Ordering::Acquire,
-            Ordering::Relaxed,
-        ) {
+        let swapped = compat::atomic_i32_from_ptr(val_ptr.as_mut_raw_ptr())
+            .compare_exchange(expected, x, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok();
+        if swapped {
+            return 1;
+        }
+    }
+    return 0;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_misc_Unsafe_compareAndSwapLong<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    o: JObject<'local>,
+    offset: jlong,
+    expected: jlong,
+    x: jlong,
+) -> jboolean {
+    let target = ObjectPtr::from_raw(o.as_raw() as _);
+    let val_ptr: Ptr<JLong> = target.read_value_ptr(offset as isize);
+    unsafe {
+        let swapped = compat::atomic_i64_from_ptr(val_ptr.as_mut_raw_ptr())
+            .compare_exchange(expected, x, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok();
+        if swapped {
             return 1;
         }
     }
     return 0;
 }
 
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_misc_Unsafe_getObject<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    o: JObject<'local>,
+    offset: jlong,
+) -> jobject {
+    let val_ptr: Ptr<jobject> = volatile_value_ptr(&o, offset);
+    return unsafe { std::ptr::read_unaligned(val_ptr.as_raw_ptr()) };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_misc_Unsafe_putObject<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    o: JObject<'local>,
+    offset: jlong,
+    x: JObject<'local>,
+) {
+    let val_ptr: Ptr<jobject> = volatile_value_ptr(&o, offset);
+    unsafe {
+        std::ptr::write_unaligned(val_ptr.as_mut_raw_ptr(), x.as_raw());
+    }
+}
+
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_sun_misc_Unsafe_putOrderedObject<'local>(
@@ -204,6 +397,140 @@ pub extern "system" fn Java_sun_misc_Unsafe_putOrderedObject<'local>(
     let val_ptr: Ptr<ObjectPtr> = target.read_value_ptr(offset as isize);
 
     unsafe {
-        AtomicPtr::from_ptr(val_ptr.as_mut_raw_ptr() as _).store(x.as_raw(), Ordering::Relaxed);
+        compat::atomic_ptr_from_ptr(val_ptr.as_mut_raw_ptr() as _)
+            .store(x.as_raw(), Ordering::Relaxed);
+    }
+}
+
+macro_rules! volatile_accessors {
+    ($(($type_name:ident, $jni_type:ty)),*) => {
+        paste! {
+            $(
+                #[allow(non_snake_case)]
+                #[no_mangle]
+                pub extern "system" fn [<Java_sun_misc_Unsafe_get $type_name Volatile>]<'local>(
+                    _env: JNIEnv<'local>,
+                    _obj_ref: JObject<'local>,
+                    o: JObject<'local>,
+                    offset: jlong,
+                ) -> $jni_type {
+                    return read_volatile(volatile_value_ptr::<$jni_type>(&o, offset));
+                }
+
+                #[allow(non_snake_case)]
+                #[no_mangle]
+                pub extern "system" fn [<Java_sun_misc_Unsafe_put $type_name Volatile>]<'local>(
+                    _env: JNIEnv<'local>,
+                    _obj_ref: JObject<'local>,
+                    o: JObject<'local>,
+                    offset: jlong,
+                    x: $jni_type,
+                ) {
+                    write_volatile(volatile_value_ptr::<$jni_type>(&o, offset), x);
+                }
+            )*
+        }
+    };
+}
+
+volatile_accessors!(
+    (Object, jobject),
+    (Boolean, jboolean),
+    (Byte, jbyte),
+    (Short, jshort),
+    (Char, jchar),
+    (Int, jint),
+    (Long, jlong),
+    (Float, jfloat),
+    (Double, jdouble)
+);
+
+/// The accessors of an absolute address, e.g. `getInt(long)`, which overload
+/// those of an object field, so they are registered by their long names.
+macro_rules! address_accessors {
+    ($(($type_name:ident, $jni_type:ty, $sig:ident)),*) => {
+        paste! {
+            $(
+                #[allow(non_snake_case)]
+                #[no_mangle]
+                pub extern "system" fn [<Java_sun_misc_Unsafe_get $type_name __J>]<'local>(
+                    _env: JNIEnv<'local>,
+                    _obj_ref: JObject<'local>,
+                    address: jlong,
+                ) -> $jni_type {
+                    return unsafe { std::ptr::read_unaligned(address as *const $jni_type) };
+                }
+
+                #[allow(non_snake_case)]
+                #[no_mangle]
+                pub extern "system" fn [<Java_sun_misc_Unsafe_put $type_name __J $sig>]<'local>(
+                    _env: JNIEnv<'local>,
+                    _obj_ref: JObject<'local>,
+                    address: jlong,
+                    x: $jni_type,
+                ) {
+                    unsafe { std::ptr::write_unaligned(address as *mut $jni_type, x) };
+                }
+            )*
+        }
+    };
+}
+
+address_accessors!(
+    (Byte, jbyte, B),
+    (Short, jshort, S),
+    (Int, jint, I),
+    (Long, jlong, J)
+);
+
+/// A null `o` means `offset` is an absolute address. Plain accesses share it
+/// with the volatile ones.
+fn volatile_value_ptr<T>(o: &JObject, offset: jlong) -> Ptr<T> {
+    if o.is_null() {
+        return Ptr::from_isize(offset as isize);
+    }
+    let target = ObjectPtr::from_raw(o.as_raw() as _);
+    return Ptr::from_addr(target.as_address().offset(offset as isize));
+}
+
+/// A volatile read, single-copy atomic for the 64-bit values like the
+/// volatile fields, see jvms-17.7.
+fn read_volatile<T: Copy>(val_ptr: Ptr<T>) -> T {
+    if size_of::<T>() == size_of::<i64>() {
+        debug_assert!(is_align_of(val_ptr.as_usize(), size_of::<i64>()));
+        let bits = unsafe { compat::atomic_i64_from_ptr(val_ptr.as_mut_raw_ptr().cast()) }
+            .load(Ordering::SeqCst);
+        return unsafe { std::mem::transmute_copy(&bits) };
+    }
+    let value = unsafe { std::ptr::read_unaligned(val_ptr.as_raw_ptr()) };
+    fence(Ordering::Acquire);
+    return value;
+}
+
+/// A volatile write, see [`read_volatile`].
+fn write_volatile<T: Copy>(val_ptr: Ptr<T>, x: T) {
+    if size_of::<T>() == size_of::<i64>() {
+        debug_assert!(is_align_of(val_ptr.as_usize(), size_of::<i64>()));
+        let bits: i64 = unsafe { std::mem::transmute_copy(&x) };
+        unsafe { compat::atomic_i64_from_ptr(val_ptr.as_mut_raw_ptr().cast()) }
+            .store(bits, Ordering::SeqCst);
+        return;
+    }
+    fence(Ordering::Release);
+    unsafe {
+        std::ptr::write_unaligned(val_ptr.as_mut_raw_ptr(), x);
+    }
+    fence(Ordering::SeqCst);
+}
+
+fn byte_array_range(b: JByteArray, off: jint, len: jint) -> Vec<u8> {
+    if b.is_null() {
+        todo!("throw NullPointerException");
+    }
+    let b = JByteArrayPtr::from_raw(b.as_raw() as _);
+    if off < 0 || len < 0 || off > b.length() - len {
+        todo!("throw ArrayIndexOutOfBoundsException");
     }
+    let bytes = &b.to_slice()[off as usize..(off + len) as usize];
+    return bytes.iter().map(|byte| *byte as u8).collect();
 }
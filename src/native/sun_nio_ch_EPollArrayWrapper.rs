@@ -0,0 +1,104 @@
+use jni::{
+    objects::{JClass, JObject},
+    sys::{jint, jlong},
+    JNIEnv,
+};
+
+use crate::{memory::Address, os::aio};
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_EPollArrayWrapper_init<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_EPollArrayWrapper_epollCreate<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jint {
+    return match aio::epoll_create() {
+        Ok(epfd) => epfd,
+        Err(_e) => todo!("throw IOException: epoll_create failed"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_EPollArrayWrapper_sizeofEPollEvent<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jint {
+    return event_layout().0 as jint;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_EPollArrayWrapper_offsetofData<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jint {
+    return event_layout().1 as jint;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_EPollArrayWrapper_epollCtl<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    epfd: jint,
+    opcode: jint,
+    fd: jint,
+    events: jint,
+) {
+    if let Err(e) = aio::epoll_ctl(epfd, opcode, fd, events as u32) {
+        // the descriptor of a channel closed before its key was deregistered
+        let stale_fd = [libc::EBADF, libc::ENOENT, libc::EPERM];
+        if !e
+            .raw_os_error()
+            .map_or(false, |err| stale_fd.contains(&err))
+        {
+            todo!("throw IOException: epoll_ctl failed");
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_EPollArrayWrapper_epollWait<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    address: jlong,
+    numfds: jint,
+    timeout: jlong,
+    epfd: jint,
+) -> jint {
+    let events = Address::from_usize(address as usize);
+    return match aio::epoll_wait(epfd, events, numfds as usize, timeout) {
+        Ok(updated) => updated as jint,
+        Err(_e) => todo!("throw IOException: epoll_wait failed"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_EPollArrayWrapper_interrupt<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: jint,
+) {
+    if let Err(_e) = aio::wake_up(fd) {
+        todo!("throw IOException: write failed");
+    }
+}
+
+/// The size of an `epoll_event` and the offset of its data.
+fn event_layout() -> (usize, usize) {
+    return match aio::epoll_event_layout() {
+        Ok(layout) => layout,
+        Err(e) => unreachable!("the epoll selector is only chosen on Linux: {}", e),
+    };
+}
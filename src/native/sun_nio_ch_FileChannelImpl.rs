@@ -0,0 +1,103 @@
+use std::io::{Seek, SeekFrom};
+
+use jni::{
+    objects::{JClass, JObject},
+    sys::{jint, jlong},
+    JNIEnv,
+};
+
+use crate::{memory::Address, os, ObjectPtr};
+
+use super::{java_io_FileDescriptor::file_of, jni::JNIEnvWrapper};
+
+/// see sun.nio.ch.FileChannelImpl#MAP_RO
+const MAP_RO: jint = 0;
+/// see sun.nio.ch.FileChannelImpl#MAP_RW
+const MAP_RW: jint = 1;
+/// see sun.nio.ch.FileChannelImpl#MAP_PV
+const MAP_PV: jint = 2;
+
+/// Returns the alignment of the positions passed to `map0`.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileChannelImpl_initIDs<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jlong {
+    return os::allocation_granularity() as jlong;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileChannelImpl_map0<'local>(
+    env: JNIEnv<'local>,
+    obj_ref: JObject<'local>,
+    prot: jint,
+    position: jlong,
+    length: jlong,
+) -> jlong {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mode = match prot {
+        MAP_RO => os::MapMode::ReadOnly,
+        MAP_RW => os::MapMode::ReadWrite,
+        MAP_PV => os::MapMode::Private,
+        _ => unreachable!("unknown map mode {}", prot),
+    };
+    let obj_ref = ObjectPtr::from_raw(obj_ref.as_raw() as _);
+    let fd = match vm.get_field(obj_ref, "fd") {
+        Ok(fd) => fd,
+        Err(e) => todo!("{:?}", e),
+    };
+    let file = file_of(fd, vm.as_ref());
+    return match vm
+        .mapped_files
+        .map(&file, position as u64, length as usize, mode)
+    {
+        Ok(addr) => addr.as_usize() as jlong,
+        Err(e) if e.kind() == std::io::ErrorKind::OutOfMemory => {
+            todo!("throw OutOfMemoryError: Map failed")
+        }
+        Err(_e) => todo!("throw IOException: Map failed"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileChannelImpl_unmap0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    address: jlong,
+    length: jlong,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    return match vm
+        .mapped_files
+        .unmap(Address::from_usize(address as usize), length as usize)
+    {
+        Ok(()) => 0,
+        Err(_e) => todo!("throw IOException: Unmap failed"),
+    };
+}
+
+/// Moves the file pointer of `fd` to `offset`, or just returns it for a
+/// negative `offset`.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileChannelImpl_position0<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    fd: JObject<'local>,
+    offset: jlong,
+) -> jlong {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut file = file_of(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    let pos = if offset < 0 {
+        file.stream_position()
+    } else {
+        file.seek(SeekFrom::Start(offset as u64))
+    };
+    return match pos {
+        Ok(pos) => pos as jlong,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
@@ -0,0 +1,155 @@
+use std::io::{Read, Write};
+
+use jni::{
+    objects::{JClass, JObject},
+    sys::{jboolean, jint, jlong},
+    JNIEnv,
+};
+
+use crate::{os::aio, ObjectPtr};
+
+use super::{
+    java_io_FileDescriptor::{close_file, file_of},
+    jni::JNIEnvWrapper,
+    sun_nio_ch_IOUtil::{io_status_of, IOS_EOF},
+};
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileDispatcherImpl_init<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+}
+
+/// Reads into the `len` bytes at `address`, returning the bytes read, or an
+/// `IOStatus` at the end of the stream or if a non-blocking read would block.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileDispatcherImpl_read0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    address: jlong,
+    len: jint,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut file = file_of(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    let buf = unsafe { std::slice::from_raw_parts_mut(address as *mut u8, len as usize) };
+    return match file.read(buf) {
+        Ok(0) => IOS_EOF,
+        Ok(read) => read as jint,
+        Err(e) => match io_status_of(&e) {
+            Some(status) => status,
+            None => todo!("throw IOException: {}", e),
+        },
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileDispatcherImpl_write0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    address: jlong,
+    len: jint,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut file = file_of(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    let buf = unsafe { std::slice::from_raw_parts(address as *const u8, len as usize) };
+    return match file.write(buf) {
+        Ok(written) => written as jint,
+        Err(e) => match io_status_of(&e) {
+            Some(status) => status,
+            None => todo!("throw IOException: {}", e),
+        },
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileDispatcherImpl_size0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+) -> jlong {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let file = file_of(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    return match file.metadata() {
+        Ok(metadata) => metadata.len() as jlong,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileDispatcherImpl_truncate0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    size: jlong,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let file = file_of(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    return match file.set_len(size as u64) {
+        Ok(()) => 0,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileDispatcherImpl_force0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    metadata: jboolean,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let file = file_of(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    let synced = if metadata != 0 {
+        file.sync_all()
+    } else {
+        file.sync_data()
+    };
+    return match synced {
+        Ok(()) => 0,
+        Err(_e) => todo!("throw IOException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileDispatcherImpl_preClose0<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    _fd: JObject<'local>,
+) {
+    // no thread can be blocked reading or writing the file, see NativeThread
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileDispatcherImpl_close0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    close_file(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_FileDispatcherImpl_closeIntFD<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: jint,
+) {
+    if fd != -1 {
+        if let Err(_e) = aio::close(fd) {
+            todo!("throw IOException: Close failed");
+        }
+    }
+}
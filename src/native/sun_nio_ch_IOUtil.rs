@@ -0,0 +1,142 @@
+use std::io;
+
+use jni::{
+    objects::{JClass, JObject},
+    sys::{jboolean, jint, jlong},
+    JNIEnv,
+};
+
+use crate::{os::aio, ObjectPtr};
+
+use super::{
+    java_io_FileDescriptor::{fd_val, set_fd_val},
+    jni::JNIEnvWrapper,
+};
+
+/// see sun.nio.ch.IOStatus#EOF
+pub(super) const IOS_EOF: jint = -1;
+/// see sun.nio.ch.IOStatus#UNAVAILABLE
+pub(super) const IOS_UNAVAILABLE: jint = -2;
+/// see sun.nio.ch.IOStatus#INTERRUPTED
+pub(super) const IOS_INTERRUPTED: jint = -3;
+
+/// The status a non-blocking operation failing with `err` returns instead of
+/// throwing, if any.
+pub(super) fn io_status_of(err: &io::Error) -> Option<jint> {
+    return match err.kind() {
+        io::ErrorKind::WouldBlock => Some(IOS_UNAVAILABLE),
+        io::ErrorKind::Interrupted => Some(IOS_INTERRUPTED),
+        _ => None,
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_IOUtil_initIDs<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_IOUtil_iovMax<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jint {
+    #[cfg(target_family = "unix")]
+    {
+        let iov_max = unsafe { libc::sysconf(libc::_SC_IOV_MAX) };
+        return if iov_max < 0 { 16 } else { iov_max as jint };
+    }
+    #[cfg(target_os = "windows")]
+    {
+        return 16;
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_IOUtil_fdLimit<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jint {
+    return match aio::fd_limit() {
+        Ok(limit) => limit.min(jint::MAX as usize) as jint,
+        Err(_e) => todo!("throw IOException: getrlimit failed"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_IOUtil_fdVal<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    return fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_IOUtil_setfdVal<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    value: jint,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    set_fd_val(ObjectPtr::from_raw(fd.as_raw() as _), value, vm.as_ref());
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_IOUtil_configureBlocking<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    blocking: jboolean,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    if let Err(_e) = aio::set_blocking(fd, blocking != 0) {
+        todo!("throw IOException: Configure blocking failed");
+    }
+}
+
+/// Returns the read end of a new pipe in the high and the write end in the
+/// low 32 bits.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_IOUtil_makePipe<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    blocking: jboolean,
+) -> jlong {
+    let [read_fd, write_fd] = match aio::pipe() {
+        Ok(fds) => fds,
+        Err(_e) => todo!("throw IOException: Pipe failed"),
+    };
+    if blocking == 0 {
+        let result =
+            aio::set_blocking(read_fd, false).and_then(|_| aio::set_blocking(write_fd, false));
+        if let Err(_e) = result {
+            todo!("throw IOException: Configure blocking failed");
+        }
+    }
+    return ((read_fd as jlong) << 32) | (write_fd as u32 as jlong);
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_IOUtil_drain<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: jint,
+) -> jboolean {
+    return match aio::drain(fd) {
+        Ok(drained) => drained as jboolean,
+        Err(_e) => todo!("throw IOException: Drain"),
+    };
+}
@@ -0,0 +1,94 @@
+use jni::{
+    objects::{JClass, JObject},
+    sys::{jint, jlong, jshort},
+    JNIEnv,
+};
+
+use crate::{memory::Address, os::aio};
+
+use super::jni::JNIEnvWrapper;
+
+const KQUEUE_ARRAY_WRAPPER: &str = "sun.nio.ch.KQueueArrayWrapper";
+
+/// Sets the filters and the layout of `struct kevent` the Java code reads
+/// the events with.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_KQueueArrayWrapper_initStructSizes<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let layout = match aio::kevent_layout() {
+        Ok(layout) => layout,
+        Err(e) => unreachable!("the kqueue selector is only chosen on macOS: {}", e),
+    };
+    for (field, value) in [
+        ("EVFILT_READ", layout.read_filter),
+        ("EVFILT_WRITE", layout.write_filter),
+        ("SIZEOF_KEVENT", layout.size as jshort),
+        ("FD_OFFSET", layout.ident_offset as jshort),
+        ("FILTER_OFFSET", layout.filter_offset as jshort),
+    ] {
+        if let Err(e) = vm.set_static(KQUEUE_ARRAY_WRAPPER, field, value) {
+            todo!("{:?}", e);
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_KQueueArrayWrapper_init<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+) -> jint {
+    return match aio::kqueue_create() {
+        Ok(kq) => kq,
+        Err(_e) => todo!("throw IOException: KQueueArrayWrapper: kqueue() failed"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_KQueueArrayWrapper_register0<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    kq: jint,
+    fd: jint,
+    read: jint,
+    write: jint,
+) {
+    // like the JDK, a failed registration shows as a missing event
+    if let Err(e) = aio::kevent_register(kq, fd, read != 0, write != 0) {
+        log::debug!("cannot register {} with kqueue {}: {}", fd, kq, e);
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_KQueueArrayWrapper_kevent0<'local>(
+    _env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    kq: jint,
+    kevent_address: jlong,
+    kevent_count: jint,
+    timeout: jlong,
+) -> jint {
+    let events = Address::from_usize(kevent_address as usize);
+    return match aio::kevent_poll(kq, events, kevent_count as usize, timeout) {
+        Ok(updated) => updated as jint,
+        Err(_e) => todo!("throw IOException: KQueueArrayWrapper: kqueue failed"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_KQueueArrayWrapper_interrupt<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: jint,
+) {
+    if let Err(_e) = aio::wake_up(fd) {
+        todo!("throw IOException: KQueueArrayWrapper: interrupt failed");
+    }
+}
@@ -0,0 +1,29 @@
+use jni::{objects::JClass, sys::jlong, JNIEnv};
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_NativeThread_init<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_NativeThread_current<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jlong {
+    // no thread blocked in an I/O operation can be signalled, 0 tells
+    // NativeThreadSet so
+    return 0;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_NativeThread_signal<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    _thread: jlong,
+) {
+}
@@ -0,0 +1,316 @@
+use std::net::{Ipv4Addr, SocketAddrV4};
+
+use jni::{
+    objects::{JClass, JObject},
+    sys::{jboolean, jint, jobject, JNI_FALSE},
+    JNIEnv,
+};
+
+use crate::{os::aio, sandbox::ResourceAccess, value::JValue, vm::VM, ObjectPtr};
+
+use super::{
+    java_io_FileDescriptor::fd_val,
+    jni::JNIEnvWrapper,
+    sun_nio_ch_IOUtil::{io_status_of, IOS_UNAVAILABLE},
+};
+
+/// see java.net.InetAddress#IPv4
+const IPV4: jint = 1;
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_initIDs<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_isIPv6Available0<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jboolean {
+    // the sockets of os::aio are IPv4 only
+    return JNI_FALSE;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_isExclusiveBindAvailable<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jint {
+    return -1;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_canIPv6SocketJoinIPv4Group0<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jboolean {
+    return JNI_FALSE;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_canJoin6WithIPv4Group0<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jboolean {
+    return JNI_FALSE;
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_socket0<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    _prefer_ipv6: jboolean,
+    stream: jboolean,
+    reuse: jboolean,
+) -> jint {
+    return match aio::socket(stream != 0, reuse != 0) {
+        Ok(fd) => fd,
+        Err(_e) => todo!("throw SocketException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_bind0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    _prefer_ipv6: jboolean,
+    _use_excl_bind: jboolean,
+    addr: JObject<'local>,
+    port: jint,
+) {
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    let addr = ipv4_of(ObjectPtr::from_raw(addr.as_raw() as _), vm.as_ref());
+    let local = SocketAddrV4::new(addr, port as u16);
+    if !vm
+        .cfg
+        .sandbox
+        .check_access(ResourceAccess::Bind(local.into()), env.thread())
+    {
+        return;
+    }
+    if let Err(_e) = aio::bind(fd, local) {
+        todo!("throw BindException");
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_listen<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    backlog: jint,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    if let Err(_e) = aio::listen(fd, backlog) {
+        todo!("throw SocketException");
+    }
+}
+
+/// Returns 1 once connected, or `IOStatus.UNAVAILABLE` while a non-blocking
+/// socket is connecting.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_connect0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    _prefer_ipv6: jboolean,
+    fd: JObject<'local>,
+    remote: JObject<'local>,
+    remote_port: jint,
+) -> jint {
+    let env = JNIEnvWrapper::from_raw_env(env.get_raw());
+    let vm = env.vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    let remote = ipv4_of(ObjectPtr::from_raw(remote.as_raw() as _), vm.as_ref());
+    let remote = SocketAddrV4::new(remote, remote_port as u16);
+    if !vm
+        .cfg
+        .sandbox
+        .check_access(ResourceAccess::Connect(remote.into()), env.thread())
+    {
+        return IOS_UNAVAILABLE;
+    }
+    return match aio::connect(fd, remote) {
+        Ok(true) => 1,
+        Ok(false) => IOS_UNAVAILABLE,
+        Err(e) => match io_status_of(&e) {
+            Some(status) => status,
+            None => todo!("throw ConnectException: {}", e),
+        },
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_shutdown<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    how: jint,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    if let Err(e) = aio::shutdown(fd, how) {
+        // shutting down a socket that is not connected
+        if e.kind() != std::io::ErrorKind::NotConnected {
+            todo!("throw IOException: Unable to shutdown socket");
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_localPort<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    return match aio::local_addr(fd) {
+        Ok(addr) => addr.port() as jint,
+        Err(_e) => todo!("throw SocketException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_localInetAddress<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+) -> jobject {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    return match aio::local_addr(fd) {
+        Ok(addr) => inet_address_of(*addr.ip(), vm.as_ref()).as_raw_ptr() as _,
+        Err(_e) => todo!("throw SocketException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_remotePort<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    return match aio::peer_addr(fd) {
+        Ok(addr) => addr.port() as jint,
+        Err(_e) => todo!("throw SocketException"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_remoteInetAddress<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+) -> jobject {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    return match aio::peer_addr(fd) {
+        Ok(addr) => inet_address_of(*addr.ip(), vm.as_ref()).as_raw_ptr() as _,
+        Err(_e) => todo!("throw SocketException"),
+    };
+}
+
+/// `level` and `opt` are the native constants of `SocketOptionRegistry`.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_getIntOption0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    _may_need_conversion: jboolean,
+    level: jint,
+    opt: jint,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    return match aio::get_socket_option(fd, level, opt) {
+        Ok(value) => value,
+        Err(_e) => todo!("throw SocketException: sun.nio.ch.Net.getIntOption"),
+    };
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_Net_setIntOption0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    _may_need_conversion: jboolean,
+    level: jint,
+    opt: jint,
+    arg: jint,
+) {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    if let Err(_e) = aio::set_socket_option(fd, level, opt, arg) {
+        todo!("throw SocketException: sun.nio.ch.Net.setIntOption");
+    }
+}
+
+/// The address of the `java.net.InetAddress` `addr`.
+pub(super) fn ipv4_of(addr: ObjectPtr, vm: &VM) -> Ipv4Addr {
+    if addr.is_null() {
+        todo!("throw NullPointerException");
+    }
+    let holder: ObjectPtr = match vm.get_field(addr, "holder") {
+        Ok(holder) => holder,
+        Err(e) => todo!("{:?}", e),
+    };
+    let (family, addr): (jint, jint) = match (
+        vm.get_field(holder, "family"),
+        vm.get_field(holder, "address"),
+    ) {
+        (Ok(family), Ok(addr)) => (family, addr),
+        (Err(e), _) | (_, Err(e)) => todo!("{:?}", e),
+    };
+    if family != IPV4 {
+        todo!("throw UnsupportedAddressTypeException");
+    }
+    return Ipv4Addr::from(addr as u32);
+}
+
+/// A `java.net.Inet4Address` without a host name.
+pub(super) fn inet_address_of(addr: Ipv4Addr, vm: &VM) -> ObjectPtr {
+    let args = [
+        JValue::with_obj_null(),
+        JValue::with_int_val(u32::from(addr) as jint),
+    ];
+    return match vm.new_object("java.net.Inet4Address", "(Ljava/lang/String;I)V", &args) {
+        Ok(addr) => addr,
+        Err(e) => todo!("{:?}", e),
+    };
+}
+
+pub(super) fn inet_socket_address_of(addr: SocketAddrV4, vm: &VM) -> ObjectPtr {
+    let args = [
+        JValue::with_obj_val(inet_address_of(*addr.ip(), vm)),
+        JValue::with_int_val(addr.port() as jint),
+    ];
+    let descriptor = "(Ljava/net/InetAddress;I)V";
+    return match vm.new_object("java.net.InetSocketAddress", descriptor, &args) {
+        Ok(addr) => addr,
+        Err(e) => todo!("{:?}", e),
+    };
+}
@@ -0,0 +1,48 @@
+use jni::{
+    objects::{JClass, JObject},
+    sys::jint,
+    JNIEnv,
+};
+
+use crate::{object::array::JArrayPtr, os::aio, ObjectPtr};
+
+use super::{
+    java_io_FileDescriptor::{fd_val, set_fd_val},
+    jni::JNIEnvWrapper,
+    sun_nio_ch_IOUtil::io_status_of,
+    sun_nio_ch_Net::inet_socket_address_of,
+};
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_ServerSocketChannelImpl_initIDs<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) {
+}
+
+/// Accepts a connection into `newfd` and stores its remote address in
+/// `isaa[0]`, returns 1 or `IOStatus.UNAVAILABLE` if none is pending.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_ServerSocketChannelImpl_accept0<'local>(
+    env: JNIEnv<'local>,
+    _obj_ref: JObject<'local>,
+    ssfd: JObject<'local>,
+    newfd: JObject<'local>,
+    isaa: JObject<'local>,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let ssfd = fd_val(ObjectPtr::from_raw(ssfd.as_raw() as _), vm.as_ref());
+    let (fd, remote) = match aio::accept(ssfd) {
+        Ok(accepted) => accepted,
+        Err(e) => match io_status_of(&e) {
+            Some(status) => return status,
+            None => todo!("throw IOException: {}", e),
+        },
+    };
+    set_fd_val(ObjectPtr::from_raw(newfd.as_raw() as _), fd, vm.as_ref());
+    let isaa = JArrayPtr::from_raw(isaa.as_raw() as _);
+    isaa.set(0, inet_socket_address_of(remote, vm.as_ref()));
+    return 1;
+}
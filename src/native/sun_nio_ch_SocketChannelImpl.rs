@@ -0,0 +1,36 @@
+use jni::{
+    objects::{JClass, JObject},
+    sys::{jboolean, jint},
+    JNIEnv,
+};
+
+use crate::{os::aio, ObjectPtr};
+
+use super::{
+    java_io_FileDescriptor::fd_val,
+    jni::JNIEnvWrapper,
+    sun_nio_ch_IOUtil::{io_status_of, IOS_UNAVAILABLE},
+};
+
+/// Completes a pending connect, returns 1 or `IOStatus.UNAVAILABLE` while
+/// it is still in progress.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_nio_ch_SocketChannelImpl_checkConnect<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    fd: JObject<'local>,
+    block: jboolean,
+    ready: jboolean,
+) -> jint {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let fd = fd_val(ObjectPtr::from_raw(fd.as_raw() as _), vm.as_ref());
+    return match aio::finish_connect(fd, block != 0, ready != 0) {
+        Ok(true) => 1,
+        Ok(false) => IOS_UNAVAILABLE,
+        Err(e) => match io_status_of(&e) {
+            Some(status) => status,
+            None => todo!("throw ConnectException: {}", e),
+        },
+    };
+}
@@ -2,13 +2,14 @@ use jni::{sys::jobject, JNIEnv};
 
 use crate::{
     handle::Handle,
-    object::{array::JArrayPtr, class::JClass, Object},
+    object::{array::JArrayPtr, class::JClass, method::MethodPtr, Object},
     thread::Thread,
     value::JValue,
-    JClassPtr, ObjectPtr,
+    vm::VMPtr,
+    ObjectPtr,
 };
 
-use super::jni::JNIEnvWrapper;
+use super::{java_lang_Class::param_type, jni::JNIEnvWrapper};
 
 #[allow(non_snake_case)]
 #[no_mangle]
@@ -30,14 +31,28 @@ pub extern "system" fn Java_sun_reflect_NativeConstructorAccessorImpl_newInstanc
         todo!("throw InvocationTargetException");
     }
     let args = JArrayPtr::from_raw(args.as_raw() as _);
+    let j_args = unbox_args(ctor_init, args, vm);
+    let thread = Thread::current();
+    // classes loaded by Class.forName(name, false, loader) are not initialized yet
+    if let Err(_e) = decl_cls.initialize(thread) {
+        todo!("throw ExceptionInInitializerError");
+    }
+    let result = Handle::new(Object::new(decl_cls, thread)).as_ptr();
+    vm.call_obj_void(result, ctor_init, &j_args);
+    return result.as_raw_ptr() as _;
+}
+
+/// The arguments of a reflective call of `method`, with those of primitive
+/// parameters unboxed.
+pub(super) fn unbox_args(method: MethodPtr, args: JArrayPtr, vm: VMPtr) -> Vec<JValue> {
+    let class_infos = vm.shared_objs().class_infos();
     let args_len = if args.is_not_null() { args.length() } else { 0 };
-    let native_params = ctor_init.params();
-    if args_len != native_params.length() {
+    if args_len != method.param_count() {
         todo!("throw IllegalArgumentException");
     }
     let mut j_args = Vec::with_capacity(args_len as usize);
     for idx in 0..args_len {
-        let param_type: JClassPtr = native_params.get(idx).cast();
+        let param_type = param_type(method, idx, vm.as_ref());
         let arg = args.get(idx);
         if arg.is_null() {
             if JClass::is_primitive(param_type) {
@@ -58,9 +73,5 @@ pub extern "system" fn Java_sun_reflect_NativeConstructorAccessorImpl_newInstanc
         }
         todo!("throw IllegalArgumentException");
     }
-    debug_assert_eq!(j_args.len(), native_params.length() as usize);
-    let thread = Thread::current();
-    let result = Handle::new(Object::new(decl_cls, thread)).as_ptr();
-    vm.call_obj_void(result, ctor_init, &j_args);
-    return result.as_raw_ptr() as _;
+    return j_args;
 }
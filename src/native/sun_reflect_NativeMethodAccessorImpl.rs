@@ -0,0 +1,64 @@
+use jni::{
+    objects::{JClass, JObject, JObjectArray},
+    sys::jobject,
+    JNIEnv,
+};
+
+use crate::{
+    object::{array::JArrayPtr, class::JClass as InternalJClass},
+    value::JValue,
+    ObjectPtr,
+};
+
+use super::{
+    java_lang_Class::ret_type, jni::JNIEnvWrapper,
+    sun_reflect_NativeConstructorAccessorImpl::unbox_args,
+};
+
+/// Calls the method of `method` like `Method.invoke`, a primitive result
+/// boxed by `valueOf` like the result of a call from Java.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_reflect_NativeMethodAccessorImpl_invoke0<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    method: JObject<'local>,
+    obj: JObject<'local>,
+    args: JObjectArray<'local>,
+) -> jobject {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let class_infos = vm.shared_objs().class_infos();
+    let method_info = class_infos.java_lang_reflect_method_info();
+
+    let method = ObjectPtr::from_raw(method.as_raw() as _);
+    let decl_cls = method_info.get_decl_cls(method);
+    let target = decl_cls.get_method_with_index(method_info.get_slot(method));
+    if target.is_null() {
+        todo!("throw InternalError");
+    }
+    let args = unbox_args(target, JArrayPtr::from_raw(args.as_raw() as _), vm);
+    let result: Result<JValue, _> = if target.is_static() {
+        vm.call_static(decl_cls, target, &args)
+    } else {
+        let obj = ObjectPtr::from_raw(obj.as_raw() as _);
+        if obj.is_null() {
+            todo!("throw NullPointerException");
+        }
+        if !decl_cls.is_assignable_from(obj.jclass(), vm) {
+            todo!("throw IllegalArgumentException");
+        }
+        vm.call_virtual(obj, target, &args)
+    };
+    let result = match result {
+        Ok(result) => result,
+        Err(_e) => todo!("throw InvocationTargetException"),
+    };
+    let ret_type = ret_type(target, vm.as_ref());
+    if !InternalJClass::is_primitive(ret_type) {
+        return result.obj_val().as_raw_ptr() as _;
+    }
+    return match class_infos.get_boxed_jvalue(result, ret_type, vm.as_ref()) {
+        Ok(boxed) => boxed.as_raw_ptr() as _,
+        Err(_e) => todo!("throw InvocationTargetException"),
+    };
+}
@@ -4,19 +4,48 @@ use jni::{
     JNIEnv,
 };
 
-use crate::{thread::Thread, JClassPtr};
+use crate::JClassPtr;
+
+use super::jni::JNIEnvWrapper;
 
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "system" fn Java_sun_reflect_Reflection_getCallerClass<'local>(
-    _env: JNIEnv<'local>,
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jclass {
+    let caller_cls = match JNIEnvWrapper::from_raw_env(env.get_raw())
+        .thread()
+        .interpreter()
+        .caller_sensitive_caller_class()
+    {
+        Some(caller_cls) => caller_cls,
+        None => todo!("throw InternalError: CallerSensitive annotation expected at frame 1"),
+    };
+    if caller_cls.is_not_null() {
+        log::trace!(
+            "Java_sun_reflect_Reflection_getCallerClass {}",
+            caller_cls.name().as_str()
+        );
+    }
+    caller_cls.as_mut_raw_ptr() as jclass
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_reflect_Reflection_getCallerClass0<'local>(
+    env: JNIEnv<'local>,
     _cls_ref: JClass<'local>,
+    depth: jint,
 ) -> jclass {
-    let caller_cls = Thread::current().interpreter().grand_parent_stack_class();
-    log::trace!(
-        "Java_sun_reflect_Reflection_getCallerClass {}",
-        caller_cls.name().as_str()
-    );
+    // negative depths find no class, like a stack too shallow
+    let caller_cls = match usize::try_from(depth) {
+        Ok(depth) => JNIEnvWrapper::from_raw_env(env.get_raw())
+            .thread()
+            .interpreter()
+            .caller_class_at(depth),
+        Err(_) => JClassPtr::null(),
+    };
     caller_cls.as_mut_raw_ptr() as jclass
 }
 
@@ -27,6 +56,9 @@ pub extern "system" fn Java_sun_reflect_Reflection_getClassAccessFlags<'local>(
     _cls_ref: JClass<'local>,
     c: JClass<'local>,
 ) -> jint {
+    if c.is_null() {
+        todo!("throw NullPointerException");
+    }
     return JClassPtr::from_raw(c.as_raw() as _)
         .class_data()
         .access_flags() as jint;
@@ -0,0 +1,28 @@
+use jni::{
+    objects::{JByteArray, JClass},
+    sys::{jboolean, JNI_FALSE, JNI_TRUE},
+    JNIEnv,
+};
+
+use crate::{object::array::JByteArrayPtr, os};
+
+use super::java_io_RandomAccessFile::byte_range;
+
+/// Fills `seed` from the entropy source of the operating system. The Windows
+/// `NativeSeedGenerator` falls back to its threaded generator on false.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_security_provider_NativeSeedGenerator_nativeGenerateSeed<'local>(
+    _env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+    seed: JByteArray<'local>,
+) -> jboolean {
+    if seed.is_null() {
+        todo!("throw NullPointerException");
+    }
+    let len = JByteArrayPtr::from_raw(seed.as_raw() as _).length();
+    return match os::fill_random(byte_range(&seed, 0, len)) {
+        Ok(()) => JNI_TRUE,
+        Err(_e) => JNI_FALSE,
+    };
+}
@@ -0,0 +1,27 @@
+use jni::{objects::JClass, sys::jbyteArray, JNIEnv};
+
+use crate::os;
+
+use super::jni::JNIEnvWrapper;
+
+/// The number of bytes of `SeedGenerator.getSystemEntropy()`, the length of
+/// the SHA-1 digest the Java code returns.
+const SYSTEM_ENTROPY_LEN: usize = 20;
+
+/// Replaces `SeedGenerator.getSystemEntropy()`, which digests properties of
+/// the running system with the SHA-1 of the `SUN` provider, with bytes from
+/// the entropy source of the operating system.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "system" fn Java_sun_security_provider_SeedGenerator_getSystemEntropy<'local>(
+    env: JNIEnv<'local>,
+    _cls_ref: JClass<'local>,
+) -> jbyteArray {
+    let vm = JNIEnvWrapper::from_raw_env(env.get_raw()).vm();
+    let mut entropy = [0u8; SYSTEM_ENTROPY_LEN];
+    if let Err(e) = os::fill_random(&mut entropy) {
+        todo!("throw InternalError: {}", e);
+    }
+    let entropy = entropy.map(|byte| byte as i8);
+    return vm.new_byte_array(&entropy).as_raw_ptr() as _;
+}
@@ -1,5 +1,6 @@
 use super::class::JClassPtr;
 use super::prelude::*;
+use crate::compat;
 use crate::define_oop;
 use crate::memory::align;
 use crate::memory::heap::Heap;
@@ -7,6 +8,8 @@ use crate::object::class::JClass;
 use crate::thread::ThreadPtr;
 use crate::vm::VMPtr;
 use std::mem::size_of;
+use std::ptr::null_mut;
+use std::sync::atomic::Ordering;
 
 pub type JArrayPtr = Ptr<JArray>;
 
@@ -25,7 +28,7 @@ impl JArray {
         debug_assert!(jclass.class_data().component_type().is_not_null());
         let component_type = jclass.class_data().component_type();
         let size = Self::size(length, JClass::ref_size(component_type));
-        let mut array = Ptr::<JArray>::from_addr(Heap::alloc_obj_lab(size, thread));
+        let mut array = Ptr::<JArray>::from_addr(Heap::alloc_obj_lab(jclass, size, thread));
         array.initialize(length, jclass);
         log::trace!(
             "JArray::new component_type: {}, 0x{:x}, jclass: 0x{:x}",
@@ -46,6 +49,32 @@ impl JArray {
         return array;
     }
 
+    /// The zero-length array of `jclass` shared by every `newarray` and
+    /// `anewarray` with a count of 0, created in the permanent space on first
+    /// use. Having no elements it cannot be written to, so sharing it is only
+    /// observable through its identity.
+    pub fn empty(jclass: JClassPtr, thread: ThreadPtr) -> Ptr<JArray> {
+        let slot = unsafe {
+            compat::atomic_ptr_from_ptr(jclass.class_data().empty_array_slot() as *mut *mut JArray)
+        };
+        let empty = slot.load(Ordering::Acquire);
+        if !empty.is_null() {
+            return Ptr::from_raw(empty);
+        }
+        let array = Self::new_permanent(0, jclass, thread);
+        // a racing thread may have won, its array is kept and ours is dropped
+        // in the permanent space
+        return match slot.compare_exchange(
+            null_mut(),
+            array.as_mut_raw_ptr(),
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => array,
+            Err(empty) => Ptr::from_raw(empty),
+        };
+    }
+
     pub fn new_internal_permanent(length: JInt, thread: ThreadPtr) -> Ptr<JArray> {
         let jclass = thread.vm().shared_objs().internal_arr_cls;
         debug_assert!(jclass.class_data().is_array());
@@ -67,7 +96,7 @@ impl JArray {
         self.length = length;
     }
 
-    const fn size(length: JInt, ref_size: usize) -> usize {
+    pub(crate) const fn size(length: JInt, ref_size: usize) -> usize {
         debug_assert!(length >= 0);
         return align(Self::DATA_OFFSET + ref_size * length as usize);
     }
@@ -135,24 +164,31 @@ impl JArray {
         }
     }
 
+    /// Whether `aastore` may store `val` into the array. Most stores put an
+    /// instance of the component type itself or store into an `Object[]`,
+    /// and the classes of the others mostly hold the component type in their
+    /// `secondary_super_cache` after the first store.
+    #[inline(always)]
     pub fn is_compatible(&self, val: ObjectPtr, vm: VMPtr) -> bool {
         let component_type = self.jclass().class_data().component_type();
         if val.is_null() {
             return !JClass::is_primitive(component_type);
         }
-        return component_type.is_assignable_from(val.jclass(), vm);
+        let val_cls = val.jclass();
+        if val_cls == component_type || component_type == vm.preloaded_classes().jobject_cls() {
+            return true;
+        }
+        return component_type.is_assignable_from(val_cls, vm);
     }
 }
 
 macro_rules! DEFINE_TYPED_ARRAY {
     ($element_type:ident, $array_name:ident, $array_typed_class:expr) => {
         #[derive(Debug)]
-        pub struct $array_name {
-        }
+        pub struct $array_name {}
 
         #[allow(unused)]
         impl $array_name {
-
             pub fn size(length: JInt) -> usize {
                 assert!(length >= 0);
                 return align(JArray::DATA_OFFSET + size_of::<$element_type>() * length as usize);
@@ -232,6 +268,7 @@ DEFINE_TYPED_ARRAY!(JLong, JLongArray, global::classes::long_class());
 DEFINE_TYPED_ARRAY!(JFloat, JFloatArray, global::classes::float_class());
 DEFINE_TYPED_ARRAY!(JDouble, JDoubleArray, global::classes::double_class());
 
+pub type JBooleanArrayPtr = Ptr<JBooleanArray>;
 pub type JCharArrayPtr = Ptr<JCharArray>;
 pub type JByteArrayPtr = Ptr<JByteArray>;
 pub type JShortArrayPtr = Ptr<JShortArray>;
@@ -1,32 +1,38 @@
-use super::array::JArrayPtr;
+use super::array::{JArray, JArrayPtr};
 use super::constant_pool::{ConstMemberRef, ConstantPoolPtr};
 use super::field::FieldPtr;
 use super::hash_table::GetEntryWithKey;
 use super::method::{MethodIndex, MethodPtr, ResolvedMethod};
+use super::method_table::MethodId;
 use super::ptr::Ptr;
 use super::string::Utf8String;
 use super::symbol::Symbol;
 use super::{prelude::*, VMObject};
-use crate::classfile::ClassLoadErr;
 use crate::define_oop;
 use crate::memory::{align, Address};
 use crate::thread::{Thread, ThreadPtr};
 use crate::vm::{VMPtr, VM};
 use core::str;
 use std::convert::From;
+use std::error::Error;
+use std::fmt;
 use std::mem::size_of;
+use std::sync::atomic::{AtomicPtr, Ordering};
 
 pub type VTablePtr = Ptr<VTable>;
 pub type ClassDataPtr = Ptr<ClassData>;
 pub type JClassPtr = Ptr<JClass>;
 
-type MethodCArray = Ptr<MethodPtr>;
+type MethodIdCArray = Ptr<MethodId>;
 type InterfaceCArray = Ptr<JClassPtr>;
 
 #[allow(unused)]
 #[repr(u16)]
 pub enum ClassAccessFlags {
     AccPublic = 0x0001,
+    AccPrivate = 0x0002,
+    AccProtected = 0x0004,
+    AccStatic = 0x0008,
     AccFinal = 0x0010,
     AccSuper = 0x0020,
     AccInterface = 0x0200,
@@ -49,8 +55,9 @@ pub struct VTableInfo {
     // vtab_len: u32,
     // ifaces_len: u32,
     // ifaces_methods_len: u32,
-    methods: Vec<MethodPtr>,
+    methods: Vec<MethodId>,
     ifaces: Vec<JClassPtr>,
+    ifaces_m_offsets: Vec<IMethodIndex>,
     ifaces_m_indexes: Vec<IMethodIndex>,
 }
 
@@ -62,12 +69,20 @@ pub struct VTableInfo {
 ///  --------------------------------
 /// |       ifaces_methods_len       |
 ///  --------------------------------
-/// |             methods            |
-///  --------------------------------
 /// |            interfaces          |  \
-///  --------------------------------      itable
+///  --------------------------------   |
+/// |           method ids           |   |
+///  --------------------------------   |
+/// |    method-index offsets of     |   |
+/// |           interfaces           |    itable
+///  --------------------------------   |
 /// |  method-indexes of interfaces  |  /
 ///  --------------------------------
+///
+/// The methods are held as ids into the `MethodTable` of the VM, 4 bytes a
+/// slot instead of 8. The interfaces come first to keep their pointers
+/// aligned. The method-indexes of the i-th interface start at its offset,
+/// one vtable index per method of the interface, in declaration order.
 #[derive(Debug)]
 pub struct VTable {
     vtab_len: u32,
@@ -79,258 +94,220 @@ type IMethodIndex = u32;
 type IMethodIndexCArray = Ptr<IMethodIndex>;
 
 impl VTable {
-    const METHODS_OFFSET: usize = align(std::mem::size_of::<VTable>());
-
+    const IFACES_OFFSET: usize = align(std::mem::size_of::<VTable>());
+
+    /// Lays out the vtable and the itable of a class that is not an
+    /// interface: the slots of `super_class` with those `methods` override
+    /// replaced, the other virtual `methods`, and the Miranda methods of
+    /// `interfaces`. The only place a vtable is computed, the class is
+    /// allocated with its lengths and the contents are written right away,
+    /// see [`write`](Self::write), since subtype checks and method resolution
+    /// read the vtables of classes not linked yet.
     pub fn obtain_vtab_info(
         access_flags: u16,
+        class_name: SymbolPtr,
+        jclass_loader: ObjectPtr,
         methods: JArrayPtr,
         super_class: JClassPtr,
         interfaces: JArrayPtr,
-        ctor_init_name: SymbolPtr,
-        // ifaces_len: &mut u32,
-        // ifaces_methods_len: &mut u32,
+        vm: &VM,
     ) -> VTableInfo {
         debug_assert!(!ClassAccessFlags::is_interface(access_flags));
+        let ctor_init_name = vm.shared_objs().symbols().ctor_init;
         let mut info = VTableInfo::default();
-        // let mut super_vtab_methods = MethodCArray::null();
-        // let mut super_vtab_len: u32 = 0;
         if super_class.is_not_null() {
             let super_vtab = super_class.class_data().vtab();
-            //let super_vtab_methods = super_class.class_data().vtab().methods();
-            // let mut vtab_len = super_vtab_len;
             info.methods = super_vtab
                 .methods()
                 .as_slice(super_vtab.vtab_len as usize)
                 .to_vec();
-            let methods_len = methods.length();
-            for m_idx in 0..methods_len {
-                let method: MethodPtr = methods.get(m_idx).cast();
-                if Self::method_is_not_vtab_member(method, ctor_init_name) {
-                    continue;
-                }
-                let method_override_idx = Self::get_method_override_idx(method, &info.methods);
-                if method_override_idx != -1 {
-                    info.methods[method_override_idx as usize] = method;
-                    continue;
-                }
-                log::trace!("info.methods.push {}", method.name().as_str());
-                info.methods.push(method);
-                // vtab_len += 1;
+        }
+        let super_vtab_len = info.methods.len();
+        for m_idx in 0..methods.length() {
+            let method: MethodPtr = methods.get(m_idx).cast();
+            if Self::method_is_not_vtab_member(method, ctor_init_name) {
+                continue;
             }
-            // vtab_len
-        } else {
-            let methods_len = methods.length();
-            for m_idx in 0..methods_len {
-                let method: MethodPtr = methods.get(m_idx).cast();
-                if Self::method_is_not_vtab_member(method, ctor_init_name) {
-                    continue;
-                }
-                info.methods.push(method);
+            if Self::override_methods(
+                method,
+                class_name,
+                jclass_loader,
+                &mut info.methods[..super_vtab_len],
+                vm,
+            ) {
+                continue;
             }
+            log::trace!("info.methods.push {}", method.name().as_str());
+            info.methods.push(method.id());
         }
-        // TODO interface super class
         if interfaces.is_not_null() && interfaces.length() > 0 {
-            Self::obtain_itable(interfaces, &mut info);
+            Self::obtain_itable(interfaces, &mut info, vm);
         }
         return info;
     }
 
-    fn obtain_itable(interfaces: JArrayPtr, info: &mut VTableInfo) {
-        let ifs_len = interfaces.length();
-        for if_idx in 0..ifs_len {
-            let iface: JClassPtr = interfaces.get(if_idx).cast();
-            debug_assert!(iface.class_data().is_interface());
-            // info.ifaces.push(iface);
-            let mut iface = iface;
-            loop {
-                info.ifaces.push(iface);
-                let iface_methods = iface.class_data().methods;
-                let iface_methods_len = iface_methods.length();
-                // *ifaces_methods_len += iface_methods_len as u32;
-                for iface_m_idx in 0..iface_methods_len {
-                    let iface_method: MethodPtr = iface_methods.get(iface_m_idx).cast();
-                    let impl_idx = Self::find_method(
-                        iface_method,
-                        MethodCArray::from_raw(info.methods.as_ptr()),
-                        info.methods.len() as JInt,
-                    );
-                    if impl_idx != -1 {
-                        info.ifaces_m_indexes.push(impl_idx as IMethodIndex);
-                        continue;
-                    }
-                    info.ifaces_m_indexes
-                        .push(info.methods.len() as IMethodIndex);
-                    // vtab_len += iface.jclass().class_data().methods.length() as u32;
-                    // *vtab_len += 1;
-                    info.methods.push(iface_method);
+    /// Appends the itable of `interfaces` and their superinterfaces. An
+    /// interface method without an implementation gets a vtable slot of its
+    /// own, a Miranda method, so that invokevirtual on an abstract class finds
+    /// it and its subclasses override it at the same index. Invoking it while
+    /// still abstract fails with `AbstractMethodError`. Static and private
+    /// interface methods, `<clinit>` included, are never dispatched and get
+    /// neither, see [`itable_index`](Self::itable_index).
+    fn obtain_itable(interfaces: JArrayPtr, info: &mut VTableInfo, vm: &VM) {
+        let ctor_init_name = vm.shared_objs().symbols().ctor_init;
+        for iface in Self::all_ifaces(interfaces) {
+            info.ifaces.push(iface);
+            info.ifaces_m_offsets
+                .push(info.ifaces_m_indexes.len() as IMethodIndex);
+            let iface_methods = iface.class_data().methods;
+            for iface_m_idx in 0..iface_methods.length() {
+                let iface_method: MethodPtr = iface_methods.get(iface_m_idx).cast();
+                if Self::method_is_not_vtab_member(iface_method, ctor_init_name) {
+                    continue;
                 }
-                log::trace!("obtain_interfaces_indexes iface {:x}", iface.as_usize(),);
-                log::trace!(
-                    "obtain_interfaces_indexes iface {:x}, iface name {}, iface super_class: 0x{:x}",
-                    iface.as_usize(),
-                    iface.name().as_str(),
-                    iface.class_data().super_class().as_usize()
-                );
-                iface = iface.class_data().super_class();
-                if iface.is_null() || !iface.class_data().is_interface() {
-                    break;
+                let impl_idx = Self::find_method(iface_method, &info.methods, vm);
+                if impl_idx != -1 {
+                    info.ifaces_m_indexes.push(impl_idx as IMethodIndex);
+                    continue;
                 }
-                // *ifaces_len += 1;
+                info.ifaces_m_indexes
+                    .push(info.methods.len() as IMethodIndex);
+                info.methods.push(iface_method.id());
             }
+            log::trace!(
+                "obtain_interfaces_indexes iface {:x}, iface name {}",
+                iface.as_usize(),
+                iface.name().as_str()
+            );
         }
     }
 
-    fn initialize(
-        &self,
-        super_class: JClassPtr,
-        methods: JArrayPtr,
-        interfaces: JArrayPtr,
-        vm: &VM,
-    ) {
-        let ctor_init_name = vm.shared_objs().symbols().ctor_init;
-        let vtab = VTablePtr::from_ref(self);
-        let vtab_methods = vtab.methods();
-        let mut vtab_offset;
-        if super_class.is_not_null() {
-            let super_vtab = super_class.class_data().vtab();
-            let super_vtab_methods = super_vtab.methods();
-            let super_vtab_len = super_vtab.vtab_len;
-            unsafe {
-                std::ptr::copy(
-                    super_vtab_methods.as_mut_raw_ptr(),
-                    vtab_methods.as_mut_raw_ptr(),
-                    super_vtab_len as usize,
-                );
+    /// `interfaces` followed by their superinterfaces, depth first, each
+    /// interface once.
+    fn all_ifaces(interfaces: JArrayPtr) -> Vec<JClassPtr> {
+        fn visit(interfaces: JArrayPtr, all_ifaces: &mut Vec<JClassPtr>) {
+            if interfaces.is_null() {
+                return;
             }
-            vtab_offset = super_vtab_len;
-            for m_idx in 0..methods.length() {
-                let method: MethodPtr = methods.get(m_idx).cast();
-                if Self::method_is_not_vtab_member(method, ctor_init_name) {
+            for if_idx in 0..interfaces.length() {
+                let iface: JClassPtr = interfaces.get(if_idx).cast();
+                debug_assert!(iface.class_data().is_interface());
+                if all_ifaces.contains(&iface) {
                     continue;
                 }
-                let overridden_m_idx =
-                    VTable::find_method(method, super_vtab_methods, super_vtab_len as JInt);
-                if overridden_m_idx != -1 {
-                    *vtab_methods.offset(overridden_m_idx as isize) = method;
-                } else {
-                    *vtab_methods.offset(vtab_offset as isize) = method;
-                    vtab_offset += 1;
-                }
-            }
-        } else {
-            vtab_offset = 0;
-            for m_idx in 0..methods.length() {
-                let method: MethodPtr = methods.get(m_idx).cast();
-                if Self::method_is_not_vtab_member(method, ctor_init_name) {
-                    continue;
-                }
-                *vtab_methods.offset(vtab_offset as isize) = method;
-                vtab_offset += 1;
+                all_ifaces.push(iface);
+                visit(iface.class_data().interfaces(), all_ifaces);
             }
         }
-        if interfaces.is_not_null() && interfaces.length() > 0 {
-            Self::init_itable(vtab, interfaces, vtab_methods, &mut vtab_offset);
+        let mut all_ifaces = Vec::new();
+        visit(interfaces, &mut all_ifaces);
+        return all_ifaces;
+    }
+
+    /// Copies `info` into the vtable of `class_name`, which was allocated
+    /// with its lengths.
+    fn write(&self, info: &VTableInfo, class_name: SymbolPtr) {
+        self.verify_lengths(info, class_name);
+        unsafe {
+            std::ptr::copy(
+                info.methods.as_ptr(),
+                self.methods().as_mut_raw_ptr(),
+                info.methods.len(),
+            );
+            std::ptr::copy(
+                info.ifaces.as_ptr(),
+                self.ifaces().as_mut_raw_ptr(),
+                info.ifaces.len(),
+            );
+            std::ptr::copy(
+                info.ifaces_m_offsets.as_ptr(),
+                self.imethod_offsets().as_mut_raw_ptr(),
+                info.ifaces_m_offsets.len(),
+            );
+            std::ptr::copy(
+                info.ifaces_m_indexes.as_ptr(),
+                self.imethod_indexes().as_mut_raw_ptr(),
+                info.ifaces_m_indexes.len(),
+            );
         }
     }
 
-    fn init_itable(
-        vtab: VTablePtr,
-        interfaces: JArrayPtr,
-        vtab_methods: MethodCArray,
-        // iface: JClassPtr,
-        vtab_offset: &mut u32,
-    ) {
-        let vtab_ifaces = vtab.ifaces();
-        let mut vtab_ifaces_offset: u32 = 0;
-        let imethod_indexes = vtab.imethod_indexes();
-        let mut imethod_offset: u32 = 0;
-        for iface_idx in 0..interfaces.length() {
-            let mut iface: JClassPtr = interfaces.get(iface_idx).cast();
-            loop {
-                *vtab_ifaces.offset(vtab_ifaces_offset as isize) = iface;
-                vtab_ifaces_offset += 1;
-                let iface_methods = iface.class_data().methods;
-                for iface_m_idx in 0..iface_methods.length() {
-                    let iface_m: MethodPtr = iface_methods.get(iface_m_idx).cast();
-                    if *vtab_offset > 0 {
-                        let override_idx =
-                            VTable::find_method(iface_m, vtab_methods, *vtab_offset as JInt);
-                        let imethod_idx = if override_idx != -1 {
-                            override_idx as IMethodIndex
-                        } else {
-                            let imethod_idx = *vtab_offset;
-                            *vtab_offset = imethod_idx + 1;
-                            imethod_idx as IMethodIndex
-                        };
-                        *imethod_indexes.offset(imethod_offset as isize) = imethod_idx;
-                        imethod_offset += 1;
-                    } else {
-                        *vtab_methods.as_mut_ref() = iface_m;
-                        *imethod_indexes.as_mut_ref() = 0;
-                        *vtab_offset += 1;
-                        imethod_offset += 1;
-                    }
-                }
-                let super_class = iface.class_data().super_class();
-                if super_class.is_null() || !super_class.class_data().is_interface() {
-                    break;
+    /// Checks in debug builds that the vtable of `class_name` has the
+    /// lengths of the layout `info`.
+    fn verify_lengths(&self, info: &VTableInfo, class_name: SymbolPtr) {
+        debug_assert_eq!(
+            (self.vtab_len, self.ifaces_len, self.ifaces_methods_len),
+            (
+                info.methods.len() as u32,
+                info.ifaces.len() as u32,
+                info.ifaces_m_indexes.len() as u32,
+            ),
+            "the vtable lengths of {} differ from its layout",
+            class_name.as_str()
+        );
+        debug_assert_eq!(info.ifaces.len(), info.ifaces_m_offsets.len());
+    }
+
+    /// Puts `method`, declared by the class `class_name` of `jclass_loader`,
+    /// into every slot of `vtab_methods` holding a method it overrides, and
+    /// returns whether there was one (jvms-5.4.5). A package-private method
+    /// is only overridden from its own runtime package, otherwise `method`
+    /// needs a slot of its own.
+    fn override_methods(
+        method: MethodPtr,
+        class_name: SymbolPtr,
+        jclass_loader: ObjectPtr,
+        vtab_methods: &mut [MethodId],
+        vm: &VM,
+    ) -> bool {
+        let mut overridden = false;
+        for slot in vtab_methods {
+            let super_method = vm.method_table.get(*slot);
+            if method.name() != super_method.name()
+                || method.descriptor() != super_method.descriptor()
+                || super_method.is_private()
+            {
+                continue;
+            }
+            let is_package_private = !super_method.is_public() && !super_method.is_protected();
+            if is_package_private {
+                let super_class = super_method.decl_cls();
+                if super_class.class_loader() != jclass_loader
+                    || !Self::is_same_package(super_class.name(), class_name)
+                {
+                    continue;
                 }
-                iface = super_class;
             }
+            *slot = method.id();
+            overridden = true;
         }
-        debug_assert_eq!(vtab.ifaces_len, vtab_ifaces_offset);
-        debug_assert_eq!(vtab.ifaces_methods_len, imethod_offset);
+        return overridden;
     }
 
-    fn get_method_override_idx(current_method: MethodPtr, methods: &Vec<MethodPtr>) -> JInt {
-        if current_method.is_private() {
-            return -1;
+    fn is_same_package(class_name: SymbolPtr, other_class_name: SymbolPtr) -> bool {
+        fn package(class_name: &str) -> &str {
+            return class_name.rfind('/').map_or("", |idx| &class_name[..idx]);
         }
-        for m_idx in 0..methods.len() {
-            let method = unsafe { methods.get_unchecked(m_idx) };
-            if current_method.name() == method.name()
-                && current_method.descriptor() == method.descriptor()
-            {
-                // log::trace!("vtab method_is_override current name {}, addr 0x{:x}, current descriptor {}, addr 0x{:x}, it_method name {}, addr 0x{:x}, it_method descriptor {}, addr 0x{:x}, result {}",
-                //         current_method.name().as_str(), current_method.name().as_isize(),
-                //         current_method.descriptor().as_str(), current_method.descriptor().as_isize(),
-                //         method.name().as_str(), method.name().as_isize(),
-                //         method.descriptor().as_str(), method.descriptor().as_isize(),
-                //         m_idx as JInt
-                //     );
-                return m_idx as JInt;
-            }
-        }
-        // log::trace!("vtab method_is_override current name {}, addr 0x{:x}, current descriptor {}, addr 0x{:x}, result {}",
-        //                 current_method.name().as_str(), current_method.name().as_isize(),
-        //                 current_method.descriptor().as_str(), current_method.descriptor().as_isize(),
-        //                 -1
-        //             );
-        return -1;
+        return package(class_name.as_str()) == package(other_class_name.as_str());
     }
 
-    fn find_method(current_method: MethodPtr, methods: MethodCArray, methods_len: JInt) -> JInt {
-        for m_idx in 0..methods_len {
-            let method = *methods.offset(m_idx as isize);
-            // log::trace!("vtab find_method current name {}, addr 0x{:x}, current descriptor {}, addr 0x{:x}, it_method name {}, addr 0x{:x}, it_method descriptor {}, addr 0x{:x}",
-            //     current_method.name().as_str(), current_method.name().as_isize(),
-            //     current_method.descriptor().as_str(), current_method.descriptor().as_isize(),
-            //     method.name().as_str(), method.name().as_isize(),
-            //     method.descriptor().as_str(), method.descriptor().as_isize(),
-            // );
+    fn find_method(current_method: MethodPtr, methods: &[MethodId], vm: &VM) -> JInt {
+        for (m_idx, &method_id) in methods.iter().enumerate() {
+            let method = vm.method_table.get(method_id);
             if current_method.name() == method.name()
                 && current_method.descriptor() == method.descriptor()
             {
-                return m_idx;
+                return m_idx as JInt;
             }
         }
         return -1;
     }
 
-    fn methods(&self) -> MethodCArray {
-        return MethodCArray::from_addr(
-            Address::from_ref(self).offset(Self::METHODS_OFFSET as isize),
-        );
+    /// The method in the vtable slot `index`.
+    #[inline(always)]
+    fn method(&self, index: u32, vm: &VM) -> MethodPtr {
+        debug_assert!(index < self.vtab_len);
+        return vm.method_table.get(*self.methods().offset(index as isize));
     }
 
     fn ifaces_len(&self) -> u32 {
@@ -339,18 +316,32 @@ impl VTable {
 
     fn ifaces(&self) -> InterfaceCArray {
         return InterfaceCArray::from_addr(
-            Address::from_ref(self)
-                .offset(Self::METHODS_OFFSET as isize)
-                .offset(self.vtab_len as isize * std::mem::size_of::<MethodPtr>() as isize),
+            Address::from_ref(self).offset(Self::IFACES_OFFSET as isize),
+        );
+    }
+
+    fn methods(&self) -> MethodIdCArray {
+        return MethodIdCArray::from_addr(
+            self.ifaces()
+                .as_address()
+                .offset(self.ifaces_len as isize * std::mem::size_of::<JClassPtr>() as isize),
+        );
+    }
+
+    /// The offset of the first method-index of each interface.
+    fn imethod_offsets(&self) -> IMethodIndexCArray {
+        return IMethodIndexCArray::from_addr(
+            self.methods()
+                .as_address()
+                .offset(self.vtab_len as isize * std::mem::size_of::<MethodId>() as isize),
         );
     }
 
     fn imethod_indexes(&self) -> IMethodIndexCArray {
         return IMethodIndexCArray::from_addr(
-            Address::from_ref(self)
-                .offset(Self::METHODS_OFFSET as isize)
-                .offset(self.vtab_len as isize * std::mem::size_of::<MethodPtr>() as isize)
-                .offset(self.ifaces_len as isize * std::mem::size_of::<JClassPtr>() as isize),
+            self.imethod_offsets()
+                .as_address()
+                .offset(self.ifaces_len as isize * std::mem::size_of::<IMethodIndex>() as isize),
         );
     }
 
@@ -358,13 +349,33 @@ impl VTable {
         return method.is_private() || method.is_static() || ctor_init_name == method.name();
     }
 
-    const fn size(vtab_len: u32, ifaces_len: u32, ifaces_m_indexes_len: u32) -> usize {
-        return (Self::METHODS_OFFSET + std::mem::size_of::<MethodPtr>() * vtab_len as usize)
-            + Self::itable_size(ifaces_len, ifaces_m_indexes_len);
+    /// The position of `method` in the itable entries of its interface,
+    /// which skip the methods that are not dispatched, or `None` if it is
+    /// one of them.
+    fn itable_index(method: MethodPtr, vm: &VM) -> Option<IMethodIndex> {
+        let ctor_init_name = vm.shared_objs().symbols().ctor_init;
+        if Self::method_is_not_vtab_member(method, ctor_init_name) {
+            return None;
+        }
+        let iface_methods = method.decl_cls().class_data().methods;
+        let mut itable_idx = 0;
+        for iface_m_idx in 0..iface_methods.length() {
+            let iface_method: MethodPtr = iface_methods.get(iface_m_idx).cast();
+            if iface_method == method {
+                return Some(itable_idx);
+            }
+            if !Self::method_is_not_vtab_member(iface_method, ctor_init_name) {
+                itable_idx += 1;
+            }
+        }
+        unreachable!("{} is not declared by its class", method.name().as_str());
     }
 
-    const fn itable_size(ifaces_len: u32, ifaces_m_indexes_len: u32) -> usize {
-        return std::mem::size_of::<JClassPtr>() * ifaces_len as usize
+    const fn size(vtab_len: u32, ifaces_len: u32, ifaces_m_indexes_len: u32) -> usize {
+        return Self::IFACES_OFFSET
+            + std::mem::size_of::<JClassPtr>() * ifaces_len as usize
+            + std::mem::size_of::<MethodId>() * vtab_len as usize
+            + std::mem::size_of::<IMethodIndex>() * ifaces_len as usize
             + std::mem::size_of::<IMethodIndex>() * ifaces_m_indexes_len as usize;
     }
 }
@@ -381,9 +392,22 @@ pub struct ClassData {
     jclass_loader: ObjectPtr,
     init_method: MethodPtr,
     component_type: JClassPtr,
+    /// The zero-length instance of an array class, see `JArray::empty`.
+    empty_array: JArrayPtr,
+    /// The class of arrays of this class, see
+    /// `BootstrapClassLoader::get_or_create_array_class`.
+    array_class: JClassPtr,
     inst_or_ele_size: u16,
     metadata_offset: u16,
     access_flags: u16,
+    /// The access flags of the `InnerClasses` entry of a member class.
+    inner_access_flags: Option<u16>,
+    /// The supertype a subtype check of the class last found, see
+    /// `JClass::is_assignable_from`. A racing check may overwrite it with
+    /// another supertype, which is just as valid.
+    secondary_super_cache: AtomicPtr<JClass>,
+    /// The `SourceFile` attribute, null if the class file has none.
+    source_file: SymbolPtr,
     is_primitive: bool,
     is_array: bool,
     _vtab: VTablePtr,
@@ -442,6 +466,9 @@ impl ClassData {
     ) {
         // let class_data = class.as_mut_ref();
         class_data.access_flags = access_flags;
+        class_data.inner_access_flags = None;
+        class_data.secondary_super_cache = AtomicPtr::default();
+        class_data.source_file = SymbolPtr::null();
         class_data.cp = cp;
         class_data.name = name;
         class_data.super_class = super_class;
@@ -604,10 +631,59 @@ impl ClassData {
         self.component_type
     }
 
+    pub(crate) fn empty_array_slot(&mut self) -> *mut JArrayPtr {
+        &mut self.empty_array
+    }
+
+    pub(crate) fn array_class_slot(&mut self) -> *mut JClassPtr {
+        &mut self.array_class
+    }
+
     pub fn access_flags(&self) -> u16 {
         self.access_flags
     }
 
+    pub(crate) fn set_inner_access_flags(&mut self, inner_access_flags: u16) {
+        self.inner_access_flags = Some(inner_access_flags);
+    }
+
+    pub(crate) fn secondary_super_cache(&self) -> JClassPtr {
+        return JClassPtr::from_raw(self.secondary_super_cache.load(Ordering::Relaxed));
+    }
+
+    pub(crate) fn set_secondary_super_cache(&self, super_cls: JClassPtr) {
+        self.secondary_super_cache
+            .store(super_cls.as_mut_raw_ptr(), Ordering::Relaxed);
+    }
+
+    pub fn source_file(&self) -> SymbolPtr {
+        self.source_file
+    }
+
+    pub(crate) fn set_source_file(&mut self, source_file: SymbolPtr) {
+        self.source_file = source_file;
+    }
+
+    /// The modifiers `Class.getModifiers` reports. A member class has those
+    /// of its `InnerClasses` entry (jvms-4.7.6), which tell if it is private,
+    /// protected or static. An array class is final and abstract, with the
+    /// access of its component type.
+    pub fn modifiers(&self) -> u16 {
+        let final_abstract =
+            ClassAccessFlags::AccFinal as u16 | ClassAccessFlags::AccAbstract as u16;
+        if self.is_array {
+            let access = ClassAccessFlags::AccPublic as u16
+                | ClassAccessFlags::AccPrivate as u16
+                | ClassAccessFlags::AccProtected as u16;
+            return self.component_type.class_data().modifiers() & access | final_abstract;
+        }
+        if self.is_primitive {
+            return ClassAccessFlags::AccPublic as u16 | final_abstract;
+        }
+        let access_flags = self.inner_access_flags.unwrap_or(self.access_flags);
+        return access_flags & !(ClassAccessFlags::AccSuper as u16);
+    }
+
     // pub fn get_method(&self, name: SymbolPtr, descriptor: SymbolPtr) -> Option<ResolvedMethod> {
     //     return Self::resolve_method_by_str(
     //         Ptr::from_ref(self),
@@ -655,62 +731,163 @@ impl ClassData {
         jclass: JClassPtr,
         thread: ThreadPtr,
     ) -> Result<(), InitializationError> {
-        let methods = self.methods;
-
         let vm = thread.vm();
-        let vmstr_cls = vm.shared_objs().vm_str_cls;
-        let jclass_cls = vm.preloaded_classes().jclass_cls();
+        let methods = self.methods;
         for idx in 0..methods.length() {
-            let mut method: MethodPtr = methods.get(idx).cast();
-
-            let params = method.params();
-            for idx in 0..params.length() {
-                let param = params.get(idx);
-                if param.jclass() == vmstr_cls {
-                    let param = vm
-                        .bootstrap_class_loader
-                        .load_class_with_symbol(param.cast())
-                        .map_err(|_e| InitializationError::LinkingFailed)?;
-                    params.set(idx, param.cast());
-                } else {
-                    debug_assert_eq!(param.jclass(), jclass_cls);
-                }
-            }
-
-            if method.is_native() {
-                let native_fn_name =
-                    Self::get_native_fn_name(jclass.name().as_str(), method.name().as_str());
-                if let Some(native_fn) = thread.vm().get_builtin_native_fn(&native_fn_name) {
-                    method.set_native_fn(native_fn);
-                }
-            }
+            Self::link_method(methods.get(idx).cast(), jclass, thread)?;
         }
         if self.is_array {
             self._vtab = vm.preloaded_classes().jobject_cls().class_data().vtab();
+        } else if cfg!(debug_assertions) {
+            // the superclass is linked by now, a layout computed from it has
+            // to fit the vtable laid out when the class was parsed
+            let info = VTable::obtain_vtab_info(
+                self.access_flags,
+                self.name,
+                self.jclass_loader,
+                self.methods,
+                self.super_class,
+                self.interfaces,
+                vm,
+            );
+            self.vtab().verify_lengths(&info, self.name);
+        }
+        return Ok(());
+    }
+
+    /// Loads the parameter and return classes of `method` and binds it to its
+    /// builtin native function if it has one, or to the builtin replacing it
+    /// if the VM implements the Java method itself. Natives bound with
+    /// `RegisterNatives` keep their function.
+    pub(crate) fn link_method(
+        mut method: MethodPtr,
+        jclass: JClassPtr,
+        thread: ThreadPtr,
+    ) -> Result<(), InitializationError> {
+        let vm = thread.vm();
+        let param_count = method.param_count();
+        let param_types = if param_count == 0 {
+            vm.shared_objs().empty_sys_arr
         } else {
-            self.vtab()
-                .initialize(self.super_class, self.methods, self.interfaces, vm);
+            JArray::new_internal_permanent(param_count, thread)
+        };
+        for idx in 0..param_count {
+            let param_type = method
+                .param_type(idx, vm)
+                .map_err(|_e| InitializationError::LinkingFailed)?;
+            param_types.set(idx, param_type.cast());
+        }
+        method.set_param_types(param_types);
+        let ret_type = method
+            .ret_type(vm)
+            .map_err(|_e| InitializationError::LinkingFailed)?;
+        method.set_ret_type(ret_type);
+
+        if method.is_native() {
+            if !method.is_registered_native() {
+                Self::bind_builtin_native(method, jclass, vm);
+            }
+        } else if let Some(native) = vm.get_builtin_java_method_fn(
+            jclass.name().as_str(),
+            method.name().as_str(),
+            method.descriptor().as_str(),
+        ) {
+            let native_fn = native.address_for(method);
+            method.replace_with_native(native_fn);
         }
         return Ok(());
     }
 
+    /// Binds the native `method` to the builtin native function of its JNI
+    /// name, if the VM has one.
+    pub(crate) fn bind_builtin_native(mut method: MethodPtr, jclass: JClassPtr, vm: &VM) {
+        let native_fn_name =
+            Self::get_native_fn_name(jclass.name().as_str(), method.name().as_str());
+        // the long name of an overloaded native takes precedence, like JNI
+        let overloaded_fn_name =
+            Self::get_overloaded_native_fn_name(&native_fn_name, method.descriptor().as_str());
+        if let Some(native) = vm
+            .get_builtin_native_fn(&overloaded_fn_name)
+            .or_else(|| vm.get_builtin_native_fn(&native_fn_name))
+        {
+            let native_fn = native.address_for(method);
+            method.set_native_fn(native_fn);
+        }
+    }
+
+    pub(crate) fn init_method(&self) -> MethodPtr {
+        return self.init_method;
+    }
+
+    pub(crate) fn set_init_method(&mut self, init_method: MethodPtr) {
+        self.init_method = init_method;
+    }
+
+    /// Points the vtable slots holding `old` at `new`, returns whether any
+    /// slot held `old`.
+    pub(crate) fn replace_vtab_method(&self, old: MethodPtr, new: MethodPtr) -> bool {
+        if self._vtab.is_null() {
+            return false;
+        }
+        let vtab = self.vtab();
+        let mut replaced = false;
+        for slot in vtab.methods().as_mut_slice(vtab.vtab_len as usize) {
+            if *slot == old.id() {
+                *slot = new.id();
+                replaced = true;
+            }
+        }
+        return replaced;
+    }
+
+    /// The JNI short name of a native, e.g. `Java_java_lang_Object_hashCode`.
     pub fn get_native_fn_name(class_name: &str, method_name: &str) -> String {
         let prefix = "Java_";
         let mut result =
             String::with_capacity(prefix.len() + class_name.len() + 1 + method_name.len());
         result.push_str(prefix);
-        let mut last_end = 0;
-        for (start, part) in class_name.match_indices('/') {
-            result.push_str(unsafe { class_name.get_unchecked(last_end..start) });
-            result.push('_');
-            last_end = start + part.len();
-        }
-        result.push_str(unsafe { class_name.get_unchecked(last_end..class_name.len()) });
+        Self::mangle_native_fn_name(&mut result, class_name);
         result.push('_');
-        result.push_str(method_name);
+        Self::mangle_native_fn_name(&mut result, method_name);
         result
     }
 
+    /// The JNI long name of a native, its short name followed by `__` and the
+    /// mangled argument types of `descriptor`.
+    pub fn get_overloaded_native_fn_name(native_fn_name: &str, descriptor: &str) -> String {
+        let args = match descriptor.strip_prefix('(').and_then(|d| d.split_once(')')) {
+            Some((args, _)) => args,
+            None => "",
+        };
+        let mut result = String::with_capacity(native_fn_name.len() + 2 + args.len());
+        result.push_str(native_fn_name);
+        result.push_str("__");
+        Self::mangle_native_fn_name(&mut result, args);
+        return result;
+    }
+
+    /// Appends `name` mangled the way JNI does: `/` becomes `_`, `_`, `;` and
+    /// `[` are escaped as `_1`, `_2` and `_3`, and any other character but an
+    /// ASCII letter or digit, e.g. the `$` of an inner class, as `_0` and its
+    /// UTF-16 code units in lowercase hex.
+    fn mangle_native_fn_name(result: &mut String, name: &str) {
+        for c in name.chars() {
+            match c {
+                '/' => result.push('_'),
+                '_' => result.push_str("_1"),
+                ';' => result.push_str("_2"),
+                '[' => result.push_str("_3"),
+                c if c.is_ascii_alphanumeric() => result.push(c),
+                c => {
+                    let mut units = [0u16; 2];
+                    for unit in c.encode_utf16(&mut units) {
+                        result.push_str(&format!("_0{:04x}", unit));
+                    }
+                }
+            }
+        }
+    }
+
     pub fn debug(&self) {
         log::trace!(
             "vtab addr 0x{:x}, addr addr {:x?}, jobj vtab addr 0x{:x}",
@@ -952,25 +1129,7 @@ impl JClass {
             let method: MethodPtr = methods.get(idx).cast();
             method.as_mut_ref().set_decl_cls(jclass);
         }
-        let vtab = jclass.class_data().vtab();
-
-        unsafe {
-            std::ptr::copy(
-                vtab_info.methods.as_ptr(),
-                vtab.methods().as_mut_raw_ptr(),
-                vtab_len,
-            );
-            std::ptr::copy(
-                vtab_info.ifaces.as_ptr(),
-                vtab.ifaces().as_mut_raw_ptr(),
-                ifaces_len,
-            );
-            std::ptr::copy(
-                vtab_info.ifaces_m_indexes.as_ptr(),
-                vtab.imethod_indexes().as_mut_raw_ptr(),
-                ifaces_m_indexes_len,
-            );
-        }
+        jclass.class_data().vtab().write(vtab_info, name);
         return jclass;
     }
 
@@ -1122,6 +1281,19 @@ impl JClass {
         }
         let mut self_ptr = JClassPtr::from_ref(self);
         self_ptr._init_state = ClassInitState::Initializing;
+        self.init_constant_values(thread);
+        // jvms-5.5 step 7: a class initializes its superclass first, then the
+        // superinterfaces declaring default methods
+        let class_data = self.class_data();
+        if !class_data.is_interface() {
+            let super_class = class_data.super_class();
+            if super_class.is_not_null() {
+                super_class.initialize(thread)?;
+            }
+            for iface in Self::ifaces_with_defaults(class_data.interfaces()) {
+                iface.initialize(thread)?;
+            }
+        }
         let init_method = self.class_data().init_method;
         if init_method.is_not_null() {
             thread
@@ -1132,6 +1304,85 @@ impl JClass {
         return Ok(());
     }
 
+    /// The superinterfaces reachable from `interfaces` that declare a default
+    /// method, each after its own superinterfaces and in the order listed.
+    fn ifaces_with_defaults(interfaces: JArrayPtr) -> Vec<JClassPtr> {
+        fn visit(interfaces: JArrayPtr, visited: &mut Vec<JClassPtr>, result: &mut Vec<JClassPtr>) {
+            if interfaces.is_null() {
+                return;
+            }
+            for if_idx in 0..interfaces.length() {
+                let iface: JClassPtr = interfaces.get(if_idx).cast();
+                if visited.contains(&iface) {
+                    continue;
+                }
+                visited.push(iface);
+                visit(iface.class_data().interfaces(), visited, result);
+                let methods = iface.class_data().methods();
+                let declares_default = (0..methods.length()).any(|m_idx| {
+                    let method: MethodPtr = methods.get(m_idx).cast();
+                    return !method.is_abstract() && !method.is_static();
+                });
+                if declares_default {
+                    result.push(iface);
+                }
+            }
+        }
+        let mut result = Vec::new();
+        visit(interfaces, &mut Vec::new(), &mut result);
+        return result;
+    }
+
+    // jvms-5.5 step 6: static fields with a ConstantValue attribute are
+    // assigned before <clinit> runs.
+    fn init_constant_values(&self, thread: ThreadPtr) {
+        let class_data = self.class_data();
+        let fields = class_data.fields();
+        if fields.is_null() {
+            return;
+        }
+        let self_ptr = JClassPtr::from_ref(self);
+        let cp = class_data.cp;
+        let vm = thread.vm();
+        let preloaded_classes = vm.preloaded_classes();
+        for field_idx in 0..fields.length() {
+            let field: FieldPtr = fields.get(field_idx).cast();
+            let constval_index = field.constval_index();
+            if constval_index == 0 || !field.is_static() {
+                continue;
+            }
+            let field_class = field.field_class_unchecked();
+            if preloaded_classes.is_bool_cls(field_class)
+                || preloaded_classes.is_byte_cls(field_class)
+            {
+                field.set_static_value(self_ptr, cp.get_int32(constval_index) as JByte);
+            } else if preloaded_classes.is_char_cls(field_class)
+                || preloaded_classes.is_short_cls(field_class)
+            {
+                field.set_static_value(self_ptr, cp.get_int32(constval_index) as JShort);
+            } else if preloaded_classes.is_int_cls(field_class) {
+                field.set_static_value(self_ptr, cp.get_int32(constval_index));
+            } else if preloaded_classes.is_float_cls(field_class) {
+                field.set_static_value(self_ptr, cp.get_float(constval_index));
+            } else if preloaded_classes.is_long_cls(field_class) {
+                field.set_static_value(self_ptr, cp.get_long(constval_index));
+            } else if preloaded_classes.is_double_cls(field_class) {
+                field.set_static_value(self_ptr, cp.get_double(constval_index));
+            } else {
+                let jstr = vm
+                    .string_table
+                    .from_symbol(cp.get_string(constval_index), thread);
+                field.set_static_value(self_ptr, jstr);
+            }
+            log::trace!(
+                "init constant value {}#{}, constval_index {}",
+                self.name().as_str(),
+                field.name(vm).as_str(),
+                constval_index
+            );
+        }
+    }
+
     pub fn is_void(cls: JClassPtr, vm: VMPtr) -> bool {
         return vm.preloaded_classes().is_void_cls(cls);
     }
@@ -1144,6 +1395,10 @@ impl JClass {
         return vm.preloaded_classes().is_double_cls(cls);
     }
 
+    pub fn is_float(cls: JClassPtr, vm: VMPtr) -> bool {
+        return vm.preloaded_classes().is_float_cls(cls);
+    }
+
     pub fn is_long_arr(cls: JClassPtr, vm: VMPtr) -> bool {
         return vm.preloaded_classes().is_long_arr_cls(cls);
     }
@@ -1181,6 +1436,11 @@ impl JClass {
         return self.class_data().name();
     }
 
+    /// The binary name of the class, e.g. `java.lang.Thread`, see jls-13.1.
+    pub fn binary_name(&self) -> String {
+        return self.name().as_str().replace('/', ".");
+    }
+
     pub fn class_loader(&self) -> ObjectPtr {
         return self.class_data().jclass_loader;
     }
@@ -1189,6 +1449,12 @@ impl JClass {
         return self._init_state != ClassInitState::Created;
     }
 
+    /// Whether the `<clinit>` of the class has completed, unlike
+    /// [`is_initialized`](Self::is_initialized).
+    pub(crate) fn is_fully_initialized(&self) -> bool {
+        return self._init_state == ClassInitState::Initialized;
+    }
+
     pub fn class_data(&self) -> ClassDataPtr {
         return self.class_data;
     }
@@ -1207,24 +1473,55 @@ impl JClass {
         return self.class_data().is_implement(other.class_data());
     }
 
+    /// Whether a value of class `target` can be assigned to `self`, following
+    /// the checkcast/instanceof rules of jvms-6.5. A hit in the
+    /// `secondary_super_cache` of `target` skips the walk of the hierarchy, a
+    /// stale entry only costs that walk.
     pub fn is_assignable_from(&self, target: JClassPtr, vm: VMPtr) -> bool {
         let self_cls = JClassPtr::from_ref(self);
         if self_cls == target {
             return true;
         }
         let target_cls_data = target.class_data();
+        if target_cls_data.secondary_super_cache() == self_cls {
+            return true;
+        }
+        let result = Self::is_subtype_slow(self_cls, target, vm);
+        if result {
+            target_cls_data.set_secondary_super_cache(self_cls);
+        }
+        return result;
+    }
+
+    fn is_subtype_slow(self_cls: JClassPtr, target: JClassPtr, vm: VMPtr) -> bool {
+        let self_cls_data = self_cls.class_data();
+        let target_cls_data = target.class_data();
+        if target_cls_data.is_array() {
+            if self_cls_data.is_interface() {
+                let shared_objs = vm.shared_objs();
+                return self_cls == shared_objs.java_lang_cloneable_cls
+                    || self_cls == shared_objs.java_io_serializable_cls;
+            }
+            if !self_cls_data.is_array() {
+                return self_cls == vm.preloaded_classes().jobject_cls();
+            }
+            let self_component = self_cls_data.component_type();
+            let target_component = target_cls_data.component_type();
+            if JClass::is_primitive(self_component) || JClass::is_primitive(target_component) {
+                return self_component == target_component;
+            }
+            return self_component.is_assignable_from(target_component, vm);
+        }
         if target_cls_data.is_interface() {
-            if self_cls == vm.preloaded_classes().jobject_cls() {
-                return true;
+            if self_cls_data.is_interface() {
+                return Self::is_superinterface(target, self_cls);
             }
-            return target.is_implement(self_cls);
-        } else if target_cls_data.is_array() {
             return self_cls == vm.preloaded_classes().jobject_cls();
         }
-        if self_cls.class_data().is_interface() {
-            return target.is_implement(self_cls);
+        if self_cls_data.is_interface() {
+            return Self::is_superinterface(target, self_cls);
         }
-        let mut target_super_class = target.class_data().super_class();
+        let mut target_super_class = target_cls_data.super_class();
         loop {
             if target_super_class.is_null() {
                 return false;
@@ -1236,6 +1533,25 @@ impl JClass {
         }
     }
 
+    /// Searches the direct and inherited superinterfaces of `cls`, including
+    /// those declared by its superclasses.
+    fn is_superinterface(cls: JClassPtr, iface: JClassPtr) -> bool {
+        let class_data = cls.class_data();
+        let interfaces = class_data.interfaces();
+        if interfaces.is_not_null() {
+            for i in 0..interfaces.length() {
+                let cls_iface: JClassPtr = interfaces.get(i).cast();
+                if cls_iface == iface || Self::is_superinterface(cls_iface, iface) {
+                    return true;
+                }
+            }
+        }
+        if class_data.super_class().is_not_null() {
+            return Self::is_superinterface(class_data.super_class(), iface);
+        }
+        return false;
+    }
+
     pub fn is_unlinked_symbol(&self, thread: ThreadPtr) -> bool {
         return thread.vm().shared_objs().vm_str_cls == JClassPtr::from_ref(self);
     }
@@ -1252,55 +1568,74 @@ impl JClass {
         }
     }
 
-    pub fn get_field(&self, field_ref: &ConstMemberRef) -> (FieldPtr, JClassPtr) {
+    pub fn get_field(
+        &self,
+        field_ref: &ConstMemberRef,
+    ) -> Result<(FieldPtr, JClassPtr), FieldResolutionError> {
         return self.get_field_with_name(field_ref.member_name);
     }
 
-    pub fn get_field_with_name(&self, field_name: SymbolPtr) -> (FieldPtr, JClassPtr) {
-        let mut lookup_cls = JClassPtr::from_ref(self);
-        loop {
-            let class_data = lookup_cls.class_data();
-            let fields = class_data.fields();
-            for i in 0..fields.length() {
-                let field: FieldPtr = fields.get(i).cast();
-                if field.name() == field_name {
-                    return (field, lookup_cls);
-                }
+    /// The field and the class declaring it.
+    pub fn get_field_with_name(
+        &self,
+        field_name: SymbolPtr,
+    ) -> Result<(FieldPtr, JClassPtr), FieldResolutionError> {
+        let thread = Thread::current();
+        let result = Self::lookup_field(JClassPtr::from_ref(self), field_name, thread.vm());
+        if result.0.is_null() {
+            log::trace!("get_field {} not found", field_name.as_str());
+            return Err(FieldResolutionError::NoSuchField(
+                field_name.as_str().to_string(),
+            ));
+        }
+        return Ok(result);
+    }
+
+    // jvms-5.4.3.2: the class itself, then its direct superinterfaces
+    // recursively, then its superclass recursively.
+    fn lookup_field(
+        lookup_cls: JClassPtr,
+        field_name: SymbolPtr,
+        vm: &VM,
+    ) -> (FieldPtr, JClassPtr) {
+        let class_data = lookup_cls.class_data();
+        let fields = class_data.fields();
+        for i in 0..fields.length() {
+            let field: FieldPtr = fields.get(i).cast();
+            if field.name(vm) == field_name {
+                return (field, lookup_cls);
             }
-            if class_data.super_class().is_not_null() {
-                lookup_cls = class_data.super_class();
-            } else {
-                break;
+        }
+        let interfaces = class_data.interfaces();
+        if interfaces.is_not_null() {
+            for i in 0..interfaces.length() {
+                let iface: JClassPtr = interfaces.get(i).cast();
+                let result = Self::lookup_field(iface, field_name, vm);
+                if result.0.is_not_null() {
+                    return result;
+                }
             }
         }
-
-        log::trace!("get_field {} not found", field_name.as_str());
+        if class_data.super_class().is_not_null() {
+            return Self::lookup_field(class_data.super_class(), field_name, vm);
+        }
         return (FieldPtr::null(), JClassPtr::null());
     }
 
-    pub fn get_field_val(
-        &self,
-        obj: ObjectPtr,
-        field_ref: &ConstMemberRef,
-        thread: ThreadPtr,
-    ) -> Result<i64, ClassLoadErr> {
-        let (field, _) = self.get_field(field_ref);
-        return Ok(field.get_value(obj, thread)?);
-    }
-
     pub fn resolve_interface_method(
         class: JClassPtr,
         iface: JClassPtr,
         name: SymbolPtr,
         descriptor: SymbolPtr,
+        vm: &VM,
     ) -> Result<ResolvedMethod, MethodResolutionError> {
         let mut class_data = class.class_data();
-        let class_v_methods = class_data.vtab().methods();
+        let class_vtab = class_data.vtab();
         loop {
             let vtab = class_data.vtab();
             let ifaces = vtab.ifaces();
+            let imethod_offsets = vtab.imethod_offsets();
             let imethod_indexes = vtab.imethod_indexes();
-            let imethod_indexes_offset = 0isize;
             for if_idx in 0..vtab.ifaces_len() {
                 let impl_iface = *ifaces.offset(if_idx as isize);
                 log::trace!(
@@ -1311,58 +1646,99 @@ impl JClass {
                     descriptor.as_str(),
                 );
                 if impl_iface == iface {
-                    let mut imethod_idx = -1;
-                    impl_iface.resolve_local_method(name, descriptor, &mut imethod_idx);
-                    if imethod_idx >= 0 {
+                    let imethod = impl_iface.resolve_local_method_unchecked(name, descriptor);
+                    if imethod.is_not_null() {
+                        let imethod_idx = match VTable::itable_index(imethod, vm) {
+                            Some(imethod_idx) => imethod_idx,
+                            // static and private interface methods are not
+                            // invoked through invokeinterface
+                            None => return Err(MethodResolutionError::IncompatibleClassChange),
+                        };
+                        let imethod_offset = *imethod_offsets.offset(if_idx as isize);
                         let v_method_idx =
-                            *imethod_indexes.offset(imethod_indexes_offset + imethod_idx as isize);
+                            *imethod_indexes.offset(imethod_offset as isize + imethod_idx as isize);
                         log::trace!(
                                 "resolve_interface_method class {} success, v_method_idx {}, v_method addr 0x{:x}",
                                 class_data.name().as_str(),
                                 v_method_idx,
-                                (*class_v_methods.offset(v_method_idx as isize)).as_isize()
+                                class_vtab.method(v_method_idx, vm).as_isize()
                             );
                         return Ok(ResolvedMethod {
                             decl_class: JClassPtr::null(),
-                            method: *class_v_methods.offset(v_method_idx as isize),
+                            method: class_vtab.method(v_method_idx, vm),
                             method_idx: v_method_idx,
                         });
                     } else {
-                        log::trace!("resolve specific method failed {}", imethod_idx);
-                        JClass::debug(impl_iface);
-                        return Err(MethodResolutionError::NoSuchMethod);
+                        // declared by a superinterface of iface
+                        return Self::resolve_inherited_interface_method(
+                            class, iface, name, descriptor, vm,
+                        );
                     }
                 }
             }
             if class_data.super_class().is_not_null() {
                 class_data = class_data.super_class().class_data();
             } else {
-                return Err(MethodResolutionError::IncompatibleClassChange);
+                // iface is not implemented by the class
+                return Self::resolve_inherited_interface_method(
+                    class, iface, name, descriptor, vm,
+                );
+            }
+        }
+    }
+
+    fn resolve_inherited_interface_method(
+        class: JClassPtr,
+        iface: JClassPtr,
+        name: SymbolPtr,
+        descriptor: SymbolPtr,
+        vm: &VM,
+    ) -> Result<ResolvedMethod, MethodResolutionError> {
+        if !Self::is_superinterface(class, iface) {
+            return Err(MethodResolutionError::IncompatibleClassChange);
+        }
+        let vtab = class.class_data().vtab();
+        for v_method_idx in 0..vtab.vtab_len {
+            let method = vtab.method(v_method_idx, vm);
+            if method.name() == name && method.descriptor() == descriptor {
+                return Ok(ResolvedMethod {
+                    decl_class: JClassPtr::null(),
+                    method,
+                    method_idx: v_method_idx,
+                });
             }
         }
+        log::trace!(
+            "resolve inherited interface method {}{} failed",
+            name.as_str(),
+            descriptor.as_str()
+        );
+        return Err(MethodResolutionError::NoSuchMethod);
     }
 
+    /// Selects the implementation of `method` for a receiver of class
+    /// `receiver_cls` by its vtable index.
     pub fn resolve_virtual_with_index(
-        objref: ObjectPtr,
+        receiver_cls: JClassPtr,
         method: MethodPtr,
         method_idx: u32,
+        vm: &VM,
     ) -> Result<ResolvedMethod, MethodResolutionError> {
-        debug_assert!(objref.is_not_null());
-        let vtab = objref.jclass().class_data().vtab();
+        debug_assert!(receiver_cls.is_not_null());
+        let vtab = receiver_cls.class_data().vtab();
         let v_methods_len = vtab.vtab_len;
         if method_idx as u32 >= v_methods_len {
             log::trace!(
-                "resolve_virtual_with_index failed, objref jclass {}, method_idx {} >= v_methods_len {}, vtab addr 0x{:x}, jobject vtab addr 0x{:x}",
-                objref.jclass().name().as_str(),
+                "resolve_virtual_with_index failed, receiver class {}, method_idx {} >= v_methods_len {}, vtab addr 0x{:x}, jobject vtab addr 0x{:x}",
+                receiver_cls.name().as_str(),
                 method_idx,
                 v_methods_len,
                 vtab.as_isize(),
-                Thread::current().vm().preloaded_classes().jobject_cls().class_data().vtab().as_isize()
+                vm.preloaded_classes().jobject_cls().class_data().vtab().as_isize()
             );
             return Err(MethodResolutionError::AbstractMethod);
         }
-        let v_methods = vtab.methods();
-        let resolved_method = *v_methods.offset(method_idx as isize);
+        let resolved_method = vtab.method(method_idx, vm);
         if resolved_method.name() != method.name()
             || resolved_method.descriptor() != method.descriptor()
         {
@@ -1386,7 +1762,7 @@ impl JClass {
             return Err(MethodResolutionError::AbstractMethod);
         }
         return Ok(ResolvedMethod {
-            decl_class: objref.jclass(),
+            decl_class: receiver_cls,
             method: resolved_method,
             method_idx: method_idx,
         });
@@ -1434,10 +1810,11 @@ impl JClass {
             return self.resolve_self_method(name, descriptor);
         }
         let vtab = self.class_data().vtab();
-        let v_methods = vtab.methods();
         let vtab_len = vtab.vtab_len;
-        for idx in 0..vtab_len {
-            let v_method = *v_methods.offset(idx as isize);
+        // the last match is declared closest to the class, a package-private
+        // method of a superclass it does not override comes first
+        for idx in (0..vtab_len).rev() {
+            let v_method = vtab.method(idx, vm);
             if v_method.name() == name && v_method.descriptor() == descriptor {
                 return Ok(ResolvedMethod {
                     decl_class: JClassPtr::null(),
@@ -1455,6 +1832,23 @@ impl JClass {
         return Err(MethodResolutionError::NoSuchMethod);
     }
 
+    // jvms-5.4.3.3: the class itself, then its superclasses recursively.
+    pub fn resolve_static_method(
+        &self,
+        name: SymbolPtr,
+        descriptor: SymbolPtr,
+    ) -> Result<ResolvedMethod, MethodResolutionError> {
+        let mut lookup_cls = JClassPtr::from_ref(self);
+        while lookup_cls.is_not_null() {
+            if let Ok(mut resolved_method) = lookup_cls.resolve_self_method(name, descriptor) {
+                resolved_method.decl_class = lookup_cls;
+                return Ok(resolved_method);
+            }
+            lookup_cls = lookup_cls.class_data().super_class();
+        }
+        return Err(MethodResolutionError::NoSuchMethod);
+    }
+
     pub fn resolve_self_method(
         &self,
         name: SymbolPtr,
@@ -1606,7 +2000,7 @@ impl JClass {
         return Ok(());
     }
 
-    fn is_linked(&self) -> bool {
+    pub(crate) fn is_linked(&self) -> bool {
         return self._init_state.as_u8() >= ClassInitState::Linked.as_u8();
     }
 
@@ -1647,7 +2041,7 @@ impl JClass {
             if field.field_class_unchecked().is_null() {
                 let field_cls = vm
                     .bootstrap_class_loader
-                    .load_class_with_symbol(field.descriptor())
+                    .load_class_with_symbol(field.descriptor(vm))
                     .map_err(|_e| InitializationError::LinkingFailed)?;
                 field.as_mut_ref().set_field_class(field_cls);
             }
@@ -1658,7 +2052,7 @@ impl JClass {
                 log::trace!(
                     "adjust_fields_offset {}, field {}, offset {}",
                     self.name().as_str(),
-                    field.name().as_str(),
+                    field.name(vm).as_str(),
                     field.layout_offset()
                 );
             } else {
@@ -1735,7 +2129,9 @@ pub struct FieldLayout {
 impl FieldLayout {
     const FIELD_ALIGNMENT: u16 = 8;
 
-    pub fn obtain_field_offset(&mut self, field_val_size: u16) -> u16 {
+    /// The offset of the next field of `field_val_size` bytes, `None` once
+    /// the fields outgrow the 16-bit offsets.
+    pub fn obtain_field_offset(&mut self, field_val_size: u16) -> Option<u16> {
         // TODO
         let offset: u16;
         if self.padding >= field_val_size {
@@ -1745,15 +2141,15 @@ impl FieldLayout {
         } else if field_val_size < Self::FIELD_ALIGNMENT {
             self.padding = Self::FIELD_ALIGNMENT - field_val_size;
             offset = self.aligned_offset;
-            self.aligned_offset += Self::FIELD_ALIGNMENT;
+            self.aligned_offset = self.aligned_offset.checked_add(Self::FIELD_ALIGNMENT)?;
             self.offset += field_val_size;
         } else {
             self.padding = 0;
             offset = self.aligned_offset;
-            self.aligned_offset += field_val_size;
+            self.aligned_offset = self.aligned_offset.checked_add(field_val_size)?;
             self.offset = self.aligned_offset;
         }
-        return offset;
+        return Some(offset);
     }
 
     pub fn get_aligned_size(&self) -> u16 {
@@ -1774,3 +2170,52 @@ pub enum MethodResolutionError {
     AbstractMethod,
     IllegalAccess,
 }
+
+impl fmt::Display for InitializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            InitializationError::ResolveError(_) => write!(f, "method resolution failed"),
+            InitializationError::LinkingFailed => write!(f, "linking failed"),
+        };
+    }
+}
+
+impl Error for InitializationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return match self {
+            InitializationError::ResolveError(e) => Some(e),
+            InitializationError::LinkingFailed => None,
+        };
+    }
+}
+
+impl fmt::Display for MethodResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MethodResolutionError::IncompatibleClassChange => "IncompatibleClassChangeError",
+            MethodResolutionError::NoSuchMethod => "NoSuchMethodError",
+            MethodResolutionError::AbstractMethod => "AbstractMethodError",
+            MethodResolutionError::IllegalAccess => "IllegalAccessError",
+        };
+        return f.write_str(name);
+    }
+}
+
+impl Error for MethodResolutionError {}
+
+#[derive(Debug)]
+pub enum FieldResolutionError {
+    /// No field of the name is declared by the class, its superinterfaces or
+    /// its superclasses.
+    NoSuchField(String),
+}
+
+impl fmt::Display for FieldResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            FieldResolutionError::NoSuchField(name) => write!(f, "NoSuchFieldError: {}", name),
+        };
+    }
+}
+
+impl Error for FieldResolutionError {}
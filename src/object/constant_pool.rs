@@ -1,19 +1,32 @@
-use std::mem::size_of;
+use std::{
+    mem::size_of,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use crate::{define_oop, memory::align, thread::ThreadPtr};
 
 use super::{
     array::{JByteArray, JByteArrayPtr},
     class::JClassPtr,
+    field::{FieldAccess, FieldKind},
     prelude::{JByte, JDouble, JFloat, JInt, JLong},
     ptr::Ptr,
-    string::VMStringPtr,
+    string::{JStringPtr, VMStringPtr},
     symbol::SymbolPtr,
     Header, Object,
 };
 
 pub type ConstantPoolPtr = Ptr<ConstantPool>;
 
+/// The words of resolution cache per entry.
+const CACHE_SLOTS: usize = 2;
+
+/// The bits of the second cache slot of a resolved static field besides its
+/// offset, in bits 0-15, and its kind, in bits 16-23. The slot is non-zero
+/// once resolved even for a boolean at offset 0.
+const STATIC_FIELD_RESOLVED: u64 = 1 << 32;
+const STATIC_FIELD_VOLATILE: u64 = 1 << 24;
+
 pub struct ConstMemberRef {
     pub class_name: SymbolPtr,
     pub member_name: SymbolPtr,
@@ -30,6 +43,15 @@ impl ConstMemberRef {
     }
 }
 
+/// A static field resolved by getstatic or putstatic, see
+/// [`ConstantPool::resolved_static_field`]. The instructions access the value
+/// in `decl_cls` without going through the `Field`.
+#[derive(Clone, Copy)]
+pub struct ResolvedStaticField {
+    pub decl_cls: JClassPtr,
+    pub access: FieldAccess,
+}
+
 #[derive(Debug)]
 #[repr(u8)]
 pub enum ConstantTag {
@@ -69,6 +91,9 @@ impl ConstantPool {
         let cp = ConstantPoolPtr::from_addr(thread.heap().alloc_code(size));
         Object::init_header(cp.cast(), thread.vm().shared_objs().internal_cp_cls);
         cp.set_tags_length(length);
+        unsafe {
+            std::ptr::write_bytes(cp.raw_cache(), 0, length as usize * CACHE_SLOTS);
+        }
         return cp;
     }
 
@@ -126,14 +151,17 @@ impl ConstantPool {
         debug_assert_eq!(self.tags().get(index as JInt), ConstantTag::Float as JByte);
         unsafe {
             let val = std::ptr::read(self.raw_info().offset(index as isize));
-            return std::mem::transmute_copy(&val);
+            return JFloat::from_bits(val as u32);
         }
     }
 
     pub fn set_float(&mut self, index: u16, value: JFloat) {
         self.tags().set(index as JInt, ConstantTag::Float as JByte);
         unsafe {
-            std::ptr::write(self.raw_info().offset(index as isize), value as u64);
+            std::ptr::write(
+                self.raw_info().offset(index as isize),
+                value.to_bits() as u64,
+            );
         }
     }
 
@@ -156,14 +184,14 @@ impl ConstantPool {
         debug_assert_eq!(self.tags().get(index as JInt), ConstantTag::Double as JByte);
         unsafe {
             let val = std::ptr::read(self.raw_info().offset(index as isize));
-            return std::mem::transmute(val);
+            return JDouble::from_bits(val);
         }
     }
 
     pub fn set_double(&mut self, index: u16, value: JDouble) {
         self.tags().set(index as JInt, ConstantTag::Double as JByte);
         unsafe {
-            std::ptr::write(self.raw_info().offset(index as isize), value as u64);
+            std::ptr::write(self.raw_info().offset(index as isize), value.to_bits());
         }
     }
 
@@ -189,6 +217,14 @@ impl ConstantPool {
         }
     }
 
+    /// The index of the Utf8 entry naming the Class entry at `index`.
+    pub fn get_class_name_index(&self, index: u16) -> u16 {
+        debug_assert_eq!(self.tags().get(index as JInt), ConstantTag::Class as JByte);
+        unsafe {
+            return std::ptr::read(self.raw_info().offset(index as isize)) as u16;
+        }
+    }
+
     pub fn get_name_type_info(&self, index: u16) -> (SymbolPtr, SymbolPtr) {
         let index_tag = self.tags().get(index as i32);
         assert_eq!(index_tag, ConstantTag::NameAndType as JByte);
@@ -289,6 +325,23 @@ impl ConstantPool {
         }
     }
 
+    /// The reference kind and the member referenced by the MethodHandle at
+    /// `index`.
+    pub fn get_method_handle(&self, index: u16) -> (u8, ConstMemberRef) {
+        debug_assert_eq!(
+            self.tags().get(index as JInt),
+            ConstantTag::MethodHandle as JByte
+        );
+        unsafe {
+            let encoded_value = std::ptr::read(self.raw_info().offset(index as isize));
+            let ref_kind = (encoded_value >> 16) as u8;
+            return (
+                ref_kind,
+                self.get_member_ref((encoded_value & 0xffff) as u16),
+            );
+        }
+    }
+
     pub fn set_method_type(&mut self, index: u16, descriptor_index: u16) {
         self.tags()
             .set(index as JInt, ConstantTag::MethodType as JByte);
@@ -300,6 +353,18 @@ impl ConstantPool {
         }
     }
 
+    /// The Utf8 descriptor of the MethodType at `index`.
+    pub fn get_method_type(&self, index: u16) -> SymbolPtr {
+        debug_assert_eq!(
+            self.tags().get(index as JInt),
+            ConstantTag::MethodType as JByte
+        );
+        unsafe {
+            let descriptor_index = std::ptr::read(self.raw_info().offset(index as isize));
+            return self.get_utf8(descriptor_index as u16);
+        }
+    }
+
     pub fn set_invoke_dynamic(
         &mut self,
         index: u16,
@@ -315,6 +380,78 @@ impl ConstantPool {
         }
     }
 
+    /// The bootstrap method attribute index and the name and descriptor of
+    /// the InvokeDynamic at `index`.
+    pub fn get_invoke_dynamic(&self, index: u16) -> (u16, SymbolPtr, SymbolPtr) {
+        debug_assert_eq!(
+            self.tags().get(index as JInt),
+            ConstantTag::InvokeDynamic as JByte
+        );
+        unsafe {
+            let encoded_value = std::ptr::read(self.raw_info().offset(index as isize));
+            let (name, descriptor) = self.get_name_type_info((encoded_value & 0xffff) as u16);
+            return ((encoded_value >> 16) as u16, name, descriptor);
+        }
+    }
+
+    /// The static field of the Fieldref at `index` once
+    /// [`cache_static_field`](Self::cache_static_field) stored it.
+    pub fn resolved_static_field(&self, index: u16) -> Option<ResolvedStaticField> {
+        let (decl_cls_slot, access_slot) = self.cache_slots(index);
+        let access = access_slot.load(Ordering::Acquire);
+        if access == 0 {
+            return None;
+        }
+        let decl_cls = JClassPtr::from_usize(decl_cls_slot.load(Ordering::Relaxed) as usize);
+        return Some(ResolvedStaticField {
+            decl_cls,
+            access: FieldAccess {
+                offset: access as u16,
+                kind: FieldKind::from((access >> 16) as u8),
+                is_volatile: access & STATIC_FIELD_VOLATILE != 0,
+            },
+        });
+    }
+
+    /// Keeps the resolved static field of the Fieldref at `index`, the
+    /// declaring class must be initialized already so that later accesses
+    /// need no initialization check.
+    pub fn cache_static_field(&self, index: u16, resolved: ResolvedStaticField) {
+        debug_assert_eq!(
+            self.tags().get(index as JInt),
+            ConstantTag::Fieldref as JByte
+        );
+        let (decl_cls_slot, access_slot) = self.cache_slots(index);
+        let access = resolved.access;
+        let mut encoded =
+            STATIC_FIELD_RESOLVED | (access.kind as u64) << 16 | u64::from(access.offset);
+        if access.is_volatile {
+            encoded |= STATIC_FIELD_VOLATILE;
+        }
+        decl_cls_slot.store(resolved.decl_cls.as_usize() as u64, Ordering::Relaxed);
+        // published last, readers check the access first
+        access_slot.store(encoded, Ordering::Release);
+    }
+
+    /// The interned string of the String entry at `index` once
+    /// [`cache_string`](Self::cache_string) stored it.
+    pub fn resolved_string(&self, index: u16) -> Option<JStringPtr> {
+        let (jstr_slot, _) = self.cache_slots(index);
+        let jstr = JStringPtr::from_usize(jstr_slot.load(Ordering::Acquire) as usize);
+        if jstr.is_null() {
+            return None;
+        }
+        return Some(jstr);
+    }
+
+    /// Keeps the interned string of the String entry at `index`. Interned
+    /// strings are permanent, so the pointer stays valid across collections.
+    pub fn cache_string(&self, index: u16, jstr: JStringPtr) {
+        debug_assert_eq!(self.tags().get(index as JInt), ConstantTag::String as JByte);
+        let (jstr_slot, _) = self.cache_slots(index);
+        jstr_slot.store(jstr.as_usize() as u64, Ordering::Release);
+    }
+
     pub fn info(&self) -> Ptr<u64> {
         return Ptr::from_self_offset_bytes::<u64>(self, self.raw_info_offset() as isize);
     }
@@ -324,8 +461,30 @@ impl ConstantPool {
             .as_mut_raw_ptr();
     }
 
+    fn raw_cache(&self) -> *mut u64 {
+        return unsafe { self.raw_info().offset(self.tags().length() as isize) };
+    }
+
+    fn cache_slots(&self, index: u16) -> (&AtomicU64, &AtomicU64) {
+        debug_assert!((index as JInt) < self.tags().length());
+        unsafe {
+            let slots = self
+                .raw_cache()
+                .offset(index as isize * CACHE_SLOTS as isize);
+            return (
+                &*(slots as *const AtomicU64),
+                &*(slots.offset(1) as *const AtomicU64),
+            );
+        }
+    }
+
+    // the info of each entry, then the resolution cache of each entry
     fn size(length: u16) -> usize {
-        align(Header::size() + JByteArray::size(length as i32) + length as usize * size_of::<u64>())
+        align(
+            Header::size()
+                + JByteArray::size(length as i32)
+                + length as usize * (1 + CACHE_SLOTS) * size_of::<u64>(),
+        )
     }
 
     fn tags(&self) -> JByteArrayPtr {
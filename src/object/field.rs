@@ -1,16 +1,19 @@
-use std::mem::size_of;
+use std::{
+    mem::size_of,
+    sync::atomic::{fence, Ordering},
+};
 
 use crate::{
-    classfile::ClassLoadErr, define_oop, memory::align, object::{class::JClass, Object}, thread::ThreadPtr,
-    JClassPtr, ObjectPtr,
+    classfile::ClassLoadErr, define_oop, memory::align, object::class::JClass, thread::ThreadPtr,
+    value::JValue, vm::VM, JClassPtr, ObjectPtr,
 };
 
 use super::{
     array::{JArrayPtr, JCharArrayPtr},
-    prelude::{JByte, JChar, JDouble, JFloat, JInt, JLong, ObjectRawPtr},
+    prelude::{JBoolean, JByte, JChar, JDouble, JFloat, JInt, JLong, JShort, ObjectRawPtr},
     ptr::Ptr,
     string::JStringPtr,
-    symbol::SymbolPtr,
+    symbol::{SymbolId, SymbolPtr},
 };
 
 pub type FieldPtr = Ptr<Field>;
@@ -19,10 +22,11 @@ define_oop!(
     struct Field {
         access_flags: u16,
         layout_offset: u16,
-        name: SymbolPtr,
-        _field_class_or_null: JClassPtr,
-        descriptor: SymbolPtr,
+        name: SymbolId,
+        descriptor: SymbolId,
         constval_index: u16,
+        descriptor_char: u8,
+        _field_class_or_null: JClassPtr,
     }
 );
 
@@ -35,11 +39,13 @@ impl Field {
         field_class_or_null: JClassPtr,
         thread: ThreadPtr,
     ) -> FieldPtr {
-        let mut field = FieldPtr::from_addr(thread.vm().heap().alloc_code(Self::size()));
+        let vm = thread.vm();
+        let mut field = FieldPtr::from_addr(vm.heap().alloc_code(Self::size()));
         field.access_flags = access_flags;
         field.layout_offset = layout_offset;
-        field.name = name;
-        field.descriptor = descriptor;
+        field.name = vm.symbol_table.id(name);
+        field.descriptor = vm.symbol_table.id(descriptor);
+        field.descriptor_char = descriptor.as_bytes()[0];
         field._field_class_or_null = field_class_or_null;
         field.constval_index = 0;
         return field;
@@ -51,12 +57,14 @@ impl Field {
         // return field;
     }
 
-    pub fn name(&self) -> SymbolPtr {
-        self.name
+    pub fn name(&self, vm: &VM) -> SymbolPtr {
+        return vm.symbol_table.symbol(self.name);
     }
 
-    pub fn descriptor(&self) -> SymbolPtr {
-        self.descriptor
+    /// The descriptor of a primitive or array field, or the class name of
+    /// any other reference field.
+    pub fn descriptor(&self, vm: &VM) -> SymbolPtr {
+        return vm.symbol_table.symbol(self.descriptor);
     }
 
     pub fn access_flags(&self) -> u16 {
@@ -67,59 +75,128 @@ impl Field {
         return FieldAccessFlags::is_static(self.access_flags);
     }
 
+    pub fn is_volatile(&self) -> bool {
+        return FieldAccessFlags::is_volatile(self.access_flags);
+    }
+
+    pub fn constval_index(&self) -> u16 {
+        self.constval_index
+    }
+
     pub fn set_constval_index(&mut self, constval_index: u16) {
         self.constval_index = constval_index;
     }
 
-    pub fn get_value(&self, obj: ObjectPtr, thread: ThreadPtr) -> Result<i64, ClassLoadErr> {
+    pub fn get_typed_value<T: Copy + FieldValue>(&self, obj: ObjectPtr) -> T {
         debug_assert!(!self.is_static());
 
-        return Ok(obj.read_value(
-            self.layout_offset as i32,
-            JClass::ref_size(self.field_class(thread)?) as i32,
-        ));
+        let value = self.load_typed_value(obj);
+        self.volatile_load_fence();
+        return value;
     }
 
-    pub fn get_typed_value<T: Copy + FieldValue>(&self, obj: ObjectPtr) -> T {
-        debug_assert!(!self.is_static());
+    pub fn set_typed_value<T: FieldValue>(&self, obj: ObjectPtr, value: T) {
+        self.volatile_pre_store_fence();
+        self.store_value(obj, value);
+        self.volatile_post_store_fence();
+    }
 
-        return *obj.read_value_ptr(self.layout_offset as isize);
+    pub fn get_static_typed_value<T: FieldValue>(&self, class: JClassPtr) -> T {
+        debug_assert!(self.is_static());
+        let value = self.load_typed_value(class.cast());
+        self.volatile_load_fence();
+        return value;
     }
 
-    pub fn fast_get_value(&self, obj: ObjectPtr) -> i64 {
-        debug_assert!(!self.is_static());
-        debug_assert!(self._field_class_or_null.is_not_null());
+    pub fn set_static_value<T: FieldValue>(&self, class: JClassPtr, val: T) {
+        self.volatile_pre_store_fence();
+        self.store_value(class.cast(), val);
+        self.volatile_post_store_fence();
+    }
 
-        return obj.read_value(
-            self.layout_offset as i32,
-            JClass::ref_size(self._field_class_or_null) as i32,
-        );
+    /// The field descriptor as in the class file, reference fields keep only
+    /// their class name in `descriptor`.
+    pub fn type_descriptor(&self, vm: &VM) -> String {
+        let descriptor = self.descriptor(vm);
+        if self.field_class_is_primitive() || descriptor.as_str().starts_with('[') {
+            return descriptor.as_str().to_string();
+        }
+        return format!("L{};", descriptor.as_str());
     }
 
-    pub fn set_typed_value<T: FieldValue>(&self, obj: ObjectPtr, value: T) {
-        let fields_addr = obj.as_address();
-        let field_ptr: Ptr<T> = Ptr::from_addr(fields_addr.offset(self.layout_offset as isize));
-        unsafe {
-            std::ptr::write_unaligned(field_ptr.as_mut_raw_ptr(), value);
+    /// How the value of the field is stored, see [`FieldKind`].
+    pub fn kind(&self) -> FieldKind {
+        if !self.field_class_is_primitive() {
+            return FieldKind::Reference;
         }
-        // *field_ptr = value;
+        return FieldKind::from_descriptor(self.descriptor_char);
     }
 
-    pub fn get_static_value(&self, class: JClassPtr) -> i64 {
-        debug_assert!(self.is_static());
-        return class.get_static_value(
-            self.layout_offset as i32,
-            JClass::ref_size(self._field_class_or_null) as i32,
-        );
+    /// Where the value of the field is stored and how to access it.
+    pub fn access(&self) -> FieldAccess {
+        return FieldAccess {
+            offset: self.layout_offset,
+            kind: self.kind(),
+            is_volatile: self.is_volatile(),
+        };
     }
 
-    pub fn get_static_typed_value<T: FieldValue>(&self, class: JClassPtr) -> T {
-        debug_assert!(self.is_static());
-        return *class.cast::<Object>().read_value_ptr(self.layout_offset as isize);
+    /// Reads the field of `holder` (the declaring class for static fields)
+    /// as getfield and getstatic push it, see [`FieldAccess::get_stack_value`].
+    pub fn get_stack_value(&self, holder: ObjectPtr) -> i64 {
+        return self.access().get_stack_value(holder);
     }
 
-    pub fn set_static_value<T: FieldValue>(&self, class: JClassPtr, val: T) {
-        return class.set_static_value(self.layout_offset as i32, val);
+    /// Writes a value popped by putfield or putstatic to the field of
+    /// `holder`, see [`FieldAccess::set_stack_value`].
+    pub fn set_stack_value(&self, holder: ObjectPtr, value: i64) {
+        self.access().set_stack_value(holder, value);
+    }
+
+    /// Reads the field of `holder` (the declaring class for static fields)
+    /// into the `JValue` member matching its descriptor.
+    pub fn get_jvalue(&self, holder: ObjectPtr) -> JValue {
+        return match self.kind() {
+            FieldKind::Boolean => JValue::with_bool_val(self.load_holder_value(holder)),
+            FieldKind::Byte => JValue::with_byte_val(self.load_holder_value(holder)),
+            FieldKind::Char => JValue::with_char_val(self.load_holder_value(holder)),
+            FieldKind::Short => JValue::with_short_val(self.load_holder_value(holder)),
+            FieldKind::Int => JValue::with_int_val(self.load_holder_value(holder)),
+            FieldKind::Float => JValue::with_float_val(self.load_holder_value(holder)),
+            FieldKind::Long => JValue::with_long_val(self.load_holder_value(holder)),
+            FieldKind::Double => JValue::with_double_val(self.load_holder_value(holder)),
+            FieldKind::Reference => JValue::with_obj_val(self.load_holder_value(holder)),
+        };
+    }
+
+    /// Writes `value` to the field of `holder`, see [`Field::get_jvalue`].
+    pub fn set_jvalue(&self, holder: ObjectPtr, value: JValue) {
+        match self.kind() {
+            FieldKind::Boolean => self.store_holder_value(holder, value.bool_val() & 1),
+            FieldKind::Byte => self.store_holder_value(holder, value.byte_val()),
+            FieldKind::Char => self.store_holder_value(holder, value.char_val()),
+            FieldKind::Short => self.store_holder_value(holder, value.short_val()),
+            FieldKind::Int => self.store_holder_value(holder, value.int_val()),
+            FieldKind::Float => self.store_holder_value(holder, value.float_val()),
+            FieldKind::Long => self.store_holder_value(holder, value.long_val()),
+            FieldKind::Double => self.store_holder_value(holder, value.double_val()),
+            FieldKind::Reference => self.store_holder_value(holder, value.obj_val()),
+        }
+    }
+
+    fn load_holder_value<T: FieldValue>(&self, holder: ObjectPtr) -> T {
+        if self.is_static() {
+            return self.get_static_typed_value(holder.cast());
+        }
+        return self.get_typed_value(holder);
+    }
+
+    fn store_holder_value<T: FieldValue>(&self, holder: ObjectPtr, value: T) {
+        if self.is_static() {
+            self.set_static_value(holder.cast(), value);
+        } else {
+            self.set_typed_value(holder, value);
+        }
     }
 
     pub fn instance_size(&self) -> usize {
@@ -154,7 +231,7 @@ impl Field {
             let field_class = thread
                 .vm()
                 .bootstrap_class_loader
-                .load_class(self.descriptor.as_str())?;
+                .load_class(self.descriptor(thread.vm()).as_str())?;
             self_ptr._field_class_or_null = field_class;
             return Ok(field_class);
         }
@@ -165,11 +242,142 @@ impl Field {
         self._field_class_or_null = field_class;
     }
 
+    #[inline(always)]
+    fn load_typed_value<T: FieldValue>(&self, base: ObjectPtr) -> T {
+        return load_value(base, self.layout_offset, self.is_volatile());
+    }
+
+    #[inline(always)]
+    fn store_value<T: FieldValue>(&self, base: ObjectPtr, value: T) {
+        store_value(base, self.layout_offset, self.is_volatile(), value);
+    }
+
+    #[inline(always)]
+    fn volatile_load_fence(&self) {
+        volatile_load_fence(self.is_volatile());
+    }
+
+    #[inline(always)]
+    fn volatile_pre_store_fence(&self) {
+        volatile_pre_store_fence(self.is_volatile());
+    }
+
+    #[inline(always)]
+    fn volatile_post_store_fence(&self) {
+        volatile_post_store_fence(self.is_volatile());
+    }
+
     const fn size() -> usize {
         return align(size_of::<Self>());
     }
 }
 
+/// Where a field value is stored and how to access it, all getstatic and
+/// putstatic need once the field is resolved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldAccess {
+    pub offset: u16,
+    pub kind: FieldKind,
+    pub is_volatile: bool,
+}
+
+impl FieldAccess {
+    /// Reads the field of `holder` as getfield and getstatic push it:
+    /// booleans and chars zero-extended, bytes and shorts sign-extended,
+    /// floats and doubles as their bits.
+    pub fn get_stack_value(self, holder: ObjectPtr) -> i64 {
+        return match self.kind {
+            FieldKind::Boolean => self.load::<JBoolean>(holder) as u8 as i64,
+            FieldKind::Byte => self.load::<JByte>(holder) as i64,
+            FieldKind::Char => self.load::<JChar>(holder) as u16 as i64,
+            FieldKind::Short => self.load::<JShort>(holder) as i64,
+            FieldKind::Int => self.load::<JInt>(holder) as i64,
+            FieldKind::Float => self.load::<JFloat>(holder).to_bits() as i64,
+            FieldKind::Long => self.load::<JLong>(holder),
+            FieldKind::Double => self.load::<JDouble>(holder).to_bits() as i64,
+            FieldKind::Reference => self.load::<ObjectPtr>(holder).as_isize() as i64,
+        };
+    }
+
+    /// Writes a value popped by putfield or putstatic to the field of
+    /// `holder`, narrowed to the field type as in jvms-6.5.putfield.
+    pub fn set_stack_value(self, holder: ObjectPtr, value: i64) {
+        match self.kind {
+            FieldKind::Boolean => self.store(holder, (value & 1) as JBoolean),
+            FieldKind::Byte => self.store(holder, value as JByte),
+            FieldKind::Char => self.store(holder, value as JChar),
+            FieldKind::Short => self.store(holder, value as JShort),
+            FieldKind::Int => self.store(holder, value as JInt),
+            FieldKind::Float => self.store(holder, JFloat::from_bits(value as u32)),
+            FieldKind::Long => self.store(holder, value),
+            FieldKind::Double => self.store(holder, JDouble::from_bits(value as u64)),
+            FieldKind::Reference => self.store(holder, ObjectPtr::from_isize(value as isize)),
+        }
+    }
+
+    #[inline(always)]
+    fn load<T: FieldValue>(self, holder: ObjectPtr) -> T {
+        let value = load_value(holder, self.offset, self.is_volatile);
+        volatile_load_fence(self.is_volatile);
+        return value;
+    }
+
+    #[inline(always)]
+    fn store<T: FieldValue>(self, holder: ObjectPtr, value: T) {
+        volatile_pre_store_fence(self.is_volatile);
+        store_value(holder, self.offset, self.is_volatile, value);
+        volatile_post_store_fence(self.is_volatile);
+    }
+}
+
+// Volatile long and double accesses must not tear (jvms-17.7), 64-bit
+// fields are always 8-byte aligned by FieldLayout.
+#[inline(always)]
+fn load_value<T: FieldValue>(base: ObjectPtr, offset: u16, is_volatile: bool) -> T {
+    if size_of::<T>() == 8 && is_volatile {
+        let value = base.read_value_atomic(offset as i32);
+        return unsafe { std::mem::transmute_copy(&value) };
+    }
+    let field_ptr: Ptr<T> = base.read_value_ptr(offset as isize);
+    return unsafe { std::ptr::read_unaligned(field_ptr.as_raw_ptr()) };
+}
+
+#[inline(always)]
+fn store_value<T: FieldValue>(base: ObjectPtr, offset: u16, is_volatile: bool, value: T) {
+    if size_of::<T>() == 8 && is_volatile {
+        base.write_value_atomic(offset as i32, unsafe { std::mem::transmute_copy(&value) });
+        return;
+    }
+    let field_ptr: Ptr<T> = Ptr::from_addr(base.as_address().offset(offset as isize));
+    unsafe {
+        std::ptr::write_unaligned(field_ptr.as_mut_raw_ptr(), value);
+    }
+}
+
+// Volatile accesses follow the JSR-133 cookbook: a load is followed by
+// LoadLoad|LoadStore, a store is preceded by StoreStore|LoadStore and
+// followed by StoreLoad.
+#[inline(always)]
+fn volatile_load_fence(is_volatile: bool) {
+    if is_volatile {
+        fence(Ordering::Acquire);
+    }
+}
+
+#[inline(always)]
+fn volatile_pre_store_fence(is_volatile: bool) {
+    if is_volatile {
+        fence(Ordering::Release);
+    }
+}
+
+#[inline(always)]
+fn volatile_post_store_fence(is_volatile: bool) {
+    if is_volatile {
+        fence(Ordering::SeqCst);
+    }
+}
+
 #[allow(unused)]
 pub enum FieldAccessFlags {
     AccPublic = 0x0001,
@@ -185,6 +393,54 @@ impl FieldAccessFlags {
     pub fn is_static(access_flags: u16) -> bool {
         return access_flags & FieldAccessFlags::AccStatic as u16 != 0;
     }
+
+    pub fn is_volatile(access_flags: u16) -> bool {
+        return access_flags & FieldAccessFlags::AccVolatile as u16 != 0;
+    }
+}
+
+/// The storage of a field value, decided by the first character of the
+/// field descriptor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FieldKind {
+    Boolean,
+    Byte,
+    Char,
+    Short,
+    Int,
+    Float,
+    Long,
+    Double,
+    Reference,
+}
+
+impl FieldKind {
+    pub fn from_descriptor(first_char: u8) -> Self {
+        return match first_char {
+            b'Z' => FieldKind::Boolean,
+            b'B' => FieldKind::Byte,
+            b'C' => FieldKind::Char,
+            b'S' => FieldKind::Short,
+            b'I' => FieldKind::Int,
+            b'F' => FieldKind::Float,
+            b'J' => FieldKind::Long,
+            b'D' => FieldKind::Double,
+            _ => FieldKind::Reference,
+        };
+    }
+
+    /// Whether the value takes two operand stack slots.
+    pub fn is_wide(self) -> bool {
+        return matches!(self, FieldKind::Long | FieldKind::Double);
+    }
+}
+
+impl From<u8> for FieldKind {
+    fn from(kind: u8) -> Self {
+        debug_assert!(kind <= FieldKind::Reference as u8);
+        unsafe { std::mem::transmute(kind) }
+    }
 }
 
 pub trait FieldValue : Copy {}
@@ -1,6 +1,6 @@
-use std::mem::size_of;
+use std::{marker::PhantomData, mem::size_of};
 
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use crate::{memory::Address, object::prelude::Ptr, thread::ThreadPtr};
 
@@ -18,14 +18,27 @@ pub trait InsertNewWithKey<K, R> {
     fn new_entry_with_key(key: K, key_hash: JInt, thread: ThreadPtr) -> Ptr<R>;
 }
 
+/// An open addressing hash table of VM objects with linear probing, allocated
+/// in the permanent space. A table grows by rehashing into a new table, which
+/// is why the inserting methods return the table to use from then on.
+/// Removals shift the following entries of the probe sequence back instead of
+/// leaving tombstones, so lookups never walk over deleted slots.
+///
+/// With [`VMConfig::deterministic_tables`](crate::vm::VMConfig::deterministic_tables)
+/// a table also keeps its values in insertion order after the slots and
+/// hashes with a fixed seed, so it iterates the same way on every run.
 pub struct HashTable {
     capacity: i32,
     pub size: i32,
     hasher: TableHasher,
+    ordered: bool,
 }
 
 impl HashTable {
     const DEFAULT_SIZE: usize = 8;
+    /// The share of slots an insertion may fill before the table grows to
+    /// four times its size.
+    const MAX_LOAD_FACTOR: f32 = 0.75;
     const ENTRIES_OFFSET: usize = size_of::<HashTable>();
 
     pub fn new(thread: ThreadPtr) -> HashTablePtr {
@@ -33,21 +46,28 @@ impl HashTable {
     }
 
     pub fn new_with_init_size(init_size: i32, thread: ThreadPtr) -> HashTablePtr {
+        let ordered = thread.vm().cfg.deterministic_tables;
+        return Self::new_with_order(init_size, ordered, thread);
+    }
+
+    fn new_with_order(init_size: i32, ordered: bool, thread: ThreadPtr) -> HashTablePtr {
         let capacity = init_size / 3 * 4;
         let capacity = next_prime(capacity as u64) as i32;
         let mut table = HashTablePtr::from_addr(
             thread
                 .heap()
-                .alloc_obj_permanent(Self::object_size(capacity)),
+                .alloc_obj_permanent(Self::object_size(capacity, ordered)),
         );
         table.capacity = capacity;
         table.size = 0;
-        table.hasher = Self::get_hasher(capacity);
+        table.hasher = Self::get_hasher(capacity, ordered);
+        table.ordered = ordered;
         return table;
     }
 
-    fn object_size(capacity: i32) -> usize {
-        return Self::ENTRIES_OFFSET + size_of::<Address>() * capacity as usize;
+    fn object_size(capacity: i32, ordered: bool) -> usize {
+        let slots = if ordered { capacity * 2 } else { capacity };
+        return Self::ENTRIES_OFFSET + size_of::<Address>() * slots as usize;
     }
 
     #[must_use]
@@ -106,42 +126,18 @@ impl HashTable {
         return (table, value);
     }
 
-    pub fn foreach_entries<V, F: Fn(Ptr<V>)>(&self, it: F) {
-        if self.size == 0 {
-            return;
-        }
-        let prev_entries: Ptr<Ptr<V>> = self.entries();
-        let mut num_iter = 0;
-        for index in 0..self.capacity {
-            let entry = prev_entries.offset(index as isize);
-            if (*entry).is_not_null() {
-                it(*entry);
-                num_iter += 1;
-
-                if num_iter == self.size {
-                    break;
-                }
-            }
-        }
+    /// The values in insertion order if the table is ordered, in slot order
+    /// otherwise.
+    pub fn iter<V>(&self) -> Iter<'_, V> {
+        return Iter {
+            table: self,
+            index: 0,
+            _value: PhantomData,
+        };
     }
 
-    pub fn mut_foreach_entries<V, F: FnMut(Ptr<V>)>(&self, mut it: F) {
-        if self.size == 0 {
-            return;
-        }
-        let prev_entries: Ptr<Ptr<V>> = self.entries();
-        let mut num_iter = 0;
-        for index in 0..self.capacity {
-            let entry = prev_entries.offset(index as isize);
-            if (*entry).is_not_null() {
-                it(*entry);
-                num_iter += 1;
-
-                if num_iter == self.size {
-                    break;
-                }
-            }
-        }
+    pub fn mut_foreach_entries<V, F: FnMut(Ptr<V>)>(&self, it: F) {
+        self.iter::<V>().for_each(it);
     }
 
     #[must_use]
@@ -157,26 +153,21 @@ impl HashTable {
         // log::trace!("insert_entry self: {:x} entry: {:x}, *entry: {:x}", HashTablePtr::from_ref(self).as_usize(), entry.as_usize(), (*entry).as_usize());
         if (*entry).is_null() {
             let table = Ptr::from_ref(self);
-            if (self.size + 1) as f32 / self.capacity as f32 >= 0.75 {
-                let mut new_table = HashTable::new_with_init_size(self.size << 2, thread);
+            if (self.size + 1) as f32 / self.capacity as f32 >= Self::MAX_LOAD_FACTOR {
+                let mut new_table = HashTable::new_with_order(self.size << 2, self.ordered, thread);
 
-                let prev_entries: Ptr<Ptr<V>> = self.entries();
                 let mut prev_num_iter = 0;
-                for index in 0..self.capacity {
-                    let prev_entry = prev_entries.offset(index as isize);
-                    if (*prev_entry).is_not_null() {
-                        new_table = new_table.insert(*prev_entry, thread);
-                        prev_num_iter += 1;
-
-                        if prev_num_iter == self.size {
-                            break;
-                        }
-                    }
+                for prev_val in self.iter::<V>() {
+                    new_table = new_table.insert(prev_val, thread);
+                    prev_num_iter += 1;
                 }
                 debug_assert_eq!(prev_num_iter, self.size);
                 return new_table.insert(val, thread);
             }
             *entry = val;
+            if self.ordered {
+                *self.order::<V>().offset(self.size as isize).as_mut_ref() = val;
+            }
             self.size += 1;
 
             let mut num_it = 0;
@@ -186,6 +177,10 @@ impl HashTable {
             debug_assert_eq!(num_it, self.size);
             return table;
         } else {
+            if self.ordered {
+                let index = self.order_index(*entry);
+                *self.order::<V>().offset(index as isize).as_mut_ref() = val;
+            }
             *entry = val;
             return Ptr::from_ref(self);
         }
@@ -195,40 +190,164 @@ impl HashTable {
         Ptr::from_ref_offset_bytes(self, Self::ENTRIES_OFFSET as isize)
     }
 
+    /// The values of an ordered table in insertion order, `size` of them.
+    fn order<V>(&self) -> Ptr<Ptr<V>> {
+        debug_assert!(self.ordered);
+        self.entries::<V>().offset(self.capacity as isize)
+    }
+
+    fn order_index<V>(&self, val: Ptr<V>) -> i32 {
+        let order = self.order::<V>();
+        return (0..self.size)
+            .find(|index| *order.offset(*index as isize) == val)
+            .expect("the value is in the insertion order");
+    }
+
     fn probe<V, EqFn: Fn(Ptr<V>) -> bool>(&self, val_hash: i32, equals_fn: EqFn) -> Ptr<Ptr<V>> {
-        let origin_offset = self.hasher.hash(val_hash, self.capacity);
-        let mut offset = origin_offset;
-        let mut probe_count = 0;
+        let mut offset = self.hasher.hash(val_hash, self.capacity);
         loop {
             let entry = self.entries::<V>().offset(offset as isize);
             let entry_val = *entry;
             if entry_val.is_null() || equals_fn(entry_val) {
                 return entry;
             }
-            probe_count += 1;
-            if probe_count % 2 != 0 {
-                offset = origin_offset + (probe_count + 1) / 2 * (probe_count + 1) / 2;
-                while offset >= self.capacity {
-                    offset -= self.capacity;
-                }
-            } else {
-                offset = origin_offset - probe_count / 2 * probe_count / 2;
-                while offset < 0 {
-                    offset += self.capacity;
-                }
+            offset += 1;
+            if offset == self.capacity {
+                offset = 0;
             }
         }
     }
 
-    fn get_hasher(capacity: i32) -> TableHasher {
+    fn get_hasher(capacity: i32, ordered: bool) -> TableHasher {
         let p = next_prime(capacity as u64);
-        let mut rng = rand::thread_rng();
-        let a: u64 = rng.gen_range(1..p);
-        let b: u64 = rng.gen_range(0..p);
+        let (a, b) = if ordered {
+            let mut rng = StdRng::seed_from_u64(p);
+            (rng.gen_range(1..p), rng.gen_range(0..p))
+        } else {
+            let mut rng = rand::thread_rng();
+            (rng.gen_range(1..p), rng.gen_range(0..p))
+        };
         return TableHasher { a, b, p };
     }
 }
 
+/// The removals, for dropping the interned strings and classes that died
+/// once a collector runs, none does yet, see `gc::copying`.
+#[allow(dead_code)]
+impl HashTable {
+    /// Removes the value equal to `key`, returning it.
+    pub fn remove_by_str<K, V>(&mut self, key: K) -> Option<Ptr<V>>
+    where
+        K: Copy,
+        V: VMObject + GetEntryWithKey<K>,
+    {
+        let entry = self.probe(V::hash_key(key), |entry: Ptr<V>| {
+            V::entry_equals_key(entry.as_address(), key)
+        });
+        let value = *entry;
+        if value.is_null() {
+            return None;
+        }
+        self.remove_entry(entry);
+        return Some(value);
+    }
+
+    /// Removes the value equal to `val`, returning whether there was one.
+    pub fn remove<V>(&mut self, val: Ptr<V>) -> bool
+    where
+        V: VMObject,
+    {
+        let entry = self.probe(V::hash(val.cast()), |entry: Ptr<V>| {
+            V::equals(entry.cast(), val.cast())
+        });
+        if (*entry).is_null() {
+            return false;
+        }
+        self.remove_entry(entry);
+        return true;
+    }
+
+    /// Removes every value `keep` returns false for, e.g. the entries whose
+    /// objects died.
+    pub fn retain<V, F: FnMut(Ptr<V>) -> bool>(&mut self, mut keep: F)
+    where
+        V: VMObject,
+    {
+        let dead: Vec<Ptr<V>> = self.iter::<V>().filter(|val| !keep(*val)).collect();
+        for val in dead {
+            let entry = self.probe(V::hash(val.cast()), |entry: Ptr<V>| entry == val);
+            debug_assert!((*entry).is_not_null());
+            self.remove_entry(entry);
+        }
+    }
+
+    /// Empties `entry` and moves the values of the same cluster that probed
+    /// past it back into the gap, Knuth's algorithm R.
+    fn remove_entry<V>(&mut self, entry: Ptr<Ptr<V>>)
+    where
+        V: VMObject,
+    {
+        let entries = self.entries::<V>();
+        if self.ordered {
+            let order = self.order::<V>();
+            for index in self.order_index(*entry)..self.size - 1 {
+                *order.offset(index as isize).as_mut_ref() = *order.offset(index as isize + 1);
+            }
+        }
+        let mut hole = ((entry.as_usize() - entries.as_usize()) / size_of::<Ptr<V>>()) as i32;
+        let mut index = hole;
+        loop {
+            index = (index + 1) % self.capacity;
+            let val = *entries.offset(index as isize);
+            if val.is_null() {
+                break;
+            }
+            let home = self.hasher.hash(V::hash(val.cast()), self.capacity);
+            // the value stays if its home slot lies cyclically in (hole, index]
+            let stays = if hole <= index {
+                hole < home && home <= index
+            } else {
+                hole < home || home <= index
+            };
+            if !stays {
+                *entries.offset(hole as isize).as_mut_ref() = val;
+                hole = index;
+            }
+        }
+        *entries.offset(hole as isize).as_mut_ref() = Ptr::null();
+        self.size -= 1;
+    }
+}
+
+pub struct Iter<'a, V> {
+    table: &'a HashTable,
+    index: i32,
+    _value: PhantomData<V>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = Ptr<V>;
+
+    fn next(&mut self) -> Option<Ptr<V>> {
+        if self.table.ordered {
+            if self.index == self.table.size {
+                return None;
+            }
+            let val = *self.table.order::<V>().offset(self.index as isize);
+            self.index += 1;
+            return Some(val);
+        }
+        while self.index < self.table.capacity {
+            let val = *self.table.entries::<V>().offset(self.index as isize);
+            self.index += 1;
+            if val.is_not_null() {
+                return Some(val);
+            }
+        }
+        return None;
+    }
+}
+
 fn next_prime(mut n: u64) -> u64 {
     if n <= 2 {
         return 2;
@@ -288,3 +407,124 @@ impl TableHasher {
         return (((self.a * val as u64 + self.b) % self.p) % (capacity as u64)) as i32;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::{HashTable, HashTablePtr};
+    use crate::{
+        object::{
+            string::Utf8String,
+            symbol::{Symbol, SymbolPtr},
+        },
+        test::run_in_vm,
+        thread::Thread,
+    };
+
+    fn get(table: HashTablePtr, key: &str) -> Option<SymbolPtr> {
+        return table.get_value_by_str::<_, Symbol>(Utf8String::from(key));
+    }
+
+    fn contents(table: HashTablePtr) -> HashSet<String> {
+        return table
+            .iter::<Symbol>()
+            .map(|symbol| symbol.as_str().to_string())
+            .collect();
+    }
+
+    /// Applies random inserts and removals to a table and a `HashSet`, and
+    /// checks after each that both hold the same keys.
+    #[test]
+    fn matches_hash_set_model() {
+        run_in_vm("./tests/classes", |_| {
+            let thread = Thread::current();
+            for seed in 0..8 {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut table = HashTable::new(thread);
+                let mut model = HashSet::new();
+                for _ in 0..400 {
+                    let key = format!("key{}", rng.gen_range(0..96));
+                    if rng.gen_bool(0.6) {
+                        let (new_table, symbol) =
+                            table.get_or_insert_str::<_, Symbol>(Utf8String::from(&*key), thread);
+                        table = new_table;
+                        assert_eq!(key, symbol.as_str());
+                        model.insert(key.clone());
+                    } else {
+                        let removed = table.remove_by_str::<_, Symbol>(Utf8String::from(&*key));
+                        assert_eq!(model.remove(&key), removed.is_some());
+                    }
+                    assert_eq!(model.len(), table.size as usize);
+                    for probed in 0..96 {
+                        let probed = format!("key{}", probed);
+                        assert_eq!(model.contains(&probed), get(table, &probed).is_some());
+                    }
+                }
+                assert_eq!(model, contents(table));
+            }
+        });
+    }
+
+    #[test]
+    fn retain_removes_rejected_values() {
+        run_in_vm("./tests/classes", |_| {
+            let thread = Thread::current();
+            let mut table = HashTable::new(thread);
+            for key in 0..200 {
+                let key = key.to_string();
+                table = table
+                    .get_or_insert_str::<_, Symbol>(Utf8String::from(&*key), thread)
+                    .0;
+            }
+            table.retain::<Symbol, _>(|symbol| symbol.as_str().parse::<i32>().unwrap() % 3 == 0);
+
+            let expected: HashSet<String> = (0..200)
+                .filter(|key| key % 3 == 0)
+                .map(|key| key.to_string())
+                .collect();
+            assert_eq!(expected, contents(table));
+            assert_eq!(expected.len(), table.size as usize);
+            for key in 0..200 {
+                assert_eq!(key % 3 == 0, get(table, &key.to_string()).is_some());
+            }
+        });
+    }
+
+    #[test]
+    fn deterministic_tables_iterate_in_insertion_order() {
+        run_in_vm("./tests/classes", |vm| {
+            vm.as_mut_ref().cfg.deterministic_tables = true;
+            let thread = Thread::current();
+            let mut table = HashTable::new(thread);
+            let mut model = Vec::new();
+            // the keys in reverse, so that slot order would differ
+            for key in (0..200).rev() {
+                let key = format!("key{}", key);
+                table = table
+                    .get_or_insert_str::<_, Symbol>(Utf8String::from(&*key), thread)
+                    .0;
+                model.push(key);
+            }
+            for key in ["key7", "key150", "key0"] {
+                assert!(table
+                    .remove_by_str::<_, Symbol>(Utf8String::from(key))
+                    .is_some());
+                model.retain(|model_key| model_key != key);
+            }
+            table = table
+                .get_or_insert_str::<_, Symbol>(Utf8String::from("key7"), thread)
+                .0;
+            model.push("key7".to_string());
+
+            let order: Vec<String> = table
+                .iter::<Symbol>()
+                .map(|symbol| symbol.as_str().to_string())
+                .collect();
+            assert_eq!(model, order);
+            assert_eq!(model.len(), table.size as usize);
+        });
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use parking_lot::Mutex;
+
+use crate::os;
+
+const CHUNK_BITS: u32 = 12;
+const CHUNK_LEN: usize = 1 << CHUNK_BITS;
+const MAX_CHUNKS: usize = 1 << 12;
+
+/// Maps 4-byte ids to the values registered in a VM, so that vtables and
+/// fields hold ids instead of pointers. The values are kept in chunks
+/// allocated as the table grows; a chunk never moves, so looking a value up
+/// takes no lock.
+pub(crate) struct IdTable<T: Copy + Default> {
+    chunks: Box<[AtomicPtr<T>]>,
+    len: Mutex<usize>,
+}
+
+impl<T: Copy + Default> Default for IdTable<T> {
+    fn default() -> Self {
+        return Self {
+            chunks: (0..MAX_CHUNKS).map(|_| AtomicPtr::default()).collect(),
+            len: Mutex::new(0),
+        };
+    }
+}
+
+impl<T: Copy + Default> IdTable<T> {
+    /// Assigns the next id to `value`.
+    pub fn register(&self, value: T) -> u32 {
+        let mut len = self.len.lock();
+        let index = *len;
+        let chunk_index = index >> CHUNK_BITS;
+        if chunk_index == MAX_CHUNKS {
+            os::fatal_error("the id table is full\n");
+        }
+        let mut chunk = self.chunks[chunk_index].load(Ordering::Relaxed);
+        if chunk.is_null() {
+            let values = vec![T::default(); CHUNK_LEN].into_boxed_slice();
+            chunk = Box::into_raw(values) as *mut T;
+            self.chunks[chunk_index].store(chunk, Ordering::Release);
+        }
+        unsafe {
+            *chunk.add(index & (CHUNK_LEN - 1)) = value;
+        }
+        *len = index + 1;
+        return index as u32;
+    }
+
+    #[inline(always)]
+    pub fn get(&self, id: u32) -> T {
+        let index = id as usize;
+        let chunk = self.chunks[index >> CHUNK_BITS].load(Ordering::Acquire);
+        debug_assert!(!chunk.is_null());
+        return unsafe { *chunk.add(index & (CHUNK_LEN - 1)) };
+    }
+}
+
+impl<T: Copy + Default> Drop for IdTable<T> {
+    fn drop(&mut self) {
+        for chunk in self.chunks.iter() {
+            let chunk = chunk.load(Ordering::Relaxed);
+            if chunk.is_null() {
+                break;
+            }
+            unsafe {
+                drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                    chunk, CHUNK_LEN,
+                )));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdTable, CHUNK_LEN};
+    use crate::object::method::MethodPtr;
+
+    #[test]
+    fn ids_across_chunks() {
+        let table = IdTable::default();
+        let methods: Vec<MethodPtr> = (1..=CHUNK_LEN * 2 + 1)
+            .map(|addr| MethodPtr::from_raw((addr * 8) as *const _))
+            .collect();
+        let ids: Vec<_> = methods
+            .iter()
+            .map(|&method| table.register(method))
+            .collect();
+        for (index, (&method, &id)) in methods.iter().zip(&ids).enumerate() {
+            assert_eq!(index as u32, id);
+            assert_eq!(method, table.get(id));
+        }
+    }
+}
@@ -1,30 +1,51 @@
 use std::mem::size_of;
 
 use crate::{
+    classfile::{
+        descriptor::{Descriptor, DescriptorParser},
+        ClassLoadErr,
+    },
     define_oop,
     memory::{align, Address},
     thread::ThreadPtr,
+    vm::VM,
 };
 
-use super::{array::JArrayPtr, class::JClassPtr, prelude::JInt, ptr::Ptr, symbol::SymbolPtr};
+use super::{
+    array::JArrayPtr, class::JClassPtr, constant_pool::ConstantPoolPtr, field::FieldKind,
+    method_table::MethodId, prelude::JInt, ptr::Ptr, symbol::SymbolPtr,
+};
 
 pub type MethodIndex = JInt;
 pub type MethodPtr = Ptr<Method>;
 pub type ExceptionTablePtr = Ptr<ExceptionTable>;
+pub type LineNumberPtr = Ptr<LineNumber>;
 
 define_oop!(
     struct Method {
         decl_cls: JClassPtr,
+        cp: ConstantPoolPtr,
         name: SymbolPtr,
         descriptor: SymbolPtr,
-        params: JArrayPtr,
-        ret_type: JClassPtr,
+        // the field descriptor of each parameter, e.g. `I` or
+        // `Ljava/lang/String;`
+        param_descriptors: JArrayPtr,
+        // the class of each parameter, null until the method is linked
+        param_types: JArrayPtr,
+        // the field descriptor of the return type, or `V`
         ret_descriptor: SymbolPtr,
+        // the class of the return type, null until the method is linked
+        ret_type: JClassPtr,
+        id: MethodId,
         access_flags: u16,
         max_stack: u16,
         max_locals: u16,
         code_length: u16,
         ex_tab_length: u16,
+        line_tab_length: u16,
+        caller_sensitive: bool,
+        registered_native: bool,
+        replaced_with_native: bool,
         native_fn: Address,
     }
 );
@@ -33,34 +54,40 @@ impl Method {
     // pub const METHOD_CLASS: ClassPtr = ClassPtr::new(ObjectTag::Method as u8 as Address);
 
     pub fn new(
+        cp: ConstantPoolPtr,
         access_flags: u16,
         name: SymbolPtr,
         descriptor: SymbolPtr,
-        params: JArrayPtr,
-        ret_type: JClassPtr,
+        param_descriptors: JArrayPtr,
         ret_descriptor: SymbolPtr,
         max_stack: u16,
         max_locals: u16,
         code_length: u16,
         code: *const u8,
         ex_tab: &Vec<ExceptionTable>,
+        line_tab: &Vec<LineNumber>,
         thread: ThreadPtr,
     ) -> MethodPtr {
-        let mut method = MethodPtr::from_addr(
-            thread
-                .vm()
-                .heap()
-                .alloc_obj_permanent(Self::size(code_length, ex_tab.len() as u16)),
-        );
+        let mut method = MethodPtr::from_addr(thread.vm().heap().alloc_obj_permanent(Self::size(
+            code_length,
+            ex_tab.len() as u16,
+            line_tab.len() as u16,
+        )));
+        method.cp = cp;
         method.access_flags = access_flags;
         method.name = name;
         method.descriptor = descriptor;
-        method.params = params;
-        method.ret_type = ret_type;
+        method.param_descriptors = param_descriptors;
+        method.param_types = JArrayPtr::null();
         method.ret_descriptor = ret_descriptor;
+        method.ret_type = JClassPtr::null();
         method.max_stack = max_stack;
         method.max_locals = max_locals;
         method.code_length = code_length;
+        method.caller_sensitive = false;
+        method.registered_native = false;
+        method.replaced_with_native = false;
+        method.id = thread.vm().method_table.register(method);
         let method_code = method.code() as *mut u8;
         unsafe {
             std::ptr::copy(code, method_code, code_length as usize);
@@ -74,9 +101,23 @@ impl Method {
                 method.ex_tab_length as usize,
             );
         }
+        method.line_tab_length = line_tab.len() as u16;
+        let method_line_tab = method.line_tab();
+        unsafe {
+            std::ptr::copy(
+                line_tab.as_ptr(),
+                method_line_tab.as_mut_raw_ptr(),
+                method.line_tab_length as usize,
+            );
+        }
         return method;
     }
 
+    /// The id of the method in the `MethodTable` of the VM.
+    pub fn id(&self) -> MethodId {
+        self.id
+    }
+
     pub fn decl_cls(&self) -> JClassPtr {
         debug_assert!(self.decl_cls.is_not_null());
         return self.decl_cls;
@@ -93,6 +134,13 @@ impl Method {
         return None;
     }
 
+    /// The constant pool the code refers to. Redefining the class gives the
+    /// new methods a new pool, while running frames of the old methods keep
+    /// the old one.
+    pub fn cp(&self) -> ConstantPoolPtr {
+        self.cp
+    }
+
     pub fn name(&self) -> SymbolPtr {
         self.name
     }
@@ -101,8 +149,70 @@ impl Method {
         self.descriptor
     }
 
-    pub fn params(&self) -> JArrayPtr {
-        self.params
+    pub fn param_count(&self) -> JInt {
+        self.param_descriptors.length()
+    }
+
+    /// The field descriptors of the parameters, symbols.
+    pub fn param_descriptors(&self) -> JArrayPtr {
+        self.param_descriptors
+    }
+
+    /// How the argument `idx` is passed, told by its descriptor, so that it
+    /// is known before the parameter classes are loaded.
+    #[inline(always)]
+    pub fn param_kind(&self, idx: JInt) -> FieldKind {
+        let descriptor: SymbolPtr = self.param_descriptors.get(idx).cast();
+        return FieldKind::from_descriptor(descriptor.as_bytes()[0]);
+    }
+
+    /// The slots the arguments take in the frame, two for each long and
+    /// double and one for any other type, without the receiver.
+    pub fn args_slots(&self) -> isize {
+        let mut args_slots = 0;
+        for param_idx in 0..self.param_count() {
+            args_slots += if self.param_kind(param_idx).is_wide() {
+                2
+            } else {
+                1
+            };
+        }
+        return args_slots;
+    }
+
+    /// The classes of the parameters, see
+    /// [`ClassData::link_method`](super::class::ClassData::link_method).
+    pub fn param_types(&self) -> JArrayPtr {
+        debug_assert!(
+            self.param_types.is_not_null(),
+            "{} is not linked",
+            self.name.as_str()
+        );
+        self.param_types
+    }
+
+    pub(crate) fn set_param_types(&mut self, param_types: JArrayPtr) {
+        debug_assert_eq!(self.param_count(), param_types.length());
+        self.param_types = param_types;
+    }
+
+    /// The class of the parameter `idx`, loaded here if the method is not
+    /// linked, e.g. one of an interface.
+    pub fn param_type(&self, idx: JInt, vm: &VM) -> Result<JClassPtr, ClassLoadErr> {
+        if self.param_types.is_not_null() {
+            return Ok(self.param_types.get(idx).cast());
+        }
+        return Self::type_of(self.param_descriptors.get(idx).cast(), vm);
+    }
+
+    fn type_of(descriptor: SymbolPtr, vm: &VM) -> Result<JClassPtr, ClassLoadErr> {
+        return match DescriptorParser::from_symbol(descriptor, vm).next() {
+            Descriptor::ResolvedClass(class, _) => Ok(class),
+            Descriptor::Symbol(class_name, _) => {
+                vm.bootstrap_class_loader.load_class_with_symbol(class_name)
+            }
+            _ => unreachable!("the method descriptors are checked by the parser"),
+        };
     }
 
     pub fn access_flags(&self) -> u16 {
@@ -132,16 +242,57 @@ impl Method {
         return self.access_flags & (MethodAccessFlags::AccStatic as u16) != 0;
     }
 
+    pub fn is_synchronized(&self) -> bool {
+        return self.access_flags & (MethodAccessFlags::AccSynchronized as u16) != 0;
+    }
+
+    /// Whether the method is declared native or runs a builtin native in
+    /// place of its code.
     pub fn is_native(&self) -> bool {
-        return self.access_flags & (MethodAccessFlags::AccNative as u16) != 0;
+        return self.replaced_with_native
+            || self.access_flags & (MethodAccessFlags::AccNative as u16) != 0;
     }
 
     pub fn is_not_native(&self) -> bool {
-        return self.access_flags & (MethodAccessFlags::AccNative as u16) == 0;
+        return !self.is_native();
+    }
+
+    /// Whether the method is annotated with `@sun.reflect.CallerSensitive`,
+    /// which lets it call `Reflection.getCallerClass()`.
+    pub fn is_caller_sensitive(&self) -> bool {
+        return self.caller_sensitive;
     }
 
-    pub fn ret_type(&self) -> JClassPtr {
-        self.ret_type
+    pub fn set_caller_sensitive(&mut self, caller_sensitive: bool) {
+        self.caller_sensitive = caller_sensitive;
+    }
+
+    /// The field descriptor of the return type, or `V`.
+    pub fn ret_descriptor(&self) -> SymbolPtr {
+        self.ret_descriptor
+    }
+
+    /// How the result is returned, told by its descriptor, or `None` if the
+    /// method returns void.
+    #[inline(always)]
+    pub fn ret_kind(&self) -> Option<FieldKind> {
+        return match self.ret_descriptor.as_bytes()[0] {
+            b'V' => None,
+            first_char => Some(FieldKind::from_descriptor(first_char)),
+        };
+    }
+
+    pub(crate) fn set_ret_type(&mut self, ret_type: JClassPtr) {
+        self.ret_type = ret_type;
+    }
+
+    /// The class of the return type, loaded here if the method is not
+    /// linked.
+    pub fn ret_type(&self, vm: &VM) -> Result<JClassPtr, ClassLoadErr> {
+        if self.ret_type.is_not_null() {
+            return Ok(self.ret_type);
+        }
+        return Self::type_of(self.ret_descriptor, vm);
     }
 
     pub fn max_stack(&self) -> u16 {
@@ -170,12 +321,43 @@ impl Method {
             .raw_ptr();
     }
 
+    pub fn ex_tab_length(&self) -> u16 {
+        self.ex_tab_length
+    }
+
     pub fn ex_tab(&self) -> ExceptionTablePtr {
         return ExceptionTablePtr::from_addr(
             Address::from_ref(self).offset(Self::ex_tab_offset(self.code_length)),
         );
     }
 
+    pub fn line_tab_length(&self) -> u16 {
+        self.line_tab_length
+    }
+
+    /// The entries of the `LineNumberTable` attributes of the code.
+    pub fn line_tab(&self) -> LineNumberPtr {
+        return LineNumberPtr::from_addr(
+            Address::from_ref(self)
+                .offset(Self::line_tab_offset(self.code_length, self.ex_tab_length)),
+        );
+    }
+
+    /// The source line of the instruction at `bci`, which is that of the
+    /// entry starting closest before it. The entries may come in any order.
+    pub fn line_number(&self, bci: u16) -> Option<u16> {
+        let line_tab = self.line_tab();
+        let mut result: Option<&LineNumber> = None;
+        for index in 0..self.line_tab_length as usize {
+            let entry = unsafe { &*line_tab.as_raw_ptr().add(index) };
+            if entry.start_pc <= bci && result.map_or(true, |found| found.start_pc < entry.start_pc)
+            {
+                result = Some(entry);
+            }
+        }
+        return result.map(|entry| entry.line_number);
+    }
+
     pub fn native_fn(&self) -> Address {
         self.native_fn
     }
@@ -184,15 +366,47 @@ impl Method {
         self.native_fn = native_fn;
     }
 
-    const fn size(code_length: u16, ex_tab_length: u16) -> usize {
-        return (Self::ex_tab_offset(code_length)
-            + size_of::<ExceptionTable>() as isize * ex_tab_length as isize)
-            as usize;
+    /// Whether the native was bound with `RegisterNatives`, which takes
+    /// precedence over library symbols and builtin natives.
+    pub(crate) fn is_registered_native(&self) -> bool {
+        return self.registered_native;
+    }
+
+    /// Binds the native to `native_fn` until `UnregisterNatives`.
+    pub(crate) fn register_native(&mut self, native_fn: Address) {
+        self.registered_native = true;
+        self.native_fn = native_fn;
+    }
+
+    /// Unbinds a registered native, leaving the method to be bound again.
+    pub(crate) fn unregister_native(&mut self) {
+        self.registered_native = false;
+        self.native_fn = Address::null();
+    }
+
+    /// Turns the method into a native one running `native_fn`, for Java
+    /// methods the VM implements itself. Its access flags stay those of the
+    /// class file, which reflection reports.
+    pub(crate) fn replace_with_native(&mut self, native_fn: Address) {
+        self.replaced_with_native = true;
+        self.native_fn = native_fn;
+    }
+
+    const fn size(code_length: u16, ex_tab_length: u16, line_tab_length: u16) -> usize {
+        return align(
+            (Self::line_tab_offset(code_length, ex_tab_length)
+                + size_of::<LineNumber>() as isize * line_tab_length as isize) as usize,
+        );
     }
 
     const fn ex_tab_offset(code_length: u16) -> isize {
         return align(size_of::<Self>() + code_length as usize * size_of::<u8>()) as isize;
     }
+
+    const fn line_tab_offset(code_length: u16, ex_tab_length: u16) -> isize {
+        return Self::ex_tab_offset(code_length)
+            + size_of::<ExceptionTable>() as isize * ex_tab_length as isize;
+    }
 }
 
 pub struct ExceptionTable {
@@ -213,6 +427,22 @@ impl ExceptionTable {
     }
 }
 
+/// An entry of a `LineNumberTable` attribute, the code from `start_pc` on
+/// comes from the source line `line_number`.
+pub struct LineNumber {
+    pub(crate) start_pc: u16,
+    pub(crate) line_number: u16,
+}
+
+impl LineNumber {
+    pub fn new(start_pc: u16, line_number: u16) -> Self {
+        return Self {
+            start_pc,
+            line_number,
+        };
+    }
+}
+
 pub enum MethodAccessFlags {
     AccPublic = 0x0001,
     AccPrivate = 0x0002,
@@ -0,0 +1,37 @@
+use super::{id_table::IdTable, method::MethodPtr};
+
+/// The index of a method in the [`MethodTable`] of its VM. Methods live in
+/// the permanent space until the VM is destroyed, so an id is never reused.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct MethodId(u32);
+
+impl MethodId {
+    pub const fn as_u32(self) -> u32 {
+        return self.0;
+    }
+
+    /// The id `as_u32` gave, of a method of the same VM.
+    pub(crate) const fn from_u32(id: u32) -> Self {
+        return Self(id);
+    }
+}
+
+/// Maps the ids of the methods of a VM to the methods, so that vtables hold
+/// 4-byte ids instead of pointers.
+#[derive(Default)]
+pub(crate) struct MethodTable {
+    methods: IdTable<MethodPtr>,
+}
+
+impl MethodTable {
+    /// Assigns the next id to `method`.
+    pub fn register(&self, method: MethodPtr) -> MethodId {
+        return MethodId(self.methods.register(method));
+    }
+
+    #[inline(always)]
+    pub fn get(&self, id: MethodId) -> MethodPtr {
+        return self.methods.get(id.0);
+    }
+}
@@ -3,7 +3,9 @@ pub mod class;
 pub mod constant_pool;
 pub mod field;
 pub mod hash_table;
+mod id_table;
 pub mod method;
+pub mod method_table;
 pub mod prelude;
 mod ptr;
 pub mod string;
@@ -12,13 +14,19 @@ pub mod symbol;
 use field::FieldValue;
 
 use self::{
-    array::JArrayPtr,
-    class::JClassPtr,
+    array::{JArray, JArrayPtr},
+    class::{JClass, JClassPtr},
     prelude::{JInt, ObjectPtr, Ptr},
 };
-use crate::{handle::Handle, memory::heap::Heap, thread::ThreadPtr, vm::VMPtr};
+use crate::{
+    compat,
+    handle::Handle,
+    memory::{heap::Heap, is_align_of},
+    thread::ThreadPtr,
+    vm::VMPtr,
+};
 
-use std::{fmt::Debug, hash::Hasher, mem::size_of};
+use std::{fmt::Debug, hash::Hasher, mem::size_of, sync::atomic::Ordering};
 
 #[macro_export]
 macro_rules! define_oop {
@@ -49,7 +57,9 @@ pub trait VMObject {
     fn equals(obj: ObjectPtr, other: ObjectPtr) -> bool;
 }
 
+// the class pointer is the first word, `Heap::iterate_objects` relies on it
 #[derive(Debug)]
+#[repr(C)]
 struct Header {
     jclass: JClassPtr,
     word: MultiUseWord,
@@ -73,7 +83,7 @@ impl Object {
         debug_assert!(jclass.is_initialized());
         let inst_or_ele_size = jclass.class_data().inst_or_ele_size();
         let size = Self::FIELDS_OFFSET + inst_or_ele_size;
-        let obj = ObjectPtr::from_addr(Heap::alloc_obj_lab(size, thread));
+        let obj = ObjectPtr::from_addr(Heap::alloc_obj_lab(jclass, size, thread));
         Object::init_header(obj, jclass);
         return obj;
     }
@@ -82,7 +92,7 @@ impl Object {
         debug_assert!(jclass.is_initialized());
         let inst_or_ele_size = jclass.class_data().inst_or_ele_size();
         let size = Self::FIELDS_OFFSET + inst_or_ele_size;
-        let obj = ObjectPtr::from_addr(Heap::alloc_obj_lab(size, thread));
+        let obj = ObjectPtr::from_addr(Heap::alloc_obj_lab(jclass, size, thread));
         Object::init_header_with_hash(obj, jclass, hash);
         return obj;
     }
@@ -109,6 +119,18 @@ impl Object {
         self.header.jclass
     }
 
+    /// The number of bytes the object occupies on the heap.
+    pub(crate) fn heap_size(&self) -> usize {
+        let class_data = self.jclass().class_data();
+        if class_data.is_array() {
+            return JArray::size(
+                self.as_ref_array().length(),
+                JClass::ref_size(class_data.component_type()),
+            );
+        }
+        return Self::FIELDS_OFFSET + class_data.inst_or_ele_size();
+    }
+
     pub(crate) fn init_header(obj: ObjectPtr, cls: JClassPtr) {
         Self::init_header_with_hash(obj, cls, Self::generate_hash(obj));
     }
@@ -149,6 +171,22 @@ impl Object {
         unreachable!();
     }
 
+    /// Single-copy atomic read of a 64-bit value, see jvms-17.7.
+    pub fn read_value_atomic(&self, offset: i32) -> i64 {
+        let dst: Ptr<i64> = Ptr::from_ref_offset_bytes(self, offset as isize);
+        debug_assert!(is_align_of(dst.as_usize(), size_of::<i64>()));
+        return unsafe { compat::atomic_i64_from_ptr(dst.as_mut_raw_ptr()) }
+            .load(Ordering::Relaxed);
+    }
+
+    /// Single-copy atomic write of a 64-bit value, see jvms-17.7.
+    pub fn write_value_atomic(&self, offset: i32, value: i64) {
+        let dst: Ptr<i64> = Ptr::from_ref_offset_bytes(self, offset as isize);
+        debug_assert!(is_align_of(dst.as_usize(), size_of::<i64>()));
+        unsafe { compat::atomic_i64_from_ptr(dst.as_mut_raw_ptr()) }
+            .store(value, Ordering::Relaxed);
+    }
+
     pub fn read_value_ptr<T: FieldValue>(&self, offset: isize) -> Ptr<T> {
         let dst: Ptr<Self> = Ptr::from_ref_offset_bytes(self, offset);
         debug_assert!(std::mem::size_of::<T>() <= 8);
@@ -70,9 +70,12 @@ impl HeapString {
         return vm_str;
     }
 
+    /// Hashes the UTF-16 code units of `content`, so that it agrees with
+    /// [`hash_utf16_ptr`](Self::hash_utf16_ptr) for the same text, also for
+    /// characters outside the BMP.
     pub fn hash_utf8(content: &str) -> JInt {
         let mut hash: JInt = 0;
-        for ch in content.chars() {
+        for ch in content.encode_utf16() {
             hash = hash ^ ch as JInt;
             hash = hash * 0x01000193;
         }
@@ -93,10 +96,35 @@ impl HeapString {
         return Self::hash_utf16_ptr(Ptr::from_raw(utf16_str.as_ptr()), utf16_str.len() as JInt);
     }
 
+    /// The length of the content in UTF-8 bytes, which is how it is stored.
     pub fn length(&self) -> JInt {
         self.length
     }
 
+    /// The length of the content in UTF-16 code units, i.e. the `length()` of
+    /// the `java.lang.String` with the same text.
+    pub fn utf16_length(&self) -> JInt {
+        return self.as_str().encode_utf16().count() as JInt;
+    }
+
+    /// The length of the content in modified UTF-8 bytes, as written to a
+    /// `CONSTANT_Utf8` entry. NUL takes two bytes and a character outside the
+    /// BMP six, one surrogate pair.
+    pub fn modified_utf8_length(&self) -> JInt {
+        let mut length = 0;
+        for ch in self.as_str().chars() {
+            length += match ch as u32 {
+                0 => 2,
+                0x10000.. => 6,
+                _ => ch.len_utf8(),
+            };
+        }
+        return length as JInt;
+    }
+
+    /// A hash of the UTF-16 code units of the content. It does not depend on
+    /// the run or the address of the string, and equals the hash the string
+    /// table keeps for the interned `java.lang.String` with the same text.
     pub fn hash_code(&self) -> JInt {
         self.hash
     }
@@ -122,18 +150,21 @@ impl HeapString {
         return self.as_str().encode_utf16().collect();
     }
 
+    /// Compares the content with `ref_str` byte by byte.
     pub fn equals_utf8(&self, ref_str: Utf8String) -> bool {
         return self.as_str() == ref_str.value;
     }
 
+    /// Compares the content with the `target_len` UTF-16 code units at
+    /// `target`, e.g. the chars of a `java.lang.String`.
     pub fn equals_utf16_ptr(&self, target: Ptr<i16>, target_len: JInt) -> bool {
         let target = target.as_slice(target_len as usize);
         let mut src_idx = 0;
-        for ch in self.as_str().chars() {
+        for ch in self.as_str().encode_utf16() {
             if src_idx == target_len {
                 return false;
             }
-            if target[src_idx as usize] as JInt != ch as JInt {
+            if target[src_idx as usize] as u16 != ch {
                 return false;
             }
             src_idx += 1;
@@ -1,4 +1,8 @@
-use std::sync::{Mutex, RwLock};
+use std::{
+    collections::HashMap,
+    str::Utf8Error,
+    sync::{Mutex, RwLock},
+};
 
 use crate::{
     handle::{Handle, HandleScope},
@@ -10,28 +14,56 @@ use crate::{
 use super::{
     array::JCharArrayPtr,
     hash_table::{GetEntryWithKey, HashTable, HashTablePtr, InsertNewWithKey},
+    id_table::IdTable,
     prelude::JInt,
     ptr::Ptr,
     string::{HeapString, JString, JStringPtr, Utf8String, Utf16String},
     Object,
 };
 
+/// An interned name, descriptor or string constant of the VM. Each content is
+/// interned once by [`SymbolTable`], so symbols compare by address. The
+/// content is kept as UTF-8, see [`HeapString::length`],
+/// [`HeapString::utf16_length`] and [`HeapString::modified_utf8_length`] for
+/// its length in each encoding.
 pub type Symbol = HeapString;
 
 pub type SymbolPtr = Ptr<Symbol>;
 
+/// The 4-byte id a [`SymbolTable`] gave a symbol, see [`SymbolTable::id`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(transparent)]
+pub struct SymbolId(u32);
+
 #[derive(Default)]
 pub(crate) struct SymbolTable {
     table: RwLock<HashTablePtr>,
+    symbols: IdTable<SymbolPtr>,
+    ids: Mutex<HashMap<usize, SymbolId>>,
 }
 
 impl SymbolTable {
     pub fn new(thread: ThreadPtr) -> Self {
         Self {
             table: RwLock::new(HashTable::new(thread)),
+            ..Default::default()
         }
     }
 
+    /// The id of `symbol`, assigned on the first call. Symbols are never
+    /// freed, so the id stays valid until the VM is destroyed.
+    pub fn id(&self, symbol: SymbolPtr) -> SymbolId {
+        let mut ids = self.ids.lock().expect("SymbolTable locked failed");
+        return *ids
+            .entry(symbol.as_usize())
+            .or_insert_with(|| SymbolId(self.symbols.register(symbol)));
+    }
+
+    #[inline(always)]
+    pub fn symbol(&self, id: SymbolId) -> SymbolPtr {
+        return self.symbols.get(id.0);
+    }
+
     pub fn get_or_insert(&self, content: &str) -> SymbolPtr {
         let mut locked_table = self.table.write().expect("SymbolTable locked failed");
         let (table, symbol) =
@@ -40,12 +72,57 @@ impl SymbolTable {
         return symbol;
     }
 
+    /// Interns the content of a `CONSTANT_Utf8` entry or another byte string
+    /// taken from a class file. `bytes` may be plain UTF-8, which is interned
+    /// without copying, or modified UTF-8, whose encoded NULs and surrogate
+    /// pairs are converted to UTF-8 first.
+    pub fn intern_bytes(&self, bytes: &[u8]) -> Result<SymbolPtr, Utf8Error> {
+        return match std::str::from_utf8(bytes) {
+            Ok(content) => Ok(self.get_or_insert(content)),
+            Err(e) => match decode_modified_utf8(bytes) {
+                Some(content) => Ok(self.get_or_insert(&content)),
+                None => Err(e),
+            },
+        };
+    }
+
     pub fn get_with_jstr(&self, jstr: JStringPtr) -> SymbolPtr {
         let locked_table = self.table.write().expect("SymbolTable locked failed");
         return locked_table.get_value_by_str_unchecked(jstr);
     }
 }
 
+/// Decodes modified UTF-8 (JVMS 4.4.7). Returns `None` for malformed bytes and
+/// for unpaired surrogates, which have no UTF-8 form.
+pub(crate) fn decode_modified_utf8(bytes: &[u8]) -> Option<String> {
+    let mut units: Vec<u16> = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let x = bytes[idx] as u16;
+        let continuation = |offset: usize| -> Option<u16> {
+            let byte = *bytes.get(idx + offset)?;
+            return if byte & 0xC0 == 0x80 {
+                Some((byte & 0x3F) as u16)
+            } else {
+                None
+            };
+        };
+        if x & 0x80 == 0 && x != 0 {
+            units.push(x);
+            idx += 1;
+        } else if x & 0xE0 == 0xC0 {
+            units.push(((x & 0x1F) << 6) | continuation(1)?);
+            idx += 2;
+        } else if x & 0xF0 == 0xE0 {
+            units.push(((x & 0x0F) << 12) | (continuation(1)? << 6) | continuation(2)?);
+            idx += 3;
+        } else {
+            return None;
+        }
+    }
+    return String::from_utf16(&units).ok();
+}
+
 impl<'a> GetEntryWithKey<Utf8String<'a>> for Symbol {
     fn hash_key(ref_str: Utf8String) -> JInt {
         return Symbol::hash_utf8(ref_str.value);
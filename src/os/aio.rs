@@ -0,0 +1,831 @@
+//! Non-blocking sockets and pipes, and the readiness pollers the selectors of
+//! `sun.nio.ch` wait on: epoll on Linux and kqueue on macOS.
+//!
+//! Descriptors are the `fd` of a `java.io.FileDescriptor`. Sockets are IPv4
+//! only. Where a call has no implementation it fails with
+//! [`io::ErrorKind::Unsupported`].
+//!
+//! The blocking sockets of `java.net` share the sockets, their options by
+//! [`SOCKET_OPTIONS`] and the timeouts of [`wait_ready`].
+
+use std::{io, net::SocketAddrV4};
+
+use crate::memory::Address;
+
+/// A socket, pipe or poller descriptor.
+pub type RawFd = i32;
+
+/// The ids of `java.net.SocketOptions`.
+pub mod socket_options {
+    pub const TCP_NODELAY: i32 = 0x0001;
+    pub const IP_TOS: i32 = 0x0003;
+    pub const SO_REUSEADDR: i32 = 0x0004;
+    pub const SO_KEEPALIVE: i32 = 0x0008;
+    pub const SO_BINDADDR: i32 = 0x000f;
+    pub const SO_LINGER: i32 = 0x0080;
+    pub const SO_SNDBUF: i32 = 0x1001;
+    pub const SO_RCVBUF: i32 = 0x1002;
+    pub const SO_OOBINLINE: i32 = 0x1003;
+    pub const SO_TIMEOUT: i32 = 0x1006;
+}
+
+/// The level and name of the socket option each id of [`socket_options`]
+/// maps to. `SO_BINDADDR` is the local address and `SO_TIMEOUT` the timeout
+/// of blocking calls, so neither is a socket option.
+#[cfg(target_family = "unix")]
+pub const SOCKET_OPTIONS: &[(i32, i32, i32)] = &[
+    (
+        socket_options::TCP_NODELAY,
+        libc::IPPROTO_TCP,
+        libc::TCP_NODELAY,
+    ),
+    (socket_options::IP_TOS, libc::IPPROTO_IP, libc::IP_TOS),
+    (
+        socket_options::SO_REUSEADDR,
+        libc::SOL_SOCKET,
+        libc::SO_REUSEADDR,
+    ),
+    (
+        socket_options::SO_KEEPALIVE,
+        libc::SOL_SOCKET,
+        libc::SO_KEEPALIVE,
+    ),
+    (socket_options::SO_LINGER, libc::SOL_SOCKET, libc::SO_LINGER),
+    (socket_options::SO_SNDBUF, libc::SOL_SOCKET, libc::SO_SNDBUF),
+    (socket_options::SO_RCVBUF, libc::SOL_SOCKET, libc::SO_RCVBUF),
+    (
+        socket_options::SO_OOBINLINE,
+        libc::SOL_SOCKET,
+        libc::SO_OOBINLINE,
+    ),
+];
+#[cfg(not(target_family = "unix"))]
+pub const SOCKET_OPTIONS: &[(i32, i32, i32)] = &[];
+
+/// The level and name of the socket option `id` of [`socket_options`].
+pub fn socket_option(id: i32) -> Option<(i32, i32)> {
+    return SOCKET_OPTIONS
+        .iter()
+        .find(|(option, _, _)| *option == id)
+        .map(|(_, level, name)| (*level, *name));
+}
+
+/// Creates a pipe, returning its read and write ends.
+pub fn pipe() -> io::Result<[RawFd; 2]> {
+    #[cfg(target_family = "unix")]
+    {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(fds);
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        return Err(unsupported());
+    }
+}
+
+pub fn set_blocking(fd: RawFd, blocking: bool) -> io::Result<()> {
+    #[cfg(target_family = "unix")]
+    {
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let new_flags = if blocking {
+            flags & !libc::O_NONBLOCK
+        } else {
+            flags | libc::O_NONBLOCK
+        };
+        if new_flags != flags && unsafe { libc::fcntl(fd, libc::F_SETFL, new_flags) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (fd, blocking);
+        return Err(unsupported());
+    }
+}
+
+/// Writes one byte to the write end of a pipe, waking up a poller waiting
+/// for its read end.
+pub fn wake_up(fd: RawFd) -> io::Result<()> {
+    #[cfg(target_family = "unix")]
+    {
+        let byte = 1u8;
+        if unsafe { libc::write(fd, &byte as *const u8 as _, 1) } != 1 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = fd;
+        return Err(unsupported());
+    }
+}
+
+/// Reads everything available from the non-blocking read end of a pipe,
+/// returning whether there was anything.
+pub fn drain(fd: RawFd) -> io::Result<bool> {
+    #[cfg(target_family = "unix")]
+    {
+        let mut buf = [0u8; 128];
+        let mut drained = false;
+        loop {
+            let read = unsafe { libc::read(fd, buf.as_mut_ptr() as _, buf.len()) };
+            if read < 0 {
+                let err = io::Error::last_os_error();
+                return match err.kind() {
+                    io::ErrorKind::WouldBlock => Ok(drained),
+                    io::ErrorKind::Interrupted => continue,
+                    _ => Err(err),
+                };
+            }
+            drained |= read > 0;
+            if read as usize != buf.len() {
+                return Ok(drained);
+            }
+        }
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = fd;
+        return Err(unsupported());
+    }
+}
+
+pub fn close(fd: RawFd) -> io::Result<()> {
+    #[cfg(target_family = "unix")]
+    {
+        if unsafe { libc::close(fd) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = fd;
+        return Err(unsupported());
+    }
+}
+
+/// The number of descriptors the process may open.
+pub fn fd_limit() -> io::Result<usize> {
+    #[cfg(target_family = "unix")]
+    {
+        let mut limit: libc::rlimit = unsafe { std::mem::zeroed() };
+        if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(if limit.rlim_cur == libc::RLIM_INFINITY {
+            i32::MAX as usize
+        } else {
+            limit.rlim_cur as usize
+        });
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        return Err(unsupported());
+    }
+}
+
+/// The size of an `epoll_event` and the offset of its data.
+pub fn epoll_event_layout() -> io::Result<(usize, usize)> {
+    #[cfg(target_os = "linux")]
+    {
+        let event = std::mem::MaybeUninit::<libc::epoll_event>::uninit();
+        let base = event.as_ptr();
+        let data = unsafe { std::ptr::addr_of!((*base).u64) };
+        return Ok((
+            std::mem::size_of::<libc::epoll_event>(),
+            data as usize - base as usize,
+        ));
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        return Err(unsupported());
+    }
+}
+
+pub fn epoll_create() -> io::Result<RawFd> {
+    #[cfg(target_os = "linux")]
+    {
+        let epfd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+        if epfd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(epfd);
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        return Err(unsupported());
+    }
+}
+
+/// Adds, modifies or deletes the `events` the epoll instance `epfd` waits
+/// for on `fd`. The data of the events is `fd`.
+pub fn epoll_ctl(epfd: RawFd, op: i32, fd: RawFd, events: u32) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        // the data is read back as the int at its offset
+        let mut data = [0u8; 8];
+        data[..4].copy_from_slice(&fd.to_ne_bytes());
+        let mut event = libc::epoll_event {
+            events,
+            u64: u64::from_ne_bytes(data),
+        };
+        loop {
+            if unsafe { libc::epoll_ctl(epfd, op, fd, &mut event) } == 0 {
+                return Ok(());
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (epfd, op, fd, events);
+        return Err(unsupported());
+    }
+}
+
+/// Waits at most `timeout` milliseconds, forever if negative, for events
+/// of `epfd`, storing up to `max_events` `epoll_event`s at `events`.
+/// Returns the number of events stored.
+pub fn epoll_wait(
+    epfd: RawFd,
+    events: Address,
+    max_events: usize,
+    timeout: i64,
+) -> io::Result<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        let deadline = deadline_of(timeout);
+        loop {
+            let res = unsafe {
+                libc::epoll_wait(
+                    epfd,
+                    events.as_mut_raw_ptr() as _,
+                    max_events as _,
+                    remaining_millis(deadline),
+                )
+            };
+            if res >= 0 {
+                return Ok(res as usize);
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (epfd, events, max_events, timeout);
+        return Err(unsupported());
+    }
+}
+
+/// The layout of a `struct kevent` and the filters of readiness.
+#[derive(Clone, Copy, Debug)]
+pub struct KEventLayout {
+    pub size: usize,
+    pub ident_offset: usize,
+    pub filter_offset: usize,
+    pub read_filter: i16,
+    pub write_filter: i16,
+}
+
+pub fn kevent_layout() -> io::Result<KEventLayout> {
+    #[cfg(target_os = "macos")]
+    {
+        let event = std::mem::MaybeUninit::<libc::kevent>::uninit();
+        let base = event.as_ptr();
+        let ident = unsafe { std::ptr::addr_of!((*base).ident) };
+        let filter = unsafe { std::ptr::addr_of!((*base).filter) };
+        return Ok(KEventLayout {
+            size: std::mem::size_of::<libc::kevent>(),
+            ident_offset: ident as usize - base as usize,
+            filter_offset: filter as usize - base as usize,
+            read_filter: libc::EVFILT_READ,
+            write_filter: libc::EVFILT_WRITE,
+        });
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        return Err(unsupported());
+    }
+}
+
+pub fn kqueue_create() -> io::Result<RawFd> {
+    #[cfg(target_os = "macos")]
+    {
+        let kq = unsafe { libc::kqueue() };
+        if kq < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(kq);
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        return Err(unsupported());
+    }
+}
+
+/// Adds or deletes the read and write filters of `fd` in the kqueue `kq`.
+/// Deleting a filter never added is not an error.
+pub fn kevent_register(kq: RawFd, fd: RawFd, read: bool, write: bool) -> io::Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        let change = |filter, add: bool| libc::kevent {
+            ident: fd as _,
+            filter,
+            flags: if add { libc::EV_ADD } else { libc::EV_DELETE },
+            fflags: 0,
+            data: 0,
+            udata: std::ptr::null_mut(),
+        };
+        let changes = [
+            change(libc::EVFILT_READ, read),
+            change(libc::EVFILT_WRITE, write),
+        ];
+        // the errors of the deletions are returned as events instead of
+        // failing the call
+        let mut errors: [libc::kevent; 2] = unsafe { std::mem::zeroed() };
+        let no_wait = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        let res = unsafe {
+            libc::kevent(
+                kq,
+                changes.as_ptr(),
+                changes.len() as _,
+                errors.as_mut_ptr(),
+                errors.len() as _,
+                &no_wait,
+            )
+        };
+        if res < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (kq, fd, read, write);
+        return Err(unsupported());
+    }
+}
+
+/// Waits at most `timeout` milliseconds, forever if negative, for events
+/// of `kq`, storing up to `max_events` `struct kevent`s at `events`. Returns
+/// the number of events stored, 0 if interrupted.
+pub fn kevent_poll(
+    kq: RawFd,
+    events: Address,
+    max_events: usize,
+    timeout: i64,
+) -> io::Result<usize> {
+    #[cfg(target_os = "macos")]
+    {
+        let timeout = (timeout >= 0).then(|| libc::timespec {
+            tv_sec: (timeout / 1000) as _,
+            tv_nsec: ((timeout % 1000) * 1_000_000) as _,
+        });
+        let res = unsafe {
+            libc::kevent(
+                kq,
+                std::ptr::null(),
+                0,
+                events.as_mut_raw_ptr() as _,
+                max_events as _,
+                timeout
+                    .as_ref()
+                    .map_or(std::ptr::null(), |t| t as *const libc::timespec),
+            )
+        };
+        if res < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                return Ok(0);
+            }
+            return Err(err);
+        }
+        return Ok(res as usize);
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (kq, events, max_events, timeout);
+        return Err(unsupported());
+    }
+}
+
+/// Creates a TCP or UDP socket, with `SO_REUSEADDR` if `reuse_addr`.
+pub fn socket(stream: bool, reuse_addr: bool) -> io::Result<RawFd> {
+    #[cfg(target_family = "unix")]
+    {
+        let ty = if stream {
+            libc::SOCK_STREAM
+        } else {
+            libc::SOCK_DGRAM
+        };
+        let fd = unsafe { libc::socket(libc::AF_INET, ty, 0) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if reuse_addr {
+            if let Err(err) = set_socket_option(fd, libc::SOL_SOCKET, libc::SO_REUSEADDR, 1) {
+                unsafe { libc::close(fd) };
+                return Err(err);
+            }
+        }
+        return Ok(fd);
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (stream, reuse_addr);
+        return Err(unsupported());
+    }
+}
+
+pub fn bind(fd: RawFd, addr: SocketAddrV4) -> io::Result<()> {
+    #[cfg(target_family = "unix")]
+    {
+        let sockaddr = sockaddr_of(addr);
+        let res = unsafe {
+            libc::bind(
+                fd,
+                &sockaddr as *const libc::sockaddr_in as _,
+                std::mem::size_of::<libc::sockaddr_in>() as _,
+            )
+        };
+        if res != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (fd, addr);
+        return Err(unsupported());
+    }
+}
+
+pub fn listen(fd: RawFd, backlog: i32) -> io::Result<()> {
+    #[cfg(target_family = "unix")]
+    {
+        if unsafe { libc::listen(fd, backlog) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (fd, backlog);
+        return Err(unsupported());
+    }
+}
+
+/// Connects the socket to `addr`, returning false if a non-blocking socket
+/// is still connecting, see [`finish_connect`].
+pub fn connect(fd: RawFd, addr: SocketAddrV4) -> io::Result<bool> {
+    #[cfg(target_family = "unix")]
+    {
+        let sockaddr = sockaddr_of(addr);
+        let res = unsafe {
+            libc::connect(
+                fd,
+                &sockaddr as *const libc::sockaddr_in as _,
+                std::mem::size_of::<libc::sockaddr_in>() as _,
+            )
+        };
+        if res != 0 {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() == Some(libc::EINPROGRESS) {
+                return Ok(false);
+            }
+            return Err(err);
+        }
+        return Ok(true);
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (fd, addr);
+        return Err(unsupported());
+    }
+}
+
+/// Connects a blocking socket to `addr`, failing with
+/// [`io::ErrorKind::TimedOut`] if that takes longer than `timeout`
+/// milliseconds. The connect is non-blocking while it waits, a `timeout`
+/// that is not positive waits forever.
+pub fn connect_timeout(fd: RawFd, addr: SocketAddrV4, timeout: i64) -> io::Result<()> {
+    if timeout <= 0 {
+        if !connect(fd, addr)? {
+            finish_connect(fd, true, false)?;
+        }
+        return Ok(());
+    }
+    set_blocking(fd, false)?;
+    let connected = match connect(fd, addr) {
+        Ok(true) => Ok(()),
+        Ok(false) => match wait_ready(fd, true, timeout) {
+            Ok(true) => finish_connect(fd, false, true).map(|_| ()),
+            Ok(false) => Err(io::Error::new(io::ErrorKind::TimedOut, "connect timed out")),
+            Err(err) => Err(err),
+        },
+        Err(err) => Err(err),
+    };
+    set_blocking(fd, true)?;
+    return connected;
+}
+
+/// Completes the connection started by [`connect`], returning false if it
+/// is still in progress. A non-blocking call does not wait for it unless
+/// the socket is known to be `ready`.
+pub fn finish_connect(fd: RawFd, block: bool, ready: bool) -> io::Result<bool> {
+    #[cfg(target_family = "unix")]
+    {
+        if !ready && !wait_ready(fd, true, if block { -1 } else { 0 })? {
+            return Ok(false);
+        }
+        let error = get_socket_option(fd, libc::SOL_SOCKET, libc::SO_ERROR)?;
+        if error != 0 {
+            return Err(io::Error::from_raw_os_error(error));
+        }
+        return Ok(true);
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (fd, block, ready);
+        return Err(unsupported());
+    }
+}
+
+/// Waits at most `timeout` milliseconds, forever if negative, until `fd`
+/// can be read, or written if `write`, without blocking. Returns false if
+/// the time is up.
+pub fn wait_ready(fd: RawFd, write: bool, timeout: i64) -> io::Result<bool> {
+    #[cfg(target_family = "unix")]
+    {
+        let deadline = deadline_of(timeout);
+        loop {
+            let mut poll_fd = libc::pollfd {
+                fd,
+                events: if write { libc::POLLOUT } else { libc::POLLIN },
+                revents: 0,
+            };
+            let res = unsafe { libc::poll(&mut poll_fd, 1, remaining_millis(deadline)) };
+            if res >= 0 {
+                return Ok(poll_fd.revents != 0);
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (fd, write, timeout);
+        return Err(unsupported());
+    }
+}
+
+/// Accepts a connection of the listening socket, failing with
+/// [`io::ErrorKind::WouldBlock`] if a non-blocking socket has none.
+pub fn accept(fd: RawFd) -> io::Result<(RawFd, SocketAddrV4)> {
+    #[cfg(target_family = "unix")]
+    {
+        loop {
+            let mut sockaddr: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+            let mut len = std::mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+            let accepted =
+                unsafe { libc::accept(fd, &mut sockaddr as *mut libc::sockaddr_in as _, &mut len) };
+            if accepted >= 0 {
+                return Ok((accepted, addr_of(&sockaddr)));
+            }
+            let err = io::Error::last_os_error();
+            // the connection was reset before it was accepted
+            if err.raw_os_error() != Some(libc::ECONNABORTED) {
+                return Err(err);
+            }
+        }
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = fd;
+        return Err(unsupported());
+    }
+}
+
+pub fn local_addr(fd: RawFd) -> io::Result<SocketAddrV4> {
+    #[cfg(target_family = "unix")]
+    {
+        return socket_addr(fd, libc::getsockname);
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = fd;
+        return Err(unsupported());
+    }
+}
+
+pub fn peer_addr(fd: RawFd) -> io::Result<SocketAddrV4> {
+    #[cfg(target_family = "unix")]
+    {
+        return socket_addr(fd, libc::getpeername);
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = fd;
+        return Err(unsupported());
+    }
+}
+
+/// Reads an int socket option. `SO_LINGER` is the linger time, or -1 when
+/// off.
+pub fn get_socket_option(fd: RawFd, level: i32, name: i32) -> io::Result<i32> {
+    #[cfg(target_family = "unix")]
+    {
+        if level == libc::SOL_SOCKET && name == libc::SO_LINGER {
+            let mut linger: libc::linger = unsafe { std::mem::zeroed() };
+            getsockopt(fd, level, name, &mut linger)?;
+            return Ok(if linger.l_onoff != 0 {
+                linger.l_linger
+            } else {
+                -1
+            });
+        }
+        let mut value: libc::c_int = 0;
+        getsockopt(fd, level, name, &mut value)?;
+        return Ok(value);
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (fd, level, name);
+        return Err(unsupported());
+    }
+}
+
+/// Sets an int socket option. A negative `SO_LINGER` turns lingering off.
+pub fn set_socket_option(fd: RawFd, level: i32, name: i32, value: i32) -> io::Result<()> {
+    #[cfg(target_family = "unix")]
+    {
+        if level == libc::SOL_SOCKET && name == libc::SO_LINGER {
+            let linger = libc::linger {
+                l_onoff: (value >= 0) as _,
+                l_linger: value.max(0),
+            };
+            return setsockopt(fd, level, name, &linger);
+        }
+        return setsockopt(fd, level, name, &(value as libc::c_int));
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (fd, level, name, value);
+        return Err(unsupported());
+    }
+}
+
+/// The number of bytes that can be read from a socket without blocking.
+pub fn available(fd: RawFd) -> io::Result<usize> {
+    #[cfg(target_family = "unix")]
+    {
+        let mut available: libc::c_int = 0;
+        if unsafe { libc::ioctl(fd, libc::FIONREAD, &mut available) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(available.max(0) as usize);
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = fd;
+        return Err(unsupported());
+    }
+}
+
+/// Sends one byte of urgent data.
+pub fn send_urgent(fd: RawFd, data: u8) -> io::Result<()> {
+    #[cfg(target_family = "unix")]
+    {
+        if unsafe { libc::send(fd, &data as *const u8 as _, 1, libc::MSG_OOB) } != 1 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (fd, data);
+        return Err(unsupported());
+    }
+}
+
+/// Shuts down reading, writing or both, `how` being `SHUT_RD`, `SHUT_WR` or
+/// `SHUT_RDWR`.
+pub fn shutdown(fd: RawFd, how: i32) -> io::Result<()> {
+    #[cfg(target_family = "unix")]
+    {
+        if unsafe { libc::shutdown(fd, how) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (fd, how);
+        return Err(unsupported());
+    }
+}
+
+#[cfg(target_family = "unix")]
+fn deadline_of(timeout: i64) -> Option<std::time::Instant> {
+    if timeout < 0 {
+        return None;
+    }
+    return Some(std::time::Instant::now() + std::time::Duration::from_millis(timeout as u64));
+}
+
+/// The milliseconds until `deadline` for a poll, -1 for none.
+#[cfg(target_family = "unix")]
+fn remaining_millis(deadline: Option<std::time::Instant>) -> libc::c_int {
+    return match deadline {
+        Some(deadline) => {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            remaining.as_millis().min(libc::c_int::MAX as u128) as libc::c_int
+        }
+        None => -1,
+    };
+}
+
+#[cfg(target_family = "unix")]
+fn sockaddr_of(addr: SocketAddrV4) -> libc::sockaddr_in {
+    let mut sockaddr: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+    sockaddr.sin_family = libc::AF_INET as _;
+    sockaddr.sin_port = addr.port().to_be();
+    sockaddr.sin_addr.s_addr = u32::from(*addr.ip()).to_be();
+    return sockaddr;
+}
+
+#[cfg(target_family = "unix")]
+fn addr_of(sockaddr: &libc::sockaddr_in) -> SocketAddrV4 {
+    return SocketAddrV4::new(
+        u32::from_be(sockaddr.sin_addr.s_addr).into(),
+        u16::from_be(sockaddr.sin_port),
+    );
+}
+
+#[cfg(target_family = "unix")]
+fn socket_addr(
+    fd: RawFd,
+    get_name: unsafe extern "C" fn(
+        libc::c_int,
+        *mut libc::sockaddr,
+        *mut libc::socklen_t,
+    ) -> libc::c_int,
+) -> io::Result<SocketAddrV4> {
+    let mut sockaddr: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    if unsafe { get_name(fd, &mut sockaddr as *mut libc::sockaddr_in as _, &mut len) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if sockaddr.sin_family != libc::AF_INET as libc::sa_family_t {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "not an IPv4 socket",
+        ));
+    }
+    return Ok(addr_of(&sockaddr));
+}
+
+#[cfg(target_family = "unix")]
+fn getsockopt<T>(fd: RawFd, level: i32, name: i32, value: &mut T) -> io::Result<()> {
+    let mut len = std::mem::size_of::<T>() as libc::socklen_t;
+    if unsafe { libc::getsockopt(fd, level, name, value as *mut T as _, &mut len) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    return Ok(());
+}
+
+#[cfg(target_family = "unix")]
+fn setsockopt<T>(fd: RawFd, level: i32, name: i32, value: &T) -> io::Result<()> {
+    let len = std::mem::size_of::<T>() as libc::socklen_t;
+    if unsafe { libc::setsockopt(fd, level, name, value as *const T as _, len) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    return Ok(());
+}
+
+fn unsupported() -> io::Error {
+    return io::Error::new(
+        io::ErrorKind::Unsupported,
+        "non-blocking I/O is not supported on this platform",
+    );
+}
@@ -0,0 +1,1197 @@
+use std::{fs::File, io, ptr::null_mut};
+
+use crate::memory::{is_align_of, Address};
+
+pub mod aio;
+
+static mut PAGE_SIZE: isize = -1;
+
+pub fn init() {
+    #[cfg(target_family = "unix")]
+    unsafe {
+        PAGE_SIZE = libc::sysconf(libc::_SC_PAGESIZE) as isize;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::sysinfoapi::GetSystemInfo;
+        use winapi::um::sysinfoapi::{LPSYSTEM_INFO, SYSTEM_INFO};
+
+        unsafe {
+            let mut sys_info: SYSTEM_INFO = std::mem::zeroed();
+            GetSystemInfo(&mut sys_info as LPSYSTEM_INFO);
+            PAGE_SIZE = sys_info.dwPageSize as isize;
+        }
+    }
+}
+
+pub fn page_size() -> usize {
+    unsafe {
+        if PAGE_SIZE == -1 {
+            panic!("must call os::init() prior to using it");
+        }
+        return PAGE_SIZE as usize;
+    }
+}
+
+pub fn reserve_memory(size: usize) -> Address {
+    debug_assert!(is_align_of(size, page_size()));
+    #[cfg(target_family = "unix")]
+    {
+        let res = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if res == libc::MAP_FAILED {
+            return Address::null();
+        } else {
+            return Address::new(res.cast());
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::{
+            memoryapi::VirtualAlloc,
+            winnt::{MEM_RESERVE, PAGE_NOACCESS},
+        };
+
+        let res = unsafe { VirtualAlloc(null_mut(), size, MEM_RESERVE, PAGE_NOACCESS) };
+        if res.is_null() {
+            return Address::null();
+        }
+        return Address::new(res.cast());
+    }
+}
+
+/// Best-effort placement hints for memory reserved by [`reserve_memory_with_hints`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MemoryHints {
+    /// Back the memory with huge pages, transparent huge pages on Linux and
+    /// large pages on Windows.
+    pub large_pages: bool,
+    /// Spread the pages over all online NUMA nodes, Linux only.
+    pub numa_interleave: bool,
+}
+
+#[cfg(target_os = "linux")]
+const HUGE_PAGE_SIZE: usize = 2 * crate::memory::MB;
+
+/// Like [`reserve_memory`], then applies `hints` where the OS supports them.
+/// Unsupported or refused hints are logged and otherwise ignored.
+pub fn reserve_memory_with_hints(size: usize, hints: MemoryHints) -> Address {
+    #[cfg(target_os = "linux")]
+    {
+        let addr = if hints.large_pages {
+            reserve_aligned_memory(size, HUGE_PAGE_SIZE)
+        } else {
+            reserve_memory(size)
+        };
+        if addr.is_null() {
+            return addr;
+        }
+        if hints.large_pages
+            && unsafe { libc::madvise(addr.as_mut_raw_ptr() as _, size, libc::MADV_HUGEPAGE) } != 0
+        {
+            log::debug!(
+                "madvise(MADV_HUGEPAGE) failed: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+        if hints.numa_interleave {
+            interleave_numa_nodes(addr, size);
+        }
+        return addr;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        if hints.numa_interleave {
+            log::debug!("NUMA interleaving is not supported on Windows");
+        }
+        if hints.large_pages {
+            let addr = reserve_large_pages(size);
+            if addr.is_not_null() {
+                return addr;
+            }
+        }
+        return reserve_memory(size);
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    {
+        if hints.large_pages || hints.numa_interleave {
+            log::debug!("memory hints are not supported on this OS");
+        }
+        return reserve_memory(size);
+    }
+}
+
+/// Reserves `size` bytes starting at a multiple of `alignment` by
+/// over-reserving and unmapping the unaligned head and tail.
+#[cfg(target_os = "linux")]
+fn reserve_aligned_memory(size: usize, alignment: usize) -> Address {
+    let padded = reserve_memory(size + alignment);
+    if padded.is_null() {
+        return padded;
+    }
+    let start = crate::memory::align_of(padded.as_usize(), alignment);
+    let head = start - padded.as_usize();
+    let tail = alignment - head;
+    if head > 0 {
+        release_memory(padded, head);
+    }
+    if tail > 0 {
+        release_memory(Address::from_usize(start + size), tail);
+    }
+    return Address::from_usize(start);
+}
+
+#[cfg(target_os = "linux")]
+fn interleave_numa_nodes(addr: Address, size: usize) {
+    const MPOL_INTERLEAVE: libc::c_int = 3;
+
+    let online = match std::fs::read_to_string("/sys/devices/system/node/online") {
+        Ok(online) => online,
+        Err(e) => {
+            log::debug!("cannot read online NUMA nodes: {}", e);
+            return;
+        }
+    };
+    let mut node_mask = [0 as libc::c_ulong; 16];
+    let mask_bits = node_mask.len() * libc::c_ulong::BITS as usize;
+    let mut nodes = 0;
+    // a list of ranges, e.g. "0-1,3"
+    for range in online.trim().split(',').filter(|range| !range.is_empty()) {
+        let (first, last) = range.split_once('-').unwrap_or((range, range));
+        let (first, last) = match (first.parse::<usize>(), last.parse::<usize>()) {
+            (Ok(first), Ok(last)) => (first, last.min(mask_bits - 1)),
+            _ => {
+                log::debug!("unexpected NUMA node list {}", online);
+                return;
+            }
+        };
+        for node in first..=last {
+            let bits = libc::c_ulong::BITS as usize;
+            node_mask[node / bits] |= 1 << (node % bits);
+            nodes += 1;
+        }
+    }
+    if nodes < 2 {
+        return;
+    }
+    let res = unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            addr.as_mut_raw_ptr(),
+            size,
+            MPOL_INTERLEAVE,
+            node_mask.as_ptr(),
+            mask_bits + 1,
+            0,
+        )
+    };
+    if res != 0 {
+        log::debug!(
+            "mbind(MPOL_INTERLEAVE) failed: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+/// Reserves and commits `size` bytes of large pages, which requires the
+/// "Lock pages in memory" privilege.
+#[cfg(target_os = "windows")]
+fn reserve_large_pages(size: usize) -> Address {
+    use winapi::um::{
+        memoryapi::{GetLargePageMinimum, VirtualAlloc},
+        winnt::{MEM_COMMIT, MEM_LARGE_PAGES, MEM_RESERVE, PAGE_READWRITE},
+    };
+
+    let large_page_size = unsafe { GetLargePageMinimum() };
+    if large_page_size == 0 || !is_align_of(size, large_page_size) {
+        log::debug!(
+            "large pages unavailable for {} bytes, minimum {}",
+            size,
+            large_page_size
+        );
+        return Address::null();
+    }
+    let res = unsafe {
+        VirtualAlloc(
+            null_mut(),
+            size,
+            MEM_RESERVE | MEM_COMMIT | MEM_LARGE_PAGES,
+            PAGE_READWRITE,
+        )
+    };
+    if res.is_null() {
+        log::debug!(
+            "VirtualAlloc(MEM_LARGE_PAGES) failed: {}",
+            std::io::Error::last_os_error()
+        );
+        return Address::null();
+    }
+    return Address::new(res.cast());
+}
+
+pub fn commit_memory(addr: Address, size: usize, exec: bool) -> bool {
+    debug_assert!(is_align_of(size, page_size()));
+    #[cfg(target_family = "unix")]
+    {
+        let mut prot = libc::PROT_READ | libc::PROT_WRITE;
+        if exec {
+            prot |= libc::PROT_EXEC;
+        }
+        let res = unsafe {
+            libc::mmap(
+                addr.raw_ptr() as _,
+                size,
+                prot,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        return res != libc::MAP_FAILED;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::{
+            memoryapi::VirtualAlloc,
+            winnt::{MEM_COMMIT, PAGE_EXECUTE_READWRITE, PAGE_READWRITE},
+        };
+
+        let prot = if !exec {
+            PAGE_READWRITE
+        } else {
+            PAGE_EXECUTE_READWRITE
+        };
+
+        let res = unsafe { VirtualAlloc(addr.raw_ptr() as _, size, MEM_COMMIT, prot) };
+        return !res.is_null();
+    }
+}
+
+pub fn release_memory(addr: Address, size: usize) -> i32 {
+    #[cfg(target_family = "unix")]
+    {
+        unsafe {
+            return libc::munmap(addr.raw_ptr() as _, size);
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::{memoryapi::VirtualFree, winnt::MEM_RELEASE};
+
+        return unsafe { VirtualFree(addr.raw_ptr() as _, size, MEM_RELEASE) };
+    }
+}
+
+/// Makes `[addr, addr + size)` inaccessible, so that any access to it raises a
+/// memory access fault.
+pub fn protect_memory(addr: Address, size: usize) -> bool {
+    debug_assert!(is_align_of(addr.as_usize(), page_size()));
+    debug_assert!(is_align_of(size, page_size()));
+    #[cfg(target_family = "unix")]
+    {
+        return unsafe { libc::mprotect(addr.raw_ptr() as _, size, libc::PROT_NONE) } == 0;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::{memoryapi::VirtualProtect, winnt::PAGE_NOACCESS};
+
+        let mut old_prot = 0;
+        return unsafe { VirtualProtect(addr.raw_ptr() as _, size, PAGE_NOACCESS, &mut old_prot) }
+            != 0;
+    }
+}
+
+/// Makes `[addr, addr + size)`, protected by `protect_memory`, readable and
+/// writable again.
+pub fn unprotect_memory(addr: Address, size: usize) -> bool {
+    debug_assert!(is_align_of(addr.as_usize(), page_size()));
+    debug_assert!(is_align_of(size, page_size()));
+    #[cfg(target_family = "unix")]
+    {
+        return unsafe {
+            libc::mprotect(
+                addr.raw_ptr() as _,
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+            )
+        } == 0;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::{memoryapi::VirtualProtect, winnt::PAGE_READWRITE};
+
+        let mut old_prot = 0;
+        return unsafe { VirtualProtect(addr.raw_ptr() as _, size, PAGE_READWRITE, &mut old_prot) }
+            != 0;
+    }
+}
+
+/// How [`map_file`] maps a file, like `FileChannel.MapMode`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapMode {
+    ReadOnly,
+    ReadWrite,
+    /// Copy-on-write, the writes never reach the file.
+    Private,
+}
+
+/// The alignment of the file offsets passed to [`map_file`], the page size on
+/// unix and the allocation granularity on Windows.
+pub fn allocation_granularity() -> usize {
+    #[cfg(target_family = "unix")]
+    {
+        return page_size();
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::sysinfoapi::{GetSystemInfo, LPSYSTEM_INFO, SYSTEM_INFO};
+
+        let mut sys_info: SYSTEM_INFO = unsafe { std::mem::zeroed() };
+        unsafe { GetSystemInfo(&mut sys_info as LPSYSTEM_INFO) };
+        return sys_info.dwAllocationGranularity as usize;
+    }
+}
+
+/// Maps `size` bytes of `file` starting at `offset`, a multiple of
+/// [`allocation_granularity`], into memory.
+pub fn map_file(file: &File, offset: u64, size: usize, mode: MapMode) -> io::Result<Address> {
+    debug_assert!(size > 0);
+    debug_assert!(is_align_of(offset as usize, allocation_granularity()));
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::fd::AsRawFd;
+
+        let (prot, flags) = match mode {
+            MapMode::ReadOnly => (libc::PROT_READ, libc::MAP_SHARED),
+            MapMode::ReadWrite => (libc::PROT_READ | libc::PROT_WRITE, libc::MAP_SHARED),
+            MapMode::Private => (libc::PROT_READ | libc::PROT_WRITE, libc::MAP_PRIVATE),
+        };
+        let res = unsafe {
+            libc::mmap(
+                null_mut(),
+                size,
+                prot,
+                flags,
+                file.as_raw_fd(),
+                offset as libc::off_t,
+            )
+        };
+        if res == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(Address::new(res.cast()));
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::io::AsRawHandle;
+        use winapi::um::{
+            handleapi::CloseHandle,
+            memoryapi::{
+                CreateFileMappingW, MapViewOfFile, FILE_MAP_COPY, FILE_MAP_READ, FILE_MAP_WRITE,
+            },
+            winnt::{PAGE_READONLY, PAGE_READWRITE, PAGE_WRITECOPY},
+        };
+
+        let (protect, access) = match mode {
+            MapMode::ReadOnly => (PAGE_READONLY, FILE_MAP_READ),
+            MapMode::ReadWrite => (PAGE_READWRITE, FILE_MAP_WRITE),
+            MapMode::Private => (PAGE_WRITECOPY, FILE_MAP_COPY),
+        };
+        let end = offset + size as u64;
+        let mapping = unsafe {
+            CreateFileMappingW(
+                file.as_raw_handle() as _,
+                null_mut(),
+                protect,
+                (end >> 32) as u32,
+                end as u32,
+                null_mut(),
+            )
+        };
+        if mapping.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let res =
+            unsafe { MapViewOfFile(mapping, access, (offset >> 32) as u32, offset as u32, size) };
+        let err = io::Error::last_os_error();
+        // the view keeps the mapping alive
+        unsafe { CloseHandle(mapping) };
+        if res.is_null() {
+            return Err(err);
+        }
+        return Ok(Address::new(res.cast()));
+    }
+}
+
+/// Unmaps the `size` bytes mapped at `addr` by [`map_file`].
+pub fn unmap_file(addr: Address, size: usize) -> io::Result<()> {
+    #[cfg(target_family = "unix")]
+    {
+        if unsafe { libc::munmap(addr.as_mut_raw_ptr() as _, size) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::memoryapi::UnmapViewOfFile;
+
+        let _ = size;
+        if unsafe { UnmapViewOfFile(addr.raw_ptr() as _) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+}
+
+/// Writes the changes to `[addr, addr + size)` of a file mapping back to the
+/// file. `addr` must be page aligned.
+pub fn flush_mapped_file(addr: Address, size: usize) -> io::Result<()> {
+    debug_assert!(is_align_of(addr.as_usize(), page_size()));
+    #[cfg(target_family = "unix")]
+    {
+        if unsafe { libc::msync(addr.as_mut_raw_ptr() as _, size, libc::MS_SYNC) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::memoryapi::FlushViewOfFile;
+
+        if unsafe { FlushViewOfFile(addr.raw_ptr() as _, size) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+}
+
+/// Whether every page of `[addr, addr + size)` is in physical memory. Always
+/// false on Windows, which cannot tell.
+pub fn is_resident(addr: Address, size: usize) -> bool {
+    debug_assert!(is_align_of(addr.as_usize(), page_size()));
+    #[cfg(target_family = "unix")]
+    {
+        let pages = (size + page_size() - 1) / page_size();
+        let mut residency = vec![0u8; pages];
+        let res = unsafe {
+            libc::mincore(
+                addr.as_mut_raw_ptr() as _,
+                size,
+                residency.as_mut_ptr() as _,
+            )
+        };
+        return res == 0 && residency.iter().all(|page| page & 1 != 0);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let _ = size;
+        return false;
+    }
+}
+
+/// Hints that `[addr, addr + size)` will be accessed soon, so its pages can be
+/// read ahead.
+pub fn prefetch(addr: Address, size: usize) {
+    debug_assert!(is_align_of(addr.as_usize(), page_size()));
+    #[cfg(target_family = "unix")]
+    {
+        if unsafe { libc::madvise(addr.as_mut_raw_ptr() as _, size, libc::MADV_WILLNEED) } != 0 {
+            log::debug!(
+                "madvise(MADV_WILLNEED) failed: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let _ = (addr, size);
+    }
+}
+
+/// Called with the faulting address on every memory access fault of the
+/// process. Returning `true` resumes the thread, at the faulting instruction
+/// if the handler made the address accessible, or where the handler
+/// redirected it with [`FaultContext::resume_at`]. Returning `false` lets the
+/// fault go on to the previously installed handler, which usually terminates
+/// the process.
+pub type FaultHandler = fn(fault_addr: Address, ctx: &mut FaultContext) -> bool;
+
+/// Whether [`FaultContext`] knows the registers of the platform, so that a
+/// fault handler can resume a thread somewhere else.
+pub const CAN_RESUME_FAULTS_ELSEWHERE: bool = cfg!(any(
+    all(
+        any(target_os = "linux", target_os = "android"),
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ),
+    all(target_os = "windows", target_arch = "x86_64")
+));
+
+/// The registers of a thread stopped by a memory access fault, as the
+/// thread resumes with them.
+pub struct FaultContext {
+    #[cfg(target_family = "unix")]
+    ucontext: *mut libc::c_void,
+    #[cfg(target_os = "windows")]
+    context: winapi::um::winnt::PCONTEXT,
+}
+
+impl FaultContext {
+    /// The address of the faulting instruction, `None` unless
+    /// [`CAN_RESUME_FAULTS_ELSEWHERE`].
+    pub fn pc(&self) -> Option<usize> {
+        #[cfg(all(
+            any(target_os = "linux", target_os = "android"),
+            target_arch = "x86_64"
+        ))]
+        unsafe {
+            let ucontext = &*(self.ucontext as *const libc::ucontext_t);
+            return Some(ucontext.uc_mcontext.gregs[libc::REG_RIP as usize] as usize);
+        }
+        #[cfg(all(
+            any(target_os = "linux", target_os = "android"),
+            target_arch = "aarch64"
+        ))]
+        unsafe {
+            let ucontext = &*(self.ucontext as *const libc::ucontext_t);
+            return Some(ucontext.uc_mcontext.pc as usize);
+        }
+        #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+        unsafe {
+            return Some((*self.context).Rip as usize);
+        }
+        #[cfg(not(any(
+            all(
+                any(target_os = "linux", target_os = "android"),
+                any(target_arch = "x86_64", target_arch = "aarch64")
+            ),
+            all(target_os = "windows", target_arch = "x86_64")
+        )))]
+        return None;
+    }
+
+    /// Resumes the thread at `pc` instead of the faulting instruction, with
+    /// `value` in the register `reserve_value!` keeps its value in, rdi or x0.
+    /// `pc` has to expect nothing but that register, and the stack pointer of
+    /// the faulting code. Returns whether the platform supports it.
+    pub fn resume_at(&mut self, pc: usize, value: usize) -> bool {
+        #[cfg(all(
+            any(target_os = "linux", target_os = "android"),
+            target_arch = "x86_64"
+        ))]
+        unsafe {
+            let ucontext = &mut *(self.ucontext as *mut libc::ucontext_t);
+            ucontext.uc_mcontext.gregs[libc::REG_RIP as usize] = pc as libc::greg_t;
+            ucontext.uc_mcontext.gregs[libc::REG_RDI as usize] = value as libc::greg_t;
+            return true;
+        }
+        #[cfg(all(
+            any(target_os = "linux", target_os = "android"),
+            target_arch = "aarch64"
+        ))]
+        unsafe {
+            let ucontext = &mut *(self.ucontext as *mut libc::ucontext_t);
+            ucontext.uc_mcontext.pc = pc as u64;
+            ucontext.uc_mcontext.regs[0] = value as u64;
+            return true;
+        }
+        #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+        unsafe {
+            (*self.context).Rip = pc as u64;
+            (*self.context).Rdi = value as u64;
+            return true;
+        }
+        #[cfg(not(any(
+            all(
+                any(target_os = "linux", target_os = "android"),
+                any(target_arch = "x86_64", target_arch = "aarch64")
+            ),
+            all(target_os = "windows", target_arch = "x86_64")
+        )))]
+        {
+            let _ = (pc, value);
+            return false;
+        }
+    }
+}
+
+static mut FAULT_HANDLER: Option<FaultHandler> = None;
+
+/// Installs `handler` for SIGSEGV/SIGBUS on unix and as a vectored exception
+/// handler on Windows. Only the first call installs a handler.
+pub fn install_fault_handler(handler: FaultHandler) {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| unsafe {
+        FAULT_HANDLER = Some(handler);
+        fault::install();
+    });
+}
+
+/// Writes `msg` to stderr and aborts, without allocating, so that it can be
+/// used from a fault handler.
+pub fn fatal_error(msg: &str) -> ! {
+    #[cfg(target_family = "unix")]
+    unsafe {
+        libc::write(libc::STDERR_FILENO, msg.as_ptr().cast(), msg.len());
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::io::Write;
+
+        let _ = std::io::stderr().write_all(msg.as_bytes());
+    }
+    std::process::abort();
+}
+
+/// The standard streams of the process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StdStream {
+    In,
+    Out,
+    Err,
+}
+
+/// Whether `stream` is attached to a terminal.
+pub fn is_tty(stream: StdStream) -> bool {
+    #[cfg(target_family = "unix")]
+    {
+        let fd = match stream {
+            StdStream::In => libc::STDIN_FILENO,
+            StdStream::Out => libc::STDOUT_FILENO,
+            StdStream::Err => libc::STDERR_FILENO,
+        };
+        return unsafe { libc::isatty(fd) } == 1;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::consoleapi::GetConsoleMode;
+
+        let mut mode = 0;
+        return unsafe { GetConsoleMode(std_handle(stream), &mut mode) } != 0;
+    }
+}
+
+/// The charset of the terminal, e.g. `UTF-8`, or `None` if the locale does
+/// not name one.
+pub fn console_encoding() -> Option<String> {
+    #[cfg(target_family = "unix")]
+    {
+        // the first of these set decides the charset, like setlocale(LC_CTYPE, "")
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|locale| !locale.is_empty())?;
+        return locale_codeset(&locale);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::consoleapi::GetConsoleOutputCP;
+
+        return match unsafe { GetConsoleOutputCP() } {
+            0 => None,
+            65001 => Some("UTF-8".to_string()),
+            code_page => Some(format!("cp{}", code_page)),
+        };
+    }
+}
+
+/// The codeset of a locale name `language[_territory][.codeset][@modifier]`.
+#[cfg(target_family = "unix")]
+fn locale_codeset(locale: &str) -> Option<String> {
+    let (_, codeset) = locale.split_once('.')?;
+    let codeset = codeset.split('@').next().unwrap_or(codeset);
+    if codeset.is_empty() {
+        return None;
+    }
+    if codeset.eq_ignore_ascii_case("utf8") || codeset.eq_ignore_ascii_case("utf-8") {
+        return Some("UTF-8".to_string());
+    }
+    return Some(codeset.to_string());
+}
+
+/// A locale in the parts `java.util.Locale` takes, e.g. `sr`, `Latn` and
+/// `RS` for Serbian in Latin script in Serbia.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Locale {
+    pub language: String,
+    pub script: String,
+    pub country: String,
+    pub variant: String,
+}
+
+/// The locale of the user, or `None` if the environment names none.
+pub fn locale() -> Option<Locale> {
+    #[cfg(target_family = "unix")]
+    {
+        // the first of these set decides the language, like setlocale(LC_MESSAGES, "")
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|locale| !locale.is_empty())?;
+        return parse_locale(&locale);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::{winnls::GetUserDefaultLocaleName, winnt::LOCALE_NAME_MAX_LENGTH};
+
+        let mut name = [0u16; LOCALE_NAME_MAX_LENGTH];
+        let len = unsafe { GetUserDefaultLocaleName(name.as_mut_ptr(), name.len() as i32) };
+        if len <= 1 {
+            return None;
+        }
+        return parse_locale_name(&String::from_utf16_lossy(&name[..len as usize - 1]));
+    }
+}
+
+/// Parses a locale name `language[_territory][.codeset][@modifier]`, where
+/// the modifier may name a script or a variant.
+#[cfg(target_family = "unix")]
+fn parse_locale(locale: &str) -> Option<Locale> {
+    let (name, modifier) = match locale.split_once('@') {
+        Some((name, modifier)) => (name, modifier),
+        None => (locale, ""),
+    };
+    let name = name.split('.').next().unwrap_or(name);
+    if name == "C" || name == "POSIX" {
+        return Some(Locale {
+            language: "en".to_string(),
+            ..Default::default()
+        });
+    }
+    let (language, country) = match name.split_once('_') {
+        Some((language, country)) => (language, country),
+        None => (name, ""),
+    };
+    if language.is_empty() {
+        return None;
+    }
+    let mut locale = Locale {
+        language: language.to_ascii_lowercase(),
+        country: country.to_ascii_uppercase(),
+        ..Default::default()
+    };
+    match modifier {
+        "latin" => locale.script = "Latn".to_string(),
+        "cyrillic" => locale.script = "Cyrl".to_string(),
+        "devanagari" => locale.script = "Deva".to_string(),
+        "nynorsk" => locale.variant = "NY".to_string(),
+        // euro only picks the charset
+        _ => {}
+    }
+    return Some(locale);
+}
+
+/// Parses a Windows locale name `language[-script][-region][_sort]`.
+#[cfg(target_os = "windows")]
+fn parse_locale_name(name: &str) -> Option<Locale> {
+    let name = name.split('_').next().unwrap_or(name);
+    let mut subtags = name.split('-');
+    let language = subtags.next().filter(|language| !language.is_empty())?;
+    let mut locale = Locale {
+        language: language.to_ascii_lowercase(),
+        ..Default::default()
+    };
+    for subtag in subtags {
+        if subtag.len() == 4 && locale.script.is_empty() && locale.country.is_empty() {
+            locale.script = subtag.to_string();
+        } else if (subtag.len() == 2 || subtag.len() == 3) && locale.country.is_empty() {
+            locale.country = subtag.to_ascii_uppercase();
+        }
+    }
+    return Some(locale);
+}
+
+/// The name of the time zone of the system in the tz database, e.g.
+/// `Europe/Berlin`, or `None` if it is unknown.
+pub fn time_zone_id() -> Option<String> {
+    #[cfg(target_family = "unix")]
+    {
+        if let Ok(tz) = std::env::var("TZ") {
+            let tz = tz.strip_prefix(':').unwrap_or(&tz);
+            if tz.starts_with('/') {
+                return zoneinfo_name(std::path::Path::new(tz));
+            }
+            if !tz.is_empty() {
+                return Some(tz.to_string());
+            }
+        }
+        if let Ok(timezone) = std::fs::read_to_string("/etc/timezone") {
+            let timezone = timezone.lines().next().unwrap_or("").trim();
+            if !timezone.is_empty() {
+                return Some(timezone.to_string());
+            }
+        }
+        let localtime = std::fs::read_link("/etc/localtime").ok()?;
+        return zoneinfo_name(&localtime);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        // the JDK maps the Windows names with lib/tzmappings, which is not bundled
+        return None;
+    }
+}
+
+/// The name of a time zone file under a `zoneinfo` directory, e.g.
+/// `Europe/Berlin` for `/usr/share/zoneinfo/Europe/Berlin`.
+#[cfg(target_family = "unix")]
+fn zoneinfo_name(path: &std::path::Path) -> Option<String> {
+    let path = path.to_str()?;
+    let (_, name) = path.rsplit_once("zoneinfo/")?;
+    let name = name.strip_prefix("posix/").unwrap_or(name);
+    if name.is_empty() || name == "localtime" || name == "posixrules" {
+        return None;
+    }
+    return Some(name.to_string());
+}
+
+/// The current offset of the local time from UTC in seconds, or `None` if
+/// it is unknown.
+pub fn utc_offset() -> Option<i32> {
+    #[cfg(target_family = "unix")]
+    {
+        let now = unsafe { libc::time(null_mut()) };
+        let mut local: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&now, &mut local) }.is_null() {
+            return None;
+        }
+        return Some(local.tm_gmtoff as i32);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::{
+            timezoneapi::{GetTimeZoneInformation, TIME_ZONE_ID_INVALID, TIME_ZONE_INFORMATION},
+            winnt::{TIME_ZONE_ID_DAYLIGHT, TIME_ZONE_ID_STANDARD},
+        };
+
+        let mut info: TIME_ZONE_INFORMATION = unsafe { std::mem::zeroed() };
+        let bias = match unsafe { GetTimeZoneInformation(&mut info) } {
+            TIME_ZONE_ID_INVALID => return None,
+            TIME_ZONE_ID_DAYLIGHT => info.Bias + info.DaylightBias,
+            TIME_ZONE_ID_STANDARD => info.Bias + info.StandardBias,
+            _ => info.Bias,
+        };
+        // the bias is in minutes and has the sign of UTC minus local time
+        return Some(-bias * 60);
+    }
+}
+
+/// The release of the operating system, e.g. `6.1.0-18-amd64` on Linux, or
+/// `None` if it is unknown.
+pub fn version() -> Option<String> {
+    #[cfg(target_family = "unix")]
+    {
+        let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+        if unsafe { libc::uname(&mut name) } != 0 {
+            return None;
+        }
+        let release = unsafe { std::ffi::CStr::from_ptr(name.release.as_ptr()) };
+        return Some(release.to_string_lossy().into_owned());
+    }
+    #[cfg(target_os = "windows")]
+    {
+        return None;
+    }
+}
+
+/// Fills `buf` with random bytes from the entropy source of the operating
+/// system, `/dev/urandom` on Unix and `BCryptGenRandom` on Windows.
+pub fn fill_random(buf: &mut [u8]) -> io::Result<()> {
+    #[cfg(target_family = "unix")]
+    {
+        use std::io::Read;
+
+        return File::open("/dev/urandom")?.read_exact(buf);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::shared::bcrypt::{BCryptGenRandom, BCRYPT_USE_SYSTEM_PREFERRED_RNG};
+
+        for chunk in buf.chunks_mut(u32::MAX as usize) {
+            let status = unsafe {
+                BCryptGenRandom(
+                    null_mut(),
+                    chunk.as_mut_ptr(),
+                    chunk.len() as u32,
+                    BCRYPT_USE_SYSTEM_PREFERRED_RNG,
+                )
+            };
+            if status < 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("BCryptGenRandom failed with 0x{:x}", status),
+                ));
+            }
+        }
+        return Ok(());
+    }
+}
+
+/// Turns the echo of the terminal input on or off, returning whether it was
+/// on before.
+pub fn set_console_echo(on: bool) -> std::io::Result<bool> {
+    #[cfg(target_family = "unix")]
+    {
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let was_on = termios.c_lflag & libc::ECHO != 0;
+        if on {
+            termios.c_lflag |= libc::ECHO;
+        } else {
+            termios.c_lflag &= !libc::ECHO;
+        }
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        return Ok(was_on);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use winapi::um::{
+            consoleapi::{GetConsoleMode, SetConsoleMode},
+            wincon::ENABLE_ECHO_INPUT,
+        };
+
+        let handle = std_handle(StdStream::In);
+        let mut mode = 0;
+        if unsafe { GetConsoleMode(handle, &mut mode) } == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let was_on = mode & ENABLE_ECHO_INPUT != 0;
+        let mode = if on {
+            mode | ENABLE_ECHO_INPUT
+        } else {
+            mode & !ENABLE_ECHO_INPUT
+        };
+        if unsafe { SetConsoleMode(handle, mode) } == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        return Ok(was_on);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn std_handle(stream: StdStream) -> winapi::um::winnt::HANDLE {
+    use winapi::um::{
+        processenv::GetStdHandle,
+        winbase::{STD_ERROR_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE},
+    };
+
+    let handle = match stream {
+        StdStream::In => STD_INPUT_HANDLE,
+        StdStream::Out => STD_OUTPUT_HANDLE,
+        StdStream::Err => STD_ERROR_HANDLE,
+    };
+    return unsafe { GetStdHandle(handle) };
+}
+
+#[cfg(target_family = "unix")]
+mod fault {
+    use std::{ffi::c_int, mem::MaybeUninit};
+
+    use crate::memory::Address;
+
+    static mut PREV_SEGV: MaybeUninit<libc::sigaction> = MaybeUninit::uninit();
+    static mut PREV_BUS: MaybeUninit<libc::sigaction> = MaybeUninit::uninit();
+
+    pub(super) unsafe fn install() {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_fault as usize;
+        action.sa_flags = libc::SA_SIGINFO | libc::SA_ONSTACK;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGSEGV, &action, PREV_SEGV.as_mut_ptr());
+        libc::sigaction(libc::SIGBUS, &action, PREV_BUS.as_mut_ptr());
+    }
+
+    extern "C" fn on_fault(sig: c_int, info: *mut libc::siginfo_t, ctx: *mut libc::c_void) {
+        unsafe {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            let fault_addr = (*info).si_addr();
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            let fault_addr = (*info).si_addr;
+            if let Some(handler) = super::FAULT_HANDLER {
+                let mut fault_ctx = super::FaultContext { ucontext: ctx };
+                if handler(Address::from_c_ptr(fault_addr), &mut fault_ctx) {
+                    return;
+                }
+            }
+
+            let prev = if sig == libc::SIGSEGV {
+                PREV_SEGV.assume_init_ref()
+            } else {
+                PREV_BUS.assume_init_ref()
+            };
+            if prev.sa_sigaction == libc::SIG_DFL || prev.sa_sigaction == libc::SIG_IGN {
+                // returning re-executes the faulting instruction, which now
+                // takes the default action
+                libc::sigaction(sig, prev, std::ptr::null_mut());
+            } else if prev.sa_flags & libc::SA_SIGINFO != 0 {
+                let prev_handler: extern "C" fn(c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+                    std::mem::transmute(prev.sa_sigaction);
+                prev_handler(sig, info, ctx);
+            } else {
+                let prev_handler: extern "C" fn(c_int) = std::mem::transmute(prev.sa_sigaction);
+                prev_handler(sig);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod fault {
+    use winapi::{
+        shared::ntdef::LONG,
+        um::{
+            errhandlingapi::AddVectoredExceptionHandler, minwinbase::EXCEPTION_ACCESS_VIOLATION,
+            winnt::EXCEPTION_POINTERS,
+        },
+        vc::excpt::{EXCEPTION_CONTINUE_EXECUTION, EXCEPTION_CONTINUE_SEARCH},
+    };
+
+    use crate::memory::Address;
+
+    pub(super) unsafe fn install() {
+        AddVectoredExceptionHandler(1, Some(on_exception));
+    }
+
+    unsafe extern "system" fn on_exception(info: *mut EXCEPTION_POINTERS) -> LONG {
+        let record = &*(*info).ExceptionRecord;
+        if record.ExceptionCode == EXCEPTION_ACCESS_VIOLATION && record.NumberParameters >= 2 {
+            if let Some(handler) = super::FAULT_HANDLER {
+                let mut fault_ctx = super::FaultContext {
+                    context: (*info).ContextRecord,
+                };
+                let fault_addr = Address::from_usize(record.ExceptionInformation[1]);
+                if handler(fault_addr, &mut fault_ctx) {
+                    return EXCEPTION_CONTINUE_EXECUTION;
+                }
+            }
+        }
+        return EXCEPTION_CONTINUE_SEARCH;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        commit_memory, init, page_size, release_memory, reserve_memory_with_hints, MemoryHints,
+    };
+    use crate::memory::{is_align_of, MB};
+
+    #[test]
+    fn reserve_with_hints() {
+        init();
+        let size = 4 * MB;
+        let hints = MemoryHints {
+            large_pages: true,
+            numa_interleave: true,
+        };
+        let addr = reserve_memory_with_hints(size, hints);
+        assert!(addr.is_not_null());
+        assert!(is_align_of(addr.as_usize(), page_size()));
+        #[cfg(target_os = "linux")]
+        assert!(is_align_of(addr.as_usize(), super::HUGE_PAGE_SIZE));
+        assert!(commit_memory(addr, size, false));
+
+        let bytes = unsafe { std::slice::from_raw_parts_mut(addr.as_mut_raw_ptr(), size) };
+        bytes[0] = 1;
+        bytes[size - 1] = 2;
+        assert_eq!(3, bytes[0] + bytes[size - 1]);
+        assert_eq!(0, release_memory(addr, size));
+    }
+
+    #[test]
+    fn fill_random_bytes() {
+        let mut first = [0u8; 32];
+        let mut second = [0u8; 32];
+        super::fill_random(&mut first).unwrap();
+        super::fill_random(&mut second).unwrap();
+        assert_ne!(first, second);
+        super::fill_random(&mut []).unwrap();
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn locale_codesets() {
+        use super::locale_codeset;
+
+        assert_eq!(Some("UTF-8".to_string()), locale_codeset("en_US.UTF-8"));
+        assert_eq!(Some("UTF-8".to_string()), locale_codeset("C.utf8"));
+        assert_eq!(
+            Some("ISO-8859-15".to_string()),
+            locale_codeset("de_DE.ISO-8859-15@euro")
+        );
+        assert_eq!(Some("eucJP".to_string()), locale_codeset("ja_JP.eucJP"));
+        assert_eq!(None, locale_codeset("C"));
+        assert_eq!(None, locale_codeset("POSIX"));
+        assert_eq!(None, locale_codeset("en_US.@euro"));
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn locale_names() {
+        use super::{parse_locale, Locale};
+
+        let locale = |language: &str, script: &str, country: &str, variant: &str| Locale {
+            language: language.to_string(),
+            script: script.to_string(),
+            country: country.to_string(),
+            variant: variant.to_string(),
+        };
+        assert_eq!(
+            Some(locale("en", "", "US", "")),
+            parse_locale("en_US.UTF-8")
+        );
+        assert_eq!(Some(locale("en", "", "", "")), parse_locale("C.UTF-8"));
+        assert_eq!(Some(locale("en", "", "", "")), parse_locale("POSIX"));
+        assert_eq!(Some(locale("de", "", "DE", "")), parse_locale("de_DE@euro"));
+        assert_eq!(
+            Some(locale("sr", "Latn", "RS", "")),
+            parse_locale("sr_RS.UTF-8@latin")
+        );
+        assert_eq!(
+            Some(locale("nn", "", "NO", "NY")),
+            parse_locale("nn_NO@nynorsk")
+        );
+        assert_eq!(None, parse_locale(".UTF-8"));
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn zoneinfo_names() {
+        use super::zoneinfo_name;
+        use std::path::Path;
+
+        let name = |path: &str| zoneinfo_name(Path::new(path));
+        assert_eq!(
+            Some("Europe/Berlin".to_string()),
+            name("/usr/share/zoneinfo/Europe/Berlin")
+        );
+        assert_eq!(
+            Some("Asia/Tokyo".to_string()),
+            name("../usr/share/zoneinfo/posix/Asia/Tokyo")
+        );
+        assert_eq!(
+            Some("UTC".to_string()),
+            name("/var/db/timezone/zoneinfo/UTC")
+        );
+        assert_eq!(None, name("/usr/share/zoneinfo/posixrules"));
+        assert_eq!(None, name("/etc/localtime"));
+    }
+
+    #[test]
+    fn utc_offset() {
+        let offset = super::utc_offset().unwrap();
+        assert!(offset.abs() <= 14 * 60 * 60);
+        assert_eq!(0, offset % 60);
+    }
+}
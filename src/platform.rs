@@ -0,0 +1,90 @@
+//! The Java platform level of the VM, see
+//! [`VM::capabilities`](crate::vm::VM::capabilities).
+//!
+//! The natives implement the class library of Java SE 7, `rt.jar`, so that
+//! is the level the VM reports in its system properties, even though the
+//! class file parser accepts newer class files.
+
+use std::ops::RangeInclusive;
+
+/// The Java SE version whose class library the natives implement.
+pub const JAVA_SPECIFICATION_VERSION: u16 = 7;
+
+/// The oldest class file major version the parser accepts, of JDK 1.1.
+pub const MIN_CLASS_FILE_VERSION: u16 = 45;
+
+/// The newest class file major version the parser accepts, of Java SE 13.
+pub const MAX_CLASS_FILE_VERSION: u16 = 57;
+
+pub const VM_NAME: &str = "rsvm";
+
+pub const VM_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// What a VM supports.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    pub vm_name: &'static str,
+    pub vm_version: &'static str,
+    /// The Java SE version of the class library, e.g. 7.
+    pub java_specification_version: u16,
+    /// The class file major versions the VM loads.
+    pub class_file_versions: RangeInclusive<u16>,
+}
+
+impl Capabilities {
+    pub(crate) fn current() -> Self {
+        return Self {
+            vm_name: VM_NAME,
+            vm_version: VM_VERSION,
+            java_specification_version: JAVA_SPECIFICATION_VERSION,
+            class_file_versions: MIN_CLASS_FILE_VERSION..=MAX_CLASS_FILE_VERSION,
+        };
+    }
+
+    /// `java.specification.version` as the class library spells it, `1.<n>`
+    /// up to Java SE 8, e.g. `1.7`, and `<n>` from Java SE 9 on.
+    pub fn specification_version_name(&self) -> String {
+        return if self.java_specification_version <= 8 {
+            format!("1.{}", self.java_specification_version)
+        } else {
+            self.java_specification_version.to_string()
+        };
+    }
+
+    /// `java.class.version`, the class file version of the specification
+    /// level rather than the newest one the parser takes, which the class
+    /// library could not back.
+    pub fn class_version_name(&self) -> String {
+        return format!("{}.0", self.java_specification_version + 44);
+    }
+
+    /// The platform properties the VM puts into the system properties,
+    /// `java.version` and `java.runtime.*` come from the class library.
+    pub fn system_properties(&self) -> Vec<(&'static str, String)> {
+        let specification_version = self.specification_version_name();
+        return vec![
+            ("java.class.version", self.class_version_name()),
+            (
+                "java.specification.name",
+                "Java Platform API Specification".to_string(),
+            ),
+            (
+                "java.specification.vendor",
+                "Oracle Corporation".to_string(),
+            ),
+            ("java.specification.version", specification_version.clone()),
+            ("java.vm.info", "interpreted mode".to_string()),
+            ("java.vm.name", self.vm_name.to_string()),
+            (
+                "java.vm.specification.name",
+                "Java Virtual Machine Specification".to_string(),
+            ),
+            (
+                "java.vm.specification.vendor",
+                "Oracle Corporation".to_string(),
+            ),
+            ("java.vm.specification.version", specification_version),
+            ("java.vm.version", self.vm_version.to_string()),
+        ];
+    }
+}
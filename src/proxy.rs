@@ -0,0 +1,249 @@
+//! Java objects whose interface methods are implemented by Rust closures.
+//!
+//! [`VM::implement`] generates a class implementing the requested interface
+//! with every method declared `native`. Those methods are linked to
+//! [`proxy_trampoline`], which the interpreter recognizes and routes to the
+//! closure registered for the proxy class instead of calling it.
+
+use std::{
+    collections::HashMap,
+    ffi::c_void,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use parking_lot::RwLock;
+
+use crate::{
+    convert::{self, FromJValue},
+    memory::Address,
+    object::{method::MethodPtr, prelude::JClassPtr, symbol::SymbolPtr, Object},
+    thread::Thread,
+    value::JValue,
+    vm::{VMError, VM},
+    ObjectPtr,
+};
+
+pub type ProxyCallback = dyn Fn(&ProxyCall) -> JValue + Send + Sync;
+
+/// An interface method call trapped by a proxy object.
+pub struct ProxyCall<'a> {
+    vm: &'a VM,
+    this: ObjectPtr,
+    name: SymbolPtr,
+    descriptor: SymbolPtr,
+    args: &'a [JValue],
+}
+
+impl<'a> ProxyCall<'a> {
+    pub fn vm(&self) -> &VM {
+        return self.vm;
+    }
+
+    pub fn this(&self) -> ObjectPtr {
+        return self.this;
+    }
+
+    pub fn method_name(&self) -> &str {
+        return self.name.as_str();
+    }
+
+    pub fn descriptor(&self) -> &str {
+        return self.descriptor.as_str();
+    }
+
+    /// The raw arguments, one `JValue` per parameter.
+    pub fn args(&self) -> &[JValue] {
+        return self.args;
+    }
+
+    /// Converts the argument at `index` according to the method descriptor.
+    pub fn arg<T: FromJValue>(&self, index: usize) -> Result<T, VMError> {
+        let (param_types, _) = convert::split_method_descriptor(self.descriptor(), self.vm)?;
+        let (ty, value) = match (param_types.get(index), self.args.get(index)) {
+            (Some(ty), Some(value)) => (ty, value),
+            _ => {
+                return Err(VMError::RuntimeError(format!(
+                    "{} has no argument {}",
+                    self.method_name(),
+                    index
+                )))
+            }
+        };
+        return T::from_jvalue(JValue::with_long_val(value.long_val()), ty, self.vm);
+    }
+}
+
+/// Native entry linked to proxy methods, the interpreter dispatches them to
+/// their closure before this is ever called.
+extern "system" fn proxy_trampoline() {
+    unreachable!("proxy methods are dispatched by the interpreter");
+}
+
+pub(crate) fn is_proxy_method(method: MethodPtr) -> bool {
+    return method.native_fn() == trampoline_address();
+}
+
+fn trampoline_address() -> Address {
+    return Address::from_c_ptr(proxy_trampoline as *mut c_void);
+}
+
+#[derive(Default)]
+pub(crate) struct ProxyRegistry {
+    next_id: AtomicUsize,
+    callbacks: RwLock<HashMap<usize, Arc<ProxyCallback>>>,
+}
+
+impl ProxyRegistry {
+    pub(crate) fn implement(
+        &self,
+        interface_name: &str,
+        callback: Arc<ProxyCallback>,
+        vm: &VM,
+    ) -> Result<ObjectPtr, VMError> {
+        let thread = Thread::current();
+        let interface = vm
+            .bootstrap_class_loader
+            .load_binary_name_class(interface_name)?;
+        if !interface.class_data().is_interface() {
+            return Err(VMError::RuntimeError(format!(
+                "{} is not an interface",
+                interface.name().as_str()
+            )));
+        }
+        let mut methods = Vec::new();
+        Self::collect_methods(interface, &mut methods);
+
+        let class_name = format!(
+            "rsvm/proxy/$Proxy{}",
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        );
+        let class_bytes = Self::class_bytes(&class_name, interface.name().as_str(), &methods);
+        let class = vm
+            .bootstrap_class_loader
+            .define_class(class_bytes, thread)?;
+        class.initialize(thread)?;
+
+        let class_methods = class.class_data().methods();
+        for idx in 0..class_methods.length() {
+            let mut method: MethodPtr = class_methods.get(idx).cast();
+            method.set_native_fn(trampoline_address());
+        }
+        self.callbacks.write().insert(class.as_usize(), callback);
+        return Ok(Object::new(class, thread));
+    }
+
+    pub(crate) fn dispatch(
+        &self,
+        this: ObjectPtr,
+        method: MethodPtr,
+        args: &[JValue],
+        vm: &VM,
+    ) -> JValue {
+        let callback = self
+            .callbacks
+            .read()
+            .get(&this.jclass().as_usize())
+            .cloned()
+            .expect("proxy class without callback");
+        return callback(&ProxyCall {
+            vm,
+            this,
+            name: method.name(),
+            descriptor: method.descriptor(),
+            args,
+        });
+    }
+
+    /// Abstract methods of `interface` and its superinterfaces as
+    /// (name, descriptor) pairs.
+    fn collect_methods(interface: JClassPtr, methods: &mut Vec<(String, String)>) {
+        let class_data = interface.class_data();
+        let iface_methods = class_data.methods();
+        for idx in 0..iface_methods.length() {
+            let method: MethodPtr = iface_methods.get(idx).cast();
+            if method.is_static() || !method.is_abstract() {
+                continue;
+            }
+            let entry = (
+                method.name().as_str().to_string(),
+                method.descriptor().as_str().to_string(),
+            );
+            if !methods.contains(&entry) {
+                methods.push(entry);
+            }
+        }
+        let interfaces = class_data.interfaces();
+        if interfaces.is_not_null() {
+            for idx in 0..interfaces.length() {
+                Self::collect_methods(interfaces.get(idx).cast(), methods);
+            }
+        }
+    }
+
+    /// A class file for `public final class <class_name> implements <interface_name>`
+    /// declaring each method as `public native`.
+    fn class_bytes(
+        class_name: &str,
+        interface_name: &str,
+        methods: &[(String, String)],
+    ) -> Vec<u8> {
+        const ACC_PUBLIC_FINAL_SUPER: u16 = 0x0031;
+        const ACC_PUBLIC_NATIVE: u16 = 0x0101;
+        const CONSTANT_UTF8: u8 = 1;
+        const CONSTANT_CLASS: u8 = 7;
+
+        fn put_u16(bytes: &mut Vec<u8>, value: u16) {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        fn put_utf8(bytes: &mut Vec<u8>, value: &str) {
+            bytes.push(CONSTANT_UTF8);
+            put_u16(bytes, value.len() as u16);
+            bytes.extend_from_slice(value.as_bytes());
+        }
+        fn put_class(bytes: &mut Vec<u8>, name_index: u16) {
+            bytes.push(CONSTANT_CLASS);
+            put_u16(bytes, name_index);
+        }
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&0xCAFEBABEu32.to_be_bytes());
+        put_u16(&mut bytes, 0);
+        put_u16(&mut bytes, 51);
+
+        // #1/#2 this class, #3/#4 super class, #5/#6 interface, then a
+        // name and a descriptor per method
+        put_u16(&mut bytes, 7 + 2 * methods.len() as u16);
+        put_utf8(&mut bytes, class_name);
+        put_class(&mut bytes, 1);
+        put_utf8(&mut bytes, "java/lang/Object");
+        put_class(&mut bytes, 3);
+        put_utf8(&mut bytes, interface_name);
+        put_class(&mut bytes, 5);
+        for (name, descriptor) in methods {
+            put_utf8(&mut bytes, name);
+            put_utf8(&mut bytes, descriptor);
+        }
+
+        put_u16(&mut bytes, ACC_PUBLIC_FINAL_SUPER);
+        put_u16(&mut bytes, 2);
+        put_u16(&mut bytes, 4);
+        put_u16(&mut bytes, 1);
+        put_u16(&mut bytes, 6);
+        // fields
+        put_u16(&mut bytes, 0);
+        put_u16(&mut bytes, methods.len() as u16);
+        for idx in 0..methods.len() as u16 {
+            put_u16(&mut bytes, ACC_PUBLIC_NATIVE);
+            put_u16(&mut bytes, 7 + 2 * idx);
+            put_u16(&mut bytes, 8 + 2 * idx);
+            // attributes
+            put_u16(&mut bytes, 0);
+        }
+        // attributes
+        put_u16(&mut bytes, 0);
+        return bytes;
+    }
+}
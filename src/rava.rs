@@ -7,12 +7,30 @@ use rsvm::{
 };
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    after_help = "VM flags are set with -XX:+<flag>, -XX:-<flag> or -XX:<flag>=<value>, \
+                  -XX:+PrintFlagsFinal prints them all."
+)]
 struct Cli {
     /// Class search path of directories and jar files
     #[arg(short, long)]
     class_path: Option<String>,
 
+    /// Print every loaded class and where it was loaded from
+    #[arg(long)]
+    verbose_class: bool,
+
+    /// Print a histogram of the heap by class when the main method returns
+    #[arg(long)]
+    histo: bool,
+
+    /// Print the bytecode of the main class like `javap -c` instead of running it
+    #[arg(long)]
+    disassemble: bool,
+
     /// The main class
     main_class: String,
 }
@@ -20,12 +38,28 @@ struct Cli {
 fn main() {
     env_logger::init();
 
-    let cli = Cli::parse();
+    // clap cannot take `-XX:` options, they are split off before it parses
+    let (flags, args): (Vec<String>, Vec<String>) =
+        std::env::args().partition(|arg| arg.starts_with("-XX:"));
+    let cli = Cli::parse_from(args);
     let mut cfg = VMConfig::default();
     if let Some(cp) = cli.class_path {
         cfg.set_class_path(&cp);
     };
+    cfg.verbose_class = cli.verbose_class;
+    let mut print_flags = false;
+    for flag in &flags {
+        if flag == "-XX:+PrintFlagsFinal" {
+            print_flags = true;
+        } else if let Err(e) = cfg.set_flag(flag) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
     let mut vm = VM::new(&cfg);
+    if print_flags {
+        vm.print_flags();
+    }
 
     let thread = std::thread::Builder::new()
         .stack_size(4 * 1024 * 1024)
@@ -35,6 +69,17 @@ fn main() {
 
             let main_class = cli.main_class.as_str();
 
+            if cli.disassemble {
+                match vm.disassemble(main_class) {
+                    Ok(disassembly) => print!("{}", disassembly),
+                    Err(e) => {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+
             let class = vm
                 .bootstrap_class_loader
                 .load_binary_name_class(main_class)
@@ -45,8 +90,36 @@ fn main() {
                 .unwrap();
             let args = JArray::new_obj_arr(1, Thread::current());
             vm.call_static_void(class, method, &[JValue::with_obj_val(args.cast())]);
+
+            if cli.histo {
+                print_class_histogram(&vm);
+            }
+
+            #[cfg(feature = "interpreter-stats")]
+            vm.print_interpreter_stats(&mut std::io::stderr().lock())
+                .unwrap();
         })
         .unwrap();
 
     thread.join().unwrap();
 }
+
+fn print_class_histogram(vm: &VM) {
+    let histogram = vm.class_histogram();
+    println!(" num     #instances         #bytes  class name");
+    println!("----------------------------------------------");
+    for (num, entry) in histogram.iter().enumerate() {
+        println!(
+            "{:>4}: {:>14} {:>14}  {}",
+            num + 1,
+            entry.instances,
+            entry.bytes,
+            entry.class_name
+        );
+    }
+    println!(
+        "Total {:>14} {:>14}",
+        histogram.iter().map(|entry| entry.instances).sum::<usize>(),
+        histogram.iter().map(|entry| entry.bytes).sum::<usize>()
+    );
+}
@@ -0,0 +1,37 @@
+use crate::object::method::MethodPtr;
+
+/// The number of calls a [`CallTrace`] keeps.
+pub(crate) const CALL_TRACE_SIZE: usize = 32;
+
+/// The methods a thread invoked last, in a ring buffer overwritten by every
+/// call so that recording costs no more than a store.
+pub(crate) struct CallTrace {
+    methods: [MethodPtr; CALL_TRACE_SIZE],
+    next: usize,
+}
+
+impl CallTrace {
+    pub(crate) fn new() -> Self {
+        return Self {
+            methods: [MethodPtr::null(); CALL_TRACE_SIZE],
+            next: 0,
+        };
+    }
+
+    #[inline(always)]
+    pub(crate) fn record(&mut self, method: MethodPtr) {
+        self.methods[self.next] = method;
+        self.next = (self.next + 1) % CALL_TRACE_SIZE;
+    }
+
+    /// The recorded methods, the oldest first.
+    pub(crate) fn methods(&self) -> Vec<MethodPtr> {
+        let (newer, older) = self.methods.split_at(self.next);
+        return older
+            .iter()
+            .chain(newer)
+            .copied()
+            .filter(|method| method.is_not_null())
+            .collect();
+    }
+}
@@ -0,0 +1,716 @@
+//! Renders the bytecode of loaded methods like `javap -c`, with the constant
+//! pool operands of instructions resolved to the classes, members and
+//! constants they name.
+
+use std::fmt::{self, Write};
+
+use crate::{
+    convert,
+    object::{
+        class::JClassPtr,
+        constant_pool::{ConstantPoolPtr, ConstantTag},
+        method::{MethodAccessFlags, MethodPtr},
+        symbol::SymbolPtr,
+    },
+    thread::Thread,
+};
+
+/// The operands following an opcode.
+#[derive(Clone, Copy)]
+enum Operands {
+    Nothing,
+    /// A signed byte, bipush.
+    Byte,
+    /// A signed short, sipush.
+    Short,
+    /// A local variable index, two bytes after wide.
+    Local,
+    /// A one byte constant pool index, ldc.
+    Constant,
+    /// A two byte constant pool index.
+    WideConstant,
+    InvokeInterface,
+    InvokeDynamic,
+    MultiANewArray,
+    /// A local variable index and a signed increment, both two bytes after
+    /// wide.
+    IInc,
+    Branch,
+    WideBranch,
+    NewArray,
+    TableSwitch,
+    LookupSwitch,
+    Wide,
+}
+
+use Operands::*;
+
+/// The mnemonics and operands of the opcodes 0x00 to 0xc9 (jvms-6.5).
+const OPCODES: [(&str, Operands); 0xca] = [
+    ("nop", Nothing),
+    ("aconst_null", Nothing),
+    ("iconst_m1", Nothing),
+    ("iconst_0", Nothing),
+    ("iconst_1", Nothing),
+    ("iconst_2", Nothing),
+    ("iconst_3", Nothing),
+    ("iconst_4", Nothing),
+    ("iconst_5", Nothing),
+    ("lconst_0", Nothing),
+    ("lconst_1", Nothing),
+    ("fconst_0", Nothing),
+    ("fconst_1", Nothing),
+    ("fconst_2", Nothing),
+    ("dconst_0", Nothing),
+    ("dconst_1", Nothing),
+    ("bipush", Byte),
+    ("sipush", Short),
+    ("ldc", Constant),
+    ("ldc_w", WideConstant),
+    ("ldc2_w", WideConstant),
+    ("iload", Local),
+    ("lload", Local),
+    ("fload", Local),
+    ("dload", Local),
+    ("aload", Local),
+    ("iload_0", Nothing),
+    ("iload_1", Nothing),
+    ("iload_2", Nothing),
+    ("iload_3", Nothing),
+    ("lload_0", Nothing),
+    ("lload_1", Nothing),
+    ("lload_2", Nothing),
+    ("lload_3", Nothing),
+    ("fload_0", Nothing),
+    ("fload_1", Nothing),
+    ("fload_2", Nothing),
+    ("fload_3", Nothing),
+    ("dload_0", Nothing),
+    ("dload_1", Nothing),
+    ("dload_2", Nothing),
+    ("dload_3", Nothing),
+    ("aload_0", Nothing),
+    ("aload_1", Nothing),
+    ("aload_2", Nothing),
+    ("aload_3", Nothing),
+    ("iaload", Nothing),
+    ("laload", Nothing),
+    ("faload", Nothing),
+    ("daload", Nothing),
+    ("aaload", Nothing),
+    ("baload", Nothing),
+    ("caload", Nothing),
+    ("saload", Nothing),
+    ("istore", Local),
+    ("lstore", Local),
+    ("fstore", Local),
+    ("dstore", Local),
+    ("astore", Local),
+    ("istore_0", Nothing),
+    ("istore_1", Nothing),
+    ("istore_2", Nothing),
+    ("istore_3", Nothing),
+    ("lstore_0", Nothing),
+    ("lstore_1", Nothing),
+    ("lstore_2", Nothing),
+    ("lstore_3", Nothing),
+    ("fstore_0", Nothing),
+    ("fstore_1", Nothing),
+    ("fstore_2", Nothing),
+    ("fstore_3", Nothing),
+    ("dstore_0", Nothing),
+    ("dstore_1", Nothing),
+    ("dstore_2", Nothing),
+    ("dstore_3", Nothing),
+    ("astore_0", Nothing),
+    ("astore_1", Nothing),
+    ("astore_2", Nothing),
+    ("astore_3", Nothing),
+    ("iastore", Nothing),
+    ("lastore", Nothing),
+    ("fastore", Nothing),
+    ("dastore", Nothing),
+    ("aastore", Nothing),
+    ("bastore", Nothing),
+    ("castore", Nothing),
+    ("sastore", Nothing),
+    ("pop", Nothing),
+    ("pop2", Nothing),
+    ("dup", Nothing),
+    ("dup_x1", Nothing),
+    ("dup_x2", Nothing),
+    ("dup2", Nothing),
+    ("dup2_x1", Nothing),
+    ("dup2_x2", Nothing),
+    ("swap", Nothing),
+    ("iadd", Nothing),
+    ("ladd", Nothing),
+    ("fadd", Nothing),
+    ("dadd", Nothing),
+    ("isub", Nothing),
+    ("lsub", Nothing),
+    ("fsub", Nothing),
+    ("dsub", Nothing),
+    ("imul", Nothing),
+    ("lmul", Nothing),
+    ("fmul", Nothing),
+    ("dmul", Nothing),
+    ("idiv", Nothing),
+    ("ldiv", Nothing),
+    ("fdiv", Nothing),
+    ("ddiv", Nothing),
+    ("irem", Nothing),
+    ("lrem", Nothing),
+    ("frem", Nothing),
+    ("drem", Nothing),
+    ("ineg", Nothing),
+    ("lneg", Nothing),
+    ("fneg", Nothing),
+    ("dneg", Nothing),
+    ("ishl", Nothing),
+    ("lshl", Nothing),
+    ("ishr", Nothing),
+    ("lshr", Nothing),
+    ("iushr", Nothing),
+    ("lushr", Nothing),
+    ("iand", Nothing),
+    ("land", Nothing),
+    ("ior", Nothing),
+    ("lor", Nothing),
+    ("ixor", Nothing),
+    ("lxor", Nothing),
+    ("iinc", IInc),
+    ("i2l", Nothing),
+    ("i2f", Nothing),
+    ("i2d", Nothing),
+    ("l2i", Nothing),
+    ("l2f", Nothing),
+    ("l2d", Nothing),
+    ("f2i", Nothing),
+    ("f2l", Nothing),
+    ("f2d", Nothing),
+    ("d2i", Nothing),
+    ("d2l", Nothing),
+    ("d2f", Nothing),
+    ("i2b", Nothing),
+    ("i2c", Nothing),
+    ("i2s", Nothing),
+    ("lcmp", Nothing),
+    ("fcmpl", Nothing),
+    ("fcmpg", Nothing),
+    ("dcmpl", Nothing),
+    ("dcmpg", Nothing),
+    ("ifeq", Branch),
+    ("ifne", Branch),
+    ("iflt", Branch),
+    ("ifge", Branch),
+    ("ifgt", Branch),
+    ("ifle", Branch),
+    ("if_icmpeq", Branch),
+    ("if_icmpne", Branch),
+    ("if_icmplt", Branch),
+    ("if_icmpge", Branch),
+    ("if_icmpgt", Branch),
+    ("if_icmple", Branch),
+    ("if_acmpeq", Branch),
+    ("if_acmpne", Branch),
+    ("goto", Branch),
+    ("jsr", Branch),
+    ("ret", Local),
+    ("tableswitch", TableSwitch),
+    ("lookupswitch", LookupSwitch),
+    ("ireturn", Nothing),
+    ("lreturn", Nothing),
+    ("freturn", Nothing),
+    ("dreturn", Nothing),
+    ("areturn", Nothing),
+    ("return", Nothing),
+    ("getstatic", WideConstant),
+    ("putstatic", WideConstant),
+    ("getfield", WideConstant),
+    ("putfield", WideConstant),
+    ("invokevirtual", WideConstant),
+    ("invokespecial", WideConstant),
+    ("invokestatic", WideConstant),
+    ("invokeinterface", InvokeInterface),
+    ("invokedynamic", InvokeDynamic),
+    ("new", WideConstant),
+    ("newarray", NewArray),
+    ("anewarray", WideConstant),
+    ("arraylength", Nothing),
+    ("athrow", Nothing),
+    ("checkcast", WideConstant),
+    ("instanceof", WideConstant),
+    ("monitorenter", Nothing),
+    ("monitorexit", Nothing),
+    ("wide", Wide),
+    ("multianewarray", MultiANewArray),
+    ("ifnull", Branch),
+    ("ifnonnull", Branch),
+    ("goto_w", WideBranch),
+    ("jsr_w", WideBranch),
+];
+
+/// The mnemonic and operands of `opcode`, including the reserved opcodes.
+fn opcode(opcode: u8) -> Option<(&'static str, Operands)> {
+    return match opcode {
+        0xca => Some(("breakpoint", Nothing)),
+        0xfe => Some(("impdep1", Nothing)),
+        0xff => Some(("impdep2", Nothing)),
+        _ => OPCODES.get(opcode as usize).copied(),
+    };
+}
+
+/// Writes every method of `class`, like `javap -c` does.
+pub(crate) fn disassemble_class(class: JClassPtr, out: &mut dyn Write) -> fmt::Result {
+    let class_data = class.class_data();
+    let class_name = class.binary_name();
+    let mut modifiers = class_data.modifiers();
+    let kind = if class_data.is_interface() {
+        // interfaces are abstract by definition, javap leaves it out
+        modifiers &= !(MethodAccessFlags::AccAbstract as u16);
+        "interface"
+    } else {
+        "class"
+    };
+    writeln!(
+        out,
+        "{}{} {} {{",
+        modifier_names(modifiers),
+        kind,
+        class_name
+    )?;
+    let methods = class_data.methods();
+    for idx in 0..methods.length() {
+        if idx > 0 {
+            writeln!(out)?;
+        }
+        disassemble_method(methods.get(idx).cast(), out)?;
+    }
+    return writeln!(out, "}}");
+}
+
+/// Writes the declaration of `method` and its code and exception table,
+/// native and abstract methods have only the declaration.
+pub(crate) fn disassemble_method(method: MethodPtr, out: &mut dyn Write) -> fmt::Result {
+    writeln!(out, "  {};", declaration(method))?;
+    if method.is_native() || method.is_abstract() {
+        return Ok(());
+    }
+    writeln!(out, "    Code:")?;
+    let code = unsafe { std::slice::from_raw_parts(method.code(), method.code_length() as usize) };
+    let this_class = method.decl_cls().name();
+    let mut pc = 0;
+    while pc < code.len() {
+        match instruction(code, pc, method.cp(), this_class, out)? {
+            Some(next_pc) => pc = next_pc,
+            None => {
+                writeln!(out, "{:>8}: <truncated>", pc)?;
+                break;
+            }
+        }
+    }
+    return exception_table(method, out);
+}
+
+/// The declaration of `method` the way javap prints it, e.g.
+/// `public static int max(int, int)`.
+fn declaration(method: MethodPtr) -> String {
+    let name = method.name();
+    if name.as_str() == "<clinit>" {
+        return "static {}".to_string();
+    }
+    let mut declaration = modifier_names(method.access_flags());
+    let descriptor = method.descriptor();
+    let (params, ret) =
+        match convert::split_method_descriptor(descriptor.as_str(), Thread::current().vm()) {
+            Ok(split) => split,
+            Err(_) => return declaration + name.as_str() + descriptor.as_str(),
+        };
+    if name.as_str() == "<init>" {
+        declaration += &method.decl_cls().binary_name();
+    } else {
+        declaration += &java_type_name(ret);
+        declaration += " ";
+        declaration += name.as_str();
+    }
+    let params: Vec<String> = params.into_iter().map(java_type_name).collect();
+    return format!("{}({})", declaration, params.join(", "));
+}
+
+/// The names of the modifiers among `access_flags`, each followed by a
+/// space, in the order of `java.lang.reflect.Modifier.toString`.
+fn modifier_names(access_flags: u16) -> String {
+    const MODIFIERS: [(MethodAccessFlags, &str); 8] = [
+        (MethodAccessFlags::AccPublic, "public"),
+        (MethodAccessFlags::AccProtected, "protected"),
+        (MethodAccessFlags::AccPrivate, "private"),
+        (MethodAccessFlags::AccAbstract, "abstract"),
+        (MethodAccessFlags::AccStatic, "static"),
+        (MethodAccessFlags::AccFinal, "final"),
+        (MethodAccessFlags::AccSynchronized, "synchronized"),
+        (MethodAccessFlags::AccNative, "native"),
+    ];
+    let mut names = String::new();
+    for (flag, name) in MODIFIERS {
+        if access_flags & flag as u16 != 0 {
+            names += name;
+            names += " ";
+        }
+    }
+    return names;
+}
+
+/// The Java type of the field descriptor `descriptor`, e.g. `java.lang.String[]`
+/// for `[Ljava/lang/String;`.
+fn java_type_name(descriptor: &str) -> String {
+    let element = descriptor.trim_start_matches('[');
+    let dimensions = descriptor.len() - element.len();
+    let name = match element {
+        "B" => "byte",
+        "C" => "char",
+        "D" => "double",
+        "F" => "float",
+        "I" => "int",
+        "J" => "long",
+        "S" => "short",
+        "Z" => "boolean",
+        "V" => "void",
+        _ => element.trim_start_matches('L').trim_end_matches(';'),
+    };
+    return name.replace('/', ".") + &"[]".repeat(dimensions);
+}
+
+fn read_u1(code: &[u8], offset: usize) -> Option<u8> {
+    return code.get(offset).copied();
+}
+
+fn read_u2(code: &[u8], offset: usize) -> Option<u16> {
+    return Some(u16::from_be_bytes([
+        read_u1(code, offset)?,
+        read_u1(code, offset + 1)?,
+    ]));
+}
+
+fn read_i4(code: &[u8], offset: usize) -> Option<i32> {
+    let bytes = code.get(offset..offset + 4)?;
+    return Some(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+}
+
+/// Writes the instruction at `pc` and returns the pc of the next one, or
+/// `None` if the instruction is cut off by the end of the code.
+fn instruction(
+    code: &[u8],
+    pc: usize,
+    cp: ConstantPoolPtr,
+    this_class: SymbolPtr,
+    out: &mut dyn Write,
+) -> Result<Option<usize>, fmt::Error> {
+    let (mnemonic, operands) = match opcode(code[pc]) {
+        Some(opcode) => opcode,
+        None => {
+            writeln!(out, "{:>8}: <unknown opcode 0x{:02x}>", pc, code[pc])?;
+            return Ok(Some(pc + 1));
+        }
+    };
+    let line = |out: &mut dyn Write, operand: String| -> fmt::Result {
+        return writeln!(out, "{:>8}: {:<13} {}", pc, mnemonic, operand);
+    };
+    let cp_line = |out: &mut dyn Write, operand: String, index: u16| -> fmt::Result {
+        let operand = format!("{:<19} // {}", operand, constant(cp, this_class, index));
+        return writeln!(out, "{:>8}: {:<13} {}", pc, mnemonic, operand);
+    };
+    macro_rules! read {
+        ($read: ident, $offset: expr) => {
+            match $read(code, $offset) {
+                Some(value) => value,
+                None => return Ok(None),
+            }
+        };
+    }
+    let next_pc = match operands {
+        Nothing => {
+            writeln!(out, "{:>8}: {}", pc, mnemonic)?;
+            pc + 1
+        }
+        Byte => {
+            line(out, (read!(read_u1, pc + 1) as i8).to_string())?;
+            pc + 2
+        }
+        Short => {
+            line(out, (read!(read_u2, pc + 1) as i16).to_string())?;
+            pc + 3
+        }
+        Local => {
+            line(out, read!(read_u1, pc + 1).to_string())?;
+            pc + 2
+        }
+        Constant => {
+            let index = read!(read_u1, pc + 1) as u16;
+            cp_line(out, format!("#{}", index), index)?;
+            pc + 2
+        }
+        WideConstant => {
+            let index = read!(read_u2, pc + 1);
+            cp_line(out, format!("#{}", index), index)?;
+            pc + 3
+        }
+        InvokeInterface => {
+            let index = read!(read_u2, pc + 1);
+            let count = read!(read_u1, pc + 3);
+            cp_line(out, format!("#{},  {}", index, count), index)?;
+            pc + 5
+        }
+        InvokeDynamic => {
+            let index = read!(read_u2, pc + 1);
+            cp_line(out, format!("#{},  0", index), index)?;
+            pc + 5
+        }
+        MultiANewArray => {
+            let index = read!(read_u2, pc + 1);
+            let dimensions = read!(read_u1, pc + 3);
+            cp_line(out, format!("#{},  {}", index, dimensions), index)?;
+            pc + 4
+        }
+        IInc => {
+            let index = read!(read_u1, pc + 1);
+            let increment = read!(read_u1, pc + 2) as i8;
+            line(out, format!("{}, {}", index, increment))?;
+            pc + 3
+        }
+        Branch => {
+            let offset = read!(read_u2, pc + 1) as i16;
+            line(out, (pc as i64 + offset as i64).to_string())?;
+            pc + 3
+        }
+        WideBranch => {
+            let offset = read!(read_i4, pc + 1);
+            line(out, (pc as i64 + offset as i64).to_string())?;
+            pc + 5
+        }
+        NewArray => {
+            let atype = read!(read_u1, pc + 1);
+            let element = match atype {
+                4 => "boolean",
+                5 => "char",
+                6 => "float",
+                7 => "double",
+                8 => "byte",
+                9 => "short",
+                10 => "int",
+                11 => "long",
+                _ => "<unknown>",
+            };
+            line(out, element.to_string())?;
+            pc + 2
+        }
+        TableSwitch => {
+            // the operands start at the next multiple of four
+            let operands_pc = (pc + 4) & !3;
+            let default = read!(read_i4, operands_pc);
+            let low = read!(read_i4, operands_pc + 4);
+            let high = read!(read_i4, operands_pc + 8);
+            if high < low {
+                return Ok(None);
+            }
+            let count = (high as i64 - low as i64 + 1) as usize;
+            let mut targets = Vec::with_capacity(count);
+            for idx in 0..count {
+                let offset = read!(read_i4, operands_pc + 12 + idx * 4);
+                targets.push((low as i64 + idx as i64, offset));
+            }
+            line(out, format!("{{ // {} to {}", low, high))?;
+            switch_targets(pc, &targets, default, out)?;
+            operands_pc + 12 + count * 4
+        }
+        LookupSwitch => {
+            let operands_pc = (pc + 4) & !3;
+            let default = read!(read_i4, operands_pc);
+            let count = read!(read_i4, operands_pc + 4).max(0) as usize;
+            let mut targets = Vec::with_capacity(count);
+            for idx in 0..count {
+                let pair_pc = operands_pc + 8 + idx * 8;
+                targets.push((read!(read_i4, pair_pc) as i64, read!(read_i4, pair_pc + 4)));
+            }
+            line(out, format!("{{ // {}", count))?;
+            switch_targets(pc, &targets, default, out)?;
+            operands_pc + 8 + count * 8
+        }
+        Wide => {
+            let (modified, _) = match opcode(read!(read_u1, pc + 1)) {
+                Some(opcode) => opcode,
+                None => return Ok(None),
+            };
+            let index = read!(read_u2, pc + 2);
+            if modified == "iinc" {
+                let increment = read!(read_u2, pc + 4) as i16;
+                let operand = format!("{}, {}", index, increment);
+                writeln!(out, "{:>8}: {:<13} {}", pc, "wide iinc", operand)?;
+                pc + 6
+            } else {
+                let mnemonic = format!("wide {}", modified);
+                writeln!(out, "{:>8}: {:<13} {}", pc, mnemonic, index)?;
+                pc + 4
+            }
+        }
+    };
+    return Ok(Some(next_pc));
+}
+
+/// Writes the cases of a tableswitch or lookupswitch at `pc` with their
+/// absolute targets.
+fn switch_targets(
+    pc: usize,
+    targets: &[(i64, i32)],
+    default: i32,
+    out: &mut dyn Write,
+) -> fmt::Result {
+    for (key, offset) in targets {
+        writeln!(out, "{:>22}: {}", key, pc as i64 + *offset as i64)?;
+    }
+    writeln!(out, "{:>22}: {}", "default", pc as i64 + default as i64)?;
+    return writeln!(out, "          }}");
+}
+
+/// The constant pool entry at `index` the way javap comments it, e.g.
+/// `Method java/lang/Object."<init>":()V`.
+fn constant(cp: ConstantPoolPtr, this_class: SymbolPtr, index: u16) -> String {
+    if index == 0 || index >= cp.length() {
+        return format!("<invalid constant #{}>", index);
+    }
+    // members of the class itself are not qualified
+    let member = |kind: &str, class_name: &str, name: &str, descriptor: &str| -> String {
+        let name = quote_special_name(name);
+        if class_name == this_class.as_str() {
+            return format!("{} {}:{}", kind, name, descriptor);
+        }
+        return format!("{} {}.{}:{}", kind, class_name, name, descriptor);
+    };
+    return match cp.get_tag(index) {
+        ConstantTag::Class => {
+            let class_name = cp.get_class_name(index);
+            if class_name.as_str().starts_with('[') {
+                format!("class \"{}\"", class_name.as_str())
+            } else {
+                format!("class {}", class_name.as_str())
+            }
+        }
+        ConstantTag::Fieldref => {
+            let field = cp.get_field_ref(index);
+            member(
+                "Field",
+                field.class_name.as_str(),
+                field.member_name.as_str(),
+                field.member_desc.as_str(),
+            )
+        }
+        ConstantTag::Methodref => {
+            let method = cp.get_method_ref(index);
+            member(
+                "Method",
+                method.class_name.as_str(),
+                method.member_name.as_str(),
+                method.member_desc.as_str(),
+            )
+        }
+        ConstantTag::InterfaceMethodref => {
+            let method = cp.get_interface_method_ref(index);
+            member(
+                "InterfaceMethod",
+                method.class_name.as_str(),
+                method.member_name.as_str(),
+                method.member_desc.as_str(),
+            )
+        }
+        ConstantTag::String => format!("String {}", escape(cp.get_string(index).as_str())),
+        ConstantTag::Integer => format!("int {}", cp.get_int32(index)),
+        ConstantTag::Float => format!("float {:?}f", cp.get_float(index)),
+        ConstantTag::Long => format!("long {}l", cp.get_long(index)),
+        ConstantTag::Double => format!("double {:?}d", cp.get_double(index)),
+        ConstantTag::MethodType => format!("MethodType {}", cp.get_method_type(index).as_str()),
+        ConstantTag::MethodHandle => {
+            let (ref_kind, referenced) = cp.get_method_handle(index);
+            member(
+                &format!("MethodHandle {}", reference_kind_name(ref_kind)),
+                referenced.class_name.as_str(),
+                referenced.member_name.as_str(),
+                referenced.member_desc.as_str(),
+            )
+        }
+        ConstantTag::InvokeDynamic => {
+            let (bootstrap_method, name, descriptor) = cp.get_invoke_dynamic(index);
+            format!(
+                "InvokeDynamic #{}:{}:{}",
+                bootstrap_method,
+                quote_special_name(name.as_str()),
+                descriptor.as_str()
+            )
+        }
+        tag => format!("<{:?} constant #{}>", tag, index),
+    };
+}
+
+/// Quotes `<init>` and `<clinit>`, as javap does.
+fn quote_special_name(name: &str) -> String {
+    if name.starts_with('<') {
+        return format!("\"{}\"", name);
+    }
+    return name.to_string();
+}
+
+/// The name of a method handle reference kind (jvms-4.4.8).
+fn reference_kind_name(ref_kind: u8) -> &'static str {
+    return match ref_kind {
+        1 => "REF_getField",
+        2 => "REF_getStatic",
+        3 => "REF_putField",
+        4 => "REF_putStatic",
+        5 => "REF_invokeVirtual",
+        6 => "REF_invokeStatic",
+        7 => "REF_invokeSpecial",
+        8 => "REF_newInvokeSpecial",
+        9 => "REF_invokeInterface",
+        _ => "REF_unknown",
+    };
+}
+
+/// Escapes the control characters of a string constant, keeping it on one
+/// line.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\n' => escaped += "\\n",
+            '\r' => escaped += "\\r",
+            '\t' => escaped += "\\t",
+            '\\' => escaped += "\\\\",
+            ch if ch.is_control() => escaped += &format!("\\u{:04x}", ch as u32),
+            ch => escaped.push(ch),
+        }
+    }
+    return escaped;
+}
+
+fn exception_table(method: MethodPtr, out: &mut dyn Write) -> fmt::Result {
+    let ex_tab_length = method.ex_tab_length();
+    if ex_tab_length == 0 {
+        return Ok(());
+    }
+    let cp = method.cp();
+    writeln!(out, "    Exception table:")?;
+    writeln!(out, "       from    to  target type")?;
+    for idx in 0..ex_tab_length {
+        let entry = unsafe { &*method.ex_tab().as_raw_ptr().add(idx as usize) };
+        let catch_type = if entry.catch_type == 0 {
+            "any".to_string()
+        } else {
+            format!("Class {}", cp.get_class_name(entry.catch_type).as_str())
+        };
+        writeln!(
+            out,
+            "{:>12} {:>5} {:>5}   {}",
+            entry.start_pc, entry.end_pc, entry.handler_pc, catch_type
+        )?;
+    }
+    return Ok(());
+}
@@ -68,6 +68,63 @@ macro_rules! label {
     };
 }
 
+/// Loads the `$bits` wide value at `$addr`, zero-extended to a `u64`, with
+/// the instruction at the label `$label`, so that a fault of the load is known
+/// by the address of the faulting instruction.
+#[macro_export]
+macro_rules! load_at_label {
+    ( $label:expr, 8, $addr:expr ) => {
+        $crate::load_at_label!(@load $label, "movzx {:e}, byte ptr [{}]", "ldrb {:w}, [{}]", $addr)
+    };
+    ( $label:expr, 16, $addr:expr ) => {
+        $crate::load_at_label!(@load $label, "movzx {:e}, word ptr [{}]", "ldrh {:w}, [{}]", $addr)
+    };
+    ( $label:expr, 32, $addr:expr ) => {
+        $crate::load_at_label!(@load $label, "mov {:e}, dword ptr [{}]", "ldr {:w}, [{}]", $addr)
+    };
+    ( $label:expr, 64, $addr:expr ) => {
+        $crate::load_at_label!(@load $label, "mov {}, qword ptr [{}]", "ldr {}, [{}]", $addr)
+    };
+    ( @load $label:expr, $x86_64:expr, $aarch64:expr, $addr:expr ) => {
+        unsafe {
+            use core::arch::asm;
+
+            let value: u64;
+
+            #[cfg(target_arch = "x86_64")]
+            #[allow(named_asm_labels)]
+            {
+                asm!(
+                    concat!($label, ":"),
+                    $x86_64,
+                    out(reg) value,
+                    in(reg) $addr,
+                    options(nostack, readonly, preserves_flags)
+                );
+            }
+
+            #[cfg(target_arch = "aarch64")]
+            #[allow(named_asm_labels)]
+            {
+                asm!(
+                    concat!($label, ":"),
+                    $aarch64,
+                    out(reg) value,
+                    in(reg) $addr,
+                    options(nostack, readonly, preserves_flags)
+                );
+            }
+
+            #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
+            {
+                compile_error!("load_at_label not implemented");
+            }
+
+            value
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! label_addr {
     ( $label_name:expr) => {
@@ -80,7 +137,7 @@ macro_rules! label_addr {
             {
                 asm!(concat!("lea {:r}, [rip +", $label_name, "]"), out(reg) _x);
             }
-            #[cfg(any(target_arch = "aarch64"))]
+            #[cfg(all(target_arch = "aarch64", target_vendor = "apple"))]
             {
                 asm!(
                     concat!("adrp {tmp}, ", $label_name, "@PAGE"),
@@ -88,6 +145,15 @@ macro_rules! label_addr {
                     tmp = out(reg) _x,
                 );
             }
+            // ELF and COFF spell the page offset relocation as :lo12:
+            #[cfg(all(target_arch = "aarch64", not(target_vendor = "apple")))]
+            {
+                asm!(
+                    concat!("adrp {tmp}, ", $label_name),
+                    concat!("add {tmp}, {tmp}, :lo12:", $label_name),
+                    tmp = out(reg) _x,
+                );
+            }
 
             #[cfg(not(any(target_arch = "x86", target_arch = "aarch64", target_arch = "x86_64")))]
             {
@@ -192,12 +258,12 @@ mod tests {
         assert_ne!(next, 0);
         goto_label_addr(next);
 
-        assert!(false);
+        assert!(std::hint::black_box(false));
 
         label!("next");
         goto_label!("last");
 
-        assert!(false);
+        assert!(std::hint::black_box(false));
 
         label!("last");
 
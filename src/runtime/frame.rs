@@ -1,6 +1,11 @@
 use crate::{
     handle::HandleScope,
-    object::{class::JClassPtr, method::MethodPtr, prelude::Ptr},
+    object::{
+        class::JClassPtr,
+        constant_pool::ConstantPoolPtr,
+        method::MethodPtr,
+        prelude::{ObjectPtr, Ptr},
+    },
     thread::ThreadPtr,
 };
 
@@ -12,6 +17,8 @@ pub struct Frame {
     prev: FramePtr,
     frame_slots: isize,
     is_java_top: bool,
+    /// The object a synchronized method locked on entry, null otherwise.
+    monitor: ObjectPtr,
     _scope: HandleScope,
 }
 
@@ -31,6 +38,7 @@ impl Frame {
             prev,
             frame_slots,
             is_java_top,
+            monitor: ObjectPtr::null(),
             _scope: scope,
         })));
     }
@@ -51,6 +59,12 @@ impl Frame {
         self.method
     }
 
+    /// The constant pool of the executing method.
+    #[inline]
+    pub fn cp(&self) -> ConstantPoolPtr {
+        self.method.cp()
+    }
+
     #[inline]
     pub fn prev(&self) -> FramePtr {
         self.prev
@@ -70,4 +84,14 @@ impl Frame {
     pub fn is_java_top(&self) -> bool {
         self.is_java_top
     }
+
+    /// The object to unlock when the frame returns.
+    #[inline]
+    pub fn monitor(&self) -> ObjectPtr {
+        self.monitor
+    }
+
+    pub fn set_monitor(&mut self, monitor: ObjectPtr) {
+        self.monitor = monitor;
+    }
 }
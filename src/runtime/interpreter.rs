@@ -1,7 +1,12 @@
 use std::convert::TryFrom;
 
+use crate::classfile::descriptor;
+use crate::execution::{CancelReason, ExecutionBudget, ExecutionControl};
 use crate::value::JValue;
-use crate::{goto_label_addr, label, label_addr, load_reserved_value, reserve_value};
+use crate::{
+    goto_label, goto_label_addr, label, label_addr, load_at_label, load_reserved_value,
+    reserve_value,
+};
 
 use crate::{
     memory::Address,
@@ -11,19 +16,27 @@ use crate::{
             JIntArrayPtr, JLongArrayPtr, JShortArrayPtr,
         },
         class::{JClass, JClassPtr},
-        constant_pool::ConstantTag,
+        constant_pool::{ConstMemberRef, ConstantTag, ResolvedStaticField},
+        field::{FieldKind, FieldPtr},
         method::MethodPtr,
         prelude::{JByte, JChar, JDouble, JFloat, JInt, JLong, JShort, ObjectPtr},
         symbol::SymbolPtr,
         Object,
     },
+    os, proxy,
     thread::{Thread, ThreadPtr},
     vm::VMPtr,
 };
 
 use paste::paste;
 
+use super::call_trace::CallTrace;
+use super::frame::FramePtr;
+use super::native_call;
+#[cfg(feature = "interpreter-stats")]
+use super::op_stats::MethodCounters;
 use super::stack::{Stack, StackPrimitiveValue};
+use super::stack_walker::StackFrameInfo;
 
 macro_rules! jvm_instructions {
     (enum $name:ident {
@@ -362,8 +375,9 @@ macro_rules! case_label_val_store {
 
             let interp = access_interpreter!();
             let index = $index;
-            let val = interp.stack.pop_jobj();
-            interp.stack.store_jobj(val, Interpreter::num2isize(index));
+            // the value may be a return address pushed by jsr
+            let val = interp.stack.pop_slot();
+            interp.stack.store_slot(val, Interpreter::num2isize(index));
             dispatch!(interp);
         }
     }};
@@ -381,16 +395,13 @@ macro_rules! case_label_val_store {
 }
 
 macro_rules! case_label_num_arithmetic {
-    ($op_code:ident, $val_ty: ty, $arith_op: tt, $divide_check: expr) => {{
+    ($op_code:ident, $val_ty: ty, $arith_op: tt) => {{
         paste! {
             [<case_label_ $op_code>]!();
 
             let interp = access_interpreter!();
             let val2 = interp.stack.pop::<$val_ty>();
             let val1 = interp.stack.pop::<$val_ty>();
-            if $divide_check && val2 == $val_ty::from(0u8) {
-                todo!("throw ArithmeticException");
-            }
             interp.stack.push::<$val_ty>(val1 $arith_op val2);
             dispatch!(interp);
         }
@@ -398,22 +409,38 @@ macro_rules! case_label_num_arithmetic {
 }
 
 macro_rules! case_label_num_diff_types_arithmetic {
-    ($op_code:ident, $val1_ty: ty, $val2_ty: ty, $arith_op: tt, $divide_check: expr) => {{
+    ($op_code:ident, $val1_ty: ty, $val2_ty: ty, $arith_op: tt) => {{
         paste! {
             [<case_label_ $op_code>]!();
 
             let interp = access_interpreter!();
             let val2 = interp.stack.pop::<$val2_ty>();
             let val1 = interp.stack.pop::<$val1_ty>();
-            if $divide_check && val2 == $val2_ty::from(0u8) {
-                todo!("throw ArithmeticException");
-            }
             interp.stack.push::<$val1_ty>(val1 $arith_op val2);
             dispatch!(interp);
         }
     }};
 }
 
+macro_rules! case_label_int_division {
+    ($op_code:ident, $val_ty: ty, $wrapping_op: ident) => {{
+        paste! {
+            [<case_label_ $op_code>]!();
+
+            let interp = access_interpreter!();
+            let val2 = interp.stack.pop::<$val_ty>();
+            let val1 = interp.stack.pop::<$val_ty>();
+            if val2 == 0 {
+                todo!("throw ArithmeticException: / by zero");
+            }
+            // the minimum divided by -1 overflows back to the minimum, and
+            // its remainder is 0
+            interp.stack.push::<$val_ty>(val1.$wrapping_op(val2));
+            dispatch!(interp);
+        }
+    }};
+}
+
 macro_rules! case_label_num_convert {
     ($op_code:ident, $val_ty: ty, $to_val_ty:ty, $stack_ty: ty) => {{
         paste! {
@@ -427,6 +454,30 @@ macro_rules! case_label_num_convert {
     }};
 }
 
+macro_rules! case_label_float_to_int {
+    ($op_code:ident, $val_ty: ty, $to_val_ty: ty) => {{
+        paste! {
+            [<case_label_ $op_code>]!();
+
+            let interp = access_interpreter!();
+            let val = interp.stack.pop::<$val_ty>();
+            // NaN is 0, values beyond the integer are its bounds and the
+            // rest round toward zero
+            let result = if val.is_nan() {
+                0
+            } else if val <= $to_val_ty::MIN as $val_ty {
+                $to_val_ty::MIN
+            } else if val >= $to_val_ty::MAX as $val_ty {
+                $to_val_ty::MAX
+            } else {
+                val.trunc() as $to_val_ty
+            };
+            interp.stack.push::<$to_val_ty>(result);
+            dispatch!(interp);
+        }
+    }};
+}
+
 macro_rules! case_label_num_load {
     ($op_code:ident, $val_ty: ty, $( $n:literal ),+) => {{
         paste! {
@@ -481,7 +532,10 @@ macro_rules! case_label_num_if_cmp {
                 let branch = interp.read_operand_i16();
                 let val2: $val_ty = interp.stack.$val2_pop();
                 let val1: $val_ty = interp.stack.$val1_pop();
-                if val1 $arith_op val2 {
+                let taken = val1 $arith_op val2;
+                #[cfg(feature = "interpreter-stats")]
+                interp.op_counters().count_branch(taken);
+                if taken {
                     interp.goto(if_op_addr, branch);
                 }
                 dispatch!(interp);
@@ -497,7 +551,10 @@ macro_rules! case_label_num_if_cmp {
                 let branch = interp.read_operand_i16();
                 let val2: $val_ty = interp.stack.$val2_pop();
                 let val1: $val_ty = $val1;
-                if val2 $arith_op val1 {
+                let taken = val2 $arith_op val1;
+                #[cfg(feature = "interpreter-stats")]
+                interp.op_counters().count_branch(taken);
+                if taken {
                     interp.goto(if_op_addr, branch);
                 }
                 dispatch!(interp);
@@ -506,24 +563,39 @@ macro_rules! case_label_num_if_cmp {
     }};
 }
 
+// these take the interpreter, as the reserved register holding it may be
+// reused once an instruction has started
 macro_rules! do_num_load {
-    ($val_ty: ty, $index: expr) => {{
-        let interp = access_interpreter!();
-        let val = interp.stack.load::<$val_ty>(Interpreter::num2isize($index));
-        interp.stack.push::<$val_ty>(val);
+    ($interp: ident, $val_ty: ty, $index: expr) => {{
+        let val = $interp
+            .stack
+            .load::<$val_ty>(Interpreter::num2isize($index));
+        $interp.stack.push::<$val_ty>(val);
     }};
 }
 
 macro_rules! do_num_store {
-    ($val_ty: ty, $index: expr) => {{
-        let interp = access_interpreter!();
-        let val = interp.stack.pop::<$val_ty>();
-        interp.stack.store(val, Interpreter::num2isize($index));
+    ($interp: ident, $val_ty: ty, $index: expr) => {{
+        let val = $interp.stack.pop::<$val_ty>();
+        $interp.stack.store(val, Interpreter::num2isize($index));
     }};
 }
 
+/// Counts the instruction about to run and leaves it for the unwinding of
+/// the frames when the execution of the thread is cancelled, see
+/// [`ExecutionControl`].
+macro_rules! poll_execution {
+    ($interp: expr) => {
+        if $interp.execution.tick() && $interp.execution.poll() {
+            reserve_value!($interp as *mut Self as usize);
+            goto_label!("__vm_cancelled");
+        }
+    };
+}
+
 macro_rules! dispatch {
     ($interp: expr) => {
+        poll_execution!($interp);
         let target_addr;
         unsafe {
             let op_code = *$interp.pc.raw_ptr();
@@ -534,6 +606,8 @@ macro_rules! dispatch {
                 $interp.pc.raw_ptr()
             );
             target_addr = OP_CODE_TABLE[usize::from(op_code)];
+            #[cfg(feature = "interpreter-stats")]
+            $interp.op_counters().count_op(op_code);
         }
         $interp.pc = $interp.pc.offset(std::mem::size_of::<u8>() as isize);
         reserve_value!($interp as *mut Self as usize);
@@ -553,38 +627,266 @@ macro_rules! access_interpreter {
 }
 
 const OP_CODE_TABLE_SIZE: usize = 256;
+/// The address of the code of each instruction, filled in once per process.
+/// `label_addr!` computes the addresses relative to the program counter, so
+/// they are those of the image as loaded, wherever ASLR or the loader of a
+/// `cdylib` placed it.
 static mut OP_CODE_TABLE: [u64; OP_CODE_TABLE_SIZE] = [0; OP_CODE_TABLE_SIZE];
 
+/// The addresses of the loads of `getfield` and `invokevirtual` that are
+/// their null checks with `VMConfig::implicit_null_checks`.
+static mut NULL_CHECK_LOADS: [u64; 5] = [0; 5];
+/// The address of the code that unwinds a cancelled execution.
+static mut CANCELLED_ADDR: u64 = 0;
+
+fn init_op_code_table() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        JvmInstruction::init_instructions_table(unsafe { &mut OP_CODE_TABLE });
+        init_fault_resume_addrs();
+    });
+}
+
+/// Kept out of the closure of `init_op_code_table`, which is not always
+/// compiled along with the labels of `Interpreter::execute`.
+#[inline(never)]
+fn init_fault_resume_addrs() {
+    let loads = [
+        label_addr!("__vm_getfield_load8"),
+        label_addr!("__vm_getfield_load16"),
+        label_addr!("__vm_getfield_load32"),
+        label_addr!("__vm_getfield_load64"),
+        label_addr!("__vm_invokevirtual_load_class"),
+    ];
+    let cancelled = label_addr!("__vm_cancelled");
+    unsafe {
+        NULL_CHECK_LOADS = loads;
+        CANCELLED_ADDR = cancelled;
+    }
+}
+
+/// Handles a fault of one of the loads that are null checks, which read page
+/// zero for a null object. The execution of the thread is cancelled with
+/// `CancelReason::NullPointer` and resumed where cancelled executions unwind,
+/// as if the instruction had polled the cancellation.
+pub(crate) fn handle_null_access_fault(fault_addr: Address, ctx: &mut os::FaultContext) -> bool {
+    if fault_addr.as_usize() >= os::page_size() {
+        return false;
+    }
+    let is_null_check = ctx.pc().map_or(false, |pc| unsafe {
+        NULL_CHECK_LOADS.contains(&(pc as u64))
+    });
+    let thread = Thread::current();
+    if !is_null_check || thread.is_null() {
+        return false;
+    }
+    let interp = thread.as_mut_ref().interpreter_mut();
+    interp.cancel_execution(CancelReason::NullPointer);
+    return ctx.resume_at(
+        unsafe { CANCELLED_ADDR } as usize,
+        interp as *mut Interpreter as usize,
+    );
+}
+
 pub struct Interpreter {
     thread: ThreadPtr,
     stack: Stack,
     pc: Address,
     vm: VMPtr,
+    calls: CallTrace,
+    execution: ExecutionControl,
+    #[cfg(feature = "interpreter-stats")]
+    counted_method: MethodPtr,
+    #[cfg(feature = "interpreter-stats")]
+    counters: *const MethodCounters,
 }
 
 impl Interpreter {
     pub fn new(stack_addr: Address, stack_size: usize, vm: VMPtr) -> Interpreter {
-        JvmInstruction::init_instructions_table(unsafe { &mut OP_CODE_TABLE });
+        init_op_code_table();
         let stack = Stack::new(stack_addr, stack_size);
         return Interpreter {
             thread: ThreadPtr::null(),
             stack,
             pc: Address::null(),
             vm,
+            calls: CallTrace::new(),
+            execution: ExecutionControl::new(),
+            #[cfg(feature = "interpreter-stats")]
+            counted_method: MethodPtr::null(),
+            #[cfg(feature = "interpreter-stats")]
+            counters: std::ptr::null(),
         };
     }
 
-    pub fn grand_parent_stack_class(&self) -> JClassPtr {
+    pub(crate) fn stack(&self) -> &Stack {
+        &self.stack
+    }
+
+    /// The frames of this thread, the top one first.
+    pub(crate) fn stack_frames(&self) -> Vec<StackFrameInfo> {
+        let mut frames = Vec::new();
+        self.stack.walk_frames(self.pc, |frame, pc| {
+            frames.push(StackFrameInfo::at_pc(frame.method(), pc));
+        });
+        return frames;
+    }
+
+    /// Limits the Java code the thread runs from now on.
+    pub(crate) fn set_execution_budget(&mut self, budget: ExecutionBudget) {
+        self.execution.set_budget(budget);
+    }
+
+    pub(crate) fn cancel_execution(&mut self, reason: CancelReason) {
+        self.execution.cancel(reason);
+    }
+
+    /// Cancels the Java code of the thread at its next poll, safe to call
+    /// from any thread.
+    pub(crate) fn interrupt_execution(&self) {
+        self.execution.interrupt();
+    }
+
+    /// Why the last call into the interpreter was cancelled. Only the call
+    /// that returns to a thread without Java frames clears the cancellation,
+    /// the calls of natives in between see it as well and return right away.
+    pub(crate) fn take_cancellation(&mut self) -> Option<CancelReason> {
+        if self.stack.frame().is_not_null() {
+            return self.execution.cancelled();
+        }
+        return self.execution.clear_cancelled();
+    }
+
+    /// Pops the frames up to the frame the interpreter was called with, the
+    /// native or Rust code that called it gets a zero result. The stack guard
+    /// a stack overflow disarmed is rearmed once the frames are gone.
+    fn unwind_cancelled(&mut self) -> JValue {
+        loop {
+            let is_top_java_frame = self.stack.is_top_java_frame();
+            self.restore_invoker_frame();
+            if is_top_java_frame {
+                self.thread.stack_memory().rearm_guard();
+                return JValue::default();
+            }
+        }
+    }
+
+    /// The methods this thread invoked last, the oldest first.
+    pub(crate) fn recent_calls(&self) -> Vec<MethodPtr> {
+        return self.calls.methods();
+    }
+
+    /// An array for `newarray` and `anewarray`, see
+    /// `VMConfig::share_empty_arrays`.
+    fn new_array(&self, count: JInt, array_cls: JClassPtr) -> JArrayPtr {
+        if count == 0 && self.vm.cfg.share_empty_arrays {
+            return JArray::empty(array_cls, self.thread);
+        }
+        return JArray::new(count, array_cls, self.thread);
+    }
+
+    /// Whether an access `offset` bytes into an object has to check the object
+    /// for null. With `VMConfig::implicit_null_checks` the access itself is
+    /// the check if it lands in the protected page zero, see
+    /// `handle_null_access_fault`.
+    #[inline(always)]
+    fn needs_null_check(&self, offset: usize) -> bool {
+        return !self.vm.cfg.implicit_null_checks
+            || !os::CAN_RESUME_FAULTS_ELSEWHERE
+            || offset >= os::page_size();
+    }
+
+    /// The class that called the `@CallerSensitive` method that called the
+    /// current native, skipping reflection frames, see
+    /// `sun.reflect.Reflection.getCallerClass()`. `None` if the caller of the
+    /// native is not caller sensitive, null if the stack has no such class.
+    pub(crate) fn caller_sensitive_caller_class(&self) -> Option<JClassPtr> {
         let frame = self.stack.frame();
-        if frame.is_not_null() && frame.has_prev() {
-            let frame = frame.prev();
-            if frame.is_not_null() && frame.has_prev() {
-                return frame.prev().class();
+        if frame.is_null() || !frame.has_prev() {
+            return None;
+        }
+        let caller_sensitive = frame.prev();
+        if !caller_sensitive.method().is_caller_sensitive() {
+            return None;
+        }
+        let mut frame = caller_sensitive.prev();
+        while frame.is_not_null() {
+            if !Self::is_reflection_frame(frame) {
+                return Some(frame.method().decl_cls());
+            }
+            frame = frame.prev();
+        }
+        return Some(JClassPtr::null());
+    }
+
+    /// The class of the method `depth` frames down the stack, skipping
+    /// reflection frames, where 0 is the current native, see
+    /// `sun.reflect.Reflection.getCallerClass0(int)`. Null if the stack is not
+    /// that deep.
+    pub(crate) fn caller_class_at(&self, depth: usize) -> JClassPtr {
+        let mut depth = depth;
+        let mut frame = self.stack.frame();
+        while frame.is_not_null() {
+            if !Self::is_reflection_frame(frame) {
+                if depth == 0 {
+                    return frame.method().decl_cls();
+                }
+                depth -= 1;
             }
+            frame = frame.prev();
         }
         return JClassPtr::null();
     }
 
+    /// The classes of the Java methods on the stack from the top down,
+    /// skipping native and reflection frames, see
+    /// `SecurityManager.getClassContext()`. With `privileged` the walk ends at
+    /// the caller of the first `AccessController.doPrivileged`, as the action
+    /// runs with the permissions of that caller alone.
+    pub(crate) fn class_context(&self, privileged: bool) -> Vec<JClassPtr> {
+        let mut classes = Vec::new();
+        let mut in_privileged = false;
+        let mut frame = self.stack.frame();
+        while frame.is_not_null() {
+            let method = frame.method();
+            if privileged && Self::is_privileged_frame(frame) {
+                in_privileged = true;
+            } else if method.is_not_native() && !Self::is_reflection_frame(frame) {
+                classes.push(method.decl_cls());
+                if in_privileged {
+                    break;
+                }
+            }
+            frame = frame.prev();
+        }
+        return classes;
+    }
+
+    /// Whether `frame` runs `AccessController.doPrivileged`.
+    fn is_privileged_frame(frame: FramePtr) -> bool {
+        let method = frame.method();
+        return method.name().as_str() == "doPrivileged"
+            && method.decl_cls().name().as_str() == "java/security/AccessController";
+    }
+
+    /// Whether `frame` runs `Method.invoke` or a method accessor behind it,
+    /// which callers looking for the class that invoked a method skip.
+    fn is_reflection_frame(frame: FramePtr) -> bool {
+        let method = frame.method();
+        let class = method.decl_cls();
+        if class.name().as_str() == "java/lang/reflect/Method" {
+            return method.name().as_str() == "invoke";
+        }
+        let mut class = class;
+        while class.is_not_null() {
+            if class.name().as_str() == "sun/reflect/MethodAccessorImpl" {
+                return true;
+            }
+            class = class.class_data().super_class();
+        }
+        return false;
+    }
+
     pub fn call_static_method(
         class: JClassPtr,
         method: MethodPtr,
@@ -593,31 +895,18 @@ impl Interpreter {
     ) -> JValue {
         let interp = thread.as_mut_ref().interpreter_mut();
         interp.thread = thread;
-        let params_len = method.params().length();
-        let args_slots = {
-            if params_len > 0 {
-                let mut args_slots = 0;
-                interp.prepare_args(ObjectPtr::null(), method, args, &mut args_slots);
-                args_slots
-            } else {
-                0
-            }
-        };
         log::trace!(
             "call_static_void_method {}#{} code {:?}",
             class.name().as_str(),
             method.name().as_str(),
             method.code()
         );
-        interp.invoke_method(
-            ObjectPtr::null(),
-            class,
-            method,
-            params_len as isize,
-            args_slots,
-            0,
-            true,
-        );
+        if !interp.enter_java_top(ObjectPtr::null(), class, method, args, 0) {
+            return JValue::default();
+        }
+        if method.is_native() {
+            return interp.pop_native_result(method);
+        }
         interp.pc = Address::new(method.code());
         return Self::execute(interp);
     }
@@ -630,26 +919,19 @@ impl Interpreter {
     ) {
         let interp = thread.as_mut_ref().interpreter_mut();
         interp.thread = thread;
-        let args_slots = {
-            let mut args_slots = 0;
-            interp.prepare_args(objref, method, args, &mut args_slots);
-            args_slots
-        };
         log::trace!(
             "call_static_void_method {}#{} code {:?}",
             objref.jclass().name().as_str(),
             method.name().as_str(),
             method.code()
         );
-        interp.invoke_method(
-            objref,
-            objref.jclass(),
-            method,
-            args.len() as isize,
-            args_slots,
-            1,
-            true,
-        );
+        if !interp.enter_java_top(objref, objref.jclass(), method, args, 1) {
+            return;
+        }
+        if method.is_native() {
+            interp.pop_native_result(method);
+            return;
+        }
         interp.pc = Address::new(method.code());
         Self::execute(interp);
     }
@@ -663,22 +945,58 @@ impl Interpreter {
         debug_assert!(method.decl_cls().is_not_null());
         let interp = thread.as_mut_ref().interpreter_mut();
         interp.thread = thread;
-        let args_slots = {
-            let mut args_slots = 0;
-            interp.prepare_args(objref, method, args, &mut args_slots);
-            args_slots
-        };
-        interp.invoke_method(
+        if !interp.enter_java_top(objref, method.decl_cls(), method, args, 1) {
+            return JValue::default();
+        }
+        if method.is_native() {
+            return interp.pop_native_result(method);
+        }
+        interp.pc = Address::new(method.code());
+        return Self::execute(interp);
+    }
+
+    /// Pushes `args` and the frame of `method` called from native or Rust
+    /// code, whether they fit on the stack. The execution of the thread is
+    /// cancelled with `CancelReason::StackOverflow` if they do not.
+    fn enter_java_top(
+        &mut self,
+        objref: ObjectPtr,
+        class: JClassPtr,
+        method: MethodPtr,
+        args: &[JValue],
+        obj_ref_size: isize,
+    ) -> bool {
+        let receiver_slots = if method.is_static() { 0 } else { 1 };
+        if !self.reserve_stack(receiver_slots + self.compute_args_slots(method)) {
+            return false;
+        }
+        let mut args_slots = 0;
+        self.prepare_args(objref, method, args, &mut args_slots);
+        if !self.invoke_method(
             objref,
-            method.decl_cls(),
+            class,
             method,
             args.len() as isize,
             args_slots,
-            1,
+            obj_ref_size,
             true,
-        );
-        interp.pc = Address::new(method.code());
-        return Self::execute(interp);
+        ) {
+            for _ in 0..args_slots {
+                self.stack.pop_slot();
+            }
+            return false;
+        }
+        return true;
+    }
+
+    /// Whether `slots` more slots fit on the stack, the execution of the
+    /// thread is cancelled with `CancelReason::StackOverflow` if they do not.
+    fn reserve_stack(&mut self, slots: isize) -> bool {
+        if self.stack.has_room(slots) {
+            return true;
+        }
+        self.cancel_execution(CancelReason::StackOverflow);
+        return false;
     }
 
     fn prepare_args(
@@ -688,29 +1006,29 @@ impl Interpreter {
         args: &[JValue],
         args_slots: &mut isize,
     ) {
-        let method_params = method.params();
-        debug_assert_eq!(method_params.length(), args.len() as JInt);
+        debug_assert_eq!(method.param_count(), args.len() as JInt);
         if !method.is_static() {
             *args_slots += 1;
             self.stack.push_jobj(objref);
         }
-        let vm = self.vm;
-        for param_index in 0..method_params.length() {
-            let param_class: JClassPtr = method_params.get(param_index).cast();
-            log::trace!("prepare_args param_class : 0x{:x}", param_class.as_isize());
-            if JClass::is_long(param_class, vm) || JClass::is_double(param_class, vm) {
-                let arg = unsafe { args.get_unchecked(param_index as usize).long_val() };
-                self.stack.push::<JLong>(arg);
-                *args_slots += 2;
-            } else if param_class.is_not_null() && JClass::is_primitive(param_class) {
-                let arg = unsafe { args.get_unchecked(param_index as usize).int_val() };
-                self.stack.push::<JInt>(arg);
-                *args_slots += 1;
-            } else {
-                let arg = unsafe { args.get_unchecked(param_index as usize).obj_val() };
-                self.stack.push_jobj(arg);
-                *args_slots += 1;
+        for param_index in 0..method.param_count() {
+            let arg = unsafe { args.get_unchecked(param_index as usize) };
+            // narrow values only initialize the low bytes of the JValue
+            match method.param_kind(param_index) {
+                FieldKind::Long | FieldKind::Double => {
+                    self.stack.push::<JLong>(arg.long_val());
+                    *args_slots += 2;
+                    continue;
+                }
+                FieldKind::Reference => self.stack.push_jobj(arg.obj_val()),
+                FieldKind::Char => self.stack.push::<JInt>(arg.char_val() as u16 as JInt),
+                FieldKind::Short => self.stack.push::<JInt>(arg.short_val() as JInt),
+                FieldKind::Byte | FieldKind::Boolean => {
+                    self.stack.push::<JInt>(arg.byte_val() as JInt)
+                }
+                FieldKind::Int | FieldKind::Float => self.stack.push::<JInt>(arg.int_val()),
             }
+            *args_slots += 1;
         }
     }
 
@@ -775,16 +1093,8 @@ impl Interpreter {
             if count < 0 {
                 todo!("throw NegativeArraySizeException");
             }
-            let index1 = u16::from(interp.read_operand());
-            let index2 = u16::from(interp.read_operand());
-            let cp_index = (index1 << 8) | index2;
-            let component_cls_name = interp
-                .stack
-                .frame()
-                .class()
-                .class_data()
-                .cp
-                .get_class_name(cp_index);
+            let cp_index = interp.read_operand_u16();
+            let component_cls_name = interp.stack.frame().cp().get_class_name(cp_index);
             if component_cls_name.is_null() {
                 todo!("Linking Exceptions")
             }
@@ -792,34 +1102,26 @@ impl Interpreter {
             //     .vm
             //     .bootstrap_class_loader
             //     .resolve_class(&format!("L{};", array_class_name.as_str()));
-            let component_class = interp
+            let array_class = interp
                 .vm
                 .bootstrap_class_loader
-                .load_class(component_cls_name.as_str());
-            if let Ok(component_class) = component_class {
-                let array_class = if component_class.class_data().is_array() {
-                    interp
-                        .vm
-                        .bootstrap_class_loader
-                        .load_class(&format!("[{}", component_cls_name.as_str()))
-                } else {
-                    interp
-                        .vm
-                        .bootstrap_class_loader
-                        .load_class(&format!("[L{};", component_cls_name.as_str()))
-                };
-                match array_class {
-                    Ok(array_cls) => {
-                        let arr = JArray::new(count, array_cls, interp.thread);
-                        // TODO
-                        interp.stack.push_jobj(arr.cast());
-                        dispatch!(interp);
-                    }
-                    Err(_e) => todo!("jvms-5.4.3.1. Class and Interface Resolution"),
-                };
-            } else {
-                todo!("jvms-5.4.3.1. Class and Interface Resolution")
-            }
+                .load_class(component_cls_name.as_str())
+                .and_then(|component_class| {
+                    interp.vm.bootstrap_class_loader.get_or_create_array_class(
+                        component_class,
+                        1,
+                        interp.thread,
+                    )
+                });
+            match array_class {
+                Ok(array_cls) => {
+                    let arr = interp.new_array(count, array_cls);
+                    // TODO
+                    interp.stack.push_jobj(arr.cast());
+                    dispatch!(interp);
+                }
+                Err(_e) => todo!("jvms-5.4.3.1. Class and Interface Resolution"),
+            };
         }
 
         case_label_areturn!();
@@ -870,7 +1172,7 @@ impl Interpreter {
         {
             let interp = access_interpreter!();
             log::trace!("bipush haha {}", interp.stack.stack_trace_str());
-            let val = JInt::from(interp.read_operand());
+            let val = JInt::from(interp.read_operand_i8());
             interp.stack.push(val);
             dispatch!(interp);
         }
@@ -882,10 +1184,9 @@ impl Interpreter {
         case_label_checkcast!();
         {
             let interp = access_interpreter!();
-            let index: u16 = u16::from(interp.read_operand());
-            let index = (index << 8) | u16::from(interp.read_operand());
-            let frame_class = interp.stack.frame().class();
-            let ref_cls_name = frame_class.class_data().cp.get_class_name(index);
+            let index = interp.read_operand_u16();
+            let cp = interp.stack.frame().cp();
+            let ref_cls_name = cp.get_class_name(index);
             let obj_ref = interp.stack.peek_jobj();
             if obj_ref.is_not_null() {
                 match interp
@@ -905,10 +1206,10 @@ impl Interpreter {
         }
 
         case_label_num_convert!(d2f, JDouble, JFloat, JFloat);
-        case_label_num_convert!(d2i, JDouble, JInt, JInt);
-        case_label_num_convert!(d2l, JDouble, JLong, JLong);
+        case_label_float_to_int!(d2i, JDouble, JInt);
+        case_label_float_to_int!(d2l, JDouble, JLong);
 
-        case_label_num_arithmetic!(dadd, JDouble, +, false);
+        case_label_num_arithmetic!(dadd, JDouble, +);
 
         case_label_array_load!(daload, JDoubleArrayPtr, JDouble, JDouble);
 
@@ -938,11 +1239,11 @@ impl Interpreter {
             dispatch!(interp);
         }
 
-        case_label_num_arithmetic!(ddiv, JDouble, /, true);
+        case_label_num_arithmetic!(ddiv, JDouble, /);
 
         case_label_num_load!(dload, JDouble, 0, 1, 2, 3);
 
-        case_label_num_arithmetic!(dmul, JDouble, *, false);
+        case_label_num_arithmetic!(dmul, JDouble, *);
 
         case_label_dneg!();
         {
@@ -952,7 +1253,9 @@ impl Interpreter {
             dispatch!(interp);
         }
 
-        case_label_num_arithmetic!(drem, JDouble, %, true);
+        // `%` of floats truncates the quotient like C's fmod, so the result
+        // takes the sign of the dividend and a zero divisor gives NaN
+        case_label_num_arithmetic!(drem, JDouble, %);
 
         case_label_dreturn!();
         {
@@ -973,7 +1276,7 @@ impl Interpreter {
         case_label_val_store!(dstore2, 2, JDouble);
         case_label_val_store!(dstore3, 3, JDouble);
 
-        case_label_num_arithmetic!(dsub, JDouble, -, false);
+        case_label_num_arithmetic!(dsub, JDouble, -);
 
         case_label_dup!();
         {
@@ -993,13 +1296,17 @@ impl Interpreter {
             dispatch!(interp);
         }
 
+        // the forms of category 2 values copy slots, a long or double is
+        // two slots with the value in the top one
         case_label_dupx2!();
         {
             let interp = access_interpreter!();
             let val1 = interp.stack.pop_slot();
-            let val2 = interp.stack.pop::<JLong>();
+            let val2 = interp.stack.pop_slot();
+            let val3 = interp.stack.pop_slot();
             interp.stack.push_slot(val1);
-            interp.stack.push(val2);
+            interp.stack.push_slot(val3);
+            interp.stack.push_slot(val2);
             interp.stack.push_slot(val1);
             dispatch!(interp);
         }
@@ -1007,39 +1314,50 @@ impl Interpreter {
         case_label_dup2!();
         {
             let interp = access_interpreter!();
-            let val = interp.stack.pop::<JLong>();
-            interp.stack.push(val);
-            interp.stack.push(val);
+            let val1 = interp.stack.pop_slot();
+            let val2 = interp.stack.pop_slot();
+            interp.stack.push_slot(val2);
+            interp.stack.push_slot(val1);
+            interp.stack.push_slot(val2);
+            interp.stack.push_slot(val1);
             dispatch!(interp);
         }
 
         case_label_dup2x1!();
         {
             let interp = access_interpreter!();
-            let val1 = interp.stack.pop::<JLong>();
+            let val1 = interp.stack.pop_slot();
             let val2 = interp.stack.pop_slot();
-            interp.stack.push(val1);
+            let val3 = interp.stack.pop_slot();
+            interp.stack.push_slot(val2);
+            interp.stack.push_slot(val1);
+            interp.stack.push_slot(val3);
             interp.stack.push_slot(val2);
-            interp.stack.push(val1);
+            interp.stack.push_slot(val1);
             dispatch!(interp);
         }
 
         case_label_dup2x2!();
         {
             let interp = access_interpreter!();
-            let val1 = interp.stack.pop::<JLong>();
-            let val2 = interp.stack.pop::<JLong>();
-            interp.stack.push(val1);
-            interp.stack.push(val2);
-            interp.stack.push(val1);
+            let val1 = interp.stack.pop_slot();
+            let val2 = interp.stack.pop_slot();
+            let val3 = interp.stack.pop_slot();
+            let val4 = interp.stack.pop_slot();
+            interp.stack.push_slot(val2);
+            interp.stack.push_slot(val1);
+            interp.stack.push_slot(val4);
+            interp.stack.push_slot(val3);
+            interp.stack.push_slot(val2);
+            interp.stack.push_slot(val1);
             dispatch!(interp);
         }
 
         case_label_num_convert!(f2d, JFloat, JDouble, JDouble);
-        case_label_num_convert!(f2i, JFloat, JInt, JInt);
-        case_label_num_convert!(f2l, JFloat, JLong, JLong);
+        case_label_float_to_int!(f2i, JFloat, JInt);
+        case_label_float_to_int!(f2l, JFloat, JLong);
 
-        case_label_num_arithmetic!(fadd, JFloat, +, false);
+        case_label_num_arithmetic!(fadd, JFloat, +);
 
         case_label_array_load!(faload, JFloatArrayPtr, JFloat, JFloat);
 
@@ -1076,11 +1394,11 @@ impl Interpreter {
             dispatch!(interp);
         }
 
-        case_label_num_arithmetic!(fdiv, JFloat, /, true);
+        case_label_num_arithmetic!(fdiv, JFloat, /);
 
         case_label_num_load!(fload, JFloat, 0, 1, 2, 3);
 
-        case_label_num_arithmetic!(fmul, JFloat, *, false);
+        case_label_num_arithmetic!(fmul, JFloat, *);
 
         case_label_fneg!();
         {
@@ -1090,7 +1408,7 @@ impl Interpreter {
             dispatch!(interp);
         }
 
-        case_label_num_arithmetic!(frem, JFloat, %, true);
+        case_label_num_arithmetic!(frem, JFloat, %);
 
         case_label_freturn!();
         {
@@ -1111,23 +1429,16 @@ impl Interpreter {
         case_label_val_store!(fstore2, 2, JFloat);
         case_label_val_store!(fstore3, 3, JFloat);
 
-        case_label_num_arithmetic!(fsub, JFloat, -, false);
+        case_label_num_arithmetic!(fsub, JFloat, -);
 
         case_label_getfield!(); // jvms-5.4.3.2
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8u16) | u16::from(interp.read_operand());
+            let index = interp.read_operand_u16();
             let obj = interp.stack.pop_jobj();
-            if obj.is_null() {
-                todo!(
-                    "throws NullPointerException \n {}",
-                    interp.stack.stack_trace_str()
-                );
-            }
             let frame_cls = interp.stack.frame().method().decl_cls();
-            let thread = Thread::current();
-            let field_ref = frame_cls.class_data().cp.get_field_ref(index);
+            let cp = interp.stack.frame().cp();
+            let field_ref = cp.get_field_ref(index);
             let field_lookup_cls: JClassPtr;
             if field_ref.class_name == frame_cls.name() {
                 field_lookup_cls = frame_cls;
@@ -1142,30 +1453,51 @@ impl Interpreter {
                     todo!();
                 }
             }
-            let (field, _) = field_lookup_cls.get_field(&field_ref);
-            let field_value = match field.get_value(obj, thread) {
-                Ok(field_value) => field_value,
-                Err(_e) => todo!(),
-            };
-            log::trace!(
-                "get field ====== {}.{}, obj: 0x{:x}, val: 0x{:x}, offset {}, stacktrace: {}",
-                field_lookup_cls.name().as_str(),
-                field_ref.member_name.as_str(),
-                obj.as_isize(),
-                field_value,
-                field.layout_offset(),
-                interp.stack.stack_trace_str(),
-            );
-            let field_cls = field.field_class_unchecked();
-            let vm = interp.vm;
-            if JClass::is_long(field_cls, vm) || JClass::is_double(field_cls, vm) {
-                interp.stack.push::<JLong>(field_value);
-            } else if JClass::is_primitive(field_cls) {
-                interp.stack.push::<JInt>(field_value as JInt);
-            } else {
-                interp
-                    .stack
-                    .push_jobj(ObjectPtr::from_isize(Interpreter::num2isize(field_value)));
+            if let Some((field, _)) = interp.lookup_field(field_lookup_cls, &field_ref) {
+                let field_value;
+                if interp.needs_null_check(field.layout_offset() as usize) || field.is_volatile() {
+                    if obj.is_null() {
+                        todo!(
+                            "throws NullPointerException \n {}",
+                            interp.stack.stack_trace_str()
+                        );
+                    }
+                    field_value = field.get_stack_value(obj);
+                } else {
+                    // the load is the null check, see `handle_null_access_fault`
+                    let addr = (obj.as_isize() + field.layout_offset() as isize) as *const u8;
+                    let bits = match field.kind() {
+                        FieldKind::Boolean | FieldKind::Byte => {
+                            load_at_label!("__vm_getfield_load8", 8, addr)
+                        }
+                        FieldKind::Char | FieldKind::Short => {
+                            load_at_label!("__vm_getfield_load16", 16, addr)
+                        }
+                        FieldKind::Int | FieldKind::Float => {
+                            load_at_label!("__vm_getfield_load32", 32, addr)
+                        }
+                        FieldKind::Long | FieldKind::Double | FieldKind::Reference => {
+                            load_at_label!("__vm_getfield_load64", 64, addr)
+                        }
+                    };
+                    // extended as `FieldAccess::get_stack_value` does
+                    field_value = match field.kind() {
+                        FieldKind::Byte => bits as i8 as i64,
+                        FieldKind::Short => bits as i16 as i64,
+                        FieldKind::Int => bits as i32 as i64,
+                        _ => bits as i64,
+                    };
+                }
+                log::trace!(
+                    "get field ====== {}.{}, obj: 0x{:x}, val: 0x{:x}, offset {}, stacktrace: {}",
+                    field_lookup_cls.name().as_str(),
+                    field_ref.member_name.as_str(),
+                    obj.as_isize(),
+                    field_value,
+                    field.layout_offset(),
+                    interp.stack.stack_trace_str(),
+                );
+                interp.push_field_value(field.kind(), field_value);
             }
             dispatch!(interp);
         }
@@ -1173,59 +1505,20 @@ impl Interpreter {
         case_label_getstatic!();
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8u16) | u16::from(interp.read_operand());
-            let frame_class = interp.stack.frame().class();
-            let field_ref = frame_class.class_data().cp.get_field_ref(index);
-            let vm = interp.vm;
-            if let Ok(_resolved_class) = vm
-                .bootstrap_class_loader
-                .load_class(field_ref.class_name.as_str())
+            let index = interp.read_operand_u16();
+            if let Some(ResolvedStaticField { decl_cls, access }) =
+                interp.resolve_static_field(index)
             {
-                let thread = Thread::current();
-                let (field, decl_cls) = _resolved_class.get_field(&field_ref);
-                match decl_cls.initialize(thread) {
-                    Ok(_) => {}
-                    Err(_) => todo!(),
-                }
-                let field_class = field.field_class_unchecked();
+                let value = access.get_stack_value(decl_cls.cast());
                 log::trace!(
-                    "getstatic {}#{} : cls 0x{:x}   success, offset: {}",
+                    "getstatic #{} of {} : cls 0x{:x}, val 0x{:x}, offset: {}",
+                    index,
                     decl_cls.name().as_str(),
-                    field.name().as_str(),
                     decl_cls.as_isize(),
-                    field.layout_offset()
+                    value,
+                    access.offset
                 );
-                if JClass::is_long(field_class, vm) || JClass::is_double(field_class, vm) {
-                    log::trace!(
-                        "getstatic {}#{} , val {}",
-                        decl_cls.name().as_str(),
-                        field.name().as_str(),
-                        field.get_static_value(decl_cls)
-                    );
-                    interp.stack.push::<JLong>(field.get_static_value(decl_cls));
-                } else if JClass::is_primitive(field_class) {
-                    interp
-                        .stack
-                        .push::<JInt>(field.get_static_value(decl_cls) as JInt);
-                } else {
-                    let value = field.get_static_value(decl_cls);
-                    log::trace!(
-                        "getstatic {}#{} : cls 0x{:x}, val 0x{:x?} success, offset: {}",
-                        decl_cls.name().as_str(),
-                        field.name().as_str(),
-                        decl_cls.as_isize(),
-                        value,
-                        field.layout_offset()
-                    );
-                    interp
-                        .stack
-                        .push_jobj(ObjectPtr::from_isize(Interpreter::num2isize(
-                            field.get_static_value(decl_cls),
-                        )));
-                }
-            } else {
-                todo!("throw ClassNotFoundException");
+                interp.push_field_value(access.kind, value);
             }
             dispatch!(interp);
         }
@@ -1247,17 +1540,18 @@ impl Interpreter {
         }
 
         case_label_num_convert!(i2b, JInt, JByte, JInt);
-        case_label_num_convert!(i2c, JInt, JChar, JInt);
+        // chars are unsigned, unlike JChar
+        case_label_num_convert!(i2c, JInt, u16, JInt);
         case_label_num_convert!(i2d, JInt, JDouble, JDouble);
         case_label_num_convert!(i2f, JInt, JFloat, JFloat);
         case_label_num_convert!(i2l, JInt, JLong, JLong);
         case_label_num_convert!(i2s, JInt, JShort, JInt);
 
-        case_label_num_arithmetic!(iadd, JInt, +, false);
+        case_label_num_arithmetic!(iadd, JInt, +);
 
         case_label_array_load!(iaload, JIntArrayPtr, JInt, JInt);
 
-        case_label_num_arithmetic!(iand, JInt, &, false);
+        case_label_num_arithmetic!(iand, JInt, &);
 
         case_label_array_store!(iastore, JIntArrayPtr, JInt, JInt);
 
@@ -1270,7 +1564,7 @@ impl Interpreter {
 
         case_label_num_const!(iconst, JInt, 0, 1, 2, 3, 4, 5);
 
-        case_label_num_arithmetic!(idiv, JInt, /, true);
+        case_label_int_division!(idiv, JInt, wrapping_div);
 
         case_label_num_if_cmp!(ifacmpeq, ObjectPtr, pop_jobj, ==, pop_jobj);
 
@@ -1297,7 +1591,7 @@ impl Interpreter {
         {
             let interp = access_interpreter!();
             let index = interp.read_operand();
-            let const_val = JInt::from(interp.read_op::<i8>());
+            let const_val = JInt::from(interp.read_operand_i8());
             log::trace!(
                 "iincc index {}, raw: {}, const_val: {}",
                 index,
@@ -1310,7 +1604,7 @@ impl Interpreter {
 
         case_label_num_load!(iload, JInt, 0, 1, 2, 3);
 
-        case_label_num_arithmetic!(imul, JInt, *, false);
+        case_label_num_arithmetic!(imul, JInt, *);
 
         case_label_ineg!();
         {
@@ -1323,15 +1617,14 @@ impl Interpreter {
         case_label_instanceof!();
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8) | u16::from(interp.read_operand());
+            let index = interp.read_operand_u16();
             let obj_ref = interp.stack.pop_jobj();
             if obj_ref.is_null() {
                 interp.stack.push::<JInt>(0);
                 dispatch!(interp);
             }
-            let frame_class = interp.stack.frame().class();
-            let target_class_name = frame_class.class_data().cp.get_class_name(index);
+            let cp = interp.stack.frame().cp();
+            let target_class_name = cp.get_class_name(index);
             if let Ok(target_class) = interp
                 .vm
                 .bootstrap_class_loader
@@ -1351,8 +1644,7 @@ impl Interpreter {
         case_label_invokedynamic!();
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8) | u16::from(interp.read_operand());
+            let index = interp.read_operand_u16();
             interp.read_operand();
             interp.read_operand();
             if index < 0xff {
@@ -1364,13 +1656,26 @@ impl Interpreter {
         case_label_invokeinterface!();
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8) | u16::from(interp.read_operand());
-            let args_slots = isize::from(interp.read_operand());
-            if args_slots <= 0 {
-                todo!("throw InvalidFormatException");
+            let index = interp.read_operand_u16();
+            let count = interp.read_operand();
+            let zero = interp.read_operand();
+            let cp = interp.stack.frame().cp();
+            let member_ref = cp.get_interface_method_ref(index);
+            // the count operand is redundant with the descriptor but must
+            // agree, checked before the receiver is read at that depth
+            let args_slots = descriptor::args_slots(member_ref.member_desc.as_bytes())
+                .map(|slots| 1 + Self::num2isize(slots));
+            if args_slots != Some(isize::from(count)) || zero != 0 {
+                interp.cancel_execution(CancelReason::LinkageError(format!(
+                    "ClassFormatError: invokeinterface count {} of {}.{}{}",
+                    count,
+                    member_ref.class_name.as_str(),
+                    member_ref.member_name.as_str(),
+                    member_ref.member_desc.as_str()
+                )));
+                dispatch!(interp);
             }
-            interp.read_operand();
+            let args_slots = isize::from(count);
             let objref = interp.stack.load_callee_objref(args_slots);
             if objref.is_null() {
                 todo!("throw NullPointerException");
@@ -1382,7 +1687,6 @@ impl Interpreter {
                 index,
                 objref.jclass().name().as_str()
             );
-            let member_ref = frame_class.class_data().cp.get_interface_method_ref(index);
             if let Ok(if_class) = interp
                 .vm
                 .bootstrap_class_loader
@@ -1393,6 +1697,7 @@ impl Interpreter {
                     if_class,
                     member_ref.member_name,
                     member_ref.member_desc,
+                    interp.vm.as_ref(),
                 ) {
                     Ok(resolved_method) => {
                         let target_method = resolved_method.method;
@@ -1415,7 +1720,7 @@ impl Interpreter {
                             objref,
                             objref.jclass(),
                             target_method,
-                            target_method.params().length() as isize,
+                            target_method.param_count() as isize,
                             args_slots,
                             1,
                             false,
@@ -1435,7 +1740,8 @@ impl Interpreter {
             let index = interp.read_operand_u16();
 
             let frame_class = interp.stack.frame().class();
-            let member_ref = frame_class.class_data().cp.get_method_ref(index);
+            let cp = interp.stack.frame().cp();
+            let member_ref = cp.get_method_ref(index);
             let (resolved_method, target_cls) = if member_ref.class_name == frame_class.name() {
                 match frame_class
                     .resolve_self_method(member_ref.member_name, member_ref.member_desc)
@@ -1472,8 +1778,8 @@ impl Interpreter {
                 target_cls.name().as_str(),
                 resolved_method.name().as_str()
             );
-            let args_count = isize::try_from(resolved_method.params().length()).unwrap();
-            let args_slots = 1 + interp.compute_args_slots(resolved_method, interp.vm);
+            let args_count = isize::try_from(resolved_method.param_count()).unwrap();
+            let args_slots = 1 + interp.compute_args_slots(resolved_method);
             let objref = interp.stack.load_callee_objref(args_slots);
             if objref.is_null() {
                 todo!("throw NullPointerException");
@@ -1493,9 +1799,9 @@ impl Interpreter {
         case_label_invokestatic!();
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8) | u16::from(interp.read_operand());
+            let index = interp.read_operand_u16();
             let frame_class = interp.stack.frame().class();
+            let cp = interp.stack.frame().cp();
             log::trace!(
                 "invokestatic {}#{}, index {}, stacktrace {}",
                 frame_class.name().as_str(),
@@ -1503,7 +1809,7 @@ impl Interpreter {
                 index,
                 interp.stack.stack_trace_str()
             );
-            let member_ref = frame_class.class_data().cp.get_method_ref(index);
+            let member_ref = cp.get_method_ref(index);
             if let Ok(target_class) = interp
                 .vm
                 .bootstrap_class_loader
@@ -1512,23 +1818,25 @@ impl Interpreter {
                 if target_class.class_data().is_interface() {
                     todo!("throw IncompatibleClassChangeError");
                 }
-                match target_class.initialize(Thread::current()) {
-                    Ok(_) => {}
-                    Err(_) => todo!(),
-                }
                 match target_class
-                    .resolve_self_method(member_ref.member_name, member_ref.member_desc)
+                    .resolve_static_method(member_ref.member_name, member_ref.member_desc)
                 {
                     Ok(resolved_method) => {
+                        let decl_class = resolved_method.decl_class;
                         let resolved_method = resolved_method.method;
                         if !resolved_method.is_static() {
                             todo!("throw IncompatibleClassChangeError");
                         }
-                        let args_count = Self::num2isize(resolved_method.params().length());
-                        let args_slots = interp.compute_args_slots(resolved_method, interp.vm);
+                        // jvms-5.5: the class declaring the resolved method
+                        match decl_class.initialize(Thread::current()) {
+                            Ok(_) => {}
+                            Err(_) => todo!(),
+                        }
+                        let args_count = Self::num2isize(resolved_method.param_count());
+                        let args_slots = interp.compute_args_slots(resolved_method);
                         interp.invoke_method(
                             ObjectPtr::null(),
-                            target_class,
+                            decl_class,
                             resolved_method,
                             args_count,
                             args_slots,
@@ -1547,10 +1855,10 @@ impl Interpreter {
         case_label_invokevirtual!();
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8) | u16::from(interp.read_operand());
+            let index = interp.read_operand_u16();
             let frame_class = interp.stack.frame().class();
-            let member_ref = frame_class.class_data().cp.get_method_ref(index);
+            let cp = interp.stack.frame().cp();
+            let member_ref = cp.get_method_ref(index);
             log::trace!(
                 "invokvirtual from {}#{}, target {}#{}, index {}, stacktrace {}",
                 frame_class.name().as_str(),
@@ -1578,15 +1886,31 @@ impl Interpreter {
                             if resolved_method.method.is_static() {
                                 todo!("throw IncompatibleClassChangeError");
                             }
-                            let args_count =
-                                Self::num2isize(resolved_method.method.params().length());
-                            let args_slots =
-                                1 + interp.compute_args_slots(resolved_method.method, interp.vm);
+                            let args_count = Self::num2isize(resolved_method.method.param_count());
+                            let args_slots = 1 + interp.compute_args_slots(resolved_method.method);
                             let obj_ref = interp.stack.load_callee_objref(args_slots);
+                            // the receiver's class is read from its header
+                            let receiver_cls;
+                            if interp.needs_null_check(0) {
+                                if obj_ref.is_null() {
+                                    todo!("throw NullPointerException");
+                                }
+                                receiver_cls = obj_ref.jclass();
+                            } else {
+                                // the load is the null check, see
+                                // `handle_null_access_fault`
+                                let class_word = load_at_label!(
+                                    "__vm_invokevirtual_load_class",
+                                    64,
+                                    obj_ref.as_isize() as *const u8
+                                );
+                                receiver_cls = JClassPtr::from_isize(class_word as isize);
+                            }
                             match JClass::resolve_virtual_with_index(
-                                obj_ref,
+                                receiver_cls,
                                 resolved_method.method,
                                 resolved_method.method_idx,
+                                interp.vm.as_ref(),
                             ) {
                                 Ok(resolved_method) => {
                                     log::trace!("invokvirtual obj_ref 0x{:x}", obj_ref.as_isize());
@@ -1603,7 +1927,9 @@ impl Interpreter {
                                 }
                                 Err(_e) => {
                                     log::trace!("invokevirtual failed {:?}", _e);
-                                    todo!();
+                                    // the selected method is abstract, e.g. a
+                                    // Miranda method no subclass implements
+                                    todo!("throw AbstractMethodError");
                                 }
                             };
                         }
@@ -1621,8 +1947,8 @@ impl Interpreter {
             }
         }
 
-        case_label_num_arithmetic!(ior, JInt, |, false);
-        case_label_num_arithmetic!(irem, JInt, %, true);
+        case_label_num_arithmetic!(ior, JInt, |);
+        case_label_int_division!(irem, JInt, wrapping_rem);
 
         case_label_ireturn!();
         {
@@ -1637,8 +1963,8 @@ impl Interpreter {
             dispatch!(interp);
         }
 
-        case_label_num_arithmetic!(ishl, JInt, <<, false);
-        case_label_num_arithmetic!(ishr, JInt, >>, false);
+        case_label_num_arithmetic!(ishl, JInt, <<);
+        case_label_num_arithmetic!(ishr, JInt, >>);
 
         case_label_val_store!(istore, access_interpreter!().read_operand(), JInt);
         case_label_val_store!(istore0, 0, JInt);
@@ -1646,7 +1972,7 @@ impl Interpreter {
         case_label_val_store!(istore2, 2, JInt);
         case_label_val_store!(istore3, 3, JInt);
 
-        case_label_num_arithmetic!(isub, JInt, -, false);
+        case_label_num_arithmetic!(isub, JInt, -);
 
         case_label_iushr!();
         {
@@ -1665,15 +1991,14 @@ impl Interpreter {
             dispatch!(interp);
         }
 
-        case_label_num_arithmetic!(ixor, JInt, ^, false);
+        case_label_num_arithmetic!(ixor, JInt, ^);
 
         case_label_jsr!();
         {
             let interp = access_interpreter!();
             let jsr_op_addr = interp.pc.offset(-1);
-            let branch = i16::from(interp.read_operand());
-            let branch = (branch << 8) | i16::from(interp.read_operand());
-            interp.stack.push_jobj(ObjectPtr::from_addr(interp.pc));
+            let branch = interp.read_operand_i16();
+            interp.stack.push_return_address(interp.pc);
             interp.goto(jsr_op_addr, branch);
         }
 
@@ -1681,11 +2006,8 @@ impl Interpreter {
         {
             let interp = access_interpreter!();
             let jsrw_op_addr = interp.pc.offset(-1);
-            let branch = i32::from(interp.read_operand()) << 24;
-            let branch = branch | (i32::from(interp.read_operand()) << 16);
-            let branch = branch | (i32::from(interp.read_operand()) << 8);
-            let branch = branch | i32::from(interp.read_operand());
-            interp.stack.push_jobj(ObjectPtr::from_addr(interp.pc));
+            let branch = interp.read_operand_i32();
+            interp.stack.push_return_address(interp.pc);
             interp.goto_w(jsrw_op_addr, branch);
         }
 
@@ -1693,11 +2015,11 @@ impl Interpreter {
         case_label_num_convert!(l2f, JLong, JFloat, JFloat);
         case_label_num_convert!(l2i, JLong, JInt, JInt);
 
-        case_label_num_arithmetic!(ladd, JLong, +, false);
+        case_label_num_arithmetic!(ladd, JLong, +);
 
         case_label_array_load!(laload, JLongArrayPtr, JLong, JLong);
 
-        case_label_num_arithmetic!(land, JLong, &, false);
+        case_label_num_arithmetic!(land, JLong, &);
 
         case_label_array_store!(lastore, JLongArrayPtr, JLong, JLong);
 
@@ -1719,8 +2041,7 @@ impl Interpreter {
         case_label_ldcw!();
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8) | u16::from(interp.read_operand());
+            let index = interp.read_operand_u16();
             Self::op_ldc(interp, index);
             dispatch!(interp);
         }
@@ -1728,20 +2049,15 @@ impl Interpreter {
         case_label_ldc2w!();
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8) | u16::from(interp.read_operand());
-            let frame_class = interp.stack.frame().class();
-            let constant_tag = frame_class.class_data().cp.get_tag(index);
+            let index = interp.read_operand_u16();
+            let cp = interp.stack.frame().cp();
+            let constant_tag = cp.get_tag(index);
             match constant_tag {
                 ConstantTag::Long => {
-                    interp
-                        .stack
-                        .push(frame_class.class_data().cp.get_long(index));
+                    interp.stack.push(cp.get_long(index));
                 }
                 ConstantTag::Double => {
-                    interp
-                        .stack
-                        .push(frame_class.class_data().cp.get_double(index));
+                    interp.stack.push(cp.get_double(index));
                 }
                 _ => {
                     todo!("invalid constant tag");
@@ -1750,11 +2066,11 @@ impl Interpreter {
             dispatch!(interp);
         }
 
-        case_label_num_arithmetic!(ldiv, JLong, /, true);
+        case_label_int_division!(ldiv, JLong, wrapping_div);
 
         case_label_num_load!(lload, JLong, 0, 1, 2, 3);
 
-        case_label_num_arithmetic!(lmul, JLong, *, false);
+        case_label_num_arithmetic!(lmul, JLong, *);
 
         case_label_lneg!();
         {
@@ -1768,7 +2084,7 @@ impl Interpreter {
         {
             let interp = access_interpreter!();
             let op_addr = interp.pc.offset(-1);
-            interp.skip_operands(3);
+            interp.skip_switch_padding(op_addr);
             let default_offset = interp.read_operand_i32();
 
             let npairs = interp.read_operand_i32();
@@ -1795,8 +2111,8 @@ impl Interpreter {
             dispatch!(interp);
         }
 
-        case_label_num_arithmetic!(lor, JLong, |, false);
-        case_label_num_arithmetic!(lrem, JLong, %, true);
+        case_label_num_arithmetic!(lor, JLong, |);
+        case_label_int_division!(lrem, JLong, wrapping_rem);
 
         case_label_lreturn!();
         {
@@ -1811,8 +2127,8 @@ impl Interpreter {
             dispatch!(interp);
         }
 
-        case_label_num_diff_types_arithmetic!(lshl, JLong, JInt, <<, false);
-        case_label_num_diff_types_arithmetic!(lshr, JLong, JInt, >>, false);
+        case_label_num_diff_types_arithmetic!(lshl, JLong, JInt, <<);
+        case_label_num_diff_types_arithmetic!(lshr, JLong, JInt, >>);
 
         case_label_val_store!(lstore, access_interpreter!().read_operand(), JLong);
         case_label_val_store!(lstore0, 0, JLong);
@@ -1820,7 +2136,7 @@ impl Interpreter {
         case_label_val_store!(lstore2, 2, JLong);
         case_label_val_store!(lstore3, 3, JLong);
 
-        case_label_num_arithmetic!(lsub, JLong, -, false);
+        case_label_num_arithmetic!(lsub, JLong, -);
 
         case_label_lushr!();
         {
@@ -1839,7 +2155,7 @@ impl Interpreter {
             dispatch!(interp);
         }
 
-        case_label_num_arithmetic!(lxor, JLong, ^, false);
+        case_label_num_arithmetic!(lxor, JLong, ^);
 
         case_label_monitorenter!();
         {
@@ -1848,38 +2164,32 @@ impl Interpreter {
             if obj.is_null() {
                 todo!("throw NullPointerException");
             }
-            // TODO
+            interp.thread.enter_monitor(obj);
             dispatch!(interp);
         }
 
         case_label_monitorexit!();
         {
-            // todo!();
             let interp = access_interpreter!();
             let obj = interp.stack.pop_jobj();
             if obj.is_null() {
                 todo!("throw NullPointerException");
             }
-            // TODO
+            if !interp.thread.exit_monitor(obj) {
+                todo!("throw IllegalMonitorStateException");
+            }
             dispatch!(interp);
         }
 
         case_label_multianewarray!();
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8) | u16::from(interp.read_operand());
+            let index = interp.read_operand_u16();
             let dimensions = interp.read_operand();
             if dimensions < 1 {
                 todo!("throw ClassFormatError");
             }
-            let dimensions_class_name = interp
-                .stack
-                .frame()
-                .class()
-                .class_data()
-                .cp
-                .get_class_name(index);
+            let dimensions_class_name = interp.stack.frame().cp().get_class_name(index);
             if let Ok(dimension_class) = interp
                 .vm
                 .bootstrap_class_loader
@@ -1898,6 +2208,9 @@ impl Interpreter {
                         dimension_class.class_data().component_type(),
                     );
                 }
+                for _ in 0..dimensions {
+                    interp.stack.discard::<JInt>();
+                }
                 interp.stack.push_jobj(dimensions_array.cast());
 
                 dispatch!(interp);
@@ -1910,13 +2223,7 @@ impl Interpreter {
         {
             let interp = access_interpreter!();
             let index = interp.read_operand_u16();
-            let target_class_name = interp
-                .stack
-                .frame()
-                .class()
-                .class_data()
-                .cp
-                .get_class_name(index);
+            let target_class_name = interp.stack.frame().cp().get_class_name(index);
             if let Ok(target_class) = interp
                 .vm
                 .bootstrap_class_loader
@@ -1953,34 +2260,18 @@ impl Interpreter {
                 todo!("throw NegativeArraySizeException");
             }
             let preloaded_classes = interp.vm.preloaded_classes();
-            let thread = Thread::current();
-            let arr: ObjectPtr = match array_type {
-                ArrayType::Boolean => {
-                    JArray::new(count, preloaded_classes.bool_arr_cls(), thread).cast()
-                }
-                ArrayType::Char => {
-                    JArray::new(count, preloaded_classes.char_arr_cls(), thread).cast()
-                }
-                ArrayType::Float => {
-                    JArray::new(count, preloaded_classes.float_arr_cls(), thread).cast()
-                }
-                ArrayType::Double => {
-                    JArray::new(count, preloaded_classes.double_arr_cls(), thread).cast()
-                }
-                ArrayType::Byte => {
-                    JArray::new(count, preloaded_classes.byte_arr_cls(), thread).cast()
-                }
-                ArrayType::Short => {
-                    JArray::new(count, preloaded_classes.short_arr_cls(), thread).cast()
-                }
-                ArrayType::Int => {
-                    JArray::new(count, preloaded_classes.int_arr_cls(), thread).cast()
-                }
-                ArrayType::Long => {
-                    JArray::new(count, preloaded_classes.long_arr_cls(), thread).cast()
-                }
+            let array_cls = match array_type {
+                ArrayType::Boolean => preloaded_classes.bool_arr_cls(),
+                ArrayType::Char => preloaded_classes.char_arr_cls(),
+                ArrayType::Float => preloaded_classes.float_arr_cls(),
+                ArrayType::Double => preloaded_classes.double_arr_cls(),
+                ArrayType::Byte => preloaded_classes.byte_arr_cls(),
+                ArrayType::Short => preloaded_classes.short_arr_cls(),
+                ArrayType::Int => preloaded_classes.int_arr_cls(),
+                ArrayType::Long => preloaded_classes.long_arr_cls(),
             };
-            interp.stack.push_jobj(arr);
+            let arr = interp.new_array(count, array_cls);
+            interp.stack.push_jobj(arr.cast());
             dispatch!(interp);
         }
 
@@ -2012,83 +2303,25 @@ impl Interpreter {
         case_label_putfield!();
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8) | u16::from(interp.read_operand());
-            let field_ref = interp
-                .stack
-                .frame()
-                .class()
-                .class_data()
-                .cp
-                .get_field_ref(index);
+            let index = interp.read_operand_u16();
+            let field_ref = interp.stack.frame().cp().get_field_ref(index);
             if let Ok(target_class) = interp
                 .vm
                 .bootstrap_class_loader
                 .load_class(field_ref.class_name.as_str())
             {
-                let (target_field, _) = target_class.get_field(&field_ref);
-                let field_class = match target_field.field_class(Thread::current()) {
-                    Ok(field_class) => field_class,
-                    Err(_) => todo!(),
-                };
-                log::trace!(
-                    "prepare putfield, target {}.{} type {}, obj_ref: {}, field_offset: {}",
-                    target_class.name().as_str(),
-                    target_field.name().as_str(),
-                    field_class.name().as_str(),
-                    "prepare",
-                    target_field.layout_offset()
-                );
-                let preloaded_classes = interp.vm.as_ref().preloaded_classes();
-                if preloaded_classes.is_bool_cls(field_class)
-                    || preloaded_classes.is_byte_cls(field_class)
-                {
-                    let value = interp.stack.pop::<JInt>() as JByte;
+                if let Some((target_field, _)) = interp.lookup_field(target_class, &field_ref) {
+                    let value = interp.pop_field_value(target_field.kind());
                     let obj_ref = interp.stack.pop_jobj();
-                    target_field.set_typed_value(obj_ref, value);
-                } else if preloaded_classes.is_char_cls(field_class)
-                    || preloaded_classes.is_short_cls(field_class)
-                {
-                    let value = interp.stack.pop::<JInt>() as JShort;
-                    let obj_ref = interp.stack.pop_jobj();
-                    target_field.set_typed_value(obj_ref, value);
-                } else if preloaded_classes.is_int_cls(field_class) {
-                    let value = interp.stack.pop::<JInt>();
-                    let obj_ref = interp.stack.pop_jobj();
-
-                    log::trace!(
-                        "prepare putfield int, class {}, obj 0x{:x}, field {}, field_offset: {}",
-                        field_class.name().as_str(),
-                        obj_ref.as_isize(),
-                        target_field.name().as_str(),
-                        target_field.layout_offset()
-                    );
-                    target_field.set_typed_value(obj_ref, value);
-                } else if preloaded_classes.is_float_cls(field_class) {
-                    let value = interp.stack.pop::<JFloat>();
-                    let obj_ref = interp.stack.pop_jobj();
-                    target_field.set_typed_value(obj_ref, value);
-                } else if preloaded_classes.is_long_cls(field_class) {
-                    let value = interp.stack.pop::<JLong>();
-                    let obj_ref = interp.stack.pop_jobj();
-                    target_field.set_typed_value(obj_ref, value);
-                } else if preloaded_classes.is_double_cls(field_class) {
-                    let value = interp.stack.pop::<JDouble>();
-                    let obj_ref = interp.stack.pop_jobj();
-                    target_field.set_typed_value(obj_ref, value);
-                } else {
-                    let value = interp.stack.pop_jobj().as_mut_raw_ptr();
-                    let obj_ref = interp.stack.pop_jobj();
-                    target_field.set_typed_value(obj_ref, value);
                     log::trace!(
-                        "prepare putfield, target {}.{} type {}, obj_ref: 0x{:x}, val: 0x{:x?}, field_offset: {}",
+                        "putfield, target {}.{}, obj_ref: 0x{:x}, val: 0x{:x}, field_offset: {}",
                         target_class.name().as_str(),
-                        target_field.name().as_str(),
-                        field_class.name().as_str(),
+                        target_field.name(&interp.vm).as_str(),
                         obj_ref.as_isize(),
                         value,
                         target_field.layout_offset()
                     );
+                    target_field.set_stack_value(obj_ref, value);
                 }
                 dispatch!(interp);
             } else {
@@ -2099,89 +2332,29 @@ impl Interpreter {
         case_label_putstatic!();
         {
             let interp = access_interpreter!();
-            let index = u16::from(interp.read_operand());
-            let index = (index << 8) | u16::from(interp.read_operand());
-            let field_ref = interp
-                .stack
-                .frame()
-                .class()
-                .class_data()
-                .cp
-                .get_field_ref(index);
-            if let Ok(_target_class) = interp
-                .vm
-                .bootstrap_class_loader
-                .load_class(field_ref.class_name.as_str())
+            let index = interp.read_operand_u16();
+            if let Some(ResolvedStaticField { decl_cls, access }) =
+                interp.resolve_static_field(index)
             {
-                let (target_field, decl_cls) = _target_class.get_field(&field_ref);
-                if target_field.is_null() {
-                    todo!("throw NoSuchFieldError");
-                }
-                match decl_cls.initialize(Thread::current()) {
-                    Ok(_) => {}
-                    Err(_) => todo!(),
-                }
-                if !target_field.is_static() {
-                    todo!("throw IncompatibleClassChangeError");
-                }
-                let field_class = match target_field.field_class(Thread::current()) {
-                    Ok(field_class) => field_class,
-                    Err(_) => {
-                        log::trace!(
-                            "putstatic {}#{} load {} failed",
-                            decl_cls.name().as_str(),
-                            target_field.name().as_str(),
-                            target_field.descriptor().as_str(),
-                        );
-                        todo!()
-                    }
-                };
-                let preloaded_classes = interp.vm.preloaded_classes();
-                if preloaded_classes.is_bool_cls(field_class)
-                    || preloaded_classes.is_byte_cls(field_class)
-                {
-                    let value = interp.stack.pop::<JInt>() as JByte;
-                    target_field.set_static_value(decl_cls, value);
-                } else if preloaded_classes.is_char_cls(field_class)
-                    || preloaded_classes.is_short_cls(field_class)
-                {
-                    let value = interp.stack.pop::<JInt>() as JShort;
-                    target_field.set_static_value(decl_cls, value);
-                } else if preloaded_classes.is_int_cls(field_class) {
-                    let value = interp.stack.pop::<JInt>();
-                    target_field.set_static_value(decl_cls, value);
-                } else if preloaded_classes.is_float_cls(field_class) {
-                    let value = interp.stack.pop::<JFloat>();
-                    target_field.set_static_value(decl_cls, value);
-                } else if preloaded_classes.is_long_cls(field_class) {
-                    let value = interp.stack.pop::<JLong>();
-                    target_field.set_static_value(decl_cls, value);
-                } else if preloaded_classes.is_double_cls(field_class) {
-                    let value = interp.stack.pop::<JDouble>();
-                    target_field.set_static_value(decl_cls, value);
-                } else {
-                    let value = interp.stack.pop_jobj().as_mut_raw_ptr();
-                    log::trace!(
-                        "setstatic {}#{} : cls 0x{:x}  val {:x?} success, offset: {}",
-                        decl_cls.name().as_str(),
-                        target_field.name().as_str(),
-                        decl_cls.as_isize(),
-                        value,
-                        target_field.layout_offset()
-                    );
-                    target_field.set_static_value(decl_cls, value);
-                }
-                dispatch!(interp);
-            } else {
-                todo!("throw ClassNotFoundException");
+                let value = interp.pop_field_value(access.kind);
+                log::trace!(
+                    "putstatic #{} of {} : cls 0x{:x}, val 0x{:x}, offset: {}",
+                    index,
+                    decl_cls.name().as_str(),
+                    decl_cls.as_isize(),
+                    value,
+                    access.offset
+                );
+                access.set_stack_value(decl_cls.cast(), value);
             }
+            dispatch!(interp);
         }
 
         case_label_ret!();
         {
             let interp = access_interpreter!();
             let index = interp.read_operand();
-            interp.pc = interp.stack.load_jobj(isize::from(index)).as_address();
+            interp.pc = interp.stack.load_return_address(isize::from(index));
             dispatch!(interp);
         }
 
@@ -2207,13 +2380,13 @@ impl Interpreter {
         {
             let interp = access_interpreter!();
             let op_addr = interp.pc.offset(-1);
-            interp.skip_operands(3);
+            interp.skip_switch_padding(op_addr);
             let default_offset = interp.read_operand_i32();
             let low = interp.read_operand_i32();
             let high = interp.read_operand_i32();
             let index = interp.stack.pop::<JInt>();
             if index < low || index > high {
-                interp.pc = op_addr.offset(Self::num2isize(default_offset) * 4);
+                interp.pc = op_addr.offset(Self::num2isize(default_offset));
             } else {
                 let branch_offset = interp.peek_operand_as_int(Self::num2isize(index - low) * 4);
                 interp.pc = op_addr.offset(Self::num2isize(branch_offset));
@@ -2227,24 +2400,24 @@ impl Interpreter {
             let op_code: JvmInstruction = Self::op_code_as_instr(interp.read_operand());
             let index = interp.read_operand_u16();
             match op_code {
-                JvmInstruction::ILoad => do_num_load!(JInt, index),
-                JvmInstruction::FLoad => do_num_load!(JFloat, index),
+                JvmInstruction::ILoad => do_num_load!(interp, JInt, index),
+                JvmInstruction::FLoad => do_num_load!(interp, JFloat, index),
                 JvmInstruction::ALoad => {
                     let val = interp.stack.load_jobj(Self::num2isize(index));
                     interp.stack.push_jobj(val);
                 }
-                JvmInstruction::LLoad => do_num_load!(JLong, index),
-                JvmInstruction::DLoad => do_num_load!(JDouble, index),
-                JvmInstruction::IStore => do_num_store!(JInt, index),
-                JvmInstruction::FStore => do_num_store!(JFloat, index),
+                JvmInstruction::LLoad => do_num_load!(interp, JLong, index),
+                JvmInstruction::DLoad => do_num_load!(interp, JDouble, index),
+                JvmInstruction::IStore => do_num_store!(interp, JInt, index),
+                JvmInstruction::FStore => do_num_store!(interp, JFloat, index),
                 JvmInstruction::AStore => {
-                    let val = interp.stack.pop_jobj();
-                    interp.stack.store_jobj(val, Self::num2isize(index));
+                    let val = interp.stack.pop_slot();
+                    interp.stack.store_slot(val, Self::num2isize(index));
                 }
-                JvmInstruction::LStore => do_num_store!(JLong, index),
-                JvmInstruction::DStore => do_num_store!(JDouble, index),
+                JvmInstruction::LStore => do_num_store!(interp, JLong, index),
+                JvmInstruction::DStore => do_num_store!(interp, JDouble, index),
                 JvmInstruction::Ret => {
-                    interp.pc = interp.stack.load_jobj(Self::num2isize(index)).as_address()
+                    interp.pc = interp.stack.load_return_address(Self::num2isize(index))
                 }
                 JvmInstruction::IInc => {
                     let const_val = JInt::from(interp.read_operand_i16());
@@ -2292,7 +2465,87 @@ impl Interpreter {
             let interp = access_interpreter!();
             dispatch!(interp);
         }
-        return JValue::with_int_val(0);
+
+        label!("__vm_cancelled");
+        {
+            let interp = access_interpreter!();
+            return interp.unwind_cancelled();
+        }
+    }
+
+    /// Resolves the Fieldref at `index` of the current pool for getstatic and
+    /// putstatic and initializes the declaring class. The pool caches the
+    /// result, so only the first execution loads and looks up anything.
+    fn resolve_static_field(&mut self, index: u16) -> Option<ResolvedStaticField> {
+        let cp = self.stack.frame().cp();
+        if let Some(resolved) = cp.resolved_static_field(index) {
+            return Some(resolved);
+        }
+        let field_ref = cp.get_field_ref(index);
+        let resolved_class = match self
+            .vm
+            .bootstrap_class_loader
+            .load_class(field_ref.class_name.as_str())
+        {
+            Ok(resolved_class) => resolved_class,
+            Err(_) => todo!("throw ClassNotFoundException"),
+        };
+        let (field, decl_cls) = self.lookup_field(resolved_class, &field_ref)?;
+        if !field.is_static() {
+            todo!("throw IncompatibleClassChangeError");
+        }
+        // jvms-5.5: the class or interface declaring the resolved field
+        match decl_cls.initialize(self.thread) {
+            Ok(_) => {}
+            Err(_) => todo!(),
+        }
+        let resolved = ResolvedStaticField {
+            decl_cls,
+            access: field.access(),
+        };
+        // a class still running its <clinit> must be checked again
+        if decl_cls.is_fully_initialized() {
+            cp.cache_static_field(index, resolved);
+        }
+        return Some(resolved);
+    }
+
+    /// Looks up the field `field_ref` names in `class` and its supertypes, or
+    /// cancels the execution when there is none.
+    fn lookup_field(
+        &mut self,
+        class: JClassPtr,
+        field_ref: &ConstMemberRef,
+    ) -> Option<(FieldPtr, JClassPtr)> {
+        return match class.get_field(field_ref) {
+            Ok(resolved) => Some(resolved),
+            Err(e) => {
+                self.cancel_execution(CancelReason::LinkageError(e.to_string()));
+                None
+            }
+        };
+    }
+
+    /// Pushes a field value read by getfield or getstatic.
+    fn push_field_value(&mut self, kind: FieldKind, value: i64) {
+        if kind.is_wide() {
+            self.stack.push::<JLong>(value);
+        } else if kind == FieldKind::Reference {
+            self.stack
+                .push_jobj(ObjectPtr::from_isize(Interpreter::num2isize(value)));
+        } else {
+            self.stack.push::<JInt>(value as JInt);
+        }
+    }
+
+    /// Pops the value to store by putfield or putstatic.
+    fn pop_field_value(&mut self, kind: FieldKind) -> i64 {
+        if kind.is_wide() {
+            return self.stack.pop::<JLong>();
+        } else if kind == FieldKind::Reference {
+            return self.stack.pop_jobj().as_isize() as i64;
+        }
+        return self.stack.pop::<JInt>() as i64;
     }
 
     fn create_dimension_array(
@@ -2341,9 +2594,22 @@ impl Interpreter {
         args_slots: isize,
         obj_ref_size: isize,
         is_java_top: bool,
-    ) {
-        debug_assert!(args_count == method.params().length() as isize);
-        // todo: synchronized
+    ) -> bool {
+        debug_assert!(args_count == method.param_count() as isize);
+        if !self.reserve_stack(Stack::frame_slots(method, args_slots)) {
+            return false;
+        }
+        self.calls.record(method);
+        let mut monitor = ObjectPtr::null();
+        if method.is_synchronized() {
+            // exited when the frame returns, see restore_invoker_frame
+            monitor = if method.is_static() {
+                method.decl_cls().cast()
+            } else {
+                obj_ref
+            };
+            self.thread.enter_monitor(monitor);
+        }
 
         let prev_pc = self.pc;
         self.pc = Address::new(method.code());
@@ -2357,6 +2623,7 @@ impl Interpreter {
                 is_java_top,
                 self.thread,
             );
+            self.stack.frame().as_mut_ref().set_monitor(monitor);
         } else {
             self.stack.new_native_call_frame(
                 class,
@@ -2367,6 +2634,7 @@ impl Interpreter {
                 is_java_top,
                 self.thread,
             );
+            self.stack.frame().as_mut_ref().set_monitor(monitor);
 
             log::trace!(
                 "call native method {}:{}, descriptor {}, code: {}",
@@ -2375,29 +2643,72 @@ impl Interpreter {
                 method.descriptor().as_str(),
                 method.code().is_null()
             );
-            let ret_type = method.ret_type();
-            let ret_is_void = JClass::is_void(ret_type, self.vm);
-
             if method.native_fn().is_null() {
                 todo!("throw Exception");
             }
-            let ret_val = self.invoke_native_fn(class, method, obj_ref, obj_ref_size);
+            let ret_val = if proxy::is_proxy_method(method) {
+                self.invoke_proxy(method, obj_ref, obj_ref_size)
+            } else {
+                self.invoke_native_fn(class, method, obj_ref, obj_ref_size)
+            };
 
             self.restore_invoker_frame();
 
-            if !ret_is_void {
+            if let Some(ret_kind) = method.ret_kind() {
                 log::trace!("invoke_native_fn push value: 0x{:x}", ret_val.long_val());
-                if JClass::is_long(ret_type, self.vm) || JClass::is_double(ret_type, self.vm) {
-                    self.stack.push::<JLong>(ret_val.long_val());
-                } else if ret_type.is_not_null() && JClass::is_primitive(ret_type) {
-                    self.stack.push::<JInt>(ret_val.int_val());
-                } else {
-                    self.stack.push_jobj(ret_val.obj_val());
-                }
+                self.push_native_result(ret_kind, ret_val);
             }
-            return;
+            return true;
         }
         // Self::execute(self, class, method, is_root_frame);
+        return true;
+    }
+
+    /// Pops the result of the native `method` called from outside the
+    /// interpreter, which `invoke_method` already ran and left on the stack.
+    fn pop_native_result(&mut self, method: MethodPtr) -> JValue {
+        return match method.ret_kind() {
+            None => JValue::default(),
+            Some(FieldKind::Long | FieldKind::Double) => {
+                JValue::with_long_val(self.stack.pop::<JLong>())
+            }
+            Some(FieldKind::Reference) => JValue::with_obj_val(self.stack.pop_jobj()),
+            Some(_) => JValue::with_int_val(self.stack.pop::<JInt>()),
+        };
+    }
+
+    /// Pushes the result of a native function as the descriptor of its
+    /// method tells. A C function returning a boolean, byte, char or short
+    /// only sets the low bits of the register, which are extended here like
+    /// the JVM widens them to an int.
+    #[inline(always)]
+    fn push_native_result(&mut self, ret_kind: FieldKind, ret_val: JValue) {
+        match ret_kind {
+            FieldKind::Boolean => self.stack.push::<JInt>(ret_val.bool_val() as u8 as JInt),
+            FieldKind::Byte => self.stack.push::<JInt>(ret_val.byte_val() as JInt),
+            FieldKind::Char => self.stack.push::<JInt>(ret_val.ushort_val() as JInt),
+            FieldKind::Short => self.stack.push::<JInt>(ret_val.short_val() as JInt),
+            FieldKind::Int | FieldKind::Float => self.stack.push::<JInt>(ret_val.int_val()),
+            FieldKind::Long | FieldKind::Double => self.stack.push::<JLong>(ret_val.long_val()),
+            FieldKind::Reference => self.stack.push_jobj(ret_val.obj_val()),
+        }
+    }
+
+    fn invoke_proxy(&self, method: MethodPtr, objref: ObjectPtr, obj_ref_size: isize) -> JValue {
+        debug_assert_eq!(1, obj_ref_size);
+        let vm = self.vm;
+        let mut slot = 0;
+        let args: Vec<JValue> = (0..method.param_count())
+            .map(|idx| {
+                JValue::with_long_val(self.get_argument_as_jlong(
+                    method,
+                    obj_ref_size,
+                    idx,
+                    &mut slot,
+                ))
+            })
+            .collect();
+        return vm.proxies.dispatch(objref, method, &args, vm.as_ref());
     }
 
     fn invoke_native_fn(
@@ -2410,324 +2721,66 @@ impl Interpreter {
         debug_assert!(method.is_native());
         debug_assert!(!method.is_static() as isize == obj_ref_size);
         debug_assert!(method.native_fn().is_not_null());
-        let params = method.params();
         let func = method.native_fn().raw_ptr() as usize;
-        let vm = self.vm;
-        let jni_env = vm.jni().get_env_handle();
-        log::trace!("invoke_native_fn params_length: {}", params.length());
+        let mut thread = self.thread;
+        let env = thread.jni_env_mut();
+        env.check_not_critical("a native called back into Java");
+        let jni_env = env.as_handle();
+        let local_frame = env.push_local_frame();
+        log::trace!("invoke_native_fn params_length: {}", method.param_count());
         let target_ref = if obj_ref_size == 0 {
-            class.as_c_ptr()
+            env.new_local_ref(class.cast()).as_c_ptr()
         } else {
-            objref.as_c_ptr()
+            env.new_local_ref(objref).as_c_ptr()
         };
-        let ret_val: JLong;
-        match params.length() {
-            0 => {
-                #[cfg(all(target_arch = "x86_64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "call {}",
-                        in(reg) func,
-                        in("rdi") jni_env,
-                        in("rsi") target_ref,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "aarch64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "blr {}",
-                        in(reg) func,
-                        inout("x0") jni_env => ret_val,
-                        in("x1") target_ref,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "x86_64", target_os = "windows"))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "call {}",
-                        in(reg) func,
-                        in("rcx") jni_env,
-                        in("rdx") target_ref,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-            }
-            1 => {
-                let mut slot = 0;
-                let arg0 = self.get_argument_as_jlong(vm, obj_ref_size, params, 0, &mut slot);
-
-                #[cfg(all(target_arch = "x86_64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "call {}",
-                        in(reg) func,
-                        in("rdi") jni_env,
-                        in("rsi") target_ref,
-                        in("rdx") arg0,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "aarch64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "blr {}",
-                        in(reg) func,
-                        inout("x0") jni_env => ret_val,
-                        in("x1") target_ref,
-                        in("x2") arg0,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "x86_64", target_os = "windows"))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "call {}",
-                        in(reg) func,
-                        in("rcx") jni_env,
-                        in("rdx") target_ref,
-                        in("r8") arg0,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-            }
-            2 => {
-                let mut slot = 0;
-                let arg0 = self.get_argument_as_jlong(vm, obj_ref_size, params, 0, &mut slot);
-                let arg1 = self.get_argument_as_jlong(vm, obj_ref_size, params, 1, &mut slot);
-
-                #[cfg(all(target_arch = "x86_64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "call {}",
-                        in(reg) func,
-                        in("rdi") jni_env,
-                        in("rsi") target_ref,
-                        in("rdx") arg0,
-                        in("rcx") arg1,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "aarch64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "blr {}",
-                        in(reg) func,
-                        inout("x0") jni_env => ret_val,
-                        in("x1") target_ref,
-                        in("x2") arg0,
-                        in("x3") arg1,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "x86_64", target_os = "windows"))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "call {}",
-                        in(reg) func,
-                        in("rcx") jni_env,
-                        in("rdx") target_ref,
-                        in("r8") arg0,
-                        in("r9") arg1,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-            }
-            3 => {
-                let mut slot = 0;
-                let arg0 = self.get_argument_as_jlong(vm, obj_ref_size, params, 0, &mut slot);
-                let arg1 = self.get_argument_as_jlong(vm, obj_ref_size, params, 1, &mut slot);
-                let arg2 = self.get_argument_as_jlong(vm, obj_ref_size, params, 2, &mut slot);
-
-                #[cfg(all(target_arch = "x86_64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "call {}",
-                        in(reg) func,
-                        in("rdi") jni_env,
-                        in("rsi") target_ref,
-                        in("rdx") arg0,
-                        in("rcx") arg1,
-                        in("r8") arg2,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "aarch64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "blr {}",
-                        in(reg) func,
-                        inout("x0") jni_env => ret_val,
-                        in("x1") target_ref,
-                        in("x2") arg0,
-                        in("x3") arg1,
-                        in("x4") arg2,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "x86_64", target_os = "windows"))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "mov [rsp+32], {arg2}",
-                        "call {func}",
-                        func = in(reg) func,
-                        in("rcx") jni_env,
-                        in("rdx") target_ref,
-                        in("r8") arg0,
-                        in("r9") arg1,
-                        arg2 = in(reg) arg2,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-            }
-            4 => {
-                let mut slot = 0;
-                let arg0 = self.get_argument_as_jlong(vm, obj_ref_size, params, 0, &mut slot);
-                let arg1 = self.get_argument_as_jlong(vm, obj_ref_size, params, 1, &mut slot);
-                let arg2 = self.get_argument_as_jlong(vm, obj_ref_size, params, 2, &mut slot);
-                let arg3 = self.get_argument_as_jlong(vm, obj_ref_size, params, 3, &mut slot);
-
-                #[cfg(all(target_arch = "x86_64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "call {}",
-                        in(reg) func,
-                        in("rdi") jni_env,
-                        in("rsi") target_ref,
-                        in("rdx") arg0,
-                        in("rcx") arg1,
-                        in("r8") arg2,
-                        in("r9") arg3,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "aarch64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "blr {}",
-                        in(reg) func,
-                        inout("x0") jni_env => ret_val,
-                        in("x1") target_ref,
-                        in("x2") arg0,
-                        in("x3") arg1,
-                        in("x4") arg2,
-                        in("x5") arg3,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "x86_64", target_os = "windows"))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "mov [rsp+40], {arg3}",
-                        "mov [rsp+32], {arg2}",
-                        "call {func}",
-                        func = in(reg) func,
-                        in("rcx") jni_env,
-                        in("rdx") target_ref,
-                        in("r8") arg0,
-                        in("r9") arg1,
-                        arg2 = in(reg) arg2,
-                        arg3 = in(reg) arg3,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-            }
-            5 => {
-                let mut slot = 0;
-                let arg0 = self.get_argument_as_jlong(vm, obj_ref_size, params, 0, &mut slot);
-                let arg1 = self.get_argument_as_jlong(vm, obj_ref_size, params, 1, &mut slot);
-                let arg2 = self.get_argument_as_jlong(vm, obj_ref_size, params, 2, &mut slot);
-                let arg3 = self.get_argument_as_jlong(vm, obj_ref_size, params, 3, &mut slot);
-                let arg4 = self.get_argument_as_jlong(vm, obj_ref_size, params, 4, &mut slot);
-
-                #[cfg(all(target_arch = "x86_64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "mov [rsp], {arg4}",
-                        "call {stub}",
-                        stub = in(reg) func,
-                        in("rdi") jni_env,
-                        in("rsi") target_ref,
-                        in("rdx") arg0,
-                        in("rcx") arg1,
-                        in("r8") arg2,
-                        in("r9") arg3,
-                        arg4 = in(reg) arg4,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "aarch64", any(target_os = "linux", target_os = "macos")))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "blr {}",
-                        in(reg) func,
-                        inout("x0") jni_env => ret_val,
-                        in("x1") target_ref,
-                        in("x2") arg0,
-                        in("x3") arg1,
-                        in("x4") arg2,
-                        in("x5") arg3,
-                        in("x6") arg4,
-                        clobber_abi("C"),
-                    );
-                }
-                #[cfg(all(target_arch = "x86_64", target_os = "windows"))]
-                unsafe {
-                    use std::arch::asm;
-                    asm!(
-                        "mov [rsp+48], {arg4}",
-                        "mov [rsp+40], {arg3}",
-                        "mov [rsp+32], {arg2}",
-                        "call {func}",
-                        func = in(reg) func,
-                        in("rcx") jni_env,
-                        in("rdx") target_ref,
-                        in("r8") arg0,
-                        in("r9") arg1,
-                        arg2 = in(reg) arg2,
-                        arg3 = in(reg) arg3,
-                        arg4 = in(reg) arg4,
-                        out("rax") ret_val,
-                        clobber_abi("C"),
-                    );
-                }
-            }
-            _ => todo!(),
+        let params_length = method.param_count() as usize;
+        if params_length > native_call::MAX_ARGS {
+            todo!(
+                "native methods with more than {} parameters",
+                native_call::MAX_ARGS
+            );
         }
+        let mut args = [0; native_call::MAX_ARGS];
+        let mut is_float = [false; native_call::MAX_ARGS];
+        let mut slot = 0;
+        for idx in 0..params_length {
+            let kind = method.param_kind(idx as JInt);
+            is_float[idx] = matches!(kind, FieldKind::Float | FieldKind::Double);
+            args[idx] = self.get_argument_as_jlong(method, obj_ref_size, idx as JInt, &mut slot);
+        }
+        let float_ret = matches!(
+            method.ret_kind(),
+            Some(FieldKind::Float | FieldKind::Double)
+        );
+        let ret_val = unsafe {
+            native_call::call(
+                func,
+                jni_env,
+                target_ref,
+                &args[..params_length],
+                &is_float[..params_length],
+                float_ret,
+            )
+        };
+        let env = thread.jni_env_mut();
+        env.check_not_critical("a native returned without releasing its arrays");
+        // a native may return any reference, a weak global one included
+        let ret_val = if method.ret_kind() == Some(FieldKind::Reference) {
+            env.resolve::<Object>(ret_val as _).as_isize() as JLong
+        } else {
+            ret_val
+        };
+        env.pop_local_frame(local_frame);
         return JValue::with_long_val(ret_val);
     }
 
     #[inline(always)]
     fn restore_invoker_frame(&mut self) {
+        // the receiver local may have been overwritten since the entry
+        let monitor = self.stack.frame().monitor();
+        if monitor.is_not_null() && !self.thread.exit_monitor(monitor) {
+            todo!("throw IllegalMonitorStateException");
+        }
         log::trace!(
             "restore_invoker_frame method {:x}, locals {}",
             self.stack.frame().method().as_isize(),
@@ -2751,26 +2804,30 @@ impl Interpreter {
     #[inline]
     fn get_argument_as_jlong(
         &self,
-        vm: VMPtr,
+        method: MethodPtr,
         obj_ref_size: isize,
-        params: JArrayPtr,
-        param_idx: isize,
+        param_idx: JInt,
         slot: &mut isize,
     ) -> JLong {
         let current_slot = *slot;
-        let param_cls: JClassPtr = params.get_with_isize(param_idx).cast();
         let mut arg = JValue::default();
-        if JClass::is_long(param_cls, vm) || JClass::is_double(param_cls, vm) {
-            arg.set_long_val(self.stack.load::<JLong>(obj_ref_size + current_slot));
-            *slot += 2;
-        } else if param_cls.class_data().is_primitive() {
-            arg.set_int_val(self.stack.load::<JInt>(obj_ref_size + current_slot));
-            *slot += 1;
-        } else {
-            let obj_val = self.stack.load_jobj(obj_ref_size + current_slot);
-            arg.set_obj_val(obj_val);
-            debug_assert!(obj_val.is_null() || vm.heap().heap_contains(arg.obj_val().as_address()));
-            *slot += 1;
+        match method.param_kind(param_idx) {
+            FieldKind::Long | FieldKind::Double => {
+                arg.set_long_val(self.stack.load::<JLong>(obj_ref_size + current_slot));
+                *slot += 2;
+            }
+            FieldKind::Reference => {
+                let obj_val = self.stack.load_jobj(obj_ref_size + current_slot);
+                arg.set_obj_val(obj_val);
+                debug_assert!(
+                    obj_val.is_null() || self.vm.heap().heap_contains(obj_val.as_address())
+                );
+                *slot += 1;
+            }
+            _ => {
+                arg.set_int_val(self.stack.load::<JInt>(obj_ref_size + current_slot));
+                *slot += 1;
+            }
         }
         return arg.long_val();
     }
@@ -2798,29 +2855,32 @@ impl Interpreter {
     }
 
     fn op_ldc(interp: &mut Interpreter, index: u16) {
-        let frame_class = interp.stack.frame().class();
-        let constant_tag = frame_class.class_data().cp.get_tag(index);
+        let cp = interp.stack.frame().cp();
+        let constant_tag = cp.get_tag(index);
         match constant_tag {
             ConstantTag::Integer => {
-                interp
-                    .stack
-                    .push(frame_class.class_data().cp.get_int32(index));
+                interp.stack.push(cp.get_int32(index));
             }
             ConstantTag::Float => {
-                interp
-                    .stack
-                    .push(frame_class.class_data().cp.get_float(index));
+                interp.stack.push(cp.get_float(index));
             }
             ConstantTag::String => {
-                let symbol = frame_class.class_data().cp.get_string(index);
-                let jstr = interp
-                    .vm
-                    .string_table
-                    .from_symbol(symbol, Thread::current());
+                let jstr = match cp.resolved_string(index) {
+                    Some(jstr) => jstr,
+                    None => {
+                        let symbol = cp.get_string(index);
+                        let jstr = interp
+                            .vm
+                            .string_table
+                            .from_symbol(symbol, Thread::current());
+                        cp.cache_string(index, jstr);
+                        jstr
+                    }
+                };
                 interp.stack.push_jobj(jstr.cast());
             }
             ConstantTag::Class => {
-                let class_name = frame_class.class_data().cp.get_class_name(index);
+                let class_name = cp.get_class_name(index);
                 if let Ok(resolved_class) = interp
                     .vm
                     .bootstrap_class_loader
@@ -2842,8 +2902,11 @@ impl Interpreter {
 
     #[inline(always)]
     fn goto(&mut self, base_op_addr: Address, branch: i16) {
+        poll_execution!(self);
         let target_addr = base_op_addr.offset(Self::num2isize(branch));
         let op_code = target_addr.deref_as_u8();
+        #[cfg(feature = "interpreter-stats")]
+        self.op_counters().count_op(op_code);
         self.pc = target_addr.offset(std::mem::size_of::<u8>() as isize);
         reserve_value!(self as *mut Self as usize);
         goto_label_addr!(OP_CODE_TABLE[usize::from(op_code)]);
@@ -2851,8 +2914,11 @@ impl Interpreter {
 
     #[inline(always)]
     fn goto_w(&mut self, base_op_addr: Address, branch: i32) {
+        poll_execution!(self);
         let target_addr = base_op_addr.offset(Self::num2isize(branch));
         let op_code = target_addr.deref_as_u8();
+        #[cfg(feature = "interpreter-stats")]
+        self.op_counters().count_op(op_code);
         self.pc = target_addr.offset(std::mem::size_of::<u8>() as isize);
         reserve_value!(self as *mut Self as usize);
         goto_label_addr!(OP_CODE_TABLE[usize::from(op_code)]);
@@ -2867,43 +2933,48 @@ impl Interpreter {
     }
 
     #[inline(always)]
-    fn read_op<V: Copy>(&mut self) -> V {
-        let operand: V;
-        unsafe { operand = *(self.pc.raw_ptr() as *const V) }
-        self.pc = self.pc.offset(1);
-        return operand;
+    fn read_operand_i8(&mut self) -> i8 {
+        return self.read_operand() as i8;
     }
 
     #[inline(always)]
     fn read_operand_u16(&mut self) -> u16 {
-        let val = u16::from(self.read_operand()) << 8;
-        let val = val | u16::from(self.read_operand());
-        return val;
+        return u16::from_be_bytes([self.read_operand(), self.read_operand()]);
     }
 
     #[inline(always)]
     fn read_operand_i16(&mut self) -> i16 {
-        let val = i16::from(self.read_operand()) << 8;
-        let val = val | i16::from(self.read_operand());
-        return val;
+        return i16::from_be_bytes([self.read_operand(), self.read_operand()]);
     }
 
     #[inline(always)]
     fn read_operand_i32(&mut self) -> i32 {
-        let val = (self.read_operand() as i32) << 24;
-        let val = val | ((self.read_operand() as i32) << 16);
-        let val = val | ((self.read_operand() as i32) << 8);
-        let val = val | (self.read_operand() as i32);
-        return val;
+        return i32::from_be_bytes([
+            self.read_operand(),
+            self.read_operand(),
+            self.read_operand(),
+            self.read_operand(),
+        ]);
     }
 
     #[inline(always)]
     fn peek_operand_as_int(&self, offset: isize) -> i32 {
-        let value = i32::from(self.pc.offset(offset).deref_as_u8()) << 24;
-        let value = value | (i32::from(self.pc.offset(offset + 1).deref_as_u8()) << 16);
-        let value = value | (i32::from(self.pc.offset(offset + 2).deref_as_u8()) << 8);
-        let value = value | i32::from(self.pc.offset(offset + 3).deref_as_u8());
-        return value;
+        let operand = self.pc.offset(offset);
+        return i32::from_be_bytes([
+            operand.deref_as_u8(),
+            operand.offset(1).deref_as_u8(),
+            operand.offset(2).deref_as_u8(),
+            operand.offset(3).deref_as_u8(),
+        ]);
+    }
+
+    /// Skips the padding after the tableswitch or lookupswitch at `op_addr`,
+    /// which aligns its operands to 4 bytes from the start of the code.
+    #[inline(always)]
+    fn skip_switch_padding(&mut self, op_addr: Address) {
+        let code = self.stack.frame().method().code() as usize;
+        let bci = op_addr.as_usize() - code;
+        self.skip_operands((3 - bci % 4) as isize);
     }
 
     #[inline(always)]
@@ -2912,21 +2983,8 @@ impl Interpreter {
     }
 
     #[inline(always)]
-    pub fn compute_args_slots(&self, method: MethodPtr, vm: VMPtr) -> isize {
-        let params = method.params();
-        let mut param_idx = params.length() - 1;
-
-        let mut args_slots = 0;
-        while param_idx >= 0 {
-            let param: JClassPtr = params.get(param_idx).cast();
-            if JClass::is_long(param, vm) || JClass::is_double(param, vm) {
-                args_slots += 2;
-            } else {
-                args_slots += 1;
-            }
-            param_idx -= 1;
-        }
-        return args_slots;
+    pub fn compute_args_slots(&self, method: MethodPtr) -> isize {
+        return method.args_slots();
     }
 
     #[inline(always)]
@@ -2944,6 +3002,24 @@ impl Interpreter {
     fn op_code_as_instr(op_code: u8) -> JvmInstruction {
         return unsafe { std::mem::transmute(op_code) };
     }
+
+    /// The counters of the method of the current frame.
+    #[cfg(feature = "interpreter-stats")]
+    #[inline(always)]
+    fn op_counters(&mut self) -> &MethodCounters {
+        let method = self.stack.frame().method();
+        if method != self.counted_method {
+            self.counted_method = method;
+            self.counters = self.vm.op_stats.counters(method);
+        }
+        return unsafe { &*self.counters };
+    }
+
+    /// The mnemonic of an opcode the interpreter executed, e.g. `iload0`.
+    #[cfg(feature = "interpreter-stats")]
+    pub(crate) fn op_code_name(op_code: u8) -> String {
+        return format!("{:?}", Self::op_code_as_instr(op_code)).to_lowercase();
+    }
 }
 
 #[allow(dead_code)]
@@ -2964,3 +3040,141 @@ impl From<u8> for ArrayType {
         unsafe { std::mem::transmute(value) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        init_op_code_table, Interpreter, JvmInstruction, OP_CODE_TABLE, OP_CODE_TABLE_SIZE,
+    };
+    use crate::memory::{Address, MB};
+    use crate::vm::VMPtr;
+
+    /// Reads the operands of the instruction `code` with `read`, returns what
+    /// it decoded and the number of operand bytes it consumed.
+    fn decode<T>(code: &[u8], read: impl FnOnce(&mut Interpreter) -> T) -> (T, usize) {
+        let operands = Address::new(code.as_ptr()).offset(1);
+        let mut interp = Interpreter::new(Address::new(code.as_ptr()), 0, VMPtr::null());
+        interp.pc = operands;
+        let decoded = read(&mut interp);
+        return (decoded, interp.pc.as_usize() - operands.as_usize());
+    }
+
+    #[test]
+    fn op_code_table_initialized_once() {
+        let code = [0u8; 1];
+        let _ = Interpreter::new(Address::new(code.as_ptr()), 0, VMPtr::null());
+        let table: [u64; OP_CODE_TABLE_SIZE] = unsafe { OP_CODE_TABLE };
+        let mut expected = [0u64; OP_CODE_TABLE_SIZE];
+        JvmInstruction::init_instructions_table(&mut expected);
+        assert_eq!(table, expected);
+        assert_ne!(table[JvmInstruction::Nop as usize], 0);
+        // the test binary is position independent, the labels follow the
+        // code wherever ASLR placed it
+        let code_addr = init_op_code_table as usize as u64;
+        for label_addr in table.iter().filter(|addr| **addr != 0) {
+            assert!(label_addr.abs_diff(code_addr) < (64 * MB) as u64);
+        }
+
+        let handles = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    let code = [0u8; 1];
+                    let _ = Interpreter::new(Address::new(code.as_ptr()), 0, VMPtr::null());
+                    return unsafe { OP_CODE_TABLE };
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), table);
+        }
+    }
+
+    #[test]
+    fn read_operand_u16() {
+        let getstatic = [JvmInstruction::GetStatic as u8, 0x12, 0x34];
+        assert_eq!(
+            (0x1234, 2),
+            decode(&getstatic, Interpreter::read_operand_u16)
+        );
+        let ldc_w = [JvmInstruction::LdcW as u8, 0xff, 0x01];
+        assert_eq!((0xff01, 2), decode(&ldc_w, Interpreter::read_operand_u16));
+        let invokeinterface = [JvmInstruction::InvokeInterface as u8, 0x01, 0x02, 3, 0];
+        let decoded = decode(&invokeinterface, |interp| {
+            return (interp.read_operand_u16(), interp.read_operand());
+        });
+        assert_eq!(((0x0102, 3), 3), decoded);
+        let multianewarray = [JvmInstruction::MultiANewArray as u8, 0x01, 0x00, 2];
+        let decoded = decode(&multianewarray, |interp| {
+            return (interp.read_operand_u16(), interp.read_operand());
+        });
+        assert_eq!(((0x0100, 2), 3), decoded);
+        // unlike ldc_w, ldc has a single byte index
+        let ldc = [JvmInstruction::Ldc as u8, 0xfe];
+        assert_eq!((0xfe, 1), decode(&ldc, Interpreter::read_operand));
+    }
+
+    #[test]
+    fn read_operand_i16() {
+        let goto = [JvmInstruction::Goto as u8, 0xff, 0xf6];
+        assert_eq!((-10, 2), decode(&goto, Interpreter::read_operand_i16));
+        let ifeq = [JvmInstruction::IfEq as u8, 0x12, 0x34];
+        assert_eq!((0x1234, 2), decode(&ifeq, Interpreter::read_operand_i16));
+    }
+
+    #[test]
+    fn read_operand_i32() {
+        let goto_w = [JvmInstruction::GotoW as u8, 0xff, 0xff, 0xff, 0xf0];
+        assert_eq!((-16, 4), decode(&goto_w, Interpreter::read_operand_i32));
+        let jsr_w = [JvmInstruction::JsrW as u8, 0x00, 0x01, 0x00, 0x00];
+        assert_eq!((0x10000, 4), decode(&jsr_w, Interpreter::read_operand_i32));
+    }
+
+    #[test]
+    fn immediate_operands() {
+        let bipush = [JvmInstruction::BIPush as u8, 0x80];
+        assert_eq!((-128, 1), decode(&bipush, Interpreter::read_operand_i8));
+        let sipush = [JvmInstruction::SIPush as u8, 0xff, 0x85];
+        assert_eq!((-123, 2), decode(&sipush, Interpreter::read_operand_i16));
+        let iinc = [JvmInstruction::IInc as u8, 5, 0xfb];
+        let decoded = decode(&iinc, |interp| {
+            return (interp.read_operand(), interp.read_operand_i8());
+        });
+        assert_eq!(((5, -5), 2), decoded);
+        let wide_iinc = [
+            JvmInstruction::Wide as u8,
+            JvmInstruction::IInc as u8,
+            0x01,
+            0x00,
+            0xfc,
+            0x18,
+        ];
+        let decoded = decode(&wide_iinc, |interp| {
+            return (
+                interp.read_operand(),
+                interp.read_operand_u16(),
+                interp.read_operand_i16(),
+            );
+        });
+        assert_eq!(((JvmInstruction::IInc as u8, 0x100, -1000), 5), decoded);
+    }
+
+    #[test]
+    fn switch_operands() {
+        // the operands of a tableswitch after its padding
+        let tableswitch = [
+            JvmInstruction::TableSwitch as u8,
+            0xff,
+            0xff,
+            0xff,
+            0xfe,
+            0x00,
+            0x00,
+            0x01,
+            0x00,
+        ];
+        let decoded = decode(&tableswitch, |interp| {
+            return (interp.peek_operand_as_int(4), interp.read_operand_i32());
+        });
+        assert_eq!(((0x100, -2), 4), decoded);
+    }
+}
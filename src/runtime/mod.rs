@@ -1,4 +1,19 @@
+use crate::{memory::Address, os::FaultContext};
+
+mod call_trace;
+pub(crate) mod disassembler;
 mod frame;
 pub(crate) mod interpreter;
-mod stack;
-mod dispatch_instr;
\ No newline at end of file
+mod native_call;
+#[cfg(feature = "interpreter-stats")]
+pub(crate) mod op_stats;
+pub(crate) mod stack;
+pub(crate) mod stack_walker;
+mod dispatch_instr;
+
+/// Handles the memory access faults of the process, see
+/// `os::install_fault_handler`.
+pub(crate) fn handle_memory_fault(fault_addr: Address, ctx: &mut FaultContext) -> bool {
+    return stack::handle_stack_guard_fault(fault_addr)
+        || interpreter::handle_null_access_fault(fault_addr, ctx);
+}
@@ -0,0 +1,419 @@
+//! Calls into JNI functions using the C calling convention of the target.
+//!
+//! Every supported ABI is a module below providing `MAX_ARGS` and `call`,
+//! selected by `cfg`. Supporting another target means adding one module and
+//! its `cfg` arm here.
+
+use std::ffi::c_void;
+
+use crate::object::prelude::JLong;
+
+/// The most Java arguments (besides the `JNIEnv` and the receiver) a native
+/// method can take on this target.
+pub(crate) const MAX_ARGS: usize = abi::MAX_ARGS;
+
+/// Calls `func(env, target, args...)` with every argument passed as its raw
+/// 64 bits, in a floating-point register where `is_float` marks a `float` or
+/// `double` and in an integer register otherwise. Returns the raw floating-point
+/// return register if `float_ret`, the integer one otherwise.
+///
+/// # Safety
+///
+/// `func` must be a JNI function taking `args.len()` parameters of the classes
+/// given by `is_float` after `env` and `target`.
+pub(crate) unsafe fn call(
+    func: usize,
+    env: isize,
+    target: *mut c_void,
+    args: &[JLong],
+    is_float: &[bool],
+    float_ret: bool,
+) -> JLong {
+    debug_assert!(args.len() <= MAX_ARGS);
+    debug_assert_eq!(args.len(), is_float.len());
+    let (ret_val, float_ret_val) = abi::call(func, env, target, args, is_float);
+    return if float_ret { float_ret_val } else { ret_val };
+}
+
+/// System V AMD64: six integer and eight SSE registers, the rest on the stack.
+#[cfg(all(target_arch = "x86_64", not(target_os = "windows")))]
+mod abi {
+    use std::{arch::asm, ffi::c_void};
+
+    use crate::object::prelude::JLong;
+
+    const REG_ARGS: usize = 4;
+    const FLOAT_REG_ARGS: usize = 8;
+    const STACK_ARGS: usize = 8;
+    pub(super) const MAX_ARGS: usize = REG_ARGS + STACK_ARGS;
+
+    pub(super) unsafe fn call(
+        func: usize,
+        env: isize,
+        target: *mut c_void,
+        args: &[JLong],
+        is_float: &[bool],
+    ) -> (JLong, JLong) {
+        let mut regs = [0; REG_ARGS];
+        let mut float_regs = [0; FLOAT_REG_ARGS];
+        let mut stack = [0; STACK_ARGS];
+        super::split_args(args, is_float, &mut regs, &mut float_regs, &mut stack);
+        let float_regs = float_regs.map(|bits| f64::from_bits(bits as u64));
+        let ret_val: JLong;
+        let float_ret_val: f64;
+        asm!(
+            "sub rsp, 64",
+            "mov rax, [r10]",
+            "mov [rsp], rax",
+            "mov rax, [r10 + 8]",
+            "mov [rsp + 8], rax",
+            "mov rax, [r10 + 16]",
+            "mov [rsp + 16], rax",
+            "mov rax, [r10 + 24]",
+            "mov [rsp + 24], rax",
+            "mov rax, [r10 + 32]",
+            "mov [rsp + 32], rax",
+            "mov rax, [r10 + 40]",
+            "mov [rsp + 40], rax",
+            "mov rax, [r10 + 48]",
+            "mov [rsp + 48], rax",
+            "mov rax, [r10 + 56]",
+            "mov [rsp + 56], rax",
+            "call r11",
+            "add rsp, 64",
+            in("r11") func,
+            in("r10") stack.as_ptr(),
+            in("rdi") env,
+            in("rsi") target,
+            in("rdx") regs[0],
+            in("rcx") regs[1],
+            in("r8") regs[2],
+            in("r9") regs[3],
+            inout("xmm0") float_regs[0] => float_ret_val,
+            in("xmm1") float_regs[1],
+            in("xmm2") float_regs[2],
+            in("xmm3") float_regs[3],
+            in("xmm4") float_regs[4],
+            in("xmm5") float_regs[5],
+            in("xmm6") float_regs[6],
+            in("xmm7") float_regs[7],
+            out("rax") ret_val,
+            clobber_abi("C"),
+        );
+        return (ret_val, float_ret_val.to_bits() as JLong);
+    }
+}
+
+/// Microsoft x64: four integer registers backed by 32 bytes of shadow space,
+/// the rest on the stack above it. The registers go by position, so the
+/// first arguments are loaded into both the integer and the SSE register of
+/// their position and the callee reads the one its parameter type selects.
+#[cfg(all(target_arch = "x86_64", target_os = "windows"))]
+mod abi {
+    use std::{arch::asm, ffi::c_void};
+
+    use crate::object::prelude::JLong;
+
+    const REG_ARGS: usize = 2;
+    const STACK_ARGS: usize = 10;
+    pub(super) const MAX_ARGS: usize = REG_ARGS + STACK_ARGS;
+
+    pub(super) unsafe fn call(
+        func: usize,
+        env: isize,
+        target: *mut c_void,
+        args: &[JLong],
+        _is_float: &[bool],
+    ) -> (JLong, JLong) {
+        let mut regs = [0; REG_ARGS];
+        let mut stack = [0; STACK_ARGS];
+        super::split_args(args, &[false; MAX_ARGS], &mut regs, &mut [], &mut stack);
+        let float_regs = regs.map(|bits| f64::from_bits(bits as u64));
+        let ret_val: JLong;
+        let float_ret_val: f64;
+        asm!(
+            "sub rsp, 112",
+            "mov rax, [r10]",
+            "mov [rsp + 32], rax",
+            "mov rax, [r10 + 8]",
+            "mov [rsp + 40], rax",
+            "mov rax, [r10 + 16]",
+            "mov [rsp + 48], rax",
+            "mov rax, [r10 + 24]",
+            "mov [rsp + 56], rax",
+            "mov rax, [r10 + 32]",
+            "mov [rsp + 64], rax",
+            "mov rax, [r10 + 40]",
+            "mov [rsp + 72], rax",
+            "mov rax, [r10 + 48]",
+            "mov [rsp + 80], rax",
+            "mov rax, [r10 + 56]",
+            "mov [rsp + 88], rax",
+            "mov rax, [r10 + 64]",
+            "mov [rsp + 96], rax",
+            "mov rax, [r10 + 72]",
+            "mov [rsp + 104], rax",
+            "call r11",
+            "add rsp, 112",
+            in("r11") func,
+            in("r10") stack.as_ptr(),
+            in("rcx") env,
+            in("rdx") target,
+            in("r8") regs[0],
+            in("r9") regs[1],
+            in("xmm2") float_regs[0],
+            in("xmm3") float_regs[1],
+            out("rax") ret_val,
+            out("xmm0") float_ret_val,
+            clobber_abi("C"),
+        );
+        return (ret_val, float_ret_val.to_bits() as JLong);
+    }
+}
+
+/// AAPCS64, also used by Windows on ARM: eight integer and eight SIMD
+/// registers. Apple packs stack arguments by their natural size, so only
+/// register arguments are supported to keep one implementation for all three.
+#[cfg(target_arch = "aarch64")]
+mod abi {
+    use std::{arch::asm, ffi::c_void};
+
+    use crate::object::prelude::JLong;
+
+    const REG_ARGS: usize = 6;
+    const FLOAT_REG_ARGS: usize = 8;
+    pub(super) const MAX_ARGS: usize = REG_ARGS;
+
+    pub(super) unsafe fn call(
+        func: usize,
+        env: isize,
+        target: *mut c_void,
+        args: &[JLong],
+        is_float: &[bool],
+    ) -> (JLong, JLong) {
+        let mut regs = [0; REG_ARGS];
+        let mut float_regs = [0; FLOAT_REG_ARGS];
+        super::split_args(args, is_float, &mut regs, &mut float_regs, &mut []);
+        let float_regs = float_regs.map(|bits| f64::from_bits(bits as u64));
+        let ret_val: JLong;
+        let float_ret_val: f64;
+        asm!(
+            "blr {}",
+            in(reg) func,
+            inout("x0") env => ret_val,
+            in("x1") target,
+            in("x2") regs[0],
+            in("x3") regs[1],
+            in("x4") regs[2],
+            in("x5") regs[3],
+            in("x6") regs[4],
+            in("x7") regs[5],
+            inout("v0") float_regs[0] => float_ret_val,
+            in("v1") float_regs[1],
+            in("v2") float_regs[2],
+            in("v3") float_regs[3],
+            in("v4") float_regs[4],
+            in("v5") float_regs[5],
+            in("v6") float_regs[6],
+            in("v7") float_regs[7],
+            clobber_abi("C"),
+        );
+        return (ret_val, float_ret_val.to_bits() as JLong);
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+compile_error!("no native calling convention for this target, add one in runtime/native_call.rs");
+
+/// Assigns `args` in order to the next free integer or floating-point register
+/// as `is_float` selects, and to the next stack slot once those run out.
+fn split_args(
+    args: &[JLong],
+    is_float: &[bool],
+    regs: &mut [JLong],
+    float_regs: &mut [JLong],
+    stack: &mut [JLong],
+) {
+    let (mut reg, mut float_reg, mut slot) = (0, 0, 0);
+    for (&arg, &is_float) in args.iter().zip(is_float) {
+        if is_float && float_reg < float_regs.len() {
+            float_regs[float_reg] = arg;
+            float_reg += 1;
+        } else if !is_float && reg < regs.len() {
+            regs[reg] = arg;
+            reg += 1;
+        } else {
+            stack[slot] = arg;
+            slot += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::c_void;
+
+    use super::{call, MAX_ARGS};
+    use crate::object::prelude::JLong;
+
+    extern "system" fn weighted_sum6(
+        env: isize,
+        target: *mut c_void,
+        a0: JLong,
+        a1: JLong,
+        a2: JLong,
+        a3: JLong,
+        a4: JLong,
+        a5: JLong,
+    ) -> JLong {
+        return env as JLong * 1000
+            + target as JLong * 100
+            + a0
+            + 2 * a1
+            + 3 * a2
+            + 4 * a3
+            + 5 * a4
+            + 6 * a5;
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    extern "system" fn weighted_sum12(
+        env: isize,
+        target: *mut c_void,
+        a0: JLong,
+        a1: JLong,
+        a2: JLong,
+        a3: JLong,
+        a4: JLong,
+        a5: JLong,
+        a6: JLong,
+        a7: JLong,
+        a8: JLong,
+        a9: JLong,
+        a10: JLong,
+        a11: JLong,
+    ) -> JLong {
+        return weighted_sum6(env, target, a0, a1, a2, a3, a4, a5)
+            + 7 * a6
+            + 8 * a7
+            + 9 * a8
+            + 10 * a9
+            + 11 * a10
+            + 12 * a11;
+    }
+
+    extern "system" fn weighted_mixed_sum6(
+        env: isize,
+        target: *mut c_void,
+        a0: JLong,
+        a1: f64,
+        a2: JLong,
+        a3: f32,
+        a4: f64,
+        a5: JLong,
+    ) -> f64 {
+        return (env as JLong * 1000 + target as JLong * 100 + a0 + 3 * a2 + 6 * a5) as f64
+            + 2.0 * a1
+            + 4.0 * a3 as f64
+            + 5.0 * a4;
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    extern "system" fn weighted_float_sum12(
+        _env: isize,
+        _target: *mut c_void,
+        a0: f64,
+        a1: f64,
+        a2: f64,
+        a3: f64,
+        a4: f64,
+        a5: f64,
+        a6: f64,
+        a7: f64,
+        a8: f64,
+        a9: JLong,
+        a10: f32,
+        a11: f64,
+    ) -> f32 {
+        return (a0
+            + 2.0 * a1
+            + 3.0 * a2
+            + 4.0 * a3
+            + 5.0 * a4
+            + 6.0 * a5
+            + 7.0 * a6
+            + 8.0 * a7
+            + 9.0 * a8
+            + 10.0 * a9 as f64
+            + 11.0 * a10 as f64
+            + 12.0 * a11) as f32;
+    }
+
+    #[test]
+    fn call_passes_arguments_in_order() {
+        let target = 2 as *mut c_void;
+        let args: Vec<JLong> = (1..=MAX_ARGS as JLong).collect();
+        let ints = [false; MAX_ARGS];
+        let sum6 = unsafe {
+            call(
+                weighted_sum6 as usize,
+                3,
+                target,
+                &args[..6],
+                &ints[..6],
+                false,
+            )
+        };
+        assert_eq!(3200 + 1 + 4 + 9 + 16 + 25 + 36, sum6);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            assert_eq!(12, MAX_ARGS);
+            let sum12 = unsafe { call(weighted_sum12 as usize, 3, target, &args, &ints, false) };
+            assert_eq!(3200 + (1..=12).map(|i| i * i).sum::<JLong>(), sum12);
+        }
+    }
+
+    #[test]
+    fn call_passes_floating_point_arguments() {
+        let double = |val: f64| val.to_bits() as JLong;
+        let float = |val: f32| val.to_bits() as JLong;
+        let target = 2 as *mut c_void;
+        let args = [1, double(2.0), 3, float(4.0), double(5.0), 6];
+        let is_float = [false, true, false, true, true, false];
+        let sum6 = unsafe {
+            call(
+                weighted_mixed_sum6 as usize,
+                3,
+                target,
+                &args,
+                &is_float,
+                true,
+            )
+        };
+        assert_eq!(
+            3200.0 + 1.0 + 4.0 + 9.0 + 16.0 + 25.0 + 36.0,
+            f64::from_bits(sum6 as u64)
+        );
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            let mut args: Vec<JLong> = (1..=9).map(|i| double(i as f64)).collect();
+            args.extend([10, float(11.0), double(12.0)]);
+            let mut is_float = [true; MAX_ARGS];
+            is_float[9] = false;
+            let sum12 = unsafe {
+                call(
+                    weighted_float_sum12 as usize,
+                    3,
+                    target,
+                    &args,
+                    &is_float,
+                    true,
+                )
+            };
+            let expected = (1..=12).map(|i| i * i).sum::<i32>() as f32;
+            assert_eq!(expected, f32::from_bits(sum12 as u32));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Per method counters of the interpreter, only compiled with the
+//! `interpreter-stats` feature, see
+//! [`VM::interpreter_stats`](crate::vm::VM::interpreter_stats).
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use parking_lot::Mutex;
+
+use crate::object::method::MethodPtr;
+use crate::stats::MethodOpStats;
+
+use super::interpreter::Interpreter;
+
+pub(crate) struct MethodCounters {
+    ops: [AtomicU64; 256],
+    branches_taken: AtomicU64,
+    branches_not_taken: AtomicU64,
+}
+
+impl MethodCounters {
+    fn new() -> Self {
+        return Self {
+            ops: std::array::from_fn(|_| AtomicU64::new(0)),
+            branches_taken: AtomicU64::new(0),
+            branches_not_taken: AtomicU64::new(0),
+        };
+    }
+
+    #[inline(always)]
+    pub(crate) fn count_op(&self, op_code: u8) {
+        self.ops[usize::from(op_code)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    pub(crate) fn count_branch(&self, taken: bool) {
+        if taken {
+            self.branches_taken.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.branches_not_taken.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The counters of every method that ran in the interpreter. Counters are
+/// never dropped before the VM, so the interpreters keep pointers to the ones
+/// of their current method and only look them up when the method changes.
+#[derive(Default)]
+pub(crate) struct OpStatsCollector {
+    methods: Mutex<HashMap<usize, (MethodPtr, Box<MethodCounters>)>>,
+}
+
+impl OpStatsCollector {
+    pub(crate) fn counters(&self, method: MethodPtr) -> *const MethodCounters {
+        let mut methods = self.methods.lock();
+        let (_, counters) = methods
+            .entry(method.as_usize())
+            .or_insert_with(|| (method, Box::new(MethodCounters::new())));
+        return counters.as_ref() as *const MethodCounters;
+    }
+
+    /// The methods that executed any opcode, most executed opcodes first.
+    pub(crate) fn snapshot(&self) -> Vec<MethodOpStats> {
+        let methods = self.methods.lock();
+        let mut stats: Vec<MethodOpStats> = methods
+            .values()
+            .map(|(method, counters)| {
+                let mut op_counts: Vec<(String, u64)> = counters
+                    .ops
+                    .iter()
+                    .enumerate()
+                    .map(|(op_code, count)| (op_code as u8, count.load(Ordering::Relaxed)))
+                    .filter(|(_, count)| *count > 0)
+                    .map(|(op_code, count)| (Interpreter::op_code_name(op_code), count))
+                    .collect();
+                op_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                MethodOpStats {
+                    class_name: method.decl_cls().binary_name(),
+                    method_name: method.name().as_str().to_string(),
+                    descriptor: method.descriptor().as_str().to_string(),
+                    op_counts,
+                    branches_taken: counters.branches_taken.load(Ordering::Relaxed),
+                    branches_not_taken: counters.branches_not_taken.load(Ordering::Relaxed),
+                }
+            })
+            .filter(|method| !method.op_counts.is_empty())
+            .collect();
+        stats.sort_by(|a, b| {
+            b.total_ops()
+                .cmp(&a.total_ops())
+                .then_with(|| a.class_name.cmp(&b.class_name))
+                .then_with(|| a.method_name.cmp(&b.method_name))
+                .then_with(|| a.descriptor.cmp(&b.descriptor))
+        });
+        return stats;
+    }
+}
@@ -1,11 +1,17 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use crate::{
-    memory::{is_align_of, Address, POINTER_SIZE},
+    compat,
+    execution::CancelReason,
+    memory::{align_of, is_align_of, Address, KB, POINTER_SIZE},
     object::{
         class::JClassPtr,
         method::MethodPtr,
         prelude::{JDouble, JFloat, JInt, JLong, ObjectPtr, ObjectRawPtr},
         Object,
     },
+    os,
     thread::{Thread, ThreadPtr},
 };
 
@@ -14,12 +20,131 @@ use super::frame::{Frame, FramePtr};
 type StackSlot = *mut std::ffi::c_void;
 type StackAddress = *mut StackSlot;
 
+/// What a stack slot holds. The slots themselves are untagged, the tags are
+/// kept in a shadow array next to them and tell the references apart for
+/// root scanning.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum SlotTag {
+    /// A number, a half of a long or double, or a slot of a frame header.
+    Primitive,
+    Reference,
+    /// A code address pushed by `jsr`, it is neither a reference nor a
+    /// number and only `astore`, the dup family and `ret` may touch it.
+    ReturnAddress,
+}
+
+/// A slot moved as a whole by the dup and store instructions.
+#[derive(Clone, Copy)]
+pub struct TaggedSlot {
+    value: StackSlot,
+    tag: SlotTag,
+}
+
+/// The inaccessible region mapped below each interpreter stack. Frames are
+/// checked against the stack limit before they are pushed, the guard catches
+/// whatever slips past that check.
+const STACK_GUARD_SIZE: usize = 64 * KB;
+
+/// The memory of an interpreter stack, mapped on its own with a guard region
+/// right below the stack limit.
+pub(crate) struct StackMemory {
+    addr: Address,
+    guard_size: usize,
+    stack_size: usize,
+    /// Whether the guard is protected, it is not from a hit until the frames
+    /// of the thread unwound.
+    guard_armed: AtomicBool,
+}
+
+impl StackMemory {
+    pub(crate) fn new(stack_size: usize) -> StackMemory {
+        let stack_size = align_of(stack_size, os::page_size());
+        let guard_size = align_of(STACK_GUARD_SIZE, os::page_size());
+        let addr = os::reserve_memory(guard_size + stack_size);
+        if addr.is_null()
+            || !os::commit_memory(addr, guard_size + stack_size, false)
+            || !os::protect_memory(addr, guard_size)
+        {
+            panic!("failed to allocate a {} bytes thread stack", stack_size);
+        }
+        return StackMemory {
+            addr,
+            guard_size,
+            stack_size,
+            guard_armed: AtomicBool::new(true),
+        };
+    }
+
+    pub(crate) fn stack_addr(&self) -> Address {
+        return self.addr.uoffset(self.guard_size);
+    }
+
+    pub(crate) fn stack_size(&self) -> usize {
+        return self.stack_size;
+    }
+
+    pub(crate) fn is_guard(&self, addr: Address) -> bool {
+        return addr.as_usize() >= self.addr.as_usize()
+            && addr.as_usize() < self.stack_addr().as_usize();
+    }
+
+    pub(crate) fn is_guard_armed(&self) -> bool {
+        return self.guard_armed.load(Ordering::Acquire);
+    }
+
+    /// Lets the code that hit the guard run on, whether the guard was armed.
+    fn disarm_guard(&self) -> bool {
+        return self.guard_armed.swap(false, Ordering::AcqRel)
+            && os::unprotect_memory(self.addr, self.guard_size);
+    }
+
+    /// Protects the guard again once the frames past the stack limit are gone.
+    pub(crate) fn rearm_guard(&self) {
+        if !self.is_guard_armed() {
+            if !os::protect_memory(self.addr, self.guard_size) {
+                panic!("failed to protect the guard of a thread stack");
+            }
+            self.guard_armed.store(true, Ordering::Release);
+        }
+    }
+}
+
+impl Drop for StackMemory {
+    fn drop(&mut self) {
+        os::release_memory(self.addr, self.guard_size + self.stack_size);
+    }
+}
+
+/// Turns an access to the stack guard of the current thread into a stack
+/// overflow of the Java code it runs, whether the fault is handled. Like the
+/// yellow zone of HotSpot, the guard is disarmed so that the faulting access
+/// completes, and the execution of the thread is cancelled with
+/// `CancelReason::StackOverflow`: its frames unwind to the native or Rust
+/// caller, which rearms the guard. Accesses past the disarmed guard are left
+/// to the previous fault handler.
+pub(crate) fn handle_stack_guard_fault(fault_addr: Address) -> bool {
+    let thread = Thread::current();
+    if thread.is_null() || !thread.stack_memory().is_guard(fault_addr) {
+        return false;
+    }
+    if !thread.stack_memory().disarm_guard() {
+        os::fatal_error("java.lang.StackOverflowError: hit the guard of the interpreter stack\n");
+    }
+    thread
+        .as_mut_ref()
+        .interpreter_mut()
+        .cancel_execution(CancelReason::StackOverflow);
+    return true;
+}
+
 pub struct Stack {
     stack_base: StackAddress,
     stack_limit: StackAddress,
     sp: StackAddress,
     bp: StackAddress,
     frame: FramePtr,
+    tags: Box<[Cell<SlotTag>]>,
     time: std::time::SystemTime,
 }
 
@@ -34,10 +159,39 @@ impl Stack {
             sp: stack_base,
             bp: stack_base,
             frame: FramePtr::null(),
+            tags: vec![Cell::new(SlotTag::Primitive); stack_size / POINTER_SIZE].into_boxed_slice(),
             time: std::time::SystemTime::now(),
         };
     }
 
+    #[inline(always)]
+    fn tag_of(&self, slot: StackAddress) -> &Cell<SlotTag> {
+        let index = (compat::addr(slot) - compat::addr(self.stack_limit)) / POINTER_SIZE;
+        return &self.tags[index];
+    }
+
+    #[inline(always)]
+    fn local_addr(&self, index: isize) -> StackAddress {
+        return unsafe { self.bp.offset(-(index + 1)) };
+    }
+
+    /// Whether `slots` more slots fit below the current stack pointer.
+    #[inline(always)]
+    pub fn has_room(&self, slots: isize) -> bool {
+        let free_slots = (compat::addr(self.sp) - compat::addr(self.stack_limit)) / POINTER_SIZE;
+        return slots <= free_slots as isize;
+    }
+
+    /// The slots a frame of `method` takes beyond its arguments, the
+    /// interpreter checks that they fit before pushing the frame.
+    #[inline(always)]
+    pub fn frame_slots(method: MethodPtr, args_slots: isize) -> isize {
+        if method.is_native() {
+            return 3;
+        }
+        return method.max_locals() as isize + 3 + method.max_stack() as isize - args_slots;
+    }
+
     #[inline(always)]
     pub fn new_call_frame(
         &mut self,
@@ -50,14 +204,20 @@ impl Stack {
         thread: ThreadPtr,
     ) {
         self.time = std::time::SystemTime::now();
+        // the parser rejects such methods, the frame header would overwrite
+        // the arguments
+        if (method.max_locals() as isize) < args_slots {
+            todo!(
+                "throw VerifyError, {}#{} has {} locals for {} argument slots",
+                class.name().as_str(),
+                method.name().as_str(),
+                method.max_locals(),
+                args_slots
+            );
+        }
+        debug_assert!(self.has_room(Self::frame_slots(method, args_slots)));
         debug_assert!(
-            method.max_locals() as isize >= args_slots,
-            "trace {}#{}",
-            class.name().as_str(),
-            method.name().as_str()
-        );
-        debug_assert!(
-            args_slots >= method.params().length() as isize + obj_ref_size,
+            args_slots >= method.param_count() as isize + obj_ref_size,
             "trace {}#{}",
             class.name().as_str(),
             method.name().as_str()
@@ -99,11 +259,10 @@ impl Stack {
                 self.stack_trace_str()
             );
         }
-        unsafe {
-            self.store_jobj(std::mem::transmute(prev_sp), max_locals);
-            self.store_jobj(std::mem::transmute(prev_bp), max_locals + 1);
-            self.store_jobj(std::mem::transmute(pc), max_locals + 2);
-        }
+        // the locals past the arguments may still be tagged by a frame popped
+        // before
+        self.set_primitive_tags(self.local_addr(max_locals - 1), max_locals - args_slots);
+        self.store_frame_header(prev_sp, prev_bp, pc, max_locals);
     }
 
     pub fn new_native_call_frame(
@@ -117,6 +276,7 @@ impl Stack {
         thread: ThreadPtr,
     ) {
         self.time = std::time::SystemTime::now();
+        debug_assert!(self.has_room(Self::frame_slots(method, args_slots)));
         let prev_sp = unsafe { self.sp.offset(args_slots) };
         let prev_bp = self.bp;
         self.frame = Frame::new(
@@ -146,11 +306,25 @@ impl Stack {
             log::trace!("new_call_frame objref: 0x{:x}", obj_ref.as_isize());
             debug_assert!(obj_ref.is_not_null(), "{}", self.stack_trace_str());
         }
+        self.store_frame_header(prev_sp, prev_bp, pc, args_slots);
+    }
+
+    /// Saves the registers of the caller right after the locals of a frame.
+    #[inline(always)]
+    fn store_frame_header(
+        &self,
+        prev_sp: StackAddress,
+        prev_bp: StackAddress,
+        pc: Address,
+        locals: isize,
+    ) {
+        let header = self.local_addr(locals + 2);
         unsafe {
-            self.store_jobj(std::mem::transmute(prev_sp), args_slots);
-            self.store_jobj(std::mem::transmute(prev_bp), args_slots + 1);
-            self.store_jobj(std::mem::transmute(pc), args_slots + 2);
+            *header.offset(2) = prev_sp as StackSlot;
+            *header.offset(1) = prev_bp as StackSlot;
+            *header = pc.raw_ptr() as StackSlot;
         }
+        self.set_primitive_tags(header, 3);
     }
 
     #[inline(always)]
@@ -204,7 +378,7 @@ impl Stack {
 
     #[inline(always)]
     pub fn pop_jobj(&mut self) -> ObjectPtr {
-        debug_assert!(self.sp.addr() < self.bp.addr());
+        debug_assert!(compat::addr(self.sp) < compat::addr(self.bp));
         let val;
         unsafe {
             val = ObjectPtr::from_c_ptr(*self.sp);
@@ -220,7 +394,7 @@ impl Stack {
 
     #[inline(always)]
     pub fn peek_jobj(&self) -> ObjectPtr {
-        debug_assert!(self.sp.addr() < self.bp.addr());
+        debug_assert!(compat::addr(self.sp) < compat::addr(self.bp));
         let val;
         unsafe {
             val = ObjectPtr::from_c_ptr(*self.sp);
@@ -229,14 +403,17 @@ impl Stack {
     }
 
     #[inline(always)]
-    pub fn peek_slot(&self) -> StackSlot {
-        debug_assert!(self.sp.addr() < self.bp.addr());
-        return unsafe { *self.sp };
+    pub fn peek_slot(&self) -> TaggedSlot {
+        debug_assert!(compat::addr(self.sp) < compat::addr(self.bp));
+        return TaggedSlot {
+            value: unsafe { *self.sp },
+            tag: self.tag_of(self.sp).get(),
+        };
     }
 
     #[inline(always)]
     pub fn pop<T: StackPrimitiveValue + Copy>(&mut self) -> T {
-        debug_assert!(self.sp.addr() < self.bp.addr());
+        debug_assert!(compat::addr(self.sp) < compat::addr(self.bp));
         let slots = Self::calc_slots::<T>();
         let val;
         unsafe {
@@ -247,19 +424,15 @@ impl Stack {
     }
 
     #[inline(always)]
-    pub fn pop_slot(&mut self) -> StackSlot {
-        debug_assert!(self.sp.addr() < self.bp.addr());
-        let val;
-        unsafe {
-            val = *self.sp;
-            self.sp = self.sp.offset(1);
-        }
+    pub fn pop_slot(&mut self) -> TaggedSlot {
+        let val = self.peek_slot();
+        self.sp = unsafe { self.sp.offset(1) };
         return val;
     }
 
     #[inline(always)]
     pub fn discard<T: StackPrimitiveValue + Copy>(&mut self) {
-        debug_assert!(self.sp.addr() < self.bp.addr());
+        debug_assert!(compat::addr(self.sp) < compat::addr(self.bp));
         let slots = Self::calc_slots::<T>();
         unsafe {
             self.sp = self.sp.offset(slots);
@@ -277,6 +450,7 @@ impl Stack {
             debug_assert!(is_align_of(self.sp as usize, POINTER_SIZE));
             // *self.sp = val.as_isize();
         }
+        self.tag_of(self.sp).set(SlotTag::Reference);
     }
 
     // TODO push char 的时候错误
@@ -285,39 +459,75 @@ impl Stack {
         let slots = Self::calc_slots::<T>();
         log::trace!(
             "before push 0x{:x}, 0x{:x}, slots {}",
-            self.sp.addr(),
-            self.bp.addr(),
+            compat::addr(self.sp),
+            compat::addr(self.bp),
             slots
         );
         unsafe {
             self.sp = self.sp.offset(-slots);
             *(self.sp as *mut T) = val;
         }
+        self.set_primitive_tags(self.sp, slots);
         debug_assert!(is_align_of(self.sp as usize, 8));
         log::trace!(
             "after push 0x{:x}, 0x{:x}, slots {}",
-            self.sp.addr(),
-            self.bp.addr(),
+            compat::addr(self.sp),
+            compat::addr(self.bp),
             slots
         );
     }
 
     #[inline(always)]
-    pub fn push_slot(&mut self, val: StackSlot) {
+    pub fn push_slot(&mut self, val: TaggedSlot) {
         unsafe {
             self.sp = self.sp.offset(-1);
-            *self.sp = val;
+            *self.sp = val.value;
+        }
+        self.tag_of(self.sp).set(val.tag);
+    }
+
+    /// Pushes the address of the instruction following a `jsr`.
+    #[inline(always)]
+    pub fn push_return_address(&mut self, pc: Address) {
+        self.push_slot(TaggedSlot {
+            value: pc.raw_ptr() as StackSlot,
+            tag: SlotTag::ReturnAddress,
+        });
+    }
+
+    /// Stores a slot popped by `astore`, which may be a return address.
+    #[inline(always)]
+    pub fn store_slot(&self, val: TaggedSlot, index: isize) {
+        let local = self.local_addr(index);
+        unsafe {
+            *local = val.value;
+        }
+        self.tag_of(local).set(val.tag);
+    }
+
+    /// Loads the return address `ret` continues at.
+    #[inline(always)]
+    pub fn load_return_address(&self, index: isize) -> Address {
+        let local = self.local_addr(index);
+        if self.tag_of(local).get() != SlotTag::ReturnAddress {
+            todo!(
+                "throw VerifyError, ret with local {} not holding a return address in {}",
+                index,
+                self.frame.method().name().as_str()
+            );
         }
+        return Address::new(unsafe { *local } as *const u8);
     }
 
     #[inline(always)]
     pub fn load_jobj(&self, index: isize) -> ObjectPtr {
+        debug_assert!(self.tag_of(self.local_addr(index)).get() == SlotTag::Reference);
         return ObjectPtr::from_raw(self.load_jobj_raw(index));
     }
 
     #[inline(always)]
     pub fn load_jobj_raw(&self, index: isize) -> ObjectRawPtr {
-        debug_assert!(self.sp.addr() < self.bp.addr());
+        debug_assert!(compat::addr(self.sp) < compat::addr(self.bp));
         let result = unsafe { *(self.bp.offset(-(index + 1)) as *const ObjectRawPtr) };
         log::trace!(
             "load_jobj==addr : {:x?}==={:x?}",
@@ -329,22 +539,26 @@ impl Stack {
 
     #[inline(always)]
     pub fn load_callee_objref(&self, args_slots: isize) -> ObjectPtr {
-        debug_assert!(self.sp.addr() < self.bp.addr());
+        debug_assert!(compat::addr(self.sp) < compat::addr(self.bp));
         unsafe { ObjectPtr::from_raw(*(self.sp.offset(args_slots - 1) as *const ObjectRawPtr)) }
     }
 
+    /// Loads the local `index`. A long or double takes the locals `index`
+    /// and `index + 1` and sits at the lower address of the two, where
+    /// pushing it as an argument leaves it, so arguments become the locals
+    /// of the callee in place.
     #[inline(always)]
     pub fn load<T>(&self, index: isize) -> T
     where
         T: StackPrimitiveValue + Copy,
     {
-        debug_assert!(self.sp.addr() < self.bp.addr());
+        debug_assert!(compat::addr(self.sp) < compat::addr(self.bp));
         let slots = Self::calc_slots::<T>();
         unsafe {
             log::trace!(
                 "load 0x{:x}, 0x{:x} {:?}, index {}",
-                self.sp.addr(),
-                self.bp.addr(),
+                compat::addr(self.sp),
+                compat::addr(self.bp),
                 self.bp.offset(-(index + slots)),
                 index
             );
@@ -352,18 +566,31 @@ impl Stack {
         return unsafe { *(self.bp.offset(-(index + slots)) as *mut T) };
     }
 
+    /// Stores the local `index`, both halves of a long or double, see
+    /// [`load`](Self::load).
     #[inline(always)]
     pub fn store<T: StackPrimitiveValue>(&self, val: T, index: isize) {
         let slots = Self::calc_slots::<T>();
+        let local = unsafe { self.bp.offset(-(index + slots)) };
         unsafe {
-            (self.bp.offset(-(index + slots)) as *mut T).write(val);
+            (local as *mut T).write(val);
+        }
+        self.set_primitive_tags(local, slots);
+    }
+
+    #[inline(always)]
+    fn set_primitive_tags(&self, slot: StackAddress, slots: isize) {
+        for i in 0..slots {
+            self.tag_of(unsafe { slot.offset(i) })
+                .set(SlotTag::Primitive);
         }
     }
 
     #[inline(always)]
     pub fn iinc(&self, const_val: JInt, index: isize) {
         unsafe {
-            *(self.bp.offset(-(index + 1)) as *mut JInt) += const_val;
+            let local = self.bp.offset(-(index + 1)) as *mut JInt;
+            *local = (*local).wrapping_add(const_val);
         }
     }
 
@@ -379,18 +606,8 @@ impl Stack {
             *a_ptr = y;
             *b_ptr = x;
         };
-    }
-
-    #[inline(always)]
-    pub fn store_jobj(&self, jobj: ObjectPtr, index: isize) {
-        log::trace!(
-            "store_jobj==addr : {:x?}==={:x?}",
-            unsafe { self.bp.offset(-(index + 1)) },
-            jobj.as_isize()
-        );
-        unsafe {
-            (self.bp.offset(-(index + 1)) as *mut ObjectRawPtr).write(jobj.as_mut_raw_ptr());
-        }
+        self.tag_of(self.sp)
+            .swap(self.tag_of(unsafe { self.sp.offset(1) }));
     }
 
     #[inline(always)]
@@ -403,6 +620,23 @@ impl Stack {
         self.frame
     }
 
+    /// Visits the non-null references held by the locals and operand stacks
+    /// of all frames, the visitor may update them when objects move. For the
+    /// root walk of a collector, none runs yet, see `gc::copying`.
+    #[allow(dead_code)]
+    pub(crate) fn iterate_roots<F: FnMut(&mut ObjectRawPtr)>(&self, mut visitor: F) {
+        let mut slot = self.sp;
+        while compat::addr(slot) < compat::addr(self.stack_base) {
+            if self.tag_of(slot).get() == SlotTag::Reference {
+                let root = unsafe { &mut *(slot as *mut ObjectRawPtr) };
+                if !root.is_null() {
+                    visitor(root);
+                }
+            }
+            slot = unsafe { slot.offset(1) };
+        }
+    }
+
     pub fn stack_trace<F: FnMut(FramePtr)>(&self, mut action: F) {
         let mut frame = self.frame;
         while frame.is_not_null() {
@@ -411,6 +645,25 @@ impl Stack {
         }
     }
 
+    /// Visits the frames from the top one down along with their pcs, which
+    /// is `top_pc` for the top frame and the one saved in the header of the
+    /// frame above for the rest. The pcs of native frames mean nothing.
+    pub(crate) fn walk_frames<F: FnMut(FramePtr, Address)>(&self, top_pc: Address, mut action: F) {
+        let mut frame = self.frame;
+        let mut bp = self.bp;
+        let mut pc = top_pc;
+        while frame.is_not_null() {
+            action(frame, pc);
+            // the header right after the locals, see store_frame_header
+            let header = unsafe { bp.offset(-(frame.frame_slots() + 3)) };
+            unsafe {
+                pc = Address::new(*header as *const u8);
+                bp = *header.offset(1) as StackAddress;
+            }
+            frame = frame.prev();
+        }
+    }
+
     pub fn stack_trace_str(&self) -> String {
         let mut location = String::new();
         self.stack_trace(|frame| {
@@ -444,3 +697,37 @@ impl StackPrimitiveValue for JDouble {}
 impl StackPrimitiveValue for JInt {}
 
 impl StackPrimitiveValue for JLong {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Stack, StackMemory};
+    use crate::{memory::KB, object::prelude::*, test};
+
+    #[test]
+    fn iterate_tagged_roots() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let memory = StackMemory::new(64 * KB);
+            let mut stack = Stack::new(memory.stack_addr(), memory.stack_size());
+            let greeting: ObjectPtr = vm.new_string("hello").cast();
+            stack.push::<JLong>(greeting.as_isize() as JLong);
+            stack.push_jobj(greeting);
+            stack.push_return_address(greeting.as_address());
+            stack.swap();
+            stack.push_slot(stack.peek_slot());
+            stack.push_jobj(ObjectPtr::null());
+
+            let mut roots = Vec::new();
+            stack.iterate_roots(|root| roots.push(ObjectPtr::from_raw(*root)));
+            assert_eq!(vec![greeting, greeting], roots);
+
+            // popped slots are no roots, even when overwritten by numbers
+            stack.pop_jobj();
+            stack.pop_jobj();
+            stack.pop_jobj();
+            stack.push::<JInt>(7);
+            let mut roots = Vec::new();
+            stack.iterate_roots(|root| roots.push(ObjectPtr::from_raw(*root)));
+            assert!(roots.is_empty());
+        });
+    }
+}
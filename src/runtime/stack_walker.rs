@@ -0,0 +1,82 @@
+//! The Java frames of a thread as `Throwable.getStackTrace` reports them, see
+//! [`Interpreter::stack_frames`](super::interpreter::Interpreter::stack_frames).
+
+use crate::{memory::Address, object::method::MethodPtr};
+
+/// The line number `StackTraceElement` gives native frames.
+pub const NATIVE_LINE_NUMBER: i32 = -2;
+
+/// The line number `StackTraceElement` gives frames of unknown lines.
+pub const UNKNOWN_LINE_NUMBER: i32 = -1;
+
+/// A frame of a thread, the method and the instruction it is at.
+#[derive(Clone, Copy, Debug)]
+pub struct StackFrameInfo {
+    method: MethodPtr,
+    bci: Option<u16>,
+}
+
+impl StackFrameInfo {
+    pub(crate) fn new(method: MethodPtr, bci: Option<u16>) -> Self {
+        return Self { method, bci };
+    }
+
+    /// The frame of `method` at `pc`, which lies right after the opcode of
+    /// the current instruction of the top frame and right after the invoke
+    /// instruction of the frames below.
+    pub(crate) fn at_pc(method: MethodPtr, pc: Address) -> Self {
+        let code = Address::new(method.code());
+        let bci = if method.is_native() || pc.as_usize() <= code.as_usize() {
+            None
+        } else {
+            u16::try_from(pc.as_usize() - code.as_usize() - 1)
+                .ok()
+                .filter(|bci| *bci < method.code_length())
+        };
+        return Self::new(method, bci);
+    }
+
+    pub fn method(&self) -> MethodPtr {
+        self.method
+    }
+
+    /// The index of the instruction in the code, none for native frames.
+    pub fn bci(&self) -> Option<u16> {
+        self.bci
+    }
+
+    /// The binary name of the class declaring the method, e.g.
+    /// `java.lang.Thread`.
+    pub fn declaring_class(&self) -> String {
+        return self.method.decl_cls().binary_name();
+    }
+
+    pub fn method_name(&self) -> String {
+        return self.method.name().as_str().to_string();
+    }
+
+    /// The `SourceFile` of the class declaring the method.
+    pub fn file_name(&self) -> Option<String> {
+        let source_file = self.method.decl_cls().class_data().source_file();
+        if source_file.is_null() {
+            return None;
+        }
+        return Some(source_file.as_str().to_string());
+    }
+
+    pub fn is_native(&self) -> bool {
+        return self.method.is_native();
+    }
+
+    /// The source line the frame is at, [`NATIVE_LINE_NUMBER`] for native
+    /// frames and [`UNKNOWN_LINE_NUMBER`] without a `LineNumberTable`.
+    pub fn line_number(&self) -> i32 {
+        if self.is_native() {
+            return NATIVE_LINE_NUMBER;
+        }
+        return self
+            .bci
+            .and_then(|bci| self.method.line_number(bci))
+            .map_or(UNKNOWN_LINE_NUMBER, i32::from);
+    }
+}
@@ -0,0 +1,48 @@
+//! Allocation sampling for memory profilers, see
+//! [`VM::set_allocation_sampler`](crate::vm::VM::set_allocation_sampler).
+//!
+//! Every thread counts down the bytes it allocates and reports the allocation
+//! that reaches zero, then starts over. The countdown lives in the thread's
+//! local allocation buffer, whose fast path stops short at the sample point,
+//! so allocations between two samples cost nothing extra.
+
+use std::sync::Arc;
+
+pub type AllocationCallback = dyn Fn(&AllocationSample) + Send + Sync;
+
+/// An allocation picked by the sampler.
+#[derive(Clone, Debug)]
+pub struct AllocationSample {
+    /// The binary name of the allocated class, e.g. `java.lang.String` or `[C`.
+    pub class_name: String,
+    /// The heap bytes taken by the object, header included.
+    pub size: usize,
+    pub thread_id: u64,
+    /// The Java frames of the allocating thread as `class#method`, innermost
+    /// first. Empty for objects allocated by the VM outside of any frame.
+    pub stack: Vec<String>,
+}
+
+pub(crate) struct AllocationSampler {
+    interval: usize,
+    callback: Arc<AllocationCallback>,
+}
+
+impl AllocationSampler {
+    pub(crate) fn new(interval: usize, callback: Arc<AllocationCallback>) -> Self {
+        assert!(
+            interval > 0,
+            "the allocation sampling interval must not be zero"
+        );
+        return Self { interval, callback };
+    }
+
+    /// The bytes a thread allocates between two samples.
+    pub(crate) fn interval(&self) -> usize {
+        self.interval
+    }
+
+    pub(crate) fn report(&self, sample: &AllocationSample) {
+        (self.callback)(sample);
+    }
+}
@@ -0,0 +1,121 @@
+//! Resource limits for running semi-trusted code, see
+//! [`VMConfig::sandbox`](crate::vm::VMConfig::sandbox).
+//!
+//! The limits are checked where the resources are taken: attaching threads,
+//! handing out heap memory, and the natives that open files and sockets.
+//! Java code that exceeds them is cancelled before its next instruction, its
+//! call fails with [`VMError::Cancelled`](crate::vm::VMError::Cancelled).
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::execution::CancelReason;
+use crate::thread::ThreadPtr;
+
+pub type AccessFilter = dyn Fn(&ResourceAccess) -> bool + Send + Sync;
+
+/// A file or socket access of a native, which the filter of the
+/// [`SandboxPolicy`] allows or denies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResourceAccess {
+    /// Opening the file at `path`, for writing as well if `write`.
+    OpenFile {
+        path: PathBuf,
+        write: bool,
+    },
+    Connect(SocketAddr),
+    Bind(SocketAddr),
+}
+
+impl fmt::Display for ResourceAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            ResourceAccess::OpenFile { path, write: false } => {
+                write!(f, "open {} for reading", path.display())
+            }
+            ResourceAccess::OpenFile { path, write: true } => {
+                write!(f, "open {} for writing", path.display())
+            }
+            ResourceAccess::Connect(addr) => write!(f, "connect to {}", addr),
+            ResourceAccess::Bind(addr) => write!(f, "bind to {}", addr),
+        };
+    }
+}
+
+/// The limits of a VM, none by default, e.g.
+/// `SandboxPolicy::default().with_max_threads(4).with_access_filter(|access| matches!(access, ResourceAccess::OpenFile { write: false, .. }))`.
+#[derive(Clone, Default)]
+pub struct SandboxPolicy {
+    max_threads: Option<usize>,
+    max_heap_usage: Option<usize>,
+    access_filter: Option<Arc<AccessFilter>>,
+}
+
+impl SandboxPolicy {
+    /// Allows at most `max_threads` threads attached at once, the thread that
+    /// creates the VM included. Attaching more through the invocation API
+    /// fails and `Thread.start` cancels the code that calls it.
+    pub fn with_max_threads(mut self, max_threads: usize) -> Self {
+        self.max_threads = Some(max_threads);
+        return self;
+    }
+
+    /// Caps the bytes handed out for Java objects. Threads take the heap in
+    /// local allocation buffers, so the cap counts whole buffers. The
+    /// allocation that crosses the cap still succeeds if the heap has room.
+    pub fn with_max_heap_usage(mut self, max_heap_usage: usize) -> Self {
+        self.max_heap_usage = Some(max_heap_usage);
+        return self;
+    }
+
+    /// Asks `filter` before every file or socket access of a native, the
+    /// natives skip the accesses it rejects. The filter runs on the thread of
+    /// the native.
+    pub fn with_access_filter<F: Fn(&ResourceAccess) -> bool + Send + Sync + 'static>(
+        mut self,
+        filter: F,
+    ) -> Self {
+        self.access_filter = Some(Arc::new(filter));
+        return self;
+    }
+
+    pub fn max_threads(&self) -> Option<usize> {
+        self.max_threads
+    }
+
+    pub fn max_heap_usage(&self) -> Option<usize> {
+        self.max_heap_usage
+    }
+
+    /// Whether one more thread may be attached while `thread_count` are.
+    pub(crate) fn allows_thread(&self, thread_count: usize) -> bool {
+        return self.max_threads.map_or(true, |max| thread_count < max);
+    }
+
+    /// Whether `used` bytes of the heap plus `size` more stay within the cap.
+    pub(crate) fn allows_heap_usage(&self, used: usize, size: usize) -> bool {
+        return self.max_heap_usage.map_or(true, |max| used + size <= max);
+    }
+
+    pub(crate) fn allows(&self, access: &ResourceAccess) -> bool {
+        return self
+            .access_filter
+            .as_ref()
+            .map_or(true, |filter| filter(access));
+    }
+
+    /// Whether a native of `thread` may go on with `access`, cancels the code
+    /// of the thread if not.
+    pub(crate) fn check_access(&self, access: ResourceAccess, thread: ThreadPtr) -> bool {
+        if self.allows(&access) {
+            return true;
+        }
+        thread
+            .as_mut_ref()
+            .interpreter_mut()
+            .cancel_execution(CancelReason::AccessDenied(access));
+        return false;
+    }
+}
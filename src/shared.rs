@@ -2,10 +2,10 @@ use crate::classfile::class_info::{
     JavaIOFileDescriptorInfo, JavaIOFileInfo, JavaIOFileOutputStreamInfo, JavaLangBooleanInfo,
     JavaLangByteInfo, JavaLangCharInfo, JavaLangClassLoaderNativeLibraryInfo, JavaLangDoubleInfo,
     JavaLangFloatInfo, JavaLangIntegerInfo, JavaLangLongInfo, JavaLangReflectConstructorInfo,
-    JavaLangReflectFieldInfo, JavaLangShortInfo, JavaLangStringInfo, JavaLangThreadGroupInfo,
-    JavaLangThreadInfo, JavaSecurityPrivilegedActionInfo, JavaUtilPropertiesInfo,
+    JavaLangReflectFieldInfo, JavaLangReflectMethodInfo, JavaLangShortInfo, JavaLangStringInfo,
+    JavaLangThreadGroupInfo, JavaLangThreadInfo, JavaSecurityPrivilegedActionInfo,
+    JavaUtilPropertiesInfo,
 };
-use crate::classfile::ClassLoadErr;
 use crate::object::array::JArrayPtr;
 use crate::object::class::{InitializationError, JClass, JClassPtr};
 use crate::object::prelude::*;
@@ -53,15 +53,14 @@ macro_rules! make_class_infos {
                 $(
                     let $info_name = vm
                         .bootstrap_class_loader
-                        .load_class_with_symbol(vm.shared_objs().symbols().$class_name_symbol)
-                        .map_err(|e| VMError::ClassLoaderErr(e))?;
+                        .load_class_with_symbol(vm.shared_objs().symbols().$class_name_symbol)?;
 
                     self.$info_name = $class_info::new($info_name, thread)?;
                 )*
                 $(
                     $(
                         $is_init;
-                        $info_name.initialize(thread).map_err(|e| VMError::ClassInitError(e))?;
+                        $info_name.initialize(thread)?;
                     )*
                 )*
                 return Ok(());
@@ -182,6 +181,17 @@ macro_rules! preloaded_classes {
                     return JClassPtr::null();
                 }
 
+                /// The class of arrays of the primitive class `component`, e.g.
+                /// `[I` for `int`, null for `void` and reference classes.
+                pub fn get_primitive_array_class(&self, component: JClassPtr) -> JClassPtr {
+                    $(
+                        if $is_array && self.$component_type == component {
+                            return self.$cls_field_name;
+                        }
+                    )*
+                    return JClassPtr::null();
+                }
+
                 $(
                     #[inline(always)]
                     pub fn $cls_field_name(&self) -> JClassPtr {
@@ -225,6 +235,7 @@ make_symbols!(
     {java_util_Properties, "java/util/Properties"},
     {java_lang_reflect_Field, "java/lang/reflect/Field"},
     {java_lang_reflect_Constructor, "java/lang/reflect/Constructor"},
+    {java_lang_reflect_Method, "java/lang/reflect/Method"},
     {java_security_PrivilegedAction, "java/security/PrivilegedAction"},
     {java_io_File, "java/io/File"},
     {java_io_FileDescriptor, "java/io/FileDescriptor"},
@@ -259,6 +270,7 @@ make_class_infos!(
     {java_util_properties_info, JavaUtilPropertiesInfo, java_util_Properties, [], [true]},
     {java_lang_reflect_field_info, JavaLangReflectFieldInfo, java_lang_reflect_Field, [], [true]},
     {java_lang_reflect_constructor_info, JavaLangReflectConstructorInfo, java_lang_reflect_Constructor, [], [true]},
+    {java_lang_reflect_method_info, JavaLangReflectMethodInfo, java_lang_reflect_Method, [], [true]},
     {java_security_privileged_action_info, JavaSecurityPrivilegedActionInfo, java_security_PrivilegedAction, [], [true]},
     {java_io_file_info, JavaIOFileInfo, java_io_File, [], []},
     {java_io_file_descriptor_info, JavaIOFileDescriptorInfo, java_io_FileDescriptor, [], []},
@@ -267,6 +279,40 @@ make_class_infos!(
     {java_lang_classloader_native_library_info, JavaLangClassLoaderNativeLibraryInfo, java_lang_ClassLoader_NativeLibrary, [], [] }
 );
 
+impl ClassInfos {
+    /// Boxes `value` of the primitive class `primitive`, the counterpart of
+    /// `get_unboxed_jnumber`. The box comes from `valueOf`, so small values
+    /// share the boxes Java code gets, and `void` gives null.
+    pub(crate) fn get_boxed_jvalue(
+        &self,
+        value: JValue,
+        primitive: JClassPtr,
+        vm: &VM,
+    ) -> Result<ObjectPtr, VMError> {
+        let preloaded = vm.preloaded_classes();
+        return if preloaded.is_int_cls(primitive) {
+            self.java_lang_integer_info.box_value(value, vm)
+        } else if preloaded.is_long_cls(primitive) {
+            self.java_lang_long_info.box_value(value, vm)
+        } else if preloaded.is_bool_cls(primitive) {
+            self.java_lang_boolean_info.box_value(value, vm)
+        } else if preloaded.is_byte_cls(primitive) {
+            self.java_lang_byte_info.box_value(value, vm)
+        } else if preloaded.is_char_cls(primitive) {
+            self.java_lang_char_info.box_value(value, vm)
+        } else if preloaded.is_short_cls(primitive) {
+            self.java_lang_short_info.box_value(value, vm)
+        } else if preloaded.is_float_cls(primitive) {
+            self.java_lang_float_info.box_value(value, vm)
+        } else if preloaded.is_double_cls(primitive) {
+            self.java_lang_double_info.box_value(value, vm)
+        } else {
+            debug_assert!(preloaded.is_void_cls(primitive));
+            Ok(ObjectPtr::null())
+        };
+    }
+}
+
 preloaded_classes!(
     {char_cls, "char", true, false, size_of::<JChar>(), null, is_char_cls},
     {byte_cls, "byte", true, false, size_of::<JByte>(), null, is_byte_cls},
@@ -293,40 +339,26 @@ impl PreloadedClasses {
     pub fn init(&mut self, vm_ptr: VMPtr, thread: ThreadPtr) -> Result<(), VMError> {
         let vm = vm_ptr.as_ref();
 
-        self.jclass_cls = vm
-            .bootstrap_class_loader
-            .load_class("java/lang/Class")
-            .map_err(|e| VMError::ClassLoaderErr(e))?;
+        self.jclass_cls = vm.bootstrap_class_loader.load_class("java/lang/Class")?;
         Object::init_header(self.jclass_cls.cast(), self.jclass_cls);
 
-        self.jobject_cls = vm
-            .bootstrap_class_loader
-            .load_class("java/lang/Object")
-            .map_err(|e| VMError::ClassLoaderErr(e))?;
+        self.jobject_cls = vm.bootstrap_class_loader.load_class("java/lang/Object")?;
         Object::init_header(self.jobject_cls.cast(), self.jclass_cls);
 
         self.jobject_arr_cls = vm
             .bootstrap_class_loader
-            .load_class("[Ljava/lang/Object;")
-            .map_err(|e| VMError::ClassLoaderErr(e))?;
-        self.jclass_arr_cls = vm
-            .bootstrap_class_loader
-            .load_class("[Ljava/lang/Class;")
-            .map_err(|e| VMError::ClassLoaderErr(e))?;
+            .load_class("[Ljava/lang/Object;")?;
+        self.jclass_arr_cls = vm.bootstrap_class_loader.load_class("[Ljava/lang/Class;")?;
 
         log::trace!("jclass_cls {:x}", self.jclass_cls.as_usize());
 
         self.throwable_cls = vm
             .bootstrap_class_loader
-            .load_class("java/lang/Throwable")
-            .map_err(|e| VMError::ClassLoaderErr(e))?;
+            .load_class("java/lang/Throwable")?;
 
-        self.setup(self.jclass_cls, thread)
-            .map_err(|e| VMError::ClassInitError(e))?;
+        self.setup(self.jclass_cls, thread)?;
 
-        self.jclass_cls
-            .initialize(thread)
-            .map_err(|e| VMError::ClassInitError(e))?;
+        self.jclass_cls.initialize(thread)?;
 
         return Ok(());
     }
@@ -355,6 +387,10 @@ impl PreloadedClasses {
     pub fn jobject_arr_cls(&self) -> JClassPtr {
         self.jobject_arr_cls
     }
+
+    pub fn throwable_cls(&self) -> JClassPtr {
+        self.throwable_cls
+    }
 }
 
 #[derive(Default)]
@@ -368,6 +404,7 @@ pub(crate) struct SharedObjects {
     pub(crate) internal_cp_cls: JClassPtr,
     pub(crate) java_lang_thread_group: ObjectPtr,
     pub(crate) java_lang_cloneable_cls: JClassPtr,
+    pub(crate) java_io_serializable_cls: JClassPtr,
     java_lang_class_inst_size: u16,
 }
 
@@ -414,15 +451,17 @@ impl SharedObjects {
 
         self.java_lang_cloneable_cls = vm
             .bootstrap_class_loader
-            .load_class("java/lang/Cloneable")
-            .map_err(|e| VMError::ClassLoaderErr(e))?;
+            .load_class("java/lang/Cloneable")?;
+        self.java_io_serializable_cls = vm
+            .bootstrap_class_loader
+            .load_class("java/io/Serializable")?;
 
         self.java_lang_thread_group = self
             .class_infos
             .java_lang_thread_group_info
             .new_permanent_thread_group(thread);
 
-        Thread::create_jthread_and_bind(thread, self.java_lang_thread_group);
+        Thread::create_jthread_and_bind(thread, self.java_lang_thread_group, false);
 
         debug_assert!(vm
             .preloaded_classes()
@@ -435,20 +474,13 @@ impl SharedObjects {
             vm.preloaded_classes().jobject_cls
         );
 
-        let jsystem_cls = vm
-            .bootstrap_class_loader
-            .load_class("java/lang/System")
-            .map_err(|e| VMError::ClassLoaderErr(e))?;
+        let jsystem_cls = vm.bootstrap_class_loader.load_class("java/lang/System")?;
         let jsystem_init_method = jsystem_cls
             .resolve_self_method(
                 vm.get_symbol("initializeSystemClass"),
                 self.symbols.noargs_retv_descriptor,
             )
-            .map_err(|_e| {
-                VMError::ClassLoaderErr(ClassLoadErr::InvalidFormat(
-                    "No such method initializeSystemClass available".to_string(),
-                ))
-            })?;
+            .map_err(|e| VMError::ClassInitError(InitializationError::ResolveError(e)))?;
         let sys_init_at = std::time::SystemTime::now();
         vm.call_static_void(jsystem_cls, jsystem_init_method.method, &[]);
         log::info!(
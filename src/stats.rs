@@ -0,0 +1,161 @@
+//! Runtime statistics of a VM, see [`VM::stats`](crate::vm::VM::stats).
+
+use std::time::{Duration, SystemTime};
+
+use parking_lot::Mutex;
+
+use crate::thread::ThreadState;
+
+/// A class loaded by the VM.
+#[derive(Clone, Debug)]
+pub struct ClassLoadRecord {
+    /// The binary name of the class, e.g. `java.lang.String`.
+    pub name: String,
+    /// The class loader that defined the class.
+    pub loader: String,
+    /// The directory or jar the class was read from, `None` for classes
+    /// defined from bytes.
+    pub source: Option<String>,
+    /// The time spent reading and parsing the class, including the loading of
+    /// its super classes.
+    pub elapsed: Duration,
+}
+
+/// A snapshot of the counters of a VM.
+#[derive(Clone, Debug, Default)]
+pub struct VMStats {
+    /// The number of classes read from the class path or defined from bytes.
+    /// Array classes are not counted.
+    pub classes_loaded: usize,
+    /// The total of [`ClassLoadRecord::elapsed`] over all loaded classes.
+    pub class_load_time: Duration,
+    /// Every class load in order, only recorded with
+    /// `VMConfig::record_class_loads`.
+    pub class_loads: Vec<ClassLoadRecord>,
+    /// The number of local allocation buffers handed out to threads.
+    pub lab_refills: usize,
+    /// The bytes left unused at the end of retired local allocation buffers.
+    pub lab_waste_bytes: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct StatsCollector {
+    stats: Mutex<VMStats>,
+}
+
+impl StatsCollector {
+    pub(crate) fn record_class_load(&self, record: ClassLoadRecord, keep_record: bool) {
+        let mut stats = self.stats.lock();
+        stats.classes_loaded += 1;
+        stats.class_load_time += record.elapsed;
+        if keep_record {
+            stats.class_loads.push(record);
+        }
+    }
+
+    pub(crate) fn classes_loaded(&self) -> usize {
+        return self.stats.lock().classes_loaded;
+    }
+
+    pub(crate) fn snapshot(&self) -> VMStats {
+        return self.stats.lock().clone();
+    }
+}
+
+/// The instances of one class on the heap, see
+/// [`VM::class_histogram`](crate::vm::VM::class_histogram).
+#[derive(Clone, Debug)]
+pub struct ClassHistogramEntry {
+    /// The binary name of the class, e.g. `java.lang.String` or `[C`.
+    pub class_name: String,
+    pub instances: usize,
+    /// The heap bytes taken by all instances, headers included.
+    pub bytes: usize,
+}
+
+/// The bytecodes one method executed in the interpreter, see
+/// [`VM::interpreter_stats`](crate::vm::VM::interpreter_stats).
+#[cfg(feature = "interpreter-stats")]
+#[derive(Clone, Debug)]
+pub struct MethodOpStats {
+    /// The binary name of the declaring class, e.g. `java.lang.String`.
+    pub class_name: String,
+    pub method_name: String,
+    pub descriptor: String,
+    /// The executed opcodes by mnemonic, e.g. `("iload0", 12)`, most executed
+    /// first.
+    pub op_counts: Vec<(String, u64)>,
+    /// The conditional branches that jumped.
+    pub branches_taken: u64,
+    /// The conditional branches that fell through.
+    pub branches_not_taken: u64,
+}
+
+#[cfg(feature = "interpreter-stats")]
+impl MethodOpStats {
+    pub fn total_ops(&self) -> u64 {
+        return self.op_counts.iter().map(|(_, count)| count).sum();
+    }
+}
+
+/// The memory usage of a group of spaces, like
+/// `java.lang.management.MemoryUsage`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The bytes reserved when the VM started.
+    pub init: usize,
+    pub used: usize,
+    /// The bytes reserved now, at least `used`.
+    pub committed: usize,
+    /// The bytes the spaces may grow to.
+    pub max: usize,
+}
+
+/// What the `java.lang.management` MXBeans report about a VM, see
+/// [`VM::runtime_info`](crate::vm::VM::runtime_info).
+#[derive(Clone, Debug)]
+pub struct RuntimeInfo {
+    /// The spaces of Java objects.
+    pub heap: MemoryUsage,
+    /// The spaces of classes, methods, interned strings and code.
+    pub non_heap: MemoryUsage,
+    /// The threads attached to the VM now.
+    pub thread_count: usize,
+    pub peak_thread_count: usize,
+    /// The attached threads whose `java.lang.Thread` is a daemon.
+    pub daemon_thread_count: usize,
+    /// The threads ever attached to the VM.
+    pub total_started_thread_count: usize,
+    pub start_time: SystemTime,
+    pub uptime: Duration,
+    /// Classes are never unloaded, so this equals
+    /// [`VMStats::classes_loaded`].
+    pub loaded_class_count: usize,
+}
+
+/// A thread attached to a VM, see
+/// [`VM::thread_dump`](crate::vm::VM::thread_dump).
+#[derive(Clone, Debug)]
+pub struct ThreadSnapshot {
+    /// The name of the `java.lang.Thread`, empty for threads without one.
+    pub name: String,
+    /// The id of the OS thread.
+    pub thread_id: u64,
+    pub daemon: bool,
+    pub state: ThreadState,
+    /// The classes of the objects whose monitors the thread owns, the latest
+    /// entered first, e.g. `java.lang.Object`.
+    pub locked_monitors: Vec<String>,
+}
+
+/// The owner of a monitor, see
+/// [`VM::monitor_info`](crate::vm::VM::monitor_info).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MonitorInfo {
+    /// The id of the OS thread owning the monitor.
+    pub owner_thread_id: u64,
+    /// The name of the `java.lang.Thread` owning the monitor.
+    pub owner_name: String,
+    /// How many times the owner entered the monitor without exiting it.
+    pub entry_count: usize,
+}
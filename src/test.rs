@@ -1,6 +1,7 @@
-use std::{path::PathBuf, process::Command, str::FromStr};
+use std::{path::PathBuf, process::Command, str::FromStr, sync::Mutex};
 
 use crate::{
+    object::{array::JArray, prelude::ObjectPtr},
     thread::Thread,
     value::JValue,
     vm::{VMConfig, VMPtr, VM},
@@ -27,7 +28,9 @@ pub fn run_in_vm_and_call_static<
         let method = vm
             .get_static_method(class, method_name, method_descriptor, Thread::current())
             .unwrap();
-        let ret_val = vm.call_static(class, method, args_fn(vm).as_slice());
+        let ret_val = vm
+            .call_static(class, method, args_fn(vm).as_slice())
+            .unwrap();
         f(vm, ret_val);
     });
 }
@@ -44,6 +47,8 @@ pub fn run_in_vm<F: FnOnce(VMPtr) + Send + 'static>(class_path: &str, f: F) {
     rsvm_home.pop();
     cfg.set_rsvm_home(&rsvm_home.display().to_string());
     cfg.set_class_path(class_path);
+    // tests provoke panics on purpose
+    cfg.create_crash_reports = false;
     let vm = VM::new(&cfg);
 
     Thread::attach_current_thread(vm.as_ref());
@@ -56,6 +61,54 @@ pub fn run_in_vm<F: FnOnce(VMPtr) + Send + 'static>(class_path: &str, f: F) {
     });
 }
 
+/// Runs the `main` of every program `<package>/<Name>.java` under
+/// `class_path` that has a `<Name>.expected` next to it, and asserts that it
+/// prints exactly the expected output to `System.out`. The output is captured
+/// by `<package>.Harness`, which has to provide `static void begin()` and
+/// `static String end()`.
+pub fn check_programs(class_path: &'static str, package: &'static str) {
+    let mut dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    dir.push(class_path);
+    dir.push(package.replace(".", "/"));
+    let mut programs = std::fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("{}: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "expected"))
+        .collect::<Vec<_>>();
+    programs.sort();
+    assert!(!programs.is_empty(), "no programs in {}", dir.display());
+
+    let harness = format!("{}.Harness", package);
+    ensure_class_exists(class_path, &harness);
+    let mut expected_outputs = Vec::with_capacity(programs.len());
+    for path in programs {
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let class_name = format!("{}.{}", package, name);
+        ensure_class_exists(class_path, &class_name);
+        expected_outputs.push((class_name, std::fs::read_to_string(&path).unwrap()));
+    }
+
+    run_in_vm(class_path, move |vm| {
+        for (class_name, expected) in expected_outputs {
+            let output = run_program(vm, &harness, &class_name);
+            assert_eq!(expected, output, "output of {}", class_name);
+        }
+    });
+}
+
+/// Runs the `main` of `class_name` without arguments and returns what it
+/// printed to `System.out`.
+fn run_program(vm: VMPtr, harness: &str, class_name: &str) -> String {
+    vm.invoke_static::<_, ()>(harness, "begin", "()V", ())
+        .unwrap();
+    let args: ObjectPtr = JArray::new_obj_arr(0, Thread::current()).cast();
+    vm.invoke_static::<_, ()>(class_name, "main", "([Ljava/lang/String;)V", (args,))
+        .unwrap_or_else(|e| panic!("{}: {}", class_name, e));
+    return vm
+        .invoke_static(harness, "end", "()Ljava/lang/String;", ())
+        .unwrap();
+}
+
 fn run_in_new_thread<F: FnOnce(VMPtr) + Send + 'static>(vm: VMPtr, join: bool, f: F) {
     let thread = std::thread::Builder::new()
         .name("test".into())
@@ -81,7 +134,10 @@ fn get_file_with_suffix(class_name: &str, suffix: &str) -> String {
     return file;
 }
 
-fn ensure_class_exists(class_path: &str, class_name: &str) {
+static JAVAC_LOCK: Mutex<()> = Mutex::new(());
+
+pub fn ensure_class_exists(class_path: &str, class_name: &str) {
+    let _javac_guard = JAVAC_LOCK.lock().unwrap_or_else(|e| e.into_inner());
     let java_file = get_file_with_suffix(class_name, ".java");
     let java_file_path = get_real_file_path(class_path, &java_file);
     if !java_file_path.exists() {
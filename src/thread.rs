@@ -1,12 +1,18 @@
+use crate::compat;
 use crate::handle::{Handle, HandleData, HandleScope};
 use crate::memory::heap::{Heap, HeapPtr};
 use crate::memory::lab::LocalAllocBuf;
-use crate::object::prelude::{JInt, ObjectPtr, Ptr};
+use crate::native::jni::JNIEnvWrapper;
+use crate::object::prelude::{JBoolean, JInt, ObjectPtr, Ptr};
 use crate::object::Object;
 use crate::runtime::interpreter::Interpreter;
+use crate::runtime::stack::StackMemory;
 use crate::vm::{VMPtr, VM};
+use parking_lot::Mutex;
 use std::cell::Cell;
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
 use std::sync::RwLock;
 
 pub type ThreadPtr = Ptr<Thread>;
@@ -15,15 +21,77 @@ thread_local! {
     static VM_THREAD: Cell<ThreadPtr> = Cell::new(ThreadPtr::null());
 }
 
+/// The state of a thread, as `java.lang.Thread.State` names it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ThreadState {
+    New,
+    Runnable,
+    Blocked,
+    Waiting,
+    TimedWaiting,
+    Terminated,
+}
+
+impl ThreadState {
+    const STATES: [ThreadState; 6] = [
+        ThreadState::New,
+        ThreadState::Runnable,
+        ThreadState::Blocked,
+        ThreadState::Waiting,
+        ThreadState::TimedWaiting,
+        ThreadState::Terminated,
+    ];
+
+    /// The JVMTI thread state bits stored in `java.lang.Thread.threadStatus`,
+    /// which `sun.misc.VM.toThreadState` maps back to the state.
+    pub(crate) fn thread_status(self) -> JInt {
+        const ALIVE: JInt = 0x1;
+        const TERMINATED: JInt = 0x2;
+        const RUNNABLE: JInt = 0x4;
+        const WAITING_INDEFINITELY: JInt = 0x10;
+        const WAITING_WITH_TIMEOUT: JInt = 0x20;
+        const WAITING: JInt = 0x80;
+        const BLOCKED_ON_MONITOR_ENTER: JInt = 0x400;
+        return match self {
+            ThreadState::New => 0,
+            ThreadState::Runnable => ALIVE | RUNNABLE,
+            ThreadState::Blocked => ALIVE | BLOCKED_ON_MONITOR_ENTER,
+            ThreadState::Waiting => ALIVE | WAITING | WAITING_INDEFINITELY,
+            ThreadState::TimedWaiting => ALIVE | WAITING | WAITING_WITH_TIMEOUT,
+            ThreadState::Terminated => TERMINATED,
+        };
+    }
+}
+
+impl fmt::Display for ThreadState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(match self {
+            ThreadState::New => "NEW",
+            ThreadState::Runnable => "RUNNABLE",
+            ThreadState::Blocked => "BLOCKED",
+            ThreadState::Waiting => "WAITING",
+            ThreadState::TimedWaiting => "TIMED_WAITING",
+            ThreadState::Terminated => "TERMINATED",
+        });
+    }
+}
+
 pub struct ThreadManager {
     threads: RwLock<HashMap<u64, Box<Thread>>>,
+    peak_count: AtomicUsize,
+    started_count: AtomicUsize,
 }
 
 impl ThreadManager {
     pub fn new() -> ThreadManager {
         let threads = RwLock::new(HashMap::new());
 
-        return ThreadManager { threads };
+        return ThreadManager {
+            threads,
+            peak_count: AtomicUsize::new(0),
+            started_count: AtomicUsize::new(0),
+        };
     }
 
     pub fn add_thread(&self, thread: Box<Thread>) {
@@ -33,6 +101,8 @@ impl ThreadManager {
             .write()
             .expect("cannot add thread on the thread manager");
         threads.insert(thread_id, thread);
+        self.started_count.fetch_add(1, Ordering::Relaxed);
+        self.peak_count.fetch_max(threads.len(), Ordering::Relaxed);
     }
 
     pub fn remove_thread(&self, thread_id: u64) {
@@ -42,39 +112,99 @@ impl ThreadManager {
             .expect("cannot remove thread on the thread manager");
         threads.remove(&thread_id);
     }
+
+    /// The number of threads attached now.
+    pub fn thread_count(&self) -> usize {
+        return self
+            .threads
+            .read()
+            .expect("cannot count threads on the thread manager")
+            .len();
+    }
+
+    /// The most threads attached at once since the VM started or the last
+    /// [`reset_peak_thread_count`](Self::reset_peak_thread_count).
+    pub fn peak_thread_count(&self) -> usize {
+        return self.peak_count.load(Ordering::Relaxed);
+    }
+
+    pub fn reset_peak_thread_count(&self) {
+        self.peak_count
+            .store(self.thread_count(), Ordering::Relaxed);
+    }
+
+    /// The number of threads ever attached.
+    pub fn started_thread_count(&self) -> usize {
+        return self.started_count.load(Ordering::Relaxed);
+    }
+
+    /// The `java.lang.Thread` objects of the attached threads, threads without
+    /// one aside.
+    pub(crate) fn jthreads(&self) -> Vec<ObjectPtr> {
+        return self
+            .threads
+            .read()
+            .expect("cannot list threads on the thread manager")
+            .values()
+            .map(|thread| thread.jthread())
+            .filter(|jthread| jthread.is_not_null())
+            .collect();
+    }
+
+    /// Runs `f` on every attached thread, holding off attaches and detaches.
+    pub(crate) fn for_each_thread<F: FnMut(&Thread)>(&self, mut f: F) {
+        let threads = self
+            .threads
+            .read()
+            .expect("cannot list threads on the thread manager");
+        for thread in threads.values() {
+            f(thread);
+        }
+    }
 }
 
 pub struct Thread {
     _handle_scope: HandleScope,
     handle_data: HandleData,
-    os_thread: std::thread::Thread,
+    /// See `compat::current_thread_id_value`.
+    thread_id: u64,
     jthread: Handle<Object>,
     class_loader: ObjectPtr,
     interpreter: Interpreter,
+    jni_env: JNIEnvWrapper,
+    stack_memory: StackMemory,
     vm: VMPtr,
     heap: HeapPtr,
     lab: LocalAllocBuf,
+    state: AtomicU8,
+    /// The objects whose monitors the thread entered, once per entry, the
+    /// latest last.
+    monitors: Mutex<Vec<ObjectPtr>>,
 }
 
 impl Thread {
-    fn new(vm: &VM, os_thread: std::thread::Thread) -> Self {
+    fn new(vm: &VM) -> Self {
         let mut handle_data = HandleData::new();
         let handle_scope = HandleScope::new_with_data(&mut handle_data);
-        let stack_size = vm.cfg.stack_size;
-        let stack_addr = vm.heap().alloc_code(stack_size);
+        let stack_memory = StackMemory::new(vm.cfg.stack_size);
         let vm = VMPtr::from_ref(vm);
         let heap = HeapPtr::from_ref(vm.heap());
-        let interpreter = Interpreter::new(stack_addr, stack_size, vm);
+        let interpreter =
+            Interpreter::new(stack_memory.stack_addr(), stack_memory.stack_size(), vm);
         return Self {
             _handle_scope: handle_scope,
             handle_data,
-            os_thread,
+            thread_id: compat::current_thread_id_value(),
             jthread: Handle::null(),
             class_loader: ObjectPtr::null(),
             interpreter,
+            jni_env: JNIEnvWrapper::new(vm),
+            stack_memory,
             vm,
             heap,
-            lab: LocalAllocBuf::default(),
+            lab: LocalAllocBuf::new(vm.cfg.lab_size, vm.cfg.min_lab_size, vm.cfg.max_lab_size),
+            state: AtomicU8::new(ThreadState::Runnable as u8),
+            monitors: Mutex::new(Vec::new()),
         };
     }
 
@@ -87,20 +217,31 @@ impl Thread {
         if Thread::current().is_not_null() {
             return;
         }
-        let thread = Box::new(Thread::new(vm, std::thread::current()));
+        let mut thread = Box::new(Thread::new(vm));
+        let thread_ptr = ThreadPtr::from_ref(thread.as_ref());
+        thread.jni_env.set_thread(thread_ptr);
         thread.register_thread_local();
+        vm.log_event(format!("Thread attached tid={}", thread.thread_id()));
         vm.thread_mgr.add_thread(thread);
     }
 
     pub fn detach_current_thread() {
         let thread = Thread::current();
         if thread.is_not_null() {
+            thread.set_state(ThreadState::Terminated);
+            thread
+                .vm()
+                .log_event(format!("Thread detached tid={}", thread.thread_id()));
             thread.vm().thread_mgr.remove_thread(thread.thread_id());
             thread.deregister_thread_local();
         }
     }
 
-    pub(crate) fn create_jthread_and_bind(thread: ThreadPtr, thread_group: ObjectPtr) {
+    pub(crate) fn create_jthread_and_bind(
+        thread: ThreadPtr,
+        thread_group: ObjectPtr,
+        is_daemon: bool,
+    ) {
         if thread.jthread.is_not_null() {
             return;
         }
@@ -112,17 +253,18 @@ impl Thread {
             .new_jthread_with_native_id(
                 thread.thread_id() as JInt,
                 thread_group,
-                0,
+                is_daemon as JBoolean,
                 1,
                 |jthread| {
                     thread.as_mut_ref().jthread = jthread;
                 },
                 thread,
             );
+        thread.set_state(thread.state());
     }
 
     pub fn thread_id(&self) -> u64 {
-        return self.os_thread.id().as_u64().into();
+        return self.thread_id;
     }
 
     pub(crate) fn set_handle_data(&mut self, handle_data: HandleData) {
@@ -137,10 +279,6 @@ impl Thread {
         &mut self.handle_data
     }
 
-    pub(crate) fn lab(&self) -> &LocalAllocBuf {
-        &self.lab
-    }
-
     pub(crate) fn lab_mut(&mut self) -> &mut LocalAllocBuf {
         &mut self.lab
     }
@@ -149,6 +287,69 @@ impl Thread {
         return self.jthread.as_ptr();
     }
 
+    pub fn state(&self) -> ThreadState {
+        return ThreadState::STATES[usize::from(self.state.load(Ordering::Relaxed))];
+    }
+
+    /// Moves the thread to `state`, which its `java.lang.Thread` reports from
+    /// then on.
+    pub(crate) fn set_state(&self, state: ThreadState) {
+        self.state.store(state as u8, Ordering::Relaxed);
+        let jthread = self.jthread();
+        if jthread.is_not_null() {
+            self.vm()
+                .shared_objs()
+                .class_infos()
+                .java_lang_thread_info()
+                .set_thread_status(jthread, state.thread_status());
+        }
+    }
+
+    /// Records that the thread entered the monitor of `obj`. Monitors only
+    /// track their owners, they do not block other threads.
+    pub(crate) fn enter_monitor(&self, obj: ObjectPtr) {
+        self.monitors.lock().push(obj);
+    }
+
+    /// Records that the thread exited the monitor of `obj`, false if it did
+    /// not own it.
+    pub(crate) fn exit_monitor(&self, obj: ObjectPtr) -> bool {
+        let mut monitors = self.monitors.lock();
+        return match monitors.iter().rposition(|monitor| *monitor == obj) {
+            Some(idx) => {
+                monitors.remove(idx);
+                true
+            }
+            None => false,
+        };
+    }
+
+    pub fn holds_lock(&self, obj: ObjectPtr) -> bool {
+        return self.monitors.lock().contains(&obj);
+    }
+
+    /// How many times the thread entered the monitor of `obj` without
+    /// exiting it, 0 if it does not own it.
+    pub fn monitor_entry_count(&self, obj: ObjectPtr) -> usize {
+        return self
+            .monitors
+            .lock()
+            .iter()
+            .filter(|monitor| **monitor == obj)
+            .count();
+    }
+
+    /// The objects whose monitors the thread owns, the latest entered first.
+    pub fn locked_monitors(&self) -> Vec<ObjectPtr> {
+        let mut locked: Vec<ObjectPtr> = Vec::new();
+        for monitor in self.monitors.lock().iter().rev() {
+            if !locked.contains(monitor) {
+                locked.push(*monitor);
+            }
+        }
+        return locked;
+    }
+
     pub fn vm(&self) -> &VM {
         return self.vm.as_ref();
     }
@@ -173,6 +374,15 @@ impl Thread {
         &mut self.interpreter
     }
 
+    /// The `JNIEnv` passed to the natives this thread invokes.
+    pub(crate) fn jni_env_mut(&mut self) -> &mut JNIEnvWrapper {
+        &mut self.jni_env
+    }
+
+    pub(crate) fn stack_memory(&self) -> &StackMemory {
+        &self.stack_memory
+    }
+
     fn register_thread_local(&self) {
         VM_THREAD.with(|t| {
             t.set(ThreadPtr::from_ref(self));
@@ -189,5 +399,6 @@ impl Thread {
 impl Drop for Thread {
     fn drop(&mut self) {
         log::trace!("Thread::Drop {}", self.thread_id());
+        self.heap.retire_lab(&self.lab);
     }
 }
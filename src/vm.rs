@@ -1,23 +1,61 @@
 use crate::classfile::class_loader::BootstrapClassLoader;
 use crate::classfile::ClassLoadErr;
+use crate::convert::{self, FromJValue, JArgs, ToJValue};
+use crate::crash_report;
+use crate::event_log::EventLog;
+use crate::execution::{CancelReason, ExecutionBudget};
+use crate::flags::{self, FlagError, FlagValue};
+use crate::handle::Handle;
 use crate::memory::heap::Heap;
-use crate::memory::Address;
-use crate::native::builtin_natives::BuiltinNativeFunctions;
+use crate::memory::lab;
+use crate::memory::mapped::MappedFiles;
+use crate::native::builtin_natives::{BuiltinNative, BuiltinNativeFunctions};
 use crate::native::jni::JNIWrapper;
-use crate::object::class::InitializationError;
+use crate::object::array::{
+    JArray, JBooleanArrayPtr, JByteArrayPtr, JCharArrayPtr, JDoubleArrayPtr, JFloatArrayPtr,
+    JIntArrayPtr, JLongArrayPtr, JShortArrayPtr,
+};
+use crate::object::class::{FieldResolutionError, InitializationError};
+use crate::object::field::FieldPtr;
 use crate::object::method::MethodPtr;
+use crate::object::method_table::MethodTable;
 use crate::object::prelude::Ptr;
-use crate::object::string::{JStringPtr, Utf16String};
+use crate::object::prelude::{JBoolean, JByte, JChar, JDouble, JFloat, JInt, JLong, JShort};
+use crate::object::string::{JString, JStringPtr, Utf16String};
 use crate::object::symbol::{StringTable, SymbolPtr, SymbolTable};
+use crate::object::Object;
+use crate::os;
+use crate::platform::Capabilities;
+use crate::proxy::{ProxyCall, ProxyRegistry};
+use crate::runtime;
+use crate::runtime::disassembler;
 use crate::runtime::interpreter::Interpreter;
+#[cfg(feature = "interpreter-stats")]
+use crate::runtime::op_stats::OpStatsCollector;
+use crate::sampling::{AllocationSample, AllocationSampler};
+use crate::sandbox::SandboxPolicy;
 use crate::shared::{PreloadedClasses, SharedObjects};
+#[cfg(feature = "interpreter-stats")]
+use crate::stats::MethodOpStats;
+use crate::stats::{
+    ClassHistogramEntry, MonitorInfo, RuntimeInfo, StatsCollector, ThreadSnapshot, VMStats,
+};
 use crate::thread::{Thread, ThreadManager, ThreadPtr};
 use crate::value::JValue;
 use crate::{utils, JClassPtr, ObjectPtr};
+use paste::paste;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
 
 pub type VMPtr = Ptr<VM>;
 
+/// The configuration of a VM. The toggles and sizes among its fields are
+/// also reachable by name as `-XX` flags, see [`flags`].
 #[derive(Clone)]
 pub struct VMConfig {
     current_dir: String,
@@ -26,6 +64,44 @@ pub struct VMConfig {
     pub boot_lib_path: Option<String>,
     pub stack_size: usize,
     pub main_class: String,
+    /// Back the heap with huge pages where the OS allows it.
+    pub large_pages: bool,
+    /// Interleave the heap over the NUMA nodes where the OS allows it.
+    pub numa_interleave: bool,
+    /// The most memory the permanent space, which holds classes, methods and
+    /// interned strings, may grow to.
+    pub max_perm_size: usize,
+    /// The initial size of a thread's local allocation buffer, which then
+    /// adapts to the thread's allocation rate within
+    /// `min_lab_size..=max_lab_size`.
+    pub lab_size: usize,
+    pub min_lab_size: usize,
+    pub max_lab_size: usize,
+    /// Let `getfield` and `invokevirtual` skip their null checks and detect
+    /// null objects by the fault of accessing page zero, which cancels the
+    /// execution with `CancelReason::NullPointer`. Ignored where
+    /// `os::CAN_RESUME_FAULTS_ELSEWHERE` is false.
+    pub implicit_null_checks: bool,
+    /// Let `newarray` and `anewarray` with a count of 0 return one shared
+    /// empty array per array class instead of allocating, so `new int[0]` is
+    /// always the same object. Off by default, the JVMS requires a new array
+    /// and code may rely on the identities of empty arrays.
+    pub share_empty_arrays: bool,
+    /// Keep a [`ClassLoadRecord`](crate::stats::ClassLoadRecord) of every
+    /// loaded class in [`VM::stats`].
+    pub record_class_loads: bool,
+    /// Print `[Loaded <class> from <source>]` for every loaded class, like
+    /// `java -verbose:class`.
+    pub verbose_class: bool,
+    /// Write an `hs_err_pid<pid>.log` report into the current directory when
+    /// a thread of the VM panics.
+    pub create_crash_reports: bool,
+    /// Keep the tables of loaded classes, symbols and interned strings in
+    /// insertion order, so that iterating them gives the same order on every
+    /// run and platform. Costs a linear scan per removal.
+    pub deterministic_tables: bool,
+    /// The threads, heap memory, files and sockets the Java code may use.
+    pub sandbox: SandboxPolicy,
 }
 
 impl VMConfig {
@@ -53,6 +129,18 @@ impl VMConfig {
         self.boot_lib_path.as_ref().map(|s| s.as_str())
     }
 
+    /// Applies a flag setting like `-XX:+ImplicitNullChecks` or
+    /// `-XX:MaxPermSize=64m`, see [`flags::parse_flag`].
+    pub fn set_flag(&mut self, setting: &str) -> Result<(), FlagError> {
+        let (flag, value) = flags::parse_flag(setting)?;
+        flag.set_value(self, value);
+        return Ok(());
+    }
+
+    pub fn flag(&self, name: &str) -> Option<FlagValue> {
+        return flags::find_flag(name).map(|flag| flag.value(self));
+    }
+
     fn get_rsvm_home_from_os_env() -> Option<String> {
         if let Some(rsvm_home) = std::env::var_os("rsvm.home") {
             if let Ok(rsvm_home) = rsvm_home.into_string() {
@@ -130,6 +218,19 @@ impl Default for VMConfig {
             boot_lib_path: None,
             stack_size: 2 * crate::memory::MB,
             main_class: "Main".to_string(),
+            large_pages: false,
+            numa_interleave: false,
+            max_perm_size: 256 * crate::memory::MB,
+            lab_size: lab::DEFAULT_LAB_SIZE,
+            min_lab_size: lab::DEFAULT_MIN_LAB_SIZE,
+            max_lab_size: lab::DEFAULT_MAX_LAB_SIZE,
+            implicit_null_checks: false,
+            share_empty_arrays: false,
+            record_class_loads: false,
+            verbose_class: false,
+            create_crash_reports: true,
+            deterministic_tables: false,
+            sandbox: SandboxPolicy::default(),
         }
     }
 }
@@ -140,6 +241,49 @@ pub enum VMError {
     ClassLoaderErr(ClassLoadErr),
     ClassInitError(InitializationError),
     RuntimeError(String),
+    /// The Java code of the call was cancelled, see [`VM::set_execution_budget`]
+    /// and [`VM::interrupt_execution`].
+    Cancelled(CancelReason),
+}
+
+impl fmt::Display for VMError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            VMError::InitError(msg) => write!(f, "vm initialization failed: {}", msg),
+            VMError::ClassLoaderErr(e) => write!(f, "class loading failed: {}", e),
+            VMError::ClassInitError(e) => write!(f, "class initialization failed: {}", e),
+            VMError::RuntimeError(msg) => f.write_str(msg),
+            VMError::Cancelled(reason) => write!(f, "execution cancelled: {}", reason),
+        };
+    }
+}
+
+impl Error for VMError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return match self {
+            VMError::ClassLoaderErr(e) => Some(e),
+            VMError::ClassInitError(e) => Some(e),
+            VMError::InitError(_) | VMError::RuntimeError(_) | VMError::Cancelled(_) => None,
+        };
+    }
+}
+
+impl From<ClassLoadErr> for VMError {
+    fn from(e: ClassLoadErr) -> Self {
+        return VMError::ClassLoaderErr(e);
+    }
+}
+
+impl From<InitializationError> for VMError {
+    fn from(e: InitializationError) -> Self {
+        return VMError::ClassInitError(e);
+    }
+}
+
+impl From<FieldResolutionError> for VMError {
+    fn from(e: FieldResolutionError) -> Self {
+        return VMError::RuntimeError(e.to_string());
+    }
 }
 
 pub struct VM {
@@ -150,27 +294,57 @@ pub struct VM {
     builtin_native_fns: BuiltinNativeFunctions,
     jni: JNIWrapper,
     pub(crate) symbol_table: SymbolTable,
+    pub(crate) method_table: MethodTable,
     pub(crate) string_table: StringTable,
     pub(crate) thread_mgr: ThreadManager,
+    pub(crate) proxies: ProxyRegistry,
+    pub(crate) mapped_files: MappedFiles,
+    pub(crate) stats: StatsCollector,
+    pub(crate) events: EventLog,
+    #[cfg(feature = "interpreter-stats")]
+    pub(crate) op_stats: OpStatsCollector,
     pub(crate) cfg: VMConfig,
+    pub(crate) start_time: SystemTime,
+    started_at: Instant,
 }
 
 impl VM {
     pub fn new(cfg: &VMConfig) -> VMPtr {
-        crate::os::init();
+        os::init();
+        os::install_fault_handler(runtime::handle_memory_fault);
+        if cfg.create_crash_reports {
+            crash_report::install_panic_hook();
+        }
         let vm = Box::new(VM {
             bootstrap_class_loader: BootstrapClassLoader::default(),
-            heap: Heap::new(),
+            heap: Heap::new(
+                os::MemoryHints {
+                    large_pages: cfg.large_pages,
+                    numa_interleave: cfg.numa_interleave,
+                },
+                cfg.max_perm_size,
+            ),
             preloaded_classes: PreloadedClasses::new(),
             shared_objs: SharedObjects::default(),
             builtin_native_fns: BuiltinNativeFunctions::new(),
             jni: JNIWrapper::default(),
             symbol_table: SymbolTable::default(),
+            method_table: MethodTable::default(),
             string_table: StringTable::default(),
             thread_mgr: ThreadManager::new(),
+            proxies: ProxyRegistry::default(),
+            mapped_files: MappedFiles::default(),
+            stats: StatsCollector::default(),
+            events: EventLog::default(),
+            #[cfg(feature = "interpreter-stats")]
+            op_stats: OpStatsCollector::default(),
             cfg: cfg.clone(),
+            start_time: SystemTime::now(),
+            started_at: Instant::now(),
         });
-        return VMPtr::from_raw(Box::into_raw(vm));
+        let vm = VMPtr::from_raw(Box::into_raw(vm));
+        vm.as_mut_ref().jni.bind_vm(vm);
+        return vm;
     }
 
     pub fn init(&mut self) -> Result<(), VMError> {
@@ -179,9 +353,21 @@ impl VM {
     }
 
     pub fn destroy(&self) {
+        self.mapped_files.unmap_all();
         self.heap.destroy();
     }
 
+    /// Prints every flag with its value to stdout, like
+    /// `java -XX:+PrintFlagsFinal`.
+    pub fn print_flags(&self) {
+        flags::print_flags(&self.cfg, &mut std::io::stdout().lock()).unwrap();
+    }
+
+    /// Records `message` in the event log printed into crash reports.
+    pub(crate) fn log_event(&self, message: String) {
+        self.events.log(self.started_at.elapsed(), message);
+    }
+
     pub fn as_ptr(&self) -> VMPtr {
         VMPtr::from_ref(self)
     }
@@ -206,6 +392,23 @@ impl VM {
         return self.string_table.intern_jstr(jstr, thread);
     }
 
+    /// Creates a `java.lang.String` holding `value`.
+    pub fn new_string(&self, value: &str) -> JStringPtr {
+        let thread = Thread::current();
+        return self
+            .shared_objs
+            .class_infos()
+            .java_lang_string_info()
+            .create_with_utf8(value, thread)
+            .get_ptr();
+    }
+
+    /// Copies the content of a `java.lang.String` into a Rust `String`.
+    pub fn get_string(&self, jstr: JStringPtr) -> String {
+        assert!(jstr.is_not_null(), "get_string on null reference");
+        return JString::to_rust_string(jstr, self);
+    }
+
     pub fn get_static_method(
         &self,
         class: JClassPtr,
@@ -213,12 +416,10 @@ impl VM {
         descriptor: &str,
         thread: ThreadPtr,
     ) -> Result<MethodPtr, VMError> {
-        class
-            .initialize(thread)
-            .map_err(|e| VMError::ClassInitError(e))?;
         let method = self.get_symbol(method_name);
         let descriptor = self.get_symbol(descriptor);
-        if let Ok(resolved_method) = class.resolve_self_method(method, descriptor) {
+        if let Ok(resolved_method) = class.resolve_static_method(method, descriptor) {
+            resolved_method.decl_class.initialize(thread)?;
             return Ok(resolved_method.method);
         }
         return Err(VMError::RuntimeError(
@@ -233,9 +434,7 @@ impl VM {
         descriptor: &str,
         thread: ThreadPtr,
     ) -> Result<MethodPtr, VMError> {
-        class
-            .initialize(thread)
-            .map_err(|e| VMError::ClassInitError(e))?;
+        class.initialize(thread)?;
         let method = self.get_symbol(method_name);
         let descriptor = self.get_symbol(descriptor);
         if let Ok(resolved_method) = class.resolve_class_method(method, descriptor, self) {
@@ -246,16 +445,410 @@ impl VM {
         ));
     }
 
+    /// Resolves a static method for repeated calls, e.g.
+    /// `vm.method("java/lang/Math", "max", "(II)I")?.call(&[1.into(), 2.into()])?`.
+    pub fn method(
+        &self,
+        class_name: &str,
+        method_name: &str,
+        descriptor: &str,
+    ) -> Result<StaticMethod, VMError> {
+        let thread = Thread::current();
+        let class = self
+            .bootstrap_class_loader
+            .load_binary_name_class(class_name)?;
+        let (param_types, return_type) = convert::split_method_descriptor(descriptor, self)?;
+        let method = self.get_static_method(class, method_name, descriptor, thread)?;
+        return Ok(StaticMethod {
+            vm: self,
+            class,
+            method,
+            param_types: param_types.iter().map(|ty| ty.to_string()).collect(),
+            return_type: return_type.to_string(),
+        });
+    }
+
+    /// Calls a static method, converting `args` and the result according to
+    /// `descriptor`, e.g. `vm.invoke_static::<_, String>("a.B", "f", "(IDLjava/lang/String;)Ljava/lang/String;", (1, 2.0, "s"))`.
+    pub fn invoke_static<A: JArgs, R: FromJValue>(
+        &self,
+        class_name: &str,
+        method_name: &str,
+        descriptor: &str,
+        args: A,
+    ) -> Result<R, VMError> {
+        return self
+            .method(class_name, method_name, descriptor)?
+            .invoke(args);
+    }
+
+    /// Reads a static field converted according to its descriptor, e.g.
+    /// `vm.get_static::<i32>("java/lang/Integer", "MAX_VALUE")`.
+    pub fn get_static<T: FromJValue>(
+        &self,
+        class_name: &str,
+        field_name: &str,
+    ) -> Result<T, VMError> {
+        let (field, decl_class) = self.resolve_static_field(class_name, field_name)?;
+        let value = field.get_jvalue(decl_class.cast());
+        return T::from_jvalue(value, &field.type_descriptor(self), self);
+    }
+
+    pub fn set_static<T: ToJValue>(
+        &self,
+        class_name: &str,
+        field_name: &str,
+        value: T,
+    ) -> Result<(), VMError> {
+        let (field, decl_class) = self.resolve_static_field(class_name, field_name)?;
+        let value = value.to_jvalue(&field.type_descriptor(self), self)?;
+        field.set_jvalue(decl_class.cast(), value);
+        return Ok(());
+    }
+
+    /// Reads an instance field of `obj` converted according to its descriptor.
+    pub fn get_field<T: FromJValue>(&self, obj: ObjectPtr, field_name: &str) -> Result<T, VMError> {
+        let field = self.resolve_instance_field(obj, field_name)?;
+        let value = field.get_jvalue(obj);
+        return T::from_jvalue(value, &field.type_descriptor(self), self);
+    }
+
+    pub fn set_field<T: ToJValue>(
+        &self,
+        obj: ObjectPtr,
+        field_name: &str,
+        value: T,
+    ) -> Result<(), VMError> {
+        let field = self.resolve_instance_field(obj, field_name)?;
+        let value = value.to_jvalue(&field.type_descriptor(self), self)?;
+        field.set_jvalue(obj, value);
+        return Ok(());
+    }
+
+    fn resolve_static_field(
+        &self,
+        class_name: &str,
+        field_name: &str,
+    ) -> Result<(FieldPtr, JClassPtr), VMError> {
+        let thread = Thread::current();
+        let class = self
+            .bootstrap_class_loader
+            .load_binary_name_class(class_name)?;
+        let (field, decl_class) = match class.get_field_with_name(self.get_symbol(field_name)) {
+            Ok((field, decl_class)) if field.is_static() => (field, decl_class),
+            _ => {
+                return Err(VMError::RuntimeError(format!(
+                    "static field {}.{} not found",
+                    class_name, field_name
+                )));
+            }
+        };
+        decl_class.initialize(thread)?;
+        return Ok((field, decl_class));
+    }
+
+    fn resolve_instance_field(
+        &self,
+        obj: ObjectPtr,
+        field_name: &str,
+    ) -> Result<FieldPtr, VMError> {
+        if obj.is_null() {
+            return Err(VMError::RuntimeError(format!(
+                "cannot access field {} of null",
+                field_name
+            )));
+        }
+        return match obj
+            .jclass()
+            .get_field_with_name(self.get_symbol(field_name))
+        {
+            Ok((field, _)) if !field.is_static() => Ok(field),
+            _ => Err(VMError::RuntimeError(format!(
+                "instance field {}.{} not found",
+                obj.jclass().name().as_str(),
+                field_name
+            ))),
+        };
+    }
+
+    /// Creates an object implementing `interface_name` whose methods all call
+    /// `callback`, e.g. `vm.implement("java/lang/Runnable", |call| { ...; JValue::default() })`.
+    pub fn implement<F: Fn(&ProxyCall) -> JValue + Send + Sync + 'static>(
+        &self,
+        interface_name: &str,
+        callback: F,
+    ) -> Result<ObjectPtr, VMError> {
+        return self
+            .proxies
+            .implement(interface_name, Arc::new(callback), self);
+    }
+
+    /// Calls `callback` with one allocation out of every `interval` bytes each
+    /// thread allocates, replacing any previous sampler. The callback runs on
+    /// the allocating thread before the object is initialized, and must not
+    /// allocate Java objects itself.
+    pub fn set_allocation_sampler<F: Fn(&AllocationSample) + Send + Sync + 'static>(
+        &self,
+        interval: usize,
+        callback: F,
+    ) {
+        self.heap
+            .set_allocation_sampler(Some(AllocationSampler::new(interval, Arc::new(callback))));
+    }
+
+    pub fn clear_allocation_sampler(&self) {
+        self.heap.set_allocation_sampler(None);
+    }
+
+    /// Every class loaded so far, including array and primitive classes,
+    /// sorted by name. Like `Instrumentation.getAllLoadedClasses`, the result
+    /// is a snapshot that does not follow later loads.
+    pub fn loaded_classes(&self) -> Vec<JClassPtr> {
+        let mut classes = self.bootstrap_class_loader.loaded_classes();
+        classes.sort_by(|a, b| a.name().as_str().cmp(b.name().as_str()));
+        return classes;
+    }
+
+    /// The classes defined by `class_loader`, sorted by name. A null
+    /// `class_loader` lists the classes of the bootstrap class loader.
+    pub fn loaded_classes_of(&self, class_loader: ObjectPtr) -> Vec<JClassPtr> {
+        let mut classes = self.loaded_classes();
+        classes.retain(|class| class.class_loader() == class_loader);
+        return classes;
+    }
+
+    /// Replaces the method bodies of `class` with those in `bytes`, a new
+    /// class file of it, like `Instrumentation.redefineClasses` limited to
+    /// method bodies. Adding, removing or changing the signature of any
+    /// member fails with `ClassLoadErr::UnsupportedRedefinition` and leaves
+    /// the class as it was. Frames running an old method finish it with the
+    /// old code, and static fields keep their values.
+    pub fn redefine_class(&self, class: JClassPtr, bytes: &[u8]) -> Result<(), VMError> {
+        return self
+            .bootstrap_class_loader
+            .redefine_class(class, bytes.to_vec(), Thread::current())
+            .map_err(VMError::from);
+    }
+
+    /// The bytecode of every method of the class `class_name`, with the
+    /// constant pool operands resolved, like `javap -c` prints it. The class
+    /// is loaded but not initialized.
+    pub fn disassemble(&self, class_name: &str) -> Result<String, VMError> {
+        let class = self
+            .bootstrap_class_loader
+            .load_binary_name_class(class_name)?;
+        let mut disassembly = String::new();
+        disassembler::disassemble_class(class, &mut disassembly)
+            .expect("writing to a String does not fail");
+        return Ok(disassembly);
+    }
+
+    /// The Java platform level and class file versions the VM supports, which
+    /// its `java.specification.version` and related system properties report.
+    pub fn capabilities(&self) -> Capabilities {
+        return Capabilities::current();
+    }
+
+    /// A snapshot of the VM's counters.
+    pub fn stats(&self) -> VMStats {
+        let mut stats = self.stats.snapshot();
+        stats.lab_refills = self.heap.lab_refills();
+        stats.lab_waste_bytes = self.heap.lab_waste();
+        return stats;
+    }
+
+    /// The memory, thread and class loading figures the
+    /// `java.lang.management` MXBeans report.
+    pub fn runtime_info(&self) -> RuntimeInfo {
+        return RuntimeInfo {
+            heap: self.heap.heap_usage(),
+            non_heap: self.heap.non_heap_usage(),
+            thread_count: self.thread_mgr.thread_count(),
+            peak_thread_count: self.thread_mgr.peak_thread_count(),
+            daemon_thread_count: self.daemon_thread_count(),
+            total_started_thread_count: self.thread_mgr.started_thread_count(),
+            start_time: self.start_time,
+            uptime: self.started_at.elapsed(),
+            loaded_class_count: self.stats.classes_loaded(),
+        };
+    }
+
+    pub(crate) fn daemon_thread_count(&self) -> usize {
+        let thread_info = self.shared_objs().class_infos().java_lang_thread_info();
+        return self
+            .thread_mgr
+            .jthreads()
+            .into_iter()
+            .filter(|jthread| thread_info.is_daemon(*jthread))
+            .count();
+    }
+
+    /// The thread owning the monitor of `obj`, `None` if no thread does.
+    pub fn monitor_info(&self, obj: ObjectPtr) -> Option<MonitorInfo> {
+        let thread_info = self.shared_objs().class_infos().java_lang_thread_info();
+        let mut info = None;
+        self.thread_mgr.for_each_thread(|thread| {
+            let entry_count = thread.monitor_entry_count(obj);
+            if entry_count == 0 {
+                return;
+            }
+            let jthread = thread.jthread();
+            info = Some(MonitorInfo {
+                owner_thread_id: thread.thread_id(),
+                owner_name: if jthread.is_not_null() {
+                    thread_info.thread_name(jthread)
+                } else {
+                    String::new()
+                },
+                entry_count,
+            });
+        });
+        return info;
+    }
+
+    /// The state of every attached thread, ordered by thread id, like
+    /// `jstack` prints it.
+    pub fn thread_dump(&self) -> Vec<ThreadSnapshot> {
+        let thread_info = self.shared_objs().class_infos().java_lang_thread_info();
+        let mut threads = Vec::new();
+        self.thread_mgr.for_each_thread(|thread| {
+            let jthread = thread.jthread();
+            let (name, daemon) = if jthread.is_not_null() {
+                (
+                    thread_info.thread_name(jthread),
+                    thread_info.is_daemon(jthread),
+                )
+            } else {
+                (String::new(), false)
+            };
+            threads.push(ThreadSnapshot {
+                name,
+                thread_id: thread.thread_id(),
+                daemon,
+                state: thread.state(),
+                locked_monitors: thread
+                    .locked_monitors()
+                    .into_iter()
+                    .map(|obj| obj.jclass().binary_name())
+                    .collect(),
+            });
+        });
+        threads.sort_by_key(|thread| thread.thread_id);
+        return threads;
+    }
+
+    /// Writes [`thread_dump`](Self::thread_dump) to `out` in the format of
+    /// `jstack`.
+    pub fn print_thread_dump(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        for thread in self.thread_dump() {
+            let daemon = if thread.daemon { " daemon" } else { "" };
+            writeln!(
+                out,
+                "\"{}\"{} tid={}",
+                thread.name, daemon, thread.thread_id
+            )?;
+            writeln!(out, "   java.lang.Thread.State: {}", thread.state)?;
+            for monitor in &thread.locked_monitors {
+                writeln!(out, "\t- locked (a {})", monitor)?;
+            }
+            writeln!(out)?;
+        }
+        return Ok(());
+    }
+
+    /// Counts the instances and bytes of every class on the heap, largest
+    /// first, like `jmap -histo`. Other threads must not allocate meanwhile.
+    pub fn class_histogram(&self) -> Vec<ClassHistogramEntry> {
+        let mut classes: HashMap<usize, (JClassPtr, usize, usize)> = HashMap::new();
+        self.heap.iterate_objects(|obj| {
+            let jclass = obj.jclass();
+            let class = classes.entry(jclass.as_usize()).or_insert((jclass, 0, 0));
+            class.1 += 1;
+            class.2 += obj.heap_size();
+        });
+        let mut histogram: Vec<ClassHistogramEntry> = classes
+            .into_values()
+            .map(|(jclass, instances, bytes)| ClassHistogramEntry {
+                class_name: jclass.binary_name(),
+                instances,
+                bytes,
+            })
+            .collect();
+        histogram.sort_by(|a, b| {
+            b.bytes
+                .cmp(&a.bytes)
+                .then_with(|| a.class_name.cmp(&b.class_name))
+        });
+        return histogram;
+    }
+
+    /// The opcodes and conditional branches every method executed in the
+    /// interpreter so far, most executed opcodes first, to tell which
+    /// instructions are worth quickening or intrinsifying.
+    #[cfg(feature = "interpreter-stats")]
+    pub fn interpreter_stats(&self) -> Vec<MethodOpStats> {
+        return self.op_stats.snapshot();
+    }
+
+    /// Writes [`interpreter_stats`](Self::interpreter_stats) to `out`, the
+    /// opcode totals over all methods first.
+    #[cfg(feature = "interpreter-stats")]
+    pub fn print_interpreter_stats(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        let stats = self.interpreter_stats();
+        let mut totals: HashMap<&str, u64> = HashMap::new();
+        for method in &stats {
+            for (op, count) in &method.op_counts {
+                *totals.entry(op.as_str()).or_insert(0) += count;
+            }
+        }
+        let mut totals: Vec<(&str, u64)> = totals.into_iter().collect();
+        totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        writeln!(out, "opcode totals:")?;
+        for (op, count) in totals {
+            writeln!(out, "{:>14}  {}", count, op)?;
+        }
+        for method in &stats {
+            writeln!(out)?;
+            writeln!(
+                out,
+                "{}#{}{}: {} ops, {} taken, {} not taken branches",
+                method.class_name,
+                method.method_name,
+                method.descriptor,
+                method.total_ops(),
+                method.branches_taken,
+                method.branches_not_taken
+            )?;
+            for (op, count) in &method.op_counts {
+                writeln!(out, "{:>14}  {}", count, op)?;
+            }
+        }
+        return Ok(());
+    }
+
     pub fn call_static_void(&self, class: JClassPtr, method: MethodPtr, args: &[JValue]) {
         let thread = Thread::current();
-        class.initialize(thread).unwrap();
+        method.decl_cls().initialize(thread).unwrap();
         Interpreter::call_static_method(class, method, args, thread);
     }
 
-    pub fn call_static(&self, class: JClassPtr, method: MethodPtr, args: &[JValue]) -> JValue {
+    /// Calls the static `method` with one `JValue` per parameter and converts
+    /// the result with [`FromJValue`] according to the return type of the
+    /// method, e.g. `vm.call_static::<JInt>(class, method, &[])?`. A `T` that
+    /// does not fit the return type fails after the call, `JValue` takes the
+    /// result as is.
+    pub fn call_static<T: FromJValue>(
+        &self,
+        class: JClassPtr,
+        method: MethodPtr,
+        args: &[JValue],
+    ) -> Result<T, VMError> {
+        let return_type = self.check_call(method, args)?;
         let thread = Thread::current();
-        class.initialize(thread).unwrap();
-        return Interpreter::call_static_method(class, method, args, thread);
+        method.decl_cls().initialize(thread)?;
+        let result = Interpreter::call_static_method(class, method, args, thread);
+        Self::check_cancelled(thread)?;
+        return T::from_jvalue(result, &return_type, self);
     }
 
     pub fn call_obj_void(&self, objref: ObjectPtr, method: MethodPtr, args: &[JValue]) {
@@ -263,9 +856,117 @@ impl VM {
         Interpreter::call_obj_void_method(objref, method, args, thread);
     }
 
-    pub fn call_obj(&self, objref: ObjectPtr, method: MethodPtr, args: &[JValue]) -> JValue {
+    /// Calls the instance `method` on `objref` like invokevirtual, running the
+    /// override in the class of `objref`, and converts the result like
+    /// [`call_static`](Self::call_static).
+    pub fn call_virtual<T: FromJValue>(
+        &self,
+        objref: ObjectPtr,
+        method: MethodPtr,
+        args: &[JValue],
+    ) -> Result<T, VMError> {
+        let return_type = self.check_call(method, args)?;
+        if objref.is_null() {
+            return Err(VMError::RuntimeError(format!(
+                "cannot call {} on null",
+                method.name().as_str()
+            )));
+        }
+        let ctor_init = self.shared_objs().symbols().ctor_init;
+        let target = if method.is_private() || method.name() == ctor_init {
+            method
+        } else {
+            objref
+                .jclass()
+                .resolve_class_method(method.name(), method.descriptor(), self)
+                .map_or(method, |resolved| resolved.method)
+        };
+        if target.is_abstract() {
+            return Err(VMError::RuntimeError(format!(
+                "{} does not implement {}{}",
+                objref.jclass().name().as_str(),
+                method.name().as_str(),
+                method.descriptor().as_str()
+            )));
+        }
+        let thread = Thread::current();
+        let result = Interpreter::call_obj_method(objref, target, args, thread);
+        Self::check_cancelled(thread)?;
+        return T::from_jvalue(result, &return_type, self);
+    }
+
+    /// Creates an object of the class `class_name`, a binary name, with the
+    /// constructor of `descriptor`, e.g.
+    /// `vm.new_object("java.lang.StringBuilder", "(I)V", &[JValue::with_int_val(16)])?`.
+    pub fn new_object(
+        &self,
+        class_name: &str,
+        descriptor: &str,
+        args: &[JValue],
+    ) -> Result<ObjectPtr, VMError> {
         let thread = Thread::current();
-        return Interpreter::call_obj_method(objref, method, args, thread);
+        let class = self
+            .bootstrap_class_loader
+            .load_binary_name_class(class_name)?;
+        class.initialize(thread)?;
+        let ctor = class
+            .resolve_self_method(
+                self.shared_objs().symbols().ctor_init,
+                self.get_symbol(descriptor),
+            )
+            .map_err(|_| {
+                VMError::RuntimeError(format!(
+                    "constructor {}{} not found",
+                    class_name, descriptor
+                ))
+            })?;
+        let obj = Handle::new(Object::new(class, thread));
+        self.call_virtual::<JValue>(obj.as_ptr(), ctor.method, args)?;
+        return Ok(obj.as_ptr());
+    }
+
+    /// Limits the Java code the current thread runs from now on, replacing its
+    /// previous budget. The calls that exceed it fail with
+    /// [`VMError::Cancelled`], and so do all later calls until the thread gets
+    /// a new budget.
+    pub fn set_execution_budget(&self, budget: ExecutionBudget) {
+        Thread::current()
+            .as_mut_ref()
+            .interpreter_mut()
+            .set_execution_budget(budget);
+    }
+
+    /// Cancels the Java code `thread` runs, its call fails with
+    /// [`VMError::Cancelled`]. Can be called from any thread, a thread that
+    /// does not run Java code cancels its next call. Natives, e.g.
+    /// `Object.wait`, are not interrupted, the code is cancelled once they
+    /// return.
+    pub fn interrupt_execution(&self, thread: ThreadPtr) {
+        thread.interpreter().interrupt_execution();
+    }
+
+    fn check_cancelled(thread: ThreadPtr) -> Result<(), VMError> {
+        return match thread.as_mut_ref().interpreter_mut().take_cancellation() {
+            Some(reason) => Err(VMError::Cancelled(reason)),
+            None => Ok(()),
+        };
+    }
+
+    /// The return type of `method` if `args` has one value per parameter.
+    fn check_call(&self, method: MethodPtr, args: &[JValue]) -> Result<String, VMError> {
+        let descriptor = method.descriptor();
+        let (param_types, return_type) =
+            convert::split_method_descriptor(descriptor.as_str(), self)?;
+        if args.len() != param_types.len() {
+            return Err(VMError::RuntimeError(format!(
+                "{}#{} expects {} arguments, got {}",
+                method.decl_cls().name().as_str(),
+                method.name().as_str(),
+                param_types.len(),
+                args.len()
+            )));
+        }
+        return Ok(return_type.to_string());
     }
 
     pub(crate) fn heap(&self) -> &Heap {
@@ -287,7 +988,6 @@ impl VM {
             BootstrapClassLoader::new(&self.cfg.class_path, &self.cfg.current_dir, thread);
 
         let vm = VMPtr::from_ref(self);
-        self.jni.init(vm);
         self.shared_objs.init(thread);
         self.preloaded_classes.init(vm, thread)?;
         self.shared_objs.post_init(vm, thread)?;
@@ -307,21 +1007,140 @@ impl VM {
         &self.shared_objs
     }
 
-    pub(crate) fn get_builtin_native_fn(&self, fn_name: &str) -> Option<Address> {
+    pub(crate) fn get_builtin_native_fn(&self, fn_name: &str) -> Option<&BuiltinNative> {
         return self.builtin_native_fns.get_builtin_native_fn(fn_name);
     }
 
+    pub(crate) fn get_builtin_java_method_fn(
+        &self,
+        class_name: &str,
+        method_name: &str,
+        descriptor: &str,
+    ) -> Option<&BuiltinNative> {
+        return self.builtin_native_fns.get_builtin_java_method_fn(
+            class_name,
+            method_name,
+            descriptor,
+        );
+    }
+
     pub(crate) fn jni(&self) -> &JNIWrapper {
         &self.jni
     }
 }
 
+/// A static method resolved by [`VM::method`], whose arguments are checked
+/// against its descriptor before each call.
+pub struct StaticMethod<'a> {
+    vm: &'a VM,
+    class: JClassPtr,
+    method: MethodPtr,
+    param_types: Vec<String>,
+    return_type: String,
+}
+
+impl<'a> StaticMethod<'a> {
+    pub fn param_types(&self) -> &[String] {
+        return &self.param_types;
+    }
+
+    pub fn return_type(&self) -> &str {
+        return &self.return_type;
+    }
+
+    /// Calls the method with raw arguments, one `JValue` per parameter
+    /// (`long` and `double` included).
+    pub fn call(&self, args: &[JValue]) -> Result<JValue, VMError> {
+        return self.vm.call_static(self.class, self.method, args);
+    }
+
+    /// Calls the method, converting `args` and the result with
+    /// [`ToJValue`](convert::ToJValue) and [`FromJValue`].
+    pub fn invoke<A: JArgs, R: FromJValue>(&self, args: A) -> Result<R, VMError> {
+        let param_types: Vec<&str> = self.param_types.iter().map(|ty| ty.as_str()).collect();
+        let args = args.to_jargs(&param_types, self.vm)?;
+        return self.vm.call_static(self.class, self.method, &args);
+    }
+}
+
+macro_rules! primitive_array_accessors {
+    ($(($name:ident, $element_type:ty, $array_ptr:ty, $array_cls:ident)),*) => {
+        impl VM {
+            paste! {
+                $(
+                    #[doc = concat!("Creates a `", stringify!($name), "[]` holding a copy of `values`.")]
+                    pub fn [<new_ $name _array>](&self, values: &[$element_type]) -> $array_ptr {
+                        let length = values.len() as JInt;
+                        let mut array: $array_ptr = JArray::new(
+                            length,
+                            self.preloaded_classes.$array_cls(),
+                            Thread::current(),
+                        )
+                        .cast();
+                        array.copy_from_raw(Ptr::from_raw(values.as_ptr()), length);
+                        return array;
+                    }
+
+                    #[doc = concat!("Copies the elements of a `", stringify!($name), "[]` into a `Vec`.")]
+                    pub fn [<get_ $name _array>](&self, array: $array_ptr) -> Vec<$element_type> {
+                        assert!(array.is_not_null(), "get_array on null reference");
+                        return array.to_slice().to_vec();
+                    }
+                )*
+            }
+        }
+    };
+}
+
+primitive_array_accessors!(
+    (boolean, JBoolean, JBooleanArrayPtr, bool_arr_cls),
+    (byte, JByte, JByteArrayPtr, byte_arr_cls),
+    (char, JChar, JCharArrayPtr, char_arr_cls),
+    (short, JShort, JShortArrayPtr, short_arr_cls),
+    (int, JInt, JIntArrayPtr, int_arr_cls),
+    (long, JLong, JLongArrayPtr, long_arr_cls),
+    (float, JFloat, JFloatArrayPtr, float_arr_cls),
+    (double, JDouble, JDoubleArrayPtr, double_arr_cls)
+);
+
 unsafe impl Send for VM {}
 unsafe impl Send for VMPtr {}
 
 #[cfg(test)]
 mod tests {
-    use crate::{object::string::JString, test, thread::Thread, value::JValue, JArray};
+    use std::{
+        error::Error,
+        ffi::{c_void, CStr, CString},
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicI32, AtomicUsize, Ordering},
+            Arc, Barrier, Mutex,
+        },
+        time::Duration,
+    };
+
+    use super::{SymbolPtr, VMConfig, VMError, VMPtr};
+    use crate::{
+        classfile::ClassLoadErr,
+        execution::{CancelReason, ExecutionBudget},
+        memory::MB,
+        native::signature::NativeSignature,
+        object::{
+            class::{ClassData, FieldResolutionError, JClass, MethodResolutionError},
+            constant_pool::ConstantTag,
+            field::{FieldKind, FieldPtr},
+            method::{Method, MethodPtr},
+            prelude::{JByte, JChar, JDouble, JInt, JLong},
+            string::{JString, JStringPtr},
+            Object,
+        },
+        os::{self, StdStream},
+        sandbox::{ResourceAccess, SandboxPolicy},
+        test,
+        thread::{Thread, ThreadPtr, ThreadState},
+        value::JValue,
+        JArray, JClassPtr, ObjectPtr,
+    };
 
     #[test]
     fn invoke_hello_rsvm() {
@@ -340,6 +1159,11 @@ mod tests {
         );
     }
 
+    #[test]
+    fn regression_programs() {
+        test::check_programs("./tests/classes", "programs");
+    }
+
     #[test]
     fn invoke_fibonacci() {
         test::run_in_vm_and_call_static(
@@ -354,6 +1178,87 @@ mod tests {
         );
     }
 
+    #[test]
+    fn implicit_null_checks() {
+        test::ensure_class_exists("./tests/classes", "rsvm.NullChecks");
+        test::run_in_vm("./tests/classes", |vm| {
+            let walk = |steps: i32| -> i64 {
+                return vm
+                    .invoke_static("rsvm.NullChecks", "walk", "(I)J", (steps,))
+                    .unwrap();
+            };
+            let fields = || -> f64 {
+                return vm
+                    .invoke_static("rsvm.NullChecks", "fields", "()D", ())
+                    .unwrap();
+            };
+            let expected = 1.0 - 2.0 + 65535.0 - 3.0 - 4.0 - 0.5 - (1u64 << 40) as f64 + 0.25;
+            assert_eq!(3000, walk(1000));
+            assert_eq!(expected, fields());
+            vm.as_mut_ref().cfg.implicit_null_checks = true;
+            assert_eq!(3000, walk(1000));
+            assert_eq!(expected, fields());
+
+            if os::CAN_RESUME_FAULTS_ELSEWHERE {
+                for method in ["fieldOfNull", "methodOfNull"] {
+                    let null_access: Result<i32, VMError> =
+                        vm.invoke_static("rsvm.NullChecks", method, "()I", ());
+                    assert!(matches!(
+                        null_access,
+                        Err(VMError::Cancelled(CancelReason::NullPointer))
+                    ));
+                    assert_eq!(3000, walk(1000));
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn stack_overflow() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Recursion");
+        test::run_in_vm("./tests/classes", |vm| {
+            let depth: i32 = vm
+                .invoke_static("rsvm.Recursion", "depth", "(I)I", (10000,))
+                .unwrap();
+            assert_eq!(10000, depth);
+
+            let overflow: Result<i32, VMError> =
+                vm.invoke_static("rsvm.Recursion", "unbounded", "(I)I", (0,));
+            assert!(matches!(
+                overflow,
+                Err(VMError::Cancelled(CancelReason::StackOverflow))
+            ));
+            let depth: i32 = vm
+                .invoke_static("rsvm.Recursion", "depth", "(I)I", (10,))
+                .unwrap();
+            assert_eq!(10, depth);
+        });
+    }
+
+    #[test]
+    fn stack_guard_hit() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Recursion");
+        test::run_in_vm("./tests/classes", |vm| {
+            let thread = Thread::current();
+            let stack_memory = thread.stack_memory();
+            let guard_slot = stack_memory.stack_addr().offset(-8).as_mut_raw_ptr();
+            unsafe { std::ptr::write_volatile(guard_slot, 1u8) };
+            assert!(!stack_memory.is_guard_armed());
+
+            let depth: Result<i32, VMError> =
+                vm.invoke_static("rsvm.Recursion", "depth", "(I)I", (10,));
+            assert!(matches!(
+                depth,
+                Err(VMError::Cancelled(CancelReason::StackOverflow))
+            ));
+            assert!(stack_memory.is_guard_armed());
+            let depth: i32 = vm
+                .invoke_static("rsvm.Recursion", "depth", "(I)I", (10,))
+                .unwrap();
+            assert_eq!(10, depth);
+        });
+    }
+
     #[test]
     fn invoke_virtual() {
         test::run_in_vm_and_call_static(
@@ -370,6 +1275,3377 @@ mod tests {
         );
     }
 
+    #[test]
+    fn invoke_interface() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Interfaces");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.Interfaces", method_name, "()J", ())
+                    .unwrap();
+            };
+            assert_eq!(12322, call("callImpl"));
+            assert_eq!(712302, call("callSub"));
+            // the long and double arguments take two slots each
+            assert_eq!(1002034, call("callWide"));
+        });
+    }
+
+    #[test]
+    fn invoke_interface_count() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Interfaces");
+        test::run_in_vm("./tests/classes", |vm| {
+            let mut bytes = std::fs::read("./tests/classes/rsvm/Interfaces.class").unwrap();
+            // invokeinterface Wide.mix, whose arguments take 7 slots
+            let at = bytes
+                .windows(5)
+                .position(|code| code[0] == 0xb9 && code[3] == 7 && code[4] == 0)
+                .unwrap();
+            // a count past the operand stack of the caller
+            bytes[at + 3] = 0xff;
+            vm.bootstrap_class_loader
+                .define_class(bytes, Thread::current())
+                .unwrap();
+
+            let result: Result<JLong, VMError> =
+                vm.invoke_static("rsvm.Interfaces", "callWide", "()J", ());
+            match result {
+                Err(VMError::Cancelled(CancelReason::LinkageError(msg))) => {
+                    assert_eq!(
+                        "ClassFormatError: invokeinterface count 255 of \
+                         rsvm/Interfaces$Wide.mix(JID[Ljava/lang/Object;)J",
+                        msg
+                    );
+                }
+                _ => panic!("callWide did not fail"),
+            }
+        });
+    }
+
+    #[test]
+    fn call_virtual() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Interfaces");
+        test::run_in_vm("./tests/classes", |vm| {
+            let sub: ObjectPtr = vm
+                .invoke_static("rsvm.Interfaces", "newSub", "()Ljava/lang/Object;", ())
+                .unwrap();
+            let impl_cls = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("rsvm.Interfaces$Impl")
+                .unwrap();
+            let second = vm
+                .get_method(impl_cls, "second", "()J", Thread::current())
+                .unwrap();
+            // runs the override of Sub
+            assert_eq!(50000, vm.call_virtual::<JLong>(sub, second, &[]).unwrap());
+            assert!(vm.call_virtual::<String>(sub, second, &[]).is_err());
+            assert!(vm
+                .call_virtual::<JLong>(sub, second, &[JValue::with_int_val(1)])
+                .is_err());
+            assert!(vm
+                .call_virtual::<JLong>(ObjectPtr::null(), second, &[])
+                .is_err());
+        });
+    }
+
+    #[test]
+    fn thread_states() {
+        test::ensure_class_exists("./tests/classes", "rsvm.ThreadStates");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.ThreadStates", method_name, "()J", ())
+                    .unwrap();
+            };
+            assert_eq!(11, call("currentState"));
+            assert_eq!(1111111, call("holdsLock"));
+            assert_eq!(11, call("newThread"));
+
+            let thread = Thread::current();
+            // locks the class object, like a static synchronized method
+            let lock: ObjectPtr = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("rsvm.ThreadStates")
+                .unwrap()
+                .cast();
+            thread.enter_monitor(lock);
+            let mut out = Vec::new();
+            vm.print_thread_dump(&mut out).unwrap();
+            assert!(thread.exit_monitor(lock));
+            let dump = vm.thread_dump();
+            let current = dump
+                .iter()
+                .find(|snapshot| snapshot.thread_id == thread.thread_id())
+                .unwrap();
+            assert_eq!(ThreadState::Runnable, current.state);
+            assert!(current.locked_monitors.is_empty());
+            let out = String::from_utf8(out).unwrap();
+            assert!(out.contains("java.lang.Thread.State: RUNNABLE"));
+            assert!(out.contains("- locked (a java.lang.Class)"));
+        });
+    }
+
+    #[test]
+    fn caller_class() {
+        test::ensure_class_exists("./tests/classes", "rsvm.CallerClass");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.CallerClass", method_name, "()J", ())
+                    .unwrap();
+            };
+            assert_eq!(1, call("forName"));
+            // a wrong caller has no access to the private field
+            assert_eq!(11, call("privateUpdater"));
+        });
+    }
+
+    #[test]
+    fn boot_class_path_resources() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Resources");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.Resources", method_name, "()J", ())
+                    .unwrap();
+            };
+            assert_eq!(1042, call("readProperties"));
+            let properties = vm
+                .bootstrap_class_loader
+                .read_resource("rsvm/resource.properties")
+                .unwrap();
+            assert_eq!(properties.len() as JLong, call("classResource"));
+            assert_eq!(111, call("urls"));
+            let count = |name: &str| -> JLong {
+                return vm
+                    .invoke_static(
+                        "rsvm.Resources",
+                        "countResources",
+                        "(Ljava/lang/String;)J",
+                        (name,),
+                    )
+                    .unwrap();
+            };
+            assert_eq!(1, count("java/lang/Object.class"));
+            assert_eq!(0, count("rsvm/missing.properties"));
+
+            let url = vm
+                .bootstrap_class_loader
+                .find_resource("java/lang/String.class")
+                .unwrap();
+            assert!(url.starts_with("jar:file:") && url.ends_with("!/java/lang/String.class"));
+            assert_eq!(
+                None,
+                vm.bootstrap_class_loader.find_resource("../Cargo.toml")
+            );
+        });
+    }
+
+    #[test]
+    fn class_context() {
+        test::ensure_class_exists("./tests/classes", "rsvm.ClassContext");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> String {
+                let names: Option<String> = vm
+                    .invoke_static("rsvm.ClassContext", method_name, "()Ljava/lang/String;", ())
+                    .unwrap();
+                return names.unwrap();
+            };
+            assert_eq!(
+                "rsvm.ClassContext,rsvm.ClassContext$Caller,rsvm.ClassContext",
+                call("context")
+            );
+            // the bridge of the action runs too, the native doPrivileged is
+            // left out
+            let privileged = [
+                "rsvm.ClassContext",
+                "rsvm.ClassContext$Caller",
+                "rsvm.ClassContext$1",
+                "rsvm.ClassContext$1",
+                "rsvm.ClassContext",
+            ];
+            assert_eq!(privileged.join(","), call("privilegedContext"));
+        });
+    }
+
+    #[test]
+    fn stack_traces() {
+        test::ensure_class_exists("./tests/classes", "rsvm.StackTraces");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> String {
+                let trace: Option<String> = vm
+                    .invoke_static("rsvm.StackTraces", method_name, "()Ljava/lang/String;", ())
+                    .unwrap();
+                return trace.unwrap();
+            };
+            // the lines of StackTraces.java
+            let trace = "rsvm.StackTraces.trace(StackTraces.java:26)\n";
+            assert_eq!(trace, call("trace"));
+            assert_eq!(
+                format!("{}rsvm.StackTraces.nested(StackTraces.java:30)\n", trace),
+                call("nested")
+            );
+            // the constructors of the exception are left out
+            assert_eq!(
+                "rsvm.StackTraces.subclass(StackTraces.java:34)\n",
+                call("subclass")
+            );
+            assert_eq!("callSite:39", call("callSite"));
+
+            let reflective = call("reflective");
+            assert!(reflective.starts_with(trace), "{}", reflective);
+            assert!(reflective.contains(
+                "sun.reflect.NativeMethodAccessorImpl.invoke0(NativeMethodAccessorImpl.java:native)\n"
+            ));
+            assert!(reflective.ends_with("rsvm.StackTraces.reflective(StackTraces.java:49)\n"));
+        });
+    }
+
+    #[test]
+    fn console_charset() {
+        test::ensure_class_exists("./tests/classes", "rsvm.ConsoleCharset");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.ConsoleCharset", method_name, "()J", ())
+                    .unwrap();
+            };
+            assert_eq!(1, call("printNonAscii"));
+            // on a terminal the Formatter of a Console reads lib/currency.data,
+            // which the class library here does not ship
+            if !os::is_tty(StdStream::In) || !os::is_tty(StdStream::Out) {
+                assert_eq!(0, call("hasConsole"));
+            }
+
+            let stdout_encoding: Option<String> = vm
+                .invoke_static(
+                    "rsvm.ConsoleCharset",
+                    "stdoutEncoding",
+                    "()Ljava/lang/String;",
+                    (),
+                )
+                .unwrap();
+            let expected = os::console_encoding().filter(|_| os::is_tty(StdStream::Out));
+            assert_eq!(expected, stdout_encoding);
+        });
+    }
+
+    #[test]
+    fn platform_properties() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Platform");
+        test::run_in_vm("./tests/classes", |vm| {
+            let property = |key: &str| -> Option<String> {
+                return vm
+                    .invoke_static(
+                        "rsvm.Platform",
+                        "property",
+                        "(Ljava/lang/String;)Ljava/lang/String;",
+                        (key,),
+                    )
+                    .unwrap();
+            };
+            let capabilities = vm.capabilities();
+            assert_eq!(7, capabilities.java_specification_version);
+            assert!(capabilities.class_file_versions.contains(&51));
+            for (key, value) in capabilities.system_properties() {
+                assert_eq!(Some(value), property(key), "{}", key);
+            }
+            assert_eq!(
+                Some("1.7".to_string()),
+                property("java.specification.version")
+            );
+            assert_eq!(Some("51.0".to_string()), property("java.class.version"));
+            assert_eq!(Some("rsvm".to_string()), property("java.vm.name"));
+            // put by the class library, which has to be of the same level
+            assert!(property("java.version").unwrap().starts_with("1.7."));
+        });
+    }
+
+    #[test]
+    fn locale_defaults() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Locales");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> String {
+                let value: Option<String> = vm
+                    .invoke_static("rsvm.Locales", method_name, "()Ljava/lang/String;", ())
+                    .unwrap();
+                return value.unwrap();
+            };
+            let locale = os::locale().unwrap_or_default();
+            let language = if locale.language.is_empty() {
+                "en"
+            } else {
+                &locale.language
+            };
+            assert_eq!(language, call("language"));
+            assert_eq!(locale.country, call("country"));
+        });
+    }
+
+    #[test]
+    fn file_attributes() {
+        test::ensure_class_exists("./tests/classes", "rsvm.FileAttributes");
+        let dir = std::env::temp_dir().join(format!("rsvm-file-attributes-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub").join("file.txt"), "rsvm").unwrap();
+        std::fs::write(dir.join(".hidden"), "").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("sub"), dir.join("link")).unwrap();
+        // the temporary directory itself may be behind a symlink
+        let dir = dir.canonicalize().unwrap();
+        let cleanup = dir.clone();
+        test::run_in_vm("./tests/classes", move |vm| {
+            let path = |name: &str| -> String {
+                return dir.join(name).display().to_string();
+            };
+            let attributes = |name: &str| -> JLong {
+                return vm
+                    .invoke_static(
+                        "rsvm.FileAttributes",
+                        "attributes",
+                        "(Ljava/lang/String;)J",
+                        (path(name),),
+                    )
+                    .unwrap();
+            };
+            let canonical_path = |name: &str| -> String {
+                return vm
+                    .invoke_static(
+                        "rsvm.FileAttributes",
+                        "canonicalPath",
+                        "(Ljava/lang/String;)Ljava/lang/String;",
+                        (path(name),),
+                    )
+                    .unwrap();
+            };
+            assert_eq!(1010, attributes("sub"));
+            assert_eq!(1100, attributes("sub/file.txt"));
+            assert_eq!(1101, attributes(".hidden"));
+            assert_eq!(0, attributes("missing"));
+            assert_eq!(
+                path("sub/file.txt"),
+                canonical_path("sub/../sub/./file.txt")
+            );
+            assert_eq!(path("missing/file.txt"), canonical_path("missing/file.txt"));
+            assert_eq!(path("sub"), canonical_path("missing/../sub"));
+
+            #[cfg(unix)]
+            {
+                assert_eq!(1100, attributes("link/file.txt"));
+                assert_eq!(path("sub/file.txt"), canonical_path("link/file.txt"));
+                assert_eq!(
+                    path("sub/new.txt"),
+                    canonical_path("link/missing/../new.txt")
+                );
+            }
+        });
+        std::fs::remove_dir_all(cleanup).unwrap();
+    }
+
+    #[test]
+    fn mapped_files() {
+        test::ensure_class_exists("./tests/classes", "rsvm.MappedFiles");
+        let path = std::env::temp_dir().join(format!("rsvm-mapped-files-{}", std::process::id()));
+        std::fs::write(&path, "hello, world").unwrap();
+        let cleanup = path.clone();
+        test::run_in_vm("./tests/classes", move |vm| {
+            let path = path.display().to_string();
+            let write = |position: JLong, text: &str| {
+                let _: () = vm
+                    .invoke_static(
+                        "rsvm.MappedFiles",
+                        "write",
+                        "(Ljava/lang/String;JLjava/lang/String;)V",
+                        (path.clone(), position, text.to_string()),
+                    )
+                    .unwrap();
+            };
+            write(7, "rsvm!");
+            assert_eq!("hello, rsvm!", std::fs::read_to_string(&path).unwrap());
+            // a mapping past the end grows the file
+            write(12, " mapped");
+            assert_eq!(
+                "hello, rsvm! mapped",
+                std::fs::read_to_string(&path).unwrap()
+            );
+            let text: Option<String> = vm
+                .invoke_static(
+                    "rsvm.MappedFiles",
+                    "read",
+                    "(Ljava/lang/String;)Ljava/lang/String;",
+                    (path.clone(),),
+                )
+                .unwrap();
+            assert_eq!(Some("hello, rsvm! mapped".to_string()), text);
+
+            // the buffers are never collected, so their mappings stay until
+            // the VM is destroyed. Each starts at the beginning of the file,
+            // the allocation granularity.
+            assert_eq!((7 + 5) + (12 + 7) + 19, vm.mapped_files.mapped_bytes());
+            vm.mapped_files.unmap_all();
+            assert_eq!(0, vm.mapped_files.mapped_bytes());
+        });
+        std::fs::remove_file(cleanup).unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn selectors() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Selectors");
+        test::run_in_vm("./tests/classes", |vm| {
+            let echoed: Option<String> = vm
+                .invoke_static(
+                    "rsvm.Selectors",
+                    "echo",
+                    "(Ljava/lang/String;)Ljava/lang/String;",
+                    ("hello, selector",),
+                )
+                .unwrap();
+            assert_eq!(Some("hello, selector".to_string()), echoed);
+
+            let selected: i32 = vm
+                .invoke_static("rsvm.Selectors", "wakeup", "()I", ())
+                .unwrap();
+            assert_eq!(0, selected);
+        });
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn socket_options() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Sockets");
+        test::run_in_vm("./tests/classes", |vm| {
+            let echoed: Option<String> = vm
+                .invoke_static(
+                    "rsvm.Sockets",
+                    "echo",
+                    "(Ljava/lang/String;)Ljava/lang/String;",
+                    ("hello, socket",),
+                )
+                .unwrap();
+            assert_eq!(Some("hello, socket true true 5000".to_string()), echoed);
+        });
+    }
+
+    #[test]
+    fn seed_generator() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let entropy: Vec<i8> = vm
+                .invoke_static(
+                    "sun.security.provider.SeedGenerator",
+                    "getSystemEntropy",
+                    "()[B",
+                    (),
+                )
+                .unwrap();
+            assert_eq!(20, entropy.len());
+
+            let seed = vm.new_byte_array(&[0; 32]);
+            let _: () = vm
+                .invoke_static(
+                    "sun.security.provider.SeedGenerator",
+                    "generateSeed",
+                    "([B)V",
+                    (seed.cast::<Object>(),),
+                )
+                .unwrap();
+            assert_ne!(vec![0; 32], vm.get_byte_array(seed));
+        });
+    }
+
+    #[test]
+    fn service_providers() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Services");
+        test::run_in_vm("./tests/classes", |vm| {
+            let loader = &vm.bootstrap_class_loader;
+            let services = loader.services();
+            assert!(services.contains(&"java.nio.charset.spi.CharsetProvider".to_string()));
+            assert!(services.contains(&"rsvm.Services$Greeter".to_string()));
+            assert_eq!(
+                vec!["sun.nio.cs.ext.ExtendedCharsets".to_string()],
+                loader.service_providers("java.nio.charset.spi.CharsetProvider")
+            );
+            assert_eq!(
+                vec![
+                    "rsvm.Services$Hello".to_string(),
+                    "rsvm.Services$Hi".to_string()
+                ],
+                loader.service_providers("rsvm.Services$Greeter")
+            );
+            assert!(loader
+                .service_providers("rsvm.Services$Unprovided")
+                .is_empty());
+
+            let service_name: String = vm
+                .invoke_static("rsvm.Services", "serviceName", "()Ljava/lang/String;", ())
+                .unwrap();
+            assert_eq!("rsvm.Services$Greeter", service_name);
+            let call = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.Services", method_name, "()J", ())
+                    .unwrap();
+            };
+            assert_eq!(12, call("greeters"));
+            assert_eq!(0, call("unprovided"));
+        });
+    }
+
+    #[test]
+    fn synchronized_method_exit() {
+        test::ensure_class_exists("./tests/classes", "rsvm.ThreadStates");
+        test::run_in_vm("./tests/classes", |vm| {
+            let mut bytes = std::fs::read("./tests/classes/rsvm/ThreadStates.class").unwrap();
+            // aconst_null, astore_1 and return of clobberReceiver
+            let at = bytes
+                .windows(3)
+                .position(|code| code == [0x01, 0x4c, 0xb1])
+                .unwrap();
+            // astore_0, the receiver local no longer holds the locked object
+            bytes[at + 1] = 0x4b;
+            vm.bootstrap_class_loader
+                .define_class(bytes, Thread::current())
+                .unwrap();
+
+            let _: () = vm
+                .invoke_static("rsvm.ThreadStates", "clobberedReceiver", "()V", ())
+                .unwrap();
+            assert!(Thread::current().locked_monitors().is_empty());
+        });
+    }
+
+    #[test]
+    fn monitor_info() {
+        test::ensure_class_exists("./tests/classes", "rsvm.ThreadStates");
+        test::run_in_vm("./tests/classes", |vm| {
+            let instance_lock: JLong = vm
+                .invoke_static("rsvm.ThreadStates", "instanceLock", "()J", ())
+                .unwrap();
+            assert_eq!(11, instance_lock);
+            let thread = Thread::current();
+            let lock: ObjectPtr = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("rsvm.ThreadStates")
+                .unwrap()
+                .cast();
+            assert_eq!(None, vm.monitor_info(lock));
+            thread.enter_monitor(lock);
+            thread.enter_monitor(lock);
+            let info = vm.monitor_info(lock).unwrap();
+            assert_eq!(thread.thread_id(), info.owner_thread_id);
+            assert_eq!(
+                vm.shared_objs()
+                    .class_infos()
+                    .java_lang_thread_info()
+                    .thread_name(thread.jthread()),
+                info.owner_name
+            );
+            assert_eq!(2, info.entry_count);
+            assert!(thread.holds_lock(lock));
+            assert!(thread.exit_monitor(lock));
+            assert_eq!(1, vm.monitor_info(lock).unwrap().entry_count);
+            assert!(thread.exit_monitor(lock));
+            assert!(!thread.exit_monitor(lock));
+            assert!(!thread.holds_lock(lock));
+            assert_eq!(None, vm.monitor_info(lock));
+        });
+    }
+
+    #[test]
+    fn invoke_miranda_methods() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Miranda");
+        test::run_in_vm("./tests/classes", |vm| {
+            let total: JLong = vm
+                .invoke_static("rsvm.Miranda", "total", "()J", ())
+                .unwrap();
+            assert_eq!(54322, total);
+
+            // an interface initializer gets neither an itable entry nor a
+            // Miranda slot
+            let loader = &vm.bootstrap_class_loader;
+            let counter = loader.load_class("rsvm/Miranda$Counter").unwrap();
+            let counted = loader.load_class("rsvm/Miranda$Counted").unwrap();
+            let clinit = (vm.get_symbol("<clinit>"), vm.get_symbol("()V"));
+            assert!(counter
+                .resolve_class_method(clinit.0, clinit.1, vm.as_ref())
+                .is_err());
+            assert!(matches!(
+                JClass::resolve_interface_method(counter, counted, clinit.0, clinit.1, vm.as_ref()),
+                Err(MethodResolutionError::IncompatibleClassChange)
+            ));
+            let count = JClass::resolve_interface_method(
+                counter,
+                counted,
+                vm.get_symbol("count"),
+                vm.get_symbol("()J"),
+                vm.as_ref(),
+            )
+            .unwrap();
+            assert!(count.method.is_abstract());
+        });
+    }
+
+    #[test]
+    fn vtable_before_link() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Miranda");
+        test::run_in_vm("./tests/classes", |vm| {
+            let loader = &vm.bootstrap_class_loader;
+            let larger = loader.load_class("rsvm/Miranda$Larger").unwrap();
+            let named = loader.load_class("rsvm/Miranda$Named").unwrap();
+            assert!(!larger.is_linked());
+            let size = || {
+                return larger
+                    .resolve_class_method(vm.get_symbol("size"), vm.get_symbol("()J"), vm.as_ref())
+                    .unwrap();
+            };
+            // the vtable is written when the class is parsed
+            assert!(larger.is_implement(named));
+            let unlinked = size();
+            assert_eq!(larger, unlinked.method.decl_cls());
+
+            larger.initialize(Thread::current()).unwrap();
+            let linked = size();
+            assert_eq!(unlinked.method, linked.method);
+            assert_eq!(unlinked.method_idx, linked.method_idx);
+        });
+    }
+
+    #[test]
+    fn method_params_until_link() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Sandboxed");
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = vm
+                .bootstrap_class_loader
+                .load_class("rsvm/Sandboxed")
+                .unwrap();
+            let method = |name: &str| -> MethodPtr {
+                let methods = class.class_data().methods();
+                return (0..methods.length())
+                    .map(|idx| methods.get(idx).cast::<Method>())
+                    .find(|method| method.name().as_str() == name)
+                    .unwrap();
+            };
+            let first_byte = method("firstByte");
+            let allocate = method("allocate");
+            let string_cls = vm
+                .bootstrap_class_loader
+                .load_class("java/lang/String")
+                .unwrap();
+            let int_cls = vm.preloaded_classes().int_cls();
+
+            assert!(!class.is_linked());
+            let descriptor = |method: MethodPtr| -> SymbolPtr {
+                return method.param_descriptors().get(0).cast();
+            };
+            assert_eq!("Ljava/lang/String;", descriptor(first_byte).as_str());
+            assert_eq!(FieldKind::Reference, first_byte.param_kind(0));
+            assert_eq!(string_cls, first_byte.param_type(0, &vm).unwrap());
+            assert_eq!("I", descriptor(allocate).as_str());
+            assert_eq!(FieldKind::Int, allocate.param_kind(0));
+            assert_eq!(int_cls, allocate.param_type(0, &vm).unwrap());
+
+            class.initialize(Thread::current()).unwrap();
+            assert_eq!(string_cls, first_byte.param_types().get(0).cast());
+            assert_eq!(int_cls, allocate.param_types().get(0).cast());
+        });
+    }
+
+    #[test]
+    fn package_private_overrides() {
+        test::ensure_class_exists("./tests/classes", "rsvm.PackagePrivate");
+        test::run_in_vm("./tests/classes", |vm| {
+            let result: JLong = vm
+                .invoke_static("rsvm.PackagePrivate", "callAll", "()J", ())
+                .unwrap();
+            // only the method in the same package overrides PackagePrivate.value
+            assert_eq!(2231, result);
+        });
+    }
+
+    #[test]
+    fn bridge_methods() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Bridges");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.Bridges", method_name, "()J", ())
+                    .unwrap();
+            };
+            // the generic and the covariant override run through their bridges
+            assert_eq!(34, call("dispatch"));
+            // getDeclaredMethods lists the bridge next to the method it calls
+            assert_eq!(11, call("declaredMethods"));
+        });
+    }
+
+    #[test]
+    fn reflection_modifiers() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Modifiers");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> JInt {
+                return vm
+                    .invoke_static("rsvm.Modifiers", method_name, "()I", ())
+                    .unwrap();
+            };
+            // member classes report the flags of their InnerClasses entries
+            assert_eq!(0x0002 | 0x0008 | 0x0010, call("hiddenModifiers"));
+            assert_eq!(0x0004 | 0x0008 | 0x0200 | 0x0400, call("shapeModifiers"));
+            assert_eq!(0, call("innerModifiers"));
+            // without ACC_SUPER
+            assert_eq!(0x0001, call("topLevelModifiers"));
+            assert_eq!(0x0002 | 0x0010 | 0x0400, call("arrayModifiers"));
+            assert_eq!(0x0001 | 0x0010 | 0x0400, call("primitiveModifiers"));
+            assert_eq!(0x0002 | 0x0008 | 0x0040 | 0x0080, call("fieldModifiers"));
+            assert_eq!(0x0001 | 0x0008, call("methodModifiers"));
+            assert_eq!(0x0001 | 0x0008, call("builtinModifiers"));
+            let is_native: bool = vm
+                .invoke_static("rsvm.Modifiers", "isNative", "()Z", ())
+                .unwrap();
+            assert!(is_native);
+        });
+    }
+
+    #[test]
+    fn primitive_classes() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Primitives");
+        test::run_in_vm("./tests/classes", |vm| {
+            let same_classes: bool = vm
+                .invoke_static("rsvm.Primitives", "sameClasses", "()Z", ())
+                .unwrap();
+            assert!(same_classes);
+            let reflected_types: JInt = vm
+                .invoke_static("rsvm.Primitives", "reflectedTypes", "()I", ())
+                .unwrap();
+            assert_eq!(11111, reflected_types);
+        });
+    }
+
+    #[test]
+    fn reflection_boxes() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Boxes");
+        test::run_in_vm("./tests/classes", |vm| {
+            let identities: JInt = vm
+                .invoke_static("rsvm.Boxes", "identities", "()I", ())
+                .unwrap();
+            assert_eq!(11111, identities);
+            let sum: JLong = vm.invoke_static("rsvm.Boxes", "sum", "()J", ()).unwrap();
+            assert_eq!(7, sum);
+            // boxes made for a call from Rust come from valueOf too
+            let class = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("java.lang.Integer")
+                .unwrap();
+            let value_of = vm
+                .get_static_method(
+                    class,
+                    "valueOf",
+                    "(I)Ljava/lang/Integer;",
+                    Thread::current(),
+                )
+                .unwrap();
+            let cached: ObjectPtr = vm
+                .call_static(class, value_of, &[JValue::with_int_val(7)])
+                .unwrap();
+            let same = |a: JInt, b: JInt| -> bool {
+                return vm
+                    .invoke_static(
+                        "rsvm.Boxes",
+                        "same",
+                        "(Ljava/lang/Object;Ljava/lang/Object;)Z",
+                        (a, b),
+                    )
+                    .unwrap();
+            };
+            assert!(same(7, 7));
+            assert!(!same(1000, 1000));
+            let boxed = vm
+                .shared_objs()
+                .class_infos()
+                .get_boxed_jvalue(
+                    JValue::with_int_val(7),
+                    vm.preloaded_classes().int_cls(),
+                    &vm,
+                )
+                .unwrap();
+            assert_eq!(cached, boxed);
+        });
+    }
+
+    #[test]
+    fn disassembly() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Disassembly");
+        test::run_in_vm("./tests/classes", |vm| {
+            let disassembly = vm.disassemble("rsvm.Disassembly").unwrap();
+            let lines: Vec<&str> = disassembly.lines().collect();
+            let contains = |expected: &[&str]| -> bool {
+                return lines
+                    .windows(expected.len())
+                    .any(|window| window == expected);
+            };
+            assert_eq!(Some(&"public class rsvm.Disassembly {"), lines.first());
+            assert!(contains(&[
+                "  public static int add(int, int);",
+                "    Code:",
+                "       0: iload_0",
+                "       1: iload_1",
+                "       2: iadd",
+                "       3: ireturn",
+            ]));
+            assert!(contains(&[
+                "       1: invokespecial #1                  // Method java/lang/Object.\"<init>\":()V",
+            ]));
+            assert!(contains(&[
+                "       1: tableswitch   { // 0 to 2",
+                "                     0: 28",
+                "                     1: 31",
+                "                     2: 34",
+                "               default: 37",
+                "          }",
+                "      28: ldc           #7                  // String zero",
+            ]));
+            assert!(contains(&[
+                "       1: lookupswitch  { // 2",
+                "                     1: 28",
+                "                  1000: 31",
+                "               default: 34",
+                "          }",
+                "      28: bipush        10",
+            ]));
+            assert!(contains(&[
+                "      10: getfield      #15                 // Field total:J",
+            ]));
+            assert!(contains(&[
+                "      17: ldc2_w        #21                 // long 300l",
+            ]));
+            assert!(contains(&[
+                "      25: iinc          2, 1",
+                "      28: goto          2"
+            ]));
+            assert!(contains(&[
+                "    Exception table:",
+                "       from    to  target type",
+                "           0     4     5   Class java/lang/NumberFormatException",
+            ]));
+            assert!(contains(&["  static native void nothing();", "}"]));
+        });
+    }
+
+    #[test]
+    fn class_initialization_order() {
+        test::ensure_class_exists("./tests/classes", "rsvm.InitOrder");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.InitOrder", method_name, "()J", ())
+                    .unwrap();
+            };
+            // superclasses first, each once
+            assert_eq!(123, call("initThird"));
+            // First is initialized already, Marker declares no default method
+            assert_eq!(4, call("initMarked"));
+        });
+    }
+
+    #[test]
+    fn class_initialization_triggers() {
+        test::ensure_class_exists("./tests/classes", "rsvm.InitTriggers");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.InitTriggers", method_name, "()J", ())
+                    .unwrap();
+            };
+            // only the declaring class of the resolved member is initialized
+            assert_eq!(1, call("invokeInherited"));
+            assert_eq!(3, call("getInherited"));
+            assert_eq!(5, call("putInherited"));
+            assert_eq!(7, call("getInterfaceField"));
+            assert_eq!(12, call("newInstance"));
+        });
+    }
+
+    #[test]
+    fn static_field_access() {
+        test::ensure_class_exists("./tests/classes", "rsvm.StaticAccess");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.StaticAccess", method_name, "()J", ())
+                    .unwrap();
+            };
+            assert_eq!(325001, call("loop"));
+            // the second run goes through the resolved fields
+            assert_eq!(350001, call("loop"));
+            assert_eq!(15, call("warmup"));
+            assert_eq!(20, call("warmup"));
+        });
+    }
+
+    #[test]
+    fn ldc_strings() {
+        test::ensure_class_exists("./tests/classes", "rsvm.StringSwitch");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.StringSwitch", method_name, "()J", ())
+                    .unwrap();
+            };
+            assert_eq!(24000, call("dispatch"));
+            // the second run reads the strings cached by the first
+            assert_eq!(24000, call("dispatch"));
+            assert_eq!(1, call("sameLiteral"));
+        });
+    }
+
+    #[test]
+    fn interface_fields() {
+        test::run_in_vm_and_call_static(
+            "./tests/classes",
+            "rsvm.InterfaceFields",
+            "readInheritedConstants",
+            "()I",
+            |_| vec![],
+            |_, result| {
+                assert_eq!(40, result.int_val());
+            },
+        );
+    }
+
+    #[test]
+    fn array_covariance() {
+        test::run_in_vm_and_call_static(
+            "./tests/classes",
+            "rsvm.Arrays",
+            "covariance",
+            "()I",
+            |_| vec![],
+            |_, result| {
+                assert_eq!(0b10001111, result.int_val());
+            },
+        );
+    }
+
+    #[test]
+    fn array_component_type() {
+        test::run_in_vm_and_call_static(
+            "./tests/classes",
+            "rsvm.Arrays",
+            "componentType",
+            "()I",
+            |_| vec![],
+            |_, result| {
+                assert_eq!(0b1111, result.int_val());
+            },
+        );
+    }
+
+    #[test]
+    fn empty_arrays_are_shared() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Arrays");
+        test::run_in_vm("./tests/classes", |vm| {
+            vm.as_mut_ref().cfg.share_empty_arrays = true;
+            let result: i32 = vm
+                .invoke_static("rsvm.Arrays", "emptyArrays", "()I", ())
+                .unwrap();
+            assert_eq!(0b11111, result);
+        });
+    }
+
+    #[test]
+    fn empty_arrays_are_distinct_by_default() {
+        assert!(!VMConfig::default().share_empty_arrays);
+        test::run_in_vm_and_call_static(
+            "./tests/classes",
+            "rsvm.Arrays",
+            "distinctEmptyArrays",
+            "()I",
+            |_| vec![],
+            |_, result| {
+                assert_eq!(0b111, result.int_val());
+            },
+        );
+    }
+
+    #[test]
+    fn array_interface_casts() {
+        test::run_in_vm_and_call_static(
+            "./tests/classes",
+            "rsvm.ArrayCasts",
+            "interfaceArrays",
+            "()I",
+            |_| vec![],
+            |_, result| {
+                assert_eq!(0b111110101, result.int_val());
+            },
+        );
+    }
+
+    #[test]
+    fn array_assignability() {
+        test::ensure_class_exists("./tests/classes", "rsvm.ArrayCasts");
+        test::run_in_vm("./tests/classes", |vm| {
+            let cases = [
+                ("[Ljava/lang/Object;", "[Ljava/lang/String;", true),
+                ("[Ljava/lang/String;", "[Ljava/lang/Object;", false),
+                ("java/lang/Object", "[I", true),
+                ("java/lang/Cloneable", "[I", true),
+                ("java/io/Serializable", "[[Ljava/lang/String;", true),
+                ("java/lang/Runnable", "[I", false),
+                ("java/lang/Number", "[I", false),
+                ("[I", "[I", true),
+                ("[J", "[I", false),
+                ("[Ljava/lang/Object;", "[I", false),
+                ("[Ljava/lang/Object;", "[[I", true),
+                ("[Ljava/lang/Cloneable;", "[[I", true),
+                ("[[Ljava/lang/Object;", "[[[I", true),
+                ("[[Ljava/lang/Object;", "[[I", false),
+                ("[Lrsvm/ArrayCasts$Shape;", "[Lrsvm/ArrayCasts$Tile;", true),
+                (
+                    "[Lrsvm/ArrayCasts$Shape;",
+                    "[Lrsvm/ArrayCasts$Polygon;",
+                    true,
+                ),
+                (
+                    "[Lrsvm/ArrayCasts$Square;",
+                    "[Lrsvm/ArrayCasts$Polygon;",
+                    false,
+                ),
+                ("[Ljava/lang/Object;", "[Lrsvm/ArrayCasts$Shape;", true),
+                ("rsvm/ArrayCasts$Shape", "rsvm/ArrayCasts$Tile", true),
+                ("java/lang/Object", "rsvm/ArrayCasts$Shape", true),
+                ("rsvm/ArrayCasts$Square", "rsvm/ArrayCasts$Shape", false),
+            ];
+            for (class_name, target_name, expected) in cases {
+                let class = vm.bootstrap_class_loader.load_class(class_name).unwrap();
+                let target = vm.bootstrap_class_loader.load_class(target_name).unwrap();
+                assert_eq!(
+                    expected,
+                    class.is_assignable_from(target, vm),
+                    "{} is_assignable_from {}",
+                    class_name,
+                    target_name
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn subtype_cache() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let load = |name: &str| vm.bootstrap_class_loader.load_class(name).unwrap();
+            let array_list = load("java/util/ArrayList");
+            let list = load("java/util/List");
+            let random_access = load("java/util/RandomAccess");
+            let map = load("java/util/Map");
+            // the same answers with the cache holding the other supertype
+            for _ in 0..2 {
+                assert!(list.is_assignable_from(array_list, vm));
+                assert!(list == array_list.class_data().secondary_super_cache());
+                assert!(!map.is_assignable_from(array_list, vm));
+                assert!(list == array_list.class_data().secondary_super_cache());
+                assert!(random_access.is_assignable_from(array_list, vm));
+                assert!(random_access == array_list.class_data().secondary_super_cache());
+                assert!(!array_list.is_assignable_from(list, vm));
+            }
+        });
+    }
+
+    #[test]
+    fn array_store_checks() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let thread = Thread::current();
+            let new_array = |array_class_name: &str| {
+                let array_class = vm
+                    .bootstrap_class_loader
+                    .load_class(array_class_name)
+                    .unwrap();
+                return JArray::new(1, array_class, thread);
+            };
+            let integer_cls = vm
+                .bootstrap_class_loader
+                .load_class("java/lang/Integer")
+                .unwrap();
+            let string: ObjectPtr = vm.new_string("element").cast();
+            let integer = Object::new(integer_cls, thread);
+            let ints: ObjectPtr = vm.new_int_array(&[1]).cast();
+
+            let objects = new_array("[Ljava/lang/Object;");
+            assert!(objects.is_compatible(string, vm));
+            assert!(objects.is_compatible(ints, vm));
+            assert!(objects.is_compatible(ObjectPtr::null(), vm));
+            let strings = new_array("[Ljava/lang/String;");
+            assert!(strings.is_compatible(string, vm));
+            assert!(!strings.is_compatible(integer, vm));
+            assert!(!strings.is_compatible(ints, vm));
+            // checked by the supertypes, then by the cache of Integer
+            let numbers = new_array("[Ljava/lang/Number;");
+            for _ in 0..2 {
+                assert!(numbers.is_compatible(integer, vm));
+                assert!(!numbers.is_compatible(string, vm));
+            }
+            let comparables = new_array("[Ljava/lang/Comparable;");
+            assert!(comparables.is_compatible(integer, vm));
+            assert!(comparables.is_compatible(string, vm));
+            assert!(!comparables.is_compatible(ints, vm));
+        });
+    }
+
+    #[test]
+    fn execution_budget() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Runaway");
+        test::run_in_vm("./tests/classes", |vm| {
+            let add = vm.method("rsvm.Runaway", "add", "(II)I").unwrap();
+            let spin = vm.method("rsvm.Runaway", "spin", "()V").unwrap();
+            let count_locked = vm.method("rsvm.Runaway", "countLocked", "(I)I").unwrap();
+            let instructions = |instructions| ExecutionBudget {
+                instructions: Some(instructions),
+                time: None,
+            };
+            let cancelled = |result: Result<JValue, VMError>| match result {
+                Err(VMError::Cancelled(reason)) => reason,
+                result => panic!("not cancelled: {:?}", result.map(|val| val.int_val())),
+            };
+
+            // add runs 4 instructions
+            vm.set_execution_budget(instructions(4));
+            assert_eq!(3, add.invoke::<_, JInt>((1, 2)).unwrap());
+            assert_eq!(
+                CancelReason::InstructionBudget,
+                cancelled(add.call(&[JValue::with_int_val(1), JValue::with_int_val(2)]))
+            );
+            vm.set_execution_budget(instructions(3));
+            assert_eq!(
+                CancelReason::InstructionBudget,
+                cancelled(add.call(&[JValue::with_int_val(1), JValue::with_int_val(2)]))
+            );
+
+            // the frames and the monitor of the synchronized method unwind
+            vm.set_execution_budget(instructions(100_000));
+            assert_eq!(
+                CancelReason::InstructionBudget,
+                cancelled(count_locked.call(&[JValue::with_int_val(JInt::MAX)]))
+            );
+            assert!(Thread::current().interpreter().stack().frame().is_null());
+            let runaway: ObjectPtr = vm
+                .bootstrap_class_loader
+                .load_class("rsvm/Runaway")
+                .unwrap()
+                .cast();
+            assert!(vm.monitor_info(runaway).is_none());
+
+            vm.set_execution_budget(ExecutionBudget {
+                instructions: None,
+                time: Some(Duration::from_millis(50)),
+            });
+            assert_eq!(CancelReason::TimeBudget, cancelled(spin.call(&[])));
+
+            vm.set_execution_budget(ExecutionBudget::default());
+            assert_eq!(1000, count_locked.invoke::<_, JInt>((1000,)).unwrap());
+            let thread = Thread::current().as_usize();
+            let interrupter = std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                vm.interrupt_execution(ThreadPtr::from_usize(thread));
+            });
+            assert_eq!(CancelReason::Interrupted, cancelled(spin.call(&[])));
+            interrupter.join().unwrap();
+            assert_eq!(3, add.invoke::<_, JInt>((1, 2)).unwrap());
+        });
+    }
+
+    #[test]
+    fn sandbox_policy() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Sandboxed");
+        test::run_in_vm("./tests/classes", |vm| {
+            let mut dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+            dir.push("tests/classes/rsvm");
+            let allowed = dir.join("Sandboxed.java");
+            let denied = dir.join("Sandboxed.class");
+            let accesses = Arc::new(Mutex::new(Vec::new()));
+            let recorded = accesses.clone();
+            let readable = allowed.clone();
+            vm.as_mut_ref().cfg.sandbox = SandboxPolicy::default()
+                .with_max_threads(vm.thread_mgr.thread_count())
+                .with_access_filter(move |access| {
+                    recorded.lock().unwrap().push(access.clone());
+                    return *access
+                        == ResourceAccess::OpenFile {
+                            path: readable.clone(),
+                            write: false,
+                        };
+                });
+
+            let env = unsafe {
+                jni::JNIEnv::from_raw(Thread::current().jni_env_mut().as_handle() as _).unwrap()
+            };
+            let java_vm_ptr = env.get_java_vm().unwrap().get_java_vm_pointer() as usize;
+            std::thread::spawn(move || {
+                let java_vm =
+                    unsafe { jni::JavaVM::from_raw(java_vm_ptr as *mut jni::sys::JavaVM) }.unwrap();
+                assert!(java_vm.attach_current_thread().is_err());
+            })
+            .join()
+            .unwrap();
+
+            let first_byte = |path: &PathBuf| {
+                return vm.invoke_static::<_, JInt>(
+                    "rsvm.Sandboxed",
+                    "firstByte",
+                    "(Ljava/lang/String;)I",
+                    (path.display().to_string(),),
+                );
+            };
+            let read = |path: &PathBuf| ResourceAccess::OpenFile {
+                path: path.clone(),
+                write: false,
+            };
+            assert_eq!(JInt::from(b'p'), first_byte(&allowed).unwrap());
+            match first_byte(&denied) {
+                Err(VMError::Cancelled(CancelReason::AccessDenied(access))) => {
+                    assert_eq!(read(&denied), access)
+                }
+                result => panic!("not cancelled: {:?}", result),
+            }
+            assert_eq!(
+                vec![read(&allowed), read(&denied)],
+                *accesses.lock().unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn sandbox_heap_usage() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Sandboxed");
+        test::run_in_vm("./tests/classes", |vm| {
+            let used = vm.heap().heap_usage().used;
+            vm.as_mut_ref().cfg.sandbox = SandboxPolicy::default().with_max_heap_usage(used + MB);
+            let allocate = |size: JInt| {
+                return vm.invoke_static::<_, JInt>("rsvm.Sandboxed", "allocate", "(I)I", (size,));
+            };
+            assert_eq!(64 * 1024, allocate(64 * 1024).unwrap());
+            match allocate(2 * MB as JInt) {
+                Err(VMError::Cancelled(CancelReason::HeapLimit)) => {}
+                result => panic!("not cancelled: {:?}", result),
+            }
+        });
+    }
+
+    #[test]
+    fn class_names() {
+        use crate::classfile::class_loader::{array_class_name, internal_class_name};
+
+        let names = [
+            ("java.lang.String", "java/lang/String"),
+            ("java/lang/String", "java/lang/String"),
+            ("rsvm.ArrayCasts$Shape", "rsvm/ArrayCasts$Shape"),
+            ("[I", "[I"),
+            ("[[Ljava.lang.String;", "[[Ljava/lang/String;"),
+        ];
+        for (binary_name, internal_name) in names {
+            assert_eq!(internal_name, internal_class_name(binary_name).unwrap());
+        }
+        let too_deep = format!("{}I", "[".repeat(256));
+        let malformed = [
+            "",
+            "java..lang",
+            "java.lang.",
+            "java;lang",
+            "[",
+            "[X",
+            "[II",
+            "[L;",
+            "[Ljava.lang.String",
+            "[Ljava.lang.String;;",
+            "[L[I;",
+            too_deep.as_str(),
+        ];
+        for name in malformed {
+            assert!(
+                matches!(
+                    internal_class_name(name),
+                    Err(ClassLoadErr::InvalidClassName(_))
+                ),
+                "{:?}",
+                name
+            );
+        }
+        assert_eq!("[Ljava/lang/String;", array_class_name("java/lang/String"));
+        assert_eq!("[[I", array_class_name("[I"));
+
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("[[Ljava.lang.String;")
+                .unwrap();
+            assert_eq!("[[Ljava/lang/String;", class.name().as_str());
+            let err = vm
+                .bootstrap_class_loader
+                .load_class("[Ljava/lang/String")
+                .unwrap_err();
+            assert!(matches!(err, ClassLoadErr::InvalidClassName(_)));
+        });
+    }
+
+    #[test]
+    fn array_classes_on_demand() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let loader = &vm.bootstrap_class_loader;
+            let thread = Thread::current();
+            let int_cls = vm.preloaded_classes().int_cls();
+            let int_arr_cls = loader.get_or_create_array_class(int_cls, 1, thread);
+            assert_eq!(vm.preloaded_classes().int_arr_cls(), int_arr_cls.unwrap());
+            let int_arr_arr_cls = loader
+                .get_or_create_array_class(int_cls, 2, thread)
+                .unwrap();
+            assert_eq!("[[I", int_arr_arr_cls.name().as_str());
+            assert_eq!(int_arr_arr_cls, loader.load_class("[[I").unwrap());
+
+            let string_cls = loader.load_class("java/lang/String").unwrap();
+            let created = loader
+                .get_or_create_array_class(string_cls, 3, thread)
+                .unwrap();
+            assert_eq!("[[[Ljava/lang/String;", created.name().as_str());
+            assert!(created.class_data().is_array());
+            assert_eq!(
+                loader.load_class("[[Ljava/lang/String;").unwrap(),
+                created.class_data().component_type()
+            );
+            assert_eq!(created, loader.load_class("[[[Ljava/lang/String;").unwrap());
+            assert_eq!(
+                created,
+                loader
+                    .get_or_create_array_class(string_cls, 3, thread)
+                    .unwrap()
+            );
+            assert_eq!(
+                string_cls,
+                loader
+                    .get_or_create_array_class(string_cls, 0, thread)
+                    .unwrap()
+            );
+
+            let void_cls = vm.preloaded_classes().void_cls();
+            assert!(matches!(
+                loader.get_or_create_array_class(void_cls, 1, thread),
+                Err(ClassLoadErr::InvalidClassName(_))
+            ));
+            let deepest = loader
+                .get_or_create_array_class(int_cls, 255, thread)
+                .unwrap();
+            assert!(matches!(
+                loader.get_or_create_array_class(deepest, 1, thread),
+                Err(ClassLoadErr::InvalidClassName(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn volatile_fields() {
+        test::run_in_vm_and_call_static(
+            "./tests/classes",
+            "rsvm.Volatiles",
+            "doubleCheckedLocking",
+            "()I",
+            |_| vec![],
+            |_, result| {
+                assert_eq!(30, result.int_val());
+            },
+        );
+    }
+
+    #[test]
+    fn volatile_long() {
+        test::run_in_vm_and_call_static(
+            "./tests/classes",
+            "rsvm.Volatiles",
+            "volatileLong",
+            "()J",
+            |_| vec![],
+            |_, result| {
+                assert_eq!(0x7ffffffe80000001, result.long_val());
+            },
+        );
+    }
+
+    #[test]
+    fn volatile_double() {
+        test::run_in_vm_and_call_static(
+            "./tests/classes",
+            "rsvm.Volatiles",
+            "volatileDouble",
+            "()D",
+            |_| vec![],
+            |_, result| {
+                assert_eq!(-f64::MAX / 2.0, result.double_val());
+            },
+        );
+    }
+
+    #[test]
+    fn unsafe_volatile_long() {
+        test::run_in_vm_and_call_static(
+            "./tests/classes",
+            "rsvm.Volatiles",
+            "unsafeVolatileLong",
+            "()J",
+            |_| vec![],
+            |_, result| {
+                assert_eq!(0x8000000180000001u64 as JLong, result.long_val());
+            },
+        );
+    }
+
+    #[test]
+    fn integer_bit_methods() {
+        test::ensure_class_exists("./tests/classes", "rsvm.BitOps");
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = "rsvm.BitOps";
+            for i in [0, 1, -1, 0x00f0_0000, i32::MIN, i32::MAX, 0x1234_5678] {
+                let call = |method_name: &str| -> JInt {
+                    return vm.invoke_static(class, method_name, "(I)I", (i,)).unwrap();
+                };
+                assert_eq!(i.leading_zeros() as JInt, call("intLeadingZeros"));
+                assert_eq!(i.trailing_zeros() as JInt, call("intTrailingZeros"));
+                assert_eq!(i.count_ones() as JInt, call("intBitCount"));
+                assert_eq!(i.swap_bytes(), call("intReverseBytes"));
+            }
+            for l in [
+                0,
+                1,
+                -1,
+                0x00f0_0000_0000,
+                i64::MIN,
+                i64::MAX,
+                0x1234_5678_9abc_def0,
+            ] {
+                let call = |method_name: &str| -> JInt {
+                    return vm.invoke_static(class, method_name, "(J)I", (l,)).unwrap();
+                };
+                assert_eq!(l.leading_zeros() as JInt, call("longLeadingZeros"));
+                assert_eq!(l.trailing_zeros() as JInt, call("longTrailingZeros"));
+                assert_eq!(l.count_ones() as JInt, call("longBitCount"));
+                let reversed: JLong = vm
+                    .invoke_static(class, "longReverseBytes", "(J)J", (l,))
+                    .unwrap();
+                assert_eq!(l.swap_bytes(), reversed);
+                let hex: String = vm
+                    .invoke_static(class, "hex", "(J)Ljava/lang/String;", (l,))
+                    .unwrap();
+                assert_eq!(format!("{:x}", l), hex);
+            }
+        });
+    }
+
+    #[test]
+    fn float_constants() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |name: &str| call_constants(vm, name, "()F").float_val();
+            assert_eq!(call("floatNaN").to_bits(), f32::NAN.to_bits());
+            assert_eq!(call("floatPositiveInfinity"), f32::INFINITY);
+            assert_eq!(call("floatNegativeInfinity"), f32::NEG_INFINITY);
+            assert_eq!(call("floatMinValue").to_bits(), 1);
+            assert_eq!(call("floatPi"), std::f32::consts::PI);
+        });
+    }
+
+    #[test]
+    fn double_constants() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |name: &str| call_constants(vm, name, "()D").double_val();
+            assert_eq!(call("doubleNaN").to_bits(), f64::NAN.to_bits());
+            assert_eq!(call("doublePositiveInfinity"), f64::INFINITY);
+            assert_eq!(call("doubleNegativeInfinity"), f64::NEG_INFINITY);
+            assert_eq!(call("doubleMinValue").to_bits(), 1);
+            assert_eq!(call("doubleMaxValue"), f64::MAX);
+            assert_eq!(call("doublePi"), std::f64::consts::PI);
+        });
+    }
+
+    #[test]
+    fn float_conversions() {
+        test::ensure_class_exists("./tests/classes", "rsvm.FloatConversions");
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = "rsvm.FloatConversions";
+            let f2i = |value: f32| -> JInt {
+                return vm.invoke_static(class, "f2i", "(F)I", (value,)).unwrap();
+            };
+            let f2l = |value: f32| -> JLong {
+                return vm.invoke_static(class, "f2l", "(F)J", (value,)).unwrap();
+            };
+            let d2i = |value: f64| -> JInt {
+                return vm.invoke_static(class, "d2i", "(D)I", (value,)).unwrap();
+            };
+            let d2l = |value: f64| -> JLong {
+                return vm.invoke_static(class, "d2l", "(D)J", (value,)).unwrap();
+            };
+            let d2f = |value: f64| -> f32 {
+                return vm.invoke_static(class, "d2f", "(D)F", (value,)).unwrap();
+            };
+
+            // NaN converts to 0, out of range values to the nearest bound and
+            // the rest round toward zero
+            assert_eq!(0, f2i(f32::NAN));
+            assert_eq!(JInt::MAX, f2i(f32::INFINITY));
+            assert_eq!(JInt::MIN, f2i(f32::NEG_INFINITY));
+            assert_eq!(JInt::MAX, f2i(3e10));
+            assert_eq!(-2, f2i(-2.9));
+            assert_eq!(0, f2l(f32::NAN));
+            assert_eq!(JLong::MAX, f2l(f32::INFINITY));
+            assert_eq!(JLong::MIN, f2l(-1e20));
+            assert_eq!(16777216, f2l(16777216.0));
+            assert_eq!(0, d2i(f64::NAN));
+            assert_eq!(JInt::MIN, d2i(-3e10));
+            assert_eq!(JInt::MAX, d2i(2147483647.9));
+            assert_eq!(0, d2i(-0.5));
+            assert_eq!(0, d2l(f64::NAN));
+            assert_eq!(JLong::MAX, d2l(f64::INFINITY));
+            assert_eq!(JLong::MIN, d2l(f64::NEG_INFINITY));
+            assert_eq!(1_000_000_000_000_000_000, d2l(1e18));
+            // at the bounds, where the maximum of the integer rounds up to a
+            // power of two as a float
+            assert_eq!(2147483520, f2i(2147483520.0));
+            assert_eq!(JInt::MAX, f2i(2147483648.0));
+            assert_eq!(JInt::MIN, f2i(-2147483648.0));
+            assert_eq!(JInt::MAX, d2i(2147483647.0));
+            assert_eq!(JInt::MIN + 1, d2i(-2147483647.5));
+            assert_eq!(JInt::MIN, d2i(-2147483649.0));
+            assert_eq!(9223371487098961920, f2l(9223371487098961920.0));
+            assert_eq!(JLong::MAX, f2l(9223372036854775808.0));
+            assert_eq!(9223372036854774784, d2l(9223372036854774784.0));
+            assert_eq!(JLong::MAX, d2l(9223372036854775808.0));
+            assert_eq!(JLong::MIN, d2l(-9223372036854775808.0));
+            assert_eq!(0, f2i(f32::MIN_POSITIVE));
+            assert_eq!(0, d2l(-0.0));
+
+            assert_eq!(f32::INFINITY, d2f(1e300));
+            assert_eq!((-0.0f32).to_bits(), d2f(-1e-300).to_bits());
+            assert!(d2f(f64::NAN).is_nan());
+
+            // the raw bits keep the payload of a NaN, the others canonicalize it
+            let nan = f32::from_bits(0x7fc0_0001);
+            let raw: JInt = vm
+                .invoke_static(class, "floatToRawIntBits", "(F)I", (nan,))
+                .unwrap();
+            assert_eq!(0x7fc0_0001, raw);
+            let bits: JInt = vm
+                .invoke_static(class, "floatToIntBits", "(F)I", (nan,))
+                .unwrap();
+            assert_eq!(0x7fc0_0000, bits);
+            let value: f32 = vm
+                .invoke_static(class, "intBitsToFloat", "(I)F", (0x7fc0_0001,))
+                .unwrap();
+            assert_eq!(0x7fc0_0001, value.to_bits());
+
+            let nan = f64::from_bits(0x7ff8_0000_0000_0001);
+            let raw: JLong = vm
+                .invoke_static(class, "doubleToRawLongBits", "(D)J", (nan,))
+                .unwrap();
+            assert_eq!(0x7ff8_0000_0000_0001, raw);
+            let bits: JLong = vm
+                .invoke_static(class, "doubleToLongBits", "(D)J", (nan,))
+                .unwrap();
+            assert_eq!(0x7ff8_0000_0000_0000, bits);
+            let value: f64 = vm
+                .invoke_static(class, "longBitsToDouble", "(J)D", (-1i64,))
+                .unwrap();
+            assert_eq!(u64::MAX, value.to_bits());
+        });
+    }
+
+    #[test]
+    fn increments_and_backward_branches() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Increments");
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = "rsvm.Increments";
+            let call = |method_name: &str| -> JInt {
+                return vm.invoke_static(class, method_name, "()I", ()).unwrap();
+            };
+            let steps: JInt = vm
+                .invoke_static(class, "countDown", "(I)I", (300,))
+                .unwrap();
+            assert_eq!(300, steps);
+            assert_eq!(-133, call("negativeIncrements"));
+            assert_eq!(-28800, call("wideIncrements"));
+            assert_eq!(JInt::MIN, call("overflowingIncrement"));
+            let values = vm.new_int_array(&[1, 2, 3, 4, 5]);
+            let sum: JInt = vm
+                .invoke_static(class, "sumBackward", "([I)I", (values.cast::<Object>(),))
+                .unwrap();
+            assert_eq!(9, sum);
+            let wide: JLong = vm.invoke_static(class, "wideLocals", "()J", ()).unwrap();
+            assert_eq!(-2, wide);
+        });
+    }
+
+    #[test]
+    fn long_and_double_locals() {
+        test::ensure_class_exists("./tests/classes", "rsvm.LongLocals");
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = "rsvm.LongLocals";
+            let after_double: JLong = vm.invoke_static(class, "afterDouble", "()J", ()).unwrap();
+            assert_eq!(64, after_double);
+            let interleaved: JLong = vm
+                .invoke_static(
+                    class,
+                    "interleaved",
+                    "(IJIDJ)J",
+                    (3, 10_000_000_000 as JLong, 4, 1.5, -2 as JLong),
+                )
+                .unwrap();
+            assert_eq!(10_000_000_011, interleaved);
+            let reused: JInt = vm
+                .invoke_static(class, "reusedSlots", "(I)I", (4,))
+                .unwrap();
+            assert_eq!(23, reused);
+            let high_halves: JLong = vm.invoke_static(class, "highHalves", "()J", ()).unwrap();
+            assert_eq!(-1, high_halves);
+        });
+    }
+
+    #[cfg(feature = "interpreter-stats")]
+    #[test]
+    fn interpreter_stats() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Increments");
+        test::run_in_vm("./tests/classes", |vm| {
+            let steps: JInt = vm
+                .invoke_static("rsvm.Increments", "countDown", "(I)I", (300,))
+                .unwrap();
+            assert_eq!(300, steps);
+            let stats = vm.interpreter_stats();
+            let count_down = stats
+                .iter()
+                .find(|method| {
+                    method.class_name == "rsvm.Increments" && method.method_name == "countDown"
+                })
+                .unwrap();
+            assert_eq!("(I)I", count_down.descriptor);
+            // the loop condition falls through 300 times and jumps out once
+            assert_eq!(1, count_down.branches_taken);
+            assert_eq!(300, count_down.branches_not_taken);
+            let count = |op: &str| {
+                count_down
+                    .op_counts
+                    .iter()
+                    .find(|(name, _)| name == op)
+                    .map(|(_, count)| *count)
+            };
+            assert_eq!(Some(600), count("iinc"));
+            assert_eq!(Some(1), count("ireturn"));
+            assert_eq!(
+                count_down.total_ops(),
+                count_down.op_counts.iter().map(|(_, count)| count).sum()
+            );
+
+            let mut out = Vec::new();
+            vm.print_interpreter_stats(&mut out).unwrap();
+            let out = String::from_utf8(out).unwrap();
+            assert!(out.starts_with("opcode totals:\n"));
+            assert!(out.contains("rsvm.Increments#countDown(I)I: "));
+        });
+    }
+
+    #[test]
+    fn integer_division() {
+        test::ensure_class_exists("./tests/classes", "rsvm.IntDivision");
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = "rsvm.IntDivision";
+            let int_call = |method_name: &str, dividend: JInt, divisor: JInt| -> JInt {
+                return vm
+                    .invoke_static(class, method_name, "(II)I", (dividend, divisor))
+                    .unwrap();
+            };
+            let long_call = |method_name: &str, dividend: JLong, divisor: JLong| -> JLong {
+                return vm
+                    .invoke_static(class, method_name, "(JJ)J", (dividend, divisor))
+                    .unwrap();
+            };
+            // the only overflow, the quotient wraps to the minimum
+            assert_eq!(JInt::MIN, int_call("idiv", JInt::MIN, -1));
+            assert_eq!(0, int_call("irem", JInt::MIN, -1));
+            assert_eq!(JLong::MIN, long_call("ldiv", JLong::MIN, -1));
+            assert_eq!(0, long_call("lrem", JLong::MIN, -1));
+
+            assert_eq!(-3, int_call("idiv", -7, 2));
+            assert_eq!(-1, int_call("irem", -7, 2));
+            assert_eq!(1, int_call("irem", 7, -2));
+            assert_eq!(-3, long_call("ldiv", 7, -2));
+            assert_eq!(-1, long_call("lrem", -7, 2));
+        });
+    }
+
+    #[test]
+    fn float_remainders() {
+        test::ensure_class_exists("./tests/classes", "rsvm.FloatRemainders");
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = "rsvm.FloatRemainders";
+            let inf = f64::INFINITY;
+            let nan = f64::NAN;
+            // dividend, divisor and the remainder from the JVMS
+            let remainders = [
+                (5.5, 2.0, 1.5),
+                (-5.5, 2.0, -1.5),
+                (5.5, -2.0, 1.5),
+                (-5.5, -2.0, -1.5),
+                (1.0, 0.0, nan),
+                (-1.0, -0.0, nan),
+                (inf, 2.0, nan),
+                (-inf, inf, nan),
+                (nan, 1.0, nan),
+                (1.0, nan, nan),
+                (3.0, inf, 3.0),
+                (-3.0, -inf, -3.0),
+                (0.0, 2.0, 0.0),
+                (-0.0, 2.0, -0.0),
+                (-4.0, 2.0, -0.0),
+                (1e17, 7.0, 5.0),
+            ];
+            for (dividend, divisor, remainder) in remainders {
+                let drem: f64 = vm
+                    .invoke_static(class, "drem", "(DD)D", (dividend, divisor))
+                    .unwrap();
+                let frem: f32 = vm
+                    .invoke_static(class, "frem", "(FF)F", (dividend as f32, divisor as f32))
+                    .unwrap();
+                if remainder.is_nan() {
+                    assert!(drem.is_nan(), "{} % {}", dividend, divisor);
+                    assert!(frem.is_nan(), "{} % {}", dividend, divisor);
+                } else {
+                    assert_eq!(
+                        remainder.to_bits(),
+                        drem.to_bits(),
+                        "{} % {}",
+                        dividend,
+                        divisor
+                    );
+                    let remainder = (dividend as f32) % (divisor as f32);
+                    assert_eq!(
+                        remainder.to_bits(),
+                        frem.to_bits(),
+                        "{} % {}",
+                        dividend,
+                        divisor
+                    );
+                }
+            }
+
+            // division by zero gives an infinity or NaN rather than throwing
+            let ddiv: f64 = vm
+                .invoke_static(class, "ddiv", "(DD)D", (-1.0, 0.0))
+                .unwrap();
+            assert_eq!(f64::NEG_INFINITY, ddiv);
+            let fdiv: f32 = vm
+                .invoke_static(class, "fdiv", "(FF)F", (0.0f32, 0.0f32))
+                .unwrap();
+            assert!(fdiv.is_nan());
+        });
+    }
+
+    #[test]
+    fn arithmetic_conformance() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Conformance");
+        let vectors = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/conformance/arithmetic.json"
+        ))
+        .unwrap();
+        let vectors: Vec<serde_json::Value> = serde_json::from_str(&vectors).unwrap();
+        test::run_in_vm("./tests/classes", move |vm| {
+            let thread = Thread::current();
+            let class = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("rsvm.Conformance")
+                .unwrap();
+            // the vectors hold floats and doubles as their raw bits
+            let to_jvalue = |ty: u8, value: &serde_json::Value| -> JValue {
+                let value = value.as_i64().unwrap();
+                return match ty {
+                    b'I' => JValue::with_int_val(value as JInt),
+                    b'J' => JValue::with_long_val(value),
+                    b'F' => JValue::with_float_val(f32::from_bits(value as u32)),
+                    b'D' => JValue::with_double_val(f64::from_bits(value as u64)),
+                    _ => unreachable!("{}", ty as char),
+                };
+            };
+            for vector in &vectors {
+                let method_name = vector["method"].as_str().unwrap();
+                let descriptor = vector["descriptor"].as_str().unwrap();
+                let (params, ret) = descriptor[1..].split_once(')').unwrap();
+                let args = params
+                    .bytes()
+                    .zip(vector["args"].as_array().unwrap())
+                    .map(|(ty, arg)| to_jvalue(ty, arg))
+                    .collect::<Vec<_>>();
+                let method = vm
+                    .get_static_method(class, method_name, descriptor, thread)
+                    .unwrap();
+                let result: JValue = vm.call_static(class, method, &args).unwrap();
+                let expected = to_jvalue(ret.as_bytes()[0], &vector["result"]);
+                // the JVMS leaves the bits of NaNs open
+                let (result, expected) = match ret {
+                    "I" => (result.int_val() as i64, expected.int_val() as i64),
+                    "J" => (result.long_val(), expected.long_val()),
+                    "F" if expected.float_val().is_nan() => {
+                        assert!(result.float_val().is_nan(), "{}", vector);
+                        continue;
+                    }
+                    "F" => (
+                        result.float_val().to_bits() as i64,
+                        expected.float_val().to_bits() as i64,
+                    ),
+                    "D" if expected.double_val().is_nan() => {
+                        assert!(result.double_val().is_nan(), "{}", vector);
+                        continue;
+                    }
+                    _ => (
+                        result.double_val().to_bits() as i64,
+                        expected.double_val().to_bits() as i64,
+                    ),
+                };
+                assert_eq!(expected, result, "{}", vector);
+            }
+        });
+    }
+
+    #[test]
+    fn long_constants() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = |name: &str| call_constants(vm, name, "()J").long_val();
+            assert_eq!(call("longLowBytesSigned"), 0x1234567880000000);
+            assert_eq!(call("longMinValue"), i64::MIN);
+        });
+    }
+
+    #[test]
+    fn constant_values() {
+        test::run_in_vm("./tests/classes", |vm| {
+            test::ensure_class_exists("./tests/classes", "rsvm.Constants");
+            let class = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("rsvm.Constants")
+                .unwrap();
+            class.initialize(Thread::current()).unwrap();
+            let field = |name: &str| class.get_field_with_name(vm.get_symbol(name)).unwrap().0;
+            assert_eq!(
+                i32::MAX,
+                field("INT_CONST").get_static_typed_value::<JInt>(class)
+            );
+            assert_eq!(
+                i64::MIN,
+                field("LONG_CONST").get_static_typed_value::<JLong>(class)
+            );
+            assert_eq!(
+                std::f64::consts::PI,
+                field("DOUBLE_CONST").get_static_typed_value::<JDouble>(class)
+            );
+            assert_eq!(
+                0x20ac,
+                field("CHAR_CONST").get_static_typed_value::<JChar>(class)
+            );
+            assert_eq!(
+                -2,
+                field("BYTE_CONST").get_static_typed_value::<JByte>(class)
+            );
+            let jstr: JStringPtr = field("STRING_CONST").get_static_typed_value(class);
+            assert_eq!("rsvm", JString::to_rust_string(jstr, vm.as_ref()));
+        });
+    }
+
+    #[test]
+    fn embedder_strings_and_arrays() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Embedding");
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("rsvm.Embedding")
+                .unwrap();
+            let thread = Thread::current();
+
+            let greet = vm
+                .get_static_method(
+                    class,
+                    "greet",
+                    "(Ljava/lang/String;)Ljava/lang/String;",
+                    thread,
+                )
+                .unwrap();
+            let name = vm.new_string("rsvm \u{4e16}\u{754c}");
+            let greeting: String = vm
+                .call_static(class, greet, &[JValue::with_obj_val(name.cast())])
+                .unwrap();
+            assert_eq!("Hello, rsvm \u{4e16}\u{754c}!", greeting);
+
+            let doubled = vm
+                .get_static_method(class, "doubled", "([I)[I", thread)
+                .unwrap();
+            let values = vm.new_int_array(&[1, -2, 3, i32::MAX / 2]);
+            let result: ObjectPtr = vm
+                .call_static(class, doubled, &[JValue::with_obj_val(values.cast())])
+                .unwrap();
+            assert_eq!(
+                vec![2, -4, 6, i32::MAX - 1],
+                vm.get_int_array(result.cast())
+            );
+
+            let sum = vm.get_static_method(class, "sum", "([D)D", thread).unwrap();
+            let values = vm.new_double_array(&[0.5, 1.25, -2.0]);
+            let result: JDouble = vm
+                .call_static(class, sum, &[JValue::with_obj_val(values.cast())])
+                .unwrap();
+            assert_eq!(-0.25, result);
+
+            assert_eq!(
+                vec![b'a' as JChar, 0x4e16],
+                vm.get_char_array(vm.new_char_array(&[b'a' as JChar, 0x4e16]))
+            );
+            assert_eq!(
+                Vec::<JLong>::new(),
+                vm.get_long_array(vm.new_long_array(&[]))
+            );
+        });
+    }
+
+    #[test]
+    fn invoke_static_conversions() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Embedding");
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = "rsvm.Embedding";
+            let repeated: String = vm
+                .invoke_static(
+                    class,
+                    "repeat",
+                    "(Ljava/lang/String;IC)Ljava/lang/String;",
+                    ("ab", 3, ','),
+                )
+                .unwrap();
+            assert_eq!("ab,ab,ab", repeated);
+
+            let scaled: f64 = vm
+                .invoke_static(class, "scale", "(IDZ)D", (3, 1.5, true))
+                .unwrap();
+            assert_eq!(-4.5, scaled);
+
+            let identity = "(Ljava/lang/Object;)Ljava/lang/Object;";
+            let boxed: i32 = vm
+                .invoke_static(class, "identity", identity, (42,))
+                .unwrap();
+            assert_eq!(42, boxed);
+            let boxed: i64 = vm
+                .invoke_static(class, "identity", identity, (-7i64,))
+                .unwrap();
+            assert_eq!(-7, boxed);
+            let boxed: bool = vm
+                .invoke_static(class, "identity", identity, (true,))
+                .unwrap();
+            assert!(boxed);
+            let boxed: char = vm
+                .invoke_static(class, "identity", identity, ('\u{e9}',))
+                .unwrap();
+            assert_eq!('\u{e9}', boxed);
+            let boxed: Option<String> = vm
+                .invoke_static(class, "identity", identity, (Some("s"),))
+                .unwrap();
+            assert_eq!(Some("s".to_string()), boxed);
+            let boxed: Option<String> = vm
+                .invoke_static(class, "identity", identity, (None::<String>,))
+                .unwrap();
+            assert_eq!(None, boxed);
+            assert!(vm
+                .invoke_static::<_, i64>(class, "identity", identity, (1,))
+                .is_err());
+
+            let sum: i32 = vm
+                .invoke_static(
+                    class,
+                    "boxedSum",
+                    "(Ljava/lang/Integer;Ljava/lang/Long;)Ljava/lang/Integer;",
+                    (40, 2i64),
+                )
+                .unwrap();
+            assert_eq!(42, sum);
+
+            let is_null: bool = vm
+                .invoke_static(class, "isNull", "(Ljava/lang/Object;)Z", (None::<i32>,))
+                .unwrap();
+            assert!(is_null);
+
+            let reversed: Vec<i64> = vm
+                .invoke_static(class, "reversed", "([J)[J", (vec![1i64, 2, i64::MIN],))
+                .unwrap();
+            assert_eq!(vec![i64::MIN, 2, 1], reversed);
+
+            assert!(vm
+                .invoke_static::<_, f64>(class, "scale", "(IDZ)D", (3, 1.5))
+                .is_err());
+            assert!(vm
+                .invoke_static::<_, f64>(class, "scale", "(IDZ)D", ("3", 1.5, true))
+                .is_err());
+        });
+    }
+
+    #[test]
+    fn static_method_builder() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Embedding");
+        test::run_in_vm("./tests/classes", |vm| {
+            let max = vm.method("java/lang/Math", "max", "(II)I").unwrap();
+            assert_eq!(&["I", "I"], max.param_types());
+            assert_eq!("I", max.return_type());
+            assert_eq!(2, max.call(&[1.into(), 2.into()]).unwrap().int_val());
+            assert_eq!(-1, max.call(&[(-1).into(), (-5).into()]).unwrap().int_val());
+            assert!(max.call(&[1.into()]).is_err());
+            assert!(max.call(&[1.into(), 2.into(), 3.into()]).is_err());
+
+            let max = vm.method("java.lang.Math", "max", "(JJ)J").unwrap();
+            let result = max.call(&[i64::MIN.into(), (1i64 << 40).into()]);
+            assert_eq!(1i64 << 40, result.unwrap().long_val());
+
+            let scale = vm.method("rsvm/Embedding", "scale", "(IDZ)D").unwrap();
+            assert_eq!(
+                7.5,
+                scale
+                    .call(&[5.into(), 1.5.into(), false.into()])
+                    .unwrap()
+                    .double_val()
+            );
+            assert_eq!(-3.0, scale.invoke::<_, f64>((2, 1.5, true)).unwrap());
+
+            assert!(vm.method("java/lang/Math", "max", "(II").is_err());
+            assert!(vm.method("java/lang/Math", "missing", "()V").is_err());
+        });
+    }
+
+    #[test]
+    fn rust_implemented_interfaces() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Callbacks");
+        test::run_in_vm("./tests/classes", |vm| {
+            let runs = Arc::new(AtomicI32::new(0));
+            let counter = runs.clone();
+            let runnable = vm
+                .implement("java/lang/Runnable", move |call| {
+                    assert_eq!("run", call.method_name());
+                    assert_eq!("()V", call.descriptor());
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    return JValue::default();
+                })
+                .unwrap();
+            let ran: i32 = vm
+                .invoke_static(
+                    "rsvm.Callbacks",
+                    "runTwice",
+                    "(Ljava/lang/Runnable;)I",
+                    (runnable,),
+                )
+                .unwrap();
+            assert_eq!(2, ran);
+            assert_eq!(2, runs.load(Ordering::SeqCst));
+
+            let listener = vm
+                .implement("rsvm.Callbacks$Listener", |call| {
+                    let name: String = call.arg(0).unwrap();
+                    let count: i32 = call.arg(1).unwrap();
+                    return JValue::from(name.len() as i32 * count);
+                })
+                .unwrap();
+            let fired: i32 = vm
+                .invoke_static(
+                    "rsvm.Callbacks",
+                    "fire",
+                    "(Lrsvm/Callbacks$Listener;)I",
+                    (listener,),
+                )
+                .unwrap();
+            assert_eq!(5 * 3 + 3 * 4, fired);
+
+            let named = vm
+                .implement("rsvm/Callbacks$NamedListener", |call| {
+                    return match call.method_name() {
+                        "name" => JValue::with_obj_val(call.vm().new_string("rust").cast()),
+                        "onEvent" => JValue::from(call.args().len() as i32),
+                        "weigh" => {
+                            let amount: i64 = call.arg(0).unwrap();
+                            let factor: f64 = call.arg(1).unwrap();
+                            JValue::from(amount as f64 * factor)
+                        }
+                        name => panic!("unexpected method {}", name),
+                    };
+                })
+                .unwrap();
+            let described: String = vm
+                .invoke_static(
+                    "rsvm.Callbacks",
+                    "describe",
+                    "(Lrsvm/Callbacks$NamedListener;)Ljava/lang/String;",
+                    (named,),
+                )
+                .unwrap();
+            assert_eq!("rust:2:true", described);
+
+            assert!(vm
+                .implement("java/lang/Object", |_| JValue::default())
+                .is_err());
+        });
+    }
+
+    #[test]
+    fn field_access() {
+        test::ensure_class_exists("./tests/classes", "rsvm.FieldAccess");
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = "rsvm/FieldAccess";
+            assert_eq!(
+                i32::MAX,
+                vm.get_static::<i32>("java/lang/Integer", "MAX_VALUE")
+                    .unwrap()
+            );
+            assert_eq!(7, vm.get_static::<i32>(class, "counter").unwrap());
+            vm.set_static(class, "counter", 35).unwrap();
+            let sum: i32 = vm
+                .invoke_static(class, "counterPlus", "(I)I", (7,))
+                .unwrap();
+            assert_eq!(42, sum);
+            assert_eq!("initial", vm.get_static::<String>(class, "label").unwrap());
+            vm.set_static(class, "label", "changed").unwrap();
+            assert_eq!(
+                Some("changed".to_string()),
+                vm.get_static::<Option<String>>(class, "label").unwrap()
+            );
+            vm.set_static(class, "ticks", i64::MIN + 1).unwrap();
+            assert_eq!(i64::MIN + 1, vm.get_static::<i64>(class, "ticks").unwrap());
+
+            let obj: ObjectPtr = vm
+                .invoke_static(class, "create", "()Lrsvm/FieldAccess;", ())
+                .unwrap();
+            assert!(!vm.get_field::<bool>(obj, "enabled").unwrap());
+            assert_eq!('r', vm.get_field::<char>(obj, "initial").unwrap());
+            assert_eq!(-3i16, vm.get_field::<i16>(obj, "small").unwrap());
+            assert_eq!(1i64 << 50, vm.get_field::<i64>(obj, "big").unwrap());
+            assert_eq!(0.25, vm.get_field::<f64>(obj, "ratio").unwrap());
+            assert_eq!(
+                None,
+                vm.get_field::<Option<String>>(obj, "payload").unwrap()
+            );
+
+            vm.set_field(obj, "enabled", true).unwrap();
+            vm.set_field(obj, "initial", 'x').unwrap();
+            vm.set_field(obj, "small", 12i16).unwrap();
+            vm.set_field(obj, "payload", 5).unwrap();
+            let described: String = vm
+                .invoke_static(
+                    class,
+                    "describe",
+                    "(Lrsvm/FieldAccess;)Ljava/lang/String;",
+                    (obj,),
+                )
+                .unwrap();
+            assert_eq!("true:x:12:5", described);
+            assert_eq!(5, vm.get_field::<i32>(obj, "payload").unwrap());
+
+            assert!(vm.get_static::<i64>(class, "counter").is_err());
+            assert!(vm.set_field(obj, "ratio", "text").is_err());
+            assert!(vm.get_static::<i32>(class, "missing").is_err());
+            assert!(vm.get_static::<i32>(class, "big").is_err());
+            assert!(vm.get_field::<i32>(obj, "counter").is_err());
+            assert!(vm.get_field::<i32>(ObjectPtr::null(), "big").is_err());
+
+            let field_access_cls = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("rsvm.FieldAccess")
+                .unwrap();
+            assert!(matches!(
+                field_access_cls.get_field_with_name(vm.get_symbol("missing")),
+                Err(FieldResolutionError::NoSuchField(name)) if name == "missing"
+            ));
+        });
+    }
+
+    #[test]
+    fn field_kinds() {
+        test::ensure_class_exists("./tests/classes", "rsvm.FieldKinds");
+        test::run_in_vm("./tests/classes", |vm| {
+            let class = "rsvm/FieldKinds";
+            let call = |method_name: &str| -> JLong {
+                return vm.invoke_static(class, method_name, "()J", ()).unwrap();
+            };
+            // getfield and getstatic widen each type as the JVM does
+            assert_eq!(5, call("booleans"));
+            assert_eq!(-128, call("bytes"));
+            assert_eq!(0xfedc + 0x8001, call("chars"));
+            assert_eq!(-32000, call("shorts"));
+            assert_eq!(i32::MIN as JLong - 8, call("ints"));
+            assert_eq!(-5, call("floats"));
+            assert_eq!(i64::MIN + 1, call("longs"));
+            assert_eq!(-7, call("doubles"));
+            assert_eq!(12, call("references"));
+
+            let obj: ObjectPtr = vm
+                .invoke_static(class, "create", "()Lrsvm/FieldKinds;", ())
+                .unwrap();
+            vm.set_field(obj, "z", true).unwrap();
+            vm.set_field(obj, "b", i8::MIN).unwrap();
+            vm.set_field(obj, "c", '\u{fedc}').unwrap();
+            vm.set_field(obj, "s", i16::MIN).unwrap();
+            vm.set_static(class, "sb", -1i8).unwrap();
+            vm.set_static(class, "ss", -2i16).unwrap();
+            assert!(vm.get_field::<bool>(obj, "z").unwrap());
+            assert_eq!(i8::MIN, vm.get_field::<i8>(obj, "b").unwrap());
+            assert_eq!('\u{fedc}', vm.get_field::<char>(obj, "c").unwrap());
+            assert_eq!(i16::MIN, vm.get_field::<i16>(obj, "s").unwrap());
+            assert_eq!(-1, vm.get_static::<i8>(class, "sb").unwrap());
+            let described: String = vm
+                .invoke_static(
+                    class,
+                    "describe",
+                    "(Lrsvm/FieldKinds;)Ljava/lang/String;",
+                    (obj,),
+                )
+                .unwrap();
+            assert_eq!("true:-128:65244:-32768:-1:-2", described);
+        });
+    }
+
+    #[test]
+    fn symbol_interning() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let symbol = vm.get_symbol("a\0b\u{e9}\u{1f600}");
+            // the same text in modified UTF-8, NUL and the surrogate pair spelled out
+            let modified_utf8 = b"a\xc0\x80b\xc3\xa9\xed\xa0\xbd\xed\xb8\x80";
+            assert_eq!(symbol, vm.symbol_table.intern_bytes(modified_utf8).unwrap());
+            assert_eq!(
+                symbol,
+                vm.symbol_table.intern_bytes(symbol.as_bytes()).unwrap()
+            );
+            assert!(vm.symbol_table.intern_bytes(b"\xed\xa0\xbd").is_err());
+
+            assert_eq!(9, symbol.length());
+            assert_eq!(6, symbol.utf16_length());
+            assert_eq!(modified_utf8.len() as JInt, symbol.modified_utf8_length());
+
+            let jstr = vm.get_jstr_from_symbol(symbol, Thread::current());
+            assert_eq!(symbol.hash_code(), jstr.cast::<Object>().hash());
+            assert_eq!(symbol, vm.get_symbol_with_jstr(jstr));
+        });
+    }
+
+    #[test]
+    fn symbol_ids() {
+        test::ensure_class_exists("./tests/classes", "rsvm.FieldKinds");
+        test::run_in_vm("./tests/classes", |vm| {
+            let name = vm.get_symbol("symbolIdsName");
+            let descriptor = vm.get_symbol("symbolIdsDescriptor");
+            let id = vm.symbol_table.id(name);
+            assert_eq!(id, vm.symbol_table.id(name));
+            assert_ne!(id, vm.symbol_table.id(descriptor));
+            assert_eq!(name, vm.symbol_table.symbol(id));
+            assert_eq!(
+                descriptor,
+                vm.symbol_table.symbol(vm.symbol_table.id(descriptor))
+            );
+
+            let class = vm
+                .bootstrap_class_loader
+                .load_class("rsvm/FieldKinds")
+                .unwrap();
+            // a field finds itself by the name its id decodes to
+            let field: FieldPtr = class.class_data().fields().get(0).cast();
+            let (found, _) = class.get_field_with_name(field.name(&vm)).unwrap();
+            assert_eq!(field, found);
+        });
+    }
+
+    #[test]
+    fn class_load_stats() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Constants");
+        test::run_in_vm("./tests/classes", |vm| {
+            let loaded = vm.stats().classes_loaded;
+            assert!(loaded > 0);
+            assert!(vm.stats().class_loads.is_empty());
+
+            vm.as_mut_ref().cfg.record_class_loads = true;
+            vm.bootstrap_class_loader
+                .load_binary_name_class("rsvm.Constants")
+                .unwrap();
+            let stats = vm.stats();
+            assert_eq!(loaded + 1, stats.classes_loaded);
+            assert_eq!(1, stats.class_loads.len());
+            let record = &stats.class_loads[0];
+            assert_eq!("rsvm.Constants", record.name);
+            assert_eq!("bootstrap", record.loader);
+            assert!(record
+                .source
+                .as_deref()
+                .unwrap()
+                .starts_with("./tests/classes"));
+            assert!(stats.class_load_time >= record.elapsed);
+        });
+    }
+
+    #[test]
+    fn loaded_classes() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Constants");
+        test::run_in_vm("./tests/classes", |vm| {
+            let names = |classes: Vec<JClassPtr>| -> Vec<String> {
+                return classes
+                    .into_iter()
+                    .map(|class| class.name().as_str().to_string())
+                    .collect();
+            };
+            let before = names(vm.loaded_classes());
+            assert!(before.windows(2).all(|w| w[0] < w[1]));
+            assert!(before.iter().any(|name| name == "java/lang/Object"));
+            assert!(!before.iter().any(|name| name == "rsvm/Constants"));
+
+            vm.bootstrap_class_loader
+                .load_binary_name_class("rsvm.Constants")
+                .unwrap();
+            let after = names(vm.loaded_classes());
+            assert_eq!(before.len() + 1, after.len());
+            assert!(after.iter().any(|name| name == "rsvm/Constants"));
+            assert_eq!(after, names(vm.loaded_classes_of(ObjectPtr::null())));
+        });
+    }
+
+    #[test]
+    fn crash_report() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Recursion");
+        test::run_in_vm("./tests/classes", |vm| {
+            let depth: i32 = vm
+                .invoke_static("rsvm.Recursion", "depth", "(I)I", (3,))
+                .unwrap();
+            assert_eq!(3, depth);
+
+            let mut report = Vec::new();
+            crate::crash_report::write_crash_report(
+                Thread::current(),
+                "not yet implemented: throw Error\nat Foo.java:1",
+                &mut report,
+            )
+            .unwrap();
+            let report = String::from_utf8(report).unwrap();
+            assert!(report.contains("#  not yet implemented: throw Error\n#  at Foo.java:1\n"));
+            assert!(report.contains(&format!("tid={}", Thread::current().thread_id())));
+            assert!(report.contains("Last calls (oldest first):\n"));
+            assert!(report.contains("   rsvm.Recursion.depth(I)I\n"));
+            assert!(report.contains("CreateCrashReports"));
+            assert!(report.contains("Loaded classes: "));
+            assert!(report.contains(" Loaded rsvm.Recursion from "));
+        });
+    }
+
+    #[test]
+    fn concurrent_class_loading() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Volatiles");
+        test::run_in_vm("./tests/classes", |vm| {
+            let classes_loaded = vm.stats().classes_loaded;
+            let barrier = Arc::new(Barrier::new(4));
+            let loaders: Vec<_> = (0..4)
+                .map(|_| {
+                    let barrier = barrier.clone();
+                    return std::thread::spawn(move || {
+                        Thread::attach_current_thread(vm.as_ref());
+                        barrier.wait();
+                        let class = vm
+                            .bootstrap_class_loader
+                            .load_binary_name_class("rsvm.Volatiles")
+                            .unwrap();
+                        Thread::detach_current_thread();
+                        return class.as_usize();
+                    });
+                })
+                .collect();
+            let classes: Vec<usize> = loaders
+                .into_iter()
+                .map(|loader| loader.join().unwrap())
+                .collect();
+            assert!(classes.iter().all(|class| *class == classes[0]));
+            assert_eq!(classes_loaded + 1, vm.stats().classes_loaded);
+        });
+    }
+
+    #[test]
+    fn jni_env_per_thread() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let mut thread = Thread::current();
+            let env = thread.jni_env_mut();
+            assert!(env.thread() == Thread::current());
+            assert!(env.vm() == vm);
+            let env_handle = env.as_handle();
+
+            // the native finds its thread through the env, and its local
+            // references are gone once it returns
+            let obj = vm.new_int_array(&[1]).cast::<Object>();
+            let holds_lock: bool = vm
+                .invoke_static(
+                    "java.lang.Thread",
+                    "holdsLock",
+                    "(Ljava/lang/Object;)Z",
+                    (obj,),
+                )
+                .unwrap();
+            assert!(!holds_lock);
+            assert_eq!(0, thread.jni_env_mut().push_local_frame());
+
+            let other_env_handle = std::thread::spawn(move || {
+                Thread::attach_current_thread(vm.as_ref());
+                let mut thread = Thread::current();
+                let env = thread.jni_env_mut();
+                assert!(env.thread() == Thread::current());
+                let env_handle = env.as_handle();
+                Thread::detach_current_thread();
+                return env_handle;
+            })
+            .join()
+            .unwrap();
+            assert_ne!(env_handle, other_env_handle);
+            assert_eq!(env_handle, thread.jni_env_mut().as_handle());
+        });
+    }
+
+    #[test]
+    fn register_natives() {
+        extern "system" fn answer(_env: jni::JNIEnv, _cls: jni::objects::JClass) -> JInt {
+            return 42;
+        }
+        extern "system" fn another_answer(_env: jni::JNIEnv, _cls: jni::objects::JClass) -> JInt {
+            return 43;
+        }
+        extern "system" fn no_bits(
+            _env: jni::JNIEnv,
+            _cls: jni::objects::JClass,
+            _i: JInt,
+        ) -> JInt {
+            return -1;
+        }
+
+        test::ensure_class_exists("./tests/classes", "rsvm.RegisteredNatives");
+        test::run_in_vm("./tests/classes", |vm| {
+            let mut env = unsafe {
+                jni::JNIEnv::from_raw(Thread::current().jni_env_mut().as_handle() as _).unwrap()
+            };
+            let register = |env: &mut jni::JNIEnv, class: &str, name: &str, sig: &str, f| {
+                let class = vm
+                    .bootstrap_class_loader
+                    .load_binary_name_class(class)
+                    .unwrap();
+                let class = unsafe { jni::objects::JClass::from_raw(class.as_raw_ptr() as _) };
+                let method = jni::NativeMethod {
+                    name: name.into(),
+                    sig: sig.into(),
+                    fn_ptr: f,
+                };
+                env.register_native_methods(&class, &[method]).unwrap();
+            };
+            let class = "rsvm.RegisteredNatives";
+            let call_answer = || -> JInt {
+                return vm.invoke_static(class, "callAnswer", "()I", ()).unwrap();
+            };
+            register(&mut env, class, "answer", "()I", answer as _);
+            assert_eq!(42, call_answer());
+            // registering again rebinds the native
+            register(&mut env, class, "answer", "()I", another_answer as _);
+            assert_eq!(43, call_answer());
+
+            // a registered function takes precedence over the builtin one,
+            // which is back once unregistered
+            let bit_count = || -> JInt {
+                return vm
+                    .invoke_static("java.lang.Integer", "bitCount", "(I)I", (7,))
+                    .unwrap();
+            };
+            assert_eq!(3, bit_count());
+            register(
+                &mut env,
+                "java.lang.Integer",
+                "bitCount",
+                "(I)I",
+                no_bits as _,
+            );
+            assert_eq!(-1, bit_count());
+            let integer = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("java.lang.Integer")
+                .unwrap();
+            let integer = unsafe { jni::objects::JClass::from_raw(integer.as_raw_ptr() as _) };
+            env.unregister_native_methods(&integer).unwrap();
+            assert_eq!(3, bit_count());
+
+            // one unknown method fails the call and binds none of them
+            let raw_env = env.get_raw();
+            let register_raw = |methods: *const jni::sys::JNINativeMethod, count: JInt| {
+                let class = vm
+                    .bootstrap_class_loader
+                    .load_binary_name_class(class)
+                    .unwrap();
+                return unsafe {
+                    (**raw_env).RegisterNatives.unwrap()(
+                        raw_env,
+                        class.as_raw_ptr() as _,
+                        methods,
+                        count,
+                    )
+                };
+            };
+            let native = |name: &CStr, sig: &CStr, f: *mut c_void| jni::sys::JNINativeMethod {
+                name: name.as_ptr() as _,
+                signature: sig.as_ptr() as _,
+                fnPtr: f,
+            };
+            let answer_name = CString::new("answer").unwrap();
+            let no_name = CString::new("noSuchNative").unwrap();
+            let sig = CString::new("()I").unwrap();
+            let methods = [
+                native(&answer_name, &sig, answer as _),
+                native(&no_name, &sig, answer as _),
+            ];
+            assert_eq!(jni::sys::JNI_ERR, register_raw(methods.as_ptr(), 2));
+            assert_eq!(43, call_answer());
+            let methods = [native(&answer_name, &sig, std::ptr::null_mut())];
+            assert_eq!(jni::sys::JNI_ERR, register_raw(methods.as_ptr(), 1));
+            assert_eq!(43, call_answer());
+            assert_eq!(jni::sys::JNI_ERR, register_raw(std::ptr::null(), 1));
+            assert_eq!(jni::sys::JNI_OK, register_raw(std::ptr::null(), 0));
+        });
+    }
+
+    #[test]
+    fn narrow_native_results() {
+        // the natives set the bits above the width of their Java return
+        // types, like C functions may leave them in the result register
+        extern "system" fn no_flag(_env: jni::JNIEnv, _cls: jni::objects::JClass) -> JLong {
+            return 0x1_0000_0100;
+        }
+        extern "system" fn flag(_env: jni::JNIEnv, _cls: jni::objects::JClass) -> JLong {
+            return 0x7fff_ff01;
+        }
+        extern "system" fn letter(_env: jni::JNIEnv, _cls: jni::objects::JClass) -> JLong {
+            return -1;
+        }
+        extern "system" fn signed_byte(_env: jni::JNIEnv, _cls: jni::objects::JClass) -> JLong {
+            return 0x180;
+        }
+
+        test::ensure_class_exists("./tests/classes", "rsvm.RegisteredNatives");
+        test::run_in_vm("./tests/classes", |vm| {
+            let mut env = unsafe {
+                jni::JNIEnv::from_raw(Thread::current().jni_env_mut().as_handle() as _).unwrap()
+            };
+            let class = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("rsvm.RegisteredNatives")
+                .unwrap();
+            let class = unsafe { jni::objects::JClass::from_raw(class.as_raw_ptr() as _) };
+            let mut register = |name: &str, sig: &str, f| {
+                let method = jni::NativeMethod {
+                    name: name.into(),
+                    sig: sig.into(),
+                    fn_ptr: f,
+                };
+                env.register_native_methods(&class, &[method]).unwrap();
+            };
+            let call = |name: &str| -> JInt {
+                return vm
+                    .invoke_static("rsvm.RegisteredNatives", name, "()I", ())
+                    .unwrap();
+            };
+            register("flag", "()Z", no_flag as _);
+            assert_eq!(0, call("callFlag"));
+            register("flag", "()Z", flag as _);
+            assert_eq!(1, call("callFlag"));
+            register("letter", "()C", letter as _);
+            assert_eq!(0xffff, call("callLetter"));
+            register("signedByte", "()B", signed_byte as _);
+            assert_eq!(-128, call("callSignedByte"));
+        });
+    }
+
+    #[test]
+    fn primitive_array_critical() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let env = Thread::current().jni_env_mut().as_handle() as *mut jni::sys::JNIEnv;
+            let (get, release) = unsafe {
+                (
+                    (**env).GetPrimitiveArrayCritical.unwrap(),
+                    (**env).ReleasePrimitiveArrayCritical.unwrap(),
+                )
+            };
+            let ints = vm.new_int_array(&[1, 2, 3]);
+            let bytes = vm.new_byte_array(&[4, 5]);
+            let mut is_copy = jni::sys::JNI_TRUE;
+            unsafe {
+                let int_elements = get(env, ints.as_raw_ptr() as _, &mut is_copy) as *mut JInt;
+                assert_eq!(jni::sys::JNI_FALSE, is_copy);
+                assert_eq!(2, *int_elements.offset(1));
+                // critical regions nest
+                let byte_elements = get(env, bytes.as_raw_ptr() as _, std::ptr::null_mut());
+                let byte_elements = byte_elements as *mut JByte;
+                assert!(Thread::current().jni_env_mut().in_critical());
+                *int_elements.offset(2) = 30;
+                *byte_elements = 40;
+                release(env, ints.as_raw_ptr() as _, int_elements as _, 0);
+                assert!(Thread::current().jni_env_mut().in_critical());
+                release(env, bytes.as_raw_ptr() as _, byte_elements as _, 0);
+            }
+            assert!(!Thread::current().jni_env_mut().in_critical());
+            // the native wrote to the arrays themselves
+            assert_eq!(&[1, 2, 30], ints.to_slice());
+            assert_eq!(&[40, 5], bytes.to_slice());
+        });
+    }
+
+    #[test]
+    fn weak_global_refs() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let env = Thread::current().jni_env_mut().as_handle() as *mut jni::sys::JNIEnv;
+            let functions = unsafe { &**env };
+            let new_weak = functions.NewWeakGlobalRef.unwrap();
+            let delete_weak = functions.DeleteWeakGlobalRef.unwrap();
+            let new_local = functions.NewLocalRef.unwrap();
+            let is_same = functions.IsSameObject.unwrap();
+            let obj = vm.new_int_array(&[1]).cast::<Object>();
+            let other = vm.new_int_array(&[2]).cast::<Object>();
+            let null = std::ptr::null_mut();
+            unsafe {
+                let weak = new_weak(env, obj.as_c_ptr() as _);
+                let other_weak = new_weak(env, other.as_c_ptr() as _);
+                assert_ne!(obj.as_c_ptr(), weak as _);
+                assert_eq!(obj.as_c_ptr(), new_local(env, weak) as _);
+                assert_eq!(jni::sys::JNI_TRUE, is_same(env, weak, obj.as_c_ptr() as _));
+                assert_eq!(jni::sys::JNI_FALSE, is_same(env, weak, null));
+                assert!(new_weak(env, null).is_null());
+
+                // a collected referent leaves the reference null
+                vm.jni().weak_globals.clear_unreachable(|obj| obj == other);
+                assert!(new_local(env, weak).is_null());
+                assert_eq!(jni::sys::JNI_TRUE, is_same(env, weak, null));
+                assert_eq!(other.as_c_ptr(), new_local(env, other_weak) as _);
+
+                // other JNI functions take weak references like any other
+                let get_critical = functions.GetPrimitiveArrayCritical.unwrap();
+                let release_critical = functions.ReleasePrimitiveArrayCritical.unwrap();
+                let elements = get_critical(env, other_weak, std::ptr::null_mut()) as *mut JInt;
+                assert_eq!(2, *elements);
+                release_critical(env, other_weak, elements as _, 0);
+
+                // deleted slots are reused, once however often deleted
+                delete_weak(env, weak);
+                delete_weak(env, weak);
+                let renewed = new_weak(env, other.as_c_ptr() as _);
+                assert_eq!(weak, renewed);
+                assert_eq!(other.as_c_ptr(), new_local(env, renewed) as _);
+                let another = new_weak(env, other.as_c_ptr() as _);
+                assert_ne!(renewed, another);
+                delete_weak(env, another);
+                delete_weak(env, renewed);
+                delete_weak(env, other_weak);
+            }
+        });
+    }
+
+    #[test]
+    fn weak_global_native_results() {
+        static CACHED: AtomicUsize = AtomicUsize::new(0);
+        extern "system" fn cached(
+            _env: jni::JNIEnv,
+            _cls: jni::objects::JClass,
+        ) -> jni::sys::jobject {
+            return CACHED.load(Ordering::Relaxed) as _;
+        }
+
+        test::ensure_class_exists("./tests/classes", "rsvm.RegisteredNatives");
+        test::run_in_vm("./tests/classes", |vm| {
+            let raw_env = Thread::current().jni_env_mut().as_handle() as *mut jni::sys::JNIEnv;
+            let mut env = unsafe { jni::JNIEnv::from_raw(raw_env).unwrap() };
+            let class = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("rsvm.RegisteredNatives")
+                .unwrap();
+            let class = unsafe { jni::objects::JClass::from_raw(class.as_raw_ptr() as _) };
+            let method = jni::NativeMethod {
+                name: "cached".into(),
+                sig: "()[I".into(),
+                fn_ptr: cached as _,
+            };
+            env.register_native_methods(&class, &[method]).unwrap();
+            let ints = vm.new_int_array(&[7]);
+            let weak =
+                unsafe { (**raw_env).NewWeakGlobalRef.unwrap()(raw_env, ints.as_raw_ptr() as _) };
+            CACHED.store(weak as usize, Ordering::Relaxed);
+            let call = || -> JInt {
+                return vm
+                    .invoke_static("rsvm.RegisteredNatives", "callCached", "()I", ())
+                    .unwrap();
+            };
+            // the native returns the weak reference, Java gets its referent
+            assert_eq!(7, call());
+            vm.jni().weak_globals.clear_unreachable(|_| false);
+            assert_eq!(-1, call());
+            unsafe { (**raw_env).DeleteWeakGlobalRef.unwrap()(raw_env, weak) };
+        });
+    }
+
+    #[test]
+    fn define_classes() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Greeter");
+        test::run_in_vm("./tests/classes", |vm| {
+            let thread = Thread::current();
+            let bytes = std::fs::read("./tests/classes/rsvm/Greeter.class").unwrap();
+            let mut env = unsafe {
+                jni::JNIEnv::from_raw(Thread::current().jni_env_mut().as_handle() as _).unwrap()
+            };
+            let host = env
+                .define_class("rsvm/Greeter", &jni::objects::JObject::null(), &bytes)
+                .unwrap();
+            let host = JClassPtr::from_raw(host.as_raw() as _);
+            assert_eq!("rsvm/Greeter", host.name().as_str());
+            let greeting: String = vm
+                .invoke_static("rsvm.Greeter", "greeting", "()Ljava/lang/String;", ())
+                .unwrap();
+            assert_eq!("hello", greeting);
+
+            // patch the string constant the anonymous class returns
+            let cp = host.class_data().cp;
+            let greeting_index = (1..cp.length())
+                .find(|idx| {
+                    matches!(cp.get_tag(*idx), ConstantTag::String)
+                        && cp.get_string(*idx).as_str() == "hello"
+                })
+                .unwrap();
+            let mut cp_patches = vec![ObjectPtr::null(); usize::from(greeting_index) + 1];
+            cp_patches[usize::from(greeting_index)] = vm.new_string("bonjour").cast();
+            let define_anonymous = |cp_patches: Vec<ObjectPtr>| {
+                return vm
+                    .bootstrap_class_loader
+                    .define_anonymous_class(bytes.clone(), host, cp_patches, thread)
+                    .unwrap();
+            };
+            let anonymous = define_anonymous(cp_patches);
+            let other = define_anonymous(Vec::new());
+            assert_ne!(anonymous.name(), other.name());
+            assert!(anonymous.name().as_str().starts_with("rsvm/Greeter/"));
+            assert_eq!(host.class_loader(), anonymous.class_loader());
+
+            let anonymous_name = anonymous.binary_name();
+            let greeting: String = vm
+                .invoke_static(&anonymous_name, "greeting", "()Ljava/lang/String;", ())
+                .unwrap();
+            assert_eq!("bonjour", greeting);
+            let other_name = other.binary_name();
+            let greeting: String = vm
+                .invoke_static(&other_name, "greeting", "()Ljava/lang/String;", ())
+                .unwrap();
+            assert_eq!("hello", greeting);
+            // references to itself resolve to the anonymous class
+            let doubled: JInt = vm
+                .invoke_static(&anonymous_name, "doubled", "(I)I", (21,))
+                .unwrap();
+            assert_eq!(42, doubled);
+
+            let err = vm
+                .bootstrap_class_loader
+                .define_class_with_loader(
+                    Some("rsvm/Welcomer"),
+                    bytes.clone(),
+                    host.class_loader(),
+                    thread,
+                )
+                .unwrap_err();
+            assert!(matches!(err, ClassLoadErr::WrongName { .. }));
+        });
+    }
+
+    #[test]
+    fn java_vm_attach() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let env = unsafe {
+                jni::JNIEnv::from_raw(Thread::current().jni_env_mut().as_handle() as _).unwrap()
+            };
+            let java_vm = env.get_java_vm().unwrap();
+            assert_eq!(
+                jni::sys::JNI_VERSION_1_6,
+                jni::sys::jint::from(env.get_version().unwrap())
+            );
+
+            let java_vm_ptr = java_vm.get_java_vm_pointer() as usize;
+            std::thread::spawn(move || {
+                let java_vm =
+                    unsafe { jni::JavaVM::from_raw(java_vm_ptr as *mut jni::sys::JavaVM) }.unwrap();
+                assert!(java_vm.get_env().is_err());
+                {
+                    let _guard = java_vm.attach_current_thread().unwrap();
+                    let mut thread = Thread::current();
+                    assert!(thread.vm_ptr() == vm);
+                    assert!(
+                        java_vm.get_env().unwrap().get_raw() as isize
+                            == thread.jni_env_mut().as_handle()
+                    );
+                    // the attached thread has a java.lang.Thread
+                    let jthread: ObjectPtr = vm
+                        .invoke_static(
+                            "java.lang.Thread",
+                            "currentThread",
+                            "()Ljava/lang/Thread;",
+                            (),
+                        )
+                        .unwrap();
+                    assert!(jthread.is_not_null());
+                }
+                // the guard detaches the thread
+                assert!(Thread::current().is_null());
+            })
+            .join()
+            .unwrap();
+        });
+    }
+
+    #[cfg(feature = "invocation-api")]
+    #[test]
+    fn invocation_api() {
+        use crate::native::invocation::{
+            JNI_CreateJavaVM, JNI_GetCreatedJavaVMs, JNI_GetDefaultJavaVMInitArgs, JavaVMWrapper,
+        };
+        use jni::sys::{JavaVMInitArgs, JavaVMOption, JNI_EEXIST, JNI_OK, JNI_VERSION_1_6};
+        use std::ffi::CString;
+
+        test::ensure_class_exists("./tests/classes", "rsvm.Greeter");
+        std::thread::Builder::new()
+            .stack_size(4 * 1024 * 1024)
+            .spawn(|| unsafe {
+                let mut rsvm_home =
+                    std::path::PathBuf::from(super::VMConfig::default().rsvm_home());
+                rsvm_home.pop();
+                let option_strings = [
+                    format!("-Drsvm.home={}", rsvm_home.display()),
+                    "-Djava.class.path=./tests/classes".to_string(),
+                    "-XX:+ShareEmptyArrays".to_string(),
+                ]
+                .map(|option| CString::new(option).unwrap());
+                let mut options: Vec<JavaVMOption> = option_strings
+                    .iter()
+                    .map(|option| JavaVMOption {
+                        optionString: option.as_ptr() as _,
+                        extraInfo: std::ptr::null_mut(),
+                    })
+                    .collect();
+                let mut args = JavaVMInitArgs {
+                    version: JNI_VERSION_1_6,
+                    nOptions: options.len() as _,
+                    options: options.as_mut_ptr(),
+                    ignoreUnrecognized: 0,
+                };
+                assert_eq!(
+                    JNI_OK,
+                    JNI_GetDefaultJavaVMInitArgs(&mut args as *mut _ as _)
+                );
+
+                let mut java_vm: *mut jni::sys::JavaVM = std::ptr::null_mut();
+                let mut env: *mut std::ffi::c_void = std::ptr::null_mut();
+                let create = |java_vm: &mut *mut jni::sys::JavaVM,
+                              env: &mut *mut std::ffi::c_void,
+                              args: &mut JavaVMInitArgs| {
+                    return JNI_CreateJavaVM(java_vm, env, args as *mut _ as _);
+                };
+                assert_eq!(JNI_OK, create(&mut java_vm, &mut env, &mut args));
+                let vm = JavaVMWrapper::from_raw_vm(java_vm).vm();
+                assert_eq!(env as isize, Thread::current().jni_env_mut().as_handle());
+                let greeting: String = vm
+                    .invoke_static("rsvm.Greeter", "greeting", "()Ljava/lang/String;", ())
+                    .unwrap();
+                assert_eq!("hello", greeting);
+
+                let mut created: *mut jni::sys::JavaVM = std::ptr::null_mut();
+                let mut n_vms = 0;
+                assert_eq!(JNI_OK, JNI_GetCreatedJavaVMs(&mut created, 1, &mut n_vms));
+                assert_eq!(1, n_vms);
+                assert_eq!(java_vm, created);
+
+                // one VM at a time
+                std::thread::spawn(move || {
+                    let mut other_vm: *mut jni::sys::JavaVM = std::ptr::null_mut();
+                    let mut other_env: *mut std::ffi::c_void = std::ptr::null_mut();
+                    let mut args = JavaVMInitArgs {
+                        version: JNI_VERSION_1_6,
+                        nOptions: 0,
+                        options: std::ptr::null_mut(),
+                        ignoreUnrecognized: 0,
+                    };
+                    assert_eq!(JNI_EEXIST, create(&mut other_vm, &mut other_env, &mut args));
+                })
+                .join()
+                .unwrap();
+
+                let destroy = (**java_vm).DestroyJavaVM.unwrap();
+                assert_eq!(JNI_OK, destroy(java_vm));
+                assert!(Thread::current().is_null());
+                assert_eq!(JNI_OK, JNI_GetCreatedJavaVMs(&mut created, 1, &mut n_vms));
+                assert_eq!(0, n_vms);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn class_histogram() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let object_arrays = |vm: VMPtr| {
+                return vm
+                    .class_histogram()
+                    .into_iter()
+                    .find(|entry| entry.class_name == "[Ljava.lang.Object;")
+                    .map_or((0, 0), |entry| (entry.instances, entry.bytes));
+            };
+            let (instances, bytes) = object_arrays(vm);
+            let arrays: Vec<_> = (0..4)
+                .map(|_| JArray::new_obj_arr(3, Thread::current()))
+                .collect();
+            let array_size = arrays[0].cast::<Object>().heap_size();
+            assert_eq!((instances + 4, bytes + 4 * array_size), object_arrays(vm));
+
+            let histogram = vm.class_histogram();
+            assert!(histogram.windows(2).all(|w| w[0].bytes >= w[1].bytes));
+            assert!(histogram
+                .iter()
+                .any(|entry| entry.class_name == "java.lang.String"));
+        });
+    }
+
+    #[test]
+    fn management_beans() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Management");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call_long = |method_name: &str| -> JLong {
+                return vm
+                    .invoke_static("rsvm.Management", method_name, "()J", ())
+                    .unwrap();
+            };
+            let call_int = |method_name: &str| -> JInt {
+                return vm
+                    .invoke_static("rsvm.Management", method_name, "()I", ())
+                    .unwrap();
+            };
+            let heap_used = call_long("heapUsed");
+            let info = vm.runtime_info();
+            assert!(heap_used > 0 && heap_used as usize <= info.heap.used);
+            assert!(info.heap.used <= info.heap.committed);
+            assert_eq!(
+                info.non_heap.committed as JLong,
+                call_long("nonHeapCommitted")
+            );
+
+            assert_eq!(info.thread_count as JInt, call_int("threadCount"));
+            assert!(call_int("allThreadIds") >= 1);
+            assert!(info.peak_thread_count >= info.thread_count);
+            assert!(info.total_started_thread_count >= info.thread_count);
+
+            let uptime = call_long("uptime");
+            // from the wall clock, which may be off from the VM's monotonic one
+            let max_uptime = vm.runtime_info().uptime.as_millis() + 100;
+            assert!(uptime >= 0 && uptime as u128 <= max_uptime, "{}", uptime);
+            let loaded_class_count = call_int("loadedClassCount") as usize;
+            assert!(loaded_class_count > 0);
+            assert!(loaded_class_count <= vm.runtime_info().loaded_class_count);
+        });
+    }
+
+    #[test]
+    fn redefine_method_bodies() {
+        test::ensure_class_exists("./tests/classes", "rsvm.Redefined");
+        test::ensure_class_exists("./tests/classes/redefine/v2", "rsvm.Redefined");
+        test::ensure_class_exists("./tests/classes/redefine/added", "rsvm.Redefined");
+        test::run_in_vm("./tests/classes", |vm| {
+            let call = || -> JLong {
+                return vm
+                    .invoke_static("rsvm.Redefined", "call", "()J", ())
+                    .unwrap();
+            };
+            assert_eq!(1101, call());
+            let class = vm
+                .bootstrap_class_loader
+                .load_binary_name_class("rsvm.Redefined")
+                .unwrap();
+            let class_file = |version: &str| {
+                let path = format!("./tests/classes/redefine/{}/rsvm/Redefined.class", version);
+                return std::fs::read(path).unwrap();
+            };
+
+            vm.redefine_class(class, &class_file("v2")).unwrap();
+            assert_eq!(2202, call());
+
+            let err = vm.redefine_class(class, &class_file("added")).unwrap_err();
+            assert!(matches!(
+                err,
+                VMError::ClassLoaderErr(ClassLoadErr::UnsupportedRedefinition {
+                    reason: "attempted to add a method",
+                    ..
+                })
+            ));
+            assert_eq!(2202, call());
+        });
+    }
+
+    #[test]
+    fn allocation_sampling() {
+        let samples = Arc::new(Mutex::new(Vec::new()));
+        let recorded = samples.clone();
+        test::run_in_vm_and_call_static(
+            "./tests/classes",
+            "rsvm.Allocations",
+            "fill",
+            "(I)I",
+            move |vm| {
+                vm.set_allocation_sampler(4 * 1024, move |sample| {
+                    recorded.lock().unwrap().push(sample.clone());
+                });
+                vec![JValue::with_int_val(1000)]
+            },
+            move |vm, result| {
+                vm.clear_allocation_sampler();
+                assert_eq!(16000, result.int_val());
+                let samples = samples.lock().unwrap();
+                // 1000 arrays of 16 ints take about 80KB
+                assert!(samples.len() >= 10, "{} samples", samples.len());
+                let sample = samples
+                    .iter()
+                    .find(|sample| sample.class_name == "[I")
+                    .unwrap();
+                assert_eq!(JArray::size(16, 4), sample.size);
+                assert_eq!("rsvm.Allocations#fill", sample.stack[0]);
+            },
+        );
+    }
+
+    #[test]
+    fn class_load_errors() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let thread = Thread::current();
+            let define = |bytes: &[u8]| {
+                return vm
+                    .bootstrap_class_loader
+                    .define_class(bytes.to_vec(), thread)
+                    .unwrap_err();
+            };
+            assert!(matches!(
+                define(&[0xCA, 0xFE, 0xD0, 0x0D, 0, 0, 0, 52]),
+                ClassLoadErr::BadMagic { found: 0xCAFED00D }
+            ));
+            assert!(matches!(
+                define(&[0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 99]),
+                ClassLoadErr::UnsupportedVersion { found: 99, max: 57 }
+            ));
+            assert!(matches!(
+                define(&[0xCA, 0xFE, 0xBA, 0xBE, 0, 0]),
+                ClassLoadErr::Truncated {
+                    offset: 6,
+                    needed: 2
+                }
+            ));
+            // #1 is a CONSTANT_Integer, #2 a CONSTANT_Utf8 missing its bytes
+            let err = define(&[
+                0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 52, 0, 3, 3, 0, 0, 0, 1, 1, 0, 8, b'a',
+            ]);
+            assert!(matches!(err, ClassLoadErr::TruncatedPool { index: 2, .. }));
+            let source = err.source().unwrap().downcast_ref::<ClassLoadErr>();
+            assert!(matches!(
+                source,
+                Some(ClassLoadErr::Truncated {
+                    offset: 18,
+                    needed: 8
+                })
+            ));
+
+            let err = vm
+                .invoke_static::<_, ()>("rsvm/DoesNotExist", "run", "()V", ())
+                .unwrap_err();
+            assert!(matches!(
+                &err,
+                VMError::ClassLoaderErr(ClassLoadErr::ClassNotFound(name))
+                    if name == "rsvm/DoesNotExist"
+            ));
+            assert_eq!(
+                "class loading failed: cannot find class rsvm/DoesNotExist",
+                err.to_string()
+            );
+            assert!(err.source().unwrap().is::<ClassLoadErr>());
+        });
+    }
+
+    #[test]
+    fn missing_fields() {
+        test::ensure_class_exists("./tests/classes", "rsvm.MissingFields");
+        test::run_in_vm("./tests/classes", |vm| {
+            let mut bytes = std::fs::read("./tests/classes/rsvm/MissingFields.class").unwrap();
+            // only the references of the caller change, Holder keeps its fields
+            for (from, to) in [
+                ("presentStatic", "missingStatic"),
+                ("presentField", "missingField"),
+            ] {
+                let at = bytes
+                    .windows(from.len())
+                    .position(|window| window == from.as_bytes())
+                    .unwrap();
+                bytes[at..at + to.len()].copy_from_slice(to.as_bytes());
+            }
+            vm.bootstrap_class_loader
+                .define_class(bytes, Thread::current())
+                .unwrap();
+
+            for (method, field) in [
+                ("readStatic", "missingStatic"),
+                ("readField", "missingField"),
+            ] {
+                let result: Result<i32, VMError> =
+                    vm.invoke_static("rsvm.MissingFields", method, "()I", ());
+                match result {
+                    Err(VMError::Cancelled(CancelReason::LinkageError(msg))) => {
+                        assert_eq!(format!("NoSuchFieldError: {}", field), msg);
+                    }
+                    _ => panic!("{} did not fail", method),
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn class_file_accounting() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let thread = Thread::current();
+            let define = |bytes: Vec<u8>| {
+                return vm.bootstrap_class_loader.define_class(bytes, thread);
+            };
+            // rsvm/Accounting with the field `static final int x = 1`, whose
+            // ConstantValue attribute is named by #`name` and declares `length`
+            // bytes, with `body`
+            let class_file = |name: u8, length: u8, body: &[u8]| -> Vec<u8> {
+                let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 51, 0, 9];
+                bytes.extend_from_slice(&[7, 0, 2, 1, 0, 15]);
+                bytes.extend_from_slice(b"rsvm/Accounting");
+                bytes.extend_from_slice(&[7, 0, 4, 1, 0, 16]);
+                bytes.extend_from_slice(b"java/lang/Object");
+                bytes.extend_from_slice(&[1, 0, 1, b'x', 1, 0, 1, b'I', 1, 0, 13]);
+                bytes.extend_from_slice(b"ConstantValue");
+                bytes.extend_from_slice(&[3, 0, 0, 0, 1]);
+                // public, this_class, super_class, no interfaces, one field
+                bytes.extend_from_slice(&[0, 0x21, 0, 1, 0, 3, 0, 0, 0, 1]);
+                bytes.extend_from_slice(&[0, 0x18, 0, 5, 0, 6, 0, 1, 0, name, 0, 0, 0, length]);
+                bytes.extend_from_slice(body);
+                // no methods and class attributes
+                bytes.extend_from_slice(&[0, 0, 0, 0]);
+                return bytes;
+            };
+
+            let err = define(class_file(7, 3, &[0, 8, 0])).unwrap_err();
+            assert_eq!(
+                "invalid class file: rsvm/Accounting: the ConstantValue attribute at offset \
+                 100 has 3 bytes, 2 were read",
+                err.to_string()
+            );
+            let err = define(class_file(8, 2, &[0, 8])).unwrap_err();
+            assert_eq!(
+                "invalid class file: the name #8 of the attribute at offset 100 is not a \
+                 Utf8 constant",
+                err.to_string()
+            );
+            let err = define(class_file(7, 200, &[0, 8])).unwrap_err();
+            assert!(matches!(
+                err,
+                ClassLoadErr::Truncated {
+                    offset: 106,
+                    needed: 200
+                }
+            ));
+            let mut extra_bytes = class_file(7, 2, &[0, 8]);
+            extra_bytes.push(0);
+            let err = define(extra_bytes).unwrap_err();
+            assert_eq!(
+                "invalid class file: rsvm/Accounting: 1 extra bytes at offset 112",
+                err.to_string()
+            );
+            let mut unknown_tag = class_file(7, 2, &[0, 8]);
+            unknown_tag[10] = 2;
+            let err = define(unknown_tag).unwrap_err();
+            assert_eq!(
+                "invalid class file: unknown constant tag 2 of #1 at offset 10",
+                err.to_string()
+            );
+
+            let class = define(class_file(7, 2, &[0, 8])).unwrap();
+            assert_eq!("rsvm/Accounting", class.name().as_str());
+        });
+    }
+
+    #[test]
+    fn frame_size_check() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let thread = Thread::current();
+            // the class `name` with the method `run` whose code is a single
+            // return instruction in a frame of `max_locals` locals
+            let class_file = |name: &str, access: u8, descriptor: &str, max_locals: u8| {
+                let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 51, 0, 8];
+                bytes.extend_from_slice(&[7, 0, 2, 1, 0, name.len() as u8]);
+                bytes.extend_from_slice(name.as_bytes());
+                bytes.extend_from_slice(&[7, 0, 4, 1, 0, 16]);
+                bytes.extend_from_slice(b"java/lang/Object");
+                bytes.extend_from_slice(&[1, 0, 3]);
+                bytes.extend_from_slice(b"run");
+                bytes.extend_from_slice(&[1, 0, descriptor.len() as u8]);
+                bytes.extend_from_slice(descriptor.as_bytes());
+                bytes.extend_from_slice(&[1, 0, 4]);
+                bytes.extend_from_slice(b"Code");
+                // public, this_class, super_class, no interfaces and fields
+                bytes.extend_from_slice(&[0, 0x21, 0, 1, 0, 3, 0, 0, 0, 0]);
+                // one method with a Code attribute of 13 bytes
+                bytes.extend_from_slice(&[0, 1, 0, access, 0, 5, 0, 6, 0, 1, 0, 7, 0, 0, 0, 13]);
+                bytes.extend_from_slice(&[0, 0, 0, max_locals, 0, 0, 0, 1, 0xb1, 0, 0, 0, 0]);
+                // no class attributes
+                bytes.extend_from_slice(&[0, 0]);
+                return bytes;
+            };
+            let define = |bytes: Vec<u8>| {
+                return vm.bootstrap_class_loader.define_class(bytes, thread);
+            };
+
+            let err = define(class_file("rsvm/Frames", 0x09, "(I)V", 0)).unwrap_err();
+            assert!(matches!(err, ClassLoadErr::Verify(_)));
+            assert_eq!(
+                "verification failed: rsvm/Frames#run(I)V: the 1 slots of the arguments do not \
+                 fit into 0 locals",
+                err.to_string()
+            );
+            let err = define(class_file("rsvm/Frames", 0x01, "(JD)V", 4)).unwrap_err();
+            assert_eq!(
+                "verification failed: rsvm/Frames#run(JD)V: the 5 slots of the arguments do not \
+                 fit into 4 locals",
+                err.to_string()
+            );
+            define(class_file("rsvm/Frames", 0x01, "(JD)V", 5)).unwrap();
+            define(class_file("rsvm/StaticFrames", 0x09, "(I)V", 1)).unwrap();
+            let _: () = vm
+                .invoke_static("rsvm.StaticFrames", "run", "(I)V", (7,))
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn jsr_ret() {
+        test::run_in_vm("./tests/classes", |vm| {
+            let thread = Thread::current();
+            let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 49, 0, 8];
+            bytes.extend_from_slice(&[7, 0, 2, 1, 0, 15]);
+            bytes.extend_from_slice(b"rsvm/Subroutine");
+            bytes.extend_from_slice(&[7, 0, 4, 1, 0, 16]);
+            bytes.extend_from_slice(b"java/lang/Object");
+            bytes.extend_from_slice(&[1, 0, 3]);
+            bytes.extend_from_slice(b"run");
+            bytes.extend_from_slice(&[1, 0, 3]);
+            bytes.extend_from_slice(b"()I");
+            bytes.extend_from_slice(&[1, 0, 4]);
+            bytes.extend_from_slice(b"Code");
+            bytes.extend_from_slice(&[0, 0x21, 0, 1, 0, 3, 0, 0, 0, 0]);
+            // public static int run(), max_stack 1, max_locals 2
+            bytes.extend_from_slice(&[0, 1, 0, 0x09, 0, 5, 0, 6, 0, 1, 0, 7, 0, 0, 0, 33]);
+            bytes.extend_from_slice(&[0, 1, 0, 2, 0, 0, 0, 21]);
+            // iconst_0, istore_0, jsr 10, jsr 10, iload_0, ireturn
+            bytes.extend_from_slice(&[0x03, 0x3b, 0xa8, 0, 8, 0xa8, 0, 5, 0x1a, 0xac]);
+            // the subroutine: wide astore 1, iinc 0 1, wide ret 1
+            bytes.extend_from_slice(&[0xc4, 0x3a, 0, 1, 0x84, 0, 1, 0xc4, 0xa9, 0, 1]);
+            bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+            vm.bootstrap_class_loader
+                .define_class(bytes, thread)
+                .unwrap();
+            let count: JInt = vm
+                .invoke_static("rsvm.Subroutine", "run", "()I", ())
+                .unwrap();
+            assert_eq!(2, count);
+        });
+    }
+
+    #[test]
+    fn native_fn_names() {
+        let native_fn_name =
+            ClassData::get_native_fn_name("java/lang/ClassLoader$NativeLibrary", "load");
+        assert_eq!(
+            "Java_java_lang_ClassLoader_00024NativeLibrary_load",
+            native_fn_name
+        );
+        assert_eq!(
+            "Java_java_lang_ClassLoader_00024NativeLibrary_load__Ljava_lang_String_2Z",
+            ClassData::get_overloaded_native_fn_name(&native_fn_name, "(Ljava/lang/String;Z)V")
+        );
+        assert_eq!(
+            "Java_rsvm_Natives__1_000e9t_0d834_0dd1e__I_3_3J",
+            ClassData::get_overloaded_native_fn_name(
+                &ClassData::get_native_fn_name("rsvm/Natives", "_\u{e9}t\u{1d11e}"),
+                "(I[[J)J"
+            )
+        );
+        assert_eq!(
+            "Java_rsvm_Natives_run__",
+            ClassData::get_overloaded_native_fn_name("Java_rsvm_Natives_run", "()V")
+        );
+    }
+
+    #[test]
+    fn builtin_native_signatures() {
+        test::run_in_vm("./tests/classes", |vm| {
+            for fn_name in vm.builtin_native_fns.names() {
+                let name = fn_name.strip_prefix("Java_").unwrap();
+                let name = name.split_once("__").map_or(name, |(name, _)| name);
+                let (class_name, _) = name.rsplit_once('_').unwrap();
+                let class = match vm
+                    .bootstrap_class_loader
+                    .load_class(&class_name.replace("_00024", "$").replace('_', "/"))
+                {
+                    Ok(class) => class,
+                    // a class of another platform
+                    Err(_) => continue,
+                };
+                let native = vm.get_builtin_native_fn(fn_name).unwrap();
+                let methods = class.class_data().methods();
+                for idx in 0..methods.length() {
+                    let method: MethodPtr = methods.get(idx).cast();
+                    let native_fn_name = ClassData::get_native_fn_name(
+                        class.name().as_str(),
+                        method.name().as_str(),
+                    );
+                    let overloaded_fn_name = ClassData::get_overloaded_native_fn_name(
+                        &native_fn_name,
+                        method.descriptor().as_str(),
+                    );
+                    let binds = if method.is_native() {
+                        overloaded_fn_name == fn_name
+                            || (native_fn_name == fn_name
+                                && vm.get_builtin_native_fn(&overloaded_fn_name).is_none())
+                    } else {
+                        native_fn_name == fn_name
+                            && vm
+                                .get_builtin_java_method_fn(
+                                    class.name().as_str(),
+                                    method.name().as_str(),
+                                    method.descriptor().as_str(),
+                                )
+                                .is_some()
+                    };
+                    if binds {
+                        assert_eq!(
+                            &NativeSignature::of_method(method),
+                            native.signature(),
+                            "{}{}",
+                            fn_name,
+                            method.descriptor().as_str()
+                        );
+                    }
+                }
+            }
+        });
+    }
+
+    fn call_constants(vm: VMPtr, method_name: &str, descriptor: &str) -> JValue {
+        test::ensure_class_exists("./tests/classes", "rsvm.Constants");
+        let class = vm
+            .bootstrap_class_loader
+            .load_binary_name_class("rsvm.Constants")
+            .unwrap();
+        let method = vm
+            .get_static_method(class, method_name, descriptor, Thread::current())
+            .unwrap();
+        return vm.call_static(class, method, &[]).unwrap();
+    }
+
     const fn rs_fibonacci(num: i32) -> i32 {
         if num == 1 || num == 2 {
             return 1;
@@ -0,0 +1,14 @@
+package programs;
+
+public class Arithmetic {
+    public static void main(String[] args) {
+        System.out.println(Integer.MIN_VALUE / -1);
+        System.out.println(-7 / 2 + " " + -7 % 2);
+        System.out.println(-1 >>> 28);
+        System.out.println((int) 3.99e10 + " " + (long) Double.NaN);
+        System.out.println(5.5 % 2 + " " + (float) 1 / 3);
+        System.out.println(Long.toHexString(Long.MAX_VALUE) + " " + Integer.toBinaryString(10));
+        System.out.println(Math.max(3, 7) + Math.abs(-2.5));
+        System.out.println(Double.compare(0.0, -0.0) + " " + (0.0 == -0.0));
+    }
+}
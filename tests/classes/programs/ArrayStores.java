@@ -0,0 +1,48 @@
+package programs;
+
+import java.util.ArrayList;
+import java.util.List;
+
+/**
+ * Fills arrays of a few element types in loops, each store checked against
+ * the component type of the array.
+ */
+public class ArrayStores {
+    private static final int LENGTH = 20000;
+
+    public static void main(String[] args) {
+        Object[] objects = new Object[LENGTH];
+        String[] strings = new String[LENGTH];
+        Number[] numbers = new Number[LENGTH];
+        CharSequence[] sequences = new CharSequence[LENGTH];
+        List<?>[] lists = new List<?>[LENGTH];
+        Object[][] nested = new Object[LENGTH][];
+        for (int i = 0; i < LENGTH; i++) {
+            String string = Integer.toString(i % 100);
+            objects[i] = (i & 1) == 0 ? string : new int[i % 3];
+            strings[i] = string;
+            numbers[i] = (i & 1) == 0 ? (Number) i : (Number) (long) i;
+            sequences[i] = (i & 1) == 0 ? string : new StringBuilder(string);
+            lists[i] = (i & 1) == 0 ? new ArrayList<Object>() : null;
+            nested[i] = strings;
+        }
+        System.out.println(objects[LENGTH - 2] + " " + ((int[]) objects[LENGTH - 1]).length);
+        long sum = 0;
+        for (Number number : numbers) {
+            sum += number.longValue();
+        }
+        System.out.println(sum);
+        int length = 0;
+        for (CharSequence sequence : sequences) {
+            length += sequence.length();
+        }
+        System.out.println(length + " " + strings[LENGTH - 1]);
+        int nulls = 0;
+        for (List<?> list : lists) {
+            if (list == null) {
+                nulls++;
+            }
+        }
+        System.out.println(nulls + " " + (nested[LENGTH - 1] == strings));
+    }
+}
@@ -0,0 +1,31 @@
+package programs;
+
+import java.util.ArrayList;
+import java.util.HashMap;
+import java.util.List;
+import java.util.Map;
+import java.util.TreeMap;
+
+public class Collections {
+    public static void main(String[] args) {
+        List<Integer> numbers = new ArrayList<Integer>();
+        for (int i = 10; i > 0; i -= 3) {
+            numbers.add(i);
+        }
+        java.util.Collections.sort(numbers);
+        System.out.println(numbers);
+
+        Map<String, Integer> lengths = new HashMap<String, Integer>();
+        for (String word : new String[] {"one", "three", "five"}) {
+            lengths.put(word, word.length());
+        }
+        System.out.println(new TreeMap<String, Integer>(lengths));
+        System.out.println(lengths.get("three") + lengths.get("one"));
+
+        long sum = 0;
+        for (int number : numbers) {
+            sum += number * (long) number;
+        }
+        System.out.println(sum);
+    }
+}
@@ -0,0 +1,24 @@
+package programs;
+
+import java.io.ByteArrayOutputStream;
+import java.io.PrintStream;
+
+/**
+ * Captures what a regression program prints to System.out.
+ */
+public class Harness {
+    private static PrintStream stdout;
+    private static ByteArrayOutputStream captured;
+
+    public static void begin() {
+        stdout = System.out;
+        captured = new ByteArrayOutputStream();
+        System.setOut(new PrintStream(captured, true));
+    }
+
+    public static String end() {
+        System.out.flush();
+        System.setOut(stdout);
+        return captured.toString();
+    }
+}
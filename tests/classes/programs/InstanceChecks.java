@@ -0,0 +1,57 @@
+package programs;
+
+import java.util.ArrayList;
+import java.util.Collection;
+import java.util.HashMap;
+import java.util.LinkedList;
+import java.util.List;
+import java.util.RandomAccess;
+
+/**
+ * Checks a few classes against their superclasses and interfaces over and
+ * over, the way collection code does.
+ */
+public class InstanceChecks {
+    private static final int ROUNDS = 20000;
+
+    public static void main(String[] args) {
+        Object[] objects = {
+            new ArrayList<Object>(), new LinkedList<Object>(), new HashMap<Object, Object>(),
+            "string", 42, new Object(), new int[0], new String[0],
+        };
+        int lists = 0;
+        int randomAccess = 0;
+        int collections = 0;
+        int comparables = 0;
+        int charSequences = 0;
+        int objectArrays = 0;
+        for (int round = 0; round < ROUNDS; round++) {
+            for (Object object : objects) {
+                if (object instanceof List) {
+                    lists++;
+                }
+                if (object instanceof RandomAccess) {
+                    randomAccess++;
+                }
+                if (object instanceof Collection) {
+                    collections++;
+                }
+                if (object instanceof Comparable) {
+                    comparables++;
+                }
+                if (object instanceof CharSequence) {
+                    charSequences++;
+                }
+                if (object instanceof Object[]) {
+                    objectArrays++;
+                }
+            }
+        }
+        System.out.println("List " + lists);
+        System.out.println("RandomAccess " + randomAccess);
+        System.out.println("Collection " + collections);
+        System.out.println("Comparable " + comparables);
+        System.out.println("CharSequence " + charSequences);
+        System.out.println("Object[] " + objectArrays);
+    }
+}
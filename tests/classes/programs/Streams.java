@@ -0,0 +1,31 @@
+package programs;
+
+import java.io.BufferedReader;
+import java.io.ByteArrayInputStream;
+import java.io.ByteArrayOutputStream;
+import java.io.DataInputStream;
+import java.io.DataOutputStream;
+import java.io.IOException;
+import java.io.InputStreamReader;
+
+public class Streams {
+    public static void main(String[] args) throws IOException {
+        ByteArrayOutputStream bytes = new ByteArrayOutputStream();
+        DataOutputStream out = new DataOutputStream(bytes);
+        out.writeInt(0xCAFEBABE);
+        out.writeUTF("rsvm");
+        out.writeDouble(2.25);
+        out.close();
+        System.out.println(bytes.size());
+
+        DataInputStream in = new DataInputStream(new ByteArrayInputStream(bytes.toByteArray()));
+        System.out.println(Integer.toHexString(in.readInt()) + " " + in.readUTF() + " " + in.readDouble());
+
+        BufferedReader reader = new BufferedReader(new InputStreamReader(
+                new ByteArrayInputStream("first\nsecond\n\nlast".getBytes("UTF-8")), "UTF-8"));
+        int count = 0;
+        for (String line = reader.readLine(); line != null; line = reader.readLine()) {
+            System.out.println(++count + ": " + line);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+package programs;
+
+public class Strings {
+    public static void main(String[] args) {
+        StringBuilder builder = new StringBuilder();
+        for (int i = 0; i < 5; i++) {
+            builder.append(i).append(',');
+        }
+        builder.setLength(builder.length() - 1);
+        System.out.println(builder);
+        System.out.println(builder.reverse());
+
+        String greeting = "Hello, rsvm";
+        System.out.println(greeting.toUpperCase());
+        System.out.println(greeting.substring(7) + " " + greeting.indexOf("rsvm"));
+        System.out.println(greeting.replace('l', 'L'));
+        System.out.println(String.valueOf(new char[] {'a', 'b', 'c'}) + 42 + 'x' + 1.5 + true);
+        System.out.println("a-b-c".split("-").length);
+
+        switch (greeting.substring(0, 5)) {
+            case "Hello":
+                System.out.println("switched");
+                break;
+            default:
+                System.out.println("fell through");
+        }
+        System.out.println("\u00e9t\u00e9".length());
+    }
+}
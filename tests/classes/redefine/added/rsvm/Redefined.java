@@ -0,0 +1,21 @@
+package rsvm;
+
+public class Redefined {
+    static final long BASE = 300;
+
+    static long version() {
+        return 3;
+    }
+
+    static long added() {
+        return 3;
+    }
+
+    long value() {
+        return BASE + added();
+    }
+
+    public static long call() {
+        return version() * 1000 + new Redefined().value();
+    }
+}
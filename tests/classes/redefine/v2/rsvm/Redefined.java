@@ -0,0 +1,17 @@
+package rsvm;
+
+public class Redefined {
+    static final long BASE = 200;
+
+    static long version() {
+        return 2;
+    }
+
+    long value() {
+        return BASE + 2;
+    }
+
+    public static long call() {
+        return version() * 1000 + new Redefined().value();
+    }
+}
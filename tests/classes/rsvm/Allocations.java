@@ -0,0 +1,13 @@
+package rsvm;
+
+public class Allocations {
+    public static int fill(int count) {
+        int total = 0;
+        for (int i = 0; i < count; i++) {
+            int[] values = new int[16];
+            values[i % 16] = i;
+            total += values.length;
+        }
+        return total;
+    }
+}
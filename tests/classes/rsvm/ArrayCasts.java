@@ -0,0 +1,37 @@
+package rsvm;
+
+import java.io.Serializable;
+
+public class ArrayCasts {
+    interface Shape {
+    }
+
+    interface Polygon extends Shape {
+    }
+
+    static class Square implements Polygon {
+    }
+
+    static class Tile extends Square {
+    }
+
+    public static int interfaceArrays() {
+        int result = 0;
+        Object tiles = new Tile[1];
+        Object polygons = new Polygon[1];
+        Object ints = new int[1];
+        result |= (tiles instanceof Shape[]) ? 1 : 0;
+        result |= (tiles instanceof Polygon[][]) ? 1 << 1 : 0;
+        result |= (polygons instanceof Shape[]) ? 1 << 2 : 0;
+        result |= (polygons instanceof Square[]) ? 1 << 3 : 0;
+        result |= (tiles instanceof Cloneable) ? 1 << 4 : 0;
+        result |= (ints instanceof Serializable) ? 1 << 5 : 0;
+        result |= (new Tile[1][1] instanceof Cloneable[]) ? 1 << 6 : 0;
+        result |= (new Tile() instanceof Shape) ? 1 << 7 : 0;
+        Shape[] shapes = (Shape[]) tiles;
+        Cloneable cloneable = (Cloneable) polygons;
+        Serializable[] serializables = (Serializable[]) new int[1][];
+        result |= (shapes.length + serializables.length == 2 && cloneable != null) ? 1 << 8 : 0;
+        return result;
+    }
+}
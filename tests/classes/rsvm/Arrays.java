@@ -0,0 +1,48 @@
+package rsvm;
+
+public class Arrays {
+    public static int covariance() {
+        int result = 0;
+        Object strs = new String[1];
+        Object strs2d = new String[1][1];
+        Object ints = new int[1];
+        Object ints2d = new int[1][1];
+        result |= (strs instanceof Object[]) ? 1 : 0;
+        result |= (strs2d instanceof Object[][]) ? 1 << 1 : 0;
+        result |= (strs2d instanceof Object[]) ? 1 << 2 : 0;
+        result |= (ints2d instanceof Object[]) ? 1 << 3 : 0;
+        result |= (ints instanceof Object[]) ? 1 << 4 : 0;
+        result |= (strs instanceof Integer[]) ? 1 << 5 : 0;
+        result |= (ints2d instanceof long[][]) ? 1 << 6 : 0;
+        result |= Object[].class.isAssignableFrom(String[].class) ? 1 << 7 : 0;
+        result |= String[].class.isAssignableFrom(Object[].class) ? 1 << 8 : 0;
+        return result;
+    }
+
+    public static int componentType() {
+        int result = 0;
+        result |= new String[0].getClass().getComponentType() == String.class ? 1 : 0;
+        result |= int[][].class.getComponentType() == int[].class ? 1 << 1 : 0;
+        result |= new Arrays[0][0].getClass().getComponentType() == Arrays[].class ? 1 << 2 : 0;
+        result |= String.class.getComponentType() == null ? 1 << 3 : 0;
+        return result;
+    }
+
+    public static int emptyArrays() {
+        int result = 0;
+        result |= new int[0] == new int[0] ? 1 : 0;
+        result |= new String[0] == new String[0] ? 1 << 1 : 0;
+        result |= (Object) new String[0] != new Object[0] ? 1 << 2 : 0;
+        result |= new String[0].getClass() == String[].class ? 1 << 3 : 0;
+        result |= new int[1] != new int[1] ? 1 << 4 : 0;
+        return result;
+    }
+
+    public static int distinctEmptyArrays() {
+        int result = 0;
+        result |= new int[0] != new int[0] ? 1 : 0;
+        result |= new String[0] != new String[0] ? 1 << 1 : 0;
+        result |= new String[0].getClass() == String[].class ? 1 << 2 : 0;
+        return result;
+    }
+}
@@ -0,0 +1,41 @@
+package rsvm;
+
+public class BitOps {
+
+    public static int intLeadingZeros(int i) {
+        return Integer.numberOfLeadingZeros(i);
+    }
+
+    public static int intTrailingZeros(int i) {
+        return Integer.numberOfTrailingZeros(i);
+    }
+
+    public static int intBitCount(int i) {
+        return Integer.bitCount(i);
+    }
+
+    public static int intReverseBytes(int i) {
+        return Integer.reverseBytes(i);
+    }
+
+    public static int longLeadingZeros(long l) {
+        return Long.numberOfLeadingZeros(l);
+    }
+
+    public static int longTrailingZeros(long l) {
+        return Long.numberOfTrailingZeros(l);
+    }
+
+    public static int longBitCount(long l) {
+        return Long.bitCount(l);
+    }
+
+    public static long longReverseBytes(long l) {
+        return Long.reverseBytes(l);
+    }
+
+    public static String hex(long l) {
+        // Long.toHexString counts the leading zeros
+        return Long.toHexString(l);
+    }
+}
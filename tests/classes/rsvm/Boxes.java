@@ -0,0 +1,84 @@
+package rsvm;
+
+import java.lang.reflect.Method;
+
+public class Boxes {
+    private final int base;
+
+    public Boxes(int base) {
+        this.base = base;
+    }
+
+    public int add(int x) {
+        return base + x;
+    }
+
+    static int small() {
+        return 100;
+    }
+
+    static int large() {
+        return 1000;
+    }
+
+    static boolean yes() {
+        return true;
+    }
+
+    static char letter() {
+        return 'a';
+    }
+
+    static long sum(long a, Long b) {
+        return a + b;
+    }
+
+    static double half(double x) {
+        return x / 2;
+    }
+
+    static void nothing() {
+    }
+
+    static String name(String prefix) {
+        return prefix + "box";
+    }
+
+    static Object call(String name, Object target, Object... args) throws Exception {
+        for (Method method : Boxes.class.getDeclaredMethods()) {
+            if (method.getName().equals(name)) {
+                return method.invoke(target, args);
+            }
+        }
+        return null;
+    }
+
+    // the boxes of reflective results are the cached ones for small values
+    public static int identities() throws Exception {
+        int result = 0;
+        if (call("small", null) == Integer.valueOf(100)) {
+            result += 1;
+        }
+        if (call("large", null) != Integer.valueOf(1000) && call("large", null).equals(1000)) {
+            result += 10;
+        }
+        if (call("yes", null) == Boolean.TRUE && call("letter", null) == Character.valueOf('a')) {
+            result += 100;
+        }
+        if (call("nothing", null) == null && call("name", null, "a").equals("abox")) {
+            result += 1000;
+        }
+        if (call("add", new Boxes(40), 2) == Integer.valueOf(42)) {
+            result += 10000;
+        }
+        return result;
+    }
+
+    public static boolean same(Object a, Object b) {
+        return a == b;
+    }
+
+    public static long sum() throws Exception {
+        return (Long) call("sum", null, 3L, 4L);
+    }
+}
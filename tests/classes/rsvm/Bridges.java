@@ -0,0 +1,69 @@
+package rsvm;
+
+import java.lang.reflect.Method;
+
+public class Bridges {
+    static class Box<T> {
+        T value;
+
+        Box(T value) {
+            this.value = value;
+        }
+
+        T get() {
+            return value;
+        }
+    }
+
+    static class StringBox extends Box<String> {
+        StringBox(String value) {
+            super(value);
+        }
+
+        String get() {
+            return "[" + value + "]";
+        }
+    }
+
+    static class Shape {
+        Shape copy() {
+            return new Shape();
+        }
+
+        long sides() {
+            return 0;
+        }
+    }
+
+    static class Square extends Shape {
+        Square copy() {
+            return new Square();
+        }
+
+        long sides() {
+            return 4;
+        }
+    }
+
+    public static long dispatch() {
+        Box<String> box = new StringBox("a");
+        Object value = box.get();
+        Shape shape = new Square();
+        return ((String) value).length() * 10 + shape.copy().sides();
+    }
+
+    public static long declaredMethods() {
+        long result = 0;
+        for (Method method : StringBox.class.getDeclaredMethods()) {
+            if (!method.getName().equals("get")) {
+                return -1;
+            }
+            if (method.isBridge() && method.isSynthetic()) {
+                result += method.getReturnType() == Object.class ? 1 : 1000;
+            } else {
+                result += method.getReturnType() == String.class ? 10 : 1000;
+            }
+        }
+        return result;
+    }
+}
@@ -0,0 +1,28 @@
+package rsvm;
+
+public class Callbacks {
+    public interface Listener {
+        int onEvent(String name, int count);
+    }
+
+    public interface NamedListener extends Listener {
+        String name();
+
+        double weigh(long amount, double factor);
+    }
+
+    public static int runTwice(Runnable runnable) {
+        runnable.run();
+        runnable.run();
+        return 2;
+    }
+
+    public static int fire(Listener listener) {
+        return listener.onEvent("click", 3) + listener.onEvent("key", 4);
+    }
+
+    public static String describe(NamedListener listener) {
+        double weight = listener.weigh(1L << 40, 0.5);
+        return listener.name() + ":" + listener.onEvent("touch", 1) + ":" + (weight == (double) (1L << 39));
+    }
+}
@@ -0,0 +1,23 @@
+package rsvm;
+
+import java.util.concurrent.atomic.AtomicIntegerFieldUpdater;
+
+public class CallerClass {
+
+    private volatile int count;
+
+    public static long forName() throws ClassNotFoundException {
+        Class<?> cls = Class.forName("rsvm.CallerClass");
+        return cls == CallerClass.class ? 1 : 0;
+    }
+
+    public static long privateUpdater() {
+        // the updater checks the access of its caller to the private field
+        AtomicIntegerFieldUpdater<CallerClass> updater =
+                AtomicIntegerFieldUpdater.newUpdater(CallerClass.class, "count");
+        CallerClass callerClass = new CallerClass();
+        updater.incrementAndGet(callerClass);
+        updater.addAndGet(callerClass, 10);
+        return callerClass.count;
+    }
+}
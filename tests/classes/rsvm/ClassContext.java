@@ -0,0 +1,41 @@
+package rsvm;
+
+import java.security.AccessController;
+import java.security.PrivilegedAction;
+
+public class ClassContext extends SecurityManager {
+
+    static class Caller {
+        static Class<?>[] context() {
+            return new ClassContext().classes();
+        }
+    }
+
+    Class<?>[] classes() {
+        return getClassContext();
+    }
+
+    public static String context() {
+        return names(Caller.context());
+    }
+
+    public static String privilegedContext() {
+        Class<?>[] context = AccessController.doPrivileged(new PrivilegedAction<Class<?>[]>() {
+            public Class<?>[] run() {
+                return Caller.context();
+            }
+        });
+        return names(context);
+    }
+
+    private static String names(Class<?>[] classes) {
+        StringBuilder names = new StringBuilder();
+        for (Class<?> cls : classes) {
+            if (names.length() > 0) {
+                names.append(',');
+            }
+            names.append(cls.getName());
+        }
+        return names.toString();
+    }
+}
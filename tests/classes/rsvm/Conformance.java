@@ -0,0 +1,250 @@
+package rsvm;
+
+/**
+ * One method per arithmetic, comparison and conversion opcode, run by the
+ * vectors in tests/conformance/arithmetic.json, see ConformanceVectors.
+ */
+public class Conformance {
+
+    public static int iadd(int a, int b) {
+        return a + b;
+    }
+
+    public static int isub(int a, int b) {
+        return a - b;
+    }
+
+    public static int imul(int a, int b) {
+        return a * b;
+    }
+
+    public static int idiv(int a, int b) {
+        return a / b;
+    }
+
+    public static int irem(int a, int b) {
+        return a % b;
+    }
+
+    public static int ineg(int a) {
+        return -a;
+    }
+
+    public static int ishl(int a, int b) {
+        return a << b;
+    }
+
+    public static int ishr(int a, int b) {
+        return a >> b;
+    }
+
+    public static int iushr(int a, int b) {
+        return a >>> b;
+    }
+
+    public static int iand(int a, int b) {
+        return a & b;
+    }
+
+    public static int ior(int a, int b) {
+        return a | b;
+    }
+
+    public static int ixor(int a, int b) {
+        return a ^ b;
+    }
+
+    public static long ladd(long a, long b) {
+        return a + b;
+    }
+
+    public static long lsub(long a, long b) {
+        return a - b;
+    }
+
+    public static long lmul(long a, long b) {
+        return a * b;
+    }
+
+    public static long ldiv(long a, long b) {
+        return a / b;
+    }
+
+    public static long lrem(long a, long b) {
+        return a % b;
+    }
+
+    public static long lneg(long a) {
+        return -a;
+    }
+
+    public static long lshl(long a, int b) {
+        return a << b;
+    }
+
+    public static long lshr(long a, int b) {
+        return a >> b;
+    }
+
+    public static long lushr(long a, int b) {
+        return a >>> b;
+    }
+
+    public static long land(long a, long b) {
+        return a & b;
+    }
+
+    public static long lor(long a, long b) {
+        return a | b;
+    }
+
+    public static long lxor(long a, long b) {
+        return a ^ b;
+    }
+
+    public static int lcmp(long a, long b) {
+        if (a > b) {
+            return 1;
+        }
+        if (a == b) {
+            return 0;
+        }
+        return -1;
+    }
+
+    public static float fadd(float a, float b) {
+        return a + b;
+    }
+
+    public static float fsub(float a, float b) {
+        return a - b;
+    }
+
+    public static float fmul(float a, float b) {
+        return a * b;
+    }
+
+    public static float fdiv(float a, float b) {
+        return a / b;
+    }
+
+    public static float frem(float a, float b) {
+        return a % b;
+    }
+
+    public static float fneg(float a) {
+        return -a;
+    }
+
+    /** `>` compiles to fcmpl and `<` to fcmpg, so NaN falls through both. */
+    public static int fcmp(float a, float b) {
+        if (a > b) {
+            return 1;
+        }
+        if (a == b) {
+            return 0;
+        }
+        if (a < b) {
+            return -1;
+        }
+        return 2;
+    }
+
+    public static double dadd(double a, double b) {
+        return a + b;
+    }
+
+    public static double dsub(double a, double b) {
+        return a - b;
+    }
+
+    public static double dmul(double a, double b) {
+        return a * b;
+    }
+
+    public static double ddiv(double a, double b) {
+        return a / b;
+    }
+
+    public static double drem(double a, double b) {
+        return a % b;
+    }
+
+    public static double dneg(double a) {
+        return -a;
+    }
+
+    /** `>` compiles to dcmpl and `<` to dcmpg, so NaN falls through both. */
+    public static int dcmp(double a, double b) {
+        if (a > b) {
+            return 1;
+        }
+        if (a == b) {
+            return 0;
+        }
+        if (a < b) {
+            return -1;
+        }
+        return 2;
+    }
+
+    public static long i2l(int a) {
+        return a;
+    }
+
+    public static float i2f(int a) {
+        return a;
+    }
+
+    public static double i2d(int a) {
+        return a;
+    }
+
+    public static int i2b(int a) {
+        return (byte) a;
+    }
+
+    public static int i2c(int a) {
+        return (char) a;
+    }
+
+    public static int i2s(int a) {
+        return (short) a;
+    }
+
+    public static int l2i(long a) {
+        return (int) a;
+    }
+
+    public static float l2f(long a) {
+        return a;
+    }
+
+    public static double l2d(long a) {
+        return a;
+    }
+
+    public static int f2i(float a) {
+        return (int) a;
+    }
+
+    public static long f2l(float a) {
+        return (long) a;
+    }
+
+    public static double f2d(float a) {
+        return a;
+    }
+
+    public static int d2i(double a) {
+        return (int) a;
+    }
+
+    public static long d2l(double a) {
+        return (long) a;
+    }
+
+    public static float d2f(double a) {
+        return (float) a;
+    }
+}
@@ -0,0 +1,149 @@
+package rsvm;
+
+import java.lang.reflect.InvocationTargetException;
+import java.lang.reflect.Method;
+import java.util.ArrayList;
+import java.util.Arrays;
+import java.util.Comparator;
+import java.util.List;
+
+/**
+ * Prints the vectors of tests/conformance/arithmetic.json, running every
+ * method of Conformance on the combinations of edge case operands. Run it on
+ * a reference JVM after changing Conformance:
+ *
+ * <pre>
+ * java -cp tests/classes rsvm.ConformanceVectors &gt; tests/conformance/arithmetic.json
+ * </pre>
+ *
+ * Floats and doubles are written as their raw bits, which keeps NaNs and
+ * negative zeros exact. Operands that throw, the divisions by zero, are
+ * left out.
+ */
+public class ConformanceVectors {
+
+    private static final int[] INTS = {
+        0, 1, -1, 2, -7, 31, 32, 33, -33, 0x12345678, 0xCAFE, 0x8000,
+        Integer.MAX_VALUE, Integer.MIN_VALUE,
+    };
+
+    private static final long[] LONGS = {
+        0L, 1L, -1L, 2L, -7L, 63L, 64L, 0x123456789ABCDEFL, 0x80000000L,
+        Integer.MIN_VALUE, Long.MAX_VALUE, Long.MIN_VALUE,
+    };
+
+    private static final float[] FLOATS = {
+        0.0f, -0.0f, 1.0f, -1.0f, 0.5f, 2.5f, -2.5f, 3.7f, 1e10f, -1e20f,
+        Float.MIN_VALUE, Float.MAX_VALUE, Float.POSITIVE_INFINITY,
+        Float.NEGATIVE_INFINITY, Float.NaN,
+    };
+
+    private static final double[] DOUBLES = {
+        0.0, -0.0, 1.0, -1.0, 0.5, 2.5, -2.5, 3.7, 1e10, -1e20, 1e300,
+        2147483647.5, -9.3e18, Double.MIN_VALUE, Double.MAX_VALUE,
+        Double.POSITIVE_INFINITY, Double.NEGATIVE_INFINITY, Double.NaN,
+    };
+
+    public static void main(String[] args) throws Exception {
+        Method[] methods = Conformance.class.getDeclaredMethods();
+        Arrays.sort(methods, new Comparator<Method>() {
+            @Override
+            public int compare(Method a, Method b) {
+                return a.getName().compareTo(b.getName());
+            }
+        });
+        StringBuilder json = new StringBuilder("[\n");
+        String separator = "";
+        for (Method method : methods) {
+            String descriptor = descriptor(method);
+            for (Object[] operands : combinations(method.getParameterTypes(), 0)) {
+                Object result;
+                try {
+                    result = method.invoke(null, operands);
+                } catch (InvocationTargetException e) {
+                    continue;
+                }
+                json.append(separator);
+                json.append("  {\"method\": \"").append(method.getName());
+                json.append("\", \"descriptor\": \"").append(descriptor);
+                json.append("\", \"args\": [");
+                for (int i = 0; i < operands.length; i++) {
+                    json.append(i == 0 ? "" : ", ").append(encode(operands[i]));
+                }
+                json.append("], \"result\": ").append(encode(result)).append("}");
+                separator = ",\n";
+            }
+        }
+        json.append("\n]");
+        System.out.println(json);
+    }
+
+    private static List<Object[]> combinations(Class<?>[] types, int index) {
+        List<Object[]> result = new ArrayList<Object[]>();
+        if (index == types.length) {
+            result.add(new Object[types.length]);
+            return result;
+        }
+        for (Object[] rest : combinations(types, index + 1)) {
+            for (Object operand : operands(types[index])) {
+                Object[] combination = rest.clone();
+                combination[index] = operand;
+                result.add(combination);
+            }
+        }
+        return result;
+    }
+
+    private static List<Object> operands(Class<?> type) {
+        List<Object> result = new ArrayList<Object>();
+        if (type == int.class) {
+            for (int value : INTS) {
+                result.add(value);
+            }
+        } else if (type == long.class) {
+            for (long value : LONGS) {
+                result.add(value);
+            }
+        } else if (type == float.class) {
+            for (float value : FLOATS) {
+                result.add(value);
+            }
+        } else {
+            for (double value : DOUBLES) {
+                result.add(value);
+            }
+        }
+        return result;
+    }
+
+    private static String encode(Object value) {
+        if (value instanceof Float) {
+            return String.valueOf(Float.floatToRawIntBits((Float) value));
+        }
+        if (value instanceof Double) {
+            return String.valueOf(Double.doubleToRawLongBits((Double) value));
+        }
+        return String.valueOf(value);
+    }
+
+    private static String descriptor(Method method) {
+        StringBuilder descriptor = new StringBuilder("(");
+        for (Class<?> type : method.getParameterTypes()) {
+            descriptor.append(descriptor(type));
+        }
+        return descriptor.append(')').append(descriptor(method.getReturnType())).toString();
+    }
+
+    private static char descriptor(Class<?> type) {
+        if (type == int.class) {
+            return 'I';
+        }
+        if (type == long.class) {
+            return 'J';
+        }
+        if (type == float.class) {
+            return 'F';
+        }
+        return 'D';
+    }
+}
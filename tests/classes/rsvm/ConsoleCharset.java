@@ -0,0 +1,21 @@
+package rsvm;
+
+import java.io.Console;
+
+public class ConsoleCharset {
+
+    public static long printNonAscii() {
+        System.out.println("h\u00e9llo w\u00f6rld \u2713");
+        System.err.println("\u00fcber \u2192 stderr");
+        return 1;
+    }
+
+    public static long hasConsole() {
+        Console console = System.console();
+        return console == null ? 0 : 1;
+    }
+
+    public static String stdoutEncoding() {
+        return System.getProperty("sun.stdout.encoding");
+    }
+}
@@ -0,0 +1,62 @@
+package rsvm;
+
+public class Constants {
+    public static final int INT_CONST = Integer.MAX_VALUE;
+    public static final long LONG_CONST = Long.MIN_VALUE;
+    public static final double DOUBLE_CONST = Math.PI;
+    public static final char CHAR_CONST = '\u20ac';
+    public static final byte BYTE_CONST = -2;
+    public static final String STRING_CONST = "rsvm";
+
+    public static float floatNaN() {
+        return Float.NaN;
+    }
+
+    public static float floatPositiveInfinity() {
+        return Float.POSITIVE_INFINITY;
+    }
+
+    public static float floatNegativeInfinity() {
+        return Float.NEGATIVE_INFINITY;
+    }
+
+    public static float floatMinValue() {
+        return Float.MIN_VALUE;
+    }
+
+    public static float floatPi() {
+        return 3.1415927f;
+    }
+
+    public static double doubleNaN() {
+        return Double.NaN;
+    }
+
+    public static double doublePositiveInfinity() {
+        return Double.POSITIVE_INFINITY;
+    }
+
+    public static double doubleNegativeInfinity() {
+        return Double.NEGATIVE_INFINITY;
+    }
+
+    public static double doubleMinValue() {
+        return Double.MIN_VALUE;
+    }
+
+    public static double doubleMaxValue() {
+        return Double.MAX_VALUE;
+    }
+
+    public static double doublePi() {
+        return 3.141592653589793;
+    }
+
+    public static long longLowBytesSigned() {
+        return 0x1234567880000000L;
+    }
+
+    public static long longMinValue() {
+        return Long.MIN_VALUE;
+    }
+}
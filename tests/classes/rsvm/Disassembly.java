@@ -0,0 +1,49 @@
+package rsvm;
+
+public class Disassembly {
+    private long total;
+
+    public static int add(int a, int b) {
+        return a + b;
+    }
+
+    static String describe(int kind) {
+        switch (kind) {
+            case 0:
+                return "zero";
+            case 1:
+                return "one";
+            case 2:
+                return "two";
+            default:
+                return "many";
+        }
+    }
+
+    static int lookup(int key) {
+        switch (key) {
+            case 1:
+                return 10;
+            case 1000:
+                return 20;
+            default:
+                return -1;
+        }
+    }
+
+    void accumulate(int[] values) {
+        for (int idx = 0; idx < values.length; idx++) {
+            total += values[idx] * 300L;
+        }
+    }
+
+    static int parse(String value) {
+        try {
+            return Integer.parseInt(value);
+        } catch (NumberFormatException e) {
+            return 0;
+        }
+    }
+
+    static native void nothing();
+}
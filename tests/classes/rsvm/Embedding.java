@@ -0,0 +1,58 @@
+package rsvm;
+
+public class Embedding {
+    public static String greet(String name) {
+        return "Hello, " + name + "!";
+    }
+
+    public static int[] doubled(int[] values) {
+        int[] result = new int[values.length];
+        for (int i = 0; i < values.length; i++) {
+            result[i] = values[i] * 2;
+        }
+        return result;
+    }
+
+    public static double sum(double[] values) {
+        double result = 0;
+        for (double value : values) {
+            result += value;
+        }
+        return result;
+    }
+
+    public static String repeat(String value, int times, char separator) {
+        StringBuilder result = new StringBuilder();
+        for (int i = 0; i < times; i++) {
+            if (i > 0) {
+                result.append(separator);
+            }
+            result.append(value);
+        }
+        return result.toString();
+    }
+
+    public static double scale(int value, double factor, boolean negate) {
+        return negate ? -value * factor : value * factor;
+    }
+
+    public static Object identity(Object value) {
+        return value;
+    }
+
+    public static Integer boxedSum(Integer a, Long b) {
+        return (int) (a + b);
+    }
+
+    public static boolean isNull(Object value) {
+        return value == null;
+    }
+
+    public static long[] reversed(long[] values) {
+        long[] result = new long[values.length];
+        for (int i = 0; i < values.length; i++) {
+            result[values.length - 1 - i] = values[i];
+        }
+        return result;
+    }
+}
@@ -0,0 +1,26 @@
+package rsvm;
+
+public class FieldAccess {
+    public static int counter = 7;
+    public static String label = "initial";
+    public static volatile long ticks;
+
+    public boolean enabled;
+    public char initial = 'r';
+    public short small = -3;
+    public long big = 1L << 50;
+    public double ratio = 0.25;
+    public Object payload;
+
+    public static FieldAccess create() {
+        return new FieldAccess();
+    }
+
+    public static int counterPlus(int value) {
+        return counter + value;
+    }
+
+    public static String describe(FieldAccess obj) {
+        return obj.enabled + ":" + obj.initial + ":" + obj.small + ":" + obj.payload;
+    }
+}
@@ -0,0 +1,94 @@
+package rsvm;
+
+public class FieldKinds {
+    public boolean z;
+    public byte b;
+    public char c;
+    public short s;
+    public int i;
+    public float f;
+    public long j;
+    public double d;
+    public Object l;
+
+    public static boolean sz;
+    public static byte sb;
+    public static char sc;
+    public static short ss;
+    public static int si;
+    public static float sf;
+    public static long sj;
+    public static double sd;
+    public static Object sl;
+
+    public static FieldKinds create() {
+        return new FieldKinds();
+    }
+
+    public static long booleans() {
+        FieldKinds k = new FieldKinds();
+        k.z = true;
+        sz = false;
+        return (k.z ? 1 : 0) + (sz ? 2 : 0) + (!sz ? 4 : 0);
+    }
+
+    public static long bytes() {
+        FieldKinds k = new FieldKinds();
+        k.b = -100;
+        sb = -28;
+        return k.b + sb;
+    }
+
+    public static long chars() {
+        FieldKinds k = new FieldKinds();
+        k.c = (char) 0xfedc;
+        sc = (char) 0x8001;
+        return k.c + sc;
+    }
+
+    public static long shorts() {
+        FieldKinds k = new FieldKinds();
+        k.s = -30000;
+        ss = -2000;
+        return k.s + ss;
+    }
+
+    public static long ints() {
+        FieldKinds k = new FieldKinds();
+        k.i = Integer.MIN_VALUE;
+        si = -8;
+        return (long) k.i + si;
+    }
+
+    public static long floats() {
+        FieldKinds k = new FieldKinds();
+        k.f = -1.5f;
+        sf = 0.25f;
+        return (long) ((k.f + sf) * 4);
+    }
+
+    public static long longs() {
+        FieldKinds k = new FieldKinds();
+        k.j = Long.MIN_VALUE;
+        sj = 1;
+        return k.j + sj;
+    }
+
+    public static long doubles() {
+        FieldKinds k = new FieldKinds();
+        k.d = -2.25;
+        sd = 0.5;
+        return (long) ((k.d + sd) * 4);
+    }
+
+    public static long references() {
+        FieldKinds k = new FieldKinds();
+        k.l = "a";
+        sl = "bc";
+        return ((String) k.l).length() * 10 + ((String) sl).length();
+    }
+
+    public static String describe(FieldKinds k) {
+        return k.z + ":" + k.b + ":" + (int) k.c + ":" + k.s + ":" + sb + ":" + ss;
+    }
+}
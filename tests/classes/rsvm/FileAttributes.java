@@ -0,0 +1,23 @@
+package rsvm;
+
+import java.io.File;
+import java.io.IOException;
+
+public class FileAttributes {
+
+    /**
+     * exists(), isFile(), isDirectory() and isHidden() of the file as the
+     * digits of a number, e.g. 1100 for a regular file.
+     */
+    public static long attributes(String path) {
+        File file = new File(path);
+        return (file.exists() ? 1000 : 0)
+                + (file.isFile() ? 100 : 0)
+                + (file.isDirectory() ? 10 : 0)
+                + (file.isHidden() ? 1 : 0);
+    }
+
+    public static String canonicalPath(String path) throws IOException {
+        return new File(path).getCanonicalPath();
+    }
+}
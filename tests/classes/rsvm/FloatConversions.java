@@ -0,0 +1,48 @@
+package rsvm;
+
+public class FloatConversions {
+
+    public static int f2i(float value) {
+        return (int) value;
+    }
+
+    public static long f2l(float value) {
+        return (long) value;
+    }
+
+    public static int d2i(double value) {
+        return (int) value;
+    }
+
+    public static long d2l(double value) {
+        return (long) value;
+    }
+
+    public static float d2f(double value) {
+        return (float) value;
+    }
+
+    public static int floatToRawIntBits(float value) {
+        return Float.floatToRawIntBits(value);
+    }
+
+    public static int floatToIntBits(float value) {
+        return Float.floatToIntBits(value);
+    }
+
+    public static float intBitsToFloat(int bits) {
+        return Float.intBitsToFloat(bits);
+    }
+
+    public static long doubleToRawLongBits(double value) {
+        return Double.doubleToRawLongBits(value);
+    }
+
+    public static long doubleToLongBits(double value) {
+        return Double.doubleToLongBits(value);
+    }
+
+    public static double longBitsToDouble(long bits) {
+        return Double.longBitsToDouble(bits);
+    }
+}
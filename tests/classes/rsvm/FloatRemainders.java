@@ -0,0 +1,20 @@
+package rsvm;
+
+public class FloatRemainders {
+
+    public static float frem(float dividend, float divisor) {
+        return dividend % divisor;
+    }
+
+    public static double drem(double dividend, double divisor) {
+        return dividend % divisor;
+    }
+
+    public static float fdiv(float dividend, float divisor) {
+        return dividend / divisor;
+    }
+
+    public static double ddiv(double dividend, double divisor) {
+        return dividend / divisor;
+    }
+}
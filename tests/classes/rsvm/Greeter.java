@@ -0,0 +1,16 @@
+package rsvm;
+
+public class Greeter {
+
+    public static String greeting() {
+        return "hello";
+    }
+
+    public static int doubled(int x) {
+        return Greeter.twice(x);
+    }
+
+    static int twice(int x) {
+        return x * 2;
+    }
+}
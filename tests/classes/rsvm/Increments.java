@@ -0,0 +1,67 @@
+package rsvm;
+
+public class Increments {
+
+    public static int countDown(int from) {
+        // a backward branch and an increment of -1
+        int steps = 0;
+        for (int i = from; i > 0; i--) {
+            steps++;
+        }
+        return steps;
+    }
+
+    public static int negativeIncrements() {
+        int value = 0;
+        value -= 5;
+        value += -128;
+        return value;
+    }
+
+    public static int wideIncrements() {
+        // beyond a byte, the increments take the wide form
+        int value = 0;
+        value += 1000;
+        value -= 30000;
+        value += 200;
+        return value;
+    }
+
+    public static int overflowingIncrement() {
+        int value = Integer.MAX_VALUE;
+        value += 1;
+        return value;
+    }
+
+    public static int sumBackward(int[] values) {
+        int sum = 0;
+        int i = values.length - 1;
+        while (i >= 0) {
+            sum += values[i];
+            i -= 2;
+        }
+        return sum;
+    }
+
+    public static long wideLocals() {
+        // a local index beyond 255 takes the wide load, store and iinc
+        long l0 = 0, l1 = 0, l2 = 0, l3 = 0, l4 = 0, l5 = 0, l6 = 0, l7 = 0, l8 = 0, l9 = 0;
+        long m0 = 0, m1 = 0, m2 = 0, m3 = 0, m4 = 0, m5 = 0, m6 = 0, m7 = 0, m8 = 0, m9 = 0;
+        long n0 = 0, n1 = 0, n2 = 0, n3 = 0, n4 = 0, n5 = 0, n6 = 0, n7 = 0, n8 = 0, n9 = 0;
+        long o0 = 0, o1 = 0, o2 = 0, o3 = 0, o4 = 0, o5 = 0, o6 = 0, o7 = 0, o8 = 0, o9 = 0;
+        long p0 = 0, p1 = 0, p2 = 0, p3 = 0, p4 = 0, p5 = 0, p6 = 0, p7 = 0, p8 = 0, p9 = 0;
+        long q0 = 0, q1 = 0, q2 = 0, q3 = 0, q4 = 0, q5 = 0, q6 = 0, q7 = 0, q8 = 0, q9 = 0;
+        long r0 = 0, r1 = 0, r2 = 0, r3 = 0, r4 = 0, r5 = 0, r6 = 0, r7 = 0, r8 = 0, r9 = 0;
+        long s0 = 0, s1 = 0, s2 = 0, s3 = 0, s4 = 0, s5 = 0, s6 = 0, s7 = 0, s8 = 0, s9 = 0;
+        long t0 = 0, t1 = 0, t2 = 0, t3 = 0, t4 = 0, t5 = 0, t6 = 0, t7 = 0, t8 = 0, t9 = 0;
+        long u0 = 0, u1 = 0, u2 = 0, u3 = 0, u4 = 0, u5 = 0, u6 = 0, u7 = 0, u8 = 0, u9 = 0;
+        long v0 = 0, v1 = 0, v2 = 0, v3 = 0, v4 = 0, v5 = 0, v6 = 0, v7 = 0, v8 = 0, v9 = 0;
+        long w0 = 0, w1 = 0, w2 = 0, w3 = 0, w4 = 0, w5 = 0, w6 = 0, w7 = 0, w8 = 0, w9 = 0;
+        long x0 = 0, x1 = 0, x2 = 0, x3 = 0, x4 = 0, x5 = 0, x6 = 0, x7 = 0, x8 = 0, x9 = 0;
+        int wide = 7;
+        for (int i = 3; i > 0; i--) {
+            wide -= 3;
+        }
+        return wide + l0 + m0 + n0 + o0 + p0 + q0 + r0 + s0 + t0 + u0 + v0 + w0 + x0;
+    }
+}
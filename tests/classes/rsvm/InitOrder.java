@@ -0,0 +1,40 @@
+package rsvm;
+
+public class InitOrder {
+    static long order;
+
+    static int record(int id) {
+        order = order * 10 + id;
+        return id;
+    }
+
+    static class First {
+        static int first = record(1);
+    }
+
+    static class Second extends First {
+        static int second = record(2);
+    }
+
+    static class Third extends Second {
+        static int third = record(3);
+    }
+
+    interface Marker {
+        int MARKER = record(9);
+    }
+
+    static class Marked extends First implements Marker {
+        static int marked = record(4);
+    }
+
+    public static long initThird() {
+        order = 0;
+        return Third.third == 3 ? order : -1;
+    }
+
+    public static long initMarked() {
+        order = 0;
+        return Marked.marked == 4 ? order : -1;
+    }
+}
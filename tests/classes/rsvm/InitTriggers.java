@@ -0,0 +1,81 @@
+package rsvm;
+
+public class InitTriggers {
+    static long triggered;
+
+    static int record(int id) {
+        triggered = triggered * 10 + id;
+        return id;
+    }
+
+    static class StaticBase {
+        static int base = record(1);
+
+        static int twice(int value) {
+            return value * 2;
+        }
+    }
+
+    static class StaticSub extends StaticBase {
+        static int sub = record(2);
+    }
+
+    static class GetBase {
+        static int value = record(3);
+    }
+
+    static class GetSub extends GetBase {
+        static int sub = record(4);
+    }
+
+    static class PutBase {
+        static int value;
+        static int base = record(5);
+    }
+
+    static class PutSub extends PutBase {
+        static int sub = record(6);
+    }
+
+    interface Values {
+        int VALUE = record(7);
+    }
+
+    static class ValuesImpl implements Values {
+        static int impl = record(8);
+    }
+
+    static class NewBase {
+        static int base = record(1);
+    }
+
+    static class NewSub extends NewBase {
+        static int sub = record(2);
+    }
+
+    public static long invokeInherited() {
+        triggered = 0;
+        return StaticSub.twice(2) == 4 ? triggered : -1;
+    }
+
+    public static long getInherited() {
+        triggered = 0;
+        return GetSub.value == 3 ? triggered : -1;
+    }
+
+    public static long putInherited() {
+        triggered = 0;
+        PutSub.value = 1;
+        return triggered;
+    }
+
+    public static long getInterfaceField() {
+        triggered = 0;
+        return ValuesImpl.VALUE == 7 ? triggered : -1;
+    }
+
+    public static long newInstance() {
+        triggered = 0;
+        return new NewSub() != null ? triggered : -1;
+    }
+}
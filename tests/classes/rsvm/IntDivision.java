@@ -0,0 +1,20 @@
+package rsvm;
+
+public class IntDivision {
+
+    public static int idiv(int dividend, int divisor) {
+        return dividend / divisor;
+    }
+
+    public static int irem(int dividend, int divisor) {
+        return dividend % divisor;
+    }
+
+    public static long ldiv(long dividend, long divisor) {
+        return dividend / divisor;
+    }
+
+    public static long lrem(long dividend, long divisor) {
+        return dividend % divisor;
+    }
+}
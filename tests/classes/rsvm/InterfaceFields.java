@@ -0,0 +1,21 @@
+package rsvm;
+
+public class InterfaceFields {
+    public static int readInheritedConstants() {
+        return Impl.VALUES.length + Sub.VALUES[2] + Sub.NAME.length();
+    }
+
+    interface Base {
+        int[] VALUES = { 1, 2, 30 };
+    }
+
+    interface Derived extends Base {
+        String NAME = new String("derived");
+    }
+
+    static class Impl implements Derived {
+    }
+
+    static class Sub extends Impl {
+    }
+}
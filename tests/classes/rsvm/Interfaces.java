@@ -0,0 +1,88 @@
+package rsvm;
+
+public class Interfaces {
+    interface First {
+        long first();
+
+        long shared();
+    }
+
+    interface Second {
+        long second();
+
+        long shared();
+    }
+
+    interface Third {
+        long third();
+
+        long shared();
+
+        long first();
+    }
+
+    interface Fourth extends Second {
+        long fourth();
+    }
+
+    interface Wide {
+        long mix(long a, int b, double c, Object[] d);
+    }
+
+    static class Impl implements First, Second, Third, Wide {
+        public long first() {
+            return 1;
+        }
+
+        public long second() {
+            return 20;
+        }
+
+        public long third() {
+            return 300;
+        }
+
+        public long shared() {
+            return 4000;
+        }
+
+        public long mix(long a, int b, double c, Object[] d) {
+            return a + b + (long) c + d.length;
+        }
+    }
+
+    static class Sub extends Impl implements Fourth {
+        public long second() {
+            return 50000;
+        }
+
+        public long fourth() {
+            return 600000;
+        }
+    }
+
+    static long callAll(First first, Second second, Third third) {
+        return first.first() + first.shared() + second.second() + second.shared() + third.third()
+                + third.shared() + third.first();
+    }
+
+    public static long callImpl() {
+        Impl impl = new Impl();
+        return callAll(impl, impl, impl);
+    }
+
+    public static long callSub() {
+        Sub sub = new Sub();
+        Fourth fourth = sub;
+        return callAll(sub, sub, sub) + fourth.fourth() + fourth.second();
+    }
+
+    public static long callWide() {
+        Wide wide = new Sub();
+        return wide.mix(1000000L, 2000, 30.5, new Object[4]);
+    }
+
+    public static Object newSub() {
+        return new Sub();
+    }
+}
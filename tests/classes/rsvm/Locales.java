@@ -0,0 +1,14 @@
+package rsvm;
+
+import java.util.Locale;
+
+public class Locales {
+
+    public static String language() {
+        return Locale.getDefault().getLanguage();
+    }
+
+    public static String country() {
+        return Locale.getDefault().getCountry();
+    }
+}
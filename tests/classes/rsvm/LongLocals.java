@@ -0,0 +1,54 @@
+package rsvm;
+
+public class LongLocals {
+
+    public static long afterDouble() {
+        // istore_0, dstore_1 and lstore_3, so the long starts right after
+        // the two slots of the double
+        int i = 7;
+        double d = 2.5;
+        long l = 40L;
+        l += i;
+        d *= i;
+        return l + (long) d;
+    }
+
+    public static long interleaved(int a, long b, int c, double d, long e) {
+        // the parameters take the slots 0, 1-2, 3, 4-5 and 6-7
+        long sum = b + e;
+        int product = a * c;
+        double scaled = d * product;
+        long mixed = sum - product;
+        return mixed + (long) scaled + a + c;
+    }
+
+    public static int reusedSlots(int n) {
+        // the slots of a long going out of scope take ints and a reference
+        int total = 0;
+        for (int k = 0; k < n; k++) {
+            long wide = (long) k << 33;
+            total += (int) (wide >>> 33);
+        }
+        {
+            int a = 5;
+            String s = "six";
+            total += a * s.length();
+        }
+        {
+            double d = 0.5;
+            total += (int) (d * 4);
+        }
+        return total;
+    }
+
+    public static long highHalves() {
+        // the high and low words of each value must not leak into the
+        // neighbouring locals
+        long a = 0xffffffff00000000L;
+        int b = -1;
+        long c = 0x00000000ffffffffL;
+        double d = -0.0;
+        int e = 0;
+        return (a ^ c) + b + e + (Double.doubleToRawLongBits(d) >>> 63);
+    }
+}
@@ -0,0 +1,32 @@
+package rsvm;
+
+import java.lang.management.ManagementFactory;
+import java.lang.management.MemoryUsage;
+
+public class Management {
+
+    public static long heapUsed() {
+        MemoryUsage usage = ManagementFactory.getMemoryMXBean().getHeapMemoryUsage();
+        return usage.getUsed() <= usage.getCommitted() ? usage.getUsed() : -1;
+    }
+
+    public static long nonHeapCommitted() {
+        return ManagementFactory.getMemoryMXBean().getNonHeapMemoryUsage().getCommitted();
+    }
+
+    public static int threadCount() {
+        return ManagementFactory.getThreadMXBean().getThreadCount();
+    }
+
+    public static int allThreadIds() {
+        return ManagementFactory.getThreadMXBean().getAllThreadIds().length;
+    }
+
+    public static long uptime() {
+        return ManagementFactory.getRuntimeMXBean().getUptime();
+    }
+
+    public static int loadedClassCount() {
+        return ManagementFactory.getClassLoadingMXBean().getLoadedClassCount();
+    }
+}
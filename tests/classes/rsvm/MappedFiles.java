@@ -0,0 +1,44 @@
+package rsvm;
+
+import java.io.IOException;
+import java.io.RandomAccessFile;
+import java.nio.MappedByteBuffer;
+import java.nio.channels.FileChannel;
+
+public class MappedFiles {
+
+    /**
+     * Writes the ASCII text at the position of the file through a mapping,
+     * growing the file if it is shorter.
+     */
+    public static void write(String path, long position, String text) throws IOException {
+        RandomAccessFile file = new RandomAccessFile(path, "rw");
+        try {
+            MappedByteBuffer buf = file.getChannel()
+                    .map(FileChannel.MapMode.READ_WRITE, position, text.length());
+            for (int i = 0; i < text.length(); i++) {
+                buf.put((byte) text.charAt(i));
+            }
+            buf.force();
+        } finally {
+            file.close();
+        }
+    }
+
+    /** Reads the whole file as ASCII through a read-only mapping. */
+    public static String read(String path) throws IOException {
+        RandomAccessFile file = new RandomAccessFile(path, "r");
+        try {
+            FileChannel channel = file.getChannel();
+            MappedByteBuffer buf = channel.map(FileChannel.MapMode.READ_ONLY, 0, channel.size());
+            buf.load();
+            StringBuilder text = new StringBuilder();
+            while (buf.hasRemaining()) {
+                text.append((char) buf.get());
+            }
+            return text.toString();
+        } finally {
+            file.close();
+        }
+    }
+}
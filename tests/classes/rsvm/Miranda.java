@@ -0,0 +1,55 @@
+package rsvm;
+
+public class Miranda {
+    interface Named {
+        long name();
+    }
+
+    interface Sized extends Named {
+        long size();
+    }
+
+    interface Counted {
+        // gives the interface a <clinit>, which is not dispatched
+        Object LOCK = new Object();
+
+        long count();
+    }
+
+    static abstract class Counter implements Counted {
+    }
+
+    static class Fixed extends Counter {
+        public long count() {
+            return 50000;
+        }
+    }
+
+    static abstract class Base implements Sized {
+        long total() {
+            return name() + size();
+        }
+    }
+
+    static class Impl extends Base {
+        public long name() {
+            return 1;
+        }
+
+        public long size() {
+            return 20;
+        }
+    }
+
+    static class Larger extends Impl {
+        public long size() {
+            return 300;
+        }
+    }
+
+    public static long total() {
+        Named named = new Larger();
+        Counted counted = new Fixed();
+        return new Impl().total() + new Larger().total() + named.name() * 4000 + counted.count();
+    }
+}
@@ -0,0 +1,16 @@
+package rsvm;
+
+public class MissingFields {
+    static class Holder {
+        static int presentStatic = 3;
+        int presentField = 4;
+    }
+
+    public static int readStatic() {
+        return Holder.presentStatic;
+    }
+
+    public static int readField() {
+        return new Holder().presentField;
+    }
+}
@@ -0,0 +1,59 @@
+package rsvm;
+
+import java.lang.reflect.Modifier;
+
+public class Modifiers {
+    private static final class Hidden {
+    }
+
+    protected interface Shape {
+    }
+
+    class Inner {
+    }
+
+    private static volatile transient int count;
+
+    public static int hiddenModifiers() {
+        return Hidden.class.getModifiers();
+    }
+
+    public static int shapeModifiers() {
+        return Shape.class.getModifiers();
+    }
+
+    public static int innerModifiers() {
+        return Inner.class.getModifiers();
+    }
+
+    public static int topLevelModifiers() {
+        return Modifiers.class.getModifiers();
+    }
+
+    public static int arrayModifiers() {
+        return Hidden[][].class.getModifiers();
+    }
+
+    public static int primitiveModifiers() {
+        return int.class.getModifiers();
+    }
+
+    public static int fieldModifiers() throws Exception {
+        return Modifiers.class.getDeclaredField("count").getModifiers();
+    }
+
+    public static int methodModifiers() throws Exception {
+        return Modifiers.class.getDeclaredMethod("hiddenModifiers").getModifiers();
+    }
+
+    // a Java method the VM runs as a builtin native
+    public static int builtinModifiers() throws Exception {
+        return Integer.class.getDeclaredMethod("bitCount", int.class).getModifiers();
+    }
+
+    public static boolean isNative() throws Exception {
+        return Modifier.isNative(Modifiers.class.getDeclaredMethod("hash").getModifiers());
+    }
+
+    private static native synchronized int hash();
+}
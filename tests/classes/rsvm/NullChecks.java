@@ -0,0 +1,63 @@
+package rsvm;
+
+public class NullChecks {
+    private int value;
+    private NullChecks next;
+    private boolean flag = true;
+    private byte small = -2;
+    private char letter = '\uffff';
+    private short medium = -3;
+    private float ratio = -0.5f;
+    private long large = -(1L << 40);
+    private double precise = 0.25;
+
+    NullChecks(int value) {
+        this.value = value;
+    }
+
+    int value() {
+        return value;
+    }
+
+    public static long walk(int steps) {
+        NullChecks first = new NullChecks(1);
+        first.next = new NullChecks(2);
+        first.next.next = first;
+        NullChecks current = first;
+        long sum = 0;
+        for (int i = 0; i < steps; i++) {
+            sum += current.value + current.value();
+            current = current.next;
+        }
+        return sum;
+    }
+
+    public static double fields() {
+        NullChecks checks = new NullChecks(-4);
+        double sum = checks.flag ? 1 : 0;
+        sum += checks.small;
+        sum += checks.letter;
+        sum += checks.medium;
+        sum += checks.value;
+        sum += checks.ratio;
+        sum += checks.large;
+        sum += checks.precise;
+        return sum;
+    }
+
+    static int valueOf(NullChecks checks) {
+        return checks.value;
+    }
+
+    static int callValue(NullChecks checks) {
+        return checks.value();
+    }
+
+    public static int fieldOfNull() {
+        return valueOf(null);
+    }
+
+    public static int methodOfNull() {
+        return callValue(null);
+    }
+}
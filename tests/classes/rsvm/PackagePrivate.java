@@ -0,0 +1,26 @@
+package rsvm;
+
+import rsvm.pkg.Overrider;
+
+public class PackagePrivate {
+    long value() {
+        return 1;
+    }
+
+    public long callValue() {
+        return value();
+    }
+
+    public static long callAll() {
+        Overrider other = new Overrider();
+        Overrider same = new SamePackage();
+        return other.callValue() + same.callValue() * 10 + other.ownValue() * 100
+                + same.ownValue() * 1000;
+    }
+}
+
+class SamePackage extends Overrider {
+    long value() {
+        return 3;
+    }
+}
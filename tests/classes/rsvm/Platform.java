@@ -0,0 +1,8 @@
+package rsvm;
+
+public class Platform {
+
+    public static String property(String key) {
+        return System.getProperty(key);
+    }
+}
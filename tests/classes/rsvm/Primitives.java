@@ -0,0 +1,43 @@
+package rsvm;
+
+import java.lang.reflect.Method;
+
+public class Primitives {
+    private double ratio;
+
+    static long sum(int a, long[] b, boolean c, char d) {
+        return a + b.length;
+    }
+
+    static void nothing() {
+    }
+
+    public static boolean sameClasses() {
+        return Integer.TYPE == int.class && Long.TYPE == long.class && Boolean.TYPE == boolean.class
+                && Byte.TYPE == byte.class && Character.TYPE == char.class && Short.TYPE == short.class
+                && Float.TYPE == float.class && Double.TYPE == double.class && Void.TYPE == void.class;
+    }
+
+    public static int reflectedTypes() throws Exception {
+        int result = 0;
+        Method sum = Primitives.class.getDeclaredMethod("sum", int.class, long[].class, boolean.class,
+                char.class);
+        Class<?>[] params = sum.getParameterTypes();
+        if (params[0] == Integer.TYPE && params[2] == Boolean.TYPE && params[3] == Character.TYPE) {
+            result += 1;
+        }
+        if (params[1].getComponentType() == Long.TYPE && sum.getReturnType() == Long.TYPE) {
+            result += 10;
+        }
+        if (Primitives.class.getDeclaredMethod("nothing").getReturnType() == Void.TYPE) {
+            result += 100;
+        }
+        if (Primitives.class.getDeclaredField("ratio").getType() == Double.TYPE) {
+            result += 1000;
+        }
+        if (int.class.isPrimitive() && int.class.getName().equals("int") && long[].class.getName().equals("[J")) {
+            result += 10000;
+        }
+        return result;
+    }
+}
@@ -0,0 +1,17 @@
+package rsvm;
+
+public class Redefined {
+    static final long BASE = 100;
+
+    static long version() {
+        return 1;
+    }
+
+    long value() {
+        return BASE + 1;
+    }
+
+    public static long call() {
+        return version() * 1000 + new Redefined().value();
+    }
+}
@@ -0,0 +1,37 @@
+package rsvm;
+
+public class RegisteredNatives {
+
+    public static native int answer();
+
+    public static native boolean flag();
+
+    public static native char letter();
+
+    public static native byte signedByte();
+
+    public static native int[] cached();
+
+    public static int callAnswer() {
+        return answer();
+    }
+
+    public static int callFlag() {
+        return flag() ? 1 : 0;
+    }
+
+    public static int callLetter() {
+        int code = letter();
+        return code;
+    }
+
+    public static int callSignedByte() {
+        int value = signedByte();
+        return value;
+    }
+
+    public static int callCached() {
+        int[] cached = cached();
+        return cached == null ? -1 : cached[0];
+    }
+}
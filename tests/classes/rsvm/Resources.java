@@ -0,0 +1,64 @@
+package rsvm;
+
+import java.io.IOException;
+import java.io.InputStream;
+import java.net.URL;
+import java.util.Enumeration;
+import java.util.Properties;
+
+public class Resources {
+
+    private static final String NAME = "rsvm/resource.properties";
+
+    public static long readProperties() throws IOException {
+        InputStream in = ClassLoader.getSystemResourceAsStream(NAME);
+        Properties props = new Properties();
+        try {
+            props.load(in);
+        } finally {
+            in.close();
+        }
+        long result = Long.parseLong(props.getProperty("answer"));
+        if ("hello".equals(props.getProperty("greeting"))) {
+            result += 1000;
+        }
+        return result;
+    }
+
+    public static long classResource() throws IOException {
+        InputStream in = Resources.class.getResourceAsStream("/" + NAME);
+        long length = 0;
+        while (in.read() != -1) {
+            length++;
+        }
+        in.close();
+        return length;
+    }
+
+    public static long urls() {
+        long flags = 0;
+        URL file = ClassLoader.getSystemResource(NAME);
+        if ("file".equals(file.getProtocol()) && file.getPath().endsWith(NAME)) {
+            flags += 1;
+        }
+        URL jar = ClassLoader.getSystemResource("java/lang/Object.class");
+        if ("jar".equals(jar.getProtocol())) {
+            flags += 10;
+        }
+        if (ClassLoader.getSystemResource("rsvm/missing.properties") == null
+                && ClassLoader.getSystemResourceAsStream("rsvm/missing.properties") == null) {
+            flags += 100;
+        }
+        return flags;
+    }
+
+    public static long countResources(String name) throws IOException {
+        Enumeration<URL> urls = ClassLoader.getSystemResources(name);
+        long count = 0;
+        while (urls.hasMoreElements()) {
+            urls.nextElement();
+            count++;
+        }
+        return count;
+    }
+}
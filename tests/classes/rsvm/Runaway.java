@@ -0,0 +1,26 @@
+package rsvm;
+
+public class Runaway {
+
+    public static int add(int a, int b) {
+        // iload_0, iload_1, iadd, ireturn
+        return a + b;
+    }
+
+    public static void spin() {
+        while (true) {
+        }
+    }
+
+    public static int count(int n) {
+        int i = 0;
+        while (i < n) {
+            i++;
+        }
+        return i;
+    }
+
+    public static synchronized int countLocked(int n) {
+        return count(n);
+    }
+}
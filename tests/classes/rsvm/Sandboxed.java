@@ -0,0 +1,20 @@
+package rsvm;
+
+import java.io.FileInputStream;
+import java.io.IOException;
+
+public class Sandboxed {
+
+    public static int firstByte(String path) throws IOException {
+        FileInputStream in = new FileInputStream(path);
+        try {
+            return in.read();
+        } finally {
+            in.close();
+        }
+    }
+
+    public static int allocate(int size) {
+        return new byte[size].length;
+    }
+}
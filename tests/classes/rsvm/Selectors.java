@@ -0,0 +1,100 @@
+package rsvm;
+
+import java.io.IOException;
+import java.net.InetAddress;
+import java.net.InetSocketAddress;
+import java.nio.Buffer;
+import java.nio.ByteBuffer;
+import java.nio.channels.SelectionKey;
+import java.nio.channels.Selector;
+import java.nio.channels.ServerSocketChannel;
+import java.nio.channels.SocketChannel;
+import java.util.Iterator;
+
+public class Selectors {
+
+    /**
+     * Sends the ASCII text to a non-blocking echo server over the loopback
+     * interface and returns what the server sent back, driving both ends
+     * from one selector.
+     */
+    public static String echo(String text) throws IOException {
+        Selector selector = Selector.open();
+        ServerSocketChannel server = ServerSocketChannel.open();
+        server.bind(new InetSocketAddress(InetAddress.getByName("127.0.0.1"), 0));
+        server.configureBlocking(false);
+        server.register(selector, SelectionKey.OP_ACCEPT);
+
+        SocketChannel client = SocketChannel.open();
+        client.configureBlocking(false);
+        if (client.connect(server.getLocalAddress())) {
+            client.register(selector, SelectionKey.OP_WRITE);
+        } else {
+            client.register(selector, SelectionKey.OP_CONNECT);
+        }
+
+        ByteBuffer request = ByteBuffer.allocateDirect(text.length());
+        for (int i = 0; i < text.length(); i++) {
+            request.put((byte) text.charAt(i));
+        }
+        ((Buffer) request).flip();
+        ByteBuffer response = ByteBuffer.allocateDirect(text.length());
+        SocketChannel accepted = null;
+        while (response.hasRemaining()) {
+            selector.select();
+            Iterator<SelectionKey> keys = selector.selectedKeys().iterator();
+            while (keys.hasNext()) {
+                SelectionKey key = keys.next();
+                keys.remove();
+                if (key.isAcceptable()) {
+                    accepted = server.accept();
+                    if (accepted != null) {
+                        accepted.configureBlocking(false);
+                        accepted.register(selector, SelectionKey.OP_READ);
+                    }
+                } else if (key.isConnectable()) {
+                    if (client.finishConnect()) {
+                        key.interestOps(SelectionKey.OP_WRITE);
+                    }
+                } else if (key.isWritable()) {
+                    client.write(request);
+                    if (!request.hasRemaining()) {
+                        key.interestOps(SelectionKey.OP_READ);
+                    }
+                } else if (key.isReadable() && key.channel() == client) {
+                    client.read(response);
+                } else if (key.isReadable()) {
+                    ByteBuffer buf = ByteBuffer.allocateDirect(64);
+                    if (accepted.read(buf) > 0) {
+                        ((Buffer) buf).flip();
+                        while (buf.hasRemaining()) {
+                            accepted.write(buf);
+                        }
+                    }
+                }
+            }
+        }
+
+        client.close();
+        accepted.close();
+        server.close();
+        selector.close();
+        ((Buffer) response).flip();
+        StringBuilder echoed = new StringBuilder();
+        while (response.hasRemaining()) {
+            echoed.append((char) response.get());
+        }
+        return echoed.toString();
+    }
+
+    /** Selects after a wakeup, which returns at once without selected keys. */
+    public static int wakeup() throws IOException {
+        Selector selector = Selector.open();
+        try {
+            selector.wakeup();
+            return selector.select();
+        } finally {
+            selector.close();
+        }
+    }
+}
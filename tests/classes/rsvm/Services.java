@@ -0,0 +1,41 @@
+package rsvm;
+
+import java.util.ServiceLoader;
+
+public class Services {
+
+    public interface Greeter {
+        long id();
+    }
+
+    public static class Hello implements Greeter {
+        public long id() {
+            return 1;
+        }
+    }
+
+    public static class Hi implements Greeter {
+        public long id() {
+            return 2;
+        }
+    }
+
+    public interface Unprovided {
+    }
+
+    public static long greeters() {
+        long ids = 0;
+        for (Greeter greeter : ServiceLoader.load(Greeter.class)) {
+            ids = ids * 10 + greeter.id();
+        }
+        return ids;
+    }
+
+    public static long unprovided() {
+        return ServiceLoader.load(Unprovided.class).iterator().hasNext() ? 1 : 0;
+    }
+
+    public static String serviceName() {
+        return Greeter.class.getName();
+    }
+}
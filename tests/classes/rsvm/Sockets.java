@@ -0,0 +1,64 @@
+package rsvm;
+
+import java.io.IOException;
+import java.io.InputStream;
+import java.io.OutputStream;
+import java.net.InetAddress;
+import java.net.InetSocketAddress;
+import java.net.Socket;
+import java.nio.Buffer;
+import java.nio.ByteBuffer;
+import java.nio.channels.ServerSocketChannel;
+import java.nio.channels.SocketChannel;
+
+public class Sockets {
+
+    /**
+     * Connects a socket with a timeout to a server on the loopback interface
+     * and sends the ASCII text through it and back. Returns the echoed text
+     * followed by TCP_NODELAY, SO_REUSEADDR and SO_TIMEOUT of the socket.
+     */
+    public static String echo(String text) throws IOException {
+        ServerSocketChannel server = ServerSocketChannel.open();
+        server.bind(new InetSocketAddress(InetAddress.getByName("127.0.0.1"), 0));
+
+        Socket socket = new Socket();
+        socket.setReuseAddress(true);
+        socket.setTcpNoDelay(true);
+        socket.setSoTimeout(5000);
+        socket.connect(server.getLocalAddress(), 5000);
+        SocketChannel peer = server.accept();
+
+        OutputStream out = socket.getOutputStream();
+        for (int i = 0; i < text.length(); i++) {
+            out.write(text.charAt(i));
+        }
+        out.flush();
+        ByteBuffer buf = ByteBuffer.allocate(text.length());
+        while (buf.hasRemaining()) {
+            peer.read(buf);
+        }
+        ((Buffer) buf).flip();
+        while (buf.hasRemaining()) {
+            peer.write(buf);
+        }
+
+        InputStream in = socket.getInputStream();
+        StringBuilder echoed = new StringBuilder();
+        while (echoed.length() < text.length()) {
+            int c = in.read();
+            if (c < 0) {
+                break;
+            }
+            echoed.append((char) c);
+        }
+        echoed.append(' ').append(socket.getTcpNoDelay());
+        echoed.append(' ').append(socket.getReuseAddress());
+        echoed.append(' ').append(socket.getSoTimeout());
+
+        socket.close();
+        peer.close();
+        server.close();
+        return echoed.toString();
+    }
+}
@@ -0,0 +1,51 @@
+package rsvm;
+
+import java.lang.reflect.Method;
+
+public class StackTraces {
+
+    static class TracedException extends RuntimeException {
+        TracedException() {
+            super("traced");
+        }
+    }
+
+    /** The stack trace as lines of {@code class.method(file:line)}. */
+    static String describe(StackTraceElement[] trace) {
+        StringBuilder result = new StringBuilder();
+        for (StackTraceElement element : trace) {
+            result.append(element.getClassName()).append('.').append(element.getMethodName());
+            result.append('(').append(element.getFileName()).append(':');
+            result.append(element.isNativeMethod() ? "native" : String.valueOf(element.getLineNumber()));
+            result.append(")\n");
+        }
+        return result.toString();
+    }
+
+    public static String trace() {
+        return describe(new Throwable().getStackTrace());
+    }
+
+    public static String nested() {
+        return trace();
+    }
+
+    public static String subclass() {
+        return describe(new TracedException().getStackTrace());
+    }
+
+    /** The call site a logger records, the caller of the logging method. */
+    public static String callSite() {
+        return log();
+    }
+
+    static String log() {
+        StackTraceElement caller = new Throwable().getStackTrace()[1];
+        return caller.getMethodName() + ":" + caller.getLineNumber();
+    }
+
+    public static String reflective() throws Exception {
+        Method trace = StackTraces.class.getMethod("trace");
+        return (String) trace.invoke(null);
+    }
+}
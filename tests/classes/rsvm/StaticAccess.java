@@ -0,0 +1,41 @@
+package rsvm;
+
+public class StaticAccess {
+    static long counter;
+    static int[] buckets = new int[4];
+    static double ratio = 0.5;
+
+    static class Warmup {
+        static int rounds;
+
+        static {
+            // runs before Warmup is initialized
+            for (int i = 0; i < 10; i++) {
+                rounds++;
+            }
+        }
+    }
+
+    static class Base {
+        static short step = 3;
+    }
+
+    static class Sub extends Base {
+    }
+
+    public static long loop() {
+        counter = 0;
+        for (int i = 0; i < 100000; i++) {
+            counter += Sub.step;
+            int bucket = i & 3;
+            buckets[bucket] = buckets[bucket] + 1;
+            ratio *= 1.0;
+        }
+        return counter + buckets[1] + (long) (ratio * 2);
+    }
+
+    public static long warmup() {
+        Warmup.rounds += 5;
+        return Warmup.rounds;
+    }
+}
@@ -0,0 +1,48 @@
+package rsvm;
+
+public class StringSwitch {
+    static final String[] COMMANDS = { "get", "put", "delete", "list" };
+
+    static int cost(String command) {
+        switch (command) {
+            case "get":
+                return 1;
+            case "put":
+                return 2;
+            case "delete":
+                return 3;
+            default:
+                return 0;
+        }
+    }
+
+    static int weight(String command) {
+        int base = 10;
+        // the switch lands on another alignment than in cost
+        switch (command) {
+            case "get":
+                return base;
+            case "list":
+                return base * 2;
+            default:
+                return base * 3;
+        }
+    }
+
+    public static long dispatch() {
+        long total = 0;
+        for (int i = 0; i < 1000; i++) {
+            total += cost(COMMANDS[i & 3]) + weight(COMMANDS[i & 3]);
+        }
+        return total;
+    }
+
+    static String literal() {
+        return "delete";
+    }
+
+    public static long sameLiteral() {
+        // every execution of ldc pushes the same interned string
+        return literal() == literal() && literal() == COMMANDS[2] ? 1 : 0;
+    }
+}
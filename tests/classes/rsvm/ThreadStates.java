@@ -0,0 +1,90 @@
+package rsvm;
+
+public class ThreadStates {
+
+    private static final Object LOCK = new Object();
+
+    public static long currentState() {
+        Thread current = Thread.currentThread();
+        long flags = 0;
+        if (current.getState() == Thread.State.RUNNABLE) {
+            flags += 1;
+        }
+        if (current.isAlive()) {
+            flags += 10;
+        }
+        return flags;
+    }
+
+    public static long holdsLock() throws InterruptedException {
+        long flags = 0;
+        if (!Thread.holdsLock(LOCK)) {
+            flags += 1;
+        }
+        synchronized (LOCK) {
+            if (Thread.holdsLock(LOCK)) {
+                flags += 10;
+            }
+            synchronized (LOCK) {
+                if (Thread.holdsLock(LOCK)) {
+                    flags += 100;
+                }
+            }
+            if (Thread.holdsLock(LOCK)) {
+                flags += 1000;
+            }
+        }
+        if (!Thread.holdsLock(LOCK)) {
+            flags += 10000;
+        }
+        if (holdsClassLock() && !Thread.holdsLock(ThreadStates.class)) {
+            flags += 100000;
+        }
+        Thread.sleep(1);
+        if (Thread.currentThread().getState() == Thread.State.RUNNABLE) {
+            flags += 1000000;
+        }
+        return flags;
+    }
+
+    private static synchronized boolean holdsClassLock() {
+        return Thread.holdsLock(ThreadStates.class);
+    }
+
+    public static long instanceLock() {
+        ThreadStates states = new ThreadStates();
+        long flags = 0;
+        if (states.holdsOwnLock()) {
+            flags += 1;
+        }
+        if (!Thread.holdsLock(states)) {
+            flags += 10;
+        }
+        return flags;
+    }
+
+    private synchronized boolean holdsOwnLock() {
+        return Thread.holdsLock(this) && !Thread.holdsLock(LOCK);
+    }
+
+    public static void clobberedReceiver() {
+        new ThreadStates().clobberReceiver();
+    }
+
+    // the test patches the store to overwrite `this` in local 0
+    private synchronized void clobberReceiver() {
+        Object local = null;
+    }
+
+    public static long newThread() {
+        Thread thread = new Thread();
+        long flags = 0;
+        if (thread.getState() == Thread.State.NEW) {
+            flags += 1;
+        }
+        if (!thread.isAlive()) {
+            flags += 10;
+        }
+        return flags;
+    }
+}
@@ -0,0 +1,73 @@
+package rsvm;
+
+import sun.misc.Unsafe;
+
+public class Volatiles {
+    private static volatile int staticCounter;
+    private static volatile Volatiles instance;
+
+    private static volatile long staticLong;
+    private static volatile double staticDouble;
+
+    private volatile int counter;
+    private volatile String name;
+    private volatile long longValue;
+    private volatile double doubleValue;
+
+    public static int doubleCheckedLocking() {
+        if (getInstance() != getInstance()) {
+            return -1;
+        }
+        Volatiles v = getInstance();
+        for (int i = 0; i < 10; i++) {
+            v.counter++;
+            staticCounter += 2;
+        }
+        v.name = "volatile";
+        if (!"volatile".equals(v.name)) {
+            return -2;
+        }
+        return v.counter + staticCounter;
+    }
+
+    public static long volatileLong() {
+        Volatiles v = new Volatiles();
+        v.longValue = 0x8000000180000001L;
+        staticLong = v.longValue ^ 0xffffffff00000000L;
+        return staticLong;
+    }
+
+    public static double volatileDouble() {
+        Volatiles v = new Volatiles();
+        v.doubleValue = -Double.MAX_VALUE;
+        staticDouble = v.doubleValue / 2;
+        return staticDouble;
+    }
+
+    public static long unsafeVolatileLong() {
+        Unsafe unsafe = Unsafe.getUnsafe();
+        long memory = unsafe.allocateMemory(16);
+        long address = (memory + 7) & ~7L;
+        unsafe.putLongVolatile(null, address, 0x8000000180000001L);
+        long result = unsafe.getLongVolatile(null, address);
+        unsafe.putDoubleVolatile(null, address, -0.5);
+        if (unsafe.getDoubleVolatile(null, address) != -0.5) {
+            result = 0;
+        }
+        unsafe.freeMemory(memory);
+        return result;
+    }
+
+    private static Volatiles getInstance() {
+        Volatiles result = instance;
+        if (result == null) {
+            synchronized (Volatiles.class) {
+                result = instance;
+                if (result == null) {
+                    instance = result = new Volatiles();
+                }
+            }
+        }
+        return result;
+    }
+}
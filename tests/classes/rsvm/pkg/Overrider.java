@@ -0,0 +1,13 @@
+package rsvm.pkg;
+
+import rsvm.PackagePrivate;
+
+public class Overrider extends PackagePrivate {
+    long value() {
+        return 2;
+    }
+
+    public long ownValue() {
+        return value();
+    }
+}